@@ -0,0 +1,76 @@
+//! Benchmarks for the hot paths of a chat reply: splitting long answers for
+//! Discord, ranking stored memories, and a full mention against the mock LLM.
+//!
+//! Run with `cargo bench`; compare runs before and after database or cache changes.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use persona::core::discord_limits::{split_message, MESSAGE_CONTENT};
+use persona::database::{Database, MemoryEntry};
+use persona::features::duplicates::encode_embedding;
+use persona::features::loadtest::{mock_command_handler, simulate_mention, LoadTestOptions};
+use persona::features::memory::{rank_memories, MEMORY_THRESHOLD};
+use persona::features::personas::PersonaManager;
+use std::hint::black_box;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+/// Dimensions of `text-embedding-3-small`
+const EMBEDDING_DIMENSIONS: usize = 1536;
+
+fn embedding(seed: usize) -> Vec<f32> {
+    (0..EMBEDDING_DIMENSIONS).map(|i| ((i * 31 + seed * 17) % 97) as f32 / 97.0).collect()
+}
+
+fn bench_split_message(c: &mut Criterion) {
+    let answer = "The quick brown fox jumps over the lazy dog. ".repeat(500);
+    c.bench_function("split_message_22k_chars", |b| b.iter(|| split_message(black_box(&answer), MESSAGE_CONTENT)));
+}
+
+fn bench_rank_memories(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rank_memories");
+    for count in [200, 1000] {
+        let memories: Vec<MemoryEntry> = (0..count)
+            .map(|i| MemoryEntry {
+                id: i as i64,
+                channel_id: "1".to_string(),
+                user_text: format!("question {i}"),
+                reply_text: format!("answer {i}"),
+                embedding: encode_embedding(&embedding(i)),
+                created_at: "2025-01-01 00:00:00".to_string(),
+            })
+            .collect();
+        let query = embedding(7);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &memories, |b, memories| {
+            b.iter(|| rank_memories(black_box(&query), memories, MEMORY_THRESHOLD, 3))
+        });
+    }
+    group.finish();
+}
+
+fn bench_mention_pipeline(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let path = std::env::temp_dir().join(format!("persona-bench-{}.db", std::process::id()));
+    let path = path.to_string_lossy().to_string();
+    let persona_manager = PersonaManager::new();
+    let (database, handler) = runtime.block_on(async {
+        let database = Database::new(&path, 4).await.unwrap();
+        let handler = mock_command_handler(database.clone(), persona_manager.clone(), Duration::ZERO);
+        (database, handler)
+    });
+    let options = LoadTestOptions::default();
+
+    let mut index = 0;
+    c.bench_function("mention_pipeline_mock_llm", |b| {
+        b.to_async(&runtime).iter(|| {
+            index += 1;
+            simulate_mention(&handler, &database, &persona_manager, &options, index)
+        })
+    });
+
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{path}{suffix}"));
+    }
+}
+
+criterion_group!(benches, bench_split_message, bench_rank_memories, bench_mention_pipeline);
+criterion_main!(benches);
@@ -1,6 +1,7 @@
 #!/bin/bash
 # Database Backup Script
 # Creates timestamped backups of the persona.db database
+# (SQLite only; back up a PostgreSQL database with pg_dump)
 
 set -e
 
@@ -20,7 +21,14 @@ fi
 
 # Create backup
 echo "Creating backup of $DB_PATH..."
-cp "$DB_PATH" "$BACKUP_FILE"
+# The bot runs SQLite in WAL mode, so recent writes may still be in persona.db-wal;
+# sqlite3's online backup includes them, a plain copy may not
+if command -v sqlite3 >/dev/null 2>&1; then
+    sqlite3 "$DB_PATH" ".backup '$BACKUP_FILE'"
+else
+    cp "$DB_PATH" "$BACKUP_FILE"
+    [ -f "$DB_PATH-wal" ] && cp "$DB_PATH-wal" "$BACKUP_FILE-wal"
+fi
 
 # Verify backup
 if [ -f "$BACKUP_FILE" ]; then
@@ -1,282 +1,150 @@
 use anyhow::Result;
 use dotenvy::dotenv;
-use log::{error, info};
-use serenity::async_trait;
-use serenity::model::application::interaction::Interaction;
-use serenity::model::channel::Message;
-use serenity::model::gateway::Ready;
-use serenity::prelude::*;
 use std::sync::Arc;
 
-use persona::commands::{CommandHandler, register_global_commands, register_guild_commands};
-use persona::core::Config;
-use persona::database::Database;
-use persona::features::analytics::{InteractionTracker, UsageTracker, metrics_collection_loop};
+use persona::core::{Config, LlmConfig, ProviderKind};
+use persona::database::{Database, DatabaseBackend};
+use persona::features::llm::build_provider;
+use persona::features::loadtest::{mock_command_handler, run_load_test, LoadTestOptions};
 use persona::features::personas::PersonaManager;
-use persona::features::reminders::ReminderScheduler;
-use persona::features::startup::StartupNotifier;
-use persona::message_components::MessageComponentHandler;
-use serenity::model::id::GuildId;
-
-struct Handler {
-    command_handler: Arc<CommandHandler>,
-    component_handler: Arc<MessageComponentHandler>,
-    guild_id: Option<GuildId>,
-    startup_notifier: StartupNotifier,
-}
+use persona::features::personas::regression::{format_report, load_suite, run_suite, Backend, DEFAULT_SUITE_PATH};
+use persona::features::startup::run_preflight;
+use persona::features::telemetry::init_telemetry;
+use persona::bot::{config_watch_loop, Fleet};
+use persona::core::MultiConfig;
+use persona::Bot;
+
+/// Handle `bot persona test [--suite <path>] [--persona <name>] [--model <model>] [--mock]`
+async fn run_persona_cli(args: &[String]) -> Result<()> {
+    if args.first().map(String::as_str) != Some("test") {
+        anyhow::bail!("Usage: bot persona test [--suite <path>] [--persona <name>] [--model <model>] [--mock]");
+    }
 
-impl Handler {
-    fn new(
-        command_handler: CommandHandler,
-        component_handler: MessageComponentHandler,
-        guild_id: Option<GuildId>,
-        startup_notifier: StartupNotifier,
-    ) -> Self {
-        Handler {
-            command_handler: Arc::new(command_handler),
-            component_handler: Arc::new(component_handler),
-            guild_id,
-            startup_notifier,
+    let mut suite_path = DEFAULT_SUITE_PATH.to_string();
+    let mut only_persona = None;
+    let mut model = std::env::var("PERSONA_TEST_MODEL").ok();
+    let mut mock = false;
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--suite" => suite_path = rest.next().cloned().ok_or_else(|| anyhow::anyhow!("--suite needs a path"))?,
+            "--persona" => only_persona = Some(rest.next().cloned().ok_or_else(|| anyhow::anyhow!("--persona needs a name"))?),
+            "--model" => model = Some(rest.next().cloned().ok_or_else(|| anyhow::anyhow!("--model needs a name"))?),
+            "--mock" => mock = true,
+            other => anyhow::bail!("Unknown option: {other}"),
         }
     }
-}
 
-#[async_trait]
-impl EventHandler for Handler {
-    async fn message(&self, ctx: Context, msg: Message) {
-        if msg.author.bot {
-            return;
+    let backend = if mock {
+        Backend::Mock
+    } else {
+        let llm = LlmConfig::from_env()?;
+        if llm.provider == ProviderKind::OpenAi && llm.openai_api_key.is_empty() {
+            anyhow::bail!("OPENAI_API_KEY must be set (or set LLM_PROVIDER, or use --mock)");
         }
+        let model = model.unwrap_or_else(|| llm.model.clone());
+        Backend::Llm { provider: build_provider(&llm)?, model }
+    };
 
-        if let Err(e) = self.command_handler.handle_message(&ctx, &msg).await {
-            error!("Error handling message: {e}");
-            if let Err(why) = msg
-                .channel_id
-                .say(&ctx.http, "Sorry, I encountered an error processing your message.")
-                .await
-            {
-                error!("Failed to send error message: {why}");
-            }
+    let suite = load_suite(&std::fs::read_to_string(&suite_path)?)?;
+    let results = run_suite(&suite, &PersonaManager::new(), &backend, only_persona.as_deref()).await?;
+    print!("{}", format_report(&results));
+
+    if results.iter().any(|r| !r.passed()) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Handle `bot loadtest [--requests <n>] [--concurrency <n>] [--users <n>] [--channels <n>]
+/// [--latency-ms <ms>] [--pool-size <n>] [--database <path>]`
+async fn run_loadtest_cli(args: &[String]) -> Result<()> {
+    let mut options = LoadTestOptions::default();
+    let mut latency_ms = 0u64;
+    let mut pool_size = 4usize;
+    let mut database_path = None;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = |name: &str| rest.next().cloned().ok_or_else(|| anyhow::anyhow!("{name} needs a value"));
+        match arg.as_str() {
+            "--requests" => options.requests = value(arg)?.parse()?,
+            "--concurrency" => options.concurrency = value(arg)?.parse()?,
+            "--users" => options.users = value(arg)?.parse()?,
+            "--channels" => options.channels = value(arg)?.parse()?,
+            "--latency-ms" => latency_ms = value(arg)?.parse()?,
+            "--pool-size" => pool_size = value(arg)?.parse()?,
+            "--database" => database_path = Some(value(arg)?),
+            other => anyhow::bail!("Unknown option: {other}"),
         }
     }
 
-    async fn ready(&self, ctx: Context, ready: Ready) {
-        info!("🎉 {} is connected and ready!", ready.user.name);
-        info!("📡 Connected to {} guilds", ready.guilds.len());
-        info!("🔗 Gateway session ID: {:?}", ready.session_id);
-        info!("🤖 Bot ID: {}", ready.user.id);
-        info!("🌐 Gateway version: {}", ready.version);
+    // A scratch SQLite file by default, so a real database is never filled with synthetic traffic
+    let scratch = database_path.is_none();
+    let database_path = database_path.unwrap_or_else(|| {
+        std::env::temp_dir()
+            .join(format!("persona-loadtest-{}.db", uuid::Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string()
+    });
+    let database = Database::new(&database_path, pool_size).await?;
+    let persona_manager = PersonaManager::new();
+    let handler = Arc::new(mock_command_handler(
+        database.clone(),
+        persona_manager.clone(),
+        std::time::Duration::from_millis(latency_ms),
+    ));
 
-        // Log shard information
-        if let Some(shard) = ready.shard {
-            info!("⚡ Shard: {}/{}", shard[0] + 1, shard[1]);
-        }
+    println!("Database: {} (pool of {pool_size}), mock latency {latency_ms} ms", DatabaseBackend::parse(&database_path).describe());
+    let report = run_load_test(handler, database, persona_manager, options).await;
+    print!("{}", report.format());
 
-        // Register slash commands - use guild commands for development (instant), global for production
-        if let Some(guild_id) = self.guild_id {
-            info!("🔧 Development mode: Registering commands for guild {guild_id}");
-            if let Err(e) = register_guild_commands(&ctx, guild_id).await {
-                error!("❌ Failed to register guild slash commands: {e}");
-            } else {
-                info!("✅ Successfully registered slash commands for guild {guild_id} (instant update)");
-            }
-        } else {
-            info!("🌍 Production mode: Registering commands globally");
-            if let Err(e) = register_global_commands(&ctx).await {
-                error!("❌ Failed to register global slash commands: {e}");
-            } else {
-                info!("✅ Successfully registered slash commands globally (may take up to 1 hour to propagate)");
-            }
+    if scratch {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{database_path}{suffix}"));
         }
-
-        // Send startup notification if enabled
-        self.startup_notifier.send_if_enabled(&ctx.http, &ready).await;
     }
+    if report.failures > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
 
-    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        match interaction {
-            Interaction::ApplicationCommand(command) => {
-                if let Err(e) = self.command_handler.handle_slash_command(&ctx, &command).await {
-                    error!("Error handling slash command '{}': {}", command.data.name, e);
-                    
-                    // Try to edit the deferred response with error message
-                    let error_message = if e.to_string().contains("timeout") || e.to_string().contains("OpenAI") {
-                        "⏱️ Sorry, the AI service is taking longer than expected. Please try again in a moment."
-                    } else {
-                        "❌ Sorry, I encountered an error processing your command. Please try again."
-                    };
-                    
-                    // Try to edit the deferred response, fallback to new response if that fails
-                    #[allow(clippy::redundant_pattern_matching)]
-                    if let Err(_) = command.edit_original_interaction_response(&ctx.http, |response| {
-                        response.content(error_message)
-                    }).await {
-                        let _ = command.create_interaction_response(&ctx.http, |response| {
-                            response
-                                .kind(serenity::model::application::interaction::InteractionResponseType::ChannelMessageWithSource)
-                                .interaction_response_data(|message| {
-                                    message.content(error_message)
-                                })
-                        }).await;
-                    }
-                }
-            }
-            Interaction::MessageComponent(component) => {
-                if let Err(e) = self.component_handler.handle_component_interaction(&ctx, &component).await {
-                    error!("Error handling component interaction '{}': {}", component.data.custom_id, e);
-                    
-                    let error_message = "❌ Sorry, I encountered an error processing your interaction. Please try again.";
-                    
-                    // Try to update the message, fallback to new response if that fails
-                    #[allow(clippy::redundant_pattern_matching)]
-                    if let Err(_) = component.create_interaction_response(&ctx.http, |response| {
-                        response
-                            .kind(serenity::model::application::interaction::InteractionResponseType::UpdateMessage)
-                            .interaction_response_data(|message| {
-                                message.content(error_message)
-                            })
-                    }).await {
-                        let _ = component.create_interaction_response(&ctx.http, |response| {
-                            response
-                                .kind(serenity::model::application::interaction::InteractionResponseType::ChannelMessageWithSource)
-                                .interaction_response_data(|message| {
-                                    message.content(error_message)
-                                })
-                        }).await;
-                    }
-                }
-            }
-            Interaction::ModalSubmit(modal) => {
-                if let Err(e) = self.component_handler.handle_modal_submit(&ctx, &modal).await {
-                    error!("Error handling modal submit '{}': {}", modal.data.custom_id, e);
-                    
-                    let error_message = if e.to_string().contains("timeout") || e.to_string().contains("OpenAI") {
-                        "⏱️ Sorry, the AI service is taking longer than expected. Please try again in a moment."
-                    } else {
-                        "❌ Sorry, I encountered an error processing your submission. Please try again."
-                    };
-                    
-                    // Try to edit the deferred response, fallback to new response if that fails
-                    #[allow(clippy::redundant_pattern_matching)]
-                    if let Err(_) = modal.edit_original_interaction_response(&ctx.http, |response| {
-                        response.content(error_message)
-                    }).await {
-                        let _ = modal.create_interaction_response(&ctx.http, |response| {
-                            response
-                                .kind(serenity::model::application::interaction::InteractionResponseType::ChannelMessageWithSource)
-                                .interaction_response_data(|message| {
-                                    message.content(error_message)
-                                })
-                        }).await;
-                    }
-                }
-            }
-            Interaction::Autocomplete(autocomplete) => {
-                info!("Autocomplete interaction received for command: {}", autocomplete.data.name);
+/// `bot --migrate-only`: bring the database schema up to date and exit, e.g. before a deploy.
+/// Only needs `DATABASE_PATH`, not Discord or OpenAI credentials.
+async fn run_migrations_only() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let database_path = std::env::var("DATABASE_PATH").unwrap_or_else(|_| "persona.db".to_string());
 
-                // Handle autocomplete based on command
-                let _ = match autocomplete.data.name.as_str() {
-                    "set_guild_setting" => {
-                        // Get the setting option to determine which choices to show
-                        let setting = autocomplete.data.options.iter()
-                            .find(|opt| opt.name == "setting")
-                            .and_then(|opt| opt.value.as_ref())
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("");
+    // Opening the database creates missing tables and applies pending migrations
+    let database = Database::new(&database_path, 1).await?;
+    let location = DatabaseBackend::parse(&database_path).describe();
+    println!("Database schema at version {} ({location})", database.schema_version().await?);
+    Ok(())
+}
 
-                        autocomplete
-                            .create_autocomplete_response(&ctx.http, |response| {
-                                match setting {
-                                    "default_verbosity" => {
-                                        response
-                                            .add_string_choice("concise - Brief responses (2-3 sentences)", "concise")
-                                            .add_string_choice("normal - Balanced responses", "normal")
-                                            .add_string_choice("detailed - Comprehensive responses", "detailed")
-                                    }
-                                    "default_persona" => {
-                                        response
-                                            .add_string_choice("obi - Obi-Wan Kenobi (wise mentor)", "obi")
-                                            .add_string_choice("muppet - Enthusiastic Muppet expert", "muppet")
-                                            .add_string_choice("chef - Passionate cooking expert", "chef")
-                                            .add_string_choice("teacher - Patient educator", "teacher")
-                                            .add_string_choice("analyst - Step-by-step analyst", "analyst")
-                                    }
-                                    "conflict_mediation" => {
-                                        response
-                                            .add_string_choice("enabled - Bot will mediate conflicts", "enabled")
-                                            .add_string_choice("disabled - No conflict mediation", "disabled")
-                                    }
-                                    "conflict_sensitivity" => {
-                                        response
-                                            .add_string_choice("low - Only obvious conflicts (0.7 threshold)", "low")
-                                            .add_string_choice("medium - Balanced detection (0.5 threshold)", "medium")
-                                            .add_string_choice("high - More sensitive (0.35 threshold)", "high")
-                                            .add_string_choice("ultra - Maximum sensitivity (0.3 threshold)", "ultra")
-                                    }
-                                    "mediation_cooldown" => {
-                                        response
-                                            .add_string_choice("1 minute", "1")
-                                            .add_string_choice("5 minutes (default)", "5")
-                                            .add_string_choice("10 minutes", "10")
-                                            .add_string_choice("15 minutes", "15")
-                                            .add_string_choice("30 minutes", "30")
-                                            .add_string_choice("60 minutes", "60")
-                                    }
-                                    "max_context_messages" => {
-                                        response
-                                            .add_string_choice("10 messages (minimal context)", "10")
-                                            .add_string_choice("20 messages (light context)", "20")
-                                            .add_string_choice("40 messages (default)", "40")
-                                            .add_string_choice("60 messages (extended context)", "60")
-                                    }
-                                    "audio_transcription" => {
-                                        response
-                                            .add_string_choice("enabled - Transcribe audio files", "enabled")
-                                            .add_string_choice("disabled - Skip audio processing", "disabled")
-                                    }
-                                    "audio_transcription_mode" => {
-                                        response
-                                            .add_string_choice("always - Transcribe all audio files", "always")
-                                            .add_string_choice("mention_only - Only when @mentioned", "mention_only")
-                                    }
-                                    "audio_transcription_output" => {
-                                        response
-                                            .add_string_choice("transcription_only - Just the transcription", "transcription_only")
-                                            .add_string_choice("with_commentary - Add AI commentary", "with_commentary")
-                                    }
-                                    "mention_responses" => {
-                                        response
-                                            .add_string_choice("enabled - Respond when @mentioned", "enabled")
-                                            .add_string_choice("disabled - Ignore mentions", "disabled")
-                                    }
-                                    // Startup notification settings (global)
-                                    "startup_notification" => {
-                                        response
-                                            .add_string_choice("enabled - Send notification on startup", "enabled")
-                                            .add_string_choice("disabled - No startup notification", "disabled")
-                                    }
-                                    // For ID fields, don't show autocomplete - user must type the ID directly
-                                    // Return empty response so Discord shows the text input
-                                    "startup_notify_owner_id" | "startup_notify_channel_id" => response,
-                                    _ => response
-                                }
-                            })
-                            .await
-                    }
-                    _ => {
-                        // Default empty response for unknown commands
-                        autocomplete
-                            .create_autocomplete_response(&ctx.http, |response| response)
-                            .await
-                    }
-                };
-            }
-            Interaction::Ping(_) => {
-                info!("Ping interaction received - Discord health check");
-                // Ping interactions are automatically handled by Serenity
-            }
-        }
+/// `bot --preflight`: run the startup checks, print the results and exit
+async fn run_preflight_only() -> Result<()> {
+    let config = Config::from_env()?;
+    let database = Database::new(&config.database_path, 1).await?;
+    let report = run_preflight(&config, &database).await;
+    println!("{}", report.format());
+    if report.failed() {
+        return Err(anyhow::anyhow!("Preflight checks failed"));
     }
+    Ok(())
+}
+
+/// Run the bots in `config.yaml` until Ctrl+C, applying edits to the file as they are saved
+async fn run_fleet(base: Config, path: std::path::PathBuf) -> Result<()> {
+    let bots = MultiConfig::load(&path)?;
+    let fleet = Arc::new(tokio::sync::Mutex::new(Fleet::start(base, bots).await?));
+    log::info!("Watching {} for changes", path.display());
+    let watcher = tokio::spawn(config_watch_loop(path, fleet.clone()));
+
+    tokio::signal::ctrl_c().await?;
+    watcher.abort();
+    fleet.lock().await.shutdown().await;
+    Ok(())
 }
 
 #[tokio::main]
@@ -284,94 +152,49 @@ async fn main() -> Result<()> {
     // Load environment variables from .env file
     dotenv().ok();
 
-    let config = Config::from_env()?;
+    // CLI subcommands run without connecting to Discord
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("persona") {
+        return run_persona_cli(&args[1..]).await;
+    }
+    if args.first().map(String::as_str) == Some("loadtest") {
+        return run_loadtest_cli(&args[1..]).await;
+    }
+    if args.iter().any(|arg| arg == "--migrate-only") {
+        return run_migrations_only().await;
+    }
+    if args.iter().any(|arg| arg == "--preflight") {
+        return run_preflight_only().await;
+    }
 
-    // Ensure OPENAI_API_KEY is set in environment for the openai crate
-    // The openai crate reads from env vars, not from our config
-    // Set both OPENAI_API_KEY and OPENAI_KEY for compatibility
-    std::env::set_var("OPENAI_API_KEY", &config.openai_api_key);
-    std::env::set_var("OPENAI_KEY", &config.openai_api_key);
-    
+    // BOTS_CONFIG runs every bot in that file and reloads it on change; otherwise one bot from the environment
+    let bots_config = std::env::var("BOTS_CONFIG").ok().filter(|p| !p.is_empty());
+    let config = match &bots_config {
+        Some(_) => Config::from_env_with_token(String::new())?,
+        None => Config::from_env()?,
+    };
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(&config.log_level))
         .init();
+    // After the logger, since span-tagged events are forwarded to it
+    init_telemetry(config.otlp.as_ref())?;
+    if let Some(otlp) = &config.otlp {
+        log::info!("Exporting tracing spans to {} as {}", otlp.traces_endpoint, otlp.service_name);
+    }
 
-    info!("Starting Persona Discord Bot...");
-
-    let database = Database::new(&config.database_path).await?;
-    let usage_tracker = UsageTracker::new(database.clone());
-    let interaction_tracker = InteractionTracker::new(database.clone());
-    let persona_manager = PersonaManager::new();
-    let command_handler = CommandHandler::new(
-        database.clone(),
-        config.openai_api_key.clone(),
-        config.openai_model.clone(),
-        config.conflict_mediation_enabled,
-        &config.conflict_sensitivity,
-        config.mediation_cooldown_minutes,
-        usage_tracker.clone(),
-        interaction_tracker,
-    );
-    let component_handler = MessageComponentHandler::new(
-        command_handler.clone(),
-        persona_manager,
-        database.clone()
-    );
-
-    // Parse guild ID if provided for development mode
-    let guild_id = config.discord_guild_id.as_ref().and_then(|id| id.parse::<u64>().ok()).map(GuildId);
-
-    // Create startup notifier (reads config from database)
-    let startup_notifier = StartupNotifier::new(Arc::new(database.clone()));
-
-    let handler = Handler::new(command_handler, component_handler, guild_id, startup_notifier);
-
-    let intents = GatewayIntents::GUILD_MESSAGES
-        | GatewayIntents::DIRECT_MESSAGES
-        | GatewayIntents::MESSAGE_CONTENT;
-
-    // Build the Discord client with proper gateway configuration
-    let mut client = Client::builder(&config.discord_token, intents)
-        .event_handler(handler)
-        .await
-        .map_err(|e| {
-            error!("Failed to create Discord client: {e}");
-            error!("This could indicate:");
-            error!("  - Invalid bot token format");
-            error!("  - Network issues reaching Discord API");
-            error!("  - Insufficient permissions");
-            anyhow::anyhow!("Client creation failed: {}", e)
-        })?;
-
-    info!("Bot configured successfully. Connecting to Discord gateway...");
+    if let Some(path) = bots_config {
+        return run_fleet(config, path.into()).await;
+    }
 
-    // Start the reminder scheduler
-    let scheduler = ReminderScheduler::new(database.clone(), config.openai_model.clone(), usage_tracker);
-    let http = client.cache_and_http.http.clone();
-    tokio::spawn(async move {
-        scheduler.run(http).await;
-    });
+    let bot = Bot::builder(config).build().await?;
 
-    // Start the system metrics collection task
-    let metrics_db = Arc::new(database);
-    let db_path = config.database_path.clone();
+    // Ctrl+C closes the gateway and stops the background tasks before exiting
+    let signal_bot = bot.clone();
     tokio::spawn(async move {
-        metrics_collection_loop(metrics_db, db_path).await;
+        if tokio::signal::ctrl_c().await.is_ok() {
+            signal_bot.shutdown().await;
+        }
     });
 
-    // Log gateway connection attempt
-    info!("Establishing WebSocket connection to Discord gateway...");
-    info!("Gateway intents: {intents:?}");
-
-    if let Err(why) = client.start().await {
-        error!("Gateway connection failed: {why:?}");
-        error!("This could be due to:");
-        error!("  - Invalid bot token");
-        error!("  - Network connectivity issues");
-        error!("  - Discord API outage");
-        error!("  - Missing required permissions");
-        return Err(anyhow::anyhow!("Failed to establish gateway connection: {}", why));
-    }
-
+    bot.run().await?;
     Ok(())
 }
-
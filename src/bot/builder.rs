@@ -0,0 +1,742 @@
+//! # Bot Builder
+//!
+//! Assembles the bot from a [`Config`] for programs that embed it. The caller
+//! may supply its own database, LLM provider and plugins, and switch features
+//! off for every guild; anything left unset is built from the config. Tests
+//! can also swap in a fake clock and ID source.
+//! [`Bot::run`] connects to Discord, or serves the interactions endpoint, and
+//! starts the background tasks under the supervisor, and [`Bot::shutdown`]
+//! closes the gateway and stops them.
+//!
+//! - **Version**: 1.11.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.11.0: Runs the gateway with a fixed or recommended number of shards from `DISCORD_SHARDS`
+//! - 1.10.0: Serves the interactions endpoint instead of the gateway when one is configured
+//! - 1.9.0: Starts the unanswered question digest
+//! - 1.8.0: Live settings handle, updated in place by [`Bot::reload`]
+//! - 1.7.0: LLM provider wrapped so each completion is a tracing span
+//! - 1.6.0: Prometheus exporter on its own listener, fed by the handler, usage tracker and metrics loop
+//! - 1.5.0: Starts the nightly S3 analytics export when a bucket is configured
+//! - 1.4.0: Loads guild-defined custom personas at startup
+//! - 1.3.0: Web dashboard served next to the health endpoint
+//! - 1.2.0: Live event stream served next to the health endpoint
+//! - 1.1.0: Injectable clock and ID source
+//! - 1.0.0: Initial release, moved out of the binary's `main`
+
+use super::handler::Handler;
+use super::plugin::Plugin;
+use crate::commands::CommandHandler;
+use crate::core::clock::{random_ids, system_clock};
+use crate::core::{BotError, Clock, Config, IdGen, Result, ShardMode};
+use crate::database::Database;
+use crate::features::analytics::{
+    metrics_collection_loop, monthly_invoice_loop, s3_export_loop, sheets_export_loop, weekly_digest_loop, EmailSender,
+    InteractionTracker, S3Client, SheetsClient, ShardSlot, UsageTracker,
+};
+use crate::features::auto_slowmode::slowmode_revert_loop;
+use crate::features::budgets::budget_alert_loop;
+use crate::features::calculator::WolframClient;
+use crate::features::code_runner::CodeRunner;
+use crate::features::content_moderation::moderation_alert_loop;
+use crate::features::dashboard::dashboard_router;
+use crate::features::focus::{focus_timer_loop, VoiceRoster};
+use crate::features::get_feature;
+use crate::features::help_digest::HelpDigester;
+use crate::features::history_import::HistoryImporter;
+use crate::features::hot_reload::LiveSettings;
+use crate::features::interactions_endpoint::{http_context, serve_interactions};
+use crate::features::issue_lookup::IssueTracker;
+use crate::features::knowledge_sync::{knowledge_sync_loop, KnowledgeExporters, MarkdownExporter, NotionClient};
+use crate::features::live_events::{live_events_router, LiveEvents};
+use crate::features::llm::{build_provider, CircuitBreaker, LlmProvider, ResilientProvider, RetryPolicy, TracedProvider};
+use crate::features::prometheus::{serve_metrics, Metrics};
+use crate::features::matrix_bridge::{matrix_bridge_loop, MatrixClient};
+use crate::features::moderation::timeout_expiry_loop;
+use crate::features::panic_capture::install_panic_hook;
+use crate::features::personas::{custom_persona, PersonaManager};
+use crate::features::reminders::ReminderScheduler;
+use crate::features::slack_bridge::{slack_bridge_loop, SlackClient};
+use crate::features::stale_settings::stale_settings_loop;
+use crate::features::startup::{run_preflight, StartupNotifier};
+use crate::features::supervisor::{serve_health, RestartPolicy, Supervisor, TaskState};
+use crate::features::verification_gate::gate_kick_loop;
+use crate::features::webhook_ingest::serve_webhooks;
+use crate::message_components::MessageComponentHandler;
+use log::{error, info, warn};
+use serenity::http::Http;
+use serenity::model::id::GuildId;
+use serenity::prelude::*;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+pub struct BotBuilder {
+    config: Config,
+    database: Option<Database>,
+    llm: Option<Arc<dyn LlmProvider>>,
+    plugins: Vec<Arc<dyn Plugin>>,
+    disabled_features: HashSet<String>,
+    clock: Option<Arc<dyn Clock>>,
+    ids: Option<Arc<dyn IdGen>>,
+}
+
+impl BotBuilder {
+    pub fn new(config: Config) -> Self {
+        BotBuilder {
+            config,
+            database: None,
+            llm: None,
+            plugins: Vec::new(),
+            disabled_features: HashSet::new(),
+            clock: None,
+            ids: None,
+        }
+    }
+
+    /// Use this database instead of opening `DATABASE_PATH`
+    pub fn database(mut self, database: Database) -> Self {
+        self.database = Some(database);
+        self
+    }
+
+    /// Use this chat backend instead of the one `LLM_PROVIDER` selects
+    pub fn llm_provider(mut self, llm: Arc<dyn LlmProvider>) -> Self {
+        self.llm = Some(llm);
+        self
+    }
+
+    pub fn plugin(mut self, plugin: impl Plugin + 'static) -> Self {
+        self.plugins.push(Arc::new(plugin));
+        self
+    }
+
+    /// Use this clock instead of the system clock for the database, reminders and DM session tracking
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Use this ID source instead of random UUIDs for DM session IDs
+    pub fn id_gen(mut self, ids: Arc<dyn IdGen>) -> Self {
+        self.ids = Some(ids);
+        self
+    }
+
+    /// Switch a feature off in every guild by its registry ID, e.g. `slack_bridge`.
+    /// Toggleable features read as disabled and background tasks are not started.
+    pub fn disable_feature(mut self, id: &str) -> Self {
+        self.disabled_features.insert(id.to_string());
+        self
+    }
+
+    /// Open the database, run the preflight checks and wire up the handlers
+    pub async fn build(self) -> Result<Bot> {
+        let BotBuilder { config, database, llm, plugins, disabled_features, clock, ids } = self;
+        if let Some(unknown) = disabled_features.iter().find(|id| get_feature(id).is_none()) {
+            return Err(BotError::validation(format!("Unknown feature: {unknown}")));
+        }
+        install_panic_hook();
+
+        // Embeddings still use the openai crate, which reads its key from the environment
+        // Set both OPENAI_API_KEY and OPENAI_KEY for compatibility
+        std::env::set_var("OPENAI_API_KEY", &config.openai_api_key);
+        std::env::set_var("OPENAI_KEY", &config.openai_api_key);
+
+        let circuit_breaker = CircuitBreaker::from_config(&config.llm);
+        let llm = match llm {
+            Some(llm) => llm,
+            None => build_provider(&config.llm)?,
+        };
+        let llm = TracedProvider::wrap(ResilientProvider::wrap(llm, RetryPolicy::from_config(&config.llm), circuit_breaker.clone()));
+        let database = match database {
+            Some(database) => database,
+            None => Database::open(&config).await?,
+        };
+        let database = match &clock {
+            Some(clock) => database.with_clock(clock.clone()),
+            None => database,
+        };
+        let clock = clock.unwrap_or_else(system_clock);
+        let ids = ids.unwrap_or_else(random_ids);
+
+        // Check dependencies before connecting; missing tools only disable what needs them
+        let preflight = run_preflight(&config, &database).await;
+        preflight.log();
+        if preflight.failed() {
+            return Err(BotError::validation("Preflight checks failed, fix the errors above and restart"));
+        }
+
+        let live_events = LiveEvents::with_clock(clock.clone());
+        let metrics = Metrics::new();
+        let usage_tracker = UsageTracker::with_observers(database.clone(), live_events.clone(), metrics.clone());
+        let interaction_tracker =
+            InteractionTracker::with_sources(database.clone(), clock.clone(), ids.clone());
+        let persona_manager = PersonaManager::new().with_avatars(&config.persona_avatars);
+        match database.get_custom_personas().await {
+            Ok(records) => {
+                for record in &records {
+                    persona_manager.set_custom(&record.key, custom_persona(record));
+                }
+            }
+            Err(e) => warn!("⚠️ Failed to load custom personas: {e}"),
+        }
+        let slack_client = config.slack_bot_token.clone().map(SlackClient::new);
+        let matrix_client = match (&config.matrix_homeserver_url, &config.matrix_access_token) {
+            (Some(homeserver), Some(token)) => {
+                Some(MatrixClient::new(homeserver.clone(), token.clone(), config.matrix_puppet_prefix.clone()))
+            }
+            _ => None,
+        };
+        let issue_tracker = IssueTracker::new(
+            config.jira_base_url.clone(),
+            config.jira_email.clone(),
+            config.jira_api_token.clone(),
+            config.linear_api_key.clone(),
+        );
+        let code_runner = CodeRunner::from_config(
+            config.code_runner.as_deref(),
+            config.piston_url.clone(),
+            config.code_run_timeout_secs,
+        )
+        .unwrap_or_else(|e| {
+            warn!("Invalid code runner configuration, /run disabled: {e}");
+            None
+        });
+        let supervisor = Supervisor::new(RestartPolicy::new(
+            config.supervisor_backoff_base_secs,
+            config.supervisor_backoff_max_secs,
+            config.supervisor_max_restarts,
+        ));
+        let live_settings = LiveSettings::from_config(&config);
+        let voice_roster = VoiceRoster::new();
+        let command_handler = CommandHandler::new(
+            database.clone(),
+            config.openai_api_key.clone(),
+            config.openai_model.clone(),
+            config.conflict_mediation_enabled,
+            &config.conflict_sensitivity,
+            config.mediation_cooldown_minutes,
+            usage_tracker.clone(),
+            interaction_tracker,
+            config.clamav_address.clone(),
+            slack_client.clone(),
+            matrix_client.clone(),
+            issue_tracker,
+            config.wolfram_app_id.clone().map(WolframClient::new),
+            code_runner,
+            persona_manager.clone(),
+            supervisor.clone(),
+            llm.clone(),
+            config.llm.vision_model.clone(),
+            config.memory.clone(),
+        )
+        .with_disabled_features(disabled_features.clone())
+        .with_live_events(live_events.clone())
+        .with_metrics(metrics.clone())
+        .with_live_settings(live_settings.clone())
+        .with_voice_roster(voice_roster.clone())
+        .with_circuit_breaker(circuit_breaker);
+        let component_handler = MessageComponentHandler::new(command_handler.clone(), persona_manager, database.clone());
+
+        // Parse guild ID if provided for development mode
+        let guild_id = config.discord_guild_id.as_ref().and_then(|id| id.parse::<u64>().ok()).map(GuildId);
+
+        // Create startup notifier (reads config from database)
+        let startup_notifier = StartupNotifier::new(Arc::new(database.clone()));
+
+        let handler =
+            Handler::new(command_handler, component_handler, guild_id, startup_notifier, database.clone(), plugins);
+
+        Ok(Bot {
+            inner: Arc::new(BotInner {
+                config,
+                database,
+                llm,
+                usage_tracker,
+                slack_client,
+                matrix_client,
+                supervisor,
+                handler,
+                disabled_features,
+                clock,
+                ids,
+                live_events,
+                metrics,
+                live_settings,
+                voice_roster,
+                shard_manager: ShardSlot::default(),
+                started: AtomicBool::new(false),
+            }),
+        })
+    }
+}
+
+struct BotInner {
+    config: Config,
+    database: Database,
+    llm: Arc<dyn LlmProvider>,
+    usage_tracker: UsageTracker,
+    slack_client: Option<SlackClient>,
+    matrix_client: Option<MatrixClient>,
+    supervisor: Supervisor,
+    handler: Handler,
+    disabled_features: HashSet<String>,
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGen>,
+    live_events: LiveEvents,
+    metrics: Metrics,
+    live_settings: LiveSettings,
+    /// Shared with the handler, which keeps it current from voice state events
+    voice_roster: VoiceRoster,
+    shard_manager: ShardSlot,
+    started: AtomicBool,
+}
+
+/// A configured bot; clones share the same instance, so one can call
+/// [`Bot::shutdown`] while another is inside [`Bot::run`]
+#[derive(Clone)]
+pub struct Bot {
+    inner: Arc<BotInner>,
+}
+
+impl Bot {
+    pub fn builder(config: Config) -> BotBuilder {
+        BotBuilder::new(config)
+    }
+
+    pub fn database(&self) -> &Database {
+        &self.inner.database
+    }
+
+    pub fn supervisor(&self) -> &Supervisor {
+        &self.inner.supervisor
+    }
+
+    /// Events the bot publishes as it runs, for hosts that want them without the WebSocket
+    pub fn live_events(&self) -> &LiveEvents {
+        &self.inner.live_events
+    }
+
+    /// Counters and gauges behind `GET /metrics`, for hosts that expose them another way
+    pub fn metrics(&self) -> &Metrics {
+        &self.inner.metrics
+    }
+
+    /// Apply `config`'s model, conflict sensitivity, mediation cooldown and command
+    /// allowlist to the running bot. Other fields need a new bot to take effect.
+    pub fn reload(&self, config: &Config) {
+        self.inner.live_settings.apply(config);
+    }
+
+    fn feature_enabled(&self, id: &str) -> bool {
+        !self.inner.disabled_features.contains(id)
+    }
+
+    /// Start the background tasks and connect to Discord, returning once the bot is
+    /// shut down or the gateway is given up on. A bot can only be run once.
+    pub async fn run(&self) -> Result<()> {
+        if self.inner.started.swap(true, Ordering::SeqCst) {
+            return Err(BotError::validation("The bot is already running"));
+        }
+        let config = &self.inner.config;
+        let supervisor = &self.inner.supervisor;
+        let database = &self.inner.database;
+
+        info!("Starting Persona Discord Bot...");
+        if config.memory.low_memory {
+            info!("🪶 Low-memory mode: {:?}", config.memory);
+        }
+
+        // GUILDS and GUILD_MESSAGES also deliver thread lifecycle events and thread messages
+        let mut intents = GatewayIntents::GUILDS
+            | GatewayIntents::GUILD_MESSAGES
+            | GatewayIntents::GUILD_MESSAGE_REACTIONS
+            | GatewayIntents::DIRECT_MESSAGES
+            | GatewayIntents::MESSAGE_CONTENT
+            | GatewayIntents::GUILD_VOICE_STATES;
+        // Privileged: must also be enabled in the Developer Portal
+        if config.guild_members_intent {
+            intents |= GatewayIntents::GUILD_MEMBERS;
+        }
+
+        // Background tasks share one HTTP client; the gateway builds its own on each (re)start
+        let http = Arc::new(Http::new(&config.discord_token));
+
+        // Start the reminder scheduler
+        if self.feature_enabled("reminders") {
+            let scheduler = Arc::new(ReminderScheduler::new(
+                database.clone(),
+                config.openai_model.clone(),
+                self.inner.llm.clone(),
+                self.inner.usage_tracker.clone(),
+            )
+            .with_clock(self.inner.clock.clone()));
+            let scheduler_http = http.clone();
+            supervisor.spawn("reminders", move || {
+                let (scheduler, http) = (scheduler.clone(), scheduler_http.clone());
+                async move {
+                    scheduler.run(http).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Start the system metrics collection task (also refreshes the Prometheus gauges)
+        let db = Arc::new(database.clone());
+        let (metrics_db, db_path, metrics_http) = (db.clone(), config.database_path.clone(), http.clone());
+        let (shards, metrics) = (self.inner.shard_manager.clone(), self.inner.metrics.clone());
+        supervisor.spawn("metrics", move || {
+            let (db, db_path, http) = (metrics_db.clone(), db_path.clone(), metrics_http.clone());
+            let (shards, metrics) = (shards.clone(), metrics.clone());
+            async move {
+                metrics_collection_loop(db, db_path, http, shards, metrics).await;
+                Ok(())
+            }
+        });
+
+        // Start the Prometheus exporter when a listen address is configured
+        if let Some(addr) = config.metrics_listen_addr.clone().filter(|_| self.feature_enabled("prometheus")) {
+            let (exporter_metrics, exporter_db) = (self.inner.metrics.clone(), database.clone());
+            supervisor.spawn("prometheus", move || {
+                let (addr, metrics, database) = (addr.clone(), exporter_metrics.clone(), exporter_db.clone());
+                async move { serve_metrics(&addr, metrics, database).await }
+            });
+        }
+
+        // Owner reports and budget alerts are also emailed when SMTP is configured
+        let owner_email = EmailSender::from_config(config.smtp.as_ref());
+
+        // Start the weekly cost digest and monthly invoice tasks (DM and optionally email the bot owner)
+        if self.feature_enabled("cost_reports") {
+            let (invoice_db, invoice_http, invoice_email) = (db.clone(), http.clone(), owner_email.clone());
+            supervisor.spawn("monthly_invoice", move || {
+                let (http, db, email) = (invoice_http.clone(), invoice_db.clone(), invoice_email.clone());
+                async move {
+                    monthly_invoice_loop(http, db, email).await;
+                    Ok(())
+                }
+            });
+            let (digest_db, digest_http, digest_email) = (db.clone(), http.clone(), owner_email.clone());
+            supervisor.spawn("weekly_digest", move || {
+                let (http, db, email) = (digest_http.clone(), digest_db.clone(), digest_email.clone());
+                async move {
+                    weekly_digest_loop(http, db, email).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Start the Google Sheets analytics export when a service account is configured
+        if let Some(path) = config.google_service_account_file.as_deref().filter(|_| self.feature_enabled("sheets_export")) {
+            match SheetsClient::from_file(path) {
+                Ok(sheets) => {
+                    let sheets_db = db.clone();
+                    supervisor.spawn("sheets_export", move || {
+                        let (db, sheets) = (sheets_db.clone(), sheets.clone());
+                        async move {
+                            sheets_export_loop(db, sheets).await;
+                            Ok(())
+                        }
+                    });
+                }
+                Err(e) => warn!("Failed to load Google service account from {path}, Sheets export disabled: {e}"),
+            }
+        }
+
+        // Start the nightly analytics export when an S3-compatible bucket is configured
+        if let Some(s3_config) = config.s3_export.clone().filter(|_| self.feature_enabled("s3_export")) {
+            let s3 = S3Client::new(s3_config);
+            let s3_db = db.clone();
+            supervisor.spawn("s3_export", move || {
+                let (db, s3) = (s3_db.clone(), s3.clone());
+                async move {
+                    s3_export_loop(db, s3).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Start the knowledge base sync when a Notion token or markdown folder is configured
+        if (config.notion_token.is_some() || config.knowledge_base_dir.is_some()) && self.feature_enabled("knowledge_sync") {
+            let exporters = KnowledgeExporters {
+                markdown: config.knowledge_base_dir.as_deref().map(|dir| MarkdownExporter::new(dir, config.knowledge_base_git_push)),
+                notion: config.notion_token.clone().map(NotionClient::new),
+            };
+            let knowledge_db = db.clone();
+            supervisor.spawn("knowledge_sync", move || {
+                let (db, exporters) = (knowledge_db.clone(), exporters.clone());
+                async move {
+                    knowledge_sync_loop(db, exporters).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Start the unanswered question digest (guilds opt in with help_digest_channel)
+        if self.feature_enabled("help_digest") {
+            let digester = Arc::new(HelpDigester::new(
+                database.clone(),
+                self.inner.llm.clone(),
+                self.inner.live_settings.clone(),
+                self.inner.usage_tracker.clone(),
+            ));
+            let digest_http = http.clone();
+            supervisor.spawn("help_digest", move || {
+                let (digester, http) = (digester.clone(), digest_http.clone());
+                async move {
+                    digester.run(http).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Post budget warnings and overrun notifications recorded by the budget guard
+        if self.feature_enabled("budgets") {
+            let (budget_db, budget_http, budget_email) = (database.clone(), http.clone(), owner_email.clone());
+            supervisor.spawn("budget_alerts", move || {
+                let (db, http, email) = (budget_db.clone(), budget_http.clone(), budget_email.clone());
+                async move {
+                    budget_alert_loop(db, http, email).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // DM moderators about messages and replies flagged by content moderation
+        if self.feature_enabled("content_moderation") {
+            let (moderation_db, moderation_http) = (database.clone(), http.clone());
+            supervisor.spawn("moderation_alerts", move || {
+                let (db, http) = (moderation_db.clone(), moderation_http.clone());
+                async move {
+                    moderation_alert_loop(db, http).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Work through queued /import_history backfills
+        if self.feature_enabled("history_import") {
+            let importer = Arc::new(HistoryImporter::new(database.clone()));
+            let import_http = http.clone();
+            supervisor.spawn("history_import", move || {
+                let (importer, http) = (importer.clone(), import_http.clone());
+                async move {
+                    importer.run(http).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Start the incoming webhook endpoint when a listen address is configured
+        if let Some(addr) = config.webhook_listen_addr.clone().filter(|_| self.feature_enabled("webhook_ingest")) {
+            let (webhook_db, webhook_http) = (db.clone(), http.clone());
+            supervisor.spawn("webhook_ingest", move || {
+                let (addr, db, http) = (addr.clone(), webhook_db.clone(), webhook_http.clone());
+                async move { serve_webhooks(&addr, db, http).await }
+            });
+        }
+
+        // Start the health endpoint when a listen address is configured, with the live event
+        // stream and the dashboard when they are configured too
+        if let Some(addr) = config.health_listen_addr.clone() {
+            let mut extra = axum::Router::new();
+            if let Some(token) = config.live_events_token.as_deref().filter(|_| self.feature_enabled("live_events")) {
+                extra = extra.merge(live_events_router(self.inner.live_events.clone(), token));
+            }
+            if let Some(dashboard) = config.dashboard.as_ref().filter(|_| self.feature_enabled("dashboard")) {
+                info!("Dashboard at {}/dashboard", dashboard.public_url);
+                extra = extra.merge(dashboard_router(
+                    dashboard,
+                    database.clone(),
+                    self.inner.disabled_features.clone(),
+                    self.inner.clock.clone(),
+                    self.inner.ids.clone(),
+                ));
+            }
+            let health_supervisor = supervisor.clone();
+            supervisor.spawn("health_endpoint", move || {
+                let (addr, supervisor, extra) = (addr.clone(), health_supervisor.clone(), extra.clone());
+                async move { serve_health(&addr, supervisor, extra).await }
+            });
+        } else if config.live_events_token.is_some() || config.dashboard.is_some() {
+            warn!("LIVE_EVENTS_TOKEN or the dashboard is configured but HEALTH_LISTEN_ADDR is not, so they are not served");
+        }
+
+        // Start the stale settings validator (flags settings pointing at deleted channels/roles)
+        if self.feature_enabled("stale_settings") {
+            let (validator_db, validator_http) = (db.clone(), http.clone());
+            supervisor.spawn("stale_settings", move || {
+                let (http, db) = (validator_http.clone(), validator_db.clone());
+                async move {
+                    stale_settings_loop(http, db).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Start the auto slowmode revert task (restores slowmode after activity spikes)
+        if self.feature_enabled("auto_slowmode") {
+            let (slowmode_db, slowmode_http) = (db.clone(), http.clone());
+            supervisor.spawn("slowmode_revert", move || {
+                let (http, db) = (slowmode_http.clone(), slowmode_db.clone());
+                async move {
+                    slowmode_revert_loop(http, db).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Start the focus session timer (moves /pomodoro sessions between focus and break)
+        if self.feature_enabled("focus_sessions") {
+            let (focus_db, focus_http, roster) = (db.clone(), http.clone(), self.inner.voice_roster.clone());
+            supervisor.spawn("focus_timer", move || {
+                let (http, db, roster) = (focus_http.clone(), focus_db.clone(), roster.clone());
+                async move {
+                    focus_timer_loop(http, db, roster).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Start the timeout expiry task (ends /timeout actions when they are due)
+        if self.feature_enabled("moderation") {
+            let (timeout_db, timeout_http) = (db.clone(), http.clone());
+            supervisor.spawn("timeout_expiry", move || {
+                let (http, db) = (timeout_http.clone(), timeout_db.clone());
+                async move {
+                    timeout_expiry_loop(http, db).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Start the verification gate task (kicks members who don't verify in time)
+        if self.feature_enabled("verification_gate") {
+            let (gate_db, gate_http) = (db.clone(), http.clone());
+            supervisor.spawn("verification_gate", move || {
+                let (http, db) = (gate_http.clone(), gate_db.clone());
+                async move {
+                    gate_kick_loop(http, db).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Start the Slack bridge task (relays Slack messages to bridged Discord channels)
+        if let Some(slack) = self.inner.slack_client.clone().filter(|_| self.feature_enabled("slack_bridge")) {
+            let (bridge_db, bridge_http) = (db.clone(), http.clone());
+            supervisor.spawn("slack_bridge", move || {
+                let (http, db, slack) = (bridge_http.clone(), bridge_db.clone(), slack.clone());
+                async move {
+                    slack_bridge_loop(http, db, slack).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // Start the Matrix bridge task (syncs with the homeserver and relays room messages)
+        if let Some(matrix) = self.inner.matrix_client.clone().filter(|_| self.feature_enabled("matrix_bridge")) {
+            let (matrix_db, matrix_http) = (db.clone(), http.clone());
+            supervisor.spawn("matrix_bridge", move || {
+                let (http, db, matrix) = (matrix_http.clone(), matrix_db.clone(), matrix.clone());
+                async move {
+                    matrix_bridge_loop(http, db, matrix).await;
+                    Ok(())
+                }
+            });
+        }
+
+        // A bot with an interactions endpoint takes its interactions over HTTP instead of the gateway
+        if let (Some(addr), Some(public_key)) =
+            (config.interactions_listen_addr.clone(), config.discord_public_key.clone())
+        {
+            let (handler, endpoint_http, token) = (self.inner.handler.clone(), http.clone(), config.discord_token.clone());
+            let endpoint = supervisor.spawn("interactions_endpoint", move || {
+                let (addr, public_key, token, handler, http) =
+                    (addr.clone(), public_key.clone(), token.clone(), handler.clone(), endpoint_http.clone());
+                async move { run_interactions_endpoint(&addr, &public_key, &token, handler, http).await }
+            });
+            return match endpoint.await {
+                Ok(TaskState::Failed) => Err(BotError::internal("Interactions endpoint kept failing, giving up")),
+                Ok(_) => Ok(()),
+                Err(e) => Err(BotError::internal(format!("Interactions endpoint task ended unexpectedly: {e}"))),
+            };
+        }
+
+        // The gateway is supervised like the other tasks; run only fails if it is given up on
+        info!("Gateway intents: {intents:?}");
+        let (token, shards) = (config.discord_token.clone(), config.shards);
+        let (handler, slot) = (self.inner.handler.clone(), self.inner.shard_manager.clone());
+        let gateway = supervisor.spawn("discord_gateway", move || {
+            let (token, handler, slot) = (token.clone(), handler.clone(), slot.clone());
+            async move { run_gateway(&token, intents, shards, handler, slot).await }
+        });
+
+        match gateway.await {
+            Ok(TaskState::Failed) => Err(BotError::internal("Discord gateway kept failing, giving up")),
+            Ok(_) => Ok(()),
+            Err(e) => Err(BotError::internal(format!("Gateway task ended unexpectedly: {e}"))),
+        }
+    }
+
+    /// Close the gateway connection and stop every background task, letting [`Bot::run`] return
+    pub async fn shutdown(&self) {
+        info!("Shutting down...");
+        if let Some(shard_manager) = self.inner.shard_manager.lock().await.take() {
+            shard_manager.lock().await.shutdown_all().await;
+        }
+        self.inner.supervisor.shutdown();
+    }
+}
+
+/// Register commands over HTTP, then serve the interactions endpoint until it fails or is shut down
+async fn run_interactions_endpoint(addr: &str, public_key: &str, token: &str, handler: Handler, http: Arc<Http>) -> Result<()> {
+    handler.prepare_http_mode(&http_context(http.clone())).await?;
+    serve_interactions(addr, public_key, token, http, Arc::new(handler)).await
+}
+
+/// Connect to the Discord gateway and run until the connection fails or is shut down
+async fn run_gateway(token: &str, intents: GatewayIntents, shards: ShardMode, handler: Handler, slot: ShardSlot) -> Result<()> {
+    // Build the Discord client with proper gateway configuration
+    let mut client = Client::builder(token, intents)
+        .event_handler(handler)
+        .await
+        .map_err(|e| {
+            error!("Failed to create Discord client: {e}");
+            error!("This could indicate:");
+            error!("  - Invalid bot token format");
+            error!("  - Network issues reaching Discord API");
+            error!("  - Insufficient permissions");
+            BotError::from(e)
+        })?;
+    *slot.lock().await = Some(client.shard_manager.clone());
+
+    // Log gateway connection attempt
+    info!("Establishing WebSocket connection to Discord gateway...");
+
+    let started = match shards {
+        ShardMode::Single => client.start().await,
+        ShardMode::Auto => {
+            info!("Starting the number of shards Discord recommends");
+            client.start_autosharded().await
+        }
+        ShardMode::Fixed(count) => {
+            info!("Starting {count} shards");
+            client.start_shards(count).await
+        }
+    };
+    if let Err(why) = started {
+        error!("Gateway connection failed: {why:?}");
+        error!("This could be due to:");
+        error!("  - Invalid bot token");
+        error!("  - Network connectivity issues");
+        error!("  - Discord API outage");
+        error!("  - Missing required permissions");
+        return Err(BotError::from(why));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+//! # Bot Fleet
+//!
+//! Runs the bots listed in a [`MultiConfig`] in one process, sharing the
+//! database, and applies a changed config to them: added bots are started,
+//! removed ones shut down, bots with a new token reconnected and the rest
+//! updated in place through [`Bot::reload`]. [`config_watch_loop`] polls the
+//! file and applies each valid change.
+//!
+//! Each bot's conversation history is kept under its name. Bots paired with
+//! `share_memory_with` are told their partners through their live settings,
+//! and are reloaded when an edit to either entry changes the pairing.
+//!
+//! Bots share one connection pool unless their entry sets `database_pool_size`,
+//! which opens a pool of that size for the bot alone on the same database.
+//!
+//! Only one bot serves the health, metrics, webhook and dashboard listeners,
+//! since they bind fixed addresses: the first started, or the next one started
+//! after it is removed.
+//!
+//! - **Version**: 1.2.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.2.0: Per-bot database pools from `database_pool_size`
+//! - 1.1.0: Per-bot conversation history and memory partners
+//! - 1.0.0: Initial release
+
+use super::builder::Bot;
+use crate::core::{Config, FleetDiff, MultiConfig, Result};
+use crate::database::Database;
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+/// How often the config file's modification time is checked
+pub const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+struct FleetBot {
+    bot: Bot,
+    task: JoinHandle<()>,
+}
+
+/// The running bots and the config they were started from
+pub struct Fleet {
+    base: Config,
+    database: Database,
+    current: MultiConfig,
+    bots: HashMap<String, FleetBot>,
+    listener_owner: Option<String>,
+}
+
+impl Fleet {
+    /// Open the shared database and start every bot in `config`. `base` supplies
+    /// everything a bot entry doesn't override; its Discord token is ignored.
+    pub async fn start(base: Config, config: MultiConfig) -> Result<Self> {
+        let database = Database::open(&base).await?;
+        let mut fleet = Fleet { base, database, current: MultiConfig::default(), bots: HashMap::new(), listener_owner: None };
+        let diff = fleet.apply(config).await?;
+        info!("🚀 Fleet started: {}", diff.describe());
+        Ok(fleet)
+    }
+
+    /// Names of the running bots
+    pub fn bot_names(&self) -> Vec<String> {
+        self.current.bots.iter().map(|b| b.name.clone()).collect()
+    }
+
+    /// Move the fleet to `next`. Every bot's config is resolved first, so a
+    /// missing token leaves the fleet untouched; a bot that then fails to start
+    /// is logged and left out.
+    pub async fn apply(&mut self, next: MultiConfig) -> Result<FleetDiff> {
+        let diff = self.current.diff(&next);
+        let mut configs = HashMap::new();
+        for entry in diff.added.iter().chain(&diff.restarted).chain(&diff.changed) {
+            let mut config = entry.config(&self.base)?;
+            config.memory_partners = next.memory_partners(&entry.name);
+            configs.insert(entry.name.clone(), config);
+        }
+        // Bots whose own entry is unchanged but whose pairing changed through another's
+        let mut repaired = Vec::new();
+        for entry in &next.bots {
+            let partners = next.memory_partners(&entry.name);
+            if !configs.contains_key(&entry.name) && self.current.memory_partners(&entry.name) != partners {
+                let mut config = entry.config(&self.base)?;
+                config.memory_partners = partners;
+                repaired.push((entry.name.clone(), config));
+            }
+        }
+
+        for name in diff.removed.iter().chain(diff.restarted.iter().map(|e| &e.name)) {
+            self.stop(name).await;
+        }
+        for entry in diff.added.iter().chain(&diff.restarted) {
+            if let Some(config) = configs.remove(&entry.name) {
+                if let Err(e) = self.launch(&entry.name, config).await {
+                    error!("❌ Failed to start bot '{}': {e}", entry.name);
+                }
+            }
+        }
+        for entry in &diff.changed {
+            if let (Some(running), Some(config)) = (self.bots.get(&entry.name), configs.get(&entry.name)) {
+                running.bot.reload(config);
+                info!("🔄 Applied new settings to bot '{}'", entry.name);
+            }
+        }
+        for (name, config) in &repaired {
+            if let Some(running) = self.bots.get(name) {
+                running.bot.reload(config);
+                info!("🔄 Bot '{name}' now shares memory with: {}", config.memory_partners.join(", "));
+            }
+        }
+
+        // Remember only what is actually running, so a failed start is retried on the next change
+        self.current = MultiConfig { bots: next.bots.into_iter().filter(|b| self.bots.contains_key(&b.name)).collect() };
+        Ok(diff)
+    }
+
+    async fn launch(&mut self, name: &str, mut config: Config) -> Result<()> {
+        if self.listener_owner.as_deref().is_some_and(|owner| owner != name) {
+            config.health_listen_addr = None;
+            config.metrics_listen_addr = None;
+            config.webhook_listen_addr = None;
+            config.live_events_token = None;
+            config.dashboard = None;
+        }
+        let database = self.database.with_pool_size(config.database_pool_size)?.with_history_bot(name);
+        let bot = Bot::builder(config).database(database).build().await?;
+        self.listener_owner.get_or_insert_with(|| name.to_string());
+
+        let (runner, bot_name) = (bot.clone(), name.to_string());
+        let task = tokio::spawn(async move {
+            if let Err(e) = runner.run().await {
+                error!("❌ Bot '{bot_name}' stopped: {e}");
+            }
+        });
+        info!("▶️ Started bot '{name}'");
+        self.bots.insert(name.to_string(), FleetBot { bot, task });
+        Ok(())
+    }
+
+    async fn stop(&mut self, name: &str) {
+        if let Some(FleetBot { bot, task }) = self.bots.remove(name) {
+            bot.shutdown().await;
+            let _ = task.await;
+            if self.listener_owner.as_deref() == Some(name) {
+                // The next bot started takes the listeners over
+                self.listener_owner = None;
+            }
+            info!("⏹️ Stopped bot '{name}'");
+        }
+    }
+
+    /// Shut every bot down
+    pub async fn shutdown(&mut self) {
+        let names: Vec<String> = self.bots.keys().cloned().collect();
+        for name in names {
+            self.stop(&name).await;
+        }
+        self.current = MultiConfig::default();
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reload `path` into the fleet whenever it changes. A file that fails to
+/// parse or names a missing token is logged and skipped; the bots keep running
+/// with their current settings until the next valid edit.
+pub async fn config_watch_loop(path: PathBuf, fleet: Arc<Mutex<Fleet>>) {
+    let mut last_modified = modified(&path);
+    let mut interval = tokio::time::interval(CONFIG_POLL_INTERVAL);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let current = modified(&path);
+        if current.is_none() || current == last_modified {
+            continue;
+        }
+        last_modified = current;
+
+        let next = match MultiConfig::load(&path) {
+            Ok(next) => next,
+            Err(e) => {
+                warn!("⚠️ Ignoring invalid {}: {e}", path.display());
+                continue;
+            }
+        };
+        match fleet.lock().await.apply(next).await {
+            Ok(diff) if diff.is_empty() => info!("{} changed, nothing to apply", path.display()),
+            Ok(diff) => info!("🔄 Reloaded {}: {}", path.display(), diff.describe()),
+            Err(e) => warn!("⚠️ Ignoring {}: {e}", path.display()),
+        }
+    }
+}
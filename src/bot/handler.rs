@@ -0,0 +1,935 @@
+//! # Event Handler
+//!
+//! Routes gateway events to the command and component handlers, then to any
+//! plugins. Each interaction runs in its own task so a panic is reported to
+//! the user instead of leaving the interaction unanswered.
+//!
+//! - **Version**: 1.3.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.3.0: Interactions and messages handled inside spans carrying bot, guild, user and interaction IDs
+//! - 1.2.0: Handled commands counted for the Prometheus exporter
+//! - 1.1.0: Handled commands and handler errors published to the live event stream, command errors logged to `error_logs`
+//! - 1.0.0: Moved from the binary into the library, with plugin dispatch
+
+use super::plugin::{command_names, Plugin};
+use crate::commands::{register_global_commands, register_guild_commands, CommandHandler, GUILD_SETTING_KEYS};
+use crate::core::discord_limits::truncate;
+use crate::core::{BotError, Result};
+use crate::database::Database;
+use crate::features::live_events::LiveEventKind;
+use crate::features::moderation::REASON_TEMPLATES;
+use crate::features::panic_capture::{record_panic, user_error_embed, PanicReport, PanicSource};
+use crate::features::reminders::UserTimezone;
+use crate::features::startup::StartupNotifier;
+use crate::message_components::MessageComponentHandler;
+use tracing::{error, field, info, info_span, warn, Instrument, Span};
+use serenity::async_trait;
+use serenity::client::bridge::gateway::event::ShardStageUpdateEvent;
+use serenity::gateway::ConnectionStage;
+use serenity::model::application::interaction::Interaction;
+use serenity::model::channel::{Message, PartialGuildChannel, Reaction};
+use serenity::model::event::ResumedEvent;
+use serenity::model::gateway::Ready;
+use serenity::model::guild::{Guild, Member, UnavailableGuild};
+use serenity::model::id::GuildId;
+use serenity::model::voice::VoiceState;
+use serenity::prelude::*;
+use std::sync::{Arc, OnceLock};
+
+#[derive(Clone)]
+pub(crate) struct Handler {
+    command_handler: Arc<CommandHandler>,
+    component_handler: Arc<MessageComponentHandler>,
+    guild_id: Option<GuildId>,
+    startup_notifier: StartupNotifier,
+    database: Database,
+    plugins: Arc<[Arc<dyn Plugin>]>,
+    /// The bot's user ID, known once the gateway is ready; tagged on every span
+    bot_id: Arc<OnceLock<String>>,
+}
+
+impl Handler {
+    pub(crate) fn new(
+        command_handler: CommandHandler,
+        component_handler: MessageComponentHandler,
+        guild_id: Option<GuildId>,
+        startup_notifier: StartupNotifier,
+        database: Database,
+        plugins: Vec<Arc<dyn Plugin>>,
+    ) -> Self {
+        Handler {
+            command_handler: Arc::new(command_handler),
+            component_handler: Arc::new(component_handler),
+            guild_id,
+            startup_notifier,
+            database,
+            plugins: plugins.into(),
+            bot_id: Arc::new(OnceLock::new()),
+        }
+    }
+
+    fn bot_id(&self) -> Option<&str> {
+        self.bot_id.get().map(String::as_str)
+    }
+
+    /// The plugin that registered the slash command `name`, if any
+    fn plugin_for_command(&self, name: &str) -> Option<&Arc<dyn Plugin>> {
+        self.plugins
+            .iter()
+            .find(|plugin| command_names(plugin.as_ref()).iter().any(|command| command == name))
+    }
+
+    /// Report a failed interaction to live event subscribers, with the full error rather than the user-facing text
+    fn publish_error(&self, source: &str, name: &str, error: &BotError) {
+        self.command_handler.live_events().publish(LiveEventKind::Error {
+            source: source.to_string(),
+            name: name.to_string(),
+            message: error.to_string(),
+        });
+    }
+
+    /// Register slash commands - use guild commands for development (instant), global for production
+    async fn register_commands(&self, ctx: &Context) {
+        let plugin_commands: Vec<_> = self.plugins.iter().flat_map(|plugin| plugin.commands()).collect();
+        if let Some(guild_id) = self.guild_id {
+            info!("🔧 Development mode: Registering commands for guild {guild_id}");
+            if let Err(e) = register_guild_commands(ctx, guild_id, plugin_commands).await {
+                error!("❌ Failed to register guild slash commands: {e}");
+            } else {
+                info!("✅ Successfully registered slash commands for guild {guild_id} (instant update)");
+            }
+        } else {
+            info!("🌍 Production mode: Registering commands globally");
+            if let Err(e) = register_global_commands(ctx, plugin_commands).await {
+                error!("❌ Failed to register global slash commands: {e}");
+            } else {
+                info!("✅ Successfully registered slash commands globally (may take up to 1 hour to propagate)");
+            }
+        }
+    }
+
+    /// What `ready` does for a gateway connection, for a bot taking interactions over HTTP:
+    /// learn the bot and application IDs, then register the commands
+    pub(crate) async fn prepare_http_mode(&self, ctx: &Context) -> Result<()> {
+        let application = ctx.http.get_current_application_info().await?;
+        ctx.http.set_application_id(application.id.0);
+        let user = ctx.http.get_current_user().await?;
+        let _ = self.bot_id.set(user.id.to_string());
+        info!("🤖 {} is taking interactions over HTTP (bot ID {})", user.name, user.id);
+        self.register_commands(ctx).await;
+        Ok(())
+    }
+
+    async fn dispatch_interaction(&self, ctx: Context, interaction: Interaction) {
+        match interaction {
+            Interaction::ApplicationCommand(command) => {
+                let started = std::time::Instant::now();
+                let result = match self.plugin_for_command(&command.data.name) {
+                    Some(plugin) => plugin.on_command(&ctx, &command).await,
+                    None => self.command_handler.handle_slash_command(&ctx, &command).await,
+                };
+
+                // Per-guild command volume and errors for /ops overview
+                if let Some(guild_id) = command.guild_id {
+                    if let Err(e) = self.database.record_guild_command(&guild_id.to_string(), result.is_err()).await {
+                        warn!("Failed to record guild activity: {e}");
+                    }
+                }
+                Span::current().record("ok", result.is_ok());
+                self.command_handler.metrics().record_command(&command.data.name, result.is_ok());
+                self.command_handler.live_events().publish(LiveEventKind::CommandHandled {
+                    command: command.data.name.clone(),
+                    user_id: command.user.id.to_string(),
+                    guild_id: command.guild_id.map(|id| id.to_string()),
+                    ok: result.is_ok(),
+                    duration_ms: started.elapsed().as_millis() as u64,
+                });
+
+                if let Err(e) = result {
+                    error!("Error handling slash command '{}': {}", command.data.name, e);
+                    self.publish_error("command", &command.data.name, &e);
+                    let guild_id = command.guild_id.map(|id| id.to_string());
+                    if let Err(log_err) = self.database.log_error(
+                        "command_error",
+                        &e.to_string(),
+                        None,
+                        Some(&command.user.id.to_string()),
+                        Some(&command.channel_id.to_string()),
+                        guild_id.as_deref(),
+                        Some(&command.data.name),
+                        None,
+                    ).await {
+                        warn!("Failed to log command error: {log_err}");
+                    }
+                    
+                    let error_message = e.user_message();
+                    
+                    // Try to edit the deferred response, fallback to new response if that fails
+                    #[allow(clippy::redundant_pattern_matching)]
+                    if let Err(_) = command.edit_original_interaction_response(&ctx.http, |response| {
+                        response.content(&error_message)
+                    }).await {
+                        let _ = command.create_interaction_response(&ctx.http, |response| {
+                            response
+                                .kind(serenity::model::application::interaction::InteractionResponseType::ChannelMessageWithSource)
+                                .interaction_response_data(|message| {
+                                    message.content(&error_message)
+                                })
+                        }).await;
+                    }
+                }
+            }
+            Interaction::MessageComponent(component) => {
+                if let Err(e) = self.component_handler.handle_component_interaction(&ctx, &component).await {
+                    error!("Error handling component interaction '{}': {}", component.data.custom_id, e);
+                    self.publish_error("component", &component.data.custom_id, &e);
+                    
+                    let error_message = e.user_message();
+                    
+                    // Try to update the message, fallback to new response if that fails
+                    #[allow(clippy::redundant_pattern_matching)]
+                    if let Err(_) = component.create_interaction_response(&ctx.http, |response| {
+                        response
+                            .kind(serenity::model::application::interaction::InteractionResponseType::UpdateMessage)
+                            .interaction_response_data(|message| {
+                                message.content(&error_message)
+                            })
+                    }).await {
+                        let _ = component.create_interaction_response(&ctx.http, |response| {
+                            response
+                                .kind(serenity::model::application::interaction::InteractionResponseType::ChannelMessageWithSource)
+                                .interaction_response_data(|message| {
+                                    message.content(&error_message)
+                                })
+                        }).await;
+                    }
+                }
+            }
+            Interaction::ModalSubmit(modal) => {
+                if let Err(e) = self.component_handler.handle_modal_submit(&ctx, &modal).await {
+                    error!("Error handling modal submit '{}': {}", modal.data.custom_id, e);
+                    self.publish_error("modal", &modal.data.custom_id, &e);
+                    
+                    let error_message = e.user_message();
+                    
+                    // Try to edit the deferred response, fallback to new response if that fails
+                    #[allow(clippy::redundant_pattern_matching)]
+                    if let Err(_) = modal.edit_original_interaction_response(&ctx.http, |response| {
+                        response.content(&error_message)
+                    }).await {
+                        let _ = modal.create_interaction_response(&ctx.http, |response| {
+                            response
+                                .kind(serenity::model::application::interaction::InteractionResponseType::ChannelMessageWithSource)
+                                .interaction_response_data(|message| {
+                                    message.content(&error_message)
+                                })
+                        }).await;
+                    }
+                }
+            }
+            Interaction::Autocomplete(autocomplete) => {
+                info!("Autocomplete interaction received for command: {}", autocomplete.data.name);
+
+                // Handle autocomplete based on command
+                let _ = match autocomplete.data.name.as_str() {
+                    "set_guild_setting" if autocomplete.data.options.iter().any(|opt| opt.name == "setting" && opt.focused) => {
+                        // Offer setting names containing what has been typed so far (max 25)
+                        let typed = autocomplete.data.options.iter()
+                            .find(|opt| opt.name == "setting")
+                            .and_then(|opt| opt.value.as_ref())
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_lowercase();
+
+                        autocomplete
+                            .create_autocomplete_response(&ctx.http, |response| {
+                                for key in GUILD_SETTING_KEYS.iter().filter(|key| key.contains(typed.as_str())).take(25) {
+                                    response.add_string_choice(*key, *key);
+                                }
+                                response
+                            })
+                            .await
+                    }
+                    "set_guild_setting" => {
+                        // Get the setting option to determine which choices to show
+                        let setting = autocomplete.data.options.iter()
+                            .find(|opt| opt.name == "setting")
+                            .and_then(|opt| opt.value.as_ref())
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("");
+
+                        autocomplete
+                            .create_autocomplete_response(&ctx.http, |response| {
+                                match setting {
+                                    "default_verbosity" => {
+                                        response
+                                            .add_string_choice("concise - Brief responses (2-3 sentences)", "concise")
+                                            .add_string_choice("normal - Balanced responses", "normal")
+                                            .add_string_choice("detailed - Comprehensive responses", "detailed")
+                                    }
+                                    "default_persona" => {
+                                        response
+                                            .add_string_choice("obi - Obi-Wan Kenobi (wise mentor)", "obi")
+                                            .add_string_choice("muppet - Enthusiastic Muppet expert", "muppet")
+                                            .add_string_choice("chef - Passionate cooking expert", "chef")
+                                            .add_string_choice("teacher - Patient educator", "teacher")
+                                            .add_string_choice("analyst - Step-by-step analyst", "analyst")
+                                    }
+                                    "conflict_mediation" => {
+                                        response
+                                            .add_string_choice("enabled - Bot will mediate conflicts", "enabled")
+                                            .add_string_choice("disabled - No conflict mediation", "disabled")
+                                    }
+                                    "conflict_sensitivity" => {
+                                        response
+                                            .add_string_choice("low - Only obvious conflicts (0.7 threshold)", "low")
+                                            .add_string_choice("medium - Balanced detection (0.5 threshold)", "medium")
+                                            .add_string_choice("high - More sensitive (0.35 threshold)", "high")
+                                            .add_string_choice("ultra - Maximum sensitivity (0.3 threshold)", "ultra")
+                                    }
+                                    "mediation_cooldown" => {
+                                        response
+                                            .add_string_choice("1 minute", "1")
+                                            .add_string_choice("5 minutes (default)", "5")
+                                            .add_string_choice("10 minutes", "10")
+                                            .add_string_choice("15 minutes", "15")
+                                            .add_string_choice("30 minutes", "30")
+                                            .add_string_choice("60 minutes", "60")
+                                    }
+                                    "max_context_messages" => {
+                                        response
+                                            .add_string_choice("10 messages (minimal context)", "10")
+                                            .add_string_choice("20 messages (light context)", "20")
+                                            .add_string_choice("40 messages (default)", "40")
+                                            .add_string_choice("60 messages (extended context)", "60")
+                                    }
+                                    "audio_transcription" => {
+                                        response
+                                            .add_string_choice("enabled - Transcribe audio files", "enabled")
+                                            .add_string_choice("disabled - Skip audio processing", "disabled")
+                                    }
+                                    "audio_transcription_mode" => {
+                                        response
+                                            .add_string_choice("always - Transcribe all audio files", "always")
+                                            .add_string_choice("mention_only - Only when @mentioned", "mention_only")
+                                    }
+                                    "audio_transcription_output" => {
+                                        response
+                                            .add_string_choice("transcription_only - Just the transcription", "transcription_only")
+                                            .add_string_choice("with_commentary - Add AI commentary", "with_commentary")
+                                    }
+                                    "mention_responses" => {
+                                        response
+                                            .add_string_choice("enabled - Respond when @mentioned", "enabled")
+                                            .add_string_choice("disabled - Ignore mentions", "disabled")
+                                    }
+                                    "cite_sources" => {
+                                        response
+                                            .add_string_choice("enabled - Link to earlier messages the answer relies on", "enabled")
+                                            .add_string_choice("disabled - No source links", "disabled")
+                                    }
+                                    "auto_verbosity" => {
+                                        response
+                                            .add_string_choice("enabled - Answer frustrated users briefly, with an apology", "enabled")
+                                            .add_string_choice("disabled - Always use the channel verbosity", "disabled")
+                                    }
+                                    "shared_bot_memory" => {
+                                        response
+                                            .add_string_choice("enabled - Read the conversation history of paired bots", "enabled")
+                                            .add_string_choice("disabled - Each bot remembers only its own conversations (default)", "disabled")
+                                    }
+                                    "response_cache" => {
+                                        response
+                                            .add_string_choice("enabled - Answer repeated questions from the cache (default)", "enabled")
+                                            .add_string_choice("disabled - Always ask the model, and clear cached answers", "disabled")
+                                    }
+                                    "typing_indicator" => {
+                                        response
+                                            .add_string_choice("enabled - Show typing while an answer is written (default)", "enabled")
+                                            .add_string_choice("disabled - Answer without a typing indicator", "disabled")
+                                    }
+                                    "max_response_delay_ms" => {
+                                        response
+                                            .add_string_choice("0 - Answer as fast as possible (default)", "0")
+                                            .add_string_choice("1000 - Let personas pause up to 1 second", "1000")
+                                            .add_string_choice("3000 - Let personas pause up to 3 seconds", "3000")
+                                    }
+                                    "follow_up_suggestions" => {
+                                        response
+                                            .add_string_choice("enabled - Suggest follow-up questions as buttons", "enabled")
+                                            .add_string_choice("disabled - Plain answers only", "disabled")
+                                    }
+                                    "persona_webhooks" => {
+                                        response
+                                            .add_string_choice("enabled - Reply under the persona's name and avatar", "enabled")
+                                            .add_string_choice("disabled - Reply as the bot", "disabled")
+                                    }
+                                    "long_term_memory" => {
+                                        response
+                                            .add_string_choice("enabled - Recall related earlier conversations (default)", "enabled")
+                                            .add_string_choice("disabled - Only use recent channel history", "disabled")
+                                    }
+                                    "vision" => {
+                                        response
+                                            .add_string_choice("enabled - Answer questions about attached images (default)", "enabled")
+                                            .add_string_choice("disabled - Ignore attached images", "disabled")
+                                    }
+                                    "thread_conversations" => {
+                                        response
+                                            .add_string_choice("enabled - Replying to the bot opens a thread with its own history", "enabled")
+                                            .add_string_choice("disabled - Replies stay in the channel (default)", "disabled")
+                                    }
+                                    "support_channels" => {
+                                        response
+                                            .add_string_choice("disabled - No duplicate question detection", "disabled")
+                                    }
+                                    "reminders_channel" => {
+                                        response
+                                            .add_string_choice("disabled - Deliver in the original channel", "disabled")
+                                    }
+                                    "attachment_scan_channels" => {
+                                        response
+                                            .add_string_choice("disabled - No attachment scanning", "disabled")
+                                    }
+                                    "attachment_scan_sensitivity" => {
+                                        response
+                                            .add_string_choice("low - Only flag clearly explicit images", "low")
+                                            .add_string_choice("medium - Balanced (default)", "medium")
+                                            .add_string_choice("high - Flag borderline images too", "high")
+                                    }
+                                    "content_moderation" => {
+                                        response
+                                            .add_string_choice("disabled - Don't check messages or replies (default)", "disabled")
+                                            .add_string_choice("log_only - Record flagged content only", "log_only")
+                                            .add_string_choice("warn - Answer with a warning on flagged content", "warn")
+                                            .add_string_choice("block - Refuse flagged messages and withhold flagged replies", "block")
+                                    }
+                                    "content_moderation_notify" => {
+                                        response
+                                            .add_string_choice("disabled - Don't DM moderators", "disabled")
+                                    }
+                                    "conflict_alert_threshold" => {
+                                        response
+                                            .add_string_choice("50 - Most conflicts wait for a moderator", "50")
+                                            .add_string_choice("70 - Balanced (default)", "70")
+                                            .add_string_choice("90 - Only the clearest conflicts", "90")
+                                    }
+                                    "conflict_alert_channel" => {
+                                        response
+                                            .add_string_choice("disabled - Post alerts in the mod log channel", "disabled")
+                                    }
+                                    "conflict_escalation_timeout" => {
+                                        response
+                                            .add_string_choice("5 minutes", "5")
+                                            .add_string_choice("10 minutes (default)", "10")
+                                            .add_string_choice("60 minutes", "60")
+                                    }
+                                    "mod_log_channel" => {
+                                        response
+                                            .add_string_choice("disabled - Don't log moderation actions", "disabled")
+                                    }
+                                    "archive_channel" => {
+                                        response
+                                            .add_string_choice("disabled - Pick a destination each time", "disabled")
+                                    }
+                                    "appeal_review_channel" => {
+                                        response
+                                            .add_string_choice("disabled - Review appeals in the mod log channel", "disabled")
+                                    }
+                                    "lockdown_categories" => {
+                                        response
+                                            .add_string_choice("disabled - No lockdown categories", "disabled")
+                                    }
+                                    "join_screening" => {
+                                        response
+                                            .add_string_choice("disabled - Don't screen new members (default)", "disabled")
+                                            .add_string_choice("log - Post suspicious joins to the mod log", "log")
+                                            .add_string_choice("verify - Also require a button captcha", "verify")
+                                    }
+                                    "join_screening_threshold" => {
+                                        response
+                                            .add_string_choice("30 - Strict", "30")
+                                            .add_string_choice("50 - Balanced (default)", "50")
+                                            .add_string_choice("70 - Only obvious alts", "70")
+                                    }
+                                    "verification_gate" => {
+                                        response
+                                            .add_string_choice("disabled - No verification gate (default)", "disabled")
+                                            .add_string_choice("button - Press Verify to get the member role", "button")
+                                            .add_string_choice("question - Also answer a simple AI-generated question", "question")
+                                    }
+                                    "verification_timeout_minutes" => {
+                                        response
+                                            .add_string_choice("0 - Never kick unverified members", "0")
+                                            .add_string_choice("60 - One hour (default)", "60")
+                                            .add_string_choice("1440 - One day", "1440")
+                                    }
+                                    "verification_channel" | "unverified_role" | "member_role" => {
+                                        response
+                                            .add_string_choice("disabled - Not set", "disabled")
+                                    }
+                                    "analytics_sheet_id" => {
+                                        response
+                                            .add_string_choice("disabled - Don't export analytics (default)", "disabled")
+                                    }
+                                    "knowledge_base_export" => {
+                                        response
+                                            .add_string_choice("disabled - Don't export the knowledge base (default)", "disabled")
+                                            .add_string_choice("markdown - Write markdown files to KNOWLEDGE_BASE_DIR", "markdown")
+                                    }
+                                    "issue_linking" => {
+                                        response
+                                            .add_string_choice("enabled - Reply with details for issue keys like ENG-123", "enabled")
+                                            .add_string_choice("disabled - Only look up issues with /issue (default)", "disabled")
+                                    }
+                                    "custom_command_prefix" => {
+                                        response
+                                            .add_string_choice("disabled - Only run custom commands with /c (default)", "disabled")
+                                            .add_string_choice("! - Run custom commands as !name", "!")
+                                            .add_string_choice("? - Run custom commands as ?name", "?")
+                                    }
+                                    "monthly_budget_usd" => {
+                                        response
+                                            .add_string_choice("disabled - No monthly AI budget (default)", "disabled")
+                                            .add_string_choice("10 - $10 per month", "10")
+                                            .add_string_choice("50 - $50 per month", "50")
+                                    }
+                                    "per_user_daily_budget_usd" => {
+                                        response
+                                            .add_string_choice("disabled - No per-member budget (default)", "disabled")
+                                            .add_string_choice("0.50 - $0.50 per member per day", "0.50")
+                                            .add_string_choice("2 - $2 per member per day", "2")
+                                    }
+                                    "budget_fallback_model" => {
+                                        response
+                                            .add_string_choice("disabled - Refuse requests over budget (default)", "disabled")
+                                            .add_string_choice("gpt-4o-mini - Answer with a cheaper model", "gpt-4o-mini")
+                                    }
+                                    // Startup notification settings (global)
+                                    "startup_notification" => {
+                                        response
+                                            .add_string_choice("enabled - Send notification on startup", "enabled")
+                                            .add_string_choice("disabled - No startup notification", "disabled")
+                                    }
+                                    // For ID fields, don't show autocomplete - user must type the ID directly
+                                    // Return empty response so Discord shows the text input
+                                    "startup_notify_owner_id" | "startup_notify_channel_id" => response,
+                                    _ => response
+                                }
+                            })
+                            .await
+                    }
+                    "timezone" => {
+                        // IANA zone names containing what has been typed so far
+                        let typed = autocomplete.data.options.iter()
+                            .find(|opt| opt.name == "zone")
+                            .and_then(|opt| opt.value.as_ref())
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("");
+
+                        autocomplete
+                            .create_autocomplete_response(&ctx.http, |response| {
+                                for name in UserTimezone::suggestions(typed, 25) {
+                                    response.add_string_choice(name, name);
+                                }
+                                response
+                            })
+                            .await
+                    }
+                    "c" | "command" => {
+                        // The guild's custom commands matching what has been typed (top level for /c, under remove for /command)
+                        let options = match autocomplete.data.name.as_str() {
+                            "command" => autocomplete.data.options.first().map(|sub| sub.options.clone()).unwrap_or_default(),
+                            _ => autocomplete.data.options.clone(),
+                        };
+                        let typed = options.iter()
+                            .find(|opt| opt.name == "name")
+                            .and_then(|opt| opt.value.as_ref())
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_lowercase();
+                        let commands = match autocomplete.guild_id {
+                            Some(guild_id) => self.database.list_custom_commands(&guild_id.to_string()).await.unwrap_or_default(),
+                            None => Vec::new(),
+                        };
+
+                        autocomplete
+                            .create_autocomplete_response(&ctx.http, |response| {
+                                for command in commands.iter().filter(|c| c.name.contains(typed.as_str())).take(25) {
+                                    response.add_string_choice(&command.name, &command.name);
+                                }
+                                response
+                            })
+                            .await
+                    }
+                    "set_persona" | "handoff" | "as" | "persona" | "thread_settings" | "story" => {
+                        // Personas matching what has been typed: every one usable here, or only the
+                        // guild's own for /persona edit, delete and share
+                        let subcommand = autocomplete.data.options.first()
+                            .filter(|_| matches!(autocomplete.data.name.as_str(), "persona" | "thread_settings" | "story"));
+                        let options = match subcommand {
+                            Some(sub) => sub.options.clone(),
+                            None => autocomplete.data.options.clone(),
+                        };
+                        let typed = options.iter()
+                            .find(|opt| opt.focused)
+                            .and_then(|opt| opt.value.as_ref())
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_lowercase();
+                        let guild_id = autocomplete.guild_id.map(|id| id.to_string());
+                        let own_only = autocomplete.data.name == "persona" && subcommand.is_some_and(|sub| sub.name != "blend");
+                        let personas: Vec<_> = self.command_handler
+                            .persona_manager()
+                            .personas_for_guild(guild_id.as_deref())
+                            .into_iter()
+                            .filter(|(_, p)| !own_only || (p.guild_id.is_some() && p.guild_id == guild_id))
+                            .filter(|(key, p)| key.contains(typed.as_str()) || p.name.to_lowercase().contains(typed.as_str()))
+                            .collect();
+
+                        autocomplete
+                            .create_autocomplete_response(&ctx.http, |response| {
+                                for (key, persona) in personas.iter().take(25) {
+                                    let emoji = persona.emoji.as_deref().map(|e| format!("{e} ")).unwrap_or_default();
+                                    response.add_string_choice(truncate(&format!("{emoji}{key} - {}", persona.description), 100), key);
+                                }
+                                response
+                            })
+                            .await
+                    }
+                    "bookmarks" => {
+                        // The user's bookmarks whose id or snippet matches what has been typed
+                        let typed = autocomplete.data.options.first()
+                            .and_then(|sub| sub.options.iter().find(|opt| opt.name == "id"))
+                            .and_then(|opt| opt.value.as_ref())
+                            .map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string()))
+                            .unwrap_or_default()
+                            .to_lowercase();
+                        let bookmarks = self.database
+                            .get_user_bookmarks(&autocomplete.user.id.to_string())
+                            .await
+                            .unwrap_or_default();
+
+                        autocomplete
+                            .create_autocomplete_response(&ctx.http, |response| {
+                                let matches = bookmarks.iter().filter(|b| {
+                                    b.id.to_string().starts_with(typed.as_str()) || b.name.to_lowercase().contains(typed.as_str())
+                                });
+                                for bookmark in matches.take(25) {
+                                    let label = truncate(&format!("#{} {}", bookmark.id, bookmark.name.replace("**", "")), 100);
+                                    response.add_int_choice(label, bookmark.id);
+                                }
+                                response
+                            })
+                            .await
+                    }
+                    "sysinfo" => {
+                        // Bots that recorded metrics in the last 7 days, plus the combined view
+                        let bots = self.database.get_metric_bots(168).await.unwrap_or_default();
+                        autocomplete
+                            .create_autocomplete_response(&ctx.http, |response| {
+                                response.add_string_choice("All bots - per-bot breakdown", "all");
+                                for (bot_id, name) in bots.iter().take(24) {
+                                    response.add_string_choice(format!("{name} ({bot_id})"), bot_id);
+                                }
+                                response
+                            })
+                            .await
+                    }
+                    "timeout" | "kick" | "ban" => {
+                        // Offer reason templates matching what has been typed so far
+                        let typed = autocomplete.data.options.iter()
+                            .find(|opt| opt.name == "reason")
+                            .and_then(|opt| opt.value.as_ref())
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_lowercase();
+
+                        autocomplete
+                            .create_autocomplete_response(&ctx.http, |response| {
+                                for (key, text) in REASON_TEMPLATES {
+                                    if key.starts_with(&typed) || text.to_lowercase().contains(&typed) {
+                                        response.add_string_choice(format!("{key} - {text}"), *key);
+                                    }
+                                }
+                                response
+                            })
+                            .await
+                    }
+                    _ => {
+                        // Default empty response for unknown commands
+                        autocomplete
+                            .create_autocomplete_response(&ctx.http, |response| response)
+                            .await
+                    }
+                };
+            }
+            Interaction::Ping(_) => {
+                info!("Ping interaction received - Discord health check");
+                // Ping interactions are automatically handled by Serenity
+            }
+        }
+    }
+
+    /// Log a panic from an interaction handler, alert the owner and reply with an error embed
+    async fn answer_after_panic(&self, ctx: &Context, interaction: &Interaction, report: &PanicReport) {
+        let Some(source) = interaction_source(interaction) else {
+            return;
+        };
+        let reference = record_panic(&ctx.http, &self.database, report, &source).await;
+        let embed = user_error_embed(&reference);
+
+        // The handler may or may not have acknowledged the interaction before panicking
+        let answered = match interaction {
+            Interaction::ApplicationCommand(command) => {
+                command
+                    .create_interaction_response(&ctx.http, |response| {
+                        response
+                            .kind(serenity::model::application::interaction::InteractionResponseType::ChannelMessageWithSource)
+                            .interaction_response_data(|message| message.set_embed(embed.clone()).ephemeral(true))
+                    })
+                    .await
+                    .is_ok()
+                    || command
+                        .edit_original_interaction_response(&ctx.http, |response| response.set_embeds(vec![embed.clone()]))
+                        .await
+                        .is_ok()
+            }
+            Interaction::MessageComponent(component) => {
+                component
+                    .create_interaction_response(&ctx.http, |response| {
+                        response
+                            .kind(serenity::model::application::interaction::InteractionResponseType::ChannelMessageWithSource)
+                            .interaction_response_data(|message| message.set_embed(embed.clone()).ephemeral(true))
+                    })
+                    .await
+                    .is_ok()
+                    || component
+                        .create_followup_message(&ctx.http, |message| message.set_embed(embed.clone()).ephemeral(true))
+                        .await
+                        .is_ok()
+            }
+            Interaction::ModalSubmit(modal) => {
+                modal
+                    .create_interaction_response(&ctx.http, |response| {
+                        response
+                            .kind(serenity::model::application::interaction::InteractionResponseType::ChannelMessageWithSource)
+                            .interaction_response_data(|message| message.set_embed(embed.clone()).ephemeral(true))
+                    })
+                    .await
+                    .is_ok()
+                    || modal
+                        .edit_original_interaction_response(&ctx.http, |response| response.set_embeds(vec![embed.clone()]))
+                        .await
+                        .is_ok()
+            }
+            // Autocomplete has nowhere to show an error
+            _ => true,
+        };
+        if !answered {
+            warn!("Could not send the error embed for panic {reference}");
+        }
+    }
+}
+
+/// Who triggered an interaction and where, None for pings
+fn interaction_source(interaction: &Interaction) -> Option<PanicSource> {
+    let source = match interaction {
+        Interaction::ApplicationCommand(command) => PanicSource {
+            kind: "command",
+            name: command.data.name.clone(),
+            user_id: command.user.id.to_string(),
+            channel_id: command.channel_id.to_string(),
+            guild_id: command.guild_id.map(|id| id.to_string()),
+        },
+        Interaction::MessageComponent(component) => PanicSource {
+            kind: "component",
+            name: component.data.custom_id.clone(),
+            user_id: component.user.id.to_string(),
+            channel_id: component.channel_id.to_string(),
+            guild_id: component.guild_id.map(|id| id.to_string()),
+        },
+        Interaction::ModalSubmit(modal) => PanicSource {
+            kind: "modal",
+            name: modal.data.custom_id.clone(),
+            user_id: modal.user.id.to_string(),
+            channel_id: modal.channel_id.to_string(),
+            guild_id: modal.guild_id.map(|id| id.to_string()),
+        },
+        Interaction::Autocomplete(autocomplete) => PanicSource {
+            kind: "autocomplete",
+            name: autocomplete.data.name.clone(),
+            user_id: autocomplete.user.id.to_string(),
+            channel_id: autocomplete.channel_id.to_string(),
+            guild_id: autocomplete.guild_id.map(|id| id.to_string()),
+        },
+        Interaction::Ping(_) => return None,
+    };
+    Some(source)
+}
+
+/// Span for everything done on behalf of one interaction, with the IDs log lines and exported traces are keyed by
+fn interaction_span(bot_id: Option<&str>, interaction: &Interaction) -> Span {
+    let source = interaction_source(interaction);
+    info_span!(
+        "interaction",
+        bot_id,
+        interaction_id = %interaction.id(),
+        kind = source.as_ref().map_or("ping", |s| s.kind),
+        name = source.as_ref().map(|s| s.name.as_str()),
+        guild_id = source.as_ref().and_then(|s| s.guild_id.as_deref()),
+        user_id = source.as_ref().map(|s| s.user_id.as_str()),
+        channel_id = source.as_ref().map(|s| s.channel_id.as_str()),
+        ok = field::Empty,
+    )
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn message(&self, ctx: Context, msg: Message) {
+        if msg.author.bot {
+            return;
+        }
+
+        let span = info_span!(
+            "message",
+            bot_id = self.bot_id(),
+            message_id = %msg.id,
+            guild_id = msg.guild_id.map(|id| id.to_string()),
+            user_id = %msg.author.id,
+            channel_id = %msg.channel_id,
+        );
+        async {
+            if let Err(e) = self.command_handler.handle_message(&ctx, &msg).await {
+                error!("Error handling message: {e}");
+                if let Err(why) = msg
+                    .channel_id
+                    .say(&ctx.http, e.user_message())
+                    .await
+                {
+                    error!("Failed to send error message: {why}");
+                }
+            }
+
+            for plugin in self.plugins.iter() {
+                if let Err(e) = plugin.on_message(&ctx, &msg).await {
+                    warn!("Plugin {} failed to handle message: {e}", plugin.name());
+                }
+            }
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn reaction_add(&self, _ctx: Context, reaction: Reaction) {
+        if let Err(e) = self.command_handler.handle_reaction_add(&reaction).await {
+            warn!("Failed to record reaction: {e}");
+        }
+    }
+
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        let _ = self.bot_id.set(ready.user.id.to_string());
+        info!("🎉 {} is connected and ready!", ready.user.name);
+        info!("📡 Connected to {} guilds", ready.guilds.len());
+        info!("🔗 Gateway session ID: {:?}", ready.session_id);
+        info!("🤖 Bot ID: {}", ready.user.id);
+        info!("🌐 Gateway version: {}", ready.version);
+
+        // Log shard information
+        if let Some(shard) = ready.shard {
+            info!("⚡ Shard: {}/{}", shard[0] + 1, shard[1]);
+        }
+
+        self.register_commands(&ctx).await;
+
+        // Send startup notification if enabled
+        self.startup_notifier.send_if_enabled(&ctx.http, &ready).await;
+
+        for plugin in self.plugins.iter() {
+            if let Err(e) = plugin.on_ready(&ctx, &ready).await {
+                warn!("Plugin {} failed to start: {e}", plugin.name());
+            }
+        }
+    }
+
+    async fn resume(&self, ctx: Context, _: ResumedEvent) {
+        info!("🔁 Shard {} resumed its gateway session", ctx.shard_id);
+    }
+
+    async fn shard_stage_update(&self, _ctx: Context, event: ShardStageUpdateEvent) {
+        let shard = event.shard_id.0;
+        match (event.old, event.new) {
+            (ConnectionStage::Connected, stage) => {
+                warn!("⚠️ Shard {shard} lost its gateway connection ({stage}), reconnecting");
+                self.command_handler.metrics().record_shard_reconnect(shard);
+            }
+            (_, ConnectionStage::Connected) => info!("⚡ Shard {shard} connected"),
+            (old, new) => info!("Shard {shard}: {old} -> {new}"),
+        }
+    }
+
+    async fn guild_create(&self, _ctx: Context, guild: Guild) {
+        // Voice states arrive with the guild (GUILD_VOICE_STATES intent)
+        let states = guild.voice_states.values().map(|state| (state.user_id.0, state.channel_id.map(|c| c.0)));
+        self.command_handler.voice_roster().seed(guild.id.0, states);
+        if let Err(e) = self
+            .database
+            .upsert_known_guild(&guild.id.to_string(), &guild.name, guild.member_count)
+            .await
+        {
+            warn!("Failed to record guild {}: {}", guild.id, e);
+        }
+    }
+
+    async fn guild_delete(&self, _ctx: Context, incomplete: UnavailableGuild) {
+        // Unavailable guilds are outages, not removals
+        if incomplete.unavailable {
+            return;
+        }
+        self.command_handler.voice_roster().forget_guild(incomplete.id.0);
+        if let Err(e) = self.database.remove_known_guild(&incomplete.id.to_string()).await {
+            warn!("Failed to forget guild {}: {}", incomplete.id, e);
+        }
+    }
+
+    async fn thread_delete(&self, _ctx: Context, thread: PartialGuildChannel) {
+        if let Err(e) = self.database.delete_conversation_thread(&thread.id.to_string()).await {
+            warn!("Failed to forget thread {}: {}", thread.id, e);
+        }
+    }
+
+    async fn voice_state_update(&self, _ctx: Context, state: VoiceState) {
+        // Other bots in a study channel aren't studying
+        if state.member.as_ref().is_some_and(|m| m.user.bot) {
+            return;
+        }
+        if let Some(guild_id) = state.guild_id {
+            self.command_handler.voice_roster().update(guild_id.0, state.user_id.0, state.channel_id.map(|c| c.0));
+        }
+    }
+
+    async fn guild_member_addition(&self, ctx: Context, new_member: Member) {
+        // Only delivered when the GUILD_MEMBERS intent is enabled
+        if let Err(e) = self.command_handler.handle_member_join(&ctx, &new_member).await {
+            warn!("Failed to handle new member {} in guild {}: {}", new_member.user.id, new_member.guild_id, e);
+        }
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        // Run in its own task so a panic is caught here instead of leaving the interaction hanging
+        let handler = self.clone();
+        let (task_ctx, task_interaction) = (ctx.clone(), interaction.clone());
+        let span = interaction_span(self.bot_id(), &interaction);
+        let outcome =
+            tokio::spawn(async move { handler.dispatch_interaction(task_ctx, task_interaction).await }.instrument(span)).await;
+        if let Err(e) = outcome {
+            if e.is_panic() {
+                let report = PanicReport::from_payload(e.into_panic().as_ref());
+                self.answer_after_panic(&ctx, &interaction, &report).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! # Bot Module
+//!
+//! Public API for running the bot from other Rust programs: build a [`Bot`]
+//! with [`BotBuilder`], extend it with [`Plugin`]s, then run and shut it down.
+//! The `bot` binary is a thin wrapper around the same API.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Fleet of bots from `config.yaml`, reloaded when the file changes
+//! - 1.0.0: Initial release with the builder, run/shutdown handle and plugins
+
+pub mod builder;
+pub mod fleet;
+mod handler;
+pub mod plugin;
+
+pub use builder::{Bot, BotBuilder};
+pub use fleet::{config_watch_loop, Fleet};
+pub use plugin::Plugin;
@@ -0,0 +1,81 @@
+//! # Plugins
+//!
+//! Extension point for programs that embed the bot. A plugin sees gateway
+//! events after the built-in handlers and can add its own slash commands,
+//! which are registered alongside the bot's and routed to it.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with ready, message and slash command hooks
+
+use crate::core::Result;
+use serenity::async_trait;
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::channel::Message;
+use serenity::model::gateway::Ready;
+use serenity::prelude::Context;
+
+#[async_trait]
+pub trait Plugin: Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &str;
+
+    /// Slash commands to register; their interactions are sent to [`Plugin::on_command`]
+    fn commands(&self) -> Vec<CreateApplicationCommand> {
+        Vec::new()
+    }
+
+    /// Called on every gateway (re)connection
+    async fn on_ready(&self, _ctx: &Context, _ready: &Ready) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called for each message from a human, after the bot has handled it
+    async fn on_message(&self, _ctx: &Context, _msg: &Message) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called for invocations of the commands from [`Plugin::commands`]. Errors are
+    /// shown to the user like those of built-in commands.
+    async fn on_command(&self, _ctx: &Context, _command: &ApplicationCommandInteraction) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Name of each command a plugin registers, for routing interactions
+pub(crate) fn command_names(plugin: &dyn Plugin) -> Vec<String> {
+    plugin
+        .commands()
+        .iter()
+        .filter_map(|command| command.0.get("name").and_then(|name| name.as_str()).map(str::to_string))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Echo;
+
+    #[async_trait]
+    impl Plugin for Echo {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn commands(&self) -> Vec<CreateApplicationCommand> {
+            let mut command = CreateApplicationCommand::default();
+            command.name("echo").description("Repeat a message");
+            vec![command]
+        }
+    }
+
+    #[test]
+    fn test_command_names() {
+        assert_eq!(command_names(&Echo), vec!["echo".to_string()]);
+    }
+}
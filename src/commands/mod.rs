@@ -17,6 +17,6 @@ pub use crate::command_handler::CommandHandler;
 
 // Re-export commonly used items from submodules
 pub use slash::{
-    create_context_menu_commands, create_slash_commands, get_channel_option, get_integer_option,
+    create_context_menu_commands, create_slash_commands, get_channel_option, get_integer_option, GUILD_SETTING_KEYS,
     get_role_option, get_string_option, register_global_commands, register_guild_commands,
 };
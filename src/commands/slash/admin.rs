@@ -1,6 +1,6 @@
-//! Admin slash commands: /introspect, /settings, /set_channel_verbosity, /set_guild_setting, /admin_role, /features, /toggle, /sysinfo, /usage
+//! Admin slash commands: /introspect, /settings, /set_channel_verbosity, /set_channel_creativity, /set_guild_setting, /admin_role, /features, /toggle, /sysinfo, /usage, /costs, /ops, /debug_last, /activity, /import_history, /community_insights, /auto_slowmode, /bridge, /matrix, /archive_channel, /conflict
 
-use serenity::builder::CreateApplicationCommand;
+use serenity::builder::{CreateApplicationCommand, CreateApplicationCommandOption};
 use serenity::model::application::command::CommandOptionType;
 use serenity::model::permissions::Permissions;
 
@@ -9,13 +9,27 @@ pub fn create_commands() -> Vec<CreateApplicationCommand> {
     vec![
         create_introspect_command(),
         create_set_channel_verbosity_command(),
+        create_set_channel_creativity_command(),
         create_set_guild_setting_command(),
         create_settings_command(),
         create_admin_role_command(),
         create_features_command(),
         create_toggle_command(),
         create_sysinfo_command(),
+        create_debug_last_command(),
+        create_activity_command(),
+        create_import_history_command(),
+        create_community_insights_command(),
+        create_auto_slowmode_command(),
         create_usage_command(),
+        create_costs_command(),
+        create_ops_command(),
+        create_bridge_command(),
+        create_matrix_command(),
+        create_webhook_command(),
+        create_calendar_command(),
+        create_archive_channel_command(),
+        create_conflict_command(),
     ]
 }
 
@@ -67,6 +81,95 @@ fn create_set_channel_verbosity_command() -> CreateApplicationCommand {
         .to_owned()
 }
 
+/// Creates the set_channel_creativity command (admin)
+fn create_set_channel_creativity_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("set_channel_creativity")
+        .description("Set the default response creativity for a channel (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("level")
+                .description("The creativity level")
+                .kind(CommandOptionType::String)
+                .required(true)
+                .add_string_choice("low", "low")
+                .add_string_choice("normal", "normal")
+                .add_string_choice("high", "high")
+        })
+        .create_option(|option| {
+            option
+                .name("channel")
+                .description("Target channel (defaults to current channel)")
+                .kind(CommandOptionType::Channel)
+                .required(false)
+        })
+        .to_owned()
+}
+
+/// Settings offered by `/set_guild_setting` autocomplete, in display order
+pub const GUILD_SETTING_KEYS: &[&str] = &[
+    // High priority settings
+    "default_verbosity",
+    "default_persona",
+    "conflict_mediation",
+    "conflict_sensitivity",
+    "mediation_cooldown",
+    "conflict_mod_alerts",
+    "conflict_alert_threshold",
+    "conflict_alert_channel",
+    "conflict_escalation_timeout",
+    // Medium priority settings
+    "max_context_messages",
+    "audio_transcription",
+    "audio_transcription_mode",
+    "audio_transcription_output",
+    "mention_responses",
+    "cite_sources",
+    "follow_up_suggestions",
+    "auto_verbosity",
+    "response_cache",
+    "shared_bot_memory",
+    "typing_indicator",
+    "max_response_delay_ms",
+    "persona_webhooks",
+    "long_term_memory",
+    "vision",
+    "thread_conversations",
+    "cross_channel_summaries",
+    "support_channels",
+    "help_digest_channel",
+    "help_digest_hours",
+    "help_digest_drafts",
+    "reminders_channel",
+    "attachment_scan_channels",
+    "attachment_scan_sensitivity",
+    "content_moderation",
+    "content_moderation_notify",
+    "mod_log_channel",
+    "appeal_review_channel",
+    "archive_channel",
+    "lockdown_categories",
+    "join_screening",
+    "join_screening_threshold",
+    "verification_channel",
+    "unverified_role",
+    "verification_gate",
+    "member_role",
+    "verification_timeout_minutes",
+    "analytics_sheet_id",
+    "knowledge_base_export",
+    "issue_linking",
+    "custom_command_prefix",
+    "monthly_budget_usd",
+    "per_user_daily_budget_usd",
+    "budget_fallback_model",
+    // Global bot settings (stored in bot_settings table)
+    "startup_notification",
+    "startup_notify_owner_id",
+    "startup_notify_channel_id",
+];
+
 /// Creates the set_guild_setting command (admin)
 fn create_set_guild_setting_command() -> CreateApplicationCommand {
     CreateApplicationCommand::default()
@@ -79,22 +182,8 @@ fn create_set_guild_setting_command() -> CreateApplicationCommand {
                 .description("The setting to change")
                 .kind(CommandOptionType::String)
                 .required(true)
-                // High priority settings
-                .add_string_choice("default_verbosity", "default_verbosity")
-                .add_string_choice("default_persona", "default_persona")
-                .add_string_choice("conflict_mediation", "conflict_mediation")
-                .add_string_choice("conflict_sensitivity", "conflict_sensitivity")
-                .add_string_choice("mediation_cooldown", "mediation_cooldown")
-                // Medium priority settings
-                .add_string_choice("max_context_messages", "max_context_messages")
-                .add_string_choice("audio_transcription", "audio_transcription")
-                .add_string_choice("audio_transcription_mode", "audio_transcription_mode")
-                .add_string_choice("audio_transcription_output", "audio_transcription_output")
-                .add_string_choice("mention_responses", "mention_responses")
-                // Global bot settings (stored in bot_settings table)
-                .add_string_choice("startup_notification", "startup_notification")
-                .add_string_choice("startup_notify_owner_id", "startup_notify_owner_id")
-                .add_string_choice("startup_notify_channel_id", "startup_notify_channel_id")
+                // Discord allows at most 25 static choices, so settings are offered by autocomplete
+                .set_autocomplete(true)
         })
         .create_option(|option| {
             option
@@ -154,11 +243,155 @@ fn create_toggle_command() -> CreateApplicationCommand {
                 .kind(CommandOptionType::String)
                 .required(true)
                 // Add choices for toggleable features
+                .add_string_choice("AI Chat", "chat")
                 .add_string_choice("Reminders", "reminders")
                 .add_string_choice("Conflict Detection", "conflict_detection")
                 .add_string_choice("Conflict Mediation", "conflict_mediation")
                 .add_string_choice("Image Generation", "image_generation")
                 .add_string_choice("Audio Transcription", "audio_transcription")
+                .add_string_choice("Emoji Statistics", "emoji_stats")
+                .add_string_choice("Community Insights", "community_insights")
+                .add_string_choice("Auto Slowmode", "auto_slowmode")
+                .add_string_choice("Attachment Scanning", "attachment_scanning")
+                .add_string_choice("Story Mode", "story_mode")
+                .add_string_choice("Focus Sessions", "focus_sessions")
+        })
+        .to_owned()
+}
+
+/// Creates the debug_last command (admin) - shows the last assembled AI prompt
+fn create_debug_last_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("debug_last")
+        .description("Show the exact prompt behind the last AI response in a channel (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("channel")
+                .description("Channel to inspect (defaults to current channel)")
+                .kind(CommandOptionType::Channel)
+                .required(false)
+        })
+        .to_owned()
+}
+
+/// Creates the activity command (admin) - hour x weekday message heatmap for a channel
+fn create_activity_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("activity")
+        .description("Show when a channel is busiest as an hour-by-weekday heatmap (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("channel")
+                .description("Channel to chart (defaults to current channel)")
+                .kind(CommandOptionType::Channel)
+                .required(false)
+        })
+        .to_owned()
+}
+
+/// Creates the import_history command (admin) - backfill stored history from a channel's Discord messages
+fn create_import_history_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("import_history")
+        .description("Import a channel's past messages so history-based features can use them (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("channel")
+                .description("Channel to import (defaults to current channel)")
+                .kind(CommandOptionType::Channel)
+                .required(false)
+        })
+        .create_option(|option| {
+            option
+                .name("days")
+                .description("How far back to import (defaults to 30 days)")
+                .kind(CommandOptionType::Integer)
+                .required(false)
+                .min_int_value(1)
+                .max_int_value(90)
+        })
+        .to_owned()
+}
+
+/// Creates the community_insights command (admin) - new-member retention per join cohort
+fn create_community_insights_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("community_insights")
+        .description("Show 7- and 30-day retention of new members per weekly join cohort (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .to_owned()
+}
+
+/// Creates the auto_slowmode command (admin) - raise slowmode automatically during message spikes
+fn create_auto_slowmode_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("auto_slowmode")
+        .description("Automatically raise slowmode when a channel's message rate spikes (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("enable")
+                .description("Monitor a channel and set spike thresholds")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("channel")
+                        .description("Channel to monitor (defaults to current channel)")
+                        .kind(CommandOptionType::Channel)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("messages")
+                        .description("Messages within the window that count as a spike (default 20)")
+                        .kind(CommandOptionType::Integer)
+                        .min_int_value(3)
+                        .max_int_value(500)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("window")
+                        .description("Window in seconds (default 30)")
+                        .kind(CommandOptionType::Integer)
+                        .min_int_value(5)
+                        .max_int_value(600)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("slowmode")
+                        .description("Slowmode in seconds during a spike (default 10)")
+                        .kind(CommandOptionType::Integer)
+                        .min_int_value(1)
+                        .max_int_value(21600)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("duration")
+                        .description("Minutes before slowmode is reverted (default 10)")
+                        .kind(CommandOptionType::Integer)
+                        .min_int_value(1)
+                        .max_int_value(1440)
+                        .required(false)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("disable")
+                .description("Stop monitoring a channel")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("channel")
+                        .description("Channel to stop monitoring (defaults to current channel)")
+                        .kind(CommandOptionType::Channel)
+                        .required(false)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("status")
+                .description("List monitored channels and their thresholds")
+                .kind(CommandOptionType::SubCommand)
         })
         .to_owned()
 }
@@ -179,25 +412,420 @@ fn create_sysinfo_command() -> CreateApplicationCommand {
                 .add_string_choice("History (24h)", "history_24h")
                 .add_string_choice("History (7d)", "history_7d")
         })
+        .create_option(|option| {
+            option
+                .name("bot")
+                .description("Which bot's metrics to show (defaults to this bot)")
+                .kind(CommandOptionType::String)
+                .required(false)
+                .set_autocomplete(true)
+        })
         .to_owned()
 }
 
-/// Creates the usage command - displays OpenAI API usage and cost metrics
+/// Creates the usage command - displays the caller's own OpenAI API usage and cost
 fn create_usage_command() -> CreateApplicationCommand {
     CreateApplicationCommand::default()
         .name("usage")
-        .description("View OpenAI API usage and cost metrics")
+        .description("View your own OpenAI API usage and estimated cost")
         .create_option(|option| {
             option
-                .name("scope")
-                .description("What usage to display")
+                .name("range")
+                .description("Time range (defaults to today)")
                 .kind(CommandOptionType::String)
                 .required(false)
-                .add_string_choice("My Usage (Today)", "personal_today")
-                .add_string_choice("My Usage (7 days)", "personal_7d")
-                .add_string_choice("Server Usage (Today) - Admin", "server_today")
-                .add_string_choice("Server Usage (7 days) - Admin", "server_7d")
-                .add_string_choice("Top Users (7 days) - Admin", "top_users")
+                .add_string_choice("Today", "today")
+                .add_string_choice("Last 7 days", "7d")
+                .add_string_choice("Last 30 days", "30d")
+        })
+        .to_owned()
+}
+
+/// Creates the costs command (admin) - invoice-style AI cost reports
+fn create_costs_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("costs")
+        .description("View AI costs attributed to bot features (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("server")
+                .description("This server's OpenAI spend per service with the top spenders")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("range")
+                        .description("Time range (defaults to the last 7 days)")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                        .add_string_choice("Today", "today")
+                        .add_string_choice("Last 7 days", "7d")
+                        .add_string_choice("Last 30 days", "30d")
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("breakdown")
+                .description("Invoice-style cost breakdown per feature for this server")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("period")
+                        .description("Billing period (defaults to this month)")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                        .add_string_choice("This month", "this_month")
+                        .add_string_choice("Last month", "last_month")
+                })
+        })
+        .to_owned()
+}
+
+/// Creates the ops command (bot owner) - multi-guild operator overview and supervised task states
+fn create_ops_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("ops")
+        .description("Operator tools for the bot owner")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("overview")
+                .description("List every guild with members, 30-day cost, commands, errors and last activity")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("sort")
+                        .description("Sort column (defaults to cost)")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                        .add_string_choice("30-day cost", "cost")
+                        .add_string_choice("Command volume", "commands")
+                        .add_string_choice("Errors", "errors")
+                        .add_string_choice("Members", "members")
+                        .add_string_choice("Last activity", "activity")
+                })
+                .create_sub_option(|sub| {
+                    sub.name("page")
+                        .description("Page to start on")
+                        .kind(CommandOptionType::Integer)
+                        .required(false)
+                        .min_int_value(1)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("bots")
+                .description("Show the gateway and background tasks with their state, restarts and last error")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .to_owned()
+}
+
+/// Creates the bridge command (admin) - relay messages between a Discord and a Slack channel
+fn create_bridge_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("bridge")
+        .description("Bridge Discord channels with Slack channels (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("link")
+                .description("Relay messages both ways between a channel and a Slack channel")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("slack_channel")
+                        .description("Slack channel ID (e.g. C0123ABCD) or channel link")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("channel")
+                        .description("Discord channel (defaults to current channel)")
+                        .kind(CommandOptionType::Channel)
+                        .required(false)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("unlink")
+                .description("Stop relaying a channel to Slack")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("channel")
+                        .description("Discord channel (defaults to current channel)")
+                        .kind(CommandOptionType::Channel)
+                        .required(false)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("list")
+                .description("Show this server's Slack bridges")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .to_owned()
+}
+
+/// Creates the webhook command (admin) - let external systems post embeds into a channel
+fn create_webhook_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("webhook")
+        .description("Let external systems (CI, monitoring) post into channels (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("create")
+                .description("Create a webhook source and get its URL path and token")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("name")
+                        .description("Source name, e.g. github-ci (letters, digits, - and _)")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("channel")
+                        .description("Channel to post into (defaults to current channel)")
+                        .kind(CommandOptionType::Channel)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("persona")
+                        .description("Persona the posts are attributed to (defaults to the server's default)")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                        .add_string_choice("muppet", "muppet")
+                        .add_string_choice("chef", "chef")
+                        .add_string_choice("obi", "obi")
+                        .add_string_choice("teacher", "teacher")
+                        .add_string_choice("analyst", "analyst")
+                })
+                .create_sub_option(|sub| {
+                    sub.name("template")
+                        .description("Description template with {{field}} placeholders, e.g. {{repo}} build {{status}}")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("rate_limit")
+                        .description("Maximum posts per minute (default 30)")
+                        .kind(CommandOptionType::Integer)
+                        .min_int_value(1)
+                        .max_int_value(120)
+                        .required(false)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("delete")
+                .description("Delete a webhook source; its token stops working")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("name")
+                        .description("Source name")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("list")
+                .description("Show this server's webhook sources")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .to_owned()
+}
+
+/// Creates the calendar command (admin) - announce iCal events in a channel
+fn create_calendar_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("calendar")
+        .description("Announce events from iCal/Google calendars (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("subscribe")
+                .description("Post event reminders and a Monday agenda from an iCal feed")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("url")
+                        .description("iCal feed URL (https:// or webcal://), e.g. Google Calendar's secret iCal address")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("channel")
+                        .description("Channel to post in (defaults to current channel)")
+                        .kind(CommandOptionType::Channel)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("filter")
+                        .description("Comma-separated keywords to include; prefix with - to exclude (e.g. raid,-cancelled)")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("reminders")
+                        .description("When to remind before events (default 24h,1h)")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("utc_offset")
+                        .description("UTC offset for the Monday agenda and zoneless times, e.g. +2 or -05:00 (default 0)")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("unsubscribe")
+                .description("Stop announcing a calendar")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("id")
+                        .description("Subscription ID from /calendar list")
+                        .kind(CommandOptionType::Integer)
+                        .required(true)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("list")
+                .description("Show this server's calendar subscriptions")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .to_owned()
+}
+
+/// Creates the matrix command (admin) - mirror a Discord channel into a Matrix room
+fn create_matrix_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("matrix")
+        .description("Mirror Discord channels into Matrix rooms (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .create_option(|option| {
+            option
+                .name("link")
+                .description("Relay messages both ways between a channel and a Matrix room")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("room")
+                        .description("Room ID (!abc:server), alias (#room:server) or matrix.to link")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("channel")
+                        .description("Discord channel (defaults to current channel)")
+                        .kind(CommandOptionType::Channel)
+                        .required(false)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("unlink")
+                .description("Stop mirroring a channel to Matrix")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("channel")
+                        .description("Discord channel (defaults to current channel)")
+                        .kind(CommandOptionType::Channel)
+                        .required(false)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("list")
+                .description("Show this server's Matrix rooms")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .to_owned()
+}
+
+/// Creates the archive_channel command (admin) - summarize, export and optionally delete a channel
+fn create_archive_channel_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("archive_channel")
+        .description("Post a summary and the full log of a channel to the archive channel (Admin)")
+        .default_member_permissions(Permissions::MANAGE_CHANNELS)
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("channel")
+                .description("Channel to archive (defaults to current channel)")
+                .kind(CommandOptionType::Channel)
+                .required(false)
+        })
+        .create_option(|option| {
+            option
+                .name("destination")
+                .description("Where to post the archive (defaults to the archive_channel setting)")
+                .kind(CommandOptionType::Channel)
+                .required(false)
+        })
+        .create_option(|option| {
+            option
+                .name("delete")
+                .description("Delete the channel once the archive is posted (default false)")
+                .kind(CommandOptionType::Boolean)
+                .required(false)
+        })
+        .to_owned()
+}
+
+/// Creates the conflict command (admin) - `/conflict exempt add|remove|list`
+fn create_conflict_command() -> CreateApplicationCommand {
+    fn target_options<'a>(sub: &'a mut CreateApplicationCommandOption, action: &str) -> &'a mut CreateApplicationCommandOption {
+        sub.create_sub_option(|opt| {
+            opt.name("user")
+                .description(format!("Member or bot to {action}"))
+                .kind(CommandOptionType::User)
+                .required(false)
+        })
+        .create_sub_option(|opt| {
+            opt.name("role")
+                .description(format!("Role to {action}"))
+                .kind(CommandOptionType::Role)
+                .required(false)
+        })
+        .create_sub_option(|opt| {
+            opt.name("channel")
+                .description("Only in this channel; on its own, the whole channel")
+                .kind(CommandOptionType::Channel)
+                .required(false)
+        })
+    }
+
+    CreateApplicationCommand::default()
+        .name("conflict")
+        .description("Manage conflict detection (Admin)")
+        .default_member_permissions(Permissions::MANAGE_GUILD)
+        .dm_permission(false)
+        .create_option(|group| {
+            group
+                .name("exempt")
+                .description("Leave users, roles or channels out of conflict detection")
+                .kind(CommandOptionType::SubCommandGroup)
+                .create_sub_option(|sub| {
+                    target_options(
+                        sub.name("add")
+                            .description("Exempt a user or role (everywhere or in one channel), or a whole channel")
+                            .kind(CommandOptionType::SubCommand),
+                        "exempt",
+                    )
+                })
+                .create_sub_option(|sub| {
+                    target_options(
+                        sub.name("remove")
+                            .description("Remove an exemption added with the same options")
+                            .kind(CommandOptionType::SubCommand),
+                        "stop exempting",
+                    )
+                })
+                .create_sub_option(|sub| {
+                    sub.name("list")
+                        .description("Show this server's exemptions")
+                        .kind(CommandOptionType::SubCommand)
+                })
         })
         .to_owned()
 }
@@ -1,6 +1,7 @@
-//! Chat/AI slash commands: /hey, /explain, /simple, /steps
+//! Chat/AI slash commands: /hey, /explain, /simple, /steps, /story
 
-use serenity::builder::CreateApplicationCommand;
+use crate::features::story::MAX_STORY_THEME_CHARS;
+use serenity::builder::{CreateApplicationCommand, CreateApplicationCommandOption};
 use serenity::model::application::command::CommandOptionType;
 
 /// Creates chat/AI commands
@@ -10,6 +11,7 @@ pub fn create_commands() -> Vec<CreateApplicationCommand> {
         create_explain_command(),
         create_simple_command(),
         create_steps_command(),
+        create_story_command(),
     ]
 }
 
@@ -25,6 +27,7 @@ fn create_hey_command() -> CreateApplicationCommand {
                 .kind(CommandOptionType::String)
                 .required(true)
         })
+        .create_option(create_creativity_option)
         .to_owned()
 }
 
@@ -40,6 +43,7 @@ fn create_explain_command() -> CreateApplicationCommand {
                 .kind(CommandOptionType::String)
                 .required(true)
         })
+        .create_option(create_creativity_option)
         .to_owned()
 }
 
@@ -55,6 +59,7 @@ fn create_simple_command() -> CreateApplicationCommand {
                 .kind(CommandOptionType::String)
                 .required(true)
         })
+        .create_option(create_creativity_option)
         .to_owned()
 }
 
@@ -70,5 +75,61 @@ fn create_steps_command() -> CreateApplicationCommand {
                 .kind(CommandOptionType::String)
                 .required(true)
         })
+        .create_option(create_creativity_option)
         .to_owned()
 }
+
+/// Creates the story command
+fn create_story_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("story")
+        .description("Play a collaborative story in a thread, narrated by a persona")
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("start")
+                .description("Open a thread with a new story")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("theme")
+                        .description("What the story is about, e.g. a heist on a floating city")
+                        .kind(CommandOptionType::String)
+                        .max_length(MAX_STORY_THEME_CHARS as u16)
+                        .required(true)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("persona")
+                        .description("Narrator (defaults to your persona)")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                        .set_autocomplete(true)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("recap")
+                .description("Show the story so far: chapters, characters and inventory")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .create_option(|option| {
+            option
+                .name("end")
+                .description("End the story in this thread with a final recap")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .to_owned()
+}
+
+/// Shared optional `creativity` option for chat commands
+fn create_creativity_option(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("creativity")
+        .description("How creative the reply should be (defaults to the channel setting)")
+        .kind(CommandOptionType::String)
+        .required(false)
+        .add_string_choice("low - factual and focused", "low")
+        .add_string_choice("normal", "normal")
+        .add_string_choice("high - loose and imaginative", "high")
+}
@@ -2,12 +2,16 @@
 
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandType;
+use serenity::model::Permissions;
 
 /// Creates context menu commands
 pub fn create_commands() -> Vec<CreateApplicationCommand> {
     vec![
         create_analyze_message_context_command(),
         create_explain_message_context_command(),
+        create_remind_message_context_command(),
+        create_bookmark_message_context_command(),
+        create_move_message_context_command(),
         create_analyze_user_context_command(),
     ]
 }
@@ -28,6 +32,31 @@ fn create_explain_message_context_command() -> CreateApplicationCommand {
         .to_owned()
 }
 
+/// Creates the remind me message context menu command
+fn create_remind_message_context_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("Remind Me")
+        .kind(CommandType::Message)
+        .to_owned()
+}
+
+/// Creates the bookmark message context menu command
+fn create_bookmark_message_context_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("Bookmark Message")
+        .kind(CommandType::Message)
+        .to_owned()
+}
+
+/// Creates the move message context menu command (moderators only)
+fn create_move_message_context_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("Move to…")
+        .kind(CommandType::Message)
+        .default_member_permissions(Permissions::MANAGE_MESSAGES)
+        .to_owned()
+}
+
 /// Creates the analyze user context menu command
 fn create_analyze_user_context_command() -> CreateApplicationCommand {
     CreateApplicationCommand::default()
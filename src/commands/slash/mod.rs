@@ -2,11 +2,12 @@
 //!
 //! Discord native slash commands with autocomplete and validation.
 //!
-//! - **Version**: 1.0.0
+//! - **Version**: 1.1.0
 //! - **Since**: 0.2.0
 //! - **Toggleable**: false
 //!
 //! ## Changelog
+//! - 1.1.0: Registration takes extra commands, such as those from plugins
 //! - 1.0.0: Reorganized from monolithic slash_commands.rs
 
 mod admin;
@@ -14,12 +15,15 @@ mod chat;
 mod context_menu;
 mod dm_stats;
 mod imagine;
+mod moderation;
 mod persona;
 mod recipe;
 mod remind;
 mod utility;
 
-use anyhow::Result;
+pub use admin::GUILD_SETTING_KEYS;
+
+use crate::core::Result;
 use log::info;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::Command;
@@ -55,6 +59,9 @@ pub fn create_slash_commands() -> Vec<CreateApplicationCommand> {
     // DM statistics commands
     commands.extend(dm_stats::create_commands());
 
+    // Moderation commands
+    commands.extend(moderation::create_commands());
+
     commands
 }
 
@@ -63,8 +70,8 @@ pub fn create_context_menu_commands() -> Vec<CreateApplicationCommand> {
     context_menu::create_commands()
 }
 
-/// Registers all slash commands globally
-pub async fn register_global_commands(ctx: &Context) -> Result<()> {
+/// Registers all slash commands globally, plus `extra` commands
+pub async fn register_global_commands(ctx: &Context, extra: Vec<CreateApplicationCommand>) -> Result<()> {
     let slash_commands = create_slash_commands();
     let context_commands = create_context_menu_commands();
 
@@ -72,7 +79,7 @@ pub async fn register_global_commands(ctx: &Context) -> Result<()> {
         for command in slash_commands {
             commands.add_application_command(command);
         }
-        for command in context_commands {
+        for command in context_commands.into_iter().chain(extra) {
             commands.add_application_command(command);
         }
         commands
@@ -83,8 +90,8 @@ pub async fn register_global_commands(ctx: &Context) -> Result<()> {
     Ok(())
 }
 
-/// Registers all slash commands for a specific guild (faster for testing)
-pub async fn register_guild_commands(ctx: &Context, guild_id: GuildId) -> Result<()> {
+/// Registers all slash commands, plus `extra` commands, for a specific guild (faster for testing)
+pub async fn register_guild_commands(ctx: &Context, guild_id: GuildId, extra: Vec<CreateApplicationCommand>) -> Result<()> {
     let slash_commands = create_slash_commands();
     let context_commands = create_context_menu_commands();
 
@@ -93,7 +100,7 @@ pub async fn register_guild_commands(ctx: &Context, guild_id: GuildId) -> Result
             for command in slash_commands {
                 commands.add_application_command(command);
             }
-            for command in context_commands {
+            for command in context_commands.into_iter().chain(extra) {
                 commands.add_application_command(command);
             }
             commands
@@ -136,6 +143,26 @@ pub fn get_role_option(options: &[CommandDataOption], name: &str) -> Option<u64>
         .and_then(|s| s.parse().ok())
 }
 
+/// Utility function to get user option from slash command
+pub fn get_user_option(options: &[CommandDataOption], name: &str) -> Option<u64> {
+    options
+        .iter()
+        .find(|opt| opt.name == name)
+        .and_then(|opt| opt.value.as_ref())
+        .and_then(|val| val.as_str())
+        .and_then(|s| s.parse().ok())
+}
+
+/// Utility function to get attachment option (the attachment ID) from slash command
+pub fn get_attachment_option(options: &[CommandDataOption], name: &str) -> Option<u64> {
+    options
+        .iter()
+        .find(|opt| opt.name == name)
+        .and_then(|opt| opt.value.as_ref())
+        .and_then(|val| val.as_str())
+        .and_then(|s| s.parse().ok())
+}
+
 /// Utility function to get integer option from slash command
 pub fn get_integer_option(options: &[CommandDataOption], name: &str) -> Option<i64> {
     options
@@ -145,6 +172,15 @@ pub fn get_integer_option(options: &[CommandDataOption], name: &str) -> Option<i
         .and_then(|val| val.as_i64())
 }
 
+/// Utility function to get boolean option from slash command
+pub fn get_bool_option(options: &[CommandDataOption], name: &str) -> Option<bool> {
+    options
+        .iter()
+        .find(|opt| opt.name == name)
+        .and_then(|opt| opt.value.as_ref())
+        .and_then(|val| val.as_bool())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,17 +207,35 @@ mod tests {
             "help",
             "personas",
             "set_persona",
+            "handoff",
+            "as",
+            "persona",
             "hey",
             "explain",
             "simple",
             "steps",
+            "story",
             "recipe",
             "imagine",
             "forget",
+            "memory",
+            "summary",
+            "summarize",
+            "issue",
+            "calc",
+            "run",
+            "explain_error",
+            "command",
+            "c",
+            "bookmarks",
+            "thread_settings",
             "remind",
             "reminders",
+            "quiet_hours",
+            "timezone",
             "introspect",
             "set_channel_verbosity",
+            "set_channel_creativity",
             "set_guild_setting",
             "settings",
             "admin_role",
@@ -189,10 +243,30 @@ mod tests {
             "status",
             "version",
             "uptime",
+            "emojistats",
             // New admin commands
             "features",
             "toggle",
             "sysinfo",
+            "debug_last",
+            "activity",
+            "community_insights",
+            "auto_slowmode",
+            // Moderation commands
+            "timeout",
+            "kick",
+            "ban",
+            "lockdown",
+            "bridge",
+            "conflict",
+            "pomodoro",
+            "focus_stats",
+            "matrix",
+            "webhook",
+            "calendar",
+            "archive_channel",
+            "costs",
+            "ops",
         ];
 
         for expected in expected_commands {
@@ -204,9 +278,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_option_choices_within_discord_limit() {
+        // Discord rejects the whole registration if any option has more than 25 choices
+        for command in create_slash_commands() {
+            let options = command.0.get("options").and_then(|o| o.as_array()).cloned().unwrap_or_default();
+            for option in options {
+                let choices = option.get("choices").and_then(|c| c.as_array()).map_or(0, |c| c.len());
+                assert!(choices <= 25, "Too many choices for {:?} option {:?}", command.0.get("name"), option.get("name"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_guild_setting_keys_unique() {
+        let mut keys = GUILD_SETTING_KEYS.to_vec();
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len(), GUILD_SETTING_KEYS.len());
+    }
+
     #[test]
     fn test_create_context_menu_commands() {
         let commands = create_context_menu_commands();
-        assert_eq!(commands.len(), 3, "Should have 3 context menu commands");
+        assert_eq!(commands.len(), 6, "Should have 6 context menu commands");
     }
 }
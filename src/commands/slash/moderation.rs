@@ -0,0 +1,108 @@
+//! Moderation slash commands: /timeout, /kick, /ban, /lockdown
+
+use serenity::builder::{CreateApplicationCommand, CreateApplicationCommandOption};
+use serenity::model::application::command::CommandOptionType;
+use serenity::model::permissions::Permissions;
+
+/// Creates moderation commands
+pub fn create_commands() -> Vec<CreateApplicationCommand> {
+    vec![
+        create_timeout_command(),
+        create_kick_command(),
+        create_ban_command(),
+        create_lockdown_command(),
+    ]
+}
+
+fn user_option<'a>(option: &'a mut CreateApplicationCommandOption, action: &str) -> &'a mut CreateApplicationCommandOption {
+    option
+        .name("user")
+        .description(format!("Member to {action}"))
+        .kind(CommandOptionType::User)
+        .required(true)
+}
+
+fn reason_option(option: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    option
+        .name("reason")
+        .description("Why (a template like spam/harassment, or your own text; {user}, {server}, {duration} allowed)")
+        .kind(CommandOptionType::String)
+        .required(true)
+        .set_autocomplete(true)
+}
+
+/// Creates the timeout command (moderators) - temporarily mute a member
+fn create_timeout_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("timeout")
+        .description("Time out a member with a reason; they are notified by DM and it's logged (Moderator)")
+        .default_member_permissions(Permissions::MODERATE_MEMBERS)
+        .create_option(|option| user_option(option, "time out"))
+        .create_option(|option| {
+            option
+                .name("duration")
+                .description("How long (e.g., 10m, 2h, 1d; max 28d)")
+                .kind(CommandOptionType::String)
+                .required(true)
+        })
+        .create_option(reason_option)
+        .to_owned()
+}
+
+/// Creates the kick command (moderators) - remove a member from the server
+fn create_kick_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("kick")
+        .description("Kick a member with a reason; they are notified by DM and it's logged (Moderator)")
+        .default_member_permissions(Permissions::KICK_MEMBERS)
+        .create_option(|option| user_option(option, "kick"))
+        .create_option(reason_option)
+        .to_owned()
+}
+
+/// Creates the ban command (moderators) - ban a member from the server
+fn create_ban_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("ban")
+        .description("Ban a member with a reason; they are notified by DM and it's logged (Moderator)")
+        .default_member_permissions(Permissions::BAN_MEMBERS)
+        .create_option(|option| user_option(option, "ban"))
+        .create_option(reason_option)
+        .create_option(|option| {
+            option
+                .name("delete_days")
+                .description("Delete the member's messages from the last N days (default 0)")
+                .kind(CommandOptionType::Integer)
+                .min_int_value(0)
+                .max_int_value(7)
+                .required(false)
+        })
+        .to_owned()
+}
+
+/// Creates the lockdown command (moderators) - stop @everyone sending in the lockdown categories
+fn create_lockdown_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("lockdown")
+        .description("Emergency lockdown of the channels in lockdown_categories (Moderator)")
+        .default_member_permissions(Permissions::MANAGE_CHANNELS)
+        .create_option(|option| {
+            option
+                .name("start")
+                .description("Remove send permissions for @everyone in the lockdown categories")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("reason")
+                        .description("Reason shown in the lockdown notice")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("end")
+                .description("Restore the permissions from before the lockdown")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .to_owned()
+}
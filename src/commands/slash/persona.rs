@@ -1,11 +1,17 @@
-//! Persona slash commands: /personas, /set_persona
+//! Persona slash commands: /personas, /set_persona, /handoff, /as, /persona
 
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
 
 /// Creates persona commands
 pub fn create_commands() -> Vec<CreateApplicationCommand> {
-    vec![create_personas_command(), create_set_persona_command()]
+    vec![
+        create_personas_command(),
+        create_set_persona_command(),
+        create_handoff_command(),
+        create_as_command(),
+        create_persona_command(),
+    ]
 }
 
 /// Creates the personas command
@@ -27,11 +33,133 @@ fn create_set_persona_command() -> CreateApplicationCommand {
                 .description("The persona to set as your default")
                 .kind(CommandOptionType::String)
                 .required(true)
-                .add_string_choice("muppet", "muppet")
-                .add_string_choice("chef", "chef")
-                .add_string_choice("obi", "obi")
-                .add_string_choice("teacher", "teacher")
-                .add_string_choice("analyst", "analyst")
+                .set_autocomplete(true)
+        })
+        .to_owned()
+}
+
+/// Creates the handoff command
+fn create_handoff_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("handoff")
+        .description("Hand the current conversation to another persona with a summary")
+        .create_option(|option| {
+            option
+                .name("persona")
+                .description("The persona to take over the conversation")
+                .kind(CommandOptionType::String)
+                .required(true)
+                .set_autocomplete(true)
+        })
+        .to_owned()
+}
+
+/// Creates the as command for a one-off question to another persona
+fn create_as_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("as")
+        .description("Ask one question as another persona, without changing your default")
+        .create_option(|option| {
+            option
+                .name("persona")
+                .description("The persona to answer")
+                .kind(CommandOptionType::String)
+                .required(true)
+                .set_autocomplete(true)
+        })
+        .create_option(|option| {
+            option
+                .name("prompt")
+                .description("Your question or message")
+                .kind(CommandOptionType::String)
+                .required(true)
+        })
+        .to_owned()
+}
+
+/// Adds the autocompleted `persona` option naming one of the guild's custom personas
+fn custom_persona_option(
+    sub: &mut serenity::builder::CreateApplicationCommandOption,
+) -> &mut serenity::builder::CreateApplicationCommandOption {
+    sub.name("persona")
+        .description("One of this server's custom personas")
+        .kind(CommandOptionType::String)
+        .required(true)
+        .set_autocomplete(true)
+}
+
+/// Creates the persona command for managing a guild's custom personas
+fn create_persona_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("persona")
+        .description("Create and manage this server's custom personas, or blend two personas")
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("create")
+                .description("Define a new persona (Admin only)")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("key")
+                        .description("Short name used to select it, e.g. pirate")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                        .min_length(2)
+                        .max_length(24)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("edit")
+                .description("Change a custom persona's prompt, temperature or emoji (Admin only)")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(custom_persona_option)
+        })
+        .create_option(|option| {
+            option
+                .name("delete")
+                .description("Delete a custom persona (Admin only)")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(custom_persona_option)
+        })
+        .create_option(|option| {
+            option
+                .name("blend")
+                .description("Experiment: answer one question in a voice mixing two personas")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("first")
+                        .description("First persona")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                        .set_autocomplete(true)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("second")
+                        .description("Second persona")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                        .set_autocomplete(true)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("prompt")
+                        .description("Your question or message")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("share")
+                .description("Make a custom persona available in every server, or private again (Admin only)")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(custom_persona_option)
+                .create_sub_option(|sub| {
+                    sub.name("shared")
+                        .description("Whether other servers can use it")
+                        .kind(CommandOptionType::Boolean)
+                        .required(true)
+                })
         })
         .to_owned()
 }
@@ -1,11 +1,16 @@
-//! Reminder slash commands: /remind, /reminders
+//! Reminder slash commands: /remind, /reminders, /quiet_hours, /timezone
 
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
 
 /// Creates reminder commands
 pub fn create_commands() -> Vec<CreateApplicationCommand> {
-    vec![create_remind_command(), create_reminders_command()]
+    vec![
+        create_remind_command(),
+        create_reminders_command(),
+        create_quiet_hours_command(),
+        create_timezone_command(),
+    ]
 }
 
 /// Creates the remind command
@@ -16,7 +21,7 @@ fn create_remind_command() -> CreateApplicationCommand {
         .create_option(|option| {
             option
                 .name("time")
-                .description("When to remind you (e.g., 30m, 2h, 1d, 1h30m)")
+                .description("When to remind you (e.g., 30m, 1h30m, 9am, tomorrow 14:30, friday 5pm)")
                 .kind(CommandOptionType::String)
                 .required(true)
         })
@@ -27,6 +32,20 @@ fn create_remind_command() -> CreateApplicationCommand {
                 .kind(CommandOptionType::String)
                 .required(true)
         })
+        .create_option(|option| {
+            option
+                .name("message_link")
+                .description("Link to a message to attach to the reminder (Copy Message Link)")
+                .kind(CommandOptionType::String)
+                .required(false)
+        })
+        .create_option(|option| {
+            option
+                .name("urgent")
+                .description("Deliver even during your quiet hours")
+                .kind(CommandOptionType::Boolean)
+                .required(false)
+        })
         .to_owned()
 }
 
@@ -53,3 +72,61 @@ fn create_reminders_command() -> CreateApplicationCommand {
         })
         .to_owned()
 }
+
+/// Creates the quiet_hours command
+fn create_quiet_hours_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("quiet_hours")
+        .description("Set do-not-disturb hours - reminders due then are held until they end")
+        .create_option(|option| {
+            option
+                .name("action")
+                .description("What to do with your quiet hours")
+                .kind(CommandOptionType::String)
+                .required(false)
+                .add_string_choice("show", "show")
+                .add_string_choice("set", "set")
+                .add_string_choice("clear", "clear")
+        })
+        .create_option(|option| {
+            option
+                .name("start")
+                .description("Start time in 24h time in your /timezone (e.g., 22:00)")
+                .kind(CommandOptionType::String)
+                .required(false)
+        })
+        .create_option(|option| {
+            option
+                .name("end")
+                .description("End time in 24h time in your /timezone (e.g., 08:00)")
+                .kind(CommandOptionType::String)
+                .required(false)
+        })
+        .to_owned()
+}
+
+/// Creates the timezone command
+fn create_timezone_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("timezone")
+        .description("Set your time zone for reminder times and timestamps")
+        .create_option(|option| {
+            option
+                .name("action")
+                .description("What to do with your time zone")
+                .kind(CommandOptionType::String)
+                .required(false)
+                .add_string_choice("show", "show")
+                .add_string_choice("set", "set")
+                .add_string_choice("clear", "clear")
+        })
+        .create_option(|option| {
+            option
+                .name("zone")
+                .description("Time zone name (e.g., Europe/Berlin, America/New_York) or UTC offset like +2")
+                .kind(CommandOptionType::String)
+                .required(false)
+                .set_autocomplete(true)
+        })
+        .to_owned()
+}
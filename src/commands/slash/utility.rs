@@ -1,6 +1,13 @@
-//! Utility slash commands: /ping, /help, /forget, /status, /version, /uptime
+//! Utility slash commands: /ping, /help, /forget, /memory, /summary, /summarize, /status, /version, /uptime, /emojistats, /issue, /calc, /run, /explain_error, /command, /c, /bookmarks, /thread_settings, /pomodoro, /focus_stats
 
+use crate::features::code_runner::{LANGUAGES, MAX_CODE_LEN};
+use crate::features::conversation_threads::{THREAD_SETTING_NAMES, THREAD_VERBOSITY_VALUES};
+use crate::features::custom_commands::{MAX_COMMAND_NAME_LEN, MAX_TEMPLATE_LEN};
+use crate::features::error_explainer::{ERROR_PERSONAS, MAX_ERROR_PASTE_LEN};
+use crate::features::focus::{DEFAULT_BREAK_MINUTES, DEFAULT_FOCUS_MINUTES, DEFAULT_ROUNDS, FOCUS_STATS_DAYS, MAX_ROUNDS};
 use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+use serenity::model::channel::ChannelType;
 
 /// Creates utility commands
 pub fn create_commands() -> Vec<CreateApplicationCommand> {
@@ -8,9 +15,23 @@ pub fn create_commands() -> Vec<CreateApplicationCommand> {
         create_ping_command(),
         create_help_command(),
         create_forget_command(),
+        create_memory_command(),
+        create_thread_settings_command(),
+        create_summary_command(),
+        create_summarize_command(),
         create_status_command(),
         create_version_command(),
         create_uptime_command(),
+        create_emojistats_command(),
+        create_issue_command(),
+        create_calc_command(),
+        create_run_command(),
+        create_explain_error_command(),
+        create_command_command(),
+        create_c_command(),
+        create_bookmarks_command(),
+        create_pomodoro_command(),
+        create_focus_stats_command(),
     ]
 }
 
@@ -38,6 +59,130 @@ fn create_forget_command() -> CreateApplicationCommand {
         .to_owned()
 }
 
+/// Creates the summary command
+fn create_summary_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("summary")
+        .description("Show the summary the bot keeps of your older messages in this channel")
+        .to_owned()
+}
+
+/// Creates the summarize command
+fn create_summarize_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("summarize")
+        .description("Catch up on this channel with a summary of recent messages (only you see it)")
+        .create_option(|option| {
+            option
+                .name("count")
+                .description("How many recent messages to summarize (default 50)")
+                .kind(CommandOptionType::Integer)
+                .required(false)
+                .min_int_value(1)
+                .max_int_value(500)
+        })
+        .create_option(|option| {
+            option
+                .name("since")
+                .description("A message link from this channel, or a time window like 2h or 1d")
+                .kind(CommandOptionType::String)
+                .required(false)
+        })
+        .to_owned()
+}
+
+/// Creates the memory command
+fn create_memory_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("memory")
+        .description("Search or forget what the bot remembers from your earlier conversations")
+        .create_option(|option| {
+            option
+                .name("search")
+                .description("Find remembered conversations about a topic")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("query")
+                        .description("What to look for")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("forget")
+                .description("Forget one remembered conversation, or all of them")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("id")
+                        .description("Memory number from /memory search (leave empty to forget everything)")
+                        .kind(CommandOptionType::Integer)
+                        .required(false)
+                })
+        })
+        .to_owned()
+}
+
+/// Creates the thread_settings command
+fn create_thread_settings_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("thread_settings")
+        .description("Give this thread its own persona, verbosity or reply language")
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("view")
+                .description("Show this thread's settings")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .create_option(|option| {
+            option
+                .name("set")
+                .description("Override settings for this thread only")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("persona")
+                        .description("Persona that answers in this thread")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                        .set_autocomplete(true)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("verbosity")
+                        .description("How long replies in this thread are")
+                        .kind(CommandOptionType::String)
+                        .required(false);
+                    for level in THREAD_VERBOSITY_VALUES {
+                        sub.add_string_choice(level, level);
+                    }
+                    sub
+                })
+                .create_sub_option(|sub| {
+                    sub.name("language")
+                        .description("Language to reply in, e.g. French")
+                        .kind(CommandOptionType::String)
+                        .required(false)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("reset")
+                .description("Go back to the channel and server settings")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("setting")
+                        .description("Setting to reset (leave empty to reset all)")
+                        .kind(CommandOptionType::String)
+                        .required(false);
+                    for name in THREAD_SETTING_NAMES {
+                        sub.add_string_choice(name, name);
+                    }
+                    sub
+                })
+        })
+        .to_owned()
+}
+
 /// Creates the status command
 fn create_status_command() -> CreateApplicationCommand {
     CreateApplicationCommand::default()
@@ -61,3 +206,294 @@ fn create_uptime_command() -> CreateApplicationCommand {
         .description("Show how long the bot has been running")
         .to_owned()
 }
+
+/// Creates the emojistats command - the guild's most-used emoji and reactions
+fn create_emojistats_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("emojistats")
+        .description("Show this server's most-used emoji and reactions")
+        .create_option(|option| {
+            option
+                .name("period")
+                .description("Time period (defaults to 30 days)")
+                .kind(CommandOptionType::String)
+                .required(false)
+                .add_string_choice("Last 7 days", "7")
+                .add_string_choice("Last 30 days", "30")
+                .add_string_choice("Last 90 days", "90")
+        })
+        .to_owned()
+}
+
+/// Creates the issue command
+fn create_issue_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("issue")
+        .description("Look up a Jira or Linear issue")
+        .create_option(|option| {
+            option
+                .name("key")
+                .description("Issue key, e.g. ENG-123")
+                .kind(CommandOptionType::String)
+                .required(true)
+        })
+        .to_owned()
+}
+
+/// Creates the calc command
+fn create_calc_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("calc")
+        .description("Calculate a math expression exactly")
+        .create_option(|option| {
+            option
+                .name("expression")
+                .description("Expression, e.g. (12.5 * 8) / 3 or sqrt(2)^2")
+                .kind(CommandOptionType::String)
+                .required(true)
+                .max_length(500)
+        })
+        .to_owned()
+}
+
+/// Creates the run command
+fn create_run_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("run")
+        .description("Run a code snippet in a sandbox")
+        .create_option(|option| {
+            option
+                .name("language")
+                .description("Programming language")
+                .kind(CommandOptionType::String)
+                .required(true);
+            for language in LANGUAGES {
+                option.add_string_choice(language.name, language.id);
+            }
+            option
+        })
+        .create_option(|option| {
+            option
+                .name("code")
+                .description("One-line snippet; leave empty to paste multi-line code in a form")
+                .kind(CommandOptionType::String)
+                .required(false)
+                .max_length(MAX_CODE_LEN as u16)
+        })
+        .to_owned()
+}
+
+/// Creates the explain_error command
+fn create_explain_error_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("explain_error")
+        .description("Explain a stack trace or error log: likely causes and next steps")
+        .create_option(|option| {
+            option
+                .name("error")
+                .description("One-line error; leave empty (and attach nothing) to paste a full trace in a form")
+                .kind(CommandOptionType::String)
+                .required(false)
+                .max_length(MAX_ERROR_PASTE_LEN as u16)
+        })
+        .create_option(|option| {
+            option
+                .name("file")
+                .description("Log or trace file to explain")
+                .kind(CommandOptionType::Attachment)
+                .required(false)
+        })
+        .create_option(|option| {
+            option
+                .name("persona")
+                .description("Who explains it (defaults to the teacher)")
+                .kind(CommandOptionType::String)
+                .required(false);
+            for persona in ERROR_PERSONAS {
+                option.add_string_choice(*persona, *persona);
+            }
+            option
+        })
+        .to_owned()
+}
+
+/// Creates the command command - manage the server's custom commands
+fn create_command_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("command")
+        .description("Manage this server's custom commands")
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("add")
+                .description("Add or replace a custom command (Admin)")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("name")
+                        .description("Command name (letters, digits, - and _)")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                        .max_length(MAX_COMMAND_NAME_LEN as u16)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("response")
+                        .description("Reply text; may use {user}, {user_name}, {channel}, {server}, {args}, {arg1}, {arg2}...")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                        .max_length(MAX_TEMPLATE_LEN as u16)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("remove")
+                .description("Remove a custom command (Admin)")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("name")
+                        .description("Command name")
+                        .kind(CommandOptionType::String)
+                        .required(true)
+                        .set_autocomplete(true)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("list")
+                .description("List this server's custom commands")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .to_owned()
+}
+
+/// Creates the c command - run a custom command
+fn create_c_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("c")
+        .description("Run one of this server's custom commands")
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("name")
+                .description("Command name")
+                .kind(CommandOptionType::String)
+                .required(true)
+                .set_autocomplete(true)
+        })
+        .create_option(|option| {
+            option
+                .name("args")
+                .description("Arguments for {arg1}, {arg2}...; quote phrases with spaces")
+                .kind(CommandOptionType::String)
+                .required(false)
+        })
+        .to_owned()
+}
+
+/// Creates the bookmarks command - browse and delete saved messages
+fn create_bookmarks_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("bookmarks")
+        .description("Your bookmarked messages")
+        .create_option(|option| {
+            option
+                .name("list")
+                .description("Show your bookmarks with links to the messages")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("page")
+                        .description("Page to start on")
+                        .kind(CommandOptionType::Integer)
+                        .required(false)
+                        .min_int_value(1)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("delete")
+                .description("Delete one of your bookmarks")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("id")
+                        .description("Bookmark id, as shown in /bookmarks list")
+                        .kind(CommandOptionType::Integer)
+                        .required(true)
+                        .set_autocomplete(true)
+                })
+        })
+        .to_owned()
+}
+
+/// Creates the pomodoro command - `/pomodoro start|stop|status`
+fn create_pomodoro_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("pomodoro")
+        .description("Focus sessions with breaks, pings and an optional study voice channel")
+        .dm_permission(false)
+        .create_option(|option| {
+            option
+                .name("start")
+                .description("Start a focus session in this channel")
+                .kind(CommandOptionType::SubCommand)
+                .create_sub_option(|sub| {
+                    sub.name("focus")
+                        .description(format!("Minutes of focus per round (default {DEFAULT_FOCUS_MINUTES})"))
+                        .kind(CommandOptionType::Integer)
+                        .min_int_value(1)
+                        .max_int_value(180)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("break")
+                        .description(format!("Minutes of break between rounds (default {DEFAULT_BREAK_MINUTES})"))
+                        .kind(CommandOptionType::Integer)
+                        .min_int_value(1)
+                        .max_int_value(60)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("rounds")
+                        .description(format!("Focus rounds (default {DEFAULT_ROUNDS})"))
+                        .kind(CommandOptionType::Integer)
+                        .min_int_value(1)
+                        .max_int_value(MAX_ROUNDS as u64)
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("voice_channel")
+                        .description("Study voice channel whose members join the session")
+                        .kind(CommandOptionType::Channel)
+                        .channel_types(&[ChannelType::Voice, ChannelType::Stage])
+                        .required(false)
+                })
+                .create_sub_option(|sub| {
+                    sub.name("silence")
+                        .description("Silence the voice channel's participants while focusing (needs Mute/Deafen Members)")
+                        .kind(CommandOptionType::String)
+                        .add_string_choice("Mute", "mute")
+                        .add_string_choice("Mute and deafen", "deafen")
+                        .required(false)
+                })
+        })
+        .create_option(|option| {
+            option
+                .name("stop")
+                .description("Stop this channel's focus session")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .create_option(|option| {
+            option
+                .name("status")
+                .description("Show this channel's focus session")
+                .kind(CommandOptionType::SubCommand)
+        })
+        .to_owned()
+}
+
+/// Creates the focus_stats command
+fn create_focus_stats_command() -> CreateApplicationCommand {
+    CreateApplicationCommand::default()
+        .name("focus_stats")
+        .description(format!("Leaderboard of completed focus rounds over the last {FOCUS_STATS_DAYS} days"))
+        .dm_permission(false)
+        .to_owned()
+}
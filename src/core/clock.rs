@@ -0,0 +1,138 @@
+//! # Clock and ID Generation
+//!
+//! Sources of the current time and of fresh UUIDs, behind traits so the
+//! scheduler, the interaction tracker and the database can be driven by fakes
+//! in tests instead of the wall clock and the random number generator.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with system and fake clocks, random and sequential IDs
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Layout of SQLite's `CURRENT_TIMESTAMP`, used for every stored timestamp
+pub const SQL_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Layout of SQLite's `date('now')`, used for daily aggregate keys
+pub const SQL_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Source of the current time
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system's wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to
+#[derive(Debug)]
+pub struct FakeClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl FakeClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        FakeClock { now: Mutex::new(start) }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        *now += by;
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Source of fresh unique IDs
+pub trait IdGen: Send + Sync {
+    fn new_id(&self) -> Uuid;
+}
+
+/// Random (v4) UUIDs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomIds;
+
+impl IdGen for RandomIds {
+    fn new_id(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+}
+
+/// UUIDs 1, 2, 3... in order, for tests that assert on IDs
+#[derive(Debug, Default)]
+pub struct SequentialIds {
+    last: AtomicU64,
+}
+
+impl IdGen for SequentialIds {
+    fn new_id(&self) -> Uuid {
+        Uuid::from_u128(u128::from(self.last.fetch_add(1, Ordering::Relaxed) + 1))
+    }
+}
+
+/// The wall clock, as the shared handle components take
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// Random IDs, as the shared handle components take
+pub fn random_ids() -> Arc<dyn IdGen> {
+    Arc::new(RandomIds)
+}
+
+/// Format a time the way SQLite's `CURRENT_TIMESTAMP` does, so stored and bound timestamps compare as strings
+pub fn sql_timestamp(at: DateTime<Utc>) -> String {
+    at.format(SQL_TIMESTAMP_FORMAT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_fake_clock_moves_only_when_told() {
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let clock = FakeClock::new(start);
+        assert_eq!(clock.now(), start);
+        clock.advance(Duration::minutes(90));
+        assert_eq!(clock.now(), start + Duration::minutes(90));
+        clock.set(start);
+        assert_eq!(clock.now(), start);
+    }
+
+    #[test]
+    fn test_sequential_ids() {
+        let ids = SequentialIds::default();
+        assert_eq!(ids.new_id(), Uuid::from_u128(1));
+        assert_eq!(ids.new_id(), Uuid::from_u128(2));
+        assert_ne!(RandomIds.new_id(), RandomIds.new_id());
+    }
+
+    #[test]
+    fn test_sql_timestamp_matches_current_timestamp_layout() {
+        let at = Utc.with_ymd_and_hms(2024, 5, 1, 8, 5, 3).unwrap();
+        assert_eq!(sql_timestamp(at), "2024-05-01 08:05:03");
+    }
+}
@@ -1,44 +1,676 @@
-use anyhow::Result;
+use crate::core::{BotError, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub discord_token: String,
+    /// Required for the OpenAI chat backend; also used for images, Whisper and image moderation
     pub openai_api_key: String,
+    /// Chat backend settings
+    pub llm: LlmConfig,
+    /// SQLite file path, or a `postgres://` URL
     pub database_path: String,
+    /// SQLite connections kept open for concurrent queries
+    pub database_pool_size: usize,
+    /// Guilds whose guild-resident data is kept in a SQLite file of their own
+    pub guild_databases: Vec<String>,
+    /// Folder holding the per-guild files
+    pub guild_database_dir: String,
     pub log_level: String,
     pub discord_guild_id: Option<String>,
+    /// Chat model, same as `llm.model`
     pub openai_model: String,
     pub conflict_mediation_enabled: bool,
     pub conflict_sensitivity: String,
+    /// Per-sensitivity overrides of the conflict stage thresholds, e.g. `high=0.4:0.8`
+    pub conflict_thresholds: Option<String>,
     pub mediation_cooldown_minutes: u64,
+    /// Slash and context menu commands the bot answers (all when None)
+    pub command_allowlist: Option<Vec<String>>,
+    /// Bots in `config.yaml` that share conversation history with this one; set by the fleet, never from the environment
+    pub memory_partners: Vec<String>,
+    /// clamd address for attachment virus scanning (`host:port` or unix socket path)
+    pub clamav_address: Option<String>,
+    /// Request the privileged GUILD_MEMBERS intent (needed for join screening)
+    pub guild_members_intent: bool,
+    /// How many gateway shards to run
+    pub shards: ShardMode,
+    /// Slack app bot token (`xoxb-...`) for the Slack bridge
+    pub slack_bot_token: Option<String>,
+    /// Matrix homeserver base URL (e.g. `https://matrix.example.org`) for the Matrix bridge
+    pub matrix_homeserver_url: Option<String>,
+    /// Matrix access token, or an appservice `as_token` when puppeting
+    pub matrix_access_token: Option<String>,
+    /// Localpart prefix for Discord puppets (appservice tokens only)
+    pub matrix_puppet_prefix: Option<String>,
+    /// SMTP delivery of owner reports, when `SMTP_HOST` is set
+    pub smtp: Option<SmtpConfig>,
+    /// Google service account key file (JSON) for exporting analytics to Sheets
+    pub google_service_account_file: Option<String>,
+    /// Notion internal integration token for knowledge base export
+    pub notion_token: Option<String>,
+    /// Folder (optionally a git checkout) for markdown knowledge base export
+    pub knowledge_base_dir: Option<String>,
+    /// Push the knowledge base repository after each commit
+    pub knowledge_base_git_push: bool,
+    /// Address for the incoming webhook endpoint (e.g. `0.0.0.0:8787`)
+    pub webhook_listen_addr: Option<String>,
+    /// Address for the Discord interactions endpoint; when set with
+    /// `discord_public_key`, interactions arrive over HTTP instead of the gateway
+    pub interactions_listen_addr: Option<String>,
+    /// The application's public key (hex) from the Developer Portal, used to verify interaction requests
+    pub discord_public_key: Option<String>,
+    /// Jira Cloud site (e.g. `https://acme.atlassian.net`) for issue lookups
+    pub jira_base_url: Option<String>,
+    /// Atlassian account email that owns `jira_api_token`
+    pub jira_email: Option<String>,
+    pub jira_api_token: Option<String>,
+    /// Linear personal API key for issue lookups
+    pub linear_api_key: Option<String>,
+    /// Wolfram Alpha app ID for `/calc` and the AI's math tool fallback
+    pub wolfram_app_id: Option<String>,
+    /// `/run` sandbox backend: `piston` or `firejail`
+    pub code_runner: Option<String>,
+    /// Piston API base (e.g. `http://localhost:2000/api/v2`) for the `piston` backend
+    pub piston_url: Option<String>,
+    pub code_run_timeout_secs: u64,
+    /// Avatar URLs for webhook replies by persona key (`obi=https://...,chef=https://...`)
+    pub persona_avatars: HashMap<String, String>,
+    /// First restart delay for a crashed task; doubles per consecutive crash
+    pub supervisor_backoff_base_secs: u64,
+    /// Longest delay between restarts of a crashed task
+    pub supervisor_backoff_max_secs: u64,
+    /// Consecutive crashes before a task is given up on (None restarts indefinitely)
+    pub supervisor_max_restarts: Option<u32>,
+    /// Address for the `GET /health` endpoint (e.g. `0.0.0.0:8788`)
+    pub health_listen_addr: Option<String>,
+    /// Token for the `GET /events` WebSocket on the health listener (the stream is off when unset)
+    pub live_events_token: Option<String>,
+    /// Address for the Prometheus `GET /metrics` endpoint (e.g. `0.0.0.0:9464`), on its own listener
+    pub metrics_listen_addr: Option<String>,
+    /// Web dashboard on the health listener, with Discord OAuth2 login
+    pub dashboard: Option<DashboardConfig>,
+    /// Bucket that nightly CSV exports of the analytics tables are uploaded to
+    pub s3_export: Option<S3ExportConfig>,
+    /// OTLP collector that finished tracing spans are exported to
+    pub otlp: Option<OtlpConfig>,
+    /// Cache and buffer sizes, smaller under `LOW_MEMORY`
+    pub memory: MemoryLimits,
+    /// Pragmas applied to each SQLite connection when it opens
+    pub sqlite: SqlitePragmas,
+}
+
+/// Backends selectable with `LLM_PROVIDER`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    OpenAi,
+    Azure,
+    Anthropic,
+    Ollama,
+}
+
+impl ProviderKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "openai" => Some(ProviderKind::OpenAi),
+            "azure" | "azure_openai" => Some(ProviderKind::Azure),
+            "anthropic" | "claude" => Some(ProviderKind::Anthropic),
+            "ollama" => Some(ProviderKind::Ollama),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProviderKind::OpenAi => "openai",
+            ProviderKind::Azure => "azure",
+            ProviderKind::Anthropic => "anthropic",
+            ProviderKind::Ollama => "ollama",
+        }
+    }
+
+    /// Model used when neither `LLM_MODEL` nor `OPENAI_MODEL` is set
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            ProviderKind::OpenAi | ProviderKind::Azure => "gpt-5.1",
+            ProviderKind::Anthropic => "claude-sonnet-4-5",
+            ProviderKind::Ollama => "llama3.1",
+        }
+    }
+}
+
+/// Gateway sharding selectable with `DISCORD_SHARDS`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShardMode {
+    /// One shard, enough for bots in fewer than 2,500 guilds
+    #[default]
+    Single,
+    /// As many shards as Discord recommends for the bot
+    Auto,
+    /// A fixed number of shards
+    Fixed(u64),
+}
+
+impl ShardMode {
+    /// `auto`, or a shard count of at least 1
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" => Some(ShardMode::Auto),
+            "1" => Some(ShardMode::Single),
+            count => count.parse().ok().filter(|&n| n > 1).map(ShardMode::Fixed),
+        }
+    }
+}
+
+/// Chat backend selection and credentials
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmConfig {
+    /// From `LLM_PROVIDER`, default OpenAI
+    pub provider: ProviderKind,
+    /// Chat model (the deployment name on Azure), from `LLM_MODEL` or `OPENAI_MODEL`
+    pub model: String,
+    /// Model for messages with attached images, from `LLM_VISION_MODEL`, default `model`
+    pub vision_model: String,
+    /// Empty unless `OPENAI_API_KEY` is set
+    pub openai_api_key: String,
+    /// Azure OpenAI resource URL, e.g. `https://my-resource.openai.azure.com`
+    pub azure_openai_endpoint: Option<String>,
+    pub azure_openai_api_key: Option<String>,
+    pub azure_openai_api_version: String,
+    pub anthropic_api_key: Option<String>,
+    /// Ollama server base URL
+    pub ollama_url: String,
+    /// Model answered with while the circuit breaker is open, from `LLM_FALLBACK_MODEL`
+    pub fallback_model: Option<String>,
+    /// Seconds one chat attempt may take, from `LLM_TIMEOUT_SECS`, default 45
+    pub timeout_secs: u64,
+    /// Retries after a rate limit, outage or timeout, from `LLM_MAX_RETRIES`, default 2
+    pub max_retries: u32,
+    /// Failed requests in a row that open the circuit breaker, from `LLM_CIRCUIT_THRESHOLD`, default 5
+    pub circuit_threshold: u32,
+    /// Seconds the circuit stays open before a probe request, from `LLM_CIRCUIT_COOLDOWN_SECS`, default 60
+    pub circuit_cooldown_secs: u64,
+}
+
+impl LlmConfig {
+    /// Read the `LLM_*`, `OPENAI_*`, `AZURE_OPENAI_*`, `ANTHROPIC_API_KEY` and `OLLAMA_URL`
+    /// variables. Needs no Discord credentials, so `bot persona test` can use it too.
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        let provider = match var("LLM_PROVIDER") {
+            Some(value) => ProviderKind::parse(&value).ok_or_else(|| {
+                BotError::validation(format!("Unknown LLM_PROVIDER '{value}' (expected openai, azure, anthropic or ollama)"))
+            })?,
+            None => ProviderKind::OpenAi,
+        };
+        let model = var("LLM_MODEL")
+            .or_else(|| var("OPENAI_MODEL"))
+            .unwrap_or_else(|| provider.default_model().to_string());
+        Ok(LlmConfig {
+            provider,
+            vision_model: var("LLM_VISION_MODEL").unwrap_or_else(|| model.clone()),
+            model,
+            openai_api_key: env::var("OPENAI_API_KEY").unwrap_or_default(),
+            azure_openai_endpoint: var("AZURE_OPENAI_ENDPOINT"),
+            azure_openai_api_key: var("AZURE_OPENAI_API_KEY"),
+            azure_openai_api_version: var("AZURE_OPENAI_API_VERSION").unwrap_or_else(|| "2024-10-21".to_string()),
+            anthropic_api_key: var("ANTHROPIC_API_KEY"),
+            ollama_url: var("OLLAMA_URL").unwrap_or_else(|| "http://localhost:11434".to_string()),
+            fallback_model: var("LLM_FALLBACK_MODEL"),
+            timeout_secs: var("LLM_TIMEOUT_SECS").and_then(|s| s.parse().ok()).filter(|&secs| secs > 0).unwrap_or(45),
+            max_retries: var("LLM_MAX_RETRIES").and_then(|s| s.parse().ok()).unwrap_or(2),
+            circuit_threshold: var("LLM_CIRCUIT_THRESHOLD").and_then(|s| s.parse().ok()).filter(|&n| n > 0).unwrap_or(5),
+            circuit_cooldown_secs: var("LLM_CIRCUIT_COOLDOWN_SECS")
+                .and_then(|s| s.parse().ok())
+                .filter(|&secs| secs > 0)
+                .unwrap_or(60),
+        })
+    }
+}
+
+/// Cache and buffer limits. `LOW_MEMORY=true` lowers every default so the bot fits
+/// on a 256 MB instance; each limit can still be set on its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryLimits {
+    pub low_memory: bool,
+    /// SQLite page cache per connection, in KiB (None keeps SQLite's default of about 2 MB)
+    pub sqlite_cache_kib: Option<u32>,
+    /// Resolved member names kept in memory
+    pub name_cache_entries: usize,
+    /// Cached AI answers kept in memory in front of the `response_cache` table
+    pub response_cache_entries: usize,
+    /// Stored memory embeddings decoded per recall
+    pub embedding_scan_limit: i64,
+    /// Largest image attachment downloaded for the vision model, in bytes
+    pub image_max_bytes: u64,
+}
+
+impl MemoryLimits {
+    /// Read `LOW_MEMORY`, `SQLITE_CACHE_KIB`, `NAME_CACHE_ENTRIES`, `RESPONSE_CACHE_ENTRIES`,
+    /// `EMBEDDING_SCAN_LIMIT` and `IMAGE_MAX_BYTES`
+    pub fn from_env() -> Self {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        let low_memory = var("LOW_MEMORY").is_some_and(|v| v.eq_ignore_ascii_case("true"));
+        let defaults = Self::defaults(low_memory);
+        MemoryLimits {
+            low_memory,
+            sqlite_cache_kib: var("SQLITE_CACHE_KIB").and_then(|v| v.parse().ok()).or(defaults.sqlite_cache_kib),
+            name_cache_entries: var("NAME_CACHE_ENTRIES")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.name_cache_entries),
+            response_cache_entries: var("RESPONSE_CACHE_ENTRIES")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.response_cache_entries),
+            embedding_scan_limit: var("EMBEDDING_SCAN_LIMIT")
+                .and_then(|v| v.parse().ok())
+                .filter(|&n| n > 0)
+                .unwrap_or(defaults.embedding_scan_limit),
+            image_max_bytes: var("IMAGE_MAX_BYTES")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.image_max_bytes),
+        }
+    }
+
+    /// Limits used when nothing is set
+    pub fn defaults(low_memory: bool) -> Self {
+        if low_memory {
+            MemoryLimits {
+                low_memory,
+                sqlite_cache_kib: Some(512),
+                name_cache_entries: 1_000,
+                response_cache_entries: 100,
+                embedding_scan_limit: 200,
+                image_max_bytes: 1024 * 1024,
+            }
+        } else {
+            MemoryLimits {
+                low_memory,
+                sqlite_cache_kib: None,
+                name_cache_entries: 10_000,
+                response_cache_entries: 500,
+                embedding_scan_limit: 1000,
+                image_max_bytes: 4 * 1024 * 1024,
+            }
+        }
+    }
+}
+
+/// Pragmas set on every SQLite connection. The defaults (WAL, `synchronous = NORMAL`,
+/// a 5 second busy timeout) let several bots or tasks share one database file
+/// without `SQLITE_BUSY` errors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SqlitePragmas {
+    /// `DELETE`, `TRUNCATE`, `PERSIST`, `MEMORY`, `WAL` or `OFF`
+    pub journal_mode: String,
+    /// `OFF`, `NORMAL`, `FULL` or `EXTRA`
+    pub synchronous: String,
+    /// How long a connection waits for another writer's lock, in milliseconds
+    pub busy_timeout_ms: u32,
+    /// Page cache per connection, in KiB (None keeps SQLite's default)
+    pub cache_kib: Option<u32>,
+}
+
+impl Default for SqlitePragmas {
+    fn default() -> Self {
+        SqlitePragmas {
+            journal_mode: "WAL".to_string(),
+            synchronous: "NORMAL".to_string(),
+            busy_timeout_ms: 5000,
+            cache_kib: None,
+        }
+    }
+}
+
+const JOURNAL_MODES: &[&str] = &["DELETE", "TRUNCATE", "PERSIST", "MEMORY", "WAL", "OFF"];
+const SYNCHRONOUS_MODES: &[&str] = &["OFF", "NORMAL", "FULL", "EXTRA"];
+
+impl SqlitePragmas {
+    /// Read `SQLITE_JOURNAL_MODE`, `SQLITE_SYNCHRONOUS` and `SQLITE_BUSY_TIMEOUT_MS`.
+    /// The cache size comes from [`MemoryLimits`].
+    pub fn from_env(cache_kib: Option<u32>) -> Self {
+        let var = |name: &str| env::var(name).ok();
+        Self::parse(
+            var("SQLITE_JOURNAL_MODE").as_deref(),
+            var("SQLITE_SYNCHRONOUS").as_deref(),
+            var("SQLITE_BUSY_TIMEOUT_MS").as_deref(),
+            cache_kib,
+        )
+    }
+
+    /// Build from raw setting values, using the defaults for missing or unknown ones
+    pub fn parse(journal_mode: Option<&str>, synchronous: Option<&str>, busy_timeout_ms: Option<&str>, cache_kib: Option<u32>) -> Self {
+        let defaults = Self::default();
+        let mode = |value: Option<&str>, allowed: &[&str]| {
+            value
+                .map(|v| v.trim().to_uppercase())
+                .filter(|v| allowed.contains(&v.as_str()))
+        };
+        SqlitePragmas {
+            journal_mode: mode(journal_mode, JOURNAL_MODES).unwrap_or(defaults.journal_mode),
+            synchronous: mode(synchronous, SYNCHRONOUS_MODES).unwrap_or(defaults.synchronous),
+            busy_timeout_ms: busy_timeout_ms
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(defaults.busy_timeout_ms),
+            cache_kib,
+        }
+    }
+}
+
+/// SMTP server and addresses for emailing owner reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    /// The bot owner's address
+    pub to: String,
+}
+
+impl SmtpConfig {
+    /// Read `SMTP_HOST`, `SMTP_PORT` (default 587), `SMTP_USERNAME`, `SMTP_PASSWORD`,
+    /// `SMTP_FROM` and `OWNER_EMAIL`. None unless host, sender and recipient are all set.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        Some(SmtpConfig {
+            host: var("SMTP_HOST")?,
+            port: var("SMTP_PORT").and_then(|p| p.parse().ok()).unwrap_or(587),
+            username: var("SMTP_USERNAME"),
+            password: var("SMTP_PASSWORD"),
+            from: var("SMTP_FROM")?,
+            to: var("OWNER_EMAIL")?,
+        })
+    }
+}
+
+/// Discord OAuth2 application and public address for the web dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardConfig {
+    /// Where the dashboard is reachable from browsers, without a trailing slash (e.g. `https://bot.example.org`)
+    pub public_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl DashboardConfig {
+    /// Read `DASHBOARD_URL`, `DISCORD_CLIENT_ID` and `DISCORD_CLIENT_SECRET`. None unless all are set.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        Some(DashboardConfig {
+            public_url: var("DASHBOARD_URL")?.trim_end_matches('/').to_string(),
+            client_id: var("DISCORD_CLIENT_ID")?,
+            client_secret: var("DISCORD_CLIENT_SECRET")?,
+        })
+    }
+
+    /// OAuth2 redirect URI, which must also be added to the application in the Developer Portal
+    pub fn redirect_uri(&self) -> String {
+        format!("{}/dashboard/callback", self.public_url)
+    }
+}
+
+/// File format of the S3 analytics export, selectable with `S3_EXPORT_FORMAT`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// Snappy-compressed Parquet, one string column per table column
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "parquet" => Some(ExportFormat::Parquet),
+            _ => None,
+        }
+    }
+
+    /// File extension of the exported objects
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+/// S3-compatible bucket (AWS S3, MinIO, R2...) for the nightly analytics export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3ExportConfig {
+    /// Endpoint URL without the bucket, e.g. `https://s3.eu-west-1.amazonaws.com` or `http://minio:9000`
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Key prefix the exports are written under, ending in `/` unless empty
+    pub prefix: String,
+    pub format: ExportFormat,
+}
+
+impl S3ExportConfig {
+    /// Read `S3_EXPORT_ENDPOINT`, `S3_EXPORT_BUCKET`, `S3_EXPORT_ACCESS_KEY_ID` and
+    /// `S3_EXPORT_SECRET_ACCESS_KEY`, with optional `S3_EXPORT_REGION` (default `us-east-1`),
+    /// `S3_EXPORT_PREFIX` (default `persona/`) and `S3_EXPORT_FORMAT` (`csv`, the default,
+    /// or `parquet`). None unless the required ones are set.
+    pub fn from_env() -> Result<Option<Self>> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        let (Some(endpoint), Some(bucket), Some(access_key_id), Some(secret_access_key)) = (
+            var("S3_EXPORT_ENDPOINT"),
+            var("S3_EXPORT_BUCKET"),
+            var("S3_EXPORT_ACCESS_KEY_ID"),
+            var("S3_EXPORT_SECRET_ACCESS_KEY"),
+        ) else {
+            return Ok(None);
+        };
+        let format = match var("S3_EXPORT_FORMAT") {
+            Some(value) => ExportFormat::parse(&value).ok_or_else(|| {
+                BotError::validation(format!("Unknown S3_EXPORT_FORMAT '{value}' (expected csv or parquet)"))
+            })?,
+            None => ExportFormat::Csv,
+        };
+        let prefix = var("S3_EXPORT_PREFIX").unwrap_or_else(|| "persona/".to_string());
+        Ok(Some(S3ExportConfig {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            bucket,
+            region: var("S3_EXPORT_REGION").unwrap_or_else(|| "us-east-1".to_string()),
+            access_key_id,
+            secret_access_key,
+            prefix: match prefix.trim_matches('/') {
+                "" => String::new(),
+                trimmed => format!("{trimmed}/"),
+            },
+            format,
+        }))
+    }
+}
+
+/// OTLP/HTTP collector (Jaeger, Tempo, Honeycomb...) for tracing spans
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OtlpConfig {
+    /// Full traces URL, e.g. `http://localhost:4318/v1/traces`
+    pub traces_endpoint: String,
+    /// `service.name` resource attribute the spans are reported under
+    pub service_name: String,
+    /// Extra request headers, e.g. an API key
+    pub headers: Vec<(String, String)>,
+}
+
+impl OtlpConfig {
+    /// Read `OTEL_EXPORTER_OTLP_ENDPOINT` (the collector's base URL, `/v1/traces` is appended)
+    /// or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` (used as is), with optional `OTEL_SERVICE_NAME`
+    /// (default `persona`) and `OTEL_EXPORTER_OTLP_HEADERS` (`key=value,key=value`).
+    /// None unless an endpoint is set.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        let traces_endpoint = var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT").or_else(|| {
+            var("OTEL_EXPORTER_OTLP_ENDPOINT").map(|base| format!("{}/v1/traces", base.trim_end_matches('/')))
+        })?;
+        Some(OtlpConfig {
+            traces_endpoint,
+            service_name: var("OTEL_SERVICE_NAME").unwrap_or_else(|| "persona".to_string()),
+            headers: var("OTEL_EXPORTER_OTLP_HEADERS").map(|h| parse_otlp_headers(&h)).unwrap_or_default(),
+        })
+    }
+}
+
+/// Parse `key=value,key=value` pairs, skipping entries without a key
+pub fn parse_otlp_headers(value: &str) -> Vec<(String, String)> {
+    value
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
 }
 
 impl Config {
     pub fn from_env() -> Result<Self> {
+        let discord_token = env::var("DISCORD_MUPPET_FRIEND")
+            .map_err(|_| BotError::validation("DISCORD_MUPPET_FRIEND environment variable not set"))?;
+        Self::from_env_with_token(discord_token)
+    }
+
+    /// Everything but the Discord token from the environment, for bots whose
+    /// tokens come from `config.yaml`
+    pub fn from_env_with_token(discord_token: String) -> Result<Self> {
+        let llm = LlmConfig::from_env()?;
+        // Other chat backends can run without an OpenAI key; images, Whisper and moderation then fail
+        if llm.provider == ProviderKind::OpenAi && env::var("OPENAI_API_KEY").is_err() {
+            return Err(BotError::validation("OPENAI_API_KEY environment variable not set"));
+        }
+
+        let memory = MemoryLimits::from_env();
+        let shards = match env::var("DISCORD_SHARDS").ok().filter(|v| !v.is_empty()) {
+            Some(value) => ShardMode::parse(&value).ok_or_else(|| {
+                BotError::validation(format!("Invalid DISCORD_SHARDS '{value}' (expected auto or a shard count)"))
+            })?,
+            None => ShardMode::Single,
+        };
+
         Ok(Config {
-            discord_token: env::var("DISCORD_MUPPET_FRIEND")
-                .map_err(|_| anyhow::anyhow!("DISCORD_MUPPET_FRIEND environment variable not set"))?,
-            openai_api_key: env::var("OPENAI_API_KEY")
-                .map_err(|_| anyhow::anyhow!("OPENAI_API_KEY environment variable not set"))?,
+            discord_token,
+            openai_api_key: llm.openai_api_key.clone(),
+            openai_model: llm.model.clone(),
+            llm,
             database_path: env::var("DATABASE_PATH").unwrap_or_else(|_| "persona.db".to_string()),
+            database_pool_size: env::var("DATABASE_POOL_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|&size| size > 0)
+                .unwrap_or(if memory.low_memory { 1 } else { 4 }),
+            guild_databases: env::var("GUILD_DATABASES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty())
+                .collect(),
+            guild_database_dir: env::var("GUILD_DATABASE_DIR")
+                .ok()
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| "guild_data".to_string()),
             log_level: env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
             discord_guild_id: env::var("DISCORD_GUILD_ID").ok(),
-            openai_model: env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-5.1".to_string()),
             conflict_mediation_enabled: env::var("CONFLICT_MEDIATION_ENABLED")
                 .unwrap_or_else(|_| "true".to_string())
                 .to_lowercase() == "true",
             conflict_sensitivity: env::var("CONFLICT_SENSITIVITY")
                 .unwrap_or_else(|_| "medium".to_string()),
+            conflict_thresholds: env::var("CONFLICT_THRESHOLDS").ok().filter(|t| !t.is_empty()),
             mediation_cooldown_minutes: env::var("MEDIATION_COOLDOWN_MINUTES")
                 .unwrap_or_else(|_| "5".to_string())
                 .parse()
                 .unwrap_or(5),
+            command_allowlist: env::var("COMMAND_ALLOWLIST")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(|v| v.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect()),
+            memory_partners: Vec::new(),
+            clamav_address: env::var("CLAMAV_ADDRESS").ok().filter(|a| !a.is_empty()),
+            guild_members_intent: env::var("GUILD_MEMBERS_INTENT")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            shards,
+            slack_bot_token: env::var("SLACK_BOT_TOKEN").ok().filter(|t| !t.is_empty()),
+            matrix_homeserver_url: env::var("MATRIX_HOMESERVER_URL").ok().filter(|u| !u.is_empty()),
+            matrix_access_token: env::var("MATRIX_ACCESS_TOKEN").ok().filter(|t| !t.is_empty()),
+            matrix_puppet_prefix: env::var("MATRIX_PUPPET_PREFIX").ok().filter(|p| !p.is_empty()),
+            smtp: SmtpConfig::from_env(),
+            google_service_account_file: env::var("GOOGLE_SERVICE_ACCOUNT_FILE").ok().filter(|f| !f.is_empty()),
+            notion_token: env::var("NOTION_TOKEN").ok().filter(|t| !t.is_empty()),
+            knowledge_base_dir: env::var("KNOWLEDGE_BASE_DIR").ok().filter(|d| !d.is_empty()),
+            knowledge_base_git_push: env::var("KNOWLEDGE_BASE_GIT_PUSH")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            webhook_listen_addr: env::var("WEBHOOK_LISTEN_ADDR").ok().filter(|a| !a.is_empty()),
+            interactions_listen_addr: env::var("INTERACTIONS_LISTEN_ADDR").ok().filter(|a| !a.is_empty()),
+            discord_public_key: env::var("DISCORD_PUBLIC_KEY").ok().filter(|k| !k.is_empty()),
+            jira_base_url: env::var("JIRA_BASE_URL").ok().filter(|u| !u.is_empty()),
+            jira_email: env::var("JIRA_EMAIL").ok().filter(|e| !e.is_empty()),
+            jira_api_token: env::var("JIRA_API_TOKEN").ok().filter(|t| !t.is_empty()),
+            linear_api_key: env::var("LINEAR_API_KEY").ok().filter(|k| !k.is_empty()),
+            wolfram_app_id: env::var("WOLFRAM_APP_ID").ok().filter(|a| !a.is_empty()),
+            code_runner: env::var("CODE_RUNNER").ok().filter(|r| !r.is_empty()).map(|r| r.to_lowercase()),
+            piston_url: env::var("PISTON_URL").ok().filter(|u| !u.is_empty()),
+            code_run_timeout_secs: env::var("CODE_RUN_TIMEOUT_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3),
+            persona_avatars: parse_persona_avatars(&env::var("PERSONA_AVATARS").unwrap_or_default()),
+            supervisor_backoff_base_secs: env::var("SUPERVISOR_BACKOFF_BASE_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|&secs| secs > 0)
+                .unwrap_or(2),
+            supervisor_backoff_max_secs: env::var("SUPERVISOR_BACKOFF_MAX_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|&secs| secs > 0)
+                .unwrap_or(300),
+            // Unset or 0 keeps restarting
+            supervisor_max_restarts: env::var("SUPERVISOR_MAX_RESTARTS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|&n| n > 0),
+            health_listen_addr: env::var("HEALTH_LISTEN_ADDR").ok().filter(|a| !a.is_empty()),
+            live_events_token: env::var("LIVE_EVENTS_TOKEN").ok().filter(|t| !t.is_empty()),
+            metrics_listen_addr: env::var("METRICS_LISTEN_ADDR").ok().filter(|a| !a.is_empty()),
+            dashboard: DashboardConfig::from_env(),
+            s3_export: S3ExportConfig::from_env()?,
+            otlp: OtlpConfig::from_env(),
+            sqlite: SqlitePragmas::from_env(memory.sqlite_cache_kib),
+            memory,
         })
     }
 }
 
+/// Parse `key=url` pairs separated by commas, skipping malformed entries
+pub fn parse_persona_avatars(value: &str) -> HashMap<String, String> {
+    value
+        .split(',')
+        .filter_map(|pair| {
+            let (key, url) = pair.split_once('=')?;
+            let (key, url) = (key.trim().to_lowercase(), url.trim());
+            (!key.is_empty() && url.starts_with("https://")).then(|| (key, url.to_string()))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,14 +691,114 @@ mod tests {
         env::set_var("OPENAI_API_KEY", "test_openai_key");
         env::remove_var("DATABASE_PATH");
         env::remove_var("LOG_LEVEL");
+        env::remove_var("DATABASE_POOL_SIZE");
+        env::remove_var("SUPERVISOR_MAX_RESTARTS");
+        env::remove_var("LLM_PROVIDER");
+        env::remove_var("LOW_MEMORY");
+        env::remove_var("SQLITE_JOURNAL_MODE");
+        env::remove_var("SQLITE_SYNCHRONOUS");
+        env::remove_var("SQLITE_BUSY_TIMEOUT_MS");
+        env::remove_var("DISCORD_SHARDS");
         
         let config = Config::from_env().unwrap();
         assert_eq!(config.discord_token, "test_discord_token");
         assert_eq!(config.openai_api_key, "test_openai_key");
         assert_eq!(config.database_path, "persona.db");
+        assert_eq!(config.database_pool_size, 4);
         assert_eq!(config.log_level, "info");
+        assert_eq!(config.supervisor_max_restarts, None);
+        assert_eq!(config.llm.provider, ProviderKind::OpenAi);
+        assert!(!config.memory.low_memory);
+        assert_eq!(config.sqlite, SqlitePragmas::default());
+        assert_eq!(config.shards, ShardMode::Single);
         
         env::remove_var("DISCORD_MUPPET_FRIEND");
         env::remove_var("OPENAI_API_KEY");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_shard_mode() {
+        assert_eq!(ShardMode::parse("auto"), Some(ShardMode::Auto));
+        assert_eq!(ShardMode::parse(" AUTO "), Some(ShardMode::Auto));
+        assert_eq!(ShardMode::parse("1"), Some(ShardMode::Single));
+        assert_eq!(ShardMode::parse("4"), Some(ShardMode::Fixed(4)));
+        assert_eq!(ShardMode::parse("0"), None);
+        assert_eq!(ShardMode::parse("many"), None);
+    }
+
+    #[test]
+    fn test_low_memory_defaults() {
+        let low = MemoryLimits::defaults(true);
+        let normal = MemoryLimits::defaults(false);
+        assert!(low.name_cache_entries < normal.name_cache_entries);
+        assert!(low.response_cache_entries < normal.response_cache_entries);
+        assert!(low.embedding_scan_limit < normal.embedding_scan_limit);
+        assert!(low.image_max_bytes < normal.image_max_bytes);
+        assert_eq!(normal.sqlite_cache_kib, None);
+    }
+
+    #[test]
+    fn test_parse_sqlite_pragmas() {
+        let pragmas = SqlitePragmas::parse(Some("delete"), Some("sometimes"), Some("15000"), Some(256));
+        assert_eq!(pragmas.journal_mode, "DELETE");
+        assert_eq!(pragmas.synchronous, "NORMAL");
+        assert_eq!(pragmas.busy_timeout_ms, 15000);
+        assert_eq!(pragmas.cache_kib, Some(256));
+
+        assert_eq!(SqlitePragmas::parse(None, None, Some("soon"), None), SqlitePragmas::default());
+    }
+
+    #[test]
+    fn test_parse_otlp_headers() {
+        let headers = parse_otlp_headers("x-honeycomb-team=abc123, Authorization=Basic dXNlcg==,=skipped,novalue");
+        assert_eq!(
+            headers,
+            vec![
+                ("x-honeycomb-team".to_string(), "abc123".to_string()),
+                ("Authorization".to_string(), "Basic dXNlcg==".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_provider_kind() {
+        assert_eq!(ProviderKind::parse("OpenAI"), Some(ProviderKind::OpenAi));
+        assert_eq!(ProviderKind::parse("azure_openai"), Some(ProviderKind::Azure));
+        assert_eq!(ProviderKind::parse(" claude "), Some(ProviderKind::Anthropic));
+        assert_eq!(ProviderKind::parse("ollama"), Some(ProviderKind::Ollama));
+        assert_eq!(ProviderKind::parse("bard"), None);
+    }
+
+    #[test]
+    fn test_parse_export_format() {
+        assert_eq!(ExportFormat::parse(" Parquet "), Some(ExportFormat::Parquet));
+        assert_eq!(ExportFormat::parse("csv"), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::parse("json"), None);
+        assert_eq!(ExportFormat::Parquet.extension(), "parquet");
+    }
+
+    #[test]
+    fn test_parse_persona_avatars() {
+        let avatars = parse_persona_avatars(" Obi=https://cdn.example.org/obi.png, chef=http://insecure, =https://x, teacher");
+        assert_eq!(avatars.len(), 1);
+        assert_eq!(avatars["obi"], "https://cdn.example.org/obi.png");
+        assert!(parse_persona_avatars("").is_empty());
+    }
+
+    #[test]
+    fn test_smtp_config_from_env() {
+        env::remove_var("SMTP_HOST");
+        env::set_var("SMTP_FROM", "bot@example.org");
+        env::set_var("OWNER_EMAIL", "owner@example.org");
+        assert!(SmtpConfig::from_env().is_none());
+
+        env::set_var("SMTP_HOST", "smtp.example.org");
+        let smtp = SmtpConfig::from_env().unwrap();
+        assert_eq!(smtp.port, 587);
+        assert_eq!(smtp.to, "owner@example.org");
+
+        env::remove_var("SMTP_HOST");
+        env::remove_var("SMTP_FROM");
+        env::remove_var("OWNER_EMAIL");
+    }
+}
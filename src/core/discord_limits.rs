@@ -0,0 +1,246 @@
+//! # Discord Limits
+//!
+//! Discord's message and embed size limits in one place, with truncation and
+//! splitting that never cut through a grapheme cluster (emoji with skin tones or
+//! ZWJ sequences, flags, combining accents). Limits count Unicode scalar values,
+//! which is what Discord measures, not bytes.
+//!
+//! Embed builders should finish with [`fit_embed`], which enforces every
+//! per-part limit plus the 6000 character total, so oversized data produces a
+//! shortened embed instead of an API 400.
+//!
+//! - **Version**: 1.2.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.2.0: Audit log reason, webhook username and modal text input limits
+//! - 1.1.0: Button label limit
+//! - 1.0.0: Initial release, replacing per-feature truncation helpers
+
+use serde_json::Value;
+use serenity::builder::CreateEmbed;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub const MESSAGE_CONTENT: usize = 2000;
+pub const EMBED_TITLE: usize = 256;
+pub const EMBED_DESCRIPTION: usize = 4096;
+pub const EMBED_FIELDS: usize = 25;
+pub const EMBED_FIELD_NAME: usize = 256;
+pub const EMBED_FIELD_VALUE: usize = 1024;
+pub const EMBED_FOOTER: usize = 2048;
+pub const EMBED_AUTHOR: usize = 256;
+/// Combined length of title, description, field names and values, footer and author
+pub const EMBED_TOTAL: usize = 6000;
+pub const BUTTON_LABEL: usize = 80;
+pub const AUDIT_LOG_REASON: usize = 512;
+pub const WEBHOOK_USERNAME: usize = 80;
+/// Value of a modal's text input, including a prefilled one
+pub const TEXT_INPUT_VALUE: usize = 4000;
+
+/// Placeholder for parts Discord rejects when empty (field names and values)
+const EMPTY: &str = "\u{200b}";
+
+/// Length as Discord counts it
+pub fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Shorten text to at most `max` characters on a grapheme boundary, ending with `…` when cut
+pub fn truncate(text: &str, max: usize) -> String {
+    if char_len(text) <= max {
+        return text.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+    let mut cut = String::new();
+    let mut len = 0;
+    for grapheme in text.graphemes(true) {
+        let grapheme_len = char_len(grapheme);
+        if len + grapheme_len > max - 1 {
+            break;
+        }
+        cut.push_str(grapheme);
+        len += grapheme_len;
+    }
+    cut.push('…');
+    cut
+}
+
+/// Split text into messages of at most `max` characters, preferring line breaks and
+/// falling back to grapheme boundaries for lines that are too long on their own
+pub fn split_message(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for line in text.lines() {
+        let line_len = char_len(line);
+        if !current.is_empty() && current_len + line_len + 1 > max {
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        if line_len > max {
+            for grapheme in line.graphemes(true) {
+                let grapheme_len = char_len(grapheme);
+                if current_len + grapheme_len > max {
+                    chunks.push(std::mem::take(&mut current));
+                    current_len = 0;
+                }
+                current.push_str(grapheme);
+                current_len += grapheme_len;
+            }
+            continue;
+        }
+        if !current.is_empty() {
+            current.push('\n');
+            current_len += 1;
+        }
+        current.push_str(line);
+        current_len += line_len;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn fit_string(value: &mut Value, max: usize, placeholder: Option<&str>) -> usize {
+    let Some(text) = value.as_str() else {
+        return 0;
+    };
+    let fitted = match placeholder {
+        Some(placeholder) if text.trim().is_empty() => placeholder.to_string(),
+        _ => truncate(text, max),
+    };
+    let len = char_len(&fitted);
+    *value = Value::String(fitted);
+    len
+}
+
+/// Enforce every embed limit in place: truncate oversized parts, replace empty field
+/// names/values, drop fields past 25, then trim the description and drop trailing
+/// fields until the total is within 6000 characters
+pub fn fit_embed(embed: &mut CreateEmbed) -> &mut CreateEmbed {
+    let map = &mut embed.0;
+    let mut total = 0;
+
+    if let Some(title) = map.get_mut("title") {
+        total += fit_string(title, EMBED_TITLE, None);
+    }
+    if let Some(footer) = map.get_mut("footer").and_then(|f| f.get_mut("text")) {
+        total += fit_string(footer, EMBED_FOOTER, None);
+    }
+    if let Some(author) = map.get_mut("author").and_then(|a| a.get_mut("name")) {
+        total += fit_string(author, EMBED_AUTHOR, None);
+    }
+
+    let mut field_lens = Vec::new();
+    if let Some(Value::Array(fields)) = map.get_mut("fields") {
+        fields.truncate(EMBED_FIELDS);
+        for field in fields.iter_mut() {
+            let mut len = 0;
+            if let Some(name) = field.get_mut("name") {
+                len += fit_string(name, EMBED_FIELD_NAME, Some(EMPTY));
+            }
+            if let Some(value) = field.get_mut("value") {
+                len += fit_string(value, EMBED_FIELD_VALUE, Some(EMPTY));
+            }
+            field_lens.push(len);
+        }
+    }
+    total += field_lens.iter().sum::<usize>();
+
+    let mut description_len = 0;
+    if let Some(description) = map.get_mut("description") {
+        description_len = fit_string(description, EMBED_DESCRIPTION, None);
+        let room = EMBED_TOTAL.saturating_sub(total);
+        if description_len > room {
+            if let Some(text) = description.as_str() {
+                let shortened = truncate(text, room);
+                description_len = char_len(&shortened);
+                *description = Value::String(shortened);
+            }
+        }
+    }
+    total += description_len;
+    if map.get("description").and_then(|d| d.as_str()) == Some("") {
+        map.remove("description");
+    }
+
+    // Still over (title/footer/fields alone exceed the total): drop fields from the end
+    if total > EMBED_TOTAL {
+        if let Some(Value::Array(fields)) = map.get_mut("fields") {
+            while total > EMBED_TOTAL {
+                let Some(len) = field_lens.pop() else { break };
+                fields.pop();
+                total -= len;
+            }
+        }
+    }
+
+    embed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_keeps_graphemes_whole() {
+        assert_eq!(truncate("hello", 10), "hello");
+        assert_eq!(truncate("hello world", 6), "hello…");
+        // Family emoji is 7 scalar values; it must not be split
+        let family = "👨‍👩‍👧‍👦";
+        assert_eq!(truncate(&format!("ab{family}cd"), 8), "ab…");
+        assert_eq!(truncate("e\u{301}e\u{301}e\u{301}", 4), "e\u{301}…");
+        assert!(char_len(&truncate(&"🎉".repeat(3000), MESSAGE_CONTENT)) <= MESSAGE_CONTENT);
+    }
+
+    #[test]
+    fn test_split_message() {
+        let text = "aaaa\nbbbb\ncccc";
+        assert_eq!(split_message(text, 9), vec!["aaaa\nbbbb", "cccc"]);
+        assert_eq!(split_message(text, 100).len(), 1);
+
+        // Long lines split on grapheme boundaries, measured in characters not bytes
+        let chunks = split_message(&"é".repeat(2500), MESSAGE_CONTENT);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(char_len(&chunks[0]), 2000);
+        assert_eq!(chunks.concat(), "é".repeat(2500));
+    }
+
+    #[test]
+    fn test_fit_embed() {
+        let mut embed = CreateEmbed::default();
+        embed.title("t".repeat(300)).description("d".repeat(5000));
+        for i in 0..30 {
+            embed.field(format!("field {i}"), "v".repeat(2000), false);
+        }
+        embed.field("", "", false);
+        fit_embed(&mut embed);
+
+        let map = &embed.0;
+        let title = map["title"].as_str().unwrap();
+        assert_eq!(char_len(title), EMBED_TITLE);
+        let fields = map["fields"].as_array().unwrap();
+        assert!(fields.len() <= EMBED_FIELDS);
+        let field_total: usize = fields
+            .iter()
+            .map(|f| char_len(f["name"].as_str().unwrap()) + char_len(f["value"].as_str().unwrap()))
+            .sum();
+        assert!(fields.iter().all(|f| char_len(f["value"].as_str().unwrap()) <= EMBED_FIELD_VALUE));
+        let description = map.get("description").and_then(|d| d.as_str()).map_or(0, char_len);
+        assert!(char_len(title) + field_total + description <= EMBED_TOTAL);
+    }
+
+    #[test]
+    fn test_fit_embed_fills_empty_fields() {
+        let mut embed = CreateEmbed::default();
+        embed.field("", " ", true);
+        fit_embed(&mut embed);
+        let field = &embed.0["fields"][0];
+        assert_eq!(field["name"], EMPTY);
+        assert_eq!(field["value"], EMPTY);
+    }
+}
@@ -0,0 +1,195 @@
+//! # Errors
+//!
+//! [`BotError`] is the error type returned by the library's public APIs, so
+//! callers can match on what went wrong instead of parsing messages. Each kind
+//! maps to the text shown to the user in one place, [`BotError::user_message`].
+//! The binary converts to `anyhow` at its boundary.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: `Unavailable` for backend outages and an open circuit breaker
+//! - 1.0.0: Initial release, replacing `anyhow` in library code
+
+use std::time::Duration;
+use thiserror::Error;
+
+/// Result with [`BotError`] as the default error
+pub type Result<T, E = BotError> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum BotError {
+    /// SQLite or PostgreSQL failed
+    #[error("{0}")]
+    Database(String),
+
+    /// A Discord API call failed (boxed, as serenity's error is large)
+    #[error("{0}")]
+    Discord(Box<serenity::Error>),
+
+    /// The chat, image, transcription or moderation backend failed or timed out
+    #[error("{0}")]
+    OpenAi(String),
+
+    /// An API told us to slow down
+    #[error("rate limited{}", .retry_after.map(|d| format!(", retry after {}s", d.as_secs())).unwrap_or_default())]
+    RateLimited { retry_after: Option<Duration> },
+
+    /// The AI backend is failing (HTTP 5xx), or calls to it are paused by the circuit breaker
+    #[error("{0}")]
+    Unavailable(String),
+
+    /// Something the caller asked for doesn't exist
+    #[error("{0} not found")]
+    NotFound(String),
+
+    /// Bad user input or configuration; the message is safe to show
+    #[error("{0}")]
+    Validation(String),
+
+    /// A request to another service failed before it got a response
+    #[error("{0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    /// Any other failure, including errors reported by third-party services
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl BotError {
+    pub fn database(message: impl Into<String>) -> Self {
+        BotError::Database(message.into())
+    }
+
+    pub fn openai(message: impl Into<String>) -> Self {
+        BotError::OpenAi(message.into())
+    }
+
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        BotError::Unavailable(message.into())
+    }
+
+    pub fn not_found(what: impl Into<String>) -> Self {
+        BotError::NotFound(what.into())
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        BotError::Validation(message.into())
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        BotError::Internal(message.into())
+    }
+
+    /// Whether trying again later may succeed
+    pub fn is_transient(&self) -> bool {
+        match self {
+            BotError::RateLimited { .. } | BotError::Unavailable(_) | BotError::Http(_) => true,
+            BotError::OpenAi(message) => message.contains("timed out"),
+            _ => false,
+        }
+    }
+
+    /// What to tell the user when a command fails with this error
+    pub fn user_message(&self) -> String {
+        match self {
+            BotError::Database(_) => "❌ Sorry, I couldn't reach my database. Please try again in a moment.".to_string(),
+            BotError::Discord(_) => "❌ Sorry, Discord rejected that request. Please try again.".to_string(),
+            BotError::OpenAi(message) if message.contains("timed out") => {
+                "⏱️ Sorry, the AI service is taking longer than expected. Please try again in a moment.".to_string()
+            }
+            BotError::OpenAi(_) => "❌ Sorry, the AI service returned an error. Please try again in a moment.".to_string(),
+            BotError::RateLimited { retry_after: Some(after) } => {
+                format!("⏳ I'm being rate limited. Please try again in {} seconds.", after.as_secs().max(1))
+            }
+            BotError::RateLimited { retry_after: None } => "⏳ I'm being rate limited. Please try again shortly.".to_string(),
+            BotError::Unavailable(_) => {
+                "⚠️ The AI service is having trouble right now. Please try again in a few minutes.".to_string()
+            }
+            BotError::NotFound(what) => format!("❌ I couldn't find that {what}."),
+            BotError::Validation(message) => format!("❌ {message}"),
+            BotError::Http(e) if e.is_timeout() => {
+                "⏱️ Sorry, a service I depend on is taking longer than expected. Please try again in a moment.".to_string()
+            }
+            BotError::Http(_) | BotError::Io(_) | BotError::Internal(_) => {
+                "❌ Sorry, I encountered an error processing your request. Please try again.".to_string()
+            }
+        }
+    }
+}
+
+impl From<serenity::Error> for BotError {
+    fn from(e: serenity::Error) -> Self {
+        BotError::Discord(Box::new(e))
+    }
+}
+
+impl From<sqlite::Error> for BotError {
+    fn from(e: sqlite::Error) -> Self {
+        BotError::Database(e.to_string())
+    }
+}
+
+impl From<tokio_postgres::Error> for BotError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        BotError::Database(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for BotError {
+    fn from(e: serde_json::Error) -> Self {
+        BotError::Internal(format!("JSON error: {e}"))
+    }
+}
+
+impl From<std::num::ParseIntError> for BotError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        BotError::Internal(format!("invalid number: {e}"))
+    }
+}
+
+impl From<std::string::FromUtf8Error> for BotError {
+    fn from(e: std::string::FromUtf8Error) -> Self {
+        BotError::Internal(format!("invalid UTF-8: {e}"))
+    }
+}
+
+impl From<tokio::time::error::Elapsed> for BotError {
+    fn from(_: tokio::time::error::Elapsed) -> Self {
+        BotError::Internal("operation timed out".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_messages() {
+        assert_eq!(BotError::validation("Pick a persona first.").user_message(), "❌ Pick a persona first.");
+        assert_eq!(BotError::not_found("reminder").user_message(), "❌ I couldn't find that reminder.");
+        assert!(BotError::openai("chat request timed out after 45 seconds").user_message().starts_with("⏱️"));
+        assert!(BotError::RateLimited { retry_after: Some(Duration::from_secs(30)) }
+            .user_message()
+            .contains("30 seconds"));
+        // Internal details stay in the logs
+        assert!(!BotError::database("disk I/O error").user_message().contains("disk"));
+    }
+
+    #[test]
+    fn test_display_and_kinds() {
+        assert_eq!(BotError::not_found("AI response 7").to_string(), "AI response 7 not found");
+        assert_eq!(BotError::RateLimited { retry_after: Some(Duration::from_secs(5)) }.to_string(), "rate limited, retry after 5s");
+        assert!(BotError::RateLimited { retry_after: None }.is_transient());
+        assert!(!BotError::validation("bad").is_transient());
+        assert!(BotError::unavailable("HTTP 503 Service Unavailable").is_transient());
+
+        let sqlite_error = sqlite::open(":memory:").unwrap().execute("SELECT * FROM missing").unwrap_err();
+        assert!(matches!(BotError::from(sqlite_error), BotError::Database(_)));
+    }
+}
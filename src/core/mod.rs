@@ -2,14 +2,25 @@
 //!
 //! Core domain types, configuration, and error handling for the persona bot.
 //!
-//! - **Version**: 1.0.0
+//! - **Version**: 1.4.0
 //! - **Since**: 0.7.0
 //! - **Toggleable**: false
 //!
 //! ## Changelog
+//! - 1.4.0: Added multi_config for running several bots from config.yaml
+//! - 1.3.0: Added clock with injectable time and ID sources
+//! - 1.2.0: Added error with the BotError taxonomy
+//! - 1.1.0: Added discord_limits for message and embed size limits
 //! - 1.0.0: Initial creation with config module
 
+pub mod clock;
 pub mod config;
+pub mod discord_limits;
+pub mod error;
+pub mod multi_config;
 
 // Re-export commonly used items
-pub use config::Config;
+pub use clock::{Clock, IdGen};
+pub use error::{BotError, Result};
+pub use config::{Config, DashboardConfig, ExportFormat, LlmConfig, MemoryLimits, OtlpConfig, ProviderKind, S3ExportConfig, ShardMode, SmtpConfig, SqlitePragmas};
+pub use multi_config::{BotEntry, BotSettings, FleetDiff, MultiConfig};
@@ -0,0 +1,383 @@
+//! # Multi-Bot Configuration
+//!
+//! `config.yaml` lists the bots one process runs, each with its own Discord
+//! token and optional overrides of the environment config: chat model,
+//! conflict sensitivity, mediation cooldown, a slash command allowlist and the
+//! database connection pool size. Everything else (database, listeners, API
+//! keys) comes from the environment and is shared.
+//!
+//! ```yaml
+//! bots:
+//!   - name: muppet
+//!     token_env: DISCORD_MUPPET_FRIEND
+//!     model: gpt-4o-mini
+//!     conflict_sensitivity: high
+//!     mediation_cooldown_minutes: 10
+//!     commands: [hey, explain, imagine, help]
+//!     database_pool_size: 8
+//!   - name: analyst
+//!     token_env: DISCORD_ANALYST_TOKEN
+//!     share_memory_with: [teacher]
+//!   - name: teacher
+//!     token_env: DISCORD_TEACHER_TOKEN
+//!     share_memory_with: [analyst]
+//!   - name: support
+//!     token_env: DISCORD_SUPPORT_TOKEN
+//!     interactions_listen_addr: 0.0.0.0:8790
+//!     discord_public_key: 3c1f...
+//! ```
+//!
+//! A bot with `interactions_listen_addr` and `discord_public_key` takes its
+//! interactions over HTTP instead of the gateway. Unlike the other fields these
+//! aren't taken from the environment, since every application has its own key.
+//!
+//! Each bot keeps its own conversation history. Two bots that list each other
+//! in `share_memory_with` read each other's history too, in guilds that enable
+//! `shared_bot_memory`; a one-sided listing shares nothing.
+//!
+//! A bot with `database_pool_size` gets a connection pool of its own on the
+//! shared database, instead of sharing the `DATABASE_POOL_SIZE` pool; changing
+//! it restarts the bot.
+//!
+//! Comparing two loads gives a [`FleetDiff`], which is what a reload applies.
+//!
+//! - **Version**: 1.3.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.3.0: Per-bot `database_pool_size`
+//! - 1.2.0: `share_memory_with` pairs bots that share conversation history
+//! - 1.1.0: Per-bot interactions endpoint mode
+//! - 1.0.0: Initial release
+
+use super::config::Config;
+use super::error::{BotError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::path::Path;
+
+/// Per-bot overrides that a reload applies to a running bot
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BotSettings {
+    /// Chat model, instead of `LLM_MODEL`/`OPENAI_MODEL`
+    #[serde(default)]
+    pub model: Option<String>,
+    /// `low`, `medium`, `high` or `ultra`, instead of `CONFLICT_SENSITIVITY`
+    #[serde(default)]
+    pub conflict_sensitivity: Option<String>,
+    /// Instead of `MEDIATION_COOLDOWN_MINUTES`
+    #[serde(default)]
+    pub mediation_cooldown_minutes: Option<u64>,
+    /// Slash and context menu commands the bot answers (all when unset)
+    #[serde(default)]
+    pub commands: Option<Vec<String>>,
+    /// Bots whose conversation history this one may read, if they list it too
+    #[serde(default)]
+    pub share_memory_with: Option<Vec<String>>,
+    /// Connections in this bot's own database pool, instead of sharing the `DATABASE_POOL_SIZE` pool
+    #[serde(default)]
+    pub database_pool_size: Option<usize>,
+}
+
+/// One bot in `config.yaml`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BotEntry {
+    /// Unique name, used in logs and to match bots across reloads
+    pub name: String,
+    /// The Discord token itself; prefer `token_env` to keep it out of the file
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Environment variable holding the Discord token
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Serve interactions over HTTP on this address instead of connecting to the gateway
+    #[serde(default)]
+    pub interactions_listen_addr: Option<String>,
+    /// The application's public key, verifying requests to `interactions_listen_addr`
+    #[serde(default)]
+    pub discord_public_key: Option<String>,
+    #[serde(flatten)]
+    pub settings: BotSettings,
+}
+
+impl BotEntry {
+    /// The Discord token, read from `token_env` when not given directly
+    pub fn discord_token(&self) -> Result<String> {
+        if let Some(token) = self.token.as_ref().filter(|t| !t.is_empty()) {
+            return Ok(token.clone());
+        }
+        let name = self
+            .token_env
+            .as_deref()
+            .ok_or_else(|| BotError::validation(format!("Bot '{}' needs a token or token_env", self.name)))?;
+        env::var(name)
+            .ok()
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| BotError::validation(format!("Bot '{}': environment variable {name} is not set", self.name)))
+    }
+
+    /// `base` with this bot's token and overrides applied
+    pub fn config(&self, base: &Config) -> Result<Config> {
+        let mut config = base.clone();
+        config.discord_token = self.discord_token()?;
+        config.interactions_listen_addr = self.interactions_listen_addr.clone();
+        config.discord_public_key = self.discord_public_key.clone();
+        self.apply_settings(&mut config);
+        Ok(config)
+    }
+
+    /// Apply the reloadable overrides to `config`
+    pub fn apply_settings(&self, config: &mut Config) {
+        let settings = &self.settings;
+        if let Some(model) = &settings.model {
+            config.openai_model = model.clone();
+            config.llm.model = model.clone();
+        }
+        if let Some(sensitivity) = &settings.conflict_sensitivity {
+            config.conflict_sensitivity = sensitivity.clone();
+        }
+        if let Some(minutes) = settings.mediation_cooldown_minutes {
+            config.mediation_cooldown_minutes = minutes;
+        }
+        if let Some(commands) = &settings.commands {
+            config.command_allowlist = Some(commands.clone());
+        }
+        if let Some(size) = settings.database_pool_size {
+            config.database_pool_size = size.max(1);
+        }
+    }
+
+    /// Whether moving from `self` to `next` needs a new gateway connection, endpoint or database pool
+    fn needs_restart(&self, next: &BotEntry) -> bool {
+        self.token != next.token
+            || self.token_env != next.token_env
+            || self.interactions_listen_addr != next.interactions_listen_addr
+            || self.discord_public_key != next.discord_public_key
+            || self.settings.database_pool_size != next.settings.database_pool_size
+    }
+}
+
+/// The bots listed in `config.yaml`, in file order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MultiConfig {
+    pub bots: Vec<BotEntry>,
+}
+
+/// What a reload changes, by bot name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FleetDiff {
+    /// Bots to start
+    pub added: Vec<BotEntry>,
+    /// Bots to stop
+    pub removed: Vec<String>,
+    /// Bots whose token, interactions endpoint or pool size changed, stopped and started again
+    pub restarted: Vec<BotEntry>,
+    /// Running bots whose settings changed
+    pub changed: Vec<BotEntry>,
+}
+
+impl FleetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.restarted.is_empty() && self.changed.is_empty()
+    }
+
+    /// One-line summary for the log, e.g. `added support; updated muppet`
+    pub fn describe(&self) -> String {
+        let names = |entries: &[BotEntry]| entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>().join(", ");
+        let mut parts = Vec::new();
+        if !self.added.is_empty() {
+            parts.push(format!("added {}", names(&self.added)));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!("removed {}", self.removed.join(", ")));
+        }
+        if !self.restarted.is_empty() {
+            parts.push(format!("restarted {}", names(&self.restarted)));
+        }
+        if !self.changed.is_empty() {
+            parts.push(format!("updated {}", names(&self.changed)));
+        }
+        if parts.is_empty() {
+            "no changes".to_string()
+        } else {
+            parts.join("; ")
+        }
+    }
+}
+
+impl MultiConfig {
+    /// Parse and validate `config.yaml` contents
+    pub fn parse(yaml: &str) -> Result<Self> {
+        let config: MultiConfig =
+            serde_yaml::from_str(yaml).map_err(|e| BotError::validation(format!("Invalid bot config: {e}")))?;
+        let mut names = HashSet::new();
+        for bot in &config.bots {
+            if bot.name.trim().is_empty() {
+                return Err(BotError::validation("Every bot needs a name"));
+            }
+            if !names.insert(bot.name.as_str()) {
+                return Err(BotError::validation(format!("Bot name '{}' is used twice", bot.name)));
+            }
+            if bot.token.is_none() && bot.token_env.is_none() {
+                return Err(BotError::validation(format!("Bot '{}' needs a token or token_env", bot.name)));
+            }
+            if bot.interactions_listen_addr.is_some() != bot.discord_public_key.is_some() {
+                return Err(BotError::validation(format!(
+                    "Bot '{}' needs both interactions_listen_addr and discord_public_key",
+                    bot.name
+                )));
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| BotError::validation(format!("Failed to read {}: {e}", path.display())))?;
+        Self::parse(&yaml)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BotEntry> {
+        self.bots.iter().find(|bot| bot.name == name)
+    }
+
+    /// Bots that `name` shares conversation history with: those it lists in
+    /// `share_memory_with` that list it back, in its order
+    pub fn memory_partners(&self, name: &str) -> Vec<String> {
+        let lists = |bot: &BotEntry, other: &str| bot.settings.share_memory_with.as_ref().is_some_and(|list| list.iter().any(|n| n == other));
+        let Some(bot) = self.get(name) else {
+            return Vec::new();
+        };
+        bot.settings
+            .share_memory_with
+            .iter()
+            .flatten()
+            .filter(|partner| partner.as_str() != name && self.get(partner).is_some_and(|other| lists(other, name)))
+            .cloned()
+            .collect()
+    }
+
+    /// What changes going from this config to `next`
+    pub fn diff(&self, next: &MultiConfig) -> FleetDiff {
+        let mut diff = FleetDiff {
+            removed: self.bots.iter().filter(|b| next.get(&b.name).is_none()).map(|b| b.name.clone()).collect(),
+            ..FleetDiff::default()
+        };
+        for bot in &next.bots {
+            match self.get(&bot.name) {
+                None => diff.added.push(bot.clone()),
+                Some(current) if current.needs_restart(bot) => diff.restarted.push(bot.clone()),
+                Some(current) if current.settings != bot.settings => diff.changed.push(bot.clone()),
+                Some(_) => {}
+            }
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = "
+bots:
+  - name: muppet
+    token_env: MUPPET_TOKEN
+    model: gpt-4o-mini
+    commands: [hey, help]
+  - name: support
+    token: abc
+    conflict_sensitivity: high
+    mediation_cooldown_minutes: 10
+";
+
+    #[test]
+    fn test_parse() {
+        let config = MultiConfig::parse(YAML).unwrap();
+        assert_eq!(config.bots.len(), 2);
+        let muppet = config.get("muppet").unwrap();
+        assert_eq!(muppet.token_env.as_deref(), Some("MUPPET_TOKEN"));
+        assert_eq!(muppet.settings.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(muppet.settings.commands, Some(vec!["hey".to_string(), "help".to_string()]));
+        let support = config.get("support").unwrap();
+        assert_eq!(support.discord_token().unwrap(), "abc");
+        assert_eq!(support.settings.mediation_cooldown_minutes, Some(10));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        assert!(MultiConfig::parse("bots:\n  - name: a\n    token: x\n  - name: a\n    token: y\n").is_err());
+        assert!(MultiConfig::parse("bots:\n  - name: a\n").is_err());
+        assert!(MultiConfig::parse("bots:\n  - name: ''\n    token: x\n").is_err());
+        assert!(MultiConfig::parse("bots: [").is_err());
+        assert!(MultiConfig::parse("bots:\n  - name: a\n    token: x\n    interactions_listen_addr: 0.0.0.0:8790\n").is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        let current = MultiConfig::parse(YAML).unwrap();
+        assert!(current.diff(&current).is_empty());
+
+        let next = MultiConfig::parse(
+            "
+bots:
+  - name: muppet
+    token_env: MUPPET_TOKEN
+    model: gpt-4o
+    commands: [hey, help]
+  - name: helper
+    token: def
+",
+        )
+        .unwrap();
+        let diff = current.diff(&next);
+        assert_eq!(diff.added.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), vec!["helper"]);
+        assert_eq!(diff.removed, vec!["support".to_string()]);
+        assert_eq!(diff.changed.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), vec!["muppet"]);
+        assert!(diff.restarted.is_empty());
+        assert_eq!(diff.describe(), "added helper; removed support; updated muppet");
+    }
+
+    #[test]
+    fn test_memory_partners_must_list_each_other() {
+        let config = MultiConfig::parse(
+            "
+bots:
+  - name: analyst
+    token: a
+    share_memory_with: [teacher, muppet, analyst, ghost]
+  - name: teacher
+    token: b
+    share_memory_with: [analyst]
+  - name: muppet
+    token: c
+",
+        )
+        .unwrap();
+        assert_eq!(config.memory_partners("analyst"), vec!["teacher".to_string()]);
+        assert_eq!(config.memory_partners("teacher"), vec!["analyst".to_string()]);
+        assert!(config.memory_partners("muppet").is_empty());
+        assert!(config.memory_partners("ghost").is_empty());
+    }
+
+    #[test]
+    fn test_token_change_restarts() {
+        let current = MultiConfig::parse(YAML).unwrap();
+        let next = MultiConfig::parse(&YAML.replace("token: abc", "token: rotated")).unwrap();
+        let diff = current.diff(&next);
+        assert_eq!(diff.restarted.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), vec!["support"]);
+        assert!(diff.changed.is_empty() && diff.added.is_empty() && diff.removed.is_empty());
+
+        let endpoint = YAML.replace("token: abc", "token: abc\n    interactions_listen_addr: 0.0.0.0:8790\n    discord_public_key: ab12");
+        let diff = current.diff(&MultiConfig::parse(&endpoint).unwrap());
+        assert_eq!(diff.restarted.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), vec!["support"]);
+
+        let pool = YAML.replace("token: abc", "token: abc\n    database_pool_size: 8");
+        let diff = current.diff(&MultiConfig::parse(&pool).unwrap());
+        assert_eq!(diff.restarted.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), vec!["support"]);
+        assert!(diff.changed.is_empty());
+    }
+}
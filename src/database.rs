@@ -1,1863 +1,7430 @@
-use anyhow::Result;
-use log::info;
-use sqlite::{Connection, State};
+use crate::core::SqlitePragmas;
+use crate::core::clock::{sql_timestamp, system_clock, SQL_DATE_FORMAT};
+use crate::core::{Clock, Config, Result};
+use chrono::Duration;
+use tracing::info;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+
+mod backend;
+mod guild_stores;
+mod migrations;
+mod pool;
+mod postgres;
+
+pub use backend::{Connection, DatabaseBackend, State, Statement};
+pub use guild_stores::GuildStores;
+pub use migrations::{Migration, MIGRATIONS};
+pub use pool::{ConnectionPool, PooledConnection, QueryLatency, QueryLatencySnapshot, QUERY_LATENCY_BUCKETS};
 
 #[derive(Clone)]
 pub struct Database {
-    connection: Arc<Mutex<Connection>>,
+    pool: Arc<ConnectionPool>,
+    /// Time source for timestamps bound into queries
+    clock: Arc<dyn Clock>,
+    /// Guilds whose guild-resident data lives in a file of its own
+    guild_stores: Option<Arc<GuildStores>>,
+    /// Bot whose conversation history this handle reads and writes, for bots run from `config.yaml`
+    history_bot: Option<String>,
+    /// Other bots whose conversation history reads include as well
+    history_partners: Vec<String>,
 }
 
 impl Database {
-    pub async fn new(database_path: &str) -> Result<Self> {
-        let connection = sqlite::open(database_path)?;
+    /// Open a SQLite file (or `:memory:`), or a PostgreSQL database when `database_path` is a `postgres://` URL
+    pub async fn new(database_path: &str, pool_size: usize) -> Result<Self> {
+        Self::with_pragmas(database_path, pool_size, &SqlitePragmas::default()).await
+    }
+
+    /// Like [`Database::new`], opening each SQLite connection with `pragmas`
+    pub async fn with_pragmas(database_path: &str, pool_size: usize, pragmas: &SqlitePragmas) -> Result<Self> {
+        let backend = DatabaseBackend::parse(database_path);
         let db = Database {
-            connection: Arc::new(Mutex::new(connection)),
+            pool: Arc::new(ConnectionPool::open(&backend, pool_size, pragmas)?),
+            clock: system_clock(),
+            guild_stores: None,
+            history_bot: None,
+            history_partners: Vec::new(),
         };
         
         db.init_tables().await?;
-        info!("Database initialized at: {database_path}");
+        db.migrate().await?;
+        info!("Database initialized at: {} (pool of {} connections)", backend.describe(), db.pool.size());
         Ok(db)
     }
 
-    async fn init_tables(&self) -> Result<()> {
-        let conn = self.connection.lock().await;
-        
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS user_preferences (
-                user_id TEXT PRIMARY KEY,
-                default_persona TEXT DEFAULT 'obi',
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+    /// Open the database `config` describes, with a file of its own for each guild in `GUILD_DATABASES`
+    pub async fn open(config: &Config) -> Result<Self> {
+        let db = Self::with_pragmas(&config.database_path, config.database_pool_size, &config.sqlite).await?;
+        if config.guild_databases.is_empty() {
+            return Ok(db);
+        }
+        let stores = GuildStores::new(
+            &config.guild_database_dir,
+            config.guild_databases.iter().cloned(),
+            config.database_pool_size,
+            config.sqlite.clone(),
+        );
+        info!("{} guilds keep their data in {}", stores.guilds().count(), stores.dir().display());
+        Ok(db.with_guild_stores(stores))
+    }
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS usage_stats (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                user_id TEXT NOT NULL,
-                command TEXT NOT NULL,
-                persona TEXT,
-                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+    /// A handle on the same database with a connection pool of its own holding
+    /// `size` connections, for a bot whose `config.yaml` entry sets `database_pool_size`
+    pub fn with_pool_size(&self, size: usize) -> Result<Self> {
+        let mut db = self.clone();
+        if size != self.pool.size() {
+            if let Some(pool) = self.pool.resized(size)? {
+                db.pool = Arc::new(pool);
+            }
+        }
+        Ok(db)
+    }
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS conversation_history (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                user_id TEXT NOT NULL,
-                channel_id TEXT NOT NULL,
-                role TEXT NOT NULL,
-                content TEXT NOT NULL,
-                persona TEXT,
-                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+    /// Route guild-resident data of the guilds in `stores` to their own files
+    pub fn with_guild_stores(mut self, stores: GuildStores) -> Self {
+        self.guild_stores = Some(Arc::new(stores));
+        self
+    }
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_user_channel
-             ON conversation_history(user_id, channel_id)",
-        )?;
+    /// The pool holding a guild's guild-resident data: its own file when it has one, else the main database
+    async fn guild_pool(&self, guild_id: Option<&str>) -> Result<Arc<ConnectionPool>> {
+        match (&self.guild_stores, guild_id) {
+            (Some(stores), Some(guild_id)) if stores.contains(guild_id) => stores.pool(guild_id, &self.clock).await,
+            _ => Ok(self.pool.clone()),
+        }
+    }
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_timestamp
-             ON conversation_history(timestamp)",
-        )?;
+    /// The main pool followed by every guild file's, for methods that span guilds
+    async fn all_pools(&self) -> Result<Vec<Arc<ConnectionPool>>> {
+        let mut pools = vec![self.pool.clone()];
+        if let Some(stores) = &self.guild_stores {
+            for guild_id in stores.guilds() {
+                pools.push(stores.pool(guild_id, &self.clock).await?);
+            }
+        }
+        Ok(pools)
+    }
 
-        // Enhanced Interaction Tracking
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS message_metadata (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                message_id TEXT NOT NULL,
-                user_id TEXT NOT NULL,
-                channel_id TEXT NOT NULL,
-                attachment_urls TEXT,
-                embed_data TEXT,
-                reactions TEXT,
-                edited_at DATETIME,
-                deleted_at DATETIME,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+    /// Use `clock` instead of the system clock for "now" in queries, e.g. a fake one in tests
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_message_id
-             ON message_metadata(message_id)",
-        )?;
+    /// Keep conversation history per bot: rows are stored under `bot` and reads
+    /// see only its own rows and those stored before bots were told apart
+    pub fn with_history_bot(mut self, bot: &str) -> Self {
+        self.history_bot = Some(bot.to_string());
+        self
+    }
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS interaction_sessions (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                user_id TEXT NOT NULL,
-                guild_id TEXT,
-                session_start DATETIME DEFAULT CURRENT_TIMESTAMP,
-                session_end DATETIME,
-                message_count INTEGER DEFAULT 0,
-                last_activity DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+    /// A handle whose conversation history reads also include `partners`' rows.
+    /// Has no effect unless the handle is scoped with [`Database::with_history_bot`].
+    pub fn sharing_history_with(&self, partners: &[String]) -> Self {
+        let mut db = self.clone();
+        db.history_partners = partners.to_vec();
+        db
+    }
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_session_user
-             ON interaction_sessions(user_id, session_start)",
-        )?;
+    /// Condition limiting `conversation_history` reads to this handle's bot and
+    /// its partners, and the bot names to bind, in order
+    fn history_bot_filter(&self) -> (String, Vec<&str>) {
+        let Some(bot) = &self.history_bot else {
+            return (String::new(), Vec::new());
+        };
+        let bots: Vec<&str> = std::iter::once(bot.as_str()).chain(self.history_partners.iter().map(String::as_str)).collect();
+        let placeholders = vec!["?"; bots.len()].join(", ");
+        (format!(" AND (bot_name IS NULL OR bot_name IN ({placeholders}))"), bots)
+    }
 
-        // Feature-Specific Data
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS user_bookmarks (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                user_id TEXT NOT NULL,
-                channel_id TEXT NOT NULL,
-                message_id TEXT NOT NULL,
-                bookmark_name TEXT,
-                bookmark_note TEXT,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+    /// Histogram of how long each method held its connection, for the metrics exporter
+    pub fn query_latency(&self) -> QueryLatencySnapshot {
+        self.pool.latency().snapshot()
+    }
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_bookmark_user
-             ON user_bookmarks(user_id)",
-        )?;
+    /// The current time as a stored timestamp
+    fn now(&self) -> String {
+        sql_timestamp(self.clock.now())
+    }
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS reminders (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                user_id TEXT NOT NULL,
-                channel_id TEXT NOT NULL,
-                reminder_text TEXT NOT NULL,
-                remind_at DATETIME NOT NULL,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                completed BOOLEAN DEFAULT 0,
-                completed_at DATETIME
-            )",
-        )?;
+    /// The stored timestamp `offset` from now (negative for the past)
+    fn timestamp_from_now(&self, offset: Duration) -> String {
+        sql_timestamp(self.clock.now() + offset)
+    }
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_reminder_time
-             ON reminders(remind_at, completed)",
-        )?;
+    /// The date (`YYYY-MM-DD`) `days` from today (negative for the past)
+    fn date_from_now(&self, days: i64) -> String {
+        (self.clock.now() + Duration::days(days)).format(SQL_DATE_FORMAT).to_string()
+    }
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS custom_commands (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                command_name TEXT NOT NULL,
-                response_text TEXT NOT NULL,
-                created_by_user_id TEXT NOT NULL,
-                guild_id TEXT,
-                is_global BOOLEAN DEFAULT 0,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                UNIQUE(command_name, guild_id)
-            )",
-        )?;
+    async fn init_tables(&self) -> Result<()> {
+        self.pool.interact(move |conn| {
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS user_preferences (
+                    user_id TEXT PRIMARY KEY,
+                    default_persona TEXT DEFAULT 'obi',
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_custom_command
-             ON custom_commands(command_name, guild_id)",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS usage_stats (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    user_id TEXT NOT NULL,
+                    command TEXT NOT NULL,
+                    persona TEXT,
+                    timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-        // Analytics & Metrics
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS daily_analytics (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                date DATE UNIQUE NOT NULL,
-                total_messages INTEGER DEFAULT 0,
-                unique_users INTEGER DEFAULT 0,
-                total_commands INTEGER DEFAULT 0,
-                total_errors INTEGER DEFAULT 0,
-                persona_usage TEXT,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS conversation_history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    user_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    role TEXT NOT NULL,
+                    content TEXT NOT NULL,
+                    persona TEXT,
+                    timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    message_id TEXT,
+                    thread_id TEXT,
+                    bot_name TEXT
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_analytics_date
-             ON daily_analytics(date)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_user_channel
+                 ON conversation_history(user_id, channel_id)",
+            )?;
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS performance_metrics (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                metric_type TEXT NOT NULL,
-                value REAL NOT NULL,
-                unit TEXT,
-                metadata TEXT,
-                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_timestamp
+                 ON conversation_history(timestamp)",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_metrics_type
-             ON performance_metrics(metric_type, timestamp)",
-        )?;
+            // Enhanced Interaction Tracking
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS message_metadata (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    message_id TEXT NOT NULL,
+                    user_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    attachment_urls TEXT,
+                    embed_data TEXT,
+                    reactions TEXT,
+                    edited_at DATETIME,
+                    deleted_at DATETIME,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS error_logs (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                error_type TEXT NOT NULL,
-                error_message TEXT NOT NULL,
-                stack_trace TEXT,
-                user_id TEXT,
-                channel_id TEXT,
-                command TEXT,
-                metadata TEXT,
-                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_message_id
+                 ON message_metadata(message_id)",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_error_type
-             ON error_logs(error_type, timestamp)",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS interaction_sessions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    user_id TEXT NOT NULL,
+                    guild_id TEXT,
+                    session_start DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    session_end DATETIME,
+                    message_count INTEGER DEFAULT 0,
+                    last_activity DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-        // Extended Configuration
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS feature_flags (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                feature_name TEXT NOT NULL,
-                enabled BOOLEAN DEFAULT 0,
-                user_id TEXT,
-                guild_id TEXT,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                UNIQUE(feature_name, user_id, guild_id)
-            )",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_session_user
+                 ON interaction_sessions(user_id, session_start)",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_feature_flag
-             ON feature_flags(feature_name, user_id, guild_id)",
-        )?;
+            // Feature-Specific Data
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS user_bookmarks (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    user_id TEXT NOT NULL,
+                    guild_id TEXT,
+                    channel_id TEXT NOT NULL,
+                    message_id TEXT NOT NULL,
+                    bookmark_name TEXT,
+                    bookmark_note TEXT,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-        // Feature versions tracking for audit trail
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS feature_versions (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                feature_name TEXT NOT NULL,
-                version TEXT NOT NULL,
-                guild_id TEXT,
-                toggled_by TEXT,
-                enabled BOOLEAN NOT NULL,
-                changed_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_bookmark_user
+                 ON user_bookmarks(user_id)",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_feature_versions
-             ON feature_versions(feature_name, guild_id, changed_at)",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS reminders (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    user_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    guild_id TEXT,
+                    reminder_text TEXT NOT NULL,
+                    remind_at DATETIME NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    completed BOOLEAN DEFAULT 0,
+                    completed_at DATETIME,
+                    source_message_link TEXT,
+                    source_snippet TEXT,
+                    urgent BOOLEAN DEFAULT 0
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS guild_settings (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                guild_id TEXT NOT NULL,
-                setting_key TEXT NOT NULL,
-                setting_value TEXT,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                UNIQUE(guild_id, setting_key)
-            )",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_reminder_time
+                 ON reminders(remind_at, completed)",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_guild_setting
-             ON guild_settings(guild_id, setting_key)",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS custom_commands (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    command_name TEXT NOT NULL,
+                    response_text TEXT NOT NULL,
+                    created_by_user_id TEXT NOT NULL,
+                    guild_id TEXT,
+                    is_global BOOLEAN DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(command_name, guild_id)
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS extended_user_preferences (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                user_id TEXT NOT NULL,
-                preference_key TEXT NOT NULL,
-                preference_value TEXT,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                UNIQUE(user_id, preference_key)
-            )",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_custom_command
+                 ON custom_commands(command_name, guild_id)",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_user_pref
-             ON extended_user_preferences(user_id, preference_key)",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS custom_personas (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    persona_key TEXT NOT NULL UNIQUE,
+                    guild_id TEXT NOT NULL,
+                    name TEXT NOT NULL,
+                    description TEXT NOT NULL,
+                    system_prompt TEXT NOT NULL,
+                    temperature REAL,
+                    emoji TEXT,
+                    shared BOOLEAN DEFAULT 0,
+                    created_by_user_id TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-        // Conflict Detection & Mediation
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS conflict_detection (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                channel_id TEXT NOT NULL,
-                guild_id TEXT,
-                participants TEXT NOT NULL,
-                detection_type TEXT NOT NULL,
-                confidence_score REAL,
-                last_message_id TEXT,
-                mediation_triggered BOOLEAN DEFAULT 0,
-                mediation_message_id TEXT,
-                first_detected DATETIME DEFAULT CURRENT_TIMESTAMP,
-                last_detected DATETIME DEFAULT CURRENT_TIMESTAMP,
-                resolved_at DATETIME
-            )",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS linked_accounts (
+                    user_id TEXT PRIMARY KEY,
+                    username TEXT NOT NULL,
+                    access_token TEXT NOT NULL,
+                    refresh_token TEXT NOT NULL,
+                    scope TEXT NOT NULL DEFAULT '',
+                    expires_at DATETIME NOT NULL,
+                    admin_guild_ids TEXT NOT NULL DEFAULT '',
+                    verified_at DATETIME,
+                    linked_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_conflict_channel
-             ON conflict_detection(channel_id, guild_id)",
-        )?;
+            // Analytics & Metrics
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS daily_analytics (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    date DATE UNIQUE NOT NULL,
+                    total_messages INTEGER DEFAULT 0,
+                    unique_users INTEGER DEFAULT 0,
+                    total_commands INTEGER DEFAULT 0,
+                    total_errors INTEGER DEFAULT 0,
+                    persona_usage TEXT,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_conflict_timestamp
-             ON conflict_detection(first_detected)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_analytics_date
+                 ON daily_analytics(date)",
+            )?;
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS mediation_history (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                conflict_id INTEGER NOT NULL,
-                channel_id TEXT NOT NULL,
-                mediation_message TEXT,
-                effectiveness_rating INTEGER,
-                follow_up_messages INTEGER DEFAULT 0,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY(conflict_id) REFERENCES conflict_detection(id)
-            )",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS performance_metrics (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    metric_type TEXT NOT NULL,
+                    value REAL NOT NULL,
+                    unit TEXT,
+                    metadata TEXT,
+                    timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    bot_id TEXT
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_mediation_conflict
-             ON mediation_history(conflict_id)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_metrics_type
+                 ON performance_metrics(metric_type, timestamp)",
+            )?;
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS user_interaction_patterns (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                user_id_a TEXT NOT NULL,
-                user_id_b TEXT NOT NULL,
-                channel_id TEXT,
-                guild_id TEXT,
-                interaction_count INTEGER DEFAULT 0,
-                last_interaction DATETIME,
-                conflict_incidents INTEGER DEFAULT 0,
-                avg_response_time_ms INTEGER,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                UNIQUE(user_id_a, user_id_b, channel_id)
-            )",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS error_logs (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    error_type TEXT NOT NULL,
+                    error_message TEXT NOT NULL,
+                    stack_trace TEXT,
+                    user_id TEXT,
+                    channel_id TEXT,
+                    command TEXT,
+                    metadata TEXT,
+                    timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    guild_id TEXT
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_interaction_users
-             ON user_interaction_patterns(user_id_a, user_id_b)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_error_type
+                 ON error_logs(error_type, timestamp)",
+            )?;
 
-        // Channel Settings (for per-channel verbosity and other settings)
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS channel_settings (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                guild_id TEXT NOT NULL,
-                channel_id TEXT NOT NULL,
-                verbosity TEXT DEFAULT 'concise',
-                conflict_enabled BOOLEAN DEFAULT 1,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                UNIQUE(guild_id, channel_id)
-            )",
-        )?;
+            // Extended Configuration
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS feature_flags (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    feature_name TEXT NOT NULL,
+                    enabled BOOLEAN DEFAULT 0,
+                    user_id TEXT,
+                    guild_id TEXT,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(feature_name, user_id, guild_id)
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_channel_settings_guild
-             ON channel_settings(guild_id)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_feature_flag
+                 ON feature_flags(feature_name, user_id, guild_id)",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_channel_settings_channel
-             ON channel_settings(channel_id)",
-        )?;
+            // Feature versions tracking for audit trail
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS feature_versions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    feature_name TEXT NOT NULL,
+                    version TEXT NOT NULL,
+                    guild_id TEXT,
+                    toggled_by TEXT,
+                    enabled BOOLEAN NOT NULL,
+                    changed_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-        // Bot Settings (for global bot configuration, not per-guild)
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS bot_settings (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                setting_key TEXT NOT NULL UNIQUE,
-                setting_value TEXT,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_feature_versions
+                 ON feature_versions(feature_name, guild_id, changed_at)",
+            )?;
 
-        // OpenAI Usage Tracking Tables
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS openai_usage (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                request_id TEXT,
-                user_id TEXT NOT NULL,
-                guild_id TEXT,
-                channel_id TEXT,
-                service_type TEXT NOT NULL,
-                model TEXT NOT NULL,
-                input_tokens INTEGER DEFAULT 0,
-                output_tokens INTEGER DEFAULT 0,
-                total_tokens INTEGER DEFAULT 0,
-                audio_duration_seconds REAL DEFAULT 0,
-                image_count INTEGER DEFAULT 0,
-                image_size TEXT,
-                estimated_cost_usd REAL NOT NULL,
-                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
-            )",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS guild_settings (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    setting_key TEXT NOT NULL,
+                    setting_value TEXT,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(guild_id, setting_key)
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_openai_usage_user_ts
-             ON openai_usage(user_id, timestamp)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_guild_setting
+                 ON guild_settings(guild_id, setting_key)",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_openai_usage_guild_ts
-             ON openai_usage(guild_id, timestamp)",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS extended_user_preferences (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    user_id TEXT NOT NULL,
+                    preference_key TEXT NOT NULL,
+                    preference_value TEXT,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(user_id, preference_key)
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_openai_usage_timestamp
-             ON openai_usage(timestamp)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_user_pref
+                 ON extended_user_preferences(user_id, preference_key)",
+            )?;
 
-        // Daily aggregates for fast queries (90-day retention)
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS openai_usage_daily (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                date DATE NOT NULL,
-                guild_id TEXT,
-                user_id TEXT,
-                service_type TEXT NOT NULL,
-                request_count INTEGER DEFAULT 0,
-                total_tokens INTEGER DEFAULT 0,
-                total_audio_seconds REAL DEFAULT 0,
-                total_images INTEGER DEFAULT 0,
-                total_cost_usd REAL DEFAULT 0,
-                UNIQUE(date, guild_id, user_id, service_type)
-            )",
-        )?;
+            // Conflict Detection & Mediation
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS conflict_detection (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    channel_id TEXT NOT NULL,
+                    guild_id TEXT,
+                    participants TEXT NOT NULL,
+                    detection_type TEXT NOT NULL,
+                    confidence_score REAL,
+                    last_message_id TEXT,
+                    mediation_triggered BOOLEAN DEFAULT 0,
+                    mediation_message_id TEXT,
+                    first_detected DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    last_detected DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    resolved_at DATETIME
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_openai_daily_guild_date
-             ON openai_usage_daily(guild_id, date)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_conflict_channel
+                 ON conflict_detection(channel_id, guild_id)",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_openai_daily_user_date
-             ON openai_usage_daily(user_id, date)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_conflict_timestamp
+                 ON conflict_detection(first_detected)",
+            )?;
 
-        // DM Interaction Tracking Tables
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS dm_sessions (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_id TEXT UNIQUE NOT NULL,
-                user_id TEXT NOT NULL,
-                channel_id TEXT NOT NULL,
-                started_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                last_activity_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                ended_at DATETIME,
-                end_reason TEXT,
-                message_count INTEGER DEFAULT 0,
-                user_message_count INTEGER DEFAULT 0,
-                bot_message_count INTEGER DEFAULT 0,
-                total_user_chars INTEGER DEFAULT 0,
-                total_bot_chars INTEGER DEFAULT 0,
-                avg_response_time_ms INTEGER
-            )",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS mediation_history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    conflict_id INTEGER NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    mediation_message TEXT,
+                    effectiveness_rating INTEGER,
+                    follow_up_messages INTEGER DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    approved_by TEXT,
+                    decision TEXT,
+                    FOREIGN KEY(conflict_id) REFERENCES conflict_detection(id)
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_dm_sessions_user
-             ON dm_sessions(user_id, started_at DESC)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_mediation_conflict
+                 ON mediation_history(conflict_id)",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_dm_sessions_active
-             ON dm_sessions(session_id) WHERE ended_at IS NULL",
-        )?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS user_interaction_patterns (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    user_id_a TEXT NOT NULL,
+                    user_id_b TEXT NOT NULL,
+                    channel_id TEXT,
+                    guild_id TEXT,
+                    interaction_count INTEGER DEFAULT 0,
+                    last_interaction DATETIME,
+                    conflict_incidents INTEGER DEFAULT 0,
+                    avg_response_time_ms INTEGER,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(user_id_a, user_id_b, channel_id)
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS dm_session_metrics (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_id TEXT UNIQUE NOT NULL,
-                total_api_calls INTEGER DEFAULT 0,
-                total_tokens INTEGER DEFAULT 0,
-                total_api_cost_usd REAL DEFAULT 0,
-                chat_calls INTEGER DEFAULT 0,
-                whisper_calls INTEGER DEFAULT 0,
-                dalle_calls INTEGER DEFAULT 0,
-                audio_transcriptions INTEGER DEFAULT 0,
-                slash_commands_used INTEGER DEFAULT 0,
-                conversation_depth INTEGER DEFAULT 0,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY(session_id) REFERENCES dm_sessions(session_id)
-            )",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_interaction_users
+                 ON user_interaction_patterns(user_id_a, user_id_b)",
+            )?;
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS dm_events (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_id TEXT NOT NULL,
-                event_type TEXT NOT NULL,
-                user_id TEXT NOT NULL,
-                channel_id TEXT NOT NULL,
-                event_data TEXT,
-                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY(session_id) REFERENCES dm_sessions(session_id)
-            )",
-        )?;
+            // Channel Settings (for per-channel verbosity and other settings)
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS channel_settings (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    verbosity TEXT DEFAULT 'concise',
+                    conflict_enabled BOOLEAN DEFAULT 1,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(guild_id, channel_id)
+                )",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_dm_events_session
-             ON dm_events(session_id, timestamp)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_channel_settings_guild
+                 ON channel_settings(guild_id)",
+            )?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_dm_events_type
-             ON dm_events(event_type, timestamp)",
-        )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_channel_settings_channel
+                 ON channel_settings(channel_id)",
+            )?;
 
-        Ok(())
-    }
+            // Bot Settings (for global bot configuration, not per-guild)
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS bot_settings (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    setting_key TEXT NOT NULL UNIQUE,
+                    setting_value TEXT,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-    pub async fn get_user_persona(&self, user_id: &str) -> Result<String> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare("SELECT default_persona FROM user_preferences WHERE user_id = ?")?;
-        statement.bind((1, user_id))?;
+            // OpenAI Usage Tracking Tables
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS openai_usage (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    request_id TEXT,
+                    user_id TEXT NOT NULL,
+                    guild_id TEXT,
+                    channel_id TEXT,
+                    service_type TEXT NOT NULL,
+                    model TEXT NOT NULL,
+                    input_tokens INTEGER DEFAULT 0,
+                    output_tokens INTEGER DEFAULT 0,
+                    total_tokens INTEGER DEFAULT 0,
+                    audio_duration_seconds REAL DEFAULT 0,
+                    image_count INTEGER DEFAULT 0,
+                    image_size TEXT,
+                    estimated_cost_usd REAL NOT NULL,
+                    timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-        if let Ok(State::Row) = statement.next() {
-            Ok(statement.read::<String, _>("default_persona")?)
-        } else {
-            // Check for PERSONA environment variable, fallback to 'obi'
-            Ok(std::env::var("PERSONA").unwrap_or_else(|_| "obi".to_string()))
-        }
-    }
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_openai_usage_user_ts
+                 ON openai_usage(user_id, timestamp)",
+            )?;
 
-    /// Get user persona with guild default fallback
-    /// Cascade: user preference -> guild default -> env var -> "obi"
-    pub async fn get_user_persona_with_guild(&self, user_id: &str, guild_id: Option<&str>) -> Result<String> {
-        let conn = self.connection.lock().await;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_openai_usage_guild_ts
+                 ON openai_usage(guild_id, timestamp)",
+            )?;
 
-        // First check user preference
-        let mut statement = conn.prepare("SELECT default_persona FROM user_preferences WHERE user_id = ?")?;
-        statement.bind((1, user_id))?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_openai_usage_timestamp
+                 ON openai_usage(timestamp)",
+            )?;
 
-        if let Ok(State::Row) = statement.next() {
-            return Ok(statement.read::<String, _>("default_persona")?);
-        }
+            // Daily aggregates for fast queries (90-day retention)
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS openai_usage_daily (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    date DATE NOT NULL,
+                    guild_id TEXT,
+                    user_id TEXT,
+                    service_type TEXT NOT NULL,
+                    request_count INTEGER DEFAULT 0,
+                    total_tokens INTEGER DEFAULT 0,
+                    total_audio_seconds REAL DEFAULT 0,
+                    total_images INTEGER DEFAULT 0,
+                    total_cost_usd REAL DEFAULT 0,
+                    UNIQUE(date, guild_id, user_id, service_type)
+                )",
+            )?;
 
-        // Check guild default if guild_id is provided
-        if let Some(gid) = guild_id {
-            drop(statement);
-            let mut guild_stmt = conn.prepare(
-                "SELECT setting_value FROM guild_settings WHERE guild_id = ? AND setting_key = 'default_persona'"
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_openai_daily_guild_date
+                 ON openai_usage_daily(guild_id, date)",
             )?;
-            guild_stmt.bind((1, gid))?;
 
-            if let Ok(State::Row) = guild_stmt.next() {
-                return Ok(guild_stmt.read::<String, _>(0)?);
-            }
-        }
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_openai_daily_user_date
+                 ON openai_usage_daily(user_id, date)",
+            )?;
 
-        // Fall back to PERSONA environment variable, then 'obi'
-        Ok(std::env::var("PERSONA").unwrap_or_else(|_| "obi".to_string()))
-    }
+            // Daily cost per guild and feature for invoice-style reports (90-day retention)
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS openai_usage_feature_daily (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    date DATE NOT NULL,
+                    guild_id TEXT,
+                    feature TEXT NOT NULL,
+                    request_count INTEGER DEFAULT 0,
+                    total_tokens INTEGER DEFAULT 0,
+                    total_cost_usd REAL DEFAULT 0,
+                    UNIQUE(date, guild_id, feature)
+                )",
+            )?;
 
-    pub async fn set_user_persona(&self, user_id: &str, persona: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        conn.execute(
-            "INSERT OR REPLACE INTO user_preferences (user_id, default_persona, updated_at) 
-             VALUES (?, ?, CURRENT_TIMESTAMP)",
-        )?;
-        
-        let mut statement = conn.prepare(
-            "INSERT OR REPLACE INTO user_preferences (user_id, default_persona, updated_at) 
-             VALUES (?, ?, CURRENT_TIMESTAMP)"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, persona))?;
-        statement.next()?;
-        
-        info!("Updated persona for user {user_id} to {persona}");
-        Ok(())
-    }
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_openai_feature_daily_date
+                 ON openai_usage_feature_daily(date, guild_id)",
+            )?;
 
-    pub async fn log_usage(&self, user_id: &str, command: &str, persona: Option<&str>) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO usage_stats (user_id, command, persona) VALUES (?, ?, ?)"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, command))?;
-        statement.bind((3, persona.unwrap_or("")))?;
-        statement.next()?;
-        Ok(())
-    }
+            // Guilds the bot is in, refreshed from gateway guild events (for /ops overview)
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS known_guilds (
+                    guild_id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    member_count INTEGER,
+                    last_activity_at DATETIME,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-    pub async fn store_message(&self, user_id: &str, channel_id: &str, role: &str, content: &str, persona: Option<&str>) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO conversation_history (user_id, channel_id, role, content, persona) VALUES (?, ?, ?, ?, ?)"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, channel_id))?;
-        statement.bind((3, role))?;
-        statement.bind((4, content))?;
-        statement.bind((5, persona.unwrap_or("")))?;
-        statement.next()?;
-        Ok(())
-    }
+            // Daily slash command and error counts per guild (90-day retention)
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS guild_activity_daily (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    date DATE NOT NULL,
+                    guild_id TEXT NOT NULL,
+                    command_count INTEGER DEFAULT 0,
+                    error_count INTEGER DEFAULT 0,
+                    UNIQUE(date, guild_id)
+                )",
+            )?;
 
-    pub async fn get_conversation_history(&self, user_id: &str, channel_id: &str, limit: i64) -> Result<Vec<(String, String)>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT role, content FROM conversation_history
-             WHERE user_id = ? AND channel_id = ?
-             ORDER BY timestamp DESC
-             LIMIT ?"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, channel_id))?;
-        statement.bind((3, limit))?;
-
-        let mut history = Vec::new();
-        while let Ok(State::Row) = statement.next() {
-            let role = statement.read::<String, _>("role")?;
-            let content = statement.read::<String, _>("content")?;
-            history.push((role, content));
-        }
+            // Guild settings that reference deleted channels/roles, refreshed by the validator
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS stale_settings (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    setting_key TEXT NOT NULL,
+                    missing_ids TEXT NOT NULL,
+                    detected_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(guild_id, setting_key)
+                )",
+            )?;
 
-        // Reverse to get chronological order (oldest first)
-        history.reverse();
-        Ok(history)
-    }
+            // Daily emoji usage per guild, from messages and reactions
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS emoji_stats (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    date DATE NOT NULL,
+                    guild_id TEXT NOT NULL,
+                    emoji TEXT NOT NULL,
+                    source TEXT NOT NULL,
+                    count INTEGER DEFAULT 0,
+                    UNIQUE(date, guild_id, emoji, source)
+                )",
+            )?;
 
-    pub async fn clear_conversation_history(&self, user_id: &str, channel_id: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "DELETE FROM conversation_history WHERE user_id = ? AND channel_id = ?"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, channel_id))?;
-        statement.next()?;
-        info!("Cleared conversation history for user {user_id} in channel {channel_id}");
-        Ok(())
-    }
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_emoji_stats_guild_date
+                 ON emoji_stats(guild_id, date)",
+            )?;
 
-    pub async fn cleanup_old_messages(&self, days: i64) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "DELETE FROM conversation_history WHERE timestamp < datetime('now', ? || ' days')"
-        )?;
-        statement.bind((1, format!("-{days}").as_str()))?;
-        statement.next()?;
-        info!("Cleaned up conversation history older than {days} days");
-        Ok(())
-    }
+            // Member join/activity dates for retention cohorts (/community_insights)
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS member_activity (
+                    guild_id TEXT NOT NULL,
+                    user_id TEXT NOT NULL,
+                    joined_at DATE,
+                    first_seen DATE NOT NULL,
+                    last_active DATE NOT NULL,
+                    message_count INTEGER DEFAULT 0,
+                    PRIMARY KEY (guild_id, user_id)
+                )",
+            )?;
 
-    // Message Metadata Methods
-    pub async fn store_message_metadata(
-        &self,
-        message_id: &str,
-        user_id: &str,
-        channel_id: &str,
-        attachment_urls: Option<&str>,
-        embed_data: Option<&str>,
-        reactions: Option<&str>,
-    ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO message_metadata (message_id, user_id, channel_id, attachment_urls, embed_data, reactions)
-             VALUES (?, ?, ?, ?, ?, ?)"
-        )?;
-        statement.bind((1, message_id))?;
-        statement.bind((2, user_id))?;
-        statement.bind((3, channel_id))?;
-        statement.bind((4, attachment_urls.unwrap_or("")))?;
-        statement.bind((5, embed_data.unwrap_or("")))?;
-        statement.bind((6, reactions.unwrap_or("")))?;
-        statement.next()?;
-        Ok(())
-    }
+            // Per-channel auto slowmode thresholds (a row means the channel is monitored)
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS auto_slowmode_channels (
+                    channel_id TEXT PRIMARY KEY,
+                    guild_id TEXT NOT NULL,
+                    messages INTEGER NOT NULL,
+                    window_seconds INTEGER NOT NULL,
+                    slowmode_seconds INTEGER NOT NULL,
+                    duration_minutes INTEGER NOT NULL,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-    pub async fn update_message_metadata_reactions(&self, message_id: &str, reactions: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "UPDATE message_metadata SET reactions = ? WHERE message_id = ?"
-        )?;
-        statement.bind((1, reactions))?;
-        statement.bind((2, message_id))?;
-        statement.next()?;
-        Ok(())
-    }
+            // Slowmodes applied automatically and not yet reverted (survives restarts)
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS auto_slowmode_active (
+                    channel_id TEXT PRIMARY KEY,
+                    guild_id TEXT NOT NULL,
+                    previous_seconds INTEGER NOT NULL,
+                    applied_seconds INTEGER NOT NULL,
+                    applied_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    revert_at DATETIME NOT NULL
+                )",
+            )?;
 
-    pub async fn mark_message_deleted(&self, message_id: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "UPDATE message_metadata SET deleted_at = CURRENT_TIMESTAMP WHERE message_id = ?"
-        )?;
-        statement.bind((1, message_id))?;
-        statement.next()?;
-        Ok(())
-    }
+            // Manual moderation actions (/timeout, /kick, /ban)
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS moderation_actions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    target_user_id TEXT NOT NULL,
+                    moderator_id TEXT NOT NULL,
+                    action TEXT NOT NULL,
+                    reason TEXT NOT NULL,
+                    duration_minutes INTEGER,
+                    expires_at DATETIME,
+                    ended_at DATETIME,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-    pub async fn mark_message_edited(&self, message_id: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "UPDATE message_metadata SET edited_at = CURRENT_TIMESTAMP WHERE message_id = ?"
-        )?;
-        statement.bind((1, message_id))?;
-        statement.next()?;
-        Ok(())
-    }
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_moderation_actions_target
+                 ON moderation_actions(guild_id, target_user_id)",
+            )?;
 
-    // Interaction Session Methods
-    pub async fn start_session(&self, user_id: &str, guild_id: Option<&str>) -> Result<i64> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO interaction_sessions (user_id, guild_id) VALUES (?, ?)"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, guild_id.unwrap_or("")))?;
-        statement.next()?;
+            // Appeals against bans and timeouts, filed by DM
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS appeals (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    moderation_action_id INTEGER NOT NULL UNIQUE,
+                    guild_id TEXT NOT NULL,
+                    user_id TEXT NOT NULL,
+                    statement TEXT NOT NULL,
+                    status TEXT NOT NULL DEFAULT 'pending',
+                    reviewer_id TEXT,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    decided_at DATETIME
+                )",
+            )?;
 
-        // Get the last inserted row id
-        let mut stmt = conn.prepare("SELECT last_insert_rowid()")?;
-        stmt.next()?;
-        let session_id = stmt.read::<i64, _>(0)?;
-        Ok(session_id)
-    }
+            // Active lockdowns and the @everyone overwrites to restore when they end
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS lockdowns (
+                    guild_id TEXT PRIMARY KEY,
+                    started_by TEXT NOT NULL,
+                    reason TEXT,
+                    started_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-    pub async fn update_session_activity(&self, session_id: i64) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "UPDATE interaction_sessions
-             SET message_count = message_count + 1, last_activity = CURRENT_TIMESTAMP
-             WHERE id = ?"
-        )?;
-        statement.bind((1, session_id))?;
-        statement.next()?;
-        Ok(())
-    }
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS lockdown_overwrites (
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    had_overwrite INTEGER NOT NULL,
+                    allow_bits INTEGER NOT NULL,
+                    deny_bits INTEGER NOT NULL,
+                    PRIMARY KEY (guild_id, channel_id)
+                )",
+            )?;
 
-    pub async fn end_session(&self, session_id: i64) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "UPDATE interaction_sessions SET session_end = CURRENT_TIMESTAMP WHERE id = ?"
-        )?;
-        statement.bind((1, session_id))?;
-        statement.next()?;
-        Ok(())
-    }
+            // Channels archived with /archive_channel, and whether the original was deleted
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS channel_archives (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    channel_name TEXT NOT NULL,
+                    archive_channel_id TEXT NOT NULL,
+                    archive_message_id TEXT NOT NULL,
+                    message_count INTEGER NOT NULL,
+                    archived_by TEXT NOT NULL,
+                    deleted INTEGER NOT NULL DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
 
-    // User Bookmark Methods
-    pub async fn add_bookmark(
+            // Users, roles and channels left out of conflict detection; channel_id '' means guild-wide
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS conflict_exemptions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL DEFAULT '',
+                    target_type TEXT NOT NULL,
+                    target_id TEXT NOT NULL,
+                    added_by TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(guild_id, channel_id, target_type, target_id)
+                )",
+            )?;
+
+            // Collaborative stories told in a thread with /story, with their tracked state as JSON arrays
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS story_sessions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    thread_id TEXT NOT NULL UNIQUE,
+                    theme TEXT NOT NULL,
+                    persona TEXT NOT NULL,
+                    started_by TEXT NOT NULL,
+                    characters TEXT NOT NULL DEFAULT '[]',
+                    inventory TEXT NOT NULL DEFAULT '[]',
+                    chapter_summaries TEXT NOT NULL DEFAULT '[]',
+                    turns_since_summary INTEGER NOT NULL DEFAULT 0,
+                    status TEXT NOT NULL DEFAULT 'active',
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    ended_at DATETIME
+                )",
+            )?;
+
+            // Running /pomodoro sessions, one per channel; the row is deleted when the session ends
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS focus_sessions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL UNIQUE,
+                    voice_channel_id TEXT,
+                    started_by TEXT NOT NULL,
+                    participants TEXT NOT NULL DEFAULT '[]',
+                    focus_minutes INTEGER NOT NULL,
+                    break_minutes INTEGER NOT NULL,
+                    rounds INTEGER NOT NULL,
+                    current_round INTEGER NOT NULL DEFAULT 1,
+                    phase TEXT NOT NULL DEFAULT 'focus',
+                    phase_ends_at DATETIME NOT NULL,
+                    silence TEXT,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
+
+            // One row per participant for each focus interval completed, for /focus_stats
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS focus_completions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    user_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    minutes INTEGER NOT NULL,
+                    completed_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_focus_completions_guild
+                 ON focus_completions(guild_id, completed_at)",
+            )?;
+
+            // Flagged member joins and their captcha verification state
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS join_screenings (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    user_id TEXT NOT NULL,
+                    score INTEGER NOT NULL,
+                    reasons TEXT NOT NULL,
+                    status TEXT NOT NULL,
+                    answer INTEGER NOT NULL DEFAULT -1,
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    resolved_at DATETIME
+                )",
+            )?;
+
+            // Verification gate prompts for new members
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS verification_gates (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    user_id TEXT NOT NULL,
+                    question TEXT NOT NULL DEFAULT '',
+                    answers TEXT NOT NULL DEFAULT '',
+                    status TEXT NOT NULL DEFAULT 'pending',
+                    channel_id TEXT NOT NULL DEFAULT '',
+                    message_id TEXT NOT NULL DEFAULT '',
+                    kick_at DATETIME,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    resolved_at DATETIME
+                )",
+            )?;
+
+            // Discord <-> Slack channel bridges; last_slack_ts is the newest Slack message relayed
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS bridge_links (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    discord_channel_id TEXT NOT NULL UNIQUE,
+                    slack_channel_id TEXT NOT NULL,
+                    last_slack_ts TEXT NOT NULL,
+                    created_by TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
+
+            // Discord <-> Matrix room bridges
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS matrix_links (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    discord_channel_id TEXT NOT NULL UNIQUE,
+                    matrix_room_id TEXT NOT NULL,
+                    created_by TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
+
+            // External systems allowed to post embeds into a channel through the webhook endpoint
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS webhook_sources (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    name TEXT NOT NULL,
+                    token TEXT NOT NULL,
+                    persona TEXT NOT NULL,
+                    template TEXT,
+                    rate_limit_per_minute INTEGER NOT NULL DEFAULT 30,
+                    created_by TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(guild_id, name)
+                )",
+            )?;
+
+            // iCal feeds announced into channels; filter and lead times are per subscription
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS calendar_subscriptions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    url TEXT NOT NULL,
+                    name TEXT NOT NULL,
+                    filter TEXT,
+                    lead_times TEXT NOT NULL,
+                    utc_offset_minutes INTEGER NOT NULL DEFAULT 0,
+                    last_agenda_date TEXT,
+                    created_by TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
+
+            // Event reminders already posted, so each occurrence is announced once per lead time
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS calendar_announcements (
+                    subscription_id INTEGER NOT NULL,
+                    event_key TEXT NOT NULL,
+                    lead_seconds INTEGER NOT NULL,
+                    sent_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    PRIMARY KEY (subscription_id, event_key, lead_seconds)
+                )",
+            )?;
+
+            // DM Interaction Tracking Tables
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS dm_sessions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    session_id TEXT UNIQUE NOT NULL,
+                    user_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    started_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    last_activity_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    ended_at DATETIME,
+                    end_reason TEXT,
+                    message_count INTEGER DEFAULT 0,
+                    user_message_count INTEGER DEFAULT 0,
+                    bot_message_count INTEGER DEFAULT 0,
+                    total_user_chars INTEGER DEFAULT 0,
+                    total_bot_chars INTEGER DEFAULT 0,
+                    avg_response_time_ms INTEGER
+                )",
+            )?;
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_dm_sessions_user
+                 ON dm_sessions(user_id, started_at DESC)",
+            )?;
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_dm_sessions_active
+                 ON dm_sessions(session_id) WHERE ended_at IS NULL",
+            )?;
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS dm_session_metrics (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    session_id TEXT UNIQUE NOT NULL,
+                    total_api_calls INTEGER DEFAULT 0,
+                    total_tokens INTEGER DEFAULT 0,
+                    total_api_cost_usd REAL DEFAULT 0,
+                    chat_calls INTEGER DEFAULT 0,
+                    whisper_calls INTEGER DEFAULT 0,
+                    dalle_calls INTEGER DEFAULT 0,
+                    audio_transcriptions INTEGER DEFAULT 0,
+                    slash_commands_used INTEGER DEFAULT 0,
+                    conversation_depth INTEGER DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY(session_id) REFERENCES dm_sessions(session_id)
+                )",
+            )?;
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS dm_events (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    session_id TEXT NOT NULL,
+                    event_type TEXT NOT NULL,
+                    user_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    event_data TEXT,
+                    timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY(session_id) REFERENCES dm_sessions(session_id)
+                )",
+            )?;
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_dm_events_session
+                 ON dm_events(session_id, timestamp)",
+            )?;
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_dm_events_type
+                 ON dm_events(event_type, timestamp)",
+            )?;
+
+            // AI responses with regenerate / edit-prompt controls
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS ai_responses (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    user_id TEXT NOT NULL,
+                    guild_id TEXT,
+                    channel_id TEXT NOT NULL,
+                    command TEXT NOT NULL,
+                    prompt TEXT NOT NULL,
+                    regenerations INTEGER DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
+
+            // Answered support questions with embeddings for duplicate detection
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS answered_questions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    question TEXT NOT NULL,
+                    embedding TEXT NOT NULL,
+                    answer_link TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_answered_questions_guild
+                 ON answered_questions(guild_id, created_at)",
+            )?;
+
+            // Help channel questions listed in an unanswered question digest, so each is listed once
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS help_digest_items (
+                    message_id TEXT PRIMARY KEY,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    reported_at INTEGER NOT NULL
+                )",
+            )?;
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_help_digest_guild
+                 ON help_digest_items(guild_id, reported_at)",
+            )?;
+
+            // Past exchanges with embeddings for long-term memory recall
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS conversation_memories (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    user_id TEXT NOT NULL,
+                    guild_id TEXT,
+                    channel_id TEXT NOT NULL,
+                    user_text TEXT NOT NULL,
+                    reply_text TEXT NOT NULL,
+                    embedding TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_conversation_memories_user
+                 ON conversation_memories(user_id, guild_id, created_at)",
+            )?;
+
+            // Rolling summary of conversation turns that fell out of the context window
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS conversation_summaries (
+                    user_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    summary TEXT NOT NULL,
+                    summarized_through INTEGER NOT NULL,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    PRIMARY KEY (user_id, channel_id)
+                )",
+            )?;
+
+            // Threads the bot opened for a conversation; their history is kept per thread
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS conversation_threads (
+                    thread_id TEXT PRIMARY KEY,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    user_id TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
+
+            // Per-thread persona, verbosity and language set with /thread_settings, apart from the parent channel's
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS thread_settings (
+                    thread_id TEXT PRIMARY KEY,
+                    guild_id TEXT NOT NULL,
+                    parent_channel_id TEXT NOT NULL,
+                    persona TEXT,
+                    verbosity TEXT,
+                    language TEXT,
+                    updated_by TEXT NOT NULL,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
+
+            // Queued and finished /import_history backfills, worked through one at a time
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS history_import_jobs (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    requested_by TEXT NOT NULL,
+                    notify_channel_id TEXT NOT NULL,
+                    since TEXT NOT NULL,
+                    before_message_id TEXT,
+                    imported INTEGER DEFAULT 0,
+                    status TEXT NOT NULL DEFAULT 'queued',
+                    error TEXT,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    finished_at DATETIME
+                )",
+            )?;
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_history_import_status
+                 ON history_import_jobs(status, id)",
+            )?;
+
+            // Budget warnings and overruns, each recorded once per budget period and posted by the alert task
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS budget_alerts (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    scope TEXT NOT NULL,
+                    user_id TEXT NOT NULL DEFAULT '',
+                    period TEXT NOT NULL,
+                    level TEXT NOT NULL,
+                    channel_id TEXT NOT NULL DEFAULT '',
+                    spent_usd REAL NOT NULL,
+                    budget_usd REAL NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    sent_at DATETIME,
+                    UNIQUE(guild_id, scope, user_id, period, level)
+                )",
+            )?;
+
+            // Exchanges answered concisely because the user seemed frustrated, kept for review
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS verbosity_adaptations (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL,
+                    user_id TEXT NOT NULL,
+                    message_id TEXT NOT NULL,
+                    signals TEXT NOT NULL,
+                    channel_verbosity TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                )",
+            )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_verbosity_adaptations_guild
+                 ON verbosity_adaptations(guild_id, created_at)",
+            )?;
+
+            // Answers to repeated questions, keyed by a digest of guild, persona, model, verbosity and prompt
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS response_cache (
+                    cache_key TEXT PRIMARY KEY,
+                    guild_id TEXT NOT NULL,
+                    persona TEXT NOT NULL,
+                    model TEXT NOT NULL,
+                    response TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    expires_at DATETIME NOT NULL
+                )",
+            )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_response_cache_expires
+                 ON response_cache(expires_at)",
+            )?;
+
+            // Messages and replies flagged by the content moderation check, DMed to moderators by the alert task
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS moderation_events (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    guild_id TEXT NOT NULL,
+                    channel_id TEXT NOT NULL DEFAULT '',
+                    user_id TEXT NOT NULL DEFAULT '',
+                    direction TEXT NOT NULL,
+                    action TEXT NOT NULL,
+                    categories TEXT NOT NULL,
+                    excerpt TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    notified_at DATETIME
+                )",
+            )?;
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_moderation_events_guild
+                 ON moderation_events(guild_id, created_at)",
+            )?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Apply pending schema migrations. Returns the versions applied.
+    pub async fn migrate(&self) -> Result<Vec<i64>> {
+        self.pool.interact(move |conn| {
+            migrations::run_migrations(conn)
+        })
+        .await
+    }
+
+    /// Highest applied schema migration version
+    pub async fn schema_version(&self) -> Result<i64> {
+        self.pool.interact(move |conn| {
+            migrations::current_version(conn)
+        })
+        .await
+    }
+
+    pub async fn get_user_persona(&self, user_id: &str) -> Result<String> {
+        let user_id = user_id.to_string();
+        self.pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            let mut statement = conn.prepare("SELECT default_persona FROM user_preferences WHERE user_id = ?")?;
+            statement.bind((1, user_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(statement.read::<String, _>("default_persona")?)
+            } else {
+                // Check for PERSONA environment variable, fallback to 'obi'
+                Ok(std::env::var("PERSONA").unwrap_or_else(|_| "obi".to_string()))
+            }
+        })
+        .await
+    }
+
+    /// Get user persona with guild default fallback
+    /// Cascade: user preference -> guild default -> env var -> "obi"
+    pub async fn get_user_persona_with_guild(&self, user_id: &str, guild_id: Option<&str>) -> Result<String> {
+        let (user_id, guild_id) = (user_id.to_string(), guild_id.map(str::to_string));
+        self.pool.interact(move |conn| {
+            let (user_id, guild_id) = (user_id.as_str(), guild_id.as_deref());
+            // First check user preference
+            let mut statement = conn.prepare("SELECT default_persona FROM user_preferences WHERE user_id = ?")?;
+            statement.bind((1, user_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                return statement.read::<String, _>("default_persona");
+            }
+
+            // Check guild default if guild_id is provided
+            if let Some(gid) = guild_id {
+                drop(statement);
+                let mut guild_stmt = conn.prepare(
+                    "SELECT setting_value FROM guild_settings WHERE guild_id = ? AND setting_key = 'default_persona'"
+                )?;
+                guild_stmt.bind((1, gid))?;
+
+                if let Ok(State::Row) = guild_stmt.next() {
+                    return guild_stmt.read::<String, _>(0);
+                }
+            }
+
+            // Fall back to PERSONA environment variable, then 'obi'
+            Ok(std::env::var("PERSONA").unwrap_or_else(|_| "obi".to_string()))
+        })
+        .await
+    }
+
+    pub async fn set_user_persona(&self, user_id: &str, persona: &str) -> Result<()> {
+        let (user_id, persona) = (user_id.to_string(), persona.to_string());
+        self.pool.interact(move |conn| {
+            let (user_id, persona) = (user_id.as_str(), persona.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO user_preferences (user_id, default_persona, updated_at)
+                 VALUES (?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(user_id) DO UPDATE SET default_persona = excluded.default_persona, updated_at = excluded.updated_at"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, persona))?;
+            statement.next()?;
+
+            info!("Updated persona for user {user_id} to {persona}");
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn log_usage(&self, user_id: &str, command: &str, persona: Option<&str>) -> Result<()> {
+        let (user_id, command, persona) = (user_id.to_string(), command.to_string(), persona.map(str::to_string));
+        self.pool.interact(move |conn| {
+            let (user_id, command, persona) = (user_id.as_str(), command.as_str(), persona.as_deref());
+            let mut statement = conn.prepare(
+                "INSERT INTO usage_stats (user_id, command, persona) VALUES (?, ?, ?)"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, command))?;
+            statement.bind((3, persona.unwrap_or("")))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn store_message(&self, user_id: &str, channel_id: &str, role: &str, content: &str, persona: Option<&str>) -> Result<()> {
+        self.store_message_with_id(user_id, channel_id, role, content, persona, None).await
+    }
+
+    /// Store a message along with its Discord message ID so it can be cited later
+    pub async fn store_message_with_id(
+        &self,
+        user_id: &str,
+        channel_id: &str,
+        role: &str,
+        content: &str,
+        persona: Option<&str>,
+        message_id: Option<&str>,
+    ) -> Result<()> {
+        let (user_id, channel_id, role, content, persona, message_id) = (
+            user_id.to_string(),
+            channel_id.to_string(),
+            role.to_string(),
+            content.to_string(),
+            persona.map(str::to_string),
+            message_id.map(str::to_string),
+        );
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (user_id, channel_id, role, content, persona, message_id) = (
+                user_id.as_str(),
+                channel_id.as_str(),
+                role.as_str(),
+                content.as_str(),
+                persona.as_deref(),
+                message_id.as_deref(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO conversation_history (user_id, channel_id, role, content, persona, message_id, bot_name)
+                 VALUES (?, ?, ?, ?, ?, NULLIF(?, ''), ?)"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, role))?;
+            statement.bind((4, content))?;
+            statement.bind((5, persona.unwrap_or("")))?;
+            statement.bind((6, message_id.unwrap_or("")))?;
+            statement.bind((7, db.history_bot.as_deref()))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Store a message imported from a channel's Discord history, with its original time.
+    /// Returns false, storing nothing, when the message is already in `conversation_history`.
+    pub async fn import_history_message(
+        &self,
+        message_id: &str,
+        user_id: &str,
+        channel_id: &str,
+        content: &str,
+        attachment_urls: Option<&str>,
+        timestamp: &str,
+    ) -> Result<bool> {
+        let (message_id, user_id, channel_id, content, attachment_urls, timestamp) = (
+            message_id.to_string(),
+            user_id.to_string(),
+            channel_id.to_string(),
+            content.to_string(),
+            attachment_urls.map(str::to_string),
+            timestamp.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (message_id, user_id, channel_id, content, attachment_urls, timestamp) = (
+                message_id.as_str(),
+                user_id.as_str(),
+                channel_id.as_str(),
+                content.as_str(),
+                attachment_urls.as_deref(),
+                timestamp.as_str(),
+            );
+            let mut existing = conn.prepare("SELECT 1 FROM conversation_history WHERE message_id = ?")?;
+            existing.bind((1, message_id))?;
+            if matches!(existing.next()?, State::Row) {
+                return Ok(false);
+            }
+
+            let mut statement = conn.prepare(
+                "INSERT INTO conversation_history (user_id, channel_id, role, content, persona, message_id, timestamp)
+                 VALUES (?, ?, 'user', ?, '', ?, ?)"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, content))?;
+            statement.bind((4, message_id))?;
+            statement.bind((5, timestamp))?;
+            statement.next()?;
+
+            let mut existing = conn.prepare("SELECT 1 FROM message_metadata WHERE message_id = ?")?;
+            existing.bind((1, message_id))?;
+            if !matches!(existing.next()?, State::Row) {
+                let mut statement = conn.prepare(
+                    "INSERT INTO message_metadata (message_id, user_id, channel_id, attachment_urls, embed_data, reactions, created_at)
+                     VALUES (?, ?, ?, ?, '', '', ?)"
+                )?;
+                statement.bind((1, message_id))?;
+                statement.bind((2, user_id))?;
+                statement.bind((3, channel_id))?;
+                statement.bind((4, attachment_urls.unwrap_or("")))?;
+                statement.bind((5, timestamp))?;
+                statement.next()?;
+            }
+            Ok(true)
+        })
+        .await
+    }
+
+    pub async fn get_conversation_history(&self, user_id: &str, channel_id: &str, limit: i64) -> Result<Vec<(String, String)>> {
+        let (user_id, channel_id) = (user_id.to_string(), channel_id.to_string());
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (user_id, channel_id) = (user_id.as_str(), channel_id.as_str());
+            let (bot_filter, bots) = db.history_bot_filter();
+            let mut statement = conn.prepare(format!(
+                "SELECT role, content FROM conversation_history
+                 WHERE user_id = ? AND channel_id = ? AND thread_id IS NULL{bot_filter}
+                 ORDER BY timestamp DESC
+                 LIMIT ?"
+            ))?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, channel_id))?;
+            for (i, bot) in bots.iter().enumerate() {
+                statement.bind((3 + i, *bot))?;
+            }
+            statement.bind((3 + bots.len(), limit))?;
+
+            let mut history = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let role = statement.read::<String, _>("role")?;
+                let content = statement.read::<String, _>("content")?;
+                history.push((role, content));
+            }
+
+            // Reverse to get chronological order (oldest first)
+            history.reverse();
+            Ok(history)
+        })
+        .await
+    }
+
+    /// Get conversation history including message IDs and timestamps (oldest first)
+    pub async fn get_conversation_history_with_refs(&self, user_id: &str, channel_id: &str, limit: i64) -> Result<Vec<HistoryEntry>> {
+        let (user_id, channel_id) = (user_id.to_string(), channel_id.to_string());
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (user_id, channel_id) = (user_id.as_str(), channel_id.as_str());
+            let (bot_filter, bots) = db.history_bot_filter();
+            let mut statement = conn.prepare(format!(
+                "SELECT role, content, message_id, timestamp FROM conversation_history
+                 WHERE user_id = ? AND channel_id = ? AND thread_id IS NULL{bot_filter}
+                 ORDER BY timestamp DESC
+                 LIMIT ?"
+            ))?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, channel_id))?;
+            for (i, bot) in bots.iter().enumerate() {
+                statement.bind((3 + i, *bot))?;
+            }
+            statement.bind((3 + bots.len(), limit))?;
+
+            let mut history = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                history.push(HistoryEntry {
+                    role: statement.read::<String, _>("role")?,
+                    content: statement.read::<String, _>("content")?,
+                    message_id: statement.read::<Option<String>, _>("message_id")?,
+                    timestamp: statement.read::<String, _>("timestamp")?,
+                });
+            }
+
+            history.reverse();
+            Ok(history)
+        })
+        .await
+    }
+
+    pub async fn clear_conversation_history(&self, user_id: &str, channel_id: &str) -> Result<()> {
+        let (user_id, channel_id) = (user_id.to_string(), channel_id.to_string());
+        self.pool.interact(move |conn| {
+            let (user_id, channel_id) = (user_id.as_str(), channel_id.as_str());
+            for table in ["conversation_history", "conversation_summaries"] {
+                let mut statement = conn.prepare(format!("DELETE FROM {table} WHERE user_id = ? AND channel_id = ?"))?;
+                statement.bind((1, user_id))?;
+                statement.bind((2, channel_id))?;
+                statement.next()?;
+            }
+            info!("Cleared conversation history for user {user_id} in channel {channel_id}");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Conversation turns stored after message `after_id` (oldest first), as (id, role, content)
+    pub async fn get_conversation_turns_after(&self, user_id: &str, channel_id: &str, after_id: i64) -> Result<Vec<(i64, String, String)>> {
+        let (user_id, channel_id) = (user_id.to_string(), channel_id.to_string());
+        self.pool.interact(move |conn| {
+            let (user_id, channel_id) = (user_id.as_str(), channel_id.as_str());
+            let mut statement = conn.prepare(
+                "SELECT id, role, content FROM conversation_history
+                 WHERE user_id = ? AND channel_id = ? AND thread_id IS NULL AND id > ?
+                 ORDER BY id ASC"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, after_id))?;
+
+            let mut turns = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                turns.push((
+                    statement.read::<i64, _>("id")?,
+                    statement.read::<String, _>("role")?,
+                    statement.read::<String, _>("content")?,
+                ));
+            }
+            Ok(turns)
+        })
+        .await
+    }
+
+    pub async fn get_conversation_summary(&self, user_id: &str, channel_id: &str) -> Result<Option<ConversationSummary>> {
+        let (user_id, channel_id) = (user_id.to_string(), channel_id.to_string());
+        self.pool.interact(move |conn| {
+            let (user_id, channel_id) = (user_id.as_str(), channel_id.as_str());
+            let mut statement = conn.prepare(
+                "SELECT summary, summarized_through, updated_at FROM conversation_summaries
+                 WHERE user_id = ? AND channel_id = ?"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, channel_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(ConversationSummary {
+                    summary: statement.read::<String, _>("summary")?,
+                    summarized_through: statement.read::<i64, _>("summarized_through")?,
+                    updated_at: statement.read::<String, _>("updated_at")?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Replace the summary, which now covers turns up to and including `summarized_through`
+    pub async fn store_conversation_summary(&self, user_id: &str, channel_id: &str, summary: &str, summarized_through: i64) -> Result<()> {
+        let (user_id, channel_id, summary) = (user_id.to_string(), channel_id.to_string(), summary.to_string());
+        self.pool.interact(move |conn| {
+            let (user_id, channel_id, summary) = (user_id.as_str(), channel_id.as_str(), summary.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO conversation_summaries (user_id, channel_id, summary, summarized_through, updated_at)
+                 VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(user_id, channel_id) DO UPDATE SET
+                    summary = excluded.summary,
+                    summarized_through = excluded.summarized_through,
+                    updated_at = excluded.updated_at"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, summary))?;
+            statement.bind((4, summarized_through))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Record a thread the bot opened for a conversation in `channel_id`
+    pub async fn create_conversation_thread(&self, thread_id: &str, guild_id: &str, channel_id: &str, user_id: &str) -> Result<()> {
+        let (thread_id, guild_id, channel_id, user_id) = (
+            thread_id.to_string(),
+            guild_id.to_string(),
+            channel_id.to_string(),
+            user_id.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (thread_id, guild_id, channel_id, user_id) = (
+                thread_id.as_str(),
+                guild_id.as_str(),
+                channel_id.as_str(),
+                user_id.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO conversation_threads (thread_id, guild_id, channel_id, user_id)
+                 VALUES (?, ?, ?, ?)
+                 ON CONFLICT(thread_id) DO NOTHING"
+            )?;
+            statement.bind((1, thread_id))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, channel_id))?;
+            statement.bind((4, user_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// The conversation thread with this ID, if the bot opened it
+    pub async fn get_conversation_thread(&self, thread_id: &str) -> Result<Option<ConversationThread>> {
+        let thread_id = thread_id.to_string();
+        self.pool.interact(move |conn| {
+            let thread_id = thread_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT thread_id, guild_id, channel_id, user_id, created_at FROM conversation_threads WHERE thread_id = ?"
+            )?;
+            statement.bind((1, thread_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(ConversationThread {
+                    thread_id: statement.read::<String, _>("thread_id")?,
+                    guild_id: statement.read::<String, _>("guild_id")?,
+                    channel_id: statement.read::<String, _>("channel_id")?,
+                    user_id: statement.read::<String, _>("user_id")?,
+                    created_at: statement.read::<String, _>("created_at")?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Forget a conversation thread and its history (when the thread is deleted)
+    pub async fn delete_conversation_thread(&self, thread_id: &str) -> Result<bool> {
+        let thread_id = thread_id.to_string();
+        self.pool.interact(move |conn| {
+            let thread_id = thread_id.as_str();
+            let mut statement = conn.prepare("DELETE FROM conversation_history WHERE thread_id = ?")?;
+            statement.bind((1, thread_id))?;
+            statement.next()?;
+            let mut statement = conn.prepare("DELETE FROM thread_settings WHERE thread_id = ?")?;
+            statement.bind((1, thread_id))?;
+            statement.next()?;
+            let mut statement = conn.prepare(
+                "UPDATE story_sessions SET status = 'ended', ended_at = CURRENT_TIMESTAMP WHERE thread_id = ? AND status = 'active'"
+            )?;
+            statement.bind((1, thread_id))?;
+            statement.next()?;
+            let mut statement = conn.prepare("DELETE FROM conversation_threads WHERE thread_id = ?")?;
+            statement.bind((1, thread_id))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
+    }
+
+    /// A thread's own settings, if any were set
+    pub async fn get_thread_settings(&self, thread_id: &str) -> Result<Option<ThreadSettings>> {
+        let thread_id = thread_id.to_string();
+        self.pool.interact(move |conn| {
+            let thread_id = thread_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT persona, verbosity, language FROM thread_settings WHERE thread_id = ?"
+            )?;
+            statement.bind((1, thread_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(ThreadSettings {
+                    persona: statement.read::<Option<String>, _>("persona")?,
+                    verbosity: statement.read::<Option<String>, _>("verbosity")?,
+                    language: statement.read::<Option<String>, _>("language")?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Replace a thread's settings; a thread left with none is forgotten
+    pub async fn set_thread_settings(
+        &self,
+        thread_id: &str,
+        guild_id: &str,
+        parent_channel_id: &str,
+        settings: &ThreadSettings,
+        updated_by: &str,
+    ) -> Result<()> {
+        let (thread_id, guild_id, parent_channel_id, settings, updated_by) = (
+            thread_id.to_string(),
+            guild_id.to_string(),
+            parent_channel_id.to_string(),
+            settings.clone(),
+            updated_by.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (thread_id, guild_id, parent_channel_id, settings, updated_by) = (
+                thread_id.as_str(),
+                guild_id.as_str(),
+                parent_channel_id.as_str(),
+                &settings,
+                updated_by.as_str(),
+            );
+            if settings.is_empty() {
+                let mut statement = conn.prepare("DELETE FROM thread_settings WHERE thread_id = ?")?;
+                statement.bind((1, thread_id))?;
+                statement.next()?;
+                return Ok(());
+            }
+            let mut statement = conn.prepare(
+                "INSERT INTO thread_settings (thread_id, guild_id, parent_channel_id, persona, verbosity, language, updated_by, updated_at)
+                 VALUES (?, ?, ?, NULLIF(?, ''), NULLIF(?, ''), NULLIF(?, ''), ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(thread_id) DO UPDATE SET
+                 persona = excluded.persona,
+                 verbosity = excluded.verbosity,
+                 language = excluded.language,
+                 updated_by = excluded.updated_by,
+                 updated_at = CURRENT_TIMESTAMP"
+            )?;
+            statement.bind((1, thread_id))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, parent_channel_id))?;
+            statement.bind((4, settings.persona.as_deref().unwrap_or("")))?;
+            statement.bind((5, settings.verbosity.as_deref().unwrap_or("")))?;
+            statement.bind((6, settings.language.as_deref().unwrap_or("")))?;
+            statement.bind((7, updated_by))?;
+            statement.next()?;
+            info!("Updated settings for thread {thread_id}");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Store a turn of a thread conversation; `channel_id` is the thread's parent channel
+    #[allow(clippy::too_many_arguments)]
+    pub async fn store_thread_message(
+        &self,
+        thread_id: &str,
+        channel_id: &str,
+        user_id: &str,
+        role: &str,
+        content: &str,
+        persona: Option<&str>,
+        message_id: Option<&str>,
+    ) -> Result<()> {
+        let (thread_id, channel_id, user_id, role, content, persona, message_id) = (
+            thread_id.to_string(),
+            channel_id.to_string(),
+            user_id.to_string(),
+            role.to_string(),
+            content.to_string(),
+            persona.map(str::to_string),
+            message_id.map(str::to_string),
+        );
+        self.pool.interact(move |conn| {
+            let (thread_id, channel_id, user_id, role, content, persona, message_id) = (
+                thread_id.as_str(),
+                channel_id.as_str(),
+                user_id.as_str(),
+                role.as_str(),
+                content.as_str(),
+                persona.as_deref(),
+                message_id.as_deref(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO conversation_history (user_id, channel_id, role, content, persona, message_id, thread_id)
+                 VALUES (?, ?, ?, ?, ?, NULLIF(?, ''), ?)"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, role))?;
+            statement.bind((4, content))?;
+            statement.bind((5, persona.unwrap_or("")))?;
+            statement.bind((6, message_id.unwrap_or("")))?;
+            statement.bind((7, thread_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// The latest `limit` turns of a thread conversation from every participant (oldest first)
+    pub async fn get_thread_history(&self, thread_id: &str, limit: i64) -> Result<Vec<(String, String)>> {
+        let thread_id = thread_id.to_string();
+        self.pool.interact(move |conn| {
+            let thread_id = thread_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT role, content FROM conversation_history
+                 WHERE thread_id = ?
+                 ORDER BY id DESC
+                 LIMIT ?"
+            )?;
+            statement.bind((1, thread_id))?;
+            statement.bind((2, limit))?;
+
+            let mut history = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                history.push((statement.read::<String, _>("role")?, statement.read::<String, _>("content")?));
+            }
+            history.reverse();
+            Ok(history)
+        })
+        .await
+    }
+
+    pub async fn cleanup_old_messages(&self, days: i64) -> Result<()> {
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "DELETE FROM conversation_history WHERE timestamp < ?"
+            )?;
+            statement.bind((1, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+            statement.next()?;
+            info!("Cleaned up conversation history older than {days} days");
+            Ok(())
+        })
+        .await
+    }
+
+    // Message Metadata Methods
+    pub async fn store_message_metadata(
+        &self,
+        message_id: &str,
+        user_id: &str,
+        channel_id: &str,
+        attachment_urls: Option<&str>,
+        embed_data: Option<&str>,
+        reactions: Option<&str>,
+    ) -> Result<()> {
+        let (message_id, user_id, channel_id, attachment_urls, embed_data, reactions) = (
+            message_id.to_string(),
+            user_id.to_string(),
+            channel_id.to_string(),
+            attachment_urls.map(str::to_string),
+            embed_data.map(str::to_string),
+            reactions.map(str::to_string),
+        );
+        self.pool.interact(move |conn| {
+            let (message_id, user_id, channel_id, attachment_urls, embed_data, reactions) = (
+                message_id.as_str(),
+                user_id.as_str(),
+                channel_id.as_str(),
+                attachment_urls.as_deref(),
+                embed_data.as_deref(),
+                reactions.as_deref(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO message_metadata (message_id, user_id, channel_id, attachment_urls, embed_data, reactions)
+                 VALUES (?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, message_id))?;
+            statement.bind((2, user_id))?;
+            statement.bind((3, channel_id))?;
+            statement.bind((4, attachment_urls.unwrap_or("")))?;
+            statement.bind((5, embed_data.unwrap_or("")))?;
+            statement.bind((6, reactions.unwrap_or("")))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn update_message_metadata_reactions(&self, message_id: &str, reactions: &str) -> Result<()> {
+        let (message_id, reactions) = (message_id.to_string(), reactions.to_string());
+        self.pool.interact(move |conn| {
+            let (message_id, reactions) = (message_id.as_str(), reactions.as_str());
+            let mut statement = conn.prepare(
+                "UPDATE message_metadata SET reactions = ? WHERE message_id = ?"
+            )?;
+            statement.bind((1, reactions))?;
+            statement.bind((2, message_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn mark_message_deleted(&self, message_id: &str) -> Result<()> {
+        let message_id = message_id.to_string();
+        self.pool.interact(move |conn| {
+            let message_id = message_id.as_str();
+            let mut statement = conn.prepare(
+                "UPDATE message_metadata SET deleted_at = CURRENT_TIMESTAMP WHERE message_id = ?"
+            )?;
+            statement.bind((1, message_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn mark_message_edited(&self, message_id: &str) -> Result<()> {
+        let message_id = message_id.to_string();
+        self.pool.interact(move |conn| {
+            let message_id = message_id.as_str();
+            let mut statement = conn.prepare(
+                "UPDATE message_metadata SET edited_at = CURRENT_TIMESTAMP WHERE message_id = ?"
+            )?;
+            statement.bind((1, message_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Interaction Session Methods
+    pub async fn start_session(&self, user_id: &str, guild_id: Option<&str>) -> Result<i64> {
+        let (user_id, guild_id) = (user_id.to_string(), guild_id.map(str::to_string));
+        self.pool.interact(move |conn| {
+            let (user_id, guild_id) = (user_id.as_str(), guild_id.as_deref());
+            let mut statement = conn.prepare(
+                "INSERT INTO interaction_sessions (user_id, guild_id) VALUES (?, ?)"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, guild_id.unwrap_or("")))?;
+            statement.next()?;
+
+            // Get the last inserted row id
+            let session_id = conn.last_insert_id()?;
+            Ok(session_id)
+        })
+        .await
+    }
+
+    pub async fn update_session_activity(&self, session_id: i64) -> Result<()> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "UPDATE interaction_sessions
+                 SET message_count = message_count + 1, last_activity = CURRENT_TIMESTAMP
+                 WHERE id = ?"
+            )?;
+            statement.bind((1, session_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn end_session(&self, session_id: i64) -> Result<()> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "UPDATE interaction_sessions SET session_end = CURRENT_TIMESTAMP WHERE id = ?"
+            )?;
+            statement.bind((1, session_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    // User Bookmark Methods
+
+    /// Save a message for a user; false when they already bookmarked it
+    pub async fn add_bookmark(
+        &self,
+        user_id: &str,
+        guild_id: Option<&str>,
+        channel_id: &str,
+        message_id: &str,
+        bookmark_name: Option<&str>,
+        bookmark_note: Option<&str>,
+    ) -> Result<bool> {
+        let (user_id, guild_id, channel_id, message_id, bookmark_name, bookmark_note) = (
+            user_id.to_string(),
+            guild_id.map(str::to_string),
+            channel_id.to_string(),
+            message_id.to_string(),
+            bookmark_name.map(str::to_string),
+            bookmark_note.map(str::to_string),
+        );
+        self.pool.interact(move |conn| {
+            let (user_id, guild_id, channel_id, message_id, bookmark_name, bookmark_note) = (
+                user_id.as_str(),
+                guild_id.as_deref(),
+                channel_id.as_str(),
+                message_id.as_str(),
+                bookmark_name.as_deref(),
+                bookmark_note.as_deref(),
+            );
+            let mut existing = conn.prepare(
+                "SELECT 1 FROM user_bookmarks WHERE user_id = ? AND message_id = ?"
+            )?;
+            existing.bind((1, user_id))?;
+            existing.bind((2, message_id))?;
+            if let Ok(State::Row) = existing.next() {
+                return Ok(false);
+            }
+
+            let mut statement = conn.prepare(
+                "INSERT INTO user_bookmarks (user_id, guild_id, channel_id, message_id, bookmark_name, bookmark_note)
+                 VALUES (?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, channel_id))?;
+            statement.bind((4, message_id))?;
+            statement.bind((5, bookmark_name.unwrap_or("")))?;
+            statement.bind((6, bookmark_note.unwrap_or("")))?;
+            statement.next()?;
+            info!("Added bookmark for user {user_id}");
+            Ok(true)
+        })
+        .await
+    }
+
+    /// A user's bookmarks, newest first
+    pub async fn get_user_bookmarks(&self, user_id: &str) -> Result<Vec<Bookmark>> {
+        let user_id = user_id.to_string();
+        self.pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, channel_id, message_id, bookmark_name, bookmark_note, created_at
+                 FROM user_bookmarks WHERE user_id = ?
+                 ORDER BY created_at DESC, id DESC"
+            )?;
+            statement.bind((1, user_id))?;
+
+            let mut bookmarks = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                bookmarks.push(Bookmark {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<Option<String>, _>(1)?,
+                    channel_id: statement.read::<String, _>(2)?,
+                    message_id: statement.read::<String, _>(3)?,
+                    name: statement.read::<Option<String>, _>(4)?.unwrap_or_default(),
+                    note: statement.read::<Option<String>, _>(5)?.filter(|n| !n.is_empty()),
+                    created_at: statement.read::<String, _>(6)?,
+                });
+            }
+            Ok(bookmarks)
+        })
+        .await
+    }
+
+    /// Delete one of a user's bookmarks by id; false when they have no such bookmark
+    pub async fn delete_bookmark(&self, user_id: &str, bookmark_id: i64) -> Result<bool> {
+        let user_id = user_id.to_string();
+        self.pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            let mut statement = conn.prepare(
+                "DELETE FROM user_bookmarks WHERE user_id = ? AND id = ?"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, bookmark_id))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
+    }
+
+    // Answered Question Methods
+
+    /// Record an answered support question and its embedding
+    pub async fn store_answered_question(
+        &self,
+        guild_id: &str,
+        channel_id: &str,
+        question: &str,
+        embedding: &str,
+        answer_link: &str,
+        answer: &str,
+    ) -> Result<()> {
+        let pool = self.guild_pool(Some(guild_id)).await?;
+        let (guild_id, channel_id, question, embedding, answer_link, answer) = (
+            guild_id.to_string(),
+            channel_id.to_string(),
+            question.to_string(),
+            embedding.to_string(),
+            answer_link.to_string(),
+            answer.to_string(),
+        );
+        pool.interact(move |conn| {
+            let (guild_id, channel_id, question, embedding, answer_link, answer) = (
+                guild_id.as_str(),
+                channel_id.as_str(),
+                question.as_str(),
+                embedding.as_str(),
+                answer_link.as_str(),
+                answer.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO answered_questions (guild_id, channel_id, question, embedding, answer_link, answer)
+                 VALUES (?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, question))?;
+            statement.bind((4, embedding))?;
+            statement.bind((5, answer_link))?;
+            statement.bind((6, answer))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Get the most recent answered questions for a guild (newest first)
+    pub async fn get_answered_questions(&self, guild_id: &str, limit: i64) -> Result<Vec<AnsweredQuestion>> {
+        let pool = self.guild_pool(Some(guild_id)).await?;
+        let guild_id = guild_id.to_string();
+        pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT question, embedding, answer_link, created_at FROM answered_questions
+                 WHERE guild_id = ?
+                 ORDER BY created_at DESC, id DESC
+                 LIMIT ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, limit))?;
+
+            let mut questions = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                questions.push(AnsweredQuestion {
+                    question: statement.read::<String, _>(0)?,
+                    embedding: statement.read::<String, _>(1)?,
+                    answer_link: statement.read::<String, _>(2)?,
+                    created_at: statement.read::<String, _>(3)?,
+                });
+            }
+            Ok(questions)
+        })
+        .await
+    }
+
+    /// Get answered questions not yet exported to the guild's knowledge base (oldest first)
+    /// When the guild's last unanswered question digest was posted (unix seconds)
+    pub async fn get_last_help_digest(&self, guild_id: &str) -> Result<Option<i64>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare("SELECT MAX(reported_at) FROM help_digest_items WHERE guild_id = ?")?;
+            statement.bind((1, guild_id))?;
+            statement.next()?;
+            statement.read::<Option<i64>, _>(0)
+        })
+        .await
+    }
+
+    /// Whether a help channel question was already listed in a digest
+    pub async fn is_help_question_reported(&self, message_id: &str) -> Result<bool> {
+        let message_id = message_id.to_string();
+        self.pool.interact(move |conn| {
+            let message_id = message_id.as_str();
+            let mut statement = conn.prepare("SELECT 1 FROM help_digest_items WHERE message_id = ?")?;
+            statement.bind((1, message_id))?;
+            Ok(matches!(statement.next()?, State::Row))
+        })
+        .await
+    }
+
+    /// Record the `(channel_id, message_id)` questions listed in a digest posted at `reported_at`
+    pub async fn record_help_digest(&self, guild_id: &str, questions: &[(String, String)], reported_at: i64) -> Result<()> {
+        let (guild_id, questions) = (guild_id.to_string(), questions.to_vec());
+        self.pool.interact(move |conn| {
+            let (guild_id, questions) = (guild_id.as_str(), questions.as_slice());
+            for (channel_id, message_id) in questions {
+                let mut statement = conn.prepare(
+                    "INSERT INTO help_digest_items (message_id, guild_id, channel_id, reported_at)
+                     VALUES (?, ?, ?, ?)
+                     ON CONFLICT DO NOTHING"
+                )?;
+                statement.bind((1, message_id.as_str()))?;
+                statement.bind((2, guild_id))?;
+                statement.bind((3, channel_id.as_str()))?;
+                statement.bind((4, reported_at))?;
+                statement.next()?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Queue a history import for a channel, unless one is already queued or running for it.
+    /// `since` is the oldest message time to import. Returns the new job's ID.
+    pub async fn enqueue_history_import(
+        &self,
+        guild_id: &str,
+        channel_id: &str,
+        requested_by: &str,
+        notify_channel_id: &str,
+        since: &str,
+    ) -> Result<Option<i64>> {
+        let (guild_id, channel_id, requested_by, notify_channel_id, since) = (
+            guild_id.to_string(),
+            channel_id.to_string(),
+            requested_by.to_string(),
+            notify_channel_id.to_string(),
+            since.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, requested_by, notify_channel_id, since) = (
+                guild_id.as_str(),
+                channel_id.as_str(),
+                requested_by.as_str(),
+                notify_channel_id.as_str(),
+                since.as_str(),
+            );
+            let mut active = conn.prepare(
+                "SELECT 1 FROM history_import_jobs WHERE channel_id = ? AND status IN ('queued', 'running')"
+            )?;
+            active.bind((1, channel_id))?;
+            if matches!(active.next()?, State::Row) {
+                return Ok(None);
+            }
+
+            let mut statement = conn.prepare(
+                "INSERT INTO history_import_jobs (guild_id, channel_id, requested_by, notify_channel_id, since)
+                 VALUES (?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, requested_by))?;
+            statement.bind((4, notify_channel_id))?;
+            statement.bind((5, since))?;
+            statement.next()?;
+
+            Ok(Some(conn.last_insert_id()?))
+        })
+        .await
+    }
+
+    /// Unfinished history imports queued before a job, which run first
+    pub async fn history_imports_ahead(&self, id: i64) -> Result<i64> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT COUNT(*) FROM history_import_jobs WHERE id < ? AND status IN ('queued', 'running')"
+            )?;
+            statement.bind((1, id))?;
+            statement.next()?;
+            statement.read::<i64, _>(0)
+        })
+        .await
+    }
+
+    /// The history import to work on next: an interrupted running one, else the oldest queued one
+    pub async fn next_history_import(&self) -> Result<Option<HistoryImportJob>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, channel_id, requested_by, notify_channel_id, since, before_message_id, imported
+                 FROM history_import_jobs
+                 WHERE status IN ('queued', 'running')
+                 ORDER BY status = 'running' DESC, id ASC
+                 LIMIT 1"
+            )?;
+            if !matches!(statement.next()?, State::Row) {
+                return Ok(None);
+            }
+            Ok(Some(HistoryImportJob {
+                id: statement.read::<i64, _>(0)?,
+                guild_id: statement.read::<String, _>(1)?,
+                channel_id: statement.read::<String, _>(2)?,
+                requested_by: statement.read::<String, _>(3)?,
+                notify_channel_id: statement.read::<String, _>(4)?,
+                since: statement.read::<String, _>(5)?,
+                before_message_id: statement.read::<Option<String>, _>(6)?,
+                imported: statement.read::<i64, _>(7)?,
+            }))
+        })
+        .await
+    }
+
+    /// Mark a history import running and record how far back it has read
+    pub async fn update_history_import_progress(&self, id: i64, before_message_id: Option<&str>, imported: i64) -> Result<()> {
+        let before_message_id = before_message_id.map(str::to_string);
+        self.pool.interact(move |conn| {
+            let before_message_id = before_message_id.as_deref();
+            let mut statement = conn.prepare(
+                "UPDATE history_import_jobs SET status = 'running', before_message_id = ?, imported = ? WHERE id = ?"
+            )?;
+            statement.bind((1, before_message_id))?;
+            statement.bind((2, imported))?;
+            statement.bind((3, id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Finish a history import as `done` or `failed`
+    pub async fn finish_history_import(&self, id: i64, status: &str, error: Option<&str>) -> Result<()> {
+        let (status, error) = (status.to_string(), error.map(str::to_string));
+        self.pool.interact(move |conn| {
+            let (status, error) = (status.as_str(), error.as_deref());
+            let mut statement = conn.prepare(
+                "UPDATE history_import_jobs SET status = ?, error = ?, finished_at = CURRENT_TIMESTAMP WHERE id = ?"
+            )?;
+            statement.bind((1, status))?;
+            statement.bind((2, error))?;
+            statement.bind((3, id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_unexported_answered_questions(&self, guild_id: &str, limit: i64) -> Result<Vec<KnowledgeEntry>> {
+        let pool = self.guild_pool(Some(guild_id)).await?;
+        let guild_id = guild_id.to_string();
+        pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT id, question, answer, answer_link, created_at FROM answered_questions
+                 WHERE guild_id = ? AND exported_at IS NULL
+                 ORDER BY id ASC
+                 LIMIT ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, limit))?;
+
+            let mut entries = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                entries.push(KnowledgeEntry {
+                    id: statement.read::<i64, _>(0)?,
+                    question: statement.read::<String, _>(1)?,
+                    answer: statement.read::<Option<String>, _>(2)?,
+                    answer_link: statement.read::<String, _>(3)?,
+                    created_at: statement.read::<String, _>(4)?,
+                });
+            }
+            Ok(entries)
+        })
+        .await
+    }
+
+    /// Mark one of a guild's answered questions as exported to the knowledge base
+    pub async fn mark_answered_question_exported(&self, guild_id: &str, id: i64) -> Result<()> {
+        let pool = self.guild_pool(Some(guild_id)).await?;
+        pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "UPDATE answered_questions SET exported_at = CURRENT_TIMESTAMP WHERE id = ?"
+            )?;
+            statement.bind((1, id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Conversation Memory Methods
+
+    /// Record an exchange and the embedding of the user's message for later recall
+    pub async fn store_memory(
+        &self,
+        user_id: &str,
+        guild_id: Option<&str>,
+        channel_id: &str,
+        user_text: &str,
+        reply_text: &str,
+        embedding: &str,
+    ) -> Result<()> {
+        let pool = self.guild_pool(guild_id).await?;
+        let (user_id, guild_id, channel_id, user_text, reply_text, embedding) = (
+            user_id.to_string(),
+            guild_id.map(str::to_string),
+            channel_id.to_string(),
+            user_text.to_string(),
+            reply_text.to_string(),
+            embedding.to_string(),
+        );
+        pool.interact(move |conn| {
+            let (user_id, guild_id, channel_id, user_text, reply_text, embedding) = (
+                user_id.as_str(),
+                guild_id.as_deref(),
+                channel_id.as_str(),
+                user_text.as_str(),
+                reply_text.as_str(),
+                embedding.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO conversation_memories (user_id, guild_id, channel_id, user_text, reply_text, embedding)
+                 VALUES (?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, channel_id))?;
+            statement.bind((4, user_text))?;
+            statement.bind((5, reply_text))?;
+            statement.bind((6, embedding))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Get a user's most recent memories from one guild, or from DMs when `guild_id` is None (newest first)
+    pub async fn get_memories(&self, user_id: &str, guild_id: Option<&str>, limit: i64) -> Result<Vec<MemoryEntry>> {
+        let pool = self.guild_pool(guild_id).await?;
+        let (user_id, guild_id) = (user_id.to_string(), guild_id.map(str::to_string));
+        pool.interact(move |conn| {
+            let (user_id, guild_id) = (user_id.as_str(), guild_id.as_deref());
+            let mut statement = conn.prepare(
+                "SELECT id, channel_id, user_text, reply_text, embedding, created_at FROM conversation_memories
+                 WHERE user_id = ? AND COALESCE(guild_id, '') = ?
+                 ORDER BY created_at DESC, id DESC
+                 LIMIT ?"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, guild_id.unwrap_or("")))?;
+            statement.bind((3, limit))?;
+
+            let mut memories = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                memories.push(MemoryEntry {
+                    id: statement.read::<i64, _>(0)?,
+                    channel_id: statement.read::<String, _>(1)?,
+                    user_text: statement.read::<String, _>(2)?,
+                    reply_text: statement.read::<String, _>(3)?,
+                    embedding: statement.read::<String, _>(4)?,
+                    created_at: statement.read::<String, _>(5)?,
+                });
+            }
+            Ok(memories)
+        })
+        .await
+    }
+
+    /// Delete one of a user's memories, as listed in `guild_id` (None for DMs). Returns false if
+    /// it doesn't exist or isn't theirs.
+    pub async fn delete_memory(&self, user_id: &str, guild_id: Option<&str>, memory_id: i64) -> Result<bool> {
+        let pool = self.guild_pool(guild_id).await?;
+        let user_id = user_id.to_string();
+        pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            let mut statement = conn.prepare(
+                "DELETE FROM conversation_memories WHERE id = ? AND user_id = ?"
+            )?;
+            statement.bind((1, memory_id))?;
+            statement.bind((2, user_id))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
+    }
+
+    /// Delete all of a user's memories everywhere. Returns how many were removed.
+    pub async fn delete_all_memories(&self, user_id: &str) -> Result<i64> {
+        let mut removed = 0;
+        for pool in self.all_pools().await? {
+            let user_id = user_id.to_string();
+            removed += pool.interact(move |conn| {
+                let mut statement = conn.prepare(
+                    "DELETE FROM conversation_memories WHERE user_id = ?"
+                )?;
+                statement.bind((1, user_id.as_str()))?;
+                statement.next()?;
+                drop(statement);
+                conn.changes()
+            })
+            .await?;
+        }
+        info!("Deleted {removed} memories for user {user_id}");
+        Ok(removed)
+    }
+
+    // AI Response Methods
+
+    /// Record the prompt behind an AI reply so it can be regenerated or edited
+    pub async fn create_ai_response(
+        &self,
+        user_id: &str,
+        guild_id: Option<&str>,
+        channel_id: &str,
+        command: &str,
+        prompt: &str,
+        creativity: Option<&str>,
+    ) -> Result<i64> {
+        let (user_id, guild_id, channel_id, command, prompt, creativity) = (
+            user_id.to_string(),
+            guild_id.map(str::to_string),
+            channel_id.to_string(),
+            command.to_string(),
+            prompt.to_string(),
+            creativity.map(str::to_string),
+        );
+        self.pool.interact(move |conn| {
+            let (user_id, guild_id, channel_id, command, prompt, creativity) = (
+                user_id.as_str(),
+                guild_id.as_deref(),
+                channel_id.as_str(),
+                command.as_str(),
+                prompt.as_str(),
+                creativity.as_deref(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO ai_responses (user_id, guild_id, channel_id, command, prompt, creativity)
+                 VALUES (?, NULLIF(?, ''), ?, ?, ?, NULLIF(?, ''))"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, guild_id.unwrap_or("")))?;
+            statement.bind((3, channel_id))?;
+            statement.bind((4, command))?;
+            statement.bind((5, prompt))?;
+            statement.bind((6, creativity.unwrap_or("")))?;
+            statement.next()?;
+
+            conn.last_insert_id()
+        })
+        .await
+    }
+
+    pub async fn get_ai_response_record(&self, response_id: i64) -> Result<Option<AiResponseRecord>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, user_id, guild_id, channel_id, command, prompt, regenerations, creativity
+                 FROM ai_responses WHERE id = ?"
+            )?;
+            statement.bind((1, response_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(AiResponseRecord {
+                    id: statement.read::<i64, _>(0)?,
+                    user_id: statement.read::<String, _>(1)?,
+                    guild_id: statement.read::<Option<String>, _>(2)?,
+                    channel_id: statement.read::<String, _>(3)?,
+                    command: statement.read::<String, _>(4)?,
+                    prompt: statement.read::<String, _>(5)?,
+                    regenerations: statement.read::<i64, _>(6)?,
+                    creativity: statement.read::<Option<String>, _>(7)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Count one regeneration against a response unless it already has `max`,
+    /// returning the new count or `None` when the limit was reached
+    pub async fn claim_ai_response_regeneration(&self, response_id: i64, max: i64) -> Result<Option<i64>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "UPDATE ai_responses SET regenerations = regenerations + 1 WHERE id = ? AND regenerations < ?"
+            )?;
+            statement.bind((1, response_id))?;
+            statement.bind((2, max))?;
+            statement.next()?;
+
+            if conn.changes()? == 0 {
+                return Ok(None);
+            }
+
+            let mut stmt = conn.prepare("SELECT regenerations FROM ai_responses WHERE id = ?")?;
+            stmt.bind((1, response_id))?;
+            stmt.next()?;
+            Ok(Some(stmt.read::<i64, _>(0)?))
+        })
+        .await
+    }
+
+    // Reminder Methods
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_reminder(
+        &self,
+        user_id: &str,
+        channel_id: &str,
+        guild_id: Option<&str>,
+        reminder_text: &str,
+        remind_at: &str,
+        source_message_link: Option<&str>,
+        source_snippet: Option<&str>,
+        urgent: bool,
+    ) -> Result<i64> {
+        let (user_id, channel_id, guild_id, reminder_text, remind_at, source_message_link, source_snippet) = (
+            user_id.to_string(),
+            channel_id.to_string(),
+            guild_id.map(str::to_string),
+            reminder_text.to_string(),
+            remind_at.to_string(),
+            source_message_link.map(str::to_string),
+            source_snippet.map(str::to_string),
+        );
+        self.pool.interact(move |conn| {
+            let (user_id, channel_id, guild_id, reminder_text, remind_at, source_message_link, source_snippet) = (
+                user_id.as_str(),
+                channel_id.as_str(),
+                guild_id.as_deref(),
+                reminder_text.as_str(),
+                remind_at.as_str(),
+                source_message_link.as_deref(),
+                source_snippet.as_deref(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO reminders (user_id, channel_id, guild_id, reminder_text, remind_at, source_message_link, source_snippet, urgent)
+                 VALUES (?, ?, NULLIF(?, ''), ?, ?, NULLIF(?, ''), NULLIF(?, ''), ?)"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, guild_id.unwrap_or("")))?;
+            statement.bind((4, reminder_text))?;
+            statement.bind((5, remind_at))?;
+            statement.bind((6, source_message_link.unwrap_or("")))?;
+            statement.bind((7, source_snippet.unwrap_or("")))?;
+            statement.bind((8, if urgent { 1i64 } else { 0i64 }))?;
+            statement.next()?;
+
+            let reminder_id = conn.last_insert_id()?;
+            info!("Added reminder {reminder_id} for user {user_id}");
+            Ok(reminder_id)
+        })
+        .await
+    }
+
+    pub async fn get_pending_reminders(&self) -> Result<Vec<PendingReminder>> {
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, user_id, channel_id, reminder_text, source_message_link, source_snippet, guild_id, urgent
+                 FROM reminders
+                 WHERE completed = 0 AND remind_at <= ?
+                 ORDER BY remind_at ASC"
+            )?;
+            statement.bind((1, db.now().as_str()))?;
+
+            let mut reminders = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                reminders.push(PendingReminder {
+                    id: statement.read::<i64, _>(0)?,
+                    user_id: statement.read::<String, _>(1)?,
+                    channel_id: statement.read::<String, _>(2)?,
+                    reminder_text: statement.read::<String, _>(3)?,
+                    source_message_link: statement.read::<Option<String>, _>(4)?,
+                    source_snippet: statement.read::<Option<String>, _>(5)?,
+                    guild_id: statement.read::<Option<String>, _>(6)?,
+                    urgent: statement.read::<Option<i64>, _>(7)?.unwrap_or(0) != 0,
+                });
+            }
+            Ok(reminders)
+        })
+        .await
+    }
+
+    /// Reminders not yet delivered, due or not
+    pub async fn count_open_reminders(&self) -> Result<i64> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare("SELECT COUNT(*) FROM reminders WHERE completed = 0")?;
+            statement.next()?;
+            statement.read::<i64, _>(0)
+        })
+        .await
+    }
+
+    pub async fn complete_reminder(&self, reminder_id: i64) -> Result<()> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "UPDATE reminders SET completed = 1, completed_at = CURRENT_TIMESTAMP WHERE id = ?"
+            )?;
+            statement.bind((1, reminder_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Move a pending reminder to a later delivery time (e.g. the end of quiet hours)
+    pub async fn reschedule_reminder(&self, reminder_id: i64, remind_at: &str) -> Result<()> {
+        let remind_at = remind_at.to_string();
+        self.pool.interact(move |conn| {
+            let remind_at = remind_at.as_str();
+            let mut statement = conn.prepare(
+                "UPDATE reminders SET remind_at = ? WHERE id = ? AND completed = 0"
+            )?;
+            statement.bind((1, remind_at))?;
+            statement.bind((2, reminder_id))?;
+            statement.next()?;
+            info!("Rescheduled reminder {reminder_id} to {remind_at}");
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_user_reminders(&self, user_id: &str) -> Result<Vec<(i64, String, String, String)>> {
+        let user_id = user_id.to_string();
+        self.pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT id, channel_id, reminder_text, remind_at
+                 FROM reminders
+                 WHERE user_id = ? AND completed = 0
+                 ORDER BY remind_at ASC"
+            )?;
+            statement.bind((1, user_id))?;
+
+            let mut reminders = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let id = statement.read::<i64, _>(0)?;
+                let channel_id = statement.read::<String, _>(1)?;
+                let reminder_text = statement.read::<String, _>(2)?;
+                let remind_at = statement.read::<String, _>(3)?;
+                reminders.push((id, channel_id, reminder_text, remind_at));
+            }
+            Ok(reminders)
+        })
+        .await
+    }
+
+    pub async fn delete_reminder(&self, reminder_id: i64, user_id: &str) -> Result<bool> {
+        let user_id = user_id.to_string();
+        self.pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            let mut statement = conn.prepare(
+                "DELETE FROM reminders WHERE id = ? AND user_id = ?"
+            )?;
+            statement.bind((1, reminder_id))?;
+            statement.bind((2, user_id))?;
+            statement.next()?;
+
+            // Check if a row was actually deleted
+            let changes = conn.changes()?;
+
+            if changes > 0 {
+                info!("Deleted reminder {reminder_id} for user {user_id}");
+                Ok(true)
+            } else {
+                Ok(false)
+            }
+        })
+        .await
+    }
+
+    // Custom Command Methods
+    pub async fn add_custom_command(
+        &self,
+        command_name: &str,
+        response_text: &str,
+        created_by_user_id: &str,
+        guild_id: Option<&str>,
+    ) -> Result<()> {
+        let (command_name, response_text, created_by_user_id, guild_id) = (
+            command_name.to_string(),
+            response_text.to_string(),
+            created_by_user_id.to_string(),
+            guild_id.map(str::to_string),
+        );
+        self.pool.interact(move |conn| {
+            let (command_name, response_text, created_by_user_id, guild_id) = (
+                command_name.as_str(),
+                response_text.as_str(),
+                created_by_user_id.as_str(),
+                guild_id.as_deref(),
+            );
+            let is_global = guild_id.is_none();
+            let mut statement = conn.prepare(
+                "INSERT INTO custom_commands (command_name, response_text, created_by_user_id, guild_id, is_global, updated_at)
+                 VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(command_name, guild_id) DO UPDATE SET
+                 response_text = excluded.response_text, created_by_user_id = excluded.created_by_user_id,
+                 is_global = excluded.is_global, updated_at = excluded.updated_at"
+            )?;
+            statement.bind((1, command_name))?;
+            statement.bind((2, response_text))?;
+            statement.bind((3, created_by_user_id))?;
+            statement.bind((4, guild_id.unwrap_or("")))?;
+            statement.bind((5, if is_global { 1i64 } else { 0i64 }))?;
+            statement.next()?;
+            info!("Added custom command: {command_name}");
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_custom_command(&self, command_name: &str, guild_id: Option<&str>) -> Result<Option<String>> {
+        let (command_name, guild_id) = (command_name.to_string(), guild_id.map(str::to_string));
+        self.pool.interact(move |conn| {
+            let (command_name, guild_id) = (command_name.as_str(), guild_id.as_deref());
+            let mut statement = conn.prepare(
+                "SELECT response_text FROM custom_commands
+                 WHERE command_name = ? AND (guild_id = ? OR is_global = 1)
+                 ORDER BY is_global ASC
+                 LIMIT 1"
+            )?;
+            statement.bind((1, command_name))?;
+            statement.bind((2, guild_id.unwrap_or("")))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(statement.read::<String, _>(0)?))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Remove a custom command. Returns false if there was none.
+    pub async fn delete_custom_command(&self, command_name: &str, guild_id: Option<&str>) -> Result<bool> {
+        let (command_name, guild_id) = (command_name.to_string(), guild_id.map(str::to_string));
+        self.pool.interact(move |conn| {
+            let (command_name, guild_id) = (command_name.as_str(), guild_id.as_deref());
+            let mut statement = conn.prepare(
+                "DELETE FROM custom_commands WHERE command_name = ? AND guild_id = ?"
+            )?;
+            statement.bind((1, command_name))?;
+            statement.bind((2, guild_id.unwrap_or("")))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
+    }
+
+    /// Custom commands usable in a guild, its own and global ones, sorted by name
+    pub async fn list_custom_commands(&self, guild_id: &str) -> Result<Vec<CustomCommand>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT command_name, response_text, created_by_user_id, is_global FROM custom_commands
+                 WHERE guild_id = ? OR is_global = 1
+                 ORDER BY command_name, is_global"
+            )?;
+            statement.bind((1, guild_id))?;
+
+            let mut results: Vec<CustomCommand> = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let name = statement.read::<String, _>(0)?;
+                // A guild command hides the global one with the same name
+                if results.last().is_some_and(|c| c.name == name) {
+                    continue;
+                }
+                results.push(CustomCommand {
+                    name,
+                    response: statement.read::<String, _>(1)?,
+                    created_by: statement.read::<String, _>(2)?,
+                    is_global: statement.read::<i64, _>(3)? == 1,
+                });
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    // Custom Persona Methods
+
+    /// Create a custom persona, or update it if the key exists. Sharing is kept on update.
+    pub async fn save_custom_persona(&self, persona: &CustomPersona) -> Result<()> {
+        let persona = persona.clone();
+        self.pool.interact(move |conn| {
+            let persona = &persona;
+            let mut statement = conn.prepare(
+                "INSERT INTO custom_personas (persona_key, guild_id, name, description, system_prompt, temperature, emoji, created_by_user_id, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(persona_key) DO UPDATE SET
+                 name = excluded.name, description = excluded.description, system_prompt = excluded.system_prompt,
+                 temperature = excluded.temperature, emoji = excluded.emoji, updated_at = excluded.updated_at"
+            )?;
+            statement.bind((1, persona.key.as_str()))?;
+            statement.bind((2, persona.guild_id.as_str()))?;
+            statement.bind((3, persona.name.as_str()))?;
+            statement.bind((4, persona.description.as_str()))?;
+            statement.bind((5, persona.system_prompt.as_str()))?;
+            statement.bind((6, persona.temperature.map(f64::from)))?;
+            statement.bind((7, persona.emoji.as_deref()))?;
+            statement.bind((8, persona.created_by.as_str()))?;
+            statement.next()?;
+            info!("Saved custom persona {} for guild {}", persona.key, persona.guild_id);
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_custom_persona(&self, key: &str) -> Result<Option<CustomPersona>> {
+        let key = key.to_string();
+        self.pool.interact(move |conn| {
+            let key = key.as_str();
+            let mut statement = conn.prepare(
+                "SELECT persona_key, guild_id, name, description, system_prompt, temperature, emoji, shared, created_by_user_id
+                 FROM custom_personas WHERE persona_key = ?"
+            )?;
+            statement.bind((1, key))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(read_custom_persona(&statement)?))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Every custom persona, for loading into the persona manager at startup
+    pub async fn get_custom_personas(&self) -> Result<Vec<CustomPersona>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT persona_key, guild_id, name, description, system_prompt, temperature, emoji, shared, created_by_user_id
+                 FROM custom_personas ORDER BY persona_key"
+            )?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(read_custom_persona(&statement)?);
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Number of custom personas a guild has defined
+    pub async fn count_custom_personas(&self, guild_id: &str) -> Result<i64> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare("SELECT COUNT(*) FROM custom_personas WHERE guild_id = ?")?;
+            statement.bind((1, guild_id))?;
+            statement.next()?;
+            statement.read::<i64, _>(0)
+        })
+        .await
+    }
+
+    /// Offer a guild's persona to other guilds, or stop. Returns false if the guild has no such persona.
+    pub async fn set_custom_persona_shared(&self, key: &str, guild_id: &str, shared: bool) -> Result<bool> {
+        let (key, guild_id) = (key.to_string(), guild_id.to_string());
+        self.pool.interact(move |conn| {
+            let (key, guild_id) = (key.as_str(), guild_id.as_str());
+            let mut statement = conn.prepare(
+                "UPDATE custom_personas SET shared = ?, updated_at = CURRENT_TIMESTAMP WHERE persona_key = ? AND guild_id = ?"
+            )?;
+            statement.bind((1, if shared { 1i64 } else { 0i64 }))?;
+            statement.bind((2, key))?;
+            statement.bind((3, guild_id))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
+    }
+
+    /// Remove a guild's persona. Returns false if the guild has no such persona.
+    pub async fn delete_custom_persona(&self, key: &str, guild_id: &str) -> Result<bool> {
+        let (key, guild_id) = (key.to_string(), guild_id.to_string());
+        self.pool.interact(move |conn| {
+            let (key, guild_id) = (key.as_str(), guild_id.as_str());
+            let mut statement = conn.prepare(
+                "DELETE FROM custom_personas WHERE persona_key = ? AND guild_id = ?"
+            )?;
+            statement.bind((1, key))?;
+            statement.bind((2, guild_id))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
+    }
+
+    /// Store a linked account's tokens and admin guilds, keeping when it was first linked
+    pub async fn save_linked_account(&self, account: &LinkedAccount) -> Result<()> {
+        let account = account.clone();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let account = &account;
+            let now = db.now();
+            let mut statement = conn.prepare(
+                "INSERT INTO linked_accounts (user_id, username, access_token, refresh_token, scope, expires_at, admin_guild_ids, verified_at, linked_at, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(user_id) DO UPDATE SET
+                 username = excluded.username, access_token = excluded.access_token, refresh_token = excluded.refresh_token,
+                 scope = excluded.scope, expires_at = excluded.expires_at, admin_guild_ids = excluded.admin_guild_ids,
+                 verified_at = excluded.verified_at, updated_at = excluded.updated_at"
+            )?;
+            statement.bind((1, account.user_id.as_str()))?;
+            statement.bind((2, account.username.as_str()))?;
+            statement.bind((3, account.access_token.as_str()))?;
+            statement.bind((4, account.refresh_token.as_str()))?;
+            statement.bind((5, account.scope.as_str()))?;
+            statement.bind((6, account.expires_at.as_str()))?;
+            statement.bind((7, account.admin_guild_ids.join(",").as_str()))?;
+            statement.bind((8, account.verified_at.as_deref()))?;
+            statement.bind((9, now.as_str()))?;
+            statement.bind((10, now.as_str()))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_linked_account(&self, user_id: &str) -> Result<Option<LinkedAccount>> {
+        let user_id = user_id.to_string();
+        self.pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT user_id, username, access_token, refresh_token, scope, expires_at, admin_guild_ids, verified_at, linked_at
+                 FROM linked_accounts WHERE user_id = ?"
+            )?;
+            statement.bind((1, user_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(LinkedAccount {
+                    user_id: statement.read::<String, _>(0)?,
+                    username: statement.read::<String, _>(1)?,
+                    access_token: statement.read::<String, _>(2)?,
+                    refresh_token: statement.read::<String, _>(3)?,
+                    scope: statement.read::<String, _>(4)?,
+                    expires_at: statement.read::<String, _>(5)?,
+                    admin_guild_ids: statement
+                        .read::<String, _>(6)?
+                        .split(',')
+                        .filter(|id| !id.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                    verified_at: statement.read::<Option<String>, _>(7)?,
+                    linked_at: statement.read::<String, _>(8)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Forget a linked account and its tokens. Returns false if it wasn't linked.
+    pub async fn delete_linked_account(&self, user_id: &str) -> Result<bool> {
+        let user_id = user_id.to_string();
+        self.pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            let mut statement = conn.prepare("DELETE FROM linked_accounts WHERE user_id = ?")?;
+            statement.bind((1, user_id))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
+    }
+
+    // Analytics Methods
+    pub async fn increment_daily_stat(&self, stat_type: &str) -> Result<()> {
+        let stat_type = stat_type.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let stat_type = stat_type.as_str();
+            let date = db.date_from_now(0);
+
+            let column = match stat_type {
+                "message" => "total_messages",
+                "command" => "total_commands",
+                "error" => "total_errors",
+                _ => "",
+            };
+            if !column.is_empty() {
+                let mut statement = conn.prepare(format!(
+                    "INSERT INTO daily_analytics (date, {column}) VALUES (?, 1)
+                     ON CONFLICT(date) DO UPDATE SET {column} = daily_analytics.{column} + 1"
+                ))?;
+                statement.bind((1, date.as_str()))?;
+                statement.next()?;
+            }
+
+            let mut statement = conn.prepare(
+                "INSERT INTO daily_analytics (date, total_messages) VALUES (?, 0)
+                 ON CONFLICT(date) DO NOTHING"
+            )?;
+            statement.bind((1, date.as_str()))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn add_performance_metric(&self, metric_type: &str, value: f64, unit: Option<&str>, metadata: Option<&str>) -> Result<()> {
+        let (metric_type, unit, metadata) = (metric_type.to_string(), unit.map(str::to_string), metadata.map(str::to_string));
+        self.pool.interact(move |conn| {
+            let (metric_type, unit, metadata) = (metric_type.as_str(), unit.as_deref(), metadata.as_deref());
+            let mut statement = conn.prepare(
+                "INSERT INTO performance_metrics (metric_type, value, unit, metadata) VALUES (?, ?, ?, ?)"
+            )?;
+            statement.bind((1, metric_type))?;
+            statement.bind((2, value))?;
+            statement.bind((3, unit.unwrap_or("")))?;
+            statement.bind((4, metadata.unwrap_or("")))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    // System Metrics Methods (for /sysinfo command)
+
+    /// Store a system metric snapshot (uses performance_metrics table).
+    /// Tagged with the bot's user ID, and its name in `metadata`, so bots sharing a database stay apart.
+    pub async fn store_system_metric(&self, bot_id: &str, bot_name: &str, metric_type: &str, value: f64) -> Result<()> {
+        let (bot_id, bot_name, metric_type) = (bot_id.to_string(), bot_name.to_string(), metric_type.to_string());
+        self.pool.interact(move |conn| {
+            let (bot_id, bot_name, metric_type) = (bot_id.as_str(), bot_name.as_str(), metric_type.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO performance_metrics (metric_type, value, unit, metadata, bot_id) VALUES (?, ?, 'system', ?, ?)"
+            )?;
+            statement.bind((1, metric_type))?;
+            statement.bind((2, value))?;
+            statement.bind((3, bot_name))?;
+            statement.bind((4, bot_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Get historical metrics data for a specific metric type, for one bot or (`None`) all of them
+    /// Returns (unix_timestamp, value) pairs ordered by time ascending
+    pub async fn get_metrics_history(&self, metric_type: &str, hours: i64, bot_id: Option<&str>) -> Result<Vec<(i64, f64)>> {
+        let (metric_type, bot_id) = (metric_type.to_string(), bot_id.map(str::to_string));
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (metric_type, bot_id) = (metric_type.as_str(), bot_id.as_deref());
+            let mut statement = conn.prepare(if conn.is_postgres() {
+                "SELECT CAST(EXTRACT(EPOCH FROM CAST(timestamp AS TIMESTAMP)) AS BIGINT) as unix_time, value
+                 FROM performance_metrics
+                 WHERE metric_type = ? AND timestamp >= ?
+                   AND (CAST(? AS TEXT) IS NULL OR bot_id = ?)
+                 ORDER BY timestamp ASC"
+            } else {
+                "SELECT strftime('%s', timestamp) as unix_time, value
+                 FROM performance_metrics
+                 WHERE metric_type = ? AND timestamp >= ?
+                   AND (? IS NULL OR bot_id = ?)
+                 ORDER BY timestamp ASC"
+            })?;
+            statement.bind((1, metric_type))?;
+            statement.bind((2, db.timestamp_from_now(Duration::hours(-hours)).as_str()))?;
+            statement.bind((3, bot_id))?;
+            statement.bind((4, bot_id))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let timestamp_str = statement.read::<String, _>(0)?;
+                let timestamp = timestamp_str.parse::<i64>().unwrap_or(0);
+                let value = statement.read::<f64, _>(1)?;
+                results.push((timestamp, value));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Bots that recorded system metrics in the last `hours`, as (bot_id, latest name), by name
+    pub async fn get_metric_bots(&self, hours: i64) -> Result<Vec<(String, String)>> {
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT bot_id, metadata FROM performance_metrics
+                 WHERE unit = 'system' AND bot_id IS NOT NULL AND timestamp >= ?
+                 ORDER BY timestamp DESC, id DESC"
+            )?;
+            statement.bind((1, db.timestamp_from_now(Duration::hours(-hours)).as_str()))?;
+
+            let mut bots: Vec<(String, String)> = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let bot_id = statement.read::<String, _>(0)?;
+                if !bots.iter().any(|(id, _)| *id == bot_id) {
+                    let name = statement.read::<Option<String>, _>(1)?.filter(|n| !n.is_empty());
+                    bots.push((bot_id.clone(), name.unwrap_or(bot_id)));
+                }
+            }
+            bots.sort_by_key(|(_, name)| name.to_lowercase());
+            Ok(bots)
+        })
+        .await
+    }
+
+    /// Cleanup old metrics data (keep last N days)
+    pub async fn cleanup_old_metrics(&self, days: i64) -> Result<()> {
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "DELETE FROM performance_metrics WHERE unit = 'system' AND timestamp < ?"
+            )?;
+            statement.bind((1, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+            statement.next()?;
+            info!("Cleaned up system metrics older than {} days", days);
+            Ok(())
+        })
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn log_error(
+        &self,
+        error_type: &str,
+        error_message: &str,
+        stack_trace: Option<&str>,
+        user_id: Option<&str>,
+        channel_id: Option<&str>,
+        guild_id: Option<&str>,
+        command: Option<&str>,
+        metadata: Option<&str>,
+    ) -> Result<()> {
+        let values = [error_type, error_message, stack_trace.unwrap_or(""), user_id.unwrap_or(""), channel_id.unwrap_or("")]
+            .into_iter()
+            .chain([command.unwrap_or(""), metadata.unwrap_or(""), guild_id.unwrap_or("")])
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "INSERT INTO error_logs (error_type, error_message, stack_trace, user_id, channel_id, command, metadata, guild_id)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+            )?;
+            for (i, value) in values.iter().enumerate() {
+                statement.bind((i + 1, value))?;
+            }
+            statement.next()?;
+            Ok(())
+        })
+        .await?;
+
+        // Also increment daily error count
+        self.increment_daily_stat("error").await?;
+        Ok(())
+    }
+
+    /// Most recent errors logged in a guild, newest first
+    pub async fn get_guild_errors(&self, guild_id: &str, limit: i64) -> Result<Vec<ErrorLogEntry>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT id, error_type, error_message, command, timestamp
+                 FROM error_logs
+                 WHERE guild_id = ?
+                 ORDER BY id DESC
+                 LIMIT ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, limit))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(ErrorLogEntry {
+                    id: statement.read::<i64, _>(0)?,
+                    error_type: statement.read::<String, _>(1)?,
+                    message: statement.read::<String, _>(2)?,
+                    command: statement.read::<Option<String>, _>(3)?.filter(|c| !c.is_empty()),
+                    timestamp: statement.read::<String, _>(4)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    // Feature Flag Methods
+    pub async fn set_feature_flag(
+        &self,
+        feature_name: &str,
+        enabled: bool,
+        user_id: Option<&str>,
+        guild_id: Option<&str>,
+    ) -> Result<()> {
+        let (feature_name, user_id, guild_id) = (
+            feature_name.to_string(),
+            user_id.map(str::to_string),
+            guild_id.map(str::to_string),
+        );
+        self.pool.interact(move |conn| {
+            let (feature_name, user_id, guild_id) = (feature_name.as_str(), user_id.as_deref(), guild_id.as_deref());
+            let mut statement = conn.prepare(
+                "INSERT INTO feature_flags (feature_name, enabled, user_id, guild_id, updated_at)
+                 VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(feature_name, user_id, guild_id) DO UPDATE SET enabled = excluded.enabled, updated_at = excluded.updated_at"
+            )?;
+            statement.bind((1, feature_name))?;
+            statement.bind((2, if enabled { 1i64 } else { 0i64 }))?;
+            statement.bind((3, user_id.unwrap_or("")))?;
+            statement.bind((4, guild_id.unwrap_or("")))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Check if a feature is enabled for a guild
+    /// Returns true by default if no record exists (features are enabled unless explicitly disabled)
+    pub async fn is_feature_enabled(&self, feature_name: &str, user_id: Option<&str>, guild_id: Option<&str>) -> Result<bool> {
+        let (feature_name, user_id, guild_id) = (
+            feature_name.to_string(),
+            user_id.map(str::to_string),
+            guild_id.map(str::to_string),
+        );
+        self.pool.interact(move |conn| {
+            let (feature_name, user_id, guild_id) = (feature_name.as_str(), user_id.as_deref(), guild_id.as_deref());
+            let mut statement = conn.prepare(
+                "SELECT enabled FROM feature_flags
+                 WHERE feature_name = ? AND user_id = ? AND guild_id = ?
+                 LIMIT 1"
+            )?;
+            statement.bind((1, feature_name))?;
+            statement.bind((2, user_id.unwrap_or("")))?;
+            statement.bind((3, guild_id.unwrap_or("")))?;
+
+            if let Ok(State::Row) = statement.next() {
+                let enabled = statement.read::<i64, _>(0)?;
+                Ok(enabled == 1)
+            } else {
+                // Default to enabled if no explicit setting exists
+                Ok(true)
+            }
+        })
+        .await
+    }
+
+    /// Get all feature flags for a guild
+    /// Returns a map of feature_name -> enabled status
+    pub async fn get_guild_feature_flags(&self, guild_id: &str) -> Result<std::collections::HashMap<String, bool>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT feature_name, enabled FROM feature_flags
+                 WHERE guild_id = ? AND user_id = ''"
+            )?;
+            statement.bind((1, guild_id))?;
+
+            let mut flags = std::collections::HashMap::new();
+            while let Ok(State::Row) = statement.next() {
+                let feature_name = statement.read::<String, _>(0)?;
+                let enabled = statement.read::<i64, _>(1)? == 1;
+                flags.insert(feature_name, enabled);
+            }
+            Ok(flags)
+        })
+        .await
+    }
+
+    /// Record a feature toggle action in the audit trail
+    pub async fn record_feature_toggle(
+        &self,
+        feature_name: &str,
+        version: &str,
+        guild_id: Option<&str>,
+        toggled_by: &str,
+        enabled: bool,
+    ) -> Result<()> {
+        let (feature_name, version, guild_id, toggled_by) = (
+            feature_name.to_string(),
+            version.to_string(),
+            guild_id.map(str::to_string),
+            toggled_by.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (feature_name, version, guild_id, toggled_by) = (
+                feature_name.as_str(),
+                version.as_str(),
+                guild_id.as_deref(),
+                toggled_by.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO feature_versions (feature_name, version, guild_id, toggled_by, enabled)
+                 VALUES (?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, feature_name))?;
+            statement.bind((2, version))?;
+            statement.bind((3, guild_id.unwrap_or("")))?;
+            statement.bind((4, toggled_by))?;
+            statement.bind((5, if enabled { 1i64 } else { 0i64 }))?;
+            statement.next()?;
+            info!("Recorded feature toggle: {feature_name} -> {enabled} by {toggled_by}");
+            Ok(())
+        })
+        .await
+    }
+
+    // Guild Settings Methods
+    pub async fn set_guild_setting(&self, guild_id: &str, setting_key: &str, setting_value: &str) -> Result<()> {
+        let (guild_id, setting_key, setting_value) = (guild_id.to_string(), setting_key.to_string(), setting_value.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, setting_key, setting_value) = (guild_id.as_str(), setting_key.as_str(), setting_value.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO guild_settings (guild_id, setting_key, setting_value, updated_at)
+                 VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(guild_id, setting_key) DO UPDATE SET setting_value = excluded.setting_value, updated_at = excluded.updated_at"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, setting_key))?;
+            statement.bind((3, setting_value))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_guild_setting(&self, guild_id: &str, setting_key: &str) -> Result<Option<String>> {
+        let (guild_id, setting_key) = (guild_id.to_string(), setting_key.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, setting_key) = (guild_id.as_str(), setting_key.as_str());
+            let mut statement = conn.prepare(
+                "SELECT setting_value FROM guild_settings WHERE guild_id = ? AND setting_key = ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, setting_key))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(statement.read::<String, _>(0)?))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Every setting stored for a guild, sorted by key
+    pub async fn get_guild_settings(&self, guild_id: &str) -> Result<Vec<(String, String)>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT setting_key, setting_value FROM guild_settings WHERE guild_id = ? ORDER BY setting_key"
+            )?;
+            statement.bind((1, guild_id))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push((statement.read::<String, _>(0)?, statement.read::<String, _>(1)?));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    // Bot Settings Methods (global, not per-guild)
+    pub async fn set_bot_setting(&self, setting_key: &str, setting_value: &str) -> Result<()> {
+        let (setting_key, setting_value) = (setting_key.to_string(), setting_value.to_string());
+        self.pool.interact(move |conn| {
+            let (setting_key, setting_value) = (setting_key.as_str(), setting_value.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO bot_settings (setting_key, setting_value, updated_at)
+                 VALUES (?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(setting_key) DO UPDATE SET setting_value = excluded.setting_value, updated_at = excluded.updated_at"
+            )?;
+            statement.bind((1, setting_key))?;
+            statement.bind((2, setting_value))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_bot_setting(&self, setting_key: &str) -> Result<Option<String>> {
+        let setting_key = setting_key.to_string();
+        self.pool.interact(move |conn| {
+            let setting_key = setting_key.as_str();
+            let mut statement = conn.prepare(
+                "SELECT setting_value FROM bot_settings WHERE setting_key = ?"
+            )?;
+            statement.bind((1, setting_key))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(statement.read::<String, _>(0)?))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    // Extended User Preferences Methods
+    pub async fn set_user_preference(&self, user_id: &str, preference_key: &str, preference_value: &str) -> Result<()> {
+        let (user_id, preference_key, preference_value) = (
+            user_id.to_string(),
+            preference_key.to_string(),
+            preference_value.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (user_id, preference_key, preference_value) = (
+                user_id.as_str(),
+                preference_key.as_str(),
+                preference_value.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO extended_user_preferences (user_id, preference_key, preference_value, updated_at)
+                 VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(user_id, preference_key) DO UPDATE SET preference_value = excluded.preference_value, updated_at = excluded.updated_at"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, preference_key))?;
+            statement.bind((3, preference_value))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_user_preference(&self, user_id: &str, preference_key: &str) -> Result<Option<String>> {
+        let (user_id, preference_key) = (user_id.to_string(), preference_key.to_string());
+        self.pool.interact(move |conn| {
+            let (user_id, preference_key) = (user_id.as_str(), preference_key.as_str());
+            let mut statement = conn.prepare(
+                "SELECT preference_value FROM extended_user_preferences WHERE user_id = ? AND preference_key = ?"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, preference_key))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(statement.read::<String, _>(0)?))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    pub async fn delete_user_preference(&self, user_id: &str, preference_key: &str) -> Result<()> {
+        let (user_id, preference_key) = (user_id.to_string(), preference_key.to_string());
+        self.pool.interact(move |conn| {
+            let (user_id, preference_key) = (user_id.as_str(), preference_key.as_str());
+            let mut statement = conn.prepare(
+                "DELETE FROM extended_user_preferences WHERE user_id = ? AND preference_key = ?"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, preference_key))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Conflict Detection & Mediation Methods
+
+    pub async fn record_conflict_detection(
+        &self,
+        channel_id: &str,
+        guild_id: Option<&str>,
+        participants: &str, // JSON array of user IDs
+        detection_type: &str,
+        confidence: f32,
+        last_message_id: &str,
+    ) -> Result<i64> {
+        let (channel_id, guild_id, participants, detection_type, last_message_id) = (
+            channel_id.to_string(),
+            guild_id.map(str::to_string),
+            participants.to_string(),
+            detection_type.to_string(),
+            last_message_id.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (channel_id, guild_id, participants, detection_type, last_message_id) = (
+                channel_id.as_str(),
+                guild_id.as_deref(),
+                participants.as_str(),
+                detection_type.as_str(),
+                last_message_id.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO conflict_detection
+                 (channel_id, guild_id, participants, detection_type, confidence_score, last_message_id)
+                 VALUES (?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, channel_id))?;
+            statement.bind((2, guild_id.unwrap_or("")))?;
+            statement.bind((3, participants))?;
+            statement.bind((4, detection_type))?;
+            statement.bind((5, confidence as f64))?;
+            statement.bind((6, last_message_id))?;
+            statement.next()?;
+
+            // Get the ID of the inserted row
+            let conflict_id = conn.last_insert_id()?;
+
+            info!("Recorded conflict detection in channel {channel_id} with confidence {confidence}");
+            Ok(conflict_id)
+        })
+        .await
+    }
+
+    pub async fn mark_conflict_resolved(&self, conflict_id: i64) -> Result<()> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "UPDATE conflict_detection SET resolved_at = CURRENT_TIMESTAMP WHERE id = ?"
+            )?;
+            statement.bind((1, conflict_id))?;
+            statement.next()?;
+            info!("Marked conflict {conflict_id} as resolved");
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn mark_mediation_triggered(&self, conflict_id: i64, message_id: &str) -> Result<()> {
+        let message_id = message_id.to_string();
+        self.pool.interact(move |conn| {
+            let message_id = message_id.as_str();
+            let mut statement = conn.prepare(
+                "UPDATE conflict_detection
+                 SET mediation_triggered = 1, mediation_message_id = ?
+                 WHERE id = ?"
+            )?;
+            statement.bind((1, message_id))?;
+            statement.bind((2, conflict_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_channel_active_conflict(&self, channel_id: &str) -> Result<Option<i64>> {
+        let channel_id = channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let channel_id = channel_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT id FROM conflict_detection
+                 WHERE channel_id = ? AND resolved_at IS NULL
+                 ORDER BY last_detected DESC LIMIT 1"
+            )?;
+            statement.bind((1, channel_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(statement.read::<i64, _>(0)?))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    pub async fn record_mediation(
+        &self,
+        conflict_id: i64,
+        channel_id: &str,
+        message_text: &str,
+    ) -> Result<()> {
+        let (channel_id, message_text) = (channel_id.to_string(), message_text.to_string());
+        self.pool.interact(move |conn| {
+            let (channel_id, message_text) = (channel_id.as_str(), message_text.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO mediation_history (conflict_id, channel_id, mediation_message)
+                 VALUES (?, ?, ?)"
+            )?;
+            statement.bind((1, conflict_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, message_text))?;
+            statement.next()?;
+            info!("Recorded mediation for conflict {conflict_id}");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Record what a moderator decided about a conflict alert, with the message
+    /// posted if any and the decision's `effectiveness_rating`
+    pub async fn record_conflict_decision(
+        &self,
+        conflict_id: i64,
+        channel_id: &str,
+        message_text: Option<&str>,
+        decided_by: &str,
+        decision: &str,
+        effectiveness_rating: i64,
+    ) -> Result<()> {
+        let (channel_id, message_text, decided_by, decision) = (
+            channel_id.to_string(),
+            message_text.map(str::to_string),
+            decided_by.to_string(),
+            decision.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (channel_id, message_text, decided_by, decision) = (
+                channel_id.as_str(),
+                message_text.as_deref(),
+                decided_by.as_str(),
+                decision.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO mediation_history (conflict_id, channel_id, mediation_message, approved_by, decision, effectiveness_rating)
+                 VALUES (?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, conflict_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, message_text))?;
+            statement.bind((4, decided_by))?;
+            statement.bind((5, decision))?;
+            statement.bind((6, effectiveness_rating))?;
+            statement.next()?;
+            info!("Recorded {decision} decision for conflict {conflict_id} by {decided_by}");
+            Ok(())
+        })
+        .await
+    }
+
+    /// The first moderator decision on this conflict as `(moderator, decision)`; older
+    /// approved suggestions have no decision recorded
+    pub async fn get_conflict_decision(&self, conflict_id: i64) -> Result<Option<(String, Option<String>)>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT approved_by, decision FROM mediation_history
+                 WHERE conflict_id = ? AND approved_by IS NOT NULL
+                 ORDER BY id LIMIT 1"
+            )?;
+            statement.bind((1, conflict_id))?;
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some((statement.read::<String, _>(0)?, statement.read::<Option<String>, _>(1)?)))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// How many of each moderator decision a guild's conflicts got over the last `days` days
+    pub async fn get_conflict_decision_counts(&self, guild_id: &str, days: i64) -> Result<Vec<(String, i64)>> {
+        let since = self.date_from_now(-days);
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT m.decision, COUNT(*)
+                 FROM mediation_history m
+                 JOIN conflict_detection c ON c.id = m.conflict_id
+                 WHERE c.guild_id = ? AND m.decision IS NOT NULL AND m.created_at >= ?
+                 GROUP BY m.decision"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, since.as_str()))?;
+
+            let mut counts = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                counts.push((statement.read::<String, _>(0)?, statement.read::<i64, _>(1)?));
+            }
+            Ok(counts)
+        })
+        .await
+    }
+
+    /// A conflict detected in this guild, with how many mediations it got
+    pub async fn get_conflict(&self, guild_id: &str, conflict_id: i64) -> Result<Option<ConflictRecord>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT c.id, c.channel_id, c.participants, c.detection_type, COALESCE(c.confidence_score, 0.0),
+                        COALESCE(c.mediation_triggered, 0), c.first_detected, c.last_detected, c.resolved_at,
+                        (SELECT COUNT(*) FROM mediation_history m WHERE m.conflict_id = c.id)
+                 FROM conflict_detection c
+                 WHERE c.id = ? AND c.guild_id = ?"
+            )?;
+            statement.bind((1, conflict_id))?;
+            statement.bind((2, guild_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(ConflictRecord {
+                    id: statement.read::<i64, _>(0)?,
+                    channel_id: statement.read::<String, _>(1)?,
+                    participants: statement.read::<String, _>(2)?,
+                    detection_type: statement.read::<String, _>(3)?,
+                    confidence: statement.read::<f64, _>(4)?,
+                    mediation_triggered: statement.read::<i64, _>(5)? != 0,
+                    first_detected: statement.read::<String, _>(6)?,
+                    last_detected: statement.read::<String, _>(7)?,
+                    resolved_at: statement.read::<Option<String>, _>(8)?,
+                    mediations: statement.read::<i64, _>(9)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// The channel a conflict was detected in, if it belongs to this guild
+    pub async fn get_conflict_channel(&self, guild_id: &str, conflict_id: i64) -> Result<Option<String>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare("SELECT channel_id FROM conflict_detection WHERE id = ? AND guild_id = ?")?;
+            statement.bind((1, conflict_id))?;
+            statement.bind((2, guild_id))?;
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(statement.read::<String, _>(0)?))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// A guild's most recent detected conflicts, newest first, with how many mediations each got
+    pub async fn get_guild_conflicts(&self, guild_id: &str, limit: i64) -> Result<Vec<ConflictRecord>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT c.id, c.channel_id, c.participants, c.detection_type, COALESCE(c.confidence_score, 0.0),
+                        COALESCE(c.mediation_triggered, 0), c.first_detected, c.last_detected, c.resolved_at,
+                        (SELECT COUNT(*) FROM mediation_history m WHERE m.conflict_id = c.id)
+                 FROM conflict_detection c
+                 WHERE c.guild_id = ?
+                 ORDER BY c.id DESC
+                 LIMIT ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, limit))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(ConflictRecord {
+                    id: statement.read::<i64, _>(0)?,
+                    channel_id: statement.read::<String, _>(1)?,
+                    participants: statement.read::<String, _>(2)?,
+                    detection_type: statement.read::<String, _>(3)?,
+                    confidence: statement.read::<f64, _>(4)?,
+                    mediation_triggered: statement.read::<i64, _>(5)? != 0,
+                    first_detected: statement.read::<String, _>(6)?,
+                    last_detected: statement.read::<String, _>(7)?,
+                    resolved_at: statement.read::<Option<String>, _>(8)?,
+                    mediations: statement.read::<i64, _>(9)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Get the timestamp of the last mediation in a channel
+    pub async fn get_last_mediation_timestamp(&self, channel_id: &str) -> Result<Option<i64>> {
+        let channel_id = channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let channel_id = channel_id.as_str();
+            let mut statement = conn.prepare(if conn.is_postgres() {
+                "SELECT CAST(EXTRACT(EPOCH FROM CAST(mh.created_at AS TIMESTAMP)) AS BIGINT) as unix_time
+                 FROM mediation_history mh
+                 WHERE mh.channel_id = ?
+                 ORDER BY mh.created_at DESC
+                 LIMIT 1"
+            } else {
+                "SELECT strftime('%s', mh.created_at) as unix_time
+                 FROM mediation_history mh
+                 WHERE mh.channel_id = ?
+                 ORDER BY mh.created_at DESC
+                 LIMIT 1"
+            })?;
+            statement.bind((1, channel_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                let timestamp_str = statement.read::<String, _>(0)?;
+                Ok(Some(timestamp_str.parse::<i64>()?))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    pub async fn get_recent_channel_messages(
+        &self,
+        channel_id: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, String, String)>> {
+        let channel_id = channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let channel_id = channel_id.as_str();
+            let mut statement = conn.prepare(if conn.is_postgres() {
+                "SELECT user_id, content, CAST(EXTRACT(EPOCH FROM CAST(timestamp AS TIMESTAMP)) AS BIGINT) as unix_time
+                 FROM conversation_history
+                 WHERE channel_id = ?
+                 ORDER BY timestamp DESC
+                 LIMIT ?"
+            } else {
+                "SELECT user_id, content, strftime('%s', timestamp) as unix_time
+                 FROM conversation_history
+                 WHERE channel_id = ?
+                 ORDER BY timestamp DESC
+                 LIMIT ?"
+            })?;
+            statement.bind((1, channel_id))?;
+            statement.bind((2, limit as i64))?;
+
+            let mut messages = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let user_id = statement.read::<String, _>(0)?;
+                let content = statement.read::<String, _>(1)?;
+                let timestamp = statement.read::<String, _>(2)?;
+                messages.push((user_id, content, timestamp));
+            }
+
+            // Reverse to get chronological order
+            messages.reverse();
+            Ok(messages)
+        })
+        .await
+    }
+
+    /// Get recent channel messages that occurred after a specific timestamp
+    /// This is used to avoid re-analyzing messages that have already been mediated
+    pub async fn get_recent_channel_messages_since(
+        &self,
+        channel_id: &str,
+        since_timestamp: i64,
+        limit: usize,
+    ) -> Result<Vec<(String, String, String)>> {
+        let channel_id = channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let channel_id = channel_id.as_str();
+            let mut statement = conn.prepare(if conn.is_postgres() {
+                "SELECT user_id, content, CAST(EXTRACT(EPOCH FROM CAST(timestamp AS TIMESTAMP)) AS BIGINT) as unix_time
+                 FROM conversation_history
+                 WHERE channel_id = ?
+                   AND CAST(EXTRACT(EPOCH FROM CAST(timestamp AS TIMESTAMP)) AS BIGINT) > ?
+                 ORDER BY timestamp DESC
+                 LIMIT ?"
+            } else {
+                "SELECT user_id, content, strftime('%s', timestamp) as unix_time
+                 FROM conversation_history
+                 WHERE channel_id = ?
+                   AND CAST(strftime('%s', timestamp) AS INTEGER) > ?
+                 ORDER BY timestamp DESC
+                 LIMIT ?"
+            })?;
+            statement.bind((1, channel_id))?;
+            statement.bind((2, since_timestamp))?;
+            statement.bind((3, limit as i64))?;
+
+            let mut messages = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let user_id = statement.read::<String, _>(0)?;
+                let content = statement.read::<String, _>(1)?;
+                let timestamp = statement.read::<String, _>(2)?;
+                messages.push((user_id, content, timestamp));
+            }
+
+            // Reverse to get chronological order
+            messages.reverse();
+            Ok(messages)
+        })
+        .await
+    }
+
+    pub async fn update_user_interaction_pattern(
+        &self,
+        user_id_a: &str,
+        user_id_b: &str,
+        channel_id: &str,
+        is_conflict: bool,
+    ) -> Result<()> {
+        let (user_id_a, user_id_b, channel_id) = (user_id_a.to_string(), user_id_b.to_string(), channel_id.to_string());
+        self.pool.interact(move |conn| {
+            let (user_id_a, user_id_b, channel_id) = (user_id_a.as_str(), user_id_b.as_str(), channel_id.as_str());
+            // Ensure user_id_a is always lexicographically smaller (for consistent lookups)
+            let (user_a, user_b) = if user_id_a < user_id_b {
+                (user_id_a, user_id_b)
+            } else {
+                (user_id_b, user_id_a)
+            };
+
+            let conflict_increment = if is_conflict { 1 } else { 0 };
+
+            let mut statement = conn.prepare(
+                "INSERT INTO user_interaction_patterns
+                 (user_id_a, user_id_b, channel_id, interaction_count, conflict_incidents, last_interaction)
+                 VALUES (?, ?, ?, 1, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(user_id_a, user_id_b, channel_id) DO UPDATE SET
+                 interaction_count = user_interaction_patterns.interaction_count + 1,
+                 conflict_incidents = user_interaction_patterns.conflict_incidents + ?,
+                 last_interaction = CURRENT_TIMESTAMP"
+            )?;
+            statement.bind((1, user_a))?;
+            statement.bind((2, user_b))?;
+            statement.bind((3, channel_id))?;
+            statement.bind((4, conflict_increment))?;
+            statement.bind((5, conflict_increment))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Channel Settings Methods
+
+    /// Get verbosity for a channel, falling back to guild default, then "concise"
+    pub async fn get_channel_verbosity(&self, guild_id: &str, channel_id: &str) -> Result<String> {
+        let (guild_id, channel_id) = (guild_id.to_string(), channel_id.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id) = (guild_id.as_str(), channel_id.as_str());
+            // First try channel-specific setting
+            let mut statement = conn.prepare(
+                "SELECT verbosity FROM channel_settings WHERE guild_id = ? AND channel_id = ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                return statement.read::<String, _>(0);
+            }
+
+            // Fall back to guild default
+            drop(statement);
+            let mut guild_stmt = conn.prepare(
+                "SELECT setting_value FROM guild_settings WHERE guild_id = ? AND setting_key = 'default_verbosity'"
+            )?;
+            guild_stmt.bind((1, guild_id))?;
+
+            if let Ok(State::Row) = guild_stmt.next() {
+                return guild_stmt.read::<String, _>(0);
+            }
+
+            // Default to concise
+            Ok("concise".to_string())
+        })
+        .await
+    }
+
+    /// Set verbosity for a specific channel
+    pub async fn set_channel_verbosity(&self, guild_id: &str, channel_id: &str, verbosity: &str) -> Result<()> {
+        let (guild_id, channel_id, verbosity) = (guild_id.to_string(), channel_id.to_string(), verbosity.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, verbosity) = (guild_id.as_str(), channel_id.as_str(), verbosity.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO channel_settings (guild_id, channel_id, verbosity, updated_at)
+                 VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(guild_id, channel_id) DO UPDATE SET
+                 verbosity = excluded.verbosity,
+                 updated_at = CURRENT_TIMESTAMP"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, verbosity))?;
+            statement.next()?;
+            info!("Set verbosity for channel {channel_id} to {verbosity}");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Get the default creativity level for a channel, if one has been set
+    pub async fn get_channel_creativity(&self, guild_id: &str, channel_id: &str) -> Result<Option<String>> {
+        let (guild_id, channel_id) = (guild_id.to_string(), channel_id.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id) = (guild_id.as_str(), channel_id.as_str());
+            let mut statement = conn.prepare(
+                "SELECT creativity FROM channel_settings WHERE guild_id = ? AND channel_id = ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(statement.read::<Option<String>, _>(0)?)
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Set the default creativity level for a channel
+    pub async fn set_channel_creativity(&self, guild_id: &str, channel_id: &str, creativity: &str) -> Result<()> {
+        let (guild_id, channel_id, creativity) = (guild_id.to_string(), channel_id.to_string(), creativity.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, creativity) = (guild_id.as_str(), channel_id.as_str(), creativity.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO channel_settings (guild_id, channel_id, creativity, updated_at)
+                 VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(guild_id, channel_id) DO UPDATE SET
+                 creativity = excluded.creativity,
+                 updated_at = CURRENT_TIMESTAMP"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, creativity))?;
+            statement.next()?;
+            info!("Set creativity for channel {channel_id} to {creativity}");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Get all settings for a channel
+    pub async fn get_channel_settings(&self, guild_id: &str, channel_id: &str) -> Result<(String, bool)> {
+        let (guild_id, channel_id) = (guild_id.to_string(), channel_id.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id) = (guild_id.as_str(), channel_id.as_str());
+            let mut statement = conn.prepare(
+                "SELECT verbosity, conflict_enabled FROM channel_settings WHERE guild_id = ? AND channel_id = ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                let verbosity = statement.read::<String, _>(0)?;
+                let conflict_enabled = statement.read::<i64, _>(1)? == 1;
+                Ok((verbosity, conflict_enabled))
+            } else {
+                // Return defaults
+                Ok(("concise".to_string(), true))
+            }
+        })
+        .await
+    }
+
+    /// Set whether conflict detection is enabled for a channel
+    pub async fn set_channel_conflict_enabled(&self, guild_id: &str, channel_id: &str, enabled: bool) -> Result<()> {
+        let (guild_id, channel_id) = (guild_id.to_string(), channel_id.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id) = (guild_id.as_str(), channel_id.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO channel_settings (guild_id, channel_id, conflict_enabled, updated_at)
+                 VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(guild_id, channel_id) DO UPDATE SET
+                 conflict_enabled = excluded.conflict_enabled,
+                 updated_at = CURRENT_TIMESTAMP"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, if enabled { 1i64 } else { 0i64 }))?;
+            statement.next()?;
+            info!("Set conflict_enabled for channel {channel_id} to {enabled}");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Check if a user has the bot admin role for a guild
+    pub async fn has_bot_admin_role(&self, guild_id: &str, user_roles: &[String]) -> Result<bool> {
+        // Get the bot admin role ID from guild settings
+        let admin_role = self.get_guild_setting(guild_id, "bot_admin_role").await?;
+
+        if let Some(role_id) = admin_role {
+            Ok(user_roles.iter().any(|r| r == &role_id))
+        } else {
+            // No bot admin role set - only Discord admins can manage
+            Ok(false)
+        }
+    }
+
+    // OpenAI Usage Tracking Methods
+
+    /// Log a ChatCompletion (GPT) usage event
+    #[allow(clippy::too_many_arguments)]
+    pub async fn log_openai_chat_usage(
+        &self,
+        model: &str,
+        input_tokens: u32,
+        cached_input_tokens: u32,
+        output_tokens: u32,
+        total_tokens: u32,
+        batch: bool,
+        estimated_cost: f64,
+        user_id: &str,
+        guild_id: Option<&str>,
+        channel_id: Option<&str>,
+        request_id: Option<&str>,
+        feature: &str,
+    ) -> Result<()> {
+        let (model, user_id, guild_id, channel_id, request_id, feature) = (
+            model.to_string(),
+            user_id.to_string(),
+            guild_id.map(str::to_string),
+            channel_id.map(str::to_string),
+            request_id.map(str::to_string),
+            feature.to_string(),
+        );
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (model, user_id, guild_id, channel_id, request_id, feature) = (
+                model.as_str(),
+                user_id.as_str(),
+                guild_id.as_deref(),
+                channel_id.as_deref(),
+                request_id.as_deref(),
+                feature.as_str(),
+            );
+            let date = db.date_from_now(0);
+
+            // Insert into raw usage table
+            let mut statement = conn.prepare(
+                "INSERT INTO openai_usage
+                 (request_id, user_id, guild_id, channel_id, service_type, model,
+                  input_tokens, cached_input_tokens, output_tokens, total_tokens, is_batch, estimated_cost_usd, feature)
+                 VALUES (?, ?, ?, ?, 'chat', ?, ?, ?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, request_id.unwrap_or("")))?;
+            statement.bind((2, user_id))?;
+            statement.bind((3, guild_id.unwrap_or("")))?;
+            statement.bind((4, channel_id.unwrap_or("")))?;
+            statement.bind((5, model))?;
+            statement.bind((6, input_tokens as i64))?;
+            statement.bind((7, cached_input_tokens as i64))?;
+            statement.bind((8, output_tokens as i64))?;
+            statement.bind((9, total_tokens as i64))?;
+            statement.bind((10, if batch { 1i64 } else { 0i64 }))?;
+            statement.bind((11, estimated_cost))?;
+            statement.bind((12, feature))?;
+            statement.next()?;
+
+            // Update daily aggregate
+            drop(statement);
+            let mut agg_stmt = conn.prepare(
+                "INSERT INTO openai_usage_daily
+                 (date, guild_id, user_id, service_type, request_count, total_tokens, total_cost_usd)
+                 VALUES (?, ?, ?, 'chat', 1, ?, ?)
+                 ON CONFLICT(date, guild_id, user_id, service_type) DO UPDATE SET
+                 request_count = openai_usage_daily.request_count + 1,
+                 total_tokens = openai_usage_daily.total_tokens + excluded.total_tokens,
+                 total_cost_usd = openai_usage_daily.total_cost_usd + excluded.total_cost_usd"
+            )?;
+            agg_stmt.bind((1, date.as_str()))?;
+            agg_stmt.bind((2, guild_id.unwrap_or("")))?;
+            agg_stmt.bind((3, user_id))?;
+            agg_stmt.bind((4, total_tokens as i64))?;
+            agg_stmt.bind((5, estimated_cost))?;
+            agg_stmt.next()?;
+
+            drop(agg_stmt);
+            Self::add_feature_cost(conn, &date, guild_id, feature, total_tokens as i64, estimated_cost)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Log a Whisper (audio transcription) usage event
+    pub async fn log_openai_whisper_usage(
+        &self,
+        audio_duration_seconds: f64,
+        estimated_cost: f64,
+        user_id: &str,
+        guild_id: Option<&str>,
+        channel_id: Option<&str>,
+    ) -> Result<()> {
+        let (user_id, guild_id, channel_id) = (user_id.to_string(), guild_id.map(str::to_string), channel_id.map(str::to_string));
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (user_id, guild_id, channel_id) = (user_id.as_str(), guild_id.as_deref(), channel_id.as_deref());
+            let date = db.date_from_now(0);
+
+            // Insert into raw usage table
+            let mut statement = conn.prepare(
+                "INSERT INTO openai_usage
+                 (user_id, guild_id, channel_id, service_type, model,
+                  audio_duration_seconds, estimated_cost_usd, feature)
+                 VALUES (?, ?, ?, 'whisper', 'whisper-1', ?, ?, 'transcription')"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, guild_id.unwrap_or("")))?;
+            statement.bind((3, channel_id.unwrap_or("")))?;
+            statement.bind((4, audio_duration_seconds))?;
+            statement.bind((5, estimated_cost))?;
+            statement.next()?;
+
+            // Update daily aggregate
+            drop(statement);
+            let mut agg_stmt = conn.prepare(
+                "INSERT INTO openai_usage_daily
+                 (date, guild_id, user_id, service_type, request_count, total_audio_seconds, total_cost_usd)
+                 VALUES (?, ?, ?, 'whisper', 1, ?, ?)
+                 ON CONFLICT(date, guild_id, user_id, service_type) DO UPDATE SET
+                 request_count = openai_usage_daily.request_count + 1,
+                 total_audio_seconds = openai_usage_daily.total_audio_seconds + excluded.total_audio_seconds,
+                 total_cost_usd = openai_usage_daily.total_cost_usd + excluded.total_cost_usd"
+            )?;
+            agg_stmt.bind((1, date.as_str()))?;
+            agg_stmt.bind((2, guild_id.unwrap_or("")))?;
+            agg_stmt.bind((3, user_id))?;
+            agg_stmt.bind((4, audio_duration_seconds))?;
+            agg_stmt.bind((5, estimated_cost))?;
+            agg_stmt.next()?;
+
+            drop(agg_stmt);
+            Self::add_feature_cost(conn, &date, guild_id, "transcription", 0, estimated_cost)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Log a DALL-E (image generation) usage event
+    pub async fn log_openai_dalle_usage(
+        &self,
+        image_size: &str,
+        image_count: u32,
+        estimated_cost: f64,
+        user_id: &str,
+        guild_id: Option<&str>,
+        channel_id: Option<&str>,
+    ) -> Result<()> {
+        let (image_size, user_id, guild_id, channel_id) = (
+            image_size.to_string(),
+            user_id.to_string(),
+            guild_id.map(str::to_string),
+            channel_id.map(str::to_string),
+        );
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (image_size, user_id, guild_id, channel_id) = (
+                image_size.as_str(),
+                user_id.as_str(),
+                guild_id.as_deref(),
+                channel_id.as_deref(),
+            );
+            let date = db.date_from_now(0);
+
+            // Insert into raw usage table
+            let mut statement = conn.prepare(
+                "INSERT INTO openai_usage
+                 (user_id, guild_id, channel_id, service_type, model,
+                  image_count, image_size, estimated_cost_usd, feature)
+                 VALUES (?, ?, ?, 'dalle', 'dall-e-3', ?, ?, ?, 'image')"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, guild_id.unwrap_or("")))?;
+            statement.bind((3, channel_id.unwrap_or("")))?;
+            statement.bind((4, image_count as i64))?;
+            statement.bind((5, image_size))?;
+            statement.bind((6, estimated_cost))?;
+            statement.next()?;
+
+            // Update daily aggregate
+            drop(statement);
+            let mut agg_stmt = conn.prepare(
+                "INSERT INTO openai_usage_daily
+                 (date, guild_id, user_id, service_type, request_count, total_images, total_cost_usd)
+                 VALUES (?, ?, ?, 'dalle', 1, ?, ?)
+                 ON CONFLICT(date, guild_id, user_id, service_type) DO UPDATE SET
+                 request_count = openai_usage_daily.request_count + 1,
+                 total_images = openai_usage_daily.total_images + excluded.total_images,
+                 total_cost_usd = openai_usage_daily.total_cost_usd + excluded.total_cost_usd"
+            )?;
+            agg_stmt.bind((1, date.as_str()))?;
+            agg_stmt.bind((2, guild_id.unwrap_or("")))?;
+            agg_stmt.bind((3, user_id))?;
+            agg_stmt.bind((4, image_count as i64))?;
+            agg_stmt.bind((5, estimated_cost))?;
+            agg_stmt.next()?;
+
+            drop(agg_stmt);
+            Self::add_feature_cost(conn, &date, guild_id, "image", 0, estimated_cost)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Add one request's cost to the per-feature daily aggregate
+    fn add_feature_cost(
+        conn: &Connection,
+        date: &str,
+        guild_id: Option<&str>,
+        feature: &str,
+        tokens: i64,
+        estimated_cost: f64,
+    ) -> Result<()> {
+        let mut statement = conn.prepare(
+            "INSERT INTO openai_usage_feature_daily
+             (date, guild_id, feature, request_count, total_tokens, total_cost_usd)
+             VALUES (?, ?, ?, 1, ?, ?)
+             ON CONFLICT(date, guild_id, feature) DO UPDATE SET
+             request_count = openai_usage_feature_daily.request_count + 1,
+             total_tokens = openai_usage_feature_daily.total_tokens + excluded.total_tokens,
+             total_cost_usd = openai_usage_feature_daily.total_cost_usd + excluded.total_cost_usd"
+        )?;
+        statement.bind((1, date))?;
+        statement.bind((2, guild_id.unwrap_or("")))?;
+        statement.bind((3, feature))?;
+        statement.bind((4, tokens))?;
+        statement.bind((5, estimated_cost))?;
+        statement.next()?;
+        Ok(())
+    }
+
+    /// Get cost per guild and feature between two dates (inclusive, `YYYY-MM-DD`).
+    ///
+    /// Pass a guild ID to restrict the breakdown to that guild.
+    pub async fn get_cost_breakdown(
+        &self,
+        guild_id: Option<&str>,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<CostLine>> {
+        let (guild_id, start_date, end_date) = (guild_id.map(str::to_string), start_date.to_string(), end_date.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, start_date, end_date) = (guild_id.as_deref(), start_date.as_str(), end_date.as_str());
+            let mut statement = conn.prepare(
+                "SELECT COALESCE(guild_id, ''), feature,
+                        SUM(request_count), SUM(total_tokens), SUM(total_cost_usd)
+                 FROM openai_usage_feature_daily
+                 WHERE date >= ? AND date <= ?
+                 AND (NULLIF(?, '') IS NULL OR guild_id = ?)
+                 GROUP BY guild_id, feature
+                 ORDER BY guild_id, SUM(total_cost_usd) DESC"
+            )?;
+            statement.bind((1, start_date))?;
+            statement.bind((2, end_date))?;
+            statement.bind((3, guild_id.unwrap_or("")))?;
+            statement.bind((4, guild_id.unwrap_or("")))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(CostLine {
+                    guild_id: statement.read::<String, _>(0)?,
+                    feature: statement.read::<String, _>(1)?,
+                    requests: statement.read::<i64, _>(2)?,
+                    tokens: statement.read::<i64, _>(3)?,
+                    cost: statement.read::<f64, _>(4)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// A guild's OpenAI spend in USD from `since` (a `YYYY-MM-DD` date) on, counting only usage inside the guild
+    pub async fn get_guild_spend_since(&self, guild_id: &str, since: &str) -> Result<f64> {
+        let (guild_id, since) = (guild_id.to_string(), since.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, since) = (guild_id.as_str(), since.as_str());
+            let mut statement = conn.prepare(
+                "SELECT COALESCE(SUM(total_cost_usd), 0) FROM openai_usage_daily WHERE guild_id = ? AND date >= ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, since))?;
+            statement.next()?;
+            statement.read::<f64, _>(0)
+        })
+        .await
+    }
+
+    /// A user's OpenAI spend in USD inside one guild from `since` (a `YYYY-MM-DD` date) on
+    pub async fn get_user_guild_spend_since(&self, user_id: &str, guild_id: &str, since: &str) -> Result<f64> {
+        let (user_id, guild_id, since) = (user_id.to_string(), guild_id.to_string(), since.to_string());
+        self.pool.interact(move |conn| {
+            let (user_id, guild_id, since) = (user_id.as_str(), guild_id.as_str(), since.as_str());
+            let mut statement = conn.prepare(
+                "SELECT COALESCE(SUM(total_cost_usd), 0) FROM openai_usage_daily WHERE user_id = ? AND guild_id = ? AND date >= ?"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, since))?;
+            statement.next()?;
+            statement.read::<f64, _>(0)
+        })
+        .await
+    }
+
+    /// Record a budget alert unless the same one was already recorded for the period; returns whether it's new
+    #[allow(clippy::too_many_arguments)]
+    pub async fn record_budget_alert(
+        &self,
+        guild_id: &str,
+        scope: &str,
+        user_id: &str,
+        period: &str,
+        level: &str,
+        channel_id: &str,
+        spent_usd: f64,
+        budget_usd: f64,
+    ) -> Result<bool> {
+        let (guild_id, scope, user_id, period, level, channel_id) = (
+            guild_id.to_string(),
+            scope.to_string(),
+            user_id.to_string(),
+            period.to_string(),
+            level.to_string(),
+            channel_id.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, scope, user_id, period, level, channel_id) = (
+                guild_id.as_str(),
+                scope.as_str(),
+                user_id.as_str(),
+                period.as_str(),
+                level.as_str(),
+                channel_id.as_str(),
+            );
+            let mut existing = conn.prepare(
+                "SELECT 1 FROM budget_alerts WHERE guild_id = ? AND scope = ? AND user_id = ? AND period = ? AND level = ?"
+            )?;
+            existing.bind((1, guild_id))?;
+            existing.bind((2, scope))?;
+            existing.bind((3, user_id))?;
+            existing.bind((4, period))?;
+            existing.bind((5, level))?;
+            if matches!(existing.next()?, State::Row) {
+                return Ok(false);
+            }
+
+            let mut statement = conn.prepare(
+                "INSERT INTO budget_alerts (guild_id, scope, user_id, period, level, channel_id, spent_usd, budget_usd)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT DO NOTHING"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, scope))?;
+            statement.bind((3, user_id))?;
+            statement.bind((4, period))?;
+            statement.bind((5, level))?;
+            statement.bind((6, channel_id))?;
+            statement.bind((7, spent_usd))?;
+            statement.bind((8, budget_usd))?;
+            statement.next()?;
+            Ok(true)
+        })
+        .await
+    }
+
+    /// Budget alerts not posted yet, oldest first
+    pub async fn get_pending_budget_alerts(&self, limit: i64) -> Result<Vec<BudgetAlert>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, scope, user_id, level, channel_id, spent_usd, budget_usd
+                 FROM budget_alerts
+                 WHERE sent_at IS NULL
+                 ORDER BY id ASC
+                 LIMIT ?"
+            )?;
+            statement.bind((1, limit))?;
+
+            let mut alerts = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                alerts.push(BudgetAlert {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    scope: statement.read::<String, _>(2)?,
+                    user_id: statement.read::<String, _>(3)?,
+                    level: statement.read::<String, _>(4)?,
+                    channel_id: statement.read::<String, _>(5)?,
+                    spent_usd: statement.read::<f64, _>(6)?,
+                    budget_usd: statement.read::<f64, _>(7)?,
+                });
+            }
+            Ok(alerts)
+        })
+        .await
+    }
+
+    pub async fn mark_budget_alert_sent(&self, id: i64) -> Result<()> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare("UPDATE budget_alerts SET sent_at = CURRENT_TIMESTAMP WHERE id = ?")?;
+            statement.bind((1, id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Record an exchange answered concisely because the user seemed frustrated
+    pub async fn log_verbosity_adaptation(
+        &self,
+        guild_id: &str,
+        channel_id: &str,
+        user_id: &str,
+        message_id: &str,
+        signals: &str,
+        channel_verbosity: &str,
+    ) -> Result<()> {
+        let (guild_id, channel_id, user_id, message_id, signals, channel_verbosity) = (
+            guild_id.to_string(),
+            channel_id.to_string(),
+            user_id.to_string(),
+            message_id.to_string(),
+            signals.to_string(),
+            channel_verbosity.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, user_id, message_id, signals, channel_verbosity) = (
+                guild_id.as_str(),
+                channel_id.as_str(),
+                user_id.as_str(),
+                message_id.as_str(),
+                signals.as_str(),
+                channel_verbosity.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO verbosity_adaptations (guild_id, channel_id, user_id, message_id, signals, channel_verbosity)
+                 VALUES (?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, user_id))?;
+            statement.bind((4, message_id))?;
+            statement.bind((5, signals))?;
+            statement.bind((6, channel_verbosity))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Number of verbosity adaptations in a guild over the last `days` days
+    pub async fn count_verbosity_adaptations(&self, guild_id: &str, days: i64) -> Result<i64> {
+        let guild_id = guild_id.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT COUNT(*) FROM verbosity_adaptations
+                 WHERE guild_id = ? AND created_at >= ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+            statement.next()?;
+            statement.read::<i64, _>(0)
+        })
+        .await
+    }
+
+    /// A cached answer that hasn't expired, with its expiry time
+    pub async fn get_cached_response(&self, cache_key: &str) -> Result<Option<(String, String)>> {
+        let cache_key = cache_key.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let cache_key = cache_key.as_str();
+            let mut statement = conn.prepare(
+                "SELECT response, expires_at FROM response_cache
+                 WHERE cache_key = ? AND expires_at > ?"
+            )?;
+            statement.bind((1, cache_key))?;
+            statement.bind((2, db.timestamp_from_now(Duration::zero()).as_str()))?;
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some((statement.read::<String, _>(0)?, statement.read::<String, _>(1)?)))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Cache an answer for `ttl_hours`, dropping expired entries. Returns the expiry time.
+    pub async fn store_cached_response(&self, cache_key: &str, guild_id: &str, persona: &str, model: &str, response: &str, ttl_hours: i64) -> Result<String> {
+        let (cache_key, guild_id, persona, model, response) = (
+            cache_key.to_string(),
+            guild_id.to_string(),
+            persona.to_string(),
+            model.to_string(),
+            response.to_string(),
+        );
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (cache_key, guild_id, persona, model, response) = (
+                cache_key.as_str(),
+                guild_id.as_str(),
+                persona.as_str(),
+                model.as_str(),
+                response.as_str(),
+            );
+            let now = db.timestamp_from_now(Duration::zero());
+            let expires_at = db.timestamp_from_now(Duration::hours(ttl_hours));
+            let mut statement = conn.prepare("DELETE FROM response_cache WHERE expires_at <= ?")?;
+            statement.bind((1, now.as_str()))?;
+            statement.next()?;
+
+            let mut statement = conn.prepare(
+                "INSERT INTO response_cache (cache_key, guild_id, persona, model, response, created_at, expires_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(cache_key) DO UPDATE SET response = excluded.response, created_at = excluded.created_at, expires_at = excluded.expires_at"
+            )?;
+            statement.bind((1, cache_key))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, persona))?;
+            statement.bind((4, model))?;
+            statement.bind((5, response))?;
+            statement.bind((6, now.as_str()))?;
+            statement.bind((7, expires_at.as_str()))?;
+            statement.next()?;
+            Ok(expires_at)
+        })
+        .await
+    }
+
+    /// Cached answers a guild has that haven't expired
+    pub async fn count_cached_responses(&self, guild_id: &str) -> Result<i64> {
+        let guild_id = guild_id.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT COUNT(*) FROM response_cache
+                 WHERE guild_id = ? AND expires_at > ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, db.timestamp_from_now(Duration::zero()).as_str()))?;
+            statement.next()?;
+            statement.read::<i64, _>(0)
+        })
+        .await
+    }
+
+    /// Drop every cached answer for a guild. Returns how many were removed.
+    pub async fn clear_response_cache(&self, guild_id: &str) -> Result<i64> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare("DELETE FROM response_cache WHERE guild_id = ?")?;
+            statement.bind((1, guild_id))?;
+            statement.next()?;
+
+            conn.changes()
+        })
+        .await
+    }
+
+    /// Record content flagged by the moderation check
+    #[allow(clippy::too_many_arguments)]
+    pub async fn log_moderation_event(
         &self,
-        user_id: &str,
+        guild_id: &str,
         channel_id: &str,
-        message_id: &str,
-        bookmark_name: Option<&str>,
-        bookmark_note: Option<&str>,
+        user_id: &str,
+        direction: &str,
+        action: &str,
+        categories: &str,
+        excerpt: &str,
     ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO user_bookmarks (user_id, channel_id, message_id, bookmark_name, bookmark_note)
-             VALUES (?, ?, ?, ?, ?)"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, channel_id))?;
-        statement.bind((3, message_id))?;
-        statement.bind((4, bookmark_name.unwrap_or("")))?;
-        statement.bind((5, bookmark_note.unwrap_or("")))?;
-        statement.next()?;
-        info!("Added bookmark for user {user_id}");
-        Ok(())
+        let (guild_id, channel_id, user_id, direction, action, categories, excerpt) = (
+            guild_id.to_string(),
+            channel_id.to_string(),
+            user_id.to_string(),
+            direction.to_string(),
+            action.to_string(),
+            categories.to_string(),
+            excerpt.to_string(),
+        );
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, user_id, direction, action, categories, excerpt) = (
+                guild_id.as_str(),
+                channel_id.as_str(),
+                user_id.as_str(),
+                direction.as_str(),
+                action.as_str(),
+                categories.as_str(),
+                excerpt.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO moderation_events (guild_id, channel_id, user_id, direction, action, categories, excerpt, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, user_id))?;
+            statement.bind((4, direction))?;
+            statement.bind((5, action))?;
+            statement.bind((6, categories))?;
+            statement.bind((7, excerpt))?;
+            statement.bind((8, db.timestamp_from_now(Duration::zero()).as_str()))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    pub async fn get_user_bookmarks(&self, user_id: &str) -> Result<Vec<(String, String, String, String)>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT message_id, channel_id, bookmark_name, bookmark_note
-             FROM user_bookmarks WHERE user_id = ?
-             ORDER BY created_at DESC"
-        )?;
-        statement.bind((1, user_id))?;
-
-        let mut bookmarks = Vec::new();
-        while let Ok(State::Row) = statement.next() {
-            let message_id = statement.read::<String, _>(0)?;
-            let channel_id = statement.read::<String, _>(1)?;
-            let bookmark_name = statement.read::<String, _>(2)?;
-            let bookmark_note = statement.read::<String, _>(3)?;
-            bookmarks.push((message_id, channel_id, bookmark_name, bookmark_note));
+    /// Moderation events moderators haven't been told about yet, oldest first
+    pub async fn get_pending_moderation_events(&self, limit: i64) -> Result<Vec<ModerationEvent>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, channel_id, user_id, direction, action, categories, excerpt
+                 FROM moderation_events
+                 WHERE notified_at IS NULL
+                 ORDER BY id ASC
+                 LIMIT ?"
+            )?;
+            statement.bind((1, limit))?;
+
+            let mut events = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                events.push(ModerationEvent {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    channel_id: statement.read::<String, _>(2)?,
+                    user_id: statement.read::<String, _>(3)?,
+                    direction: statement.read::<String, _>(4)?,
+                    action: statement.read::<String, _>(5)?,
+                    categories: statement.read::<String, _>(6)?,
+                    excerpt: statement.read::<String, _>(7)?,
+                });
+            }
+            Ok(events)
+        })
+        .await
+    }
+
+    pub async fn mark_moderation_event_notified(&self, id: i64) -> Result<()> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare("UPDATE moderation_events SET notified_at = CURRENT_TIMESTAMP WHERE id = ?")?;
+            statement.bind((1, id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Number of moderation events in a guild over the last `days` days
+    pub async fn count_moderation_events(&self, guild_id: &str, days: i64) -> Result<i64> {
+        let guild_id = guild_id.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT COUNT(*) FROM moderation_events
+                 WHERE guild_id = ? AND created_at >= ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+            statement.next()?;
+            statement.read::<i64, _>(0)
+        })
+        .await
+    }
+
+    /// Get usage statistics for a user within a date range
+    /// Returns (service_type, request_count, tokens, audio_seconds, images, cost)
+    pub async fn get_user_usage_stats(
+        &self,
+        user_id: &str,
+        days: i64,
+    ) -> Result<Vec<(String, i64, i64, f64, i64, f64)>> {
+        let user_id = user_id.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT service_type,
+                        SUM(request_count) as requests,
+                        SUM(total_tokens) as tokens,
+                        SUM(total_audio_seconds) as audio_secs,
+                        SUM(total_images) as images,
+                        SUM(total_cost_usd) as cost
+                 FROM openai_usage_daily
+                 WHERE user_id = ? AND date >= ?
+                 GROUP BY service_type"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, db.date_from_now(-days).as_str()))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let service_type = statement.read::<String, _>(0)?;
+                let requests = statement.read::<i64, _>(1)?;
+                let tokens = statement.read::<i64, _>(2)?;
+                let audio_secs = statement.read::<f64, _>(3)?;
+                let images = statement.read::<i64, _>(4)?;
+                let cost = statement.read::<f64, _>(5)?;
+                results.push((service_type, requests, tokens, audio_secs, images, cost));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Get usage statistics for an entire guild within a date range
+    /// Includes DM usage from users who have interacted in this guild
+    /// Returns (service_type, request_count, tokens, audio_seconds, images, cost)
+    pub async fn get_guild_usage_stats(
+        &self,
+        guild_id: &str,
+        days: i64,
+    ) -> Result<Vec<(String, i64, i64, f64, i64, f64)>> {
+        let guild_id = guild_id.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let since = db.date_from_now(-days);
+            let mut statement = conn.prepare(
+                "SELECT service_type,
+                        SUM(request_count) as requests,
+                        SUM(total_tokens) as tokens,
+                        SUM(total_audio_seconds) as audio_secs,
+                        SUM(total_images) as images,
+                        SUM(total_cost_usd) as cost
+                 FROM openai_usage_daily
+                 WHERE (guild_id = ? OR (guild_id = '' AND user_id IN (
+                     SELECT DISTINCT user_id FROM openai_usage_daily WHERE guild_id = ?
+                 )))
+                 AND date >= ?
+                 GROUP BY service_type"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, since.as_str()))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let service_type = statement.read::<String, _>(0)?;
+                let requests = statement.read::<i64, _>(1)?;
+                let tokens = statement.read::<i64, _>(2)?;
+                let audio_secs = statement.read::<f64, _>(3)?;
+                let images = statement.read::<i64, _>(4)?;
+                let cost = statement.read::<f64, _>(5)?;
+                results.push((service_type, requests, tokens, audio_secs, images, cost));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Requests and cost per day for a guild, oldest first
+    /// Includes DM usage from users who have interacted in this guild
+    /// Returns (date, request_count, cost)
+    pub async fn get_guild_daily_usage(&self, guild_id: &str, days: i64) -> Result<Vec<(String, i64, f64)>> {
+        let guild_id = guild_id.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let since = db.date_from_now(-days);
+            let mut statement = conn.prepare(
+                "SELECT date, SUM(request_count) as requests, SUM(total_cost_usd) as cost
+                 FROM openai_usage_daily
+                 WHERE (guild_id = ? OR (guild_id = '' AND user_id IN (
+                     SELECT DISTINCT user_id FROM openai_usage_daily WHERE guild_id = ?
+                 )))
+                 AND date >= ?
+                 GROUP BY date
+                 ORDER BY date"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, since.as_str()))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let date = statement.read::<String, _>(0)?;
+                let requests = statement.read::<i64, _>(1)?;
+                let cost = statement.read::<f64, _>(2)?;
+                results.push((date, requests, cost));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Get top users by cost for a guild
+    /// Includes DM usage from users who have interacted in this guild
+    /// Returns (user_id, request_count, total_cost)
+    pub async fn get_guild_top_users_by_cost(
+        &self,
+        guild_id: &str,
+        days: i64,
+        limit: i64,
+    ) -> Result<Vec<(String, i64, f64)>> {
+        let guild_id = guild_id.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let since = db.date_from_now(-days);
+            let mut statement = conn.prepare(
+                "SELECT user_id,
+                        SUM(request_count) as requests,
+                        SUM(total_cost_usd) as cost
+                 FROM openai_usage_daily
+                 WHERE (guild_id = ? OR (guild_id = '' AND user_id IN (
+                     SELECT DISTINCT user_id FROM openai_usage_daily WHERE guild_id = ?
+                 )))
+                 AND user_id != ''
+                 AND date >= ?
+                 GROUP BY user_id
+                 ORDER BY cost DESC
+                 LIMIT ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, since.as_str()))?;
+            statement.bind((4, limit))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let user_id = statement.read::<String, _>(0)?;
+                let requests = statement.read::<i64, _>(1)?;
+                let cost = statement.read::<f64, _>(2)?;
+                results.push((user_id, requests, cost));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Cleanup old raw usage data (keep last N days)
+    pub async fn cleanup_old_openai_usage(&self, days: i64) -> Result<()> {
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "DELETE FROM openai_usage WHERE timestamp < ?"
+            )?;
+            statement.bind((1, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+            statement.next()?;
+            info!("Cleaned up openai_usage older than {} days", days);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Cleanup old daily aggregates (keep last N days)
+    pub async fn cleanup_old_openai_usage_daily(&self, days: i64) -> Result<()> {
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "DELETE FROM openai_usage_daily WHERE date < ?"
+            )?;
+            statement.bind((1, db.date_from_now(-days).as_str()))?;
+            statement.next()?;
+            info!("Cleaned up openai_usage_daily older than {} days", days);
+
+            drop(statement);
+            let mut statement = conn.prepare(
+                "DELETE FROM openai_usage_feature_daily WHERE date < ?"
+            )?;
+            statement.bind((1, db.date_from_now(-days).as_str()))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Emoji Statistics Methods
+
+    /// Count emoji used in a guild today; `source` is `message` or `reaction`
+    pub async fn record_emoji_usage(&self, guild_id: &str, emojis: &[String], source: &str) -> Result<()> {
+        let pool = self.guild_pool(Some(guild_id)).await?;
+        let (guild_id, emojis, source) = (guild_id.to_string(), emojis.to_vec(), source.to_string());
+        let db = self.clone();
+        pool.interact(move |conn| {
+            let (guild_id, emojis, source) = (guild_id.as_str(), emojis.as_slice(), source.as_str());
+            let date = db.date_from_now(0);
+
+            for emoji in emojis {
+                let mut statement = conn.prepare(
+                    "INSERT INTO emoji_stats (date, guild_id, emoji, source, count)
+                     VALUES (?, ?, ?, ?, 1)
+                     ON CONFLICT(date, guild_id, emoji, source) DO UPDATE SET count = emoji_stats.count + 1"
+                )?;
+                statement.bind((1, date.as_str()))?;
+                statement.bind((2, guild_id))?;
+                statement.bind((3, emoji.as_str()))?;
+                statement.bind((4, source))?;
+                statement.next()?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Most-used emoji in a guild over the last N days as (emoji, count)
+    pub async fn get_top_emoji(&self, guild_id: &str, source: &str, days: i64, limit: i64) -> Result<Vec<(String, i64)>> {
+        let pool = self.guild_pool(Some(guild_id)).await?;
+        let (guild_id, source) = (guild_id.to_string(), source.to_string());
+        let db = self.clone();
+        pool.interact(move |conn| {
+            let (guild_id, source) = (guild_id.as_str(), source.as_str());
+            let mut statement = conn.prepare(
+                "SELECT emoji, SUM(count) AS total
+                 FROM emoji_stats
+                 WHERE guild_id = ? AND source = ? AND date >= ?
+                 GROUP BY emoji
+                 ORDER BY total DESC, emoji
+                 LIMIT ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, source))?;
+            statement.bind((3, db.date_from_now(-days).as_str()))?;
+            statement.bind((4, limit))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push((statement.read::<String, _>(0)?, statement.read::<i64, _>(1)?));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Weekly emoji totals for a guild as (week starting Monday, message count, reaction count), oldest first
+    pub async fn get_emoji_weekly_trend(&self, guild_id: &str, days: i64) -> Result<Vec<(String, i64, i64)>> {
+        let pool = self.guild_pool(Some(guild_id)).await?;
+        let guild_id = guild_id.to_string();
+        let db = self.clone();
+        pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(if conn.is_postgres() {
+                "SELECT to_char(date_trunc('week', CAST(date AS TIMESTAMP)), 'YYYY-MM-DD') AS week,
+                        SUM(CASE WHEN source = 'message' THEN count ELSE 0 END),
+                        SUM(CASE WHEN source = 'reaction' THEN count ELSE 0 END)
+                 FROM emoji_stats
+                 WHERE guild_id = ? AND date >= ?
+                 GROUP BY week
+                 ORDER BY week"
+            } else {
+                "SELECT date(date, '-6 days', 'weekday 1') AS week,
+                        SUM(CASE WHEN source = 'message' THEN count ELSE 0 END),
+                        SUM(CASE WHEN source = 'reaction' THEN count ELSE 0 END)
+                 FROM emoji_stats
+                 WHERE guild_id = ? AND date >= ?
+                 GROUP BY week
+                 ORDER BY week"
+            })?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, db.date_from_now(-days).as_str()))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push((
+                    statement.read::<String, _>(0)?,
+                    statement.read::<i64, _>(1)?,
+                    statement.read::<i64, _>(2)?,
+                ));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Message counts for a channel grouped by (SQLite `%w` weekday, UTC hour) over the last N days.
+    /// Combines stored user messages with message metadata not already counted from history.
+    pub async fn get_channel_activity_heatmap(&self, channel_id: &str, days: i64) -> Result<Vec<(u32, u32, u32)>> {
+        let channel_id = channel_id.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let channel_id = channel_id.as_str();
+            let mut statement = conn.prepare(if conn.is_postgres() {
+                "SELECT CAST(EXTRACT(DOW FROM CAST(ts AS TIMESTAMP)) AS INTEGER), CAST(EXTRACT(HOUR FROM CAST(ts AS TIMESTAMP)) AS INTEGER), COUNT(*)
+                 FROM (
+                     SELECT timestamp AS ts FROM conversation_history
+                     WHERE channel_id = ?1 AND role = 'user' AND timestamp >= ?2
+                     UNION ALL
+                     SELECT created_at AS ts FROM message_metadata
+                     WHERE channel_id = ?1 AND created_at >= ?2
+                       AND message_id NOT IN (
+                           SELECT message_id FROM conversation_history
+                           WHERE channel_id = ?1 AND message_id IS NOT NULL
+                       )
+                 ) AS recent
+                 GROUP BY 1, 2"
+            } else {
+                "SELECT CAST(strftime('%w', ts) AS INTEGER), CAST(strftime('%H', ts) AS INTEGER), COUNT(*)
+                 FROM (
+                     SELECT timestamp AS ts FROM conversation_history
+                     WHERE channel_id = ?1 AND role = 'user' AND timestamp >= ?2
+                     UNION ALL
+                     SELECT created_at AS ts FROM message_metadata
+                     WHERE channel_id = ?1 AND created_at >= ?2
+                       AND message_id NOT IN (
+                           SELECT message_id FROM conversation_history
+                           WHERE channel_id = ?1 AND message_id IS NOT NULL
+                       )
+                 ) AS recent
+                 GROUP BY 1, 2"
+            })?;
+            statement.bind((1, channel_id))?;
+            statement.bind((2, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push((
+                    statement.read::<i64, _>(0)? as u32,
+                    statement.read::<i64, _>(1)? as u32,
+                    statement.read::<i64, _>(2)? as u32,
+                ));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Record that a member posted today, along with their guild join date when known
+    pub async fn record_member_activity(&self, guild_id: &str, user_id: &str, joined_at: Option<&str>) -> Result<()> {
+        let pool = self.guild_pool(Some(guild_id)).await?;
+        let (guild_id, user_id, joined_at) = (guild_id.to_string(), user_id.to_string(), joined_at.map(str::to_string));
+        let db = self.clone();
+        pool.interact(move |conn| {
+            let (guild_id, user_id, joined_at) = (guild_id.as_str(), user_id.as_str(), joined_at.as_deref());
+            let date = db.date_from_now(0);
+            let mut statement = conn.prepare(
+                "INSERT INTO member_activity (guild_id, user_id, joined_at, first_seen, last_active, message_count)
+                 VALUES (?, ?, NULLIF(?, ''), ?, ?, 1)
+                 ON CONFLICT(guild_id, user_id) DO UPDATE SET
+                    joined_at = COALESCE(excluded.joined_at, member_activity.joined_at),
+                    last_active = excluded.last_active,
+                    message_count = member_activity.message_count + 1"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, user_id))?;
+            statement.bind((3, joined_at.unwrap_or("")))?;
+            statement.bind((4, date.as_str()))?;
+            statement.bind((5, date.as_str()))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// (join date, last active date) of members who joined a guild in the last N days
+    pub async fn get_member_retention_data(&self, guild_id: &str, days: i64) -> Result<Vec<(String, String)>> {
+        let pool = self.guild_pool(Some(guild_id)).await?;
+        let guild_id = guild_id.to_string();
+        let db = self.clone();
+        pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT joined_at, last_active
+                 FROM member_activity
+                 WHERE guild_id = ? AND joined_at IS NOT NULL AND joined_at >= ?
+                 ORDER BY joined_at"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, db.date_from_now(-days).as_str()))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push((statement.read::<String, _>(0)?, statement.read::<String, _>(1)?));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Cleanup member activity for members inactive for more than N days
+    pub async fn cleanup_old_member_activity(&self, days: i64) -> Result<()> {
+        for pool in self.all_pools().await? {
+            let cutoff = self.date_from_now(-days);
+            pool.interact(move |conn| {
+                let mut statement = conn.prepare(
+                    "DELETE FROM member_activity WHERE last_active < ?"
+                )?;
+                statement.bind((1, cutoff.as_str()))?;
+                statement.next()?;
+                Ok(())
+            })
+            .await?;
         }
-        Ok(bookmarks)
+        info!("Cleaned up member_activity older than {} days", days);
+        Ok(())
     }
 
-    pub async fn delete_bookmark(&self, user_id: &str, message_id: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "DELETE FROM user_bookmarks WHERE user_id = ? AND message_id = ?"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, message_id))?;
-        statement.next()?;
+    /// Cleanup old emoji statistics (keep last N days)
+    pub async fn cleanup_old_emoji_stats(&self, days: i64) -> Result<()> {
+        for pool in self.all_pools().await? {
+            let cutoff = self.date_from_now(-days);
+            pool.interact(move |conn| {
+                let mut statement = conn.prepare(
+                    "DELETE FROM emoji_stats WHERE date < ?"
+                )?;
+                statement.bind((1, cutoff.as_str()))?;
+                statement.next()?;
+                Ok(())
+            })
+            .await?;
+        }
+        info!("Cleaned up emoji_stats older than {} days", days);
         Ok(())
     }
 
-    // Reminder Methods
-    pub async fn add_reminder(
+    // Stale Settings Methods
+
+    /// Guilds that have at least one setting stored
+    pub async fn get_guilds_with_settings(&self) -> Result<Vec<String>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare("SELECT DISTINCT guild_id FROM guild_settings")?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(statement.read::<String, _>(0)?);
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Replace a guild's stale settings with the latest validation result.
+    /// Each entry is (setting_key, comma-separated missing IDs).
+    pub async fn replace_stale_settings(&self, guild_id: &str, entries: &[(String, String)]) -> Result<()> {
+        let (guild_id, entries) = (guild_id.to_string(), entries.to_vec());
+        self.pool.interact(move |conn| {
+            let (guild_id, entries) = (guild_id.as_str(), entries.as_slice());
+            let mut statement = conn.prepare("DELETE FROM stale_settings WHERE guild_id = ?")?;
+            statement.bind((1, guild_id))?;
+            statement.next()?;
+
+            for (setting_key, missing_ids) in entries {
+                let mut statement = conn.prepare(
+                    "INSERT INTO stale_settings (guild_id, setting_key, missing_ids) VALUES (?, ?, ?)"
+                )?;
+                statement.bind((1, guild_id))?;
+                statement.bind((2, setting_key.as_str()))?;
+                statement.bind((3, missing_ids.as_str()))?;
+                statement.next()?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Stale settings last detected for a guild as (setting_key, comma-separated missing IDs)
+    pub async fn get_stale_settings(&self, guild_id: &str) -> Result<Vec<(String, String)>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT setting_key, missing_ids FROM stale_settings WHERE guild_id = ? ORDER BY setting_key"
+            )?;
+            statement.bind((1, guild_id))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push((statement.read::<String, _>(0)?, statement.read::<String, _>(1)?));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    // Auto Slowmode Methods
+
+    /// Enable or update auto slowmode for a channel
+    pub async fn set_auto_slowmode_config(&self, guild_id: &str, channel_id: &str, config: &SlowmodeConfig) -> Result<()> {
+        let (guild_id, channel_id, config) = (guild_id.to_string(), channel_id.to_string(), *config);
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, config) = (guild_id.as_str(), channel_id.as_str(), &config);
+            let mut statement = conn.prepare(
+                "INSERT INTO auto_slowmode_channels
+                    (channel_id, guild_id, messages, window_seconds, slowmode_seconds, duration_minutes, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(channel_id) DO UPDATE SET
+                    messages = excluded.messages,
+                    window_seconds = excluded.window_seconds,
+                    slowmode_seconds = excluded.slowmode_seconds,
+                    duration_minutes = excluded.duration_minutes,
+                    updated_at = CURRENT_TIMESTAMP"
+            )?;
+            statement.bind((1, channel_id))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, config.messages))?;
+            statement.bind((4, config.window_seconds))?;
+            statement.bind((5, config.slowmode_seconds))?;
+            statement.bind((6, config.duration_minutes))?;
+            statement.next()?;
+            info!("Set auto slowmode for channel {channel_id}: {config:?}");
+            Ok(())
+        })
+        .await
+    }
+
+    /// Stop monitoring a channel; returns whether it was configured
+    pub async fn remove_auto_slowmode_config(&self, channel_id: &str) -> Result<bool> {
+        let channel_id = channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let channel_id = channel_id.as_str();
+            let mut statement = conn.prepare("DELETE FROM auto_slowmode_channels WHERE channel_id = ?")?;
+            statement.bind((1, channel_id))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
+    }
+
+    /// Auto slowmode thresholds for a channel, if it is monitored
+    pub async fn get_auto_slowmode_config(&self, channel_id: &str) -> Result<Option<SlowmodeConfig>> {
+        let channel_id = channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let channel_id = channel_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT messages, window_seconds, slowmode_seconds, duration_minutes
+                 FROM auto_slowmode_channels WHERE channel_id = ?"
+            )?;
+            statement.bind((1, channel_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(SlowmodeConfig {
+                    messages: statement.read::<i64, _>(0)?,
+                    window_seconds: statement.read::<i64, _>(1)?,
+                    slowmode_seconds: statement.read::<i64, _>(2)?,
+                    duration_minutes: statement.read::<i64, _>(3)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// All monitored channels in a guild as (channel_id, config)
+    pub async fn get_guild_auto_slowmode_configs(&self, guild_id: &str) -> Result<Vec<(String, SlowmodeConfig)>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT channel_id, messages, window_seconds, slowmode_seconds, duration_minutes
+                 FROM auto_slowmode_channels WHERE guild_id = ? ORDER BY channel_id"
+            )?;
+            statement.bind((1, guild_id))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push((
+                    statement.read::<String, _>(0)?,
+                    SlowmodeConfig {
+                        messages: statement.read::<i64, _>(1)?,
+                        window_seconds: statement.read::<i64, _>(2)?,
+                        slowmode_seconds: statement.read::<i64, _>(3)?,
+                        duration_minutes: statement.read::<i64, _>(4)?,
+                    },
+                ));
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Record an automatically applied slowmode; returns false if one is already active
+    pub async fn start_auto_slowmode(
         &self,
-        user_id: &str,
+        guild_id: &str,
         channel_id: &str,
-        reminder_text: &str,
-        remind_at: &str,
+        previous_seconds: i64,
+        applied_seconds: i64,
+        duration_minutes: i64,
+    ) -> Result<bool> {
+        let (guild_id, channel_id) = (guild_id.to_string(), channel_id.to_string());
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id) = (guild_id.as_str(), channel_id.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO auto_slowmode_active
+                    (channel_id, guild_id, previous_seconds, applied_seconds, revert_at)
+                 VALUES (?, ?, ?, ?, ?)
+                 ON CONFLICT DO NOTHING"
+            )?;
+            statement.bind((1, channel_id))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, previous_seconds))?;
+            statement.bind((4, applied_seconds))?;
+            statement.bind((5, db.timestamp_from_now(Duration::minutes(duration_minutes)).as_str()))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
+    }
+
+    /// Whether an automatic slowmode is currently active in a channel
+    pub async fn is_auto_slowmode_active(&self, channel_id: &str) -> Result<bool> {
+        let channel_id = channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let channel_id = channel_id.as_str();
+            let mut statement = conn.prepare("SELECT 1 FROM auto_slowmode_active WHERE channel_id = ?")?;
+            statement.bind((1, channel_id))?;
+            Ok(matches!(statement.next(), Ok(State::Row)))
+        })
+        .await
+    }
+
+    /// Automatic slowmodes whose revert time has passed
+    pub async fn get_due_auto_slowmodes(&self) -> Result<Vec<ActiveSlowmode>> {
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT channel_id, guild_id, previous_seconds, applied_seconds
+                 FROM auto_slowmode_active WHERE revert_at <= ?"
+            )?;
+            statement.bind((1, db.now().as_str()))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(ActiveSlowmode {
+                    channel_id: statement.read::<String, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    previous_seconds: statement.read::<i64, _>(2)?,
+                    applied_seconds: statement.read::<i64, _>(3)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Forget an automatic slowmode once it has been reverted
+    pub async fn finish_auto_slowmode(&self, channel_id: &str) -> Result<()> {
+        let channel_id = channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let channel_id = channel_id.as_str();
+            let mut statement = conn.prepare("DELETE FROM auto_slowmode_active WHERE channel_id = ?")?;
+            statement.bind((1, channel_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Moderation Action Methods
+
+    /// Record a moderation action, returning its ID. A new timeout supersedes any open
+    /// timeout for the same member so the old one isn't ended early.
+    pub async fn log_moderation_action(
+        &self,
+        guild_id: &str,
+        target_user_id: &str,
+        moderator_id: &str,
+        action: &str,
+        reason: &str,
+        duration_minutes: Option<i64>,
     ) -> Result<i64> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO reminders (user_id, channel_id, reminder_text, remind_at)
-             VALUES (?, ?, ?, ?)"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, channel_id))?;
-        statement.bind((3, reminder_text))?;
-        statement.bind((4, remind_at))?;
-        statement.next()?;
+        let (guild_id, target_user_id, moderator_id, action, reason) = (
+            guild_id.to_string(),
+            target_user_id.to_string(),
+            moderator_id.to_string(),
+            action.to_string(),
+            reason.to_string(),
+        );
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (guild_id, target_user_id, moderator_id, action, reason) = (
+                guild_id.as_str(),
+                target_user_id.as_str(),
+                moderator_id.as_str(),
+                action.as_str(),
+                reason.as_str(),
+            );
+            if action == "timeout" {
+                let mut statement = conn.prepare(
+                    "UPDATE moderation_actions SET ended_at = CURRENT_TIMESTAMP
+                     WHERE guild_id = ? AND target_user_id = ? AND action = 'timeout' AND ended_at IS NULL"
+                )?;
+                statement.bind((1, guild_id))?;
+                statement.bind((2, target_user_id))?;
+                statement.next()?;
+            }
 
-        let mut stmt = conn.prepare("SELECT last_insert_rowid()")?;
-        stmt.next()?;
-        let reminder_id = stmt.read::<i64, _>(0)?;
-        info!("Added reminder {reminder_id} for user {user_id}");
-        Ok(reminder_id)
+            let mut statement = conn.prepare(
+                "INSERT INTO moderation_actions
+                    (guild_id, target_user_id, moderator_id, action, reason, duration_minutes, expires_at)
+                 VALUES (?, ?, ?, ?, ?, NULLIF(?, 0), CASE WHEN ? > 0 THEN ? END)"
+            )?;
+            let minutes = duration_minutes.unwrap_or(0);
+            statement.bind((1, guild_id))?;
+            statement.bind((2, target_user_id))?;
+            statement.bind((3, moderator_id))?;
+            statement.bind((4, action))?;
+            statement.bind((5, reason))?;
+            statement.bind((6, minutes))?;
+            statement.bind((7, minutes))?;
+            statement.bind((8, db.timestamp_from_now(Duration::minutes(minutes)).as_str()))?;
+            statement.next()?;
+
+            let id = conn.last_insert_id()?;
+            info!("Logged moderation action {id}: {action} of {target_user_id} in guild {guild_id}");
+            Ok(id)
+        })
+        .await
     }
 
-    pub async fn get_pending_reminders(&self) -> Result<Vec<(i64, String, String, String)>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT id, user_id, channel_id, reminder_text
-             FROM reminders
-             WHERE completed = 0 AND remind_at <= datetime('now')
-             ORDER BY remind_at ASC"
-        )?;
+    /// Timeouts whose scheduled end has passed and that haven't been ended yet
+    pub async fn get_due_timeouts(&self) -> Result<Vec<ModerationAction>> {
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, target_user_id, moderator_id, action, reason, duration_minutes, created_at
+                 FROM moderation_actions
+                 WHERE action = 'timeout' AND ended_at IS NULL AND expires_at <= ?"
+            )?;
+            statement.bind((1, db.now().as_str()))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(ModerationAction {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    target_user_id: statement.read::<String, _>(2)?,
+                    moderator_id: statement.read::<String, _>(3)?,
+                    action: statement.read::<String, _>(4)?,
+                    reason: statement.read::<String, _>(5)?,
+                    duration_minutes: statement.read::<Option<i64>, _>(6)?,
+                    created_at: statement.read::<String, _>(7)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
+    }
 
-        let mut reminders = Vec::new();
-        while let Ok(State::Row) = statement.next() {
-            let id = statement.read::<i64, _>(0)?;
-            let user_id = statement.read::<String, _>(1)?;
-            let channel_id = statement.read::<String, _>(2)?;
-            let reminder_text = statement.read::<String, _>(3)?;
-            reminders.push((id, user_id, channel_id, reminder_text));
-        }
-        Ok(reminders)
+    /// Mark a moderation action (timeout) as ended
+    pub async fn end_moderation_action(&self, id: i64) -> Result<()> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "UPDATE moderation_actions SET ended_at = CURRENT_TIMESTAMP WHERE id = ? AND ended_at IS NULL"
+            )?;
+            statement.bind((1, id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    pub async fn complete_reminder(&self, reminder_id: i64) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "UPDATE reminders SET completed = 1, completed_at = CURRENT_TIMESTAMP WHERE id = ?"
-        )?;
-        statement.bind((1, reminder_id))?;
-        statement.next()?;
-        Ok(())
+    /// Look up a single moderation action
+    pub async fn get_moderation_action(&self, id: i64) -> Result<Option<ModerationAction>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, target_user_id, moderator_id, action, reason, duration_minutes, created_at
+                 FROM moderation_actions WHERE id = ?"
+            )?;
+            statement.bind((1, id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(ModerationAction {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    target_user_id: statement.read::<String, _>(2)?,
+                    moderator_id: statement.read::<String, _>(3)?,
+                    action: statement.read::<String, _>(4)?,
+                    reason: statement.read::<String, _>(5)?,
+                    duration_minutes: statement.read::<Option<i64>, _>(6)?,
+                    created_at: statement.read::<String, _>(7)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Bans and timeouts against a user that are still in effect and haven't been appealed,
+    /// newest first
+    pub async fn get_appealable_actions(&self, user_id: &str) -> Result<Vec<ModerationAction>> {
+        let user_id = user_id.to_string();
+        self.pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT m.id, m.guild_id, m.target_user_id, m.moderator_id, m.action, m.reason, m.duration_minutes, m.created_at
+                 FROM moderation_actions m
+                 LEFT JOIN appeals a ON a.moderation_action_id = m.id
+                 WHERE m.target_user_id = ? AND m.action IN ('ban', 'timeout')
+                   AND m.ended_at IS NULL AND a.id IS NULL
+                 ORDER BY m.created_at DESC, m.id DESC"
+            )?;
+            statement.bind((1, user_id))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(ModerationAction {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    target_user_id: statement.read::<String, _>(2)?,
+                    moderator_id: statement.read::<String, _>(3)?,
+                    action: statement.read::<String, _>(4)?,
+                    reason: statement.read::<String, _>(5)?,
+                    duration_minutes: statement.read::<Option<i64>, _>(6)?,
+                    created_at: statement.read::<String, _>(7)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
+    }
+
+    // Appeal Methods
+
+    /// File an appeal against a moderation action. Returns `None` if the action
+    /// has already been appealed.
+    pub async fn create_appeal(
+        &self,
+        moderation_action_id: i64,
+        guild_id: &str,
+        user_id: &str,
+        statement_text: &str,
+    ) -> Result<Option<i64>> {
+        let (guild_id, user_id, statement_text) = (guild_id.to_string(), user_id.to_string(), statement_text.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, user_id, statement_text) = (guild_id.as_str(), user_id.as_str(), statement_text.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO appeals (moderation_action_id, guild_id, user_id, statement)
+                 VALUES (?, ?, ?, ?)
+                 ON CONFLICT DO NOTHING"
+            )?;
+            statement.bind((1, moderation_action_id))?;
+            statement.bind((2, guild_id))?;
+            statement.bind((3, user_id))?;
+            statement.bind((4, statement_text))?;
+            statement.next()?;
+
+            if conn.changes()? == 0 {
+                return Ok(None);
+            }
+            let id = conn.last_insert_id()?;
+            info!("Appeal {id} filed by {user_id} against moderation action {moderation_action_id}");
+            Ok(Some(id))
+        })
+        .await
+    }
+
+    /// Look up an appeal by ID
+    pub async fn get_appeal(&self, id: i64) -> Result<Option<Appeal>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, moderation_action_id, guild_id, user_id, statement, status, reviewer_id, created_at
+                 FROM appeals WHERE id = ?"
+            )?;
+            statement.bind((1, id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(Appeal {
+                    id: statement.read::<i64, _>(0)?,
+                    moderation_action_id: statement.read::<i64, _>(1)?,
+                    guild_id: statement.read::<String, _>(2)?,
+                    user_id: statement.read::<String, _>(3)?,
+                    statement: statement.read::<String, _>(4)?,
+                    status: statement.read::<String, _>(5)?,
+                    reviewer_id: statement.read::<Option<String>, _>(6)?,
+                    created_at: statement.read::<String, _>(7)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Remove an appeal that could not be delivered so the member can file it again
+    pub async fn delete_appeal(&self, id: i64) -> Result<()> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare("DELETE FROM appeals WHERE id = ?")?;
+            statement.bind((1, id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    pub async fn get_user_reminders(&self, user_id: &str) -> Result<Vec<(i64, String, String, String)>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT id, channel_id, reminder_text, remind_at
-             FROM reminders
-             WHERE user_id = ? AND completed = 0
-             ORDER BY remind_at ASC"
-        )?;
-        statement.bind((1, user_id))?;
-
-        let mut reminders = Vec::new();
-        while let Ok(State::Row) = statement.next() {
-            let id = statement.read::<i64, _>(0)?;
-            let channel_id = statement.read::<String, _>(1)?;
-            let reminder_text = statement.read::<String, _>(2)?;
-            let remind_at = statement.read::<String, _>(3)?;
-            reminders.push((id, channel_id, reminder_text, remind_at));
-        }
-        Ok(reminders)
+    /// Record a moderator's decision on a pending appeal. Returns false if the
+    /// appeal was already decided (e.g. two moderators clicked at once).
+    pub async fn decide_appeal(&self, id: i64, status: &str, reviewer_id: &str) -> Result<bool> {
+        let (status, reviewer_id) = (status.to_string(), reviewer_id.to_string());
+        self.pool.interact(move |conn| {
+            let (status, reviewer_id) = (status.as_str(), reviewer_id.as_str());
+            let mut statement = conn.prepare(
+                "UPDATE appeals SET status = ?, reviewer_id = ?, decided_at = CURRENT_TIMESTAMP
+                 WHERE id = ? AND status = 'pending'"
+            )?;
+            statement.bind((1, status))?;
+            statement.bind((2, reviewer_id))?;
+            statement.bind((3, id))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
     }
 
-    pub async fn delete_reminder(&self, reminder_id: i64, user_id: &str) -> Result<bool> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "DELETE FROM reminders WHERE id = ? AND user_id = ?"
-        )?;
-        statement.bind((1, reminder_id))?;
-        statement.bind((2, user_id))?;
-        statement.next()?;
+    // Lockdown Methods
 
-        // Check if a row was actually deleted
-        let mut check = conn.prepare("SELECT changes()")?;
-        check.next()?;
-        let changes = check.read::<i64, _>(0)?;
+    /// Mark a guild as locked down. Returns false if a lockdown is already active.
+    pub async fn begin_lockdown(&self, guild_id: &str, started_by: &str, reason: &str) -> Result<bool> {
+        let (guild_id, started_by, reason) = (guild_id.to_string(), started_by.to_string(), reason.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, started_by, reason) = (guild_id.as_str(), started_by.as_str(), reason.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO lockdowns (guild_id, started_by, reason) VALUES (?, ?, NULLIF(?, '')) ON CONFLICT DO NOTHING"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, started_by))?;
+            statement.bind((3, reason))?;
+            statement.next()?;
 
-        if changes > 0 {
-            info!("Deleted reminder {reminder_id} for user {user_id}");
-            Ok(true)
-        } else {
-            Ok(false)
-        }
+            Ok(conn.changes()? > 0)
+        })
+        .await
     }
 
-    // Custom Command Methods
-    pub async fn add_custom_command(
-        &self,
-        command_name: &str,
-        response_text: &str,
-        created_by_user_id: &str,
-        guild_id: Option<&str>,
-    ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let is_global = guild_id.is_none();
-        let mut statement = conn.prepare(
-            "INSERT OR REPLACE INTO custom_commands (command_name, response_text, created_by_user_id, guild_id, is_global, updated_at)
-             VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)"
-        )?;
-        statement.bind((1, command_name))?;
-        statement.bind((2, response_text))?;
-        statement.bind((3, created_by_user_id))?;
-        statement.bind((4, guild_id.unwrap_or("")))?;
-        statement.bind((5, if is_global { 1i64 } else { 0i64 }))?;
-        statement.next()?;
-        info!("Added custom command: {command_name}");
-        Ok(())
+    /// The guild's active lockdown, if any
+    pub async fn get_lockdown(&self, guild_id: &str) -> Result<Option<Lockdown>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT guild_id, started_by, reason, started_at FROM lockdowns WHERE guild_id = ?"
+            )?;
+            statement.bind((1, guild_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(Lockdown {
+                    guild_id: statement.read::<String, _>(0)?,
+                    started_by: statement.read::<String, _>(1)?,
+                    reason: statement.read::<Option<String>, _>(2)?,
+                    started_at: statement.read::<String, _>(3)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
     }
 
-    pub async fn get_custom_command(&self, command_name: &str, guild_id: Option<&str>) -> Result<Option<String>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT response_text FROM custom_commands
-             WHERE command_name = ? AND (guild_id = ? OR is_global = 1)
-             ORDER BY is_global ASC
-             LIMIT 1"
-        )?;
-        statement.bind((1, command_name))?;
-        statement.bind((2, guild_id.unwrap_or("")))?;
+    /// Store a channel's @everyone overwrite from before the lockdown
+    pub async fn save_lockdown_overwrite(&self, guild_id: &str, overwrite: &LockdownOverwrite) -> Result<()> {
+        let (guild_id, overwrite) = (guild_id.to_string(), overwrite.clone());
+        self.pool.interact(move |conn| {
+            let (guild_id, overwrite) = (guild_id.as_str(), &overwrite);
+            let mut statement = conn.prepare(
+                "INSERT INTO lockdown_overwrites (guild_id, channel_id, had_overwrite, allow_bits, deny_bits)
+                 VALUES (?, ?, ?, ?, ?)
+                 ON CONFLICT(guild_id, channel_id) DO UPDATE SET
+                 had_overwrite = excluded.had_overwrite, allow_bits = excluded.allow_bits, deny_bits = excluded.deny_bits"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, overwrite.channel_id.as_str()))?;
+            statement.bind((3, overwrite.had_overwrite as i64))?;
+            statement.bind((4, overwrite.allow_bits))?;
+            statement.bind((5, overwrite.deny_bits))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
 
-        if let Ok(State::Row) = statement.next() {
-            Ok(Some(statement.read::<String, _>(0)?))
-        } else {
-            Ok(None)
-        }
+    /// Record a posted channel archive, returning its ID
+    #[allow(clippy::too_many_arguments)]
+    pub async fn record_channel_archive(
+        &self,
+        guild_id: &str,
+        channel_id: &str,
+        channel_name: &str,
+        archive_channel_id: &str,
+        archive_message_id: &str,
+        message_count: i64,
+        archived_by: &str,
+    ) -> Result<i64> {
+        let (guild_id, channel_id, channel_name, archive_channel_id, archive_message_id, archived_by) = (
+            guild_id.to_string(),
+            channel_id.to_string(),
+            channel_name.to_string(),
+            archive_channel_id.to_string(),
+            archive_message_id.to_string(),
+            archived_by.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, channel_name, archive_channel_id, archive_message_id, archived_by) = (
+                guild_id.as_str(),
+                channel_id.as_str(),
+                channel_name.as_str(),
+                archive_channel_id.as_str(),
+                archive_message_id.as_str(),
+                archived_by.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO channel_archives
+                 (guild_id, channel_id, channel_name, archive_channel_id, archive_message_id, message_count, archived_by)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, channel_name))?;
+            statement.bind((4, archive_channel_id))?;
+            statement.bind((5, archive_message_id))?;
+            statement.bind((6, message_count))?;
+            statement.bind((7, archived_by))?;
+            statement.next()?;
+
+            conn.last_insert_id()
+        })
+        .await
     }
 
-    pub async fn delete_custom_command(&self, command_name: &str, guild_id: Option<&str>) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "DELETE FROM custom_commands WHERE command_name = ? AND guild_id = ?"
-        )?;
-        statement.bind((1, command_name))?;
-        statement.bind((2, guild_id.unwrap_or("")))?;
-        statement.next()?;
-        Ok(())
+    /// Exempt a user, role or channel from conflict detection, guild-wide when `channel_id` is None.
+    /// Returns false when the same exemption already exists.
+    pub async fn add_conflict_exemption(
+        &self,
+        guild_id: &str,
+        channel_id: Option<&str>,
+        target_type: &str,
+        target_id: &str,
+        added_by: &str,
+    ) -> Result<bool> {
+        let (guild_id, channel_id, target_type, target_id, added_by) = (
+            guild_id.to_string(),
+            channel_id.map(str::to_string),
+            target_type.to_string(),
+            target_id.to_string(),
+            added_by.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, target_type, target_id, added_by) = (
+                guild_id.as_str(),
+                channel_id.as_deref(),
+                target_type.as_str(),
+                target_id.as_str(),
+                added_by.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO conflict_exemptions (guild_id, channel_id, target_type, target_id, added_by)
+                 VALUES (?, ?, ?, ?, ?)
+                 ON CONFLICT(guild_id, channel_id, target_type, target_id) DO NOTHING"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id.unwrap_or("")))?;
+            statement.bind((3, target_type))?;
+            statement.bind((4, target_id))?;
+            statement.bind((5, added_by))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
     }
 
-    // Analytics Methods
-    pub async fn increment_daily_stat(&self, stat_type: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
-
-        match stat_type {
-            "message" => {
-                conn.execute(
-                    "INSERT INTO daily_analytics (date, total_messages) VALUES (?, 1)
-                     ON CONFLICT(date) DO UPDATE SET total_messages = total_messages + 1"
-                )?;
-            }
-            "command" => {
-                conn.execute(
-                    "INSERT INTO daily_analytics (date, total_commands) VALUES (?, 1)
-                     ON CONFLICT(date) DO UPDATE SET total_commands = total_commands + 1"
-                )?;
-            }
-            "error" => {
-                conn.execute(
-                    "INSERT INTO daily_analytics (date, total_errors) VALUES (?, 1)
-                     ON CONFLICT(date) DO UPDATE SET total_errors = total_errors + 1"
-                )?;
+    /// Remove an exemption, returning whether there was one
+    pub async fn remove_conflict_exemption(
+        &self,
+        guild_id: &str,
+        channel_id: Option<&str>,
+        target_type: &str,
+        target_id: &str,
+    ) -> Result<bool> {
+        let (guild_id, channel_id, target_type, target_id) = (
+            guild_id.to_string(),
+            channel_id.map(str::to_string),
+            target_type.to_string(),
+            target_id.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, target_type, target_id) = (
+                guild_id.as_str(),
+                channel_id.as_deref(),
+                target_type.as_str(),
+                target_id.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "DELETE FROM conflict_exemptions
+                 WHERE guild_id = ? AND channel_id = ? AND target_type = ? AND target_id = ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id.unwrap_or("")))?;
+            statement.bind((3, target_type))?;
+            statement.bind((4, target_id))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
+    }
+
+    /// A guild's conflict detection exemptions, guild-wide ones first
+    pub async fn get_conflict_exemptions(&self, guild_id: &str) -> Result<Vec<ConflictExemption>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT id, channel_id, target_type, target_id, added_by, created_at
+                 FROM conflict_exemptions
+                 WHERE guild_id = ?
+                 ORDER BY channel_id, target_type, id"
+            )?;
+            statement.bind((1, guild_id))?;
+
+            let mut exemptions = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let channel_id = statement.read::<String, _>("channel_id")?;
+                exemptions.push(ConflictExemption {
+                    id: statement.read::<i64, _>("id")?,
+                    channel_id: Some(channel_id).filter(|id| !id.is_empty()),
+                    target_type: statement.read::<String, _>("target_type")?,
+                    target_id: statement.read::<String, _>("target_id")?,
+                    added_by: statement.read::<String, _>("added_by")?,
+                    created_at: statement.read::<String, _>("created_at")?,
+                });
             }
-            _ => {}
-        }
+            Ok(exemptions)
+        })
+        .await
+    }
 
-        let mut statement = conn.prepare(
-            "INSERT INTO daily_analytics (date, total_messages) VALUES (?, 0)
-             ON CONFLICT(date) DO NOTHING"
-        )?;
-        statement.bind((1, date.as_str()))?;
-        statement.next()?;
-        Ok(())
+    /// Start a story session in a newly opened thread
+    pub async fn create_story_session(
+        &self,
+        guild_id: &str,
+        channel_id: &str,
+        thread_id: &str,
+        theme: &str,
+        persona: &str,
+        started_by: &str,
+    ) -> Result<()> {
+        let (guild_id, channel_id, thread_id, theme, persona, started_by) = (
+            guild_id.to_string(),
+            channel_id.to_string(),
+            thread_id.to_string(),
+            theme.to_string(),
+            persona.to_string(),
+            started_by.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, thread_id, theme, persona, started_by) = (
+                guild_id.as_str(),
+                channel_id.as_str(),
+                thread_id.as_str(),
+                theme.as_str(),
+                persona.as_str(),
+                started_by.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO story_sessions (guild_id, channel_id, thread_id, theme, persona, started_by)
+                 VALUES (?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, thread_id))?;
+            statement.bind((4, theme))?;
+            statement.bind((5, persona))?;
+            statement.bind((6, started_by))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    pub async fn add_performance_metric(&self, metric_type: &str, value: f64, unit: Option<&str>, metadata: Option<&str>) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO performance_metrics (metric_type, value, unit, metadata) VALUES (?, ?, ?, ?)"
-        )?;
-        statement.bind((1, metric_type))?;
-        statement.bind((2, value))?;
-        statement.bind((3, unit.unwrap_or("")))?;
-        statement.bind((4, metadata.unwrap_or("")))?;
-        statement.next()?;
-        Ok(())
+    /// The story being told in this thread, if it hasn't ended
+    pub async fn get_active_story(&self, thread_id: &str) -> Result<Option<StorySession>> {
+        let thread_id = thread_id.to_string();
+        self.pool.interact(move |conn| {
+            let thread_id = thread_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, channel_id, thread_id, theme, persona, started_by, characters, inventory,
+                        chapter_summaries, turns_since_summary, created_at
+                 FROM story_sessions
+                 WHERE thread_id = ? AND status = 'active'"
+            )?;
+            statement.bind((1, thread_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(StorySession {
+                    id: statement.read::<i64, _>("id")?,
+                    guild_id: statement.read::<String, _>("guild_id")?,
+                    channel_id: statement.read::<String, _>("channel_id")?,
+                    thread_id: statement.read::<String, _>("thread_id")?,
+                    theme: statement.read::<String, _>("theme")?,
+                    persona: statement.read::<String, _>("persona")?,
+                    started_by: statement.read::<String, _>("started_by")?,
+                    characters: statement.read::<String, _>("characters")?,
+                    inventory: statement.read::<String, _>("inventory")?,
+                    chapter_summaries: statement.read::<String, _>("chapter_summaries")?,
+                    turns_since_summary: statement.read::<i64, _>("turns_since_summary")?,
+                    created_at: statement.read::<String, _>("created_at")?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
     }
 
-    // System Metrics Methods (for /sysinfo command)
+    /// Count a player's move in a story, returning the moves since the last chapter closed
+    pub async fn record_story_turn(&self, story_id: i64) -> Result<i64> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "UPDATE story_sessions SET turns_since_summary = turns_since_summary + 1 WHERE id = ?"
+            )?;
+            statement.bind((1, story_id))?;
+            statement.next()?;
 
-    /// Store a system metric snapshot (uses performance_metrics table)
-    pub async fn store_system_metric(&self, metric_type: &str, value: f64) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO performance_metrics (metric_type, value, unit, metadata) VALUES (?, ?, 'system', '')"
-        )?;
-        statement.bind((1, metric_type))?;
-        statement.bind((2, value))?;
-        statement.next()?;
-        Ok(())
+            let mut count_stmt = conn.prepare("SELECT turns_since_summary FROM story_sessions WHERE id = ?")?;
+            count_stmt.bind((1, story_id))?;
+            count_stmt.next()?;
+            count_stmt.read::<i64, _>(0)
+        })
+        .await
     }
 
-    /// Get historical metrics data for a specific metric type
-    /// Returns (unix_timestamp, value) pairs ordered by time ascending
-    pub async fn get_metrics_history(&self, metric_type: &str, hours: i64) -> Result<Vec<(i64, f64)>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT strftime('%s', timestamp) as unix_time, value
-             FROM performance_metrics
-             WHERE metric_type = ? AND timestamp >= datetime('now', ? || ' hours')
-             ORDER BY timestamp ASC"
-        )?;
-        statement.bind((1, metric_type))?;
-        statement.bind((2, format!("-{}", hours).as_str()))?;
-
-        let mut results = Vec::new();
-        while let Ok(State::Row) = statement.next() {
-            let timestamp_str = statement.read::<String, _>(0)?;
-            let timestamp = timestamp_str.parse::<i64>().unwrap_or(0);
-            let value = statement.read::<f64, _>(1)?;
-            results.push((timestamp, value));
-        }
-        Ok(results)
+    /// Save a story's state after a chapter closes, starting the move count again
+    pub async fn update_story_state(
+        &self,
+        story_id: i64,
+        characters: &str,
+        inventory: &str,
+        chapter_summaries: &str,
+    ) -> Result<()> {
+        let (characters, inventory, chapter_summaries) = (
+            characters.to_string(),
+            inventory.to_string(),
+            chapter_summaries.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (characters, inventory, chapter_summaries) = (
+                characters.as_str(),
+                inventory.as_str(),
+                chapter_summaries.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "UPDATE story_sessions
+                 SET characters = ?, inventory = ?, chapter_summaries = ?, turns_since_summary = 0
+                 WHERE id = ?"
+            )?;
+            statement.bind((1, characters))?;
+            statement.bind((2, inventory))?;
+            statement.bind((3, chapter_summaries))?;
+            statement.bind((4, story_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    /// Cleanup old metrics data (keep last N days)
-    pub async fn cleanup_old_metrics(&self, days: i64) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "DELETE FROM performance_metrics WHERE unit = 'system' AND timestamp < datetime('now', ? || ' days')"
-        )?;
-        statement.bind((1, format!("-{}", days).as_str()))?;
-        statement.next()?;
-        info!("Cleaned up system metrics older than {} days", days);
-        Ok(())
+    /// Mark a story as ended; its thread is then treated as an ordinary thread
+    pub async fn end_story_session(&self, story_id: i64) -> Result<()> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "UPDATE story_sessions SET status = 'ended', ended_at = CURRENT_TIMESTAMP WHERE id = ?"
+            )?;
+            statement.bind((1, story_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
+    /// Start a focus session in a channel, returning its ID, or None when one is already running there
     #[allow(clippy::too_many_arguments)]
-    pub async fn log_error(
+    pub async fn start_focus_session(
         &self,
-        error_type: &str,
-        error_message: &str,
-        stack_trace: Option<&str>,
-        user_id: Option<&str>,
-        channel_id: Option<&str>,
-        command: Option<&str>,
-        metadata: Option<&str>,
-    ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO error_logs (error_type, error_message, stack_trace, user_id, channel_id, command, metadata)
-             VALUES (?, ?, ?, ?, ?, ?, ?)"
-        )?;
-        statement.bind((1, error_type))?;
-        statement.bind((2, error_message))?;
-        statement.bind((3, stack_trace.unwrap_or("")))?;
-        statement.bind((4, user_id.unwrap_or("")))?;
-        statement.bind((5, channel_id.unwrap_or("")))?;
-        statement.bind((6, command.unwrap_or("")))?;
-        statement.bind((7, metadata.unwrap_or("")))?;
-        statement.next()?;
+        guild_id: &str,
+        channel_id: &str,
+        voice_channel_id: Option<&str>,
+        started_by: &str,
+        participants: &str,
+        focus_minutes: i64,
+        break_minutes: i64,
+        rounds: i64,
+        silence: Option<&str>,
+    ) -> Result<Option<i64>> {
+        let (guild_id, channel_id, voice_channel_id, started_by, participants, silence) = (
+            guild_id.to_string(),
+            channel_id.to_string(),
+            voice_channel_id.map(str::to_string),
+            started_by.to_string(),
+            participants.to_string(),
+            silence.map(str::to_string),
+        );
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, voice_channel_id, started_by, participants, silence) = (
+                guild_id.as_str(),
+                channel_id.as_str(),
+                voice_channel_id.as_deref(),
+                started_by.as_str(),
+                participants.as_str(),
+                silence.as_deref(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO focus_sessions
+                    (guild_id, channel_id, voice_channel_id, started_by, participants, focus_minutes, break_minutes, rounds, phase_ends_at, silence)
+                 VALUES (?, ?, NULLIF(?, ''), ?, ?, ?, ?, ?, ?, NULLIF(?, ''))
+                 ON CONFLICT DO NOTHING"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, voice_channel_id.unwrap_or("")))?;
+            statement.bind((4, started_by))?;
+            statement.bind((5, participants))?;
+            statement.bind((6, focus_minutes))?;
+            statement.bind((7, break_minutes))?;
+            statement.bind((8, rounds))?;
+            statement.bind((9, db.timestamp_from_now(Duration::minutes(focus_minutes)).as_str()))?;
+            statement.bind((10, silence.unwrap_or("")))?;
+            statement.next()?;
+
+            if conn.changes()? == 0 {
+                return Ok(None);
+            }
+            Ok(Some(conn.last_insert_id()?))
+        })
+        .await
+    }
 
-        // Also increment daily error count
-        self.increment_daily_stat("error").await?;
-        Ok(())
+    /// The focus session running in a channel
+    pub async fn get_focus_session(&self, channel_id: &str) -> Result<Option<FocusSession>> {
+        let channel_id = channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let channel_id = channel_id.as_str();
+            let mut statement = conn.prepare(if conn.is_postgres() {
+                "SELECT id, guild_id, channel_id, voice_channel_id, started_by, participants, focus_minutes, break_minutes,
+                        rounds, current_round, phase, CAST(EXTRACT(EPOCH FROM CAST(phase_ends_at AS TIMESTAMP)) AS BIGINT), silence
+                 FROM focus_sessions WHERE channel_id = ?"
+            } else {
+                "SELECT id, guild_id, channel_id, voice_channel_id, started_by, participants, focus_minutes, break_minutes,
+                        rounds, current_round, phase, strftime('%s', phase_ends_at), silence
+                 FROM focus_sessions WHERE channel_id = ?"
+            })?;
+            statement.bind((1, channel_id))?;
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(read_focus_session(&statement)?))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
     }
 
-    // Feature Flag Methods
-    pub async fn set_feature_flag(
-        &self,
-        feature_name: &str,
-        enabled: bool,
-        user_id: Option<&str>,
-        guild_id: Option<&str>,
-    ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT OR REPLACE INTO feature_flags (feature_name, enabled, user_id, guild_id, updated_at)
-             VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)"
-        )?;
-        statement.bind((1, feature_name))?;
-        statement.bind((2, if enabled { 1i64 } else { 0i64 }))?;
-        statement.bind((3, user_id.unwrap_or("")))?;
-        statement.bind((4, guild_id.unwrap_or("")))?;
-        statement.next()?;
-        Ok(())
+    /// Focus sessions whose current phase has run out
+    pub async fn get_due_focus_sessions(&self) -> Result<Vec<FocusSession>> {
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(if conn.is_postgres() {
+                "SELECT id, guild_id, channel_id, voice_channel_id, started_by, participants, focus_minutes, break_minutes,
+                        rounds, current_round, phase, CAST(EXTRACT(EPOCH FROM CAST(phase_ends_at AS TIMESTAMP)) AS BIGINT), silence
+                 FROM focus_sessions WHERE phase_ends_at <= ?"
+            } else {
+                "SELECT id, guild_id, channel_id, voice_channel_id, started_by, participants, focus_minutes, break_minutes,
+                        rounds, current_round, phase, strftime('%s', phase_ends_at), silence
+                 FROM focus_sessions WHERE phase_ends_at <= ?"
+            })?;
+            statement.bind((1, db.now().as_str()))?;
+
+            let mut sessions = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                sessions.push(read_focus_session(&statement)?);
+            }
+            Ok(sessions)
+        })
+        .await
     }
 
-    /// Check if a feature is enabled for a guild
-    /// Returns true by default if no record exists (features are enabled unless explicitly disabled)
-    pub async fn is_feature_enabled(&self, feature_name: &str, user_id: Option<&str>, guild_id: Option<&str>) -> Result<bool> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT enabled FROM feature_flags
-             WHERE feature_name = ? AND user_id = ? AND guild_id = ?
-             LIMIT 1"
-        )?;
-        statement.bind((1, feature_name))?;
-        statement.bind((2, user_id.unwrap_or("")))?;
-        statement.bind((3, guild_id.unwrap_or("")))?;
+    /// Move a focus session into its next phase, which lasts `minutes` from now
+    pub async fn advance_focus_session(&self, session_id: i64, phase: &str, round: i64, minutes: i64) -> Result<()> {
+        let phase = phase.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let phase = phase.as_str();
+            let mut statement = conn.prepare(
+                "UPDATE focus_sessions SET phase = ?, current_round = ?, phase_ends_at = ? WHERE id = ?"
+            )?;
+            statement.bind((1, phase))?;
+            statement.bind((2, round))?;
+            statement.bind((3, db.timestamp_from_now(Duration::minutes(minutes)).as_str()))?;
+            statement.bind((4, session_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
 
-        if let Ok(State::Row) = statement.next() {
-            let enabled = statement.read::<i64, _>(0)?;
-            Ok(enabled == 1)
-        } else {
-            // Default to enabled if no explicit setting exists
-            Ok(true)
-        }
+    /// Replace a focus session's participants (JSON array of user IDs)
+    pub async fn update_focus_participants(&self, session_id: i64, participants: &str) -> Result<()> {
+        let participants = participants.to_string();
+        self.pool.interact(move |conn| {
+            let participants = participants.as_str();
+            let mut statement = conn.prepare("UPDATE focus_sessions SET participants = ? WHERE id = ?")?;
+            statement.bind((1, participants))?;
+            statement.bind((2, session_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    /// Get all feature flags for a guild
-    /// Returns a map of feature_name -> enabled status
-    pub async fn get_guild_feature_flags(&self, guild_id: &str) -> Result<std::collections::HashMap<String, bool>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT feature_name, enabled FROM feature_flags
-             WHERE guild_id = ? AND user_id = ''"
-        )?;
-        statement.bind((1, guild_id))?;
+    /// Forget a focus session once it has finished or been stopped
+    pub async fn end_focus_session(&self, session_id: i64) -> Result<()> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare("DELETE FROM focus_sessions WHERE id = ?")?;
+            statement.bind((1, session_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
 
-        let mut flags = std::collections::HashMap::new();
-        while let Ok(State::Row) = statement.next() {
-            let feature_name = statement.read::<String, _>(0)?;
-            let enabled = statement.read::<i64, _>(1)? == 1;
-            flags.insert(feature_name, enabled);
-        }
-        Ok(flags)
+    /// Credit each user with a completed focus interval of `minutes`
+    pub async fn record_focus_completions(&self, guild_id: &str, channel_id: &str, user_ids: &[String], minutes: i64) -> Result<()> {
+        let (guild_id, channel_id, user_ids) = (guild_id.to_string(), channel_id.to_string(), user_ids.to_vec());
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, user_ids) = (guild_id.as_str(), channel_id.as_str(), user_ids.as_slice());
+            for user_id in user_ids {
+                let mut statement = conn.prepare(
+                    "INSERT INTO focus_completions (guild_id, user_id, channel_id, minutes) VALUES (?, ?, ?, ?)"
+                )?;
+                statement.bind((1, guild_id))?;
+                statement.bind((2, user_id.as_str()))?;
+                statement.bind((3, channel_id))?;
+                statement.bind((4, minutes))?;
+                statement.next()?;
+            }
+            Ok(())
+        })
+        .await
     }
 
-    /// Record a feature toggle action in the audit trail
-    pub async fn record_feature_toggle(
-        &self,
-        feature_name: &str,
-        version: &str,
-        guild_id: Option<&str>,
-        toggled_by: &str,
-        enabled: bool,
-    ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO feature_versions (feature_name, version, guild_id, toggled_by, enabled)
-             VALUES (?, ?, ?, ?, ?)"
-        )?;
-        statement.bind((1, feature_name))?;
-        statement.bind((2, version))?;
-        statement.bind((3, guild_id.unwrap_or("")))?;
-        statement.bind((4, toggled_by))?;
-        statement.bind((5, if enabled { 1i64 } else { 0i64 }))?;
-        statement.next()?;
-        info!("Recorded feature toggle: {feature_name} -> {enabled} by {toggled_by}");
-        Ok(())
+    /// `(user_id, completed intervals, minutes)` of a guild's most focused members over the last `days`
+    pub async fn get_focus_leaderboard(&self, guild_id: &str, days: i64, limit: i64) -> Result<Vec<(String, i64, i64)>> {
+        let guild_id = guild_id.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT user_id, COUNT(*) as completed, SUM(minutes) as total_minutes
+                 FROM focus_completions
+                 WHERE guild_id = ? AND completed_at >= ?
+                 GROUP BY user_id
+                 ORDER BY total_minutes DESC, completed DESC
+                 LIMIT ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+            statement.bind((3, limit))?;
+
+            let mut rows = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                rows.push((
+                    statement.read::<String, _>(0)?,
+                    statement.read::<i64, _>(1)?,
+                    statement.read::<i64, _>(2)?,
+                ));
+            }
+            Ok(rows)
+        })
+        .await
     }
 
-    // Guild Settings Methods
-    pub async fn set_guild_setting(&self, guild_id: &str, setting_key: &str, setting_value: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT OR REPLACE INTO guild_settings (guild_id, setting_key, setting_value, updated_at)
-             VALUES (?, ?, ?, CURRENT_TIMESTAMP)"
-        )?;
-        statement.bind((1, guild_id))?;
-        statement.bind((2, setting_key))?;
-        statement.bind((3, setting_value))?;
-        statement.next()?;
-        Ok(())
+    /// `(completed intervals, minutes)` of one member over the last `days`
+    pub async fn get_user_focus_totals(&self, guild_id: &str, user_id: &str, days: i64) -> Result<(i64, i64)> {
+        let (guild_id, user_id) = (guild_id.to_string(), user_id.to_string());
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (guild_id, user_id) = (guild_id.as_str(), user_id.as_str());
+            let mut statement = conn.prepare(
+                "SELECT COUNT(*), COALESCE(SUM(minutes), 0)
+                 FROM focus_completions
+                 WHERE guild_id = ? AND user_id = ? AND completed_at >= ?"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, user_id))?;
+            statement.bind((3, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+            statement.next()?;
+            Ok((statement.read::<i64, _>(0)?, statement.read::<i64, _>(1)?))
+        })
+        .await
     }
 
-    pub async fn get_guild_setting(&self, guild_id: &str, setting_key: &str) -> Result<Option<String>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT setting_value FROM guild_settings WHERE guild_id = ? AND setting_key = ?"
-        )?;
-        statement.bind((1, guild_id))?;
-        statement.bind((2, setting_key))?;
+    /// Note that an archived channel was deleted
+    pub async fn mark_channel_archive_deleted(&self, archive_id: i64) -> Result<()> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare("UPDATE channel_archives SET deleted = 1 WHERE id = ?")?;
+            statement.bind((1, archive_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
 
-        if let Ok(State::Row) = statement.next() {
-            Ok(Some(statement.read::<String, _>(0)?))
-        } else {
-            Ok(None)
-        }
+    /// Overwrites still waiting to be restored
+    pub async fn get_lockdown_overwrites(&self, guild_id: &str) -> Result<Vec<LockdownOverwrite>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT channel_id, had_overwrite, allow_bits, deny_bits FROM lockdown_overwrites WHERE guild_id = ?"
+            )?;
+            statement.bind((1, guild_id))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(LockdownOverwrite {
+                    channel_id: statement.read::<String, _>(0)?,
+                    had_overwrite: statement.read::<i64, _>(1)? != 0,
+                    allow_bits: statement.read::<i64, _>(2)?,
+                    deny_bits: statement.read::<i64, _>(3)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
     }
 
-    // Bot Settings Methods (global, not per-guild)
-    pub async fn set_bot_setting(&self, setting_key: &str, setting_value: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT OR REPLACE INTO bot_settings (setting_key, setting_value, updated_at)
-             VALUES (?, ?, CURRENT_TIMESTAMP)"
-        )?;
-        statement.bind((1, setting_key))?;
-        statement.bind((2, setting_value))?;
-        statement.next()?;
-        Ok(())
+    /// Forget a channel's stored overwrite once it's restored (or was never changed)
+    pub async fn remove_lockdown_overwrite(&self, guild_id: &str, channel_id: &str) -> Result<()> {
+        let (guild_id, channel_id) = (guild_id.to_string(), channel_id.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id) = (guild_id.as_str(), channel_id.as_str());
+            let mut statement = conn.prepare("DELETE FROM lockdown_overwrites WHERE guild_id = ? AND channel_id = ?")?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    pub async fn get_bot_setting(&self, setting_key: &str) -> Result<Option<String>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT setting_value FROM bot_settings WHERE setting_key = ?"
-        )?;
-        statement.bind((1, setting_key))?;
+    /// Clear the guild's lockdown and any remaining stored overwrites
+    pub async fn finish_lockdown(&self, guild_id: &str) -> Result<()> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            for sql in [
+                "DELETE FROM lockdown_overwrites WHERE guild_id = ?",
+                "DELETE FROM lockdowns WHERE guild_id = ?",
+            ] {
+                let mut statement = conn.prepare(sql)?;
+                statement.bind((1, guild_id))?;
+                statement.next()?;
+            }
+            Ok(())
+        })
+        .await
+    }
 
-        if let Ok(State::Row) = statement.next() {
-            Ok(Some(statement.read::<String, _>(0)?))
-        } else {
-            Ok(None)
-        }
+    // Join Screening Methods
+
+    /// Record a flagged join. `status` is `logged`, or `pending` while a captcha is open
+    /// (with `answer` the correct choice).
+    pub async fn record_join_screening(
+        &self,
+        guild_id: &str,
+        user_id: &str,
+        score: u32,
+        reasons: &str,
+        status: &str,
+        answer: i64,
+    ) -> Result<i64> {
+        let (guild_id, user_id, reasons, status) = (
+            guild_id.to_string(),
+            user_id.to_string(),
+            reasons.to_string(),
+            status.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, user_id, reasons, status) = (guild_id.as_str(), user_id.as_str(), reasons.as_str(), status.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO join_screenings (guild_id, user_id, score, reasons, status, answer) VALUES (?, ?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, user_id))?;
+            statement.bind((3, score as i64))?;
+            statement.bind((4, reasons))?;
+            statement.bind((5, status))?;
+            statement.bind((6, answer))?;
+            statement.next()?;
+
+            conn.last_insert_id()
+        })
+        .await
     }
 
-    // Extended User Preferences Methods
-    pub async fn set_user_preference(&self, user_id: &str, preference_key: &str, preference_value: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT OR REPLACE INTO extended_user_preferences (user_id, preference_key, preference_value, updated_at)
-             VALUES (?, ?, ?, CURRENT_TIMESTAMP)"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, preference_key))?;
-        statement.bind((3, preference_value))?;
-        statement.next()?;
-        Ok(())
+    /// Look up a join screening by ID
+    pub async fn get_join_screening(&self, id: i64) -> Result<Option<JoinScreening>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, user_id, score, status, answer, attempts FROM join_screenings WHERE id = ?"
+            )?;
+            statement.bind((1, id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(JoinScreening {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    user_id: statement.read::<String, _>(2)?,
+                    score: statement.read::<i64, _>(3)?,
+                    status: statement.read::<String, _>(4)?,
+                    answer: statement.read::<i64, _>(5)?,
+                    attempts: statement.read::<i64, _>(6)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Count a wrong captcha press, returning the attempts so far
+    pub async fn add_verification_attempt(&self, id: i64) -> Result<i64> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare("UPDATE join_screenings SET attempts = attempts + 1 WHERE id = ?")?;
+            statement.bind((1, id))?;
+            statement.next()?;
+
+            let mut stmt = conn.prepare("SELECT attempts FROM join_screenings WHERE id = ?")?;
+            stmt.bind((1, id))?;
+            stmt.next()?;
+            stmt.read::<i64, _>(0)
+        })
+        .await
     }
 
-    pub async fn get_user_preference(&self, user_id: &str, preference_key: &str) -> Result<Option<String>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT preference_value FROM extended_user_preferences WHERE user_id = ? AND preference_key = ?"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, preference_key))?;
+    /// Close a pending verification as `verified` or `failed`. Returns false if it
+    /// was no longer pending.
+    pub async fn resolve_join_screening(&self, id: i64, status: &str) -> Result<bool> {
+        let status = status.to_string();
+        self.pool.interact(move |conn| {
+            let status = status.as_str();
+            let mut statement = conn.prepare(
+                "UPDATE join_screenings SET status = ?, resolved_at = CURRENT_TIMESTAMP WHERE id = ? AND status = 'pending'"
+            )?;
+            statement.bind((1, status))?;
+            statement.bind((2, id))?;
+            statement.next()?;
 
-        if let Ok(State::Row) = statement.next() {
-            Ok(Some(statement.read::<String, _>(0)?))
-        } else {
-            Ok(None)
-        }
+            Ok(conn.changes()? > 0)
+        })
+        .await
     }
 
-    // Conflict Detection & Mediation Methods
+    // Verification Gate Methods
 
-    pub async fn record_conflict_detection(
+    /// Open a gate for a new member. `question` is empty in button-only mode; a
+    /// `timeout_minutes` of 0 means the member is never kicked.
+    pub async fn create_verification_gate(
         &self,
-        channel_id: &str,
-        guild_id: Option<&str>,
-        participants: &str, // JSON array of user IDs
-        detection_type: &str,
-        confidence: f32,
-        last_message_id: &str,
+        guild_id: &str,
+        user_id: &str,
+        question: &str,
+        answers: &[String],
+        timeout_minutes: i64,
     ) -> Result<i64> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO conflict_detection
-             (channel_id, guild_id, participants, detection_type, confidence_score, last_message_id)
-             VALUES (?, ?, ?, ?, ?, ?)"
-        )?;
-        statement.bind((1, channel_id))?;
-        statement.bind((2, guild_id.unwrap_or("")))?;
-        statement.bind((3, participants))?;
-        statement.bind((4, detection_type))?;
-        statement.bind((5, confidence as f64))?;
-        statement.bind((6, last_message_id))?;
-        statement.next()?;
-
-        // Get the ID of the inserted row
-        let mut id_statement = conn.prepare("SELECT last_insert_rowid()")?;
-        id_statement.next()?;
-        let conflict_id = id_statement.read::<i64, _>(0)?;
+        let (guild_id, user_id, question, answers) = (
+            guild_id.to_string(),
+            user_id.to_string(),
+            question.to_string(),
+            answers.to_vec(),
+        );
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let (guild_id, user_id, question, answers) = (
+                guild_id.as_str(),
+                user_id.as_str(),
+                question.as_str(),
+                answers.as_slice(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO verification_gates (guild_id, user_id, question, answers, kick_at)
+                 VALUES (?, ?, ?, ?, CASE WHEN ? > 0 THEN ? END)"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, user_id))?;
+            statement.bind((3, question))?;
+            statement.bind((4, answers.join("|").as_str()))?;
+            statement.bind((5, timeout_minutes))?;
+            statement.bind((6, db.timestamp_from_now(Duration::minutes(timeout_minutes)).as_str()))?;
+            statement.next()?;
+
+            conn.last_insert_id()
+        })
+        .await
+    }
 
-        info!("Recorded conflict detection in channel {channel_id} with confidence {confidence}");
-        Ok(conflict_id)
+    /// Remember where a gate's prompt was posted
+    pub async fn set_verification_gate_message(&self, id: i64, channel_id: &str, message_id: &str) -> Result<()> {
+        let (channel_id, message_id) = (channel_id.to_string(), message_id.to_string());
+        self.pool.interact(move |conn| {
+            let (channel_id, message_id) = (channel_id.as_str(), message_id.as_str());
+            let mut statement = conn.prepare("UPDATE verification_gates SET channel_id = ?, message_id = ? WHERE id = ?")?;
+            statement.bind((1, channel_id))?;
+            statement.bind((2, message_id))?;
+            statement.bind((3, id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    pub async fn mark_conflict_resolved(&self, conflict_id: i64) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "UPDATE conflict_detection SET resolved_at = CURRENT_TIMESTAMP WHERE id = ?"
-        )?;
-        statement.bind((1, conflict_id))?;
-        statement.next()?;
-        info!("Marked conflict {conflict_id} as resolved");
-        Ok(())
+    /// Look up a verification gate by ID
+    pub async fn get_verification_gate(&self, id: i64) -> Result<Option<VerificationGate>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, user_id, question, answers, status, channel_id, message_id
+                 FROM verification_gates WHERE id = ?"
+            )?;
+            statement.bind((1, id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(VerificationGate {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    user_id: statement.read::<String, _>(2)?,
+                    question: statement.read::<String, _>(3)?,
+                    answers: statement
+                        .read::<String, _>(4)?
+                        .split('|')
+                        .filter(|a| !a.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                    status: statement.read::<String, _>(5)?,
+                    channel_id: statement.read::<String, _>(6)?,
+                    message_id: statement.read::<String, _>(7)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
     }
 
-    pub async fn mark_mediation_triggered(&self, conflict_id: i64, message_id: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "UPDATE conflict_detection
-             SET mediation_triggered = 1, mediation_message_id = ?
-             WHERE id = ?"
-        )?;
-        statement.bind((1, message_id))?;
-        statement.bind((2, conflict_id))?;
-        statement.next()?;
-        Ok(())
+    /// Pending gates whose kick deadline has passed
+    pub async fn get_expired_verification_gates(&self) -> Result<Vec<VerificationGate>> {
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, user_id, question, answers, status, channel_id, message_id
+                 FROM verification_gates
+                 WHERE status = 'pending' AND kick_at IS NOT NULL AND kick_at <= ?"
+            )?;
+            statement.bind((1, db.now().as_str()))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(VerificationGate {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    user_id: statement.read::<String, _>(2)?,
+                    question: statement.read::<String, _>(3)?,
+                    answers: statement
+                        .read::<String, _>(4)?
+                        .split('|')
+                        .filter(|a| !a.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                    status: statement.read::<String, _>(5)?,
+                    channel_id: statement.read::<String, _>(6)?,
+                    message_id: statement.read::<String, _>(7)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
     }
 
-    pub async fn get_channel_active_conflict(&self, channel_id: &str) -> Result<Option<i64>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT id FROM conflict_detection
-             WHERE channel_id = ? AND resolved_at IS NULL
-             ORDER BY last_detected DESC LIMIT 1"
-        )?;
-        statement.bind((1, channel_id))?;
+    /// Close a pending gate as `verified` or `kicked`. Returns false if it was
+    /// already closed.
+    pub async fn resolve_verification_gate(&self, id: i64, status: &str) -> Result<bool> {
+        let status = status.to_string();
+        self.pool.interact(move |conn| {
+            let status = status.as_str();
+            let mut statement = conn.prepare(
+                "UPDATE verification_gates SET status = ?, resolved_at = CURRENT_TIMESTAMP WHERE id = ? AND status = 'pending'"
+            )?;
+            statement.bind((1, status))?;
+            statement.bind((2, id))?;
+            statement.next()?;
 
-        if let Ok(State::Row) = statement.next() {
-            Ok(Some(statement.read::<i64, _>(0)?))
-        } else {
-            Ok(None)
-        }
+            Ok(conn.changes()? > 0)
+        })
+        .await
     }
 
-    pub async fn record_mediation(
+    /// Link a Discord channel to a Slack channel, replacing any existing link for it.
+    /// Relaying from Slack starts after `start_ts`.
+    pub async fn create_bridge_link(
         &self,
-        conflict_id: i64,
-        channel_id: &str,
-        message_text: &str,
+        guild_id: &str,
+        discord_channel_id: &str,
+        slack_channel_id: &str,
+        start_ts: &str,
+        created_by: &str,
     ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO mediation_history (conflict_id, channel_id, mediation_message)
-             VALUES (?, ?, ?)"
-        )?;
-        statement.bind((1, conflict_id))?;
-        statement.bind((2, channel_id))?;
-        statement.bind((3, message_text))?;
-        statement.next()?;
-        info!("Recorded mediation for conflict {conflict_id}");
-        Ok(())
+        let (guild_id, discord_channel_id, slack_channel_id, start_ts, created_by) = (
+            guild_id.to_string(),
+            discord_channel_id.to_string(),
+            slack_channel_id.to_string(),
+            start_ts.to_string(),
+            created_by.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, discord_channel_id, slack_channel_id, start_ts, created_by) = (
+                guild_id.as_str(),
+                discord_channel_id.as_str(),
+                slack_channel_id.as_str(),
+                start_ts.as_str(),
+                created_by.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO bridge_links (guild_id, discord_channel_id, slack_channel_id, last_slack_ts, created_by)
+                 VALUES (?, ?, ?, ?, ?)
+                 ON CONFLICT(discord_channel_id) DO UPDATE SET
+                 guild_id = excluded.guild_id, slack_channel_id = excluded.slack_channel_id, last_slack_ts = excluded.last_slack_ts,
+                 created_by = excluded.created_by, created_at = excluded.created_at"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, discord_channel_id))?;
+            statement.bind((3, slack_channel_id))?;
+            statement.bind((4, start_ts))?;
+            statement.bind((5, created_by))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    /// Get the timestamp of the last mediation in a channel
-    pub async fn get_last_mediation_timestamp(&self, channel_id: &str) -> Result<Option<i64>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT strftime('%s', mh.created_at) as unix_time
-             FROM mediation_history mh
-             WHERE mh.channel_id = ?
-             ORDER BY mh.created_at DESC
-             LIMIT 1"
-        )?;
-        statement.bind((1, channel_id))?;
-
-        if let Ok(State::Row) = statement.next() {
-            let timestamp_str = statement.read::<String, _>(0)?;
-            Ok(Some(timestamp_str.parse::<i64>()?))
-        } else {
-            Ok(None)
-        }
+    /// The bridge for a Discord channel, if any
+    pub async fn get_bridge_link(&self, discord_channel_id: &str) -> Result<Option<BridgeLink>> {
+        let discord_channel_id = discord_channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let discord_channel_id = discord_channel_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, discord_channel_id, slack_channel_id, last_slack_ts
+                 FROM bridge_links WHERE discord_channel_id = ?"
+            )?;
+            statement.bind((1, discord_channel_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(BridgeLink {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    discord_channel_id: statement.read::<String, _>(2)?,
+                    slack_channel_id: statement.read::<String, _>(3)?,
+                    last_slack_ts: statement.read::<String, _>(4)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
     }
 
-    pub async fn get_recent_channel_messages(
-        &self,
-        channel_id: &str,
-        limit: usize,
-    ) -> Result<Vec<(String, String, String)>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT user_id, content, strftime('%s', timestamp) as unix_time
-             FROM conversation_history
-             WHERE channel_id = ?
-             ORDER BY timestamp DESC
-             LIMIT ?"
-        )?;
-        statement.bind((1, channel_id))?;
-        statement.bind((2, limit as i64))?;
-
-        let mut messages = Vec::new();
-        while let Ok(State::Row) = statement.next() {
-            let user_id = statement.read::<String, _>(0)?;
-            let content = statement.read::<String, _>(1)?;
-            let timestamp = statement.read::<String, _>(2)?;
-            messages.push((user_id, content, timestamp));
-        }
+    /// Bridges in one guild, or every bridge when `guild_id` is None
+    pub async fn get_bridge_links(&self, guild_id: Option<&str>) -> Result<Vec<BridgeLink>> {
+        let guild_id = guild_id.map(str::to_string);
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_deref();
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, discord_channel_id, slack_channel_id, last_slack_ts
+                 FROM bridge_links WHERE NULLIF(?, '') IS NULL OR guild_id = ? ORDER BY id"
+            )?;
+            let guild_id = guild_id.unwrap_or("");
+            statement.bind((1, guild_id))?;
+            statement.bind((2, guild_id))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(BridgeLink {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    discord_channel_id: statement.read::<String, _>(2)?,
+                    slack_channel_id: statement.read::<String, _>(3)?,
+                    last_slack_ts: statement.read::<String, _>(4)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
+    }
 
-        // Reverse to get chronological order
-        messages.reverse();
-        Ok(messages)
+    /// Record the newest Slack message relayed over a bridge
+    pub async fn set_bridge_last_ts(&self, id: i64, last_slack_ts: &str) -> Result<()> {
+        let last_slack_ts = last_slack_ts.to_string();
+        self.pool.interact(move |conn| {
+            let last_slack_ts = last_slack_ts.as_str();
+            let mut statement = conn.prepare("UPDATE bridge_links SET last_slack_ts = ? WHERE id = ?")?;
+            statement.bind((1, last_slack_ts))?;
+            statement.bind((2, id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    /// Get recent channel messages that occurred after a specific timestamp
-    /// This is used to avoid re-analyzing messages that have already been mediated
-    pub async fn get_recent_channel_messages_since(
-        &self,
-        channel_id: &str,
-        since_timestamp: i64,
-        limit: usize,
-    ) -> Result<Vec<(String, String, String)>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT user_id, content, strftime('%s', timestamp) as unix_time
-             FROM conversation_history
-             WHERE channel_id = ?
-               AND CAST(strftime('%s', timestamp) AS INTEGER) > ?
-             ORDER BY timestamp DESC
-             LIMIT ?"
-        )?;
-        statement.bind((1, channel_id))?;
-        statement.bind((2, since_timestamp))?;
-        statement.bind((3, limit as i64))?;
-
-        let mut messages = Vec::new();
-        while let Ok(State::Row) = statement.next() {
-            let user_id = statement.read::<String, _>(0)?;
-            let content = statement.read::<String, _>(1)?;
-            let timestamp = statement.read::<String, _>(2)?;
-            messages.push((user_id, content, timestamp));
-        }
+    /// Remove a Discord channel's bridge. Returns false if it had none.
+    pub async fn delete_bridge_link(&self, discord_channel_id: &str) -> Result<bool> {
+        let discord_channel_id = discord_channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let discord_channel_id = discord_channel_id.as_str();
+            let mut statement = conn.prepare("DELETE FROM bridge_links WHERE discord_channel_id = ?")?;
+            statement.bind((1, discord_channel_id))?;
+            statement.next()?;
 
-        // Reverse to get chronological order
-        messages.reverse();
-        Ok(messages)
+            Ok(conn.changes()? > 0)
+        })
+        .await
     }
 
-    pub async fn update_user_interaction_pattern(
+    /// Map a Discord channel to a Matrix room, replacing any existing mapping for it
+    pub async fn create_matrix_link(
         &self,
-        user_id_a: &str,
-        user_id_b: &str,
-        channel_id: &str,
-        is_conflict: bool,
+        guild_id: &str,
+        discord_channel_id: &str,
+        matrix_room_id: &str,
+        created_by: &str,
     ) -> Result<()> {
-        let conn = self.connection.lock().await;
-
-        // Ensure user_id_a is always lexicographically smaller (for consistent lookups)
-        let (user_a, user_b) = if user_id_a < user_id_b {
-            (user_id_a, user_id_b)
-        } else {
-            (user_id_b, user_id_a)
-        };
-
-        let conflict_increment = if is_conflict { 1 } else { 0 };
-
-        let mut statement = conn.prepare(
-            "INSERT INTO user_interaction_patterns
-             (user_id_a, user_id_b, channel_id, interaction_count, conflict_incidents, last_interaction)
-             VALUES (?, ?, ?, 1, ?, CURRENT_TIMESTAMP)
-             ON CONFLICT(user_id_a, user_id_b, channel_id) DO UPDATE SET
-             interaction_count = interaction_count + 1,
-             conflict_incidents = conflict_incidents + ?,
-             last_interaction = CURRENT_TIMESTAMP"
-        )?;
-        statement.bind((1, user_a))?;
-        statement.bind((2, user_b))?;
-        statement.bind((3, channel_id))?;
-        statement.bind((4, conflict_increment))?;
-        statement.bind((5, conflict_increment))?;
-        statement.next()?;
-        Ok(())
+        let (guild_id, discord_channel_id, matrix_room_id, created_by) = (
+            guild_id.to_string(),
+            discord_channel_id.to_string(),
+            matrix_room_id.to_string(),
+            created_by.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, discord_channel_id, matrix_room_id, created_by) = (
+                guild_id.as_str(),
+                discord_channel_id.as_str(),
+                matrix_room_id.as_str(),
+                created_by.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO matrix_links (guild_id, discord_channel_id, matrix_room_id, created_by)
+                 VALUES (?, ?, ?, ?)
+                 ON CONFLICT(discord_channel_id) DO UPDATE SET
+                 guild_id = excluded.guild_id, matrix_room_id = excluded.matrix_room_id,
+                 created_by = excluded.created_by, created_at = excluded.created_at"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, discord_channel_id))?;
+            statement.bind((3, matrix_room_id))?;
+            statement.bind((4, created_by))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    // Channel Settings Methods
-
-    /// Get verbosity for a channel, falling back to guild default, then "concise"
-    pub async fn get_channel_verbosity(&self, guild_id: &str, channel_id: &str) -> Result<String> {
-        let conn = self.connection.lock().await;
-
-        // First try channel-specific setting
-        let mut statement = conn.prepare(
-            "SELECT verbosity FROM channel_settings WHERE guild_id = ? AND channel_id = ?"
-        )?;
-        statement.bind((1, guild_id))?;
-        statement.bind((2, channel_id))?;
+    /// The Matrix room a Discord channel is mapped to, if any
+    pub async fn get_matrix_link(&self, discord_channel_id: &str) -> Result<Option<MatrixLink>> {
+        let discord_channel_id = discord_channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let discord_channel_id = discord_channel_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, discord_channel_id, matrix_room_id FROM matrix_links WHERE discord_channel_id = ?"
+            )?;
+            statement.bind((1, discord_channel_id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(MatrixLink {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    discord_channel_id: statement.read::<String, _>(2)?,
+                    matrix_room_id: statement.read::<String, _>(3)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
 
-        if let Ok(State::Row) = statement.next() {
-            return Ok(statement.read::<String, _>(0)?);
-        }
+    /// Matrix mappings in one guild, or every mapping when `guild_id` is None
+    pub async fn get_matrix_links(&self, guild_id: Option<&str>) -> Result<Vec<MatrixLink>> {
+        let guild_id = guild_id.map(str::to_string);
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_deref();
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, discord_channel_id, matrix_room_id
+                 FROM matrix_links WHERE NULLIF(?, '') IS NULL OR guild_id = ? ORDER BY id"
+            )?;
+            let guild_id = guild_id.unwrap_or("");
+            statement.bind((1, guild_id))?;
+            statement.bind((2, guild_id))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(MatrixLink {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    discord_channel_id: statement.read::<String, _>(2)?,
+                    matrix_room_id: statement.read::<String, _>(3)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
+    }
 
-        // Fall back to guild default
-        drop(statement);
-        let mut guild_stmt = conn.prepare(
-            "SELECT setting_value FROM guild_settings WHERE guild_id = ? AND setting_key = 'default_verbosity'"
-        )?;
-        guild_stmt.bind((1, guild_id))?;
+    /// Remove a Discord channel's Matrix mapping. Returns false if it had none.
+    pub async fn delete_matrix_link(&self, discord_channel_id: &str) -> Result<bool> {
+        let discord_channel_id = discord_channel_id.to_string();
+        self.pool.interact(move |conn| {
+            let discord_channel_id = discord_channel_id.as_str();
+            let mut statement = conn.prepare("DELETE FROM matrix_links WHERE discord_channel_id = ?")?;
+            statement.bind((1, discord_channel_id))?;
+            statement.next()?;
 
-        if let Ok(State::Row) = guild_stmt.next() {
-            return Ok(guild_stmt.read::<String, _>(0)?);
-        }
+            Ok(conn.changes()? > 0)
+        })
+        .await
+    }
 
-        // Default to concise
-        Ok("concise".to_string())
+    /// One guild's totals for a single day (`YYYY-MM-DD`), for spreadsheet exports
+    pub async fn get_guild_daily_stats(&self, guild_id: &str, date: &str) -> Result<GuildDailyStats> {
+        let (guild_id, date) = (guild_id.to_string(), date.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, date) = (guild_id.as_str(), date.as_str());
+            let mut statement = conn.prepare(
+                "SELECT
+                    (SELECT member_count FROM known_guilds WHERE guild_id = ?1),
+                    (SELECT COUNT(*) FROM member_activity WHERE guild_id = ?1 AND joined_at = ?2),
+                    (SELECT COUNT(*) FROM member_activity WHERE guild_id = ?1 AND first_seen = ?2),
+                    COALESCE((SELECT command_count FROM guild_activity_daily WHERE guild_id = ?1 AND date = ?2), 0),
+                    COALESCE((SELECT error_count FROM guild_activity_daily WHERE guild_id = ?1 AND date = ?2), 0),
+                    COALESCE((SELECT SUM(request_count) FROM openai_usage_feature_daily WHERE guild_id = ?1 AND date = ?2), 0),
+                    COALESCE((SELECT SUM(total_tokens) FROM openai_usage_feature_daily WHERE guild_id = ?1 AND date = ?2), 0),
+                    COALESCE((SELECT SUM(total_cost_usd) FROM openai_usage_feature_daily WHERE guild_id = ?1 AND date = ?2), 0.0)"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, date))?;
+            statement.next()?;
+
+            Ok(GuildDailyStats {
+                date: date.to_string(),
+                member_count: statement.read::<Option<i64>, _>(0)?,
+                new_members: statement.read::<i64, _>(1)?,
+                first_time_posters: statement.read::<i64, _>(2)?,
+                commands: statement.read::<i64, _>(3)?,
+                errors: statement.read::<i64, _>(4)?,
+                ai_requests: statement.read::<i64, _>(5)?,
+                ai_tokens: statement.read::<i64, _>(6)?,
+                ai_cost_usd: statement.read::<f64, _>(7)?,
+            })
+        })
+        .await
     }
 
-    /// Set verbosity for a specific channel
-    pub async fn set_channel_verbosity(&self, guild_id: &str, channel_id: &str, verbosity: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO channel_settings (guild_id, channel_id, verbosity, updated_at)
-             VALUES (?, ?, ?, CURRENT_TIMESTAMP)
-             ON CONFLICT(guild_id, channel_id) DO UPDATE SET
-             verbosity = excluded.verbosity,
-             updated_at = CURRENT_TIMESTAMP"
-        )?;
-        statement.bind((1, guild_id))?;
-        statement.bind((2, channel_id))?;
-        statement.bind((3, verbosity))?;
-        statement.next()?;
-        info!("Set verbosity for channel {channel_id} to {verbosity}");
-        Ok(())
+    /// Rows of `table` whose `date_column` falls in `[from, to)`, each column as text (NULL as None).
+    /// The table and column names are interpolated, so only pass trusted identifiers.
+    pub async fn get_table_rows_between(
+        &self,
+        table: &str,
+        columns: &[&str],
+        date_column: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<Vec<Option<String>>>> {
+        let (table, columns, date_column, from, to) = (
+            table.to_string(),
+            columns.iter().map(|c| c.to_string()).collect::<Vec<_>>(),
+            date_column.to_string(),
+            from.to_string(),
+            to.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (table, columns, date_column, from, to) = (
+                table.as_str(),
+                &columns,
+                date_column.as_str(),
+                from.as_str(),
+                to.as_str(),
+            );
+            let select = columns.iter().map(|c| format!("CAST({c} AS TEXT)")).collect::<Vec<_>>().join(", ");
+            let mut statement = conn.prepare(format!(
+                "SELECT {select} FROM {table} WHERE {date_column} >= ? AND {date_column} < ? ORDER BY {date_column}, id"
+            ))?;
+            statement.bind((1, from))?;
+            statement.bind((2, to))?;
+
+            let mut rows = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                let row = (0..columns.len())
+                    .map(|i| statement.read::<Option<String>, _>(i))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                rows.push(row);
+            }
+            Ok(rows)
+        })
+        .await
     }
 
-    /// Get all settings for a channel
-    pub async fn get_channel_settings(&self, guild_id: &str, channel_id: &str) -> Result<(String, bool)> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT verbosity, conflict_enabled FROM channel_settings WHERE guild_id = ? AND channel_id = ?"
-        )?;
-        statement.bind((1, guild_id))?;
-        statement.bind((2, channel_id))?;
+    // Webhook Source Methods
 
-        if let Ok(State::Row) = statement.next() {
-            let verbosity = statement.read::<String, _>(0)?;
-            let conflict_enabled = statement.read::<i64, _>(1)? == 1;
-            Ok((verbosity, conflict_enabled))
-        } else {
-            // Return defaults
-            Ok(("concise".to_string(), true))
-        }
+    /// Register a webhook source. Fails if the guild already has a source with this name.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_webhook_source(
+        &self,
+        guild_id: &str,
+        channel_id: &str,
+        name: &str,
+        token: &str,
+        persona: &str,
+        template: Option<&str>,
+        rate_limit_per_minute: i64,
+        created_by: &str,
+    ) -> Result<i64> {
+        let (guild_id, channel_id, name, token, persona, template, created_by) = (
+            guild_id.to_string(),
+            channel_id.to_string(),
+            name.to_string(),
+            token.to_string(),
+            persona.to_string(),
+            template.map(str::to_string),
+            created_by.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, name, token, persona, template, created_by) = (
+                guild_id.as_str(),
+                channel_id.as_str(),
+                name.as_str(),
+                token.as_str(),
+                persona.as_str(),
+                template.as_deref(),
+                created_by.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO webhook_sources (guild_id, channel_id, name, token, persona, template, rate_limit_per_minute, created_by)
+                 VALUES (?, ?, ?, ?, ?, NULLIF(?, ''), ?, ?)"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, name))?;
+            statement.bind((4, token))?;
+            statement.bind((5, persona))?;
+            statement.bind((6, template.unwrap_or("")))?;
+            statement.bind((7, rate_limit_per_minute))?;
+            statement.bind((8, created_by))?;
+            statement.next()?;
+
+            conn.last_insert_id()
+        })
+        .await
     }
 
-    /// Set whether conflict detection is enabled for a channel
-    pub async fn set_channel_conflict_enabled(&self, guild_id: &str, channel_id: &str, enabled: bool) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO channel_settings (guild_id, channel_id, conflict_enabled, updated_at)
-             VALUES (?, ?, ?, CURRENT_TIMESTAMP)
-             ON CONFLICT(guild_id, channel_id) DO UPDATE SET
-             conflict_enabled = excluded.conflict_enabled,
-             updated_at = CURRENT_TIMESTAMP"
-        )?;
-        statement.bind((1, guild_id))?;
-        statement.bind((2, channel_id))?;
-        statement.bind((3, if enabled { 1i64 } else { 0i64 }))?;
-        statement.next()?;
-        info!("Set conflict_enabled for channel {channel_id} to {enabled}");
-        Ok(())
+    /// A webhook source by ID
+    pub async fn get_webhook_source(&self, id: i64) -> Result<Option<WebhookSource>> {
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, channel_id, name, token, persona, template, rate_limit_per_minute
+                 FROM webhook_sources WHERE id = ?"
+            )?;
+            statement.bind((1, id))?;
+
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(WebhookSource {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    channel_id: statement.read::<String, _>(2)?,
+                    name: statement.read::<String, _>(3)?,
+                    token: statement.read::<String, _>(4)?,
+                    persona: statement.read::<String, _>(5)?,
+                    template: statement.read::<Option<String>, _>(6)?,
+                    rate_limit_per_minute: statement.read::<i64, _>(7)?,
+                }))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
     }
 
-    /// Check if a user has the bot admin role for a guild
-    pub async fn has_bot_admin_role(&self, guild_id: &str, user_roles: &[String]) -> Result<bool> {
-        // Get the bot admin role ID from guild settings
-        let admin_role = self.get_guild_setting(guild_id, "bot_admin_role").await?;
+    /// A guild's webhook sources, by name
+    pub async fn get_webhook_sources(&self, guild_id: &str) -> Result<Vec<WebhookSource>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, channel_id, name, token, persona, template, rate_limit_per_minute
+                 FROM webhook_sources WHERE guild_id = ? ORDER BY name"
+            )?;
+            statement.bind((1, guild_id))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(WebhookSource {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    channel_id: statement.read::<String, _>(2)?,
+                    name: statement.read::<String, _>(3)?,
+                    token: statement.read::<String, _>(4)?,
+                    persona: statement.read::<String, _>(5)?,
+                    template: statement.read::<Option<String>, _>(6)?,
+                    rate_limit_per_minute: statement.read::<i64, _>(7)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
+    }
 
-        if let Some(role_id) = admin_role {
-            Ok(user_roles.iter().any(|r| r == &role_id))
-        } else {
-            // No bot admin role set - only Discord admins can manage
-            Ok(false)
-        }
+    /// Remove a guild's webhook source by name. Returns false if there was none.
+    pub async fn delete_webhook_source(&self, guild_id: &str, name: &str) -> Result<bool> {
+        let (guild_id, name) = (guild_id.to_string(), name.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, name) = (guild_id.as_str(), name.as_str());
+            let mut statement = conn.prepare("DELETE FROM webhook_sources WHERE guild_id = ? AND name = ?")?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, name))?;
+            statement.next()?;
+
+            Ok(conn.changes()? > 0)
+        })
+        .await
     }
 
-    // OpenAI Usage Tracking Methods
+    // Calendar Subscription Methods
 
-    /// Log a ChatCompletion (GPT) usage event
+    /// Subscribe a channel to an iCal feed
     #[allow(clippy::too_many_arguments)]
-    pub async fn log_openai_chat_usage(
+    pub async fn create_calendar_subscription(
         &self,
-        model: &str,
-        input_tokens: u32,
-        output_tokens: u32,
-        total_tokens: u32,
-        estimated_cost: f64,
-        user_id: &str,
-        guild_id: Option<&str>,
-        channel_id: Option<&str>,
-        request_id: Option<&str>,
-    ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
-
-        // Insert into raw usage table
-        let mut statement = conn.prepare(
-            "INSERT INTO openai_usage
-             (request_id, user_id, guild_id, channel_id, service_type, model,
-              input_tokens, output_tokens, total_tokens, estimated_cost_usd)
-             VALUES (?, ?, ?, ?, 'chat', ?, ?, ?, ?, ?)"
-        )?;
-        statement.bind((1, request_id.unwrap_or("")))?;
-        statement.bind((2, user_id))?;
-        statement.bind((3, guild_id.unwrap_or("")))?;
-        statement.bind((4, channel_id.unwrap_or("")))?;
-        statement.bind((5, model))?;
-        statement.bind((6, input_tokens as i64))?;
-        statement.bind((7, output_tokens as i64))?;
-        statement.bind((8, total_tokens as i64))?;
-        statement.bind((9, estimated_cost))?;
-        statement.next()?;
-
-        // Update daily aggregate
-        drop(statement);
-        let mut agg_stmt = conn.prepare(
-            "INSERT INTO openai_usage_daily
-             (date, guild_id, user_id, service_type, request_count, total_tokens, total_cost_usd)
-             VALUES (?, ?, ?, 'chat', 1, ?, ?)
-             ON CONFLICT(date, guild_id, user_id, service_type) DO UPDATE SET
-             request_count = request_count + 1,
-             total_tokens = total_tokens + excluded.total_tokens,
-             total_cost_usd = total_cost_usd + excluded.total_cost_usd"
-        )?;
-        agg_stmt.bind((1, date.as_str()))?;
-        agg_stmt.bind((2, guild_id.unwrap_or("")))?;
-        agg_stmt.bind((3, user_id))?;
-        agg_stmt.bind((4, total_tokens as i64))?;
-        agg_stmt.bind((5, estimated_cost))?;
-        agg_stmt.next()?;
+        guild_id: &str,
+        channel_id: &str,
+        url: &str,
+        name: &str,
+        filter: Option<&str>,
+        lead_times: &str,
+        utc_offset_minutes: i32,
+        created_by: &str,
+    ) -> Result<i64> {
+        let (guild_id, channel_id, url, name, filter, lead_times, created_by) = (
+            guild_id.to_string(),
+            channel_id.to_string(),
+            url.to_string(),
+            name.to_string(),
+            filter.map(str::to_string),
+            lead_times.to_string(),
+            created_by.to_string(),
+        );
+        self.pool.interact(move |conn| {
+            let (guild_id, channel_id, url, name, filter, lead_times, created_by) = (
+                guild_id.as_str(),
+                channel_id.as_str(),
+                url.as_str(),
+                name.as_str(),
+                filter.as_deref(),
+                lead_times.as_str(),
+                created_by.as_str(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO calendar_subscriptions (guild_id, channel_id, url, name, filter, lead_times, utc_offset_minutes, created_by)
+                 VALUES (?, ?, ?, ?, NULLIF(?, ''), ?, ?, ?)"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, channel_id))?;
+            statement.bind((3, url))?;
+            statement.bind((4, name))?;
+            statement.bind((5, filter.unwrap_or("")))?;
+            statement.bind((6, lead_times))?;
+            statement.bind((7, utc_offset_minutes as i64))?;
+            statement.bind((8, created_by))?;
+            statement.next()?;
+
+            conn.last_insert_id()
+        })
+        .await
+    }
 
-        Ok(())
+    /// Calendar subscriptions in one guild, or every subscription when `guild_id` is None
+    pub async fn get_calendar_subscriptions(&self, guild_id: Option<&str>) -> Result<Vec<CalendarSubscription>> {
+        let guild_id = guild_id.map(str::to_string);
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_deref();
+            let mut statement = conn.prepare(
+                "SELECT id, guild_id, channel_id, url, name, filter, lead_times, utc_offset_minutes, last_agenda_date
+                 FROM calendar_subscriptions WHERE NULLIF(?, '') IS NULL OR guild_id = ? ORDER BY id"
+            )?;
+            let guild_id = guild_id.unwrap_or("");
+            statement.bind((1, guild_id))?;
+            statement.bind((2, guild_id))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(CalendarSubscription {
+                    id: statement.read::<i64, _>(0)?,
+                    guild_id: statement.read::<String, _>(1)?,
+                    channel_id: statement.read::<String, _>(2)?,
+                    url: statement.read::<String, _>(3)?,
+                    name: statement.read::<String, _>(4)?,
+                    filter: statement.read::<Option<String>, _>(5)?,
+                    lead_times: statement.read::<String, _>(6)?,
+                    utc_offset_minutes: statement.read::<i64, _>(7)? as i32,
+                    last_agenda_date: statement.read::<Option<String>, _>(8)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
     }
 
-    /// Log a Whisper (audio transcription) usage event
-    pub async fn log_openai_whisper_usage(
-        &self,
-        audio_duration_seconds: f64,
-        estimated_cost: f64,
-        user_id: &str,
-        guild_id: Option<&str>,
-        channel_id: Option<&str>,
-    ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    /// Remove a guild's calendar subscription and its announcement history. Returns false if there was none.
+    pub async fn delete_calendar_subscription(&self, guild_id: &str, id: i64) -> Result<bool> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare("DELETE FROM calendar_subscriptions WHERE guild_id = ? AND id = ?")?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, id))?;
+            statement.next()?;
+
+            let deleted = conn.changes()? > 0;
+
+            if deleted {
+                let mut statement = conn.prepare("DELETE FROM calendar_announcements WHERE subscription_id = ?")?;
+                statement.bind((1, id))?;
+                statement.next()?;
+            }
+            Ok(deleted)
+        })
+        .await
+    }
 
-        // Insert into raw usage table
-        let mut statement = conn.prepare(
-            "INSERT INTO openai_usage
-             (user_id, guild_id, channel_id, service_type, model,
-              audio_duration_seconds, estimated_cost_usd)
-             VALUES (?, ?, ?, 'whisper', 'whisper-1', ?, ?)"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, guild_id.unwrap_or("")))?;
-        statement.bind((3, channel_id.unwrap_or("")))?;
-        statement.bind((4, audio_duration_seconds))?;
-        statement.bind((5, estimated_cost))?;
-        statement.next()?;
+    /// Record the date (in the subscription's offset) the weekly agenda was last posted
+    pub async fn set_calendar_agenda_date(&self, id: i64, date: &str) -> Result<()> {
+        let date = date.to_string();
+        self.pool.interact(move |conn| {
+            let date = date.as_str();
+            let mut statement = conn.prepare("UPDATE calendar_subscriptions SET last_agenda_date = ? WHERE id = ?")?;
+            statement.bind((1, date))?;
+            statement.bind((2, id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
 
-        // Update daily aggregate
-        drop(statement);
-        let mut agg_stmt = conn.prepare(
-            "INSERT INTO openai_usage_daily
-             (date, guild_id, user_id, service_type, request_count, total_audio_seconds, total_cost_usd)
-             VALUES (?, ?, ?, 'whisper', 1, ?, ?)
-             ON CONFLICT(date, guild_id, user_id, service_type) DO UPDATE SET
-             request_count = request_count + 1,
-             total_audio_seconds = total_audio_seconds + excluded.total_audio_seconds,
-             total_cost_usd = total_cost_usd + excluded.total_cost_usd"
-        )?;
-        agg_stmt.bind((1, date.as_str()))?;
-        agg_stmt.bind((2, guild_id.unwrap_or("")))?;
-        agg_stmt.bind((3, user_id))?;
-        agg_stmt.bind((4, audio_duration_seconds))?;
-        agg_stmt.bind((5, estimated_cost))?;
-        agg_stmt.next()?;
+    /// Record an event reminder. Returns false if it was already posted.
+    pub async fn record_calendar_announcement(&self, subscription_id: i64, event_key: &str, lead_seconds: i64) -> Result<bool> {
+        let event_key = event_key.to_string();
+        self.pool.interact(move |conn| {
+            let event_key = event_key.as_str();
+            let mut statement = conn.prepare(
+                "INSERT INTO calendar_announcements (subscription_id, event_key, lead_seconds) VALUES (?, ?, ?) ON CONFLICT DO NOTHING"
+            )?;
+            statement.bind((1, subscription_id))?;
+            statement.bind((2, event_key))?;
+            statement.bind((3, lead_seconds))?;
+            statement.next()?;
 
-        Ok(())
+            Ok(conn.changes()? > 0)
+        })
+        .await
     }
 
-    /// Log a DALL-E (image generation) usage event
-    pub async fn log_openai_dalle_usage(
-        &self,
-        image_size: &str,
-        image_count: u32,
-        estimated_cost: f64,
-        user_id: &str,
-        guild_id: Option<&str>,
-        channel_id: Option<&str>,
-    ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    /// Forget event reminders older than the given number of days
+    pub async fn prune_calendar_announcements(&self, days: i64) -> Result<()> {
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "DELETE FROM calendar_announcements WHERE sent_at < ?"
+            )?;
+            statement.bind((1, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
 
-        // Insert into raw usage table
-        let mut statement = conn.prepare(
-            "INSERT INTO openai_usage
-             (user_id, guild_id, channel_id, service_type, model,
-              image_count, image_size, estimated_cost_usd)
-             VALUES (?, ?, ?, 'dalle', 'dall-e-3', ?, ?, ?)"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, guild_id.unwrap_or("")))?;
-        statement.bind((3, channel_id.unwrap_or("")))?;
-        statement.bind((4, image_count as i64))?;
-        statement.bind((5, image_size))?;
-        statement.bind((6, estimated_cost))?;
-        statement.next()?;
+    // Guild Overview Methods
+
+    /// Insert or refresh a guild the bot is in
+    pub async fn upsert_known_guild(&self, guild_id: &str, name: &str, member_count: u64) -> Result<()> {
+        let (guild_id, name) = (guild_id.to_string(), name.to_string());
+        self.pool.interact(move |conn| {
+            let (guild_id, name) = (guild_id.as_str(), name.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO known_guilds (guild_id, name, member_count, updated_at)
+                 VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(guild_id) DO UPDATE SET
+                 name = excluded.name,
+                 member_count = excluded.member_count,
+                 updated_at = CURRENT_TIMESTAMP"
+            )?;
+            statement.bind((1, guild_id))?;
+            statement.bind((2, name))?;
+            statement.bind((3, member_count as i64))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
+    }
 
-        // Update daily aggregate
-        drop(statement);
-        let mut agg_stmt = conn.prepare(
-            "INSERT INTO openai_usage_daily
-             (date, guild_id, user_id, service_type, request_count, total_images, total_cost_usd)
-             VALUES (?, ?, ?, 'dalle', 1, ?, ?)
-             ON CONFLICT(date, guild_id, user_id, service_type) DO UPDATE SET
-             request_count = request_count + 1,
-             total_images = total_images + excluded.total_images,
-             total_cost_usd = total_cost_usd + excluded.total_cost_usd"
-        )?;
-        agg_stmt.bind((1, date.as_str()))?;
-        agg_stmt.bind((2, guild_id.unwrap_or("")))?;
-        agg_stmt.bind((3, user_id))?;
-        agg_stmt.bind((4, image_count as i64))?;
-        agg_stmt.bind((5, estimated_cost))?;
-        agg_stmt.next()?;
+    /// Name of a guild the bot is in, as of its last guild create event
+    pub async fn get_known_guild_name(&self, guild_id: &str) -> Result<Option<String>> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare("SELECT name FROM known_guilds WHERE guild_id = ?")?;
+            statement.bind((1, guild_id))?;
 
-        Ok(())
+            if let Ok(State::Row) = statement.next() {
+                Ok(Some(statement.read::<String, _>(0)?))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
     }
 
-    /// Get usage statistics for a user within a date range
-    /// Returns (service_type, request_count, tokens, audio_seconds, images, cost)
-    pub async fn get_user_usage_stats(
-        &self,
-        user_id: &str,
-        days: i64,
-    ) -> Result<Vec<(String, i64, i64, f64, i64, f64)>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT service_type,
-                    SUM(request_count) as requests,
-                    SUM(total_tokens) as tokens,
-                    SUM(total_audio_seconds) as audio_secs,
-                    SUM(total_images) as images,
-                    SUM(total_cost_usd) as cost
-             FROM openai_usage_daily
-             WHERE user_id = ? AND date >= date('now', ? || ' days')
-             GROUP BY service_type"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, format!("-{}", days).as_str()))?;
-
-        let mut results = Vec::new();
-        while let Ok(State::Row) = statement.next() {
-            let service_type = statement.read::<String, _>(0)?;
-            let requests = statement.read::<i64, _>(1)?;
-            let tokens = statement.read::<i64, _>(2)?;
-            let audio_secs = statement.read::<f64, _>(3)?;
-            let images = statement.read::<i64, _>(4)?;
-            let cost = statement.read::<f64, _>(5)?;
-            results.push((service_type, requests, tokens, audio_secs, images, cost));
-        }
-        Ok(results)
+    /// Forget a guild the bot has left
+    pub async fn remove_known_guild(&self, guild_id: &str) -> Result<()> {
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let guild_id = guild_id.as_str();
+            let mut statement = conn.prepare("DELETE FROM known_guilds WHERE guild_id = ?")?;
+            statement.bind((1, guild_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    /// Get usage statistics for an entire guild within a date range
-    /// Includes DM usage from users who have interacted in this guild
-    /// Returns (service_type, request_count, tokens, audio_seconds, images, cost)
-    pub async fn get_guild_usage_stats(
-        &self,
-        guild_id: &str,
-        days: i64,
-    ) -> Result<Vec<(String, i64, i64, f64, i64, f64)>> {
-        let conn = self.connection.lock().await;
-        let days_str = format!("-{}", days);
-        let mut statement = conn.prepare(
-            "SELECT service_type,
-                    SUM(request_count) as requests,
-                    SUM(total_tokens) as tokens,
-                    SUM(total_audio_seconds) as audio_secs,
-                    SUM(total_images) as images,
-                    SUM(total_cost_usd) as cost
-             FROM openai_usage_daily
-             WHERE (guild_id = ? OR (guild_id = '' AND user_id IN (
-                 SELECT DISTINCT user_id FROM openai_usage_daily WHERE guild_id = ?
-             )))
-             AND date >= date('now', ? || ' days')
-             GROUP BY service_type"
-        )?;
-        statement.bind((1, guild_id))?;
-        statement.bind((2, guild_id))?;
-        statement.bind((3, days_str.as_str()))?;
-
-        let mut results = Vec::new();
-        while let Ok(State::Row) = statement.next() {
-            let service_type = statement.read::<String, _>(0)?;
-            let requests = statement.read::<i64, _>(1)?;
-            let tokens = statement.read::<i64, _>(2)?;
-            let audio_secs = statement.read::<f64, _>(3)?;
-            let images = statement.read::<i64, _>(4)?;
-            let cost = statement.read::<f64, _>(5)?;
-            results.push((service_type, requests, tokens, audio_secs, images, cost));
-        }
-        Ok(results)
+    /// Count a slash command (and whether it failed) towards a guild's daily activity
+    pub async fn record_guild_command(&self, guild_id: &str, failed: bool) -> Result<()> {
+        let (date, guild) = (self.date_from_now(0), guild_id.to_string());
+        self.guild_pool(Some(guild_id)).await?.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "INSERT INTO guild_activity_daily (date, guild_id, command_count, error_count)
+                 VALUES (?, ?, 1, ?)
+                 ON CONFLICT(date, guild_id) DO UPDATE SET
+                 command_count = guild_activity_daily.command_count + 1,
+                 error_count = guild_activity_daily.error_count + excluded.error_count"
+            )?;
+            statement.bind((1, date.as_str()))?;
+            statement.bind((2, guild.as_str()))?;
+            statement.bind((3, if failed { 1i64 } else { 0i64 }))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await?;
+
+        let guild_id = guild_id.to_string();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "UPDATE known_guilds SET last_activity_at = CURRENT_TIMESTAMP WHERE guild_id = ?"
+            )?;
+            statement.bind((1, guild_id.as_str()))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
-    /// Get top users by cost for a guild
-    /// Includes DM usage from users who have interacted in this guild
-    /// Returns (user_id, request_count, total_cost)
-    pub async fn get_guild_top_users_by_cost(
-        &self,
-        guild_id: &str,
-        days: i64,
-        limit: i64,
-    ) -> Result<Vec<(String, i64, f64)>> {
-        let conn = self.connection.lock().await;
-        let days_str = format!("-{}", days);
-        let mut statement = conn.prepare(
-            "SELECT user_id,
-                    SUM(request_count) as requests,
-                    SUM(total_cost_usd) as cost
-             FROM openai_usage_daily
-             WHERE (guild_id = ? OR (guild_id = '' AND user_id IN (
-                 SELECT DISTINCT user_id FROM openai_usage_daily WHERE guild_id = ?
-             )))
-             AND user_id != ''
-             AND date >= date('now', ? || ' days')
-             GROUP BY user_id
-             ORDER BY cost DESC
-             LIMIT ?"
-        )?;
-        statement.bind((1, guild_id))?;
-        statement.bind((2, guild_id))?;
-        statement.bind((3, days_str.as_str()))?;
-        statement.bind((4, limit))?;
-
-        let mut results = Vec::new();
-        while let Ok(State::Row) = statement.next() {
-            let user_id = statement.read::<String, _>(0)?;
-            let requests = statement.read::<i64, _>(1)?;
-            let cost = statement.read::<f64, _>(2)?;
-            results.push((user_id, requests, cost));
+    /// Per-guild member count, cost, command and error totals over the last N days
+    pub async fn get_guild_overview(&self, days: i64) -> Result<Vec<GuildOverview>> {
+        let since = self.date_from_now(-days);
+        let mut results = self.get_main_guild_overview(&since).await?;
+
+        // Guilds with a file of their own count their commands there
+        if let Some(stores) = &self.guild_stores {
+            for overview in results.iter_mut().filter(|o| stores.contains(&o.guild_id)) {
+                let pool = stores.pool(&overview.guild_id, &self.clock).await?;
+                let (guild_id, since) = (overview.guild_id.clone(), since.clone());
+                let (commands, errors) = pool.interact(move |conn| {
+                    let mut statement = conn.prepare(
+                        "SELECT COALESCE(SUM(command_count), 0), COALESCE(SUM(error_count), 0)
+                         FROM guild_activity_daily
+                         WHERE guild_id = ? AND date >= ?"
+                    )?;
+                    statement.bind((1, guild_id.as_str()))?;
+                    statement.bind((2, since.as_str()))?;
+                    statement.next()?;
+                    Ok((statement.read::<i64, _>(0)?, statement.read::<i64, _>(1)?))
+                })
+                .await?;
+                overview.commands += commands;
+                overview.errors += errors;
+            }
         }
         Ok(results)
     }
 
-    /// Cleanup old raw usage data (keep last N days)
-    pub async fn cleanup_old_openai_usage(&self, days: i64) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "DELETE FROM openai_usage WHERE timestamp < datetime('now', ? || ' days')"
-        )?;
-        statement.bind((1, format!("-{}", days).as_str()))?;
-        statement.next()?;
-        info!("Cleaned up openai_usage older than {} days", days);
-        Ok(())
+    /// [`Database::get_guild_overview`] from the main database alone
+    async fn get_main_guild_overview(&self, since: &str) -> Result<Vec<GuildOverview>> {
+        let since = since.to_string();
+        self.pool.interact(move |conn| {
+            let since = since.as_str();
+            let mut statement = conn.prepare(
+                "SELECT k.guild_id, k.name, k.member_count,
+                        COALESCE(c.cost, 0), COALESCE(a.commands, 0), COALESCE(a.errors, 0),
+                        k.last_activity_at
+                 FROM known_guilds k
+                 LEFT JOIN (
+                     SELECT guild_id, SUM(total_cost_usd) AS cost
+                     FROM openai_usage_daily
+                     WHERE date >= ?
+                     GROUP BY guild_id
+                 ) c ON c.guild_id = k.guild_id
+                 LEFT JOIN (
+                     SELECT guild_id, SUM(command_count) AS commands, SUM(error_count) AS errors
+                     FROM guild_activity_daily
+                     WHERE date >= ?
+                     GROUP BY guild_id
+                 ) a ON a.guild_id = k.guild_id"
+            )?;
+            statement.bind((1, since))?;
+            statement.bind((2, since))?;
+
+            let mut results = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                results.push(GuildOverview {
+                    guild_id: statement.read::<String, _>(0)?,
+                    name: statement.read::<String, _>(1)?,
+                    member_count: statement.read::<Option<i64>, _>(2)?,
+                    cost_usd: statement.read::<f64, _>(3)?,
+                    commands: statement.read::<i64, _>(4)?,
+                    errors: statement.read::<i64, _>(5)?,
+                    last_activity: statement.read::<Option<String>, _>(6)?,
+                });
+            }
+            Ok(results)
+        })
+        .await
     }
 
-    /// Cleanup old daily aggregates (keep last N days)
-    pub async fn cleanup_old_openai_usage_daily(&self, days: i64) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "DELETE FROM openai_usage_daily WHERE date < date('now', ? || ' days')"
-        )?;
-        statement.bind((1, format!("-{}", days).as_str()))?;
-        statement.next()?;
-        info!("Cleaned up openai_usage_daily older than {} days", days);
+    /// Cleanup old guild activity counts (keep last N days)
+    pub async fn cleanup_old_guild_activity(&self, days: i64) -> Result<()> {
+        for pool in self.all_pools().await? {
+            let cutoff = self.date_from_now(-days);
+            pool.interact(move |conn| {
+                let mut statement = conn.prepare(
+                    "DELETE FROM guild_activity_daily WHERE date < ?"
+                )?;
+                statement.bind((1, cutoff.as_str()))?;
+                statement.next()?;
+                Ok(())
+            })
+            .await?;
+        }
+        info!("Cleaned up guild_activity_daily older than {} days", days);
         Ok(())
     }
 
@@ -1865,35 +7432,43 @@ impl Database {
 
     /// Create a new DM session
     pub async fn create_dm_session(&self, session_id: &str, user_id: &str, channel_id: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO dm_sessions (session_id, user_id, channel_id) VALUES (?, ?, ?)"
-        )?;
-        statement.bind((1, session_id))?;
-        statement.bind((2, user_id))?;
-        statement.bind((3, channel_id))?;
-        statement.next()?;
-
-        // Also create metrics row
-        let mut metrics_stmt = conn.prepare(
-            "INSERT INTO dm_session_metrics (session_id) VALUES (?)"
-        )?;
-        metrics_stmt.bind((1, session_id))?;
-        metrics_stmt.next()?;
+        let (session_id, user_id, channel_id) = (session_id.to_string(), user_id.to_string(), channel_id.to_string());
+        self.pool.interact(move |conn| {
+            let (session_id, user_id, channel_id) = (session_id.as_str(), user_id.as_str(), channel_id.as_str());
+            let mut statement = conn.prepare(
+                "INSERT INTO dm_sessions (session_id, user_id, channel_id) VALUES (?, ?, ?)"
+            )?;
+            statement.bind((1, session_id))?;
+            statement.bind((2, user_id))?;
+            statement.bind((3, channel_id))?;
+            statement.next()?;
+
+            // Also create metrics row
+            let mut metrics_stmt = conn.prepare(
+                "INSERT INTO dm_session_metrics (session_id) VALUES (?)"
+            )?;
+            metrics_stmt.bind((1, session_id))?;
+            metrics_stmt.next()?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     /// End a DM session
     pub async fn end_dm_session(&self, session_id: &str, reason: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "UPDATE dm_sessions SET ended_at = CURRENT_TIMESTAMP, end_reason = ? WHERE session_id = ?"
-        )?;
-        statement.bind((1, reason))?;
-        statement.bind((2, session_id))?;
-        statement.next()?;
-        Ok(())
+        let (session_id, reason) = (session_id.to_string(), reason.to_string());
+        self.pool.interact(move |conn| {
+            let (session_id, reason) = (session_id.as_str(), reason.as_str());
+            let mut statement = conn.prepare(
+                "UPDATE dm_sessions SET ended_at = CURRENT_TIMESTAMP, end_reason = ? WHERE session_id = ?"
+            )?;
+            statement.bind((1, reason))?;
+            statement.bind((2, session_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
     /// Update DM session activity
@@ -1905,23 +7480,27 @@ impl Database {
         bot_chars: i32,
         avg_response_time: i32,
     ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "UPDATE dm_sessions
-             SET message_count = ?,
-                 total_user_chars = ?,
-                 total_bot_chars = ?,
-                 avg_response_time_ms = ?,
-                 last_activity_at = CURRENT_TIMESTAMP
-             WHERE session_id = ?"
-        )?;
-        statement.bind((1, msg_count as i64))?;
-        statement.bind((2, user_chars as i64))?;
-        statement.bind((3, bot_chars as i64))?;
-        statement.bind((4, avg_response_time as i64))?;
-        statement.bind((5, session_id))?;
-        statement.next()?;
-        Ok(())
+        let session_id = session_id.to_string();
+        self.pool.interact(move |conn| {
+            let session_id = session_id.as_str();
+            let mut statement = conn.prepare(
+                "UPDATE dm_sessions
+                 SET message_count = ?,
+                     total_user_chars = ?,
+                     total_bot_chars = ?,
+                     avg_response_time_ms = ?,
+                     last_activity_at = CURRENT_TIMESTAMP
+                 WHERE session_id = ?"
+            )?;
+            statement.bind((1, msg_count as i64))?;
+            statement.bind((2, user_chars as i64))?;
+            statement.bind((3, bot_chars as i64))?;
+            statement.bind((4, avg_response_time as i64))?;
+            statement.bind((5, session_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
     /// Log a DM event
@@ -1933,18 +7512,34 @@ impl Database {
         channel_id: &str,
         event_data: Option<&str>,
     ) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "INSERT INTO dm_events (session_id, event_type, user_id, channel_id, event_data)
-             VALUES (?, ?, ?, ?, ?)"
-        )?;
-        statement.bind((1, session_id))?;
-        statement.bind((2, event_type))?;
-        statement.bind((3, user_id))?;
-        statement.bind((4, channel_id))?;
-        statement.bind((5, event_data.unwrap_or("")))?;
-        statement.next()?;
-        Ok(())
+        let (session_id, event_type, user_id, channel_id, event_data) = (
+            session_id.to_string(),
+            event_type.to_string(),
+            user_id.to_string(),
+            channel_id.to_string(),
+            event_data.map(str::to_string),
+        );
+        self.pool.interact(move |conn| {
+            let (session_id, event_type, user_id, channel_id, event_data) = (
+                session_id.as_str(),
+                event_type.as_str(),
+                user_id.as_str(),
+                channel_id.as_str(),
+                event_data.as_deref(),
+            );
+            let mut statement = conn.prepare(
+                "INSERT INTO dm_events (session_id, event_type, user_id, channel_id, event_data)
+                 VALUES (?, ?, ?, ?, ?)"
+            )?;
+            statement.bind((1, session_id))?;
+            statement.bind((2, event_type))?;
+            statement.bind((3, user_id))?;
+            statement.bind((4, channel_id))?;
+            statement.bind((5, event_data.unwrap_or("")))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
     /// Update DM session metrics
@@ -1955,193 +7550,222 @@ impl Database {
         tokens: u32,
         cost: f64,
     ) -> Result<()> {
-        let conn = self.connection.lock().await;
-
-        let (api_field, tokens_update) = match api_type {
-            "chat" => ("chat_calls = chat_calls + 1", format!("total_tokens = total_tokens + {}", tokens)),
-            "whisper" => ("whisper_calls = whisper_calls + 1", String::new()),
-            "dalle" => ("dalle_calls = dalle_calls + 1", String::new()),
-            _ => return Ok(()),
-        };
+        let (session_id, api_type) = (session_id.to_string(), api_type.to_string());
+        self.pool.interact(move |conn| {
+            let (session_id, api_type) = (session_id.as_str(), api_type.as_str());
+            let (api_field, tokens_update) = match api_type {
+                "chat" => ("chat_calls = chat_calls + 1", format!("total_tokens = total_tokens + {}", tokens)),
+                "whisper" => ("whisper_calls = whisper_calls + 1", String::new()),
+                "dalle" => ("dalle_calls = dalle_calls + 1", String::new()),
+                _ => return Ok(()),
+            };
 
-        let sql = if tokens_update.is_empty() {
-            format!(
-                "UPDATE dm_session_metrics
-                 SET {},
-                     total_api_calls = total_api_calls + 1,
-                     total_api_cost_usd = total_api_cost_usd + ?,
-                     updated_at = CURRENT_TIMESTAMP
-                 WHERE session_id = ?",
-                api_field
-            )
-        } else {
-            format!(
-                "UPDATE dm_session_metrics
-                 SET {},
-                     {},
-                     total_api_calls = total_api_calls + 1,
-                     total_api_cost_usd = total_api_cost_usd + ?,
-                     updated_at = CURRENT_TIMESTAMP
-                 WHERE session_id = ?",
-                api_field, tokens_update
-            )
-        };
+            let sql = if tokens_update.is_empty() {
+                format!(
+                    "UPDATE dm_session_metrics
+                     SET {},
+                         total_api_calls = total_api_calls + 1,
+                         total_api_cost_usd = total_api_cost_usd + ?,
+                         updated_at = CURRENT_TIMESTAMP
+                     WHERE session_id = ?",
+                    api_field
+                )
+            } else {
+                format!(
+                    "UPDATE dm_session_metrics
+                     SET {},
+                         {},
+                         total_api_calls = total_api_calls + 1,
+                         total_api_cost_usd = total_api_cost_usd + ?,
+                         updated_at = CURRENT_TIMESTAMP
+                     WHERE session_id = ?",
+                    api_field, tokens_update
+                )
+            };
 
-        let mut statement = conn.prepare(&sql)?;
-        statement.bind((1, cost))?;
-        statement.bind((2, session_id))?;
-        statement.next()?;
-        Ok(())
+            let mut statement = conn.prepare(&sql)?;
+            statement.bind((1, cost))?;
+            statement.bind((2, session_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
     /// Increment DM session feature counter
     pub async fn increment_dm_session_feature(&self, session_id: &str, feature: &str) -> Result<()> {
-        let conn = self.connection.lock().await;
-
-        let field = match feature {
-            "audio" => "audio_transcriptions",
-            "slash_command" => "slash_commands_used",
-            _ => return Ok(()),
-        };
+        let (session_id, feature) = (session_id.to_string(), feature.to_string());
+        self.pool.interact(move |conn| {
+            let (session_id, feature) = (session_id.as_str(), feature.as_str());
+            let field = match feature {
+                "audio" => "audio_transcriptions",
+                "slash_command" => "slash_commands_used",
+                _ => return Ok(()),
+            };
 
-        let sql = format!(
-            "UPDATE dm_session_metrics
-             SET {} = {} + 1, updated_at = CURRENT_TIMESTAMP
-             WHERE session_id = ?",
-            field, field
-        );
+            let sql = format!(
+                "UPDATE dm_session_metrics
+                 SET {} = {} + 1, updated_at = CURRENT_TIMESTAMP
+                 WHERE session_id = ?",
+                field, field
+            );
 
-        let mut statement = conn.prepare(&sql)?;
-        statement.bind((1, session_id))?;
-        statement.next()?;
-        Ok(())
+            let mut statement = conn.prepare(&sql)?;
+            statement.bind((1, session_id))?;
+            statement.next()?;
+            Ok(())
+        })
+        .await
     }
 
     /// Get user DM stats for the last N days
     pub async fn get_user_dm_stats(&self, user_id: &str, days: i64) -> Result<DmStats> {
-        let conn = self.connection.lock().await;
-
-        // Get session counts and averages
-        let mut stmt = conn.prepare(
-            "SELECT
-                COUNT(*) as session_count,
-                SUM(message_count) as total_messages,
-                SUM(user_message_count) as user_messages,
-                SUM(bot_message_count) as bot_messages,
-                AVG(avg_response_time_ms) as avg_response_time,
-                AVG((julianday(ended_at) - julianday(started_at)) * 24 * 60) as avg_duration_min
-             FROM dm_sessions
-             WHERE user_id = ?
-             AND started_at >= datetime('now', ? || ' days')
-             AND ended_at IS NOT NULL"
-        )?;
-        stmt.bind((1, user_id))?;
-        stmt.bind((2, format!("-{}", days).as_str()))?;
-
-        let (session_count, total_messages, user_messages, bot_messages, avg_response_time, avg_duration) =
-            if let Ok(State::Row) = stmt.next() {
-                (
-                    stmt.read::<i64, _>(0).unwrap_or(0),
-                    stmt.read::<i64, _>(1).unwrap_or(0),
-                    stmt.read::<i64, _>(2).unwrap_or(0),
-                    stmt.read::<i64, _>(3).unwrap_or(0),
-                    stmt.read::<i64, _>(4).unwrap_or(0),
-                    stmt.read::<f64, _>(5).unwrap_or(0.0),
-                )
-            } else {
-                (0, 0, 0, 0, 0, 0.0)
-            };
-
-        // Get API metrics
-        let mut api_stmt = conn.prepare(
-            "SELECT
-                SUM(sm.total_api_calls) as api_calls,
-                SUM(sm.total_tokens) as tokens,
-                SUM(sm.total_api_cost_usd) as cost,
-                SUM(sm.chat_calls) as chat_calls,
-                SUM(sm.whisper_calls) as whisper_calls,
-                SUM(sm.dalle_calls) as dalle_calls,
-                SUM(sm.audio_transcriptions) as audio_count,
-                SUM(sm.slash_commands_used) as slash_count
-             FROM dm_session_metrics sm
-             JOIN dm_sessions s ON sm.session_id = s.session_id
-             WHERE s.user_id = ?
-             AND s.started_at >= datetime('now', ? || ' days')"
-        )?;
-        api_stmt.bind((1, user_id))?;
-        api_stmt.bind((2, format!("-{}", days).as_str()))?;
-
-        let (api_calls, tokens, cost, chat_calls, whisper_calls, dalle_calls, audio_count, slash_count) =
-            if let Ok(State::Row) = api_stmt.next() {
-                (
-                    api_stmt.read::<i64, _>(0).unwrap_or(0),
-                    api_stmt.read::<i64, _>(1).unwrap_or(0),
-                    api_stmt.read::<f64, _>(2).unwrap_or(0.0),
-                    api_stmt.read::<i64, _>(3).unwrap_or(0),
-                    api_stmt.read::<i64, _>(4).unwrap_or(0),
-                    api_stmt.read::<i64, _>(5).unwrap_or(0),
-                    api_stmt.read::<i64, _>(6).unwrap_or(0),
-                    api_stmt.read::<i64, _>(7).unwrap_or(0),
-                )
+        let user_id = user_id.to_string();
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            // Get session counts and averages
+            let mut stmt = conn.prepare(if conn.is_postgres() {
+                "SELECT
+                    COUNT(*) as session_count,
+                    SUM(message_count) as total_messages,
+                    SUM(user_message_count) as user_messages,
+                    SUM(bot_message_count) as bot_messages,
+                    AVG(avg_response_time_ms) as avg_response_time,
+                    AVG(EXTRACT(EPOCH FROM CAST(ended_at AS TIMESTAMP) - CAST(started_at AS TIMESTAMP)) / 60) as avg_duration_min
+                 FROM dm_sessions
+                 WHERE user_id = ?
+                 AND started_at >= ?
+                 AND ended_at IS NOT NULL"
             } else {
-                (0, 0, 0.0, 0, 0, 0, 0, 0)
-            };
-
-        Ok(DmStats {
-            session_count,
-            total_messages,
-            user_messages,
-            bot_messages,
-            avg_response_time_ms: avg_response_time,
-            avg_session_duration_min: avg_duration,
-            api_calls,
-            total_tokens: tokens,
-            total_cost_usd: cost,
-            chat_calls,
-            whisper_calls,
-            dalle_calls,
-            audio_transcriptions: audio_count,
-            slash_commands_used: slash_count,
+                "SELECT
+                    COUNT(*) as session_count,
+                    SUM(message_count) as total_messages,
+                    SUM(user_message_count) as user_messages,
+                    SUM(bot_message_count) as bot_messages,
+                    AVG(avg_response_time_ms) as avg_response_time,
+                    AVG((julianday(ended_at) - julianday(started_at)) * 24 * 60) as avg_duration_min
+                 FROM dm_sessions
+                 WHERE user_id = ?
+                 AND started_at >= ?
+                 AND ended_at IS NOT NULL"
+            })?;
+            stmt.bind((1, user_id))?;
+            stmt.bind((2, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+
+            let (session_count, total_messages, user_messages, bot_messages, avg_response_time, avg_duration) =
+                if let Ok(State::Row) = stmt.next() {
+                    (
+                        stmt.read::<i64, _>(0).unwrap_or(0),
+                        stmt.read::<i64, _>(1).unwrap_or(0),
+                        stmt.read::<i64, _>(2).unwrap_or(0),
+                        stmt.read::<i64, _>(3).unwrap_or(0),
+                        stmt.read::<i64, _>(4).unwrap_or(0),
+                        stmt.read::<f64, _>(5).unwrap_or(0.0),
+                    )
+                } else {
+                    (0, 0, 0, 0, 0, 0.0)
+                };
+
+            // Get API metrics
+            let mut api_stmt = conn.prepare(
+                "SELECT
+                    SUM(sm.total_api_calls) as api_calls,
+                    SUM(sm.total_tokens) as tokens,
+                    SUM(sm.total_api_cost_usd) as cost,
+                    SUM(sm.chat_calls) as chat_calls,
+                    SUM(sm.whisper_calls) as whisper_calls,
+                    SUM(sm.dalle_calls) as dalle_calls,
+                    SUM(sm.audio_transcriptions) as audio_count,
+                    SUM(sm.slash_commands_used) as slash_count
+                 FROM dm_session_metrics sm
+                 JOIN dm_sessions s ON sm.session_id = s.session_id
+                 WHERE s.user_id = ?
+                 AND s.started_at >= ?"
+            )?;
+            api_stmt.bind((1, user_id))?;
+            api_stmt.bind((2, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+
+            let (api_calls, tokens, cost, chat_calls, whisper_calls, dalle_calls, audio_count, slash_count) =
+                if let Ok(State::Row) = api_stmt.next() {
+                    (
+                        api_stmt.read::<i64, _>(0).unwrap_or(0),
+                        api_stmt.read::<i64, _>(1).unwrap_or(0),
+                        api_stmt.read::<f64, _>(2).unwrap_or(0.0),
+                        api_stmt.read::<i64, _>(3).unwrap_or(0),
+                        api_stmt.read::<i64, _>(4).unwrap_or(0),
+                        api_stmt.read::<i64, _>(5).unwrap_or(0),
+                        api_stmt.read::<i64, _>(6).unwrap_or(0),
+                        api_stmt.read::<i64, _>(7).unwrap_or(0),
+                    )
+                } else {
+                    (0, 0, 0.0, 0, 0, 0, 0, 0)
+                };
+
+            Ok(DmStats {
+                session_count,
+                total_messages,
+                user_messages,
+                bot_messages,
+                avg_response_time_ms: avg_response_time,
+                avg_session_duration_min: avg_duration,
+                api_calls,
+                total_tokens: tokens,
+                total_cost_usd: cost,
+                chat_calls,
+                whisper_calls,
+                dalle_calls,
+                audio_transcriptions: audio_count,
+                slash_commands_used: slash_count,
+            })
         })
+        .await
     }
 
     /// Get user's recent DM sessions
     pub async fn get_user_recent_sessions(&self, user_id: &str, limit: i64) -> Result<Vec<SessionInfo>> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "SELECT session_id, started_at, ended_at, message_count, avg_response_time_ms
-             FROM dm_sessions
-             WHERE user_id = ?
-             ORDER BY started_at DESC
-             LIMIT ?"
-        )?;
-        statement.bind((1, user_id))?;
-        statement.bind((2, limit))?;
-
-        let mut sessions = Vec::new();
-        while let Ok(State::Row) = statement.next() {
-            sessions.push(SessionInfo {
-                session_id: statement.read::<String, _>(0)?,
-                started_at: statement.read::<String, _>(1)?,
-                ended_at: statement.read::<Option<String>, _>(2)?,
-                message_count: statement.read::<i64, _>(3)?,
-                avg_response_time_ms: statement.read::<i64, _>(4).unwrap_or(0),
-            });
-        }
+        let user_id = user_id.to_string();
+        self.pool.interact(move |conn| {
+            let user_id = user_id.as_str();
+            let mut statement = conn.prepare(
+                "SELECT session_id, started_at, ended_at, message_count, avg_response_time_ms
+                 FROM dm_sessions
+                 WHERE user_id = ?
+                 ORDER BY started_at DESC
+                 LIMIT ?"
+            )?;
+            statement.bind((1, user_id))?;
+            statement.bind((2, limit))?;
+
+            let mut sessions = Vec::new();
+            while let Ok(State::Row) = statement.next() {
+                sessions.push(SessionInfo {
+                    session_id: statement.read::<String, _>(0)?,
+                    started_at: statement.read::<String, _>(1)?,
+                    ended_at: statement.read::<Option<String>, _>(2)?,
+                    message_count: statement.read::<i64, _>(3)?,
+                    avg_response_time_ms: statement.read::<i64, _>(4).unwrap_or(0),
+                });
+            }
 
-        Ok(sessions)
+            Ok(sessions)
+        })
+        .await
     }
 
     /// Cleanup old DM events (keep last N days)
     pub async fn cleanup_old_dm_events(&self, days: i64) -> Result<()> {
-        let conn = self.connection.lock().await;
-        let mut statement = conn.prepare(
-            "DELETE FROM dm_events WHERE timestamp < datetime('now', ? || ' days')"
-        )?;
-        statement.bind((1, format!("-{}", days).as_str()))?;
-        statement.next()?;
-        info!("Cleaned up dm_events older than {} days", days);
-        Ok(())
+        let db = self.clone();
+        self.pool.interact(move |conn| {
+            let mut statement = conn.prepare(
+                "DELETE FROM dm_events WHERE timestamp < ?"
+            )?;
+            statement.bind((1, db.timestamp_from_now(Duration::days(-days)).as_str()))?;
+            statement.next()?;
+            info!("Cleaned up dm_events older than {} days", days);
+            Ok(())
+        })
+        .await
     }
 }
 
@@ -2172,4 +7796,535 @@ pub struct SessionInfo {
     pub ended_at: Option<String>,
     pub message_count: i64,
     pub avg_response_time_ms: i64,
-}
\ No newline at end of file
+}
+
+/// A reminder that is due for delivery
+#[derive(Debug, Clone)]
+pub struct PendingReminder {
+    pub id: i64,
+    pub user_id: String,
+    pub channel_id: String,
+    pub reminder_text: String,
+    /// Jump link to the message the reminder was created from
+    pub source_message_link: Option<String>,
+    /// Short excerpt of the originating message
+    pub source_snippet: Option<String>,
+    /// Guild the reminder was created in (None for DMs)
+    pub guild_id: Option<String>,
+    /// Urgent reminders bypass the user's quiet hours
+    pub urgent: bool,
+}
+
+/// A stored conversation message with its Discord reference
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub role: String,
+    pub content: String,
+    /// Discord message ID, when the message originated from Discord
+    pub message_id: Option<String>,
+    pub timestamp: String,
+}
+
+/// Rolling summary of a user's older turns in a channel
+#[derive(Debug, Clone)]
+pub struct ConversationSummary {
+    pub summary: String,
+    /// ID of the last `conversation_history` row folded into the summary
+    pub summarized_through: i64,
+    pub updated_at: String,
+}
+
+/// A thread the bot opened when someone replied to it
+#[derive(Debug, Clone)]
+pub struct ConversationThread {
+    pub thread_id: String,
+    pub guild_id: String,
+    /// Parent channel the conversation started in
+    pub channel_id: String,
+    /// User whose reply opened the thread
+    pub user_id: String,
+    pub created_at: String,
+}
+
+/// A user, role or channel left out of conflict detection with `/conflict exempt`
+#[derive(Debug, Clone, Default)]
+pub struct ConflictExemption {
+    pub id: i64,
+    /// Channel the exemption applies in; None for the whole guild
+    pub channel_id: Option<String>,
+    /// `user`, `role` or `channel`
+    pub target_type: String,
+    pub target_id: String,
+    pub added_by: String,
+    pub created_at: String,
+}
+
+/// A running `/pomodoro` session
+#[derive(Debug, Clone, Default)]
+pub struct FocusSession {
+    pub id: i64,
+    pub guild_id: String,
+    /// Channel the session was started in, where its pings go
+    pub channel_id: String,
+    /// Study voice channel whose members joined the session
+    pub voice_channel_id: Option<String>,
+    pub started_by: String,
+    /// JSON array of participating user IDs
+    pub participants: String,
+    pub focus_minutes: i64,
+    pub break_minutes: i64,
+    pub rounds: i64,
+    /// Focus interval in progress or just finished, from 1
+    pub current_round: i64,
+    /// `focus` or `break`
+    pub phase: String,
+    /// When the current phase ends, as a Unix timestamp
+    pub phase_ends_at: i64,
+    /// `mute` or `deafen` participants in the voice channel while focusing
+    pub silence: Option<String>,
+}
+
+/// A collaborative story told in a thread with `/story`
+#[derive(Debug, Clone, Default)]
+pub struct StorySession {
+    pub id: i64,
+    pub guild_id: String,
+    /// Channel the story thread was opened in
+    pub channel_id: String,
+    pub thread_id: String,
+    pub theme: String,
+    /// Persona narrating the story
+    pub persona: String,
+    pub started_by: String,
+    /// JSON array of the story's characters
+    pub characters: String,
+    /// JSON array of the items the players carry
+    pub inventory: String,
+    /// JSON array with a summary of each finished chapter
+    pub chapter_summaries: String,
+    /// Player moves since the last chapter closed
+    pub turns_since_summary: i64,
+    pub created_at: String,
+}
+
+/// A thread's `/thread_settings` overrides; None falls back to the usual settings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadSettings {
+    pub persona: Option<String>,
+    pub verbosity: Option<String>,
+    pub language: Option<String>,
+}
+
+impl ThreadSettings {
+    pub fn is_empty(&self) -> bool {
+        self.persona.is_none() && self.verbosity.is_none() && self.language.is_none()
+    }
+}
+
+/// A queued or running `/import_history` backfill
+#[derive(Debug, Clone)]
+pub struct HistoryImportJob {
+    pub id: i64,
+    pub guild_id: String,
+    pub channel_id: String,
+    pub requested_by: String,
+    /// Channel the import was requested from, told when it finishes
+    pub notify_channel_id: String,
+    /// Oldest message time to import, as `YYYY-MM-DD HH:MM:SS`
+    pub since: String,
+    /// Oldest message read so far; the next page is fetched before it
+    pub before_message_id: Option<String>,
+    pub imported: i64,
+}
+
+/// Content flagged by the moderation check
+#[derive(Debug, Clone)]
+pub struct ModerationEvent {
+    pub id: i64,
+    pub guild_id: String,
+    pub channel_id: String,
+    pub user_id: String,
+    /// `input` for a member's message, `output` for a generated reply
+    pub direction: String,
+    /// `log_only`, `warn` or `block`
+    pub action: String,
+    /// Comma-separated moderation categories, e.g. `harassment, violence`
+    pub categories: String,
+    /// Start of the flagged text
+    pub excerpt: String,
+}
+
+/// A budget warning or overrun waiting to be posted
+#[derive(Debug, Clone)]
+pub struct BudgetAlert {
+    pub id: i64,
+    pub guild_id: String,
+    /// `guild` or `user`
+    pub scope: String,
+    /// Member whose daily budget it is; empty for the guild budget
+    pub user_id: String,
+    /// `warning` or `exceeded`
+    pub level: String,
+    /// Channel of the request that crossed the threshold, where warnings are posted
+    pub channel_id: String,
+    pub spent_usd: f64,
+    pub budget_usd: f64,
+}
+
+/// A previously answered support question
+#[derive(Debug, Clone)]
+pub struct AnsweredQuestion {
+    pub question: String,
+    /// JSON-encoded embedding vector
+    pub embedding: String,
+    pub answer_link: String,
+    pub created_at: String,
+}
+
+/// A remembered exchange with a user
+#[derive(Debug, Clone)]
+pub struct MemoryEntry {
+    pub id: i64,
+    pub channel_id: String,
+    pub user_text: String,
+    pub reply_text: String,
+    /// JSON-encoded embedding of `user_text`
+    pub embedding: String,
+    pub created_at: String,
+}
+
+/// An answered question awaiting knowledge base export
+#[derive(Debug, Clone)]
+pub struct KnowledgeEntry {
+    pub id: i64,
+    pub question: String,
+    /// The bot's answer; None for questions indexed before answers were stored
+    pub answer: Option<String>,
+    pub answer_link: String,
+    pub created_at: String,
+}
+
+/// The stored prompt behind an AI reply
+#[derive(Debug, Clone)]
+pub struct AiResponseRecord {
+    pub id: i64,
+    pub user_id: String,
+    pub guild_id: Option<String>,
+    pub channel_id: String,
+    /// Slash command that produced the reply (hey, explain, simple, steps, recipe)
+    pub command: String,
+    pub prompt: String,
+    pub regenerations: i64,
+    /// Creativity level the reply was generated with (low, normal, high)
+    pub creativity: Option<String>,
+}
+
+/// Aggregated AI cost for one guild and feature over a period
+#[derive(Debug, Clone)]
+pub struct CostLine {
+    /// Guild ID, empty for direct messages
+    pub guild_id: String,
+    /// Feature tag (chat, mediation, summarization, digest, image, transcription, ...)
+    pub feature: String,
+    pub requests: i64,
+    pub tokens: i64,
+    pub cost: f64,
+}
+
+/// An entry from `error_logs`, as shown on the web dashboard
+#[derive(Debug, Clone)]
+pub struct ErrorLogEntry {
+    pub id: i64,
+    pub error_type: String,
+    pub message: String,
+    /// Command or component that failed, when known
+    pub command: Option<String>,
+    pub timestamp: String,
+}
+
+/// A row of `conflict_detection`, as shown on the web dashboard
+#[derive(Debug, Clone)]
+pub struct ConflictRecord {
+    pub id: i64,
+    pub channel_id: String,
+    /// JSON array of the user IDs involved
+    pub participants: String,
+    pub detection_type: String,
+    pub confidence: f64,
+    pub mediation_triggered: bool,
+    pub first_detected: String,
+    pub last_detected: String,
+    pub resolved_at: Option<String>,
+    /// Mediation messages posted for it
+    pub mediations: i64,
+}
+
+/// A Discord account linked through the dashboard's OAuth2 login
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkedAccount {
+    pub user_id: String,
+    pub username: String,
+    pub access_token: String,
+    pub refresh_token: String,
+    pub scope: String,
+    /// When the access token stops working, as a stored timestamp
+    pub expires_at: String,
+    /// Guilds the user owned or administered, and the bot was in, when last verified
+    pub admin_guild_ids: Vec<String>,
+    pub verified_at: Option<String>,
+    pub linked_at: String,
+}
+
+/// One guild's row in the owner's `/ops overview`
+#[derive(Debug, Clone)]
+pub struct GuildOverview {
+    pub guild_id: String,
+    pub name: String,
+    /// Member count reported by Discord when the guild was last seen
+    pub member_count: Option<i64>,
+    pub cost_usd: f64,
+    pub commands: i64,
+    pub errors: i64,
+    /// Last slash command in the guild (`YYYY-MM-DD HH:MM:SS`, UTC)
+    pub last_activity: Option<String>,
+}
+
+/// Per-channel auto slowmode thresholds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlowmodeConfig {
+    /// Messages within the window that count as a spike
+    pub messages: i64,
+    pub window_seconds: i64,
+    /// Slowmode applied during a spike
+    pub slowmode_seconds: i64,
+    /// How long the slowmode stays before it is reverted
+    pub duration_minutes: i64,
+}
+
+/// A slowmode the bot applied and will revert
+#[derive(Debug, Clone)]
+pub struct ActiveSlowmode {
+    pub channel_id: String,
+    pub guild_id: String,
+    /// Slowmode the channel had before the spike
+    pub previous_seconds: i64,
+    pub applied_seconds: i64,
+}
+
+/// A recorded `/timeout`, `/kick` or `/ban`
+#[derive(Debug, Clone)]
+pub struct ModerationAction {
+    pub id: i64,
+    pub guild_id: String,
+    pub target_user_id: String,
+    pub moderator_id: String,
+    /// `timeout`, `kick` or `ban`
+    pub action: String,
+    pub reason: String,
+    pub duration_minutes: Option<i64>,
+    pub created_at: String,
+}
+
+/// An appeal against a ban or timeout
+#[derive(Debug, Clone)]
+pub struct Appeal {
+    pub id: i64,
+    pub moderation_action_id: i64,
+    pub guild_id: String,
+    pub user_id: String,
+    pub statement: String,
+    /// `pending`, `approved` or `denied`
+    pub status: String,
+    pub reviewer_id: Option<String>,
+    pub created_at: String,
+}
+
+/// An active server lockdown
+#[derive(Debug, Clone)]
+pub struct Lockdown {
+    pub guild_id: String,
+    pub started_by: String,
+    pub reason: Option<String>,
+    pub started_at: String,
+}
+
+/// A channel's @everyone overwrite from before a lockdown
+#[derive(Debug, Clone)]
+pub struct LockdownOverwrite {
+    pub channel_id: String,
+    /// False if the channel had no @everyone overwrite (restoring removes it)
+    pub had_overwrite: bool,
+    pub allow_bits: i64,
+    pub deny_bits: i64,
+}
+
+/// A flagged member join
+#[derive(Debug, Clone)]
+pub struct JoinScreening {
+    pub id: i64,
+    pub guild_id: String,
+    pub user_id: String,
+    pub score: i64,
+    /// `logged`, `pending`, `verified` or `failed`
+    pub status: String,
+    /// Correct captcha choice (`-1` when no captcha was posted)
+    pub answer: i64,
+    pub attempts: i64,
+}
+
+/// A new member's verification gate
+#[derive(Debug, Clone)]
+pub struct VerificationGate {
+    pub id: i64,
+    pub guild_id: String,
+    pub user_id: String,
+    /// Empty in button-only mode
+    pub question: String,
+    pub answers: Vec<String>,
+    /// `pending`, `verified` or `kicked`
+    pub status: String,
+    pub channel_id: String,
+    pub message_id: String,
+}
+
+/// A Discord channel bridged to a Slack channel
+#[derive(Debug, Clone)]
+pub struct BridgeLink {
+    pub id: i64,
+    pub guild_id: String,
+    pub discord_channel_id: String,
+    pub slack_channel_id: String,
+    /// Slack `ts` of the newest message relayed to Discord
+    pub last_slack_ts: String,
+}
+
+/// A Discord channel mirrored to a Matrix room
+#[derive(Debug, Clone)]
+pub struct MatrixLink {
+    pub id: i64,
+    pub guild_id: String,
+    pub discord_channel_id: String,
+    pub matrix_room_id: String,
+}
+
+/// One guild's totals for a day, as exported to Google Sheets
+#[derive(Debug, Clone)]
+pub struct GuildDailyStats {
+    pub date: String,
+    /// Current member count (not historical)
+    pub member_count: Option<i64>,
+    pub new_members: i64,
+    pub first_time_posters: i64,
+    pub commands: i64,
+    pub errors: i64,
+    pub ai_requests: i64,
+    pub ai_tokens: i64,
+    pub ai_cost_usd: f64,
+}
+
+/// A message a user saved with the Bookmark Message context menu
+#[derive(Debug, Clone)]
+pub struct Bookmark {
+    pub id: i64,
+    /// None for messages in DMs
+    pub guild_id: Option<String>,
+    pub channel_id: String,
+    pub message_id: String,
+    /// Snippet of the message when it was saved
+    pub name: String,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+/// A persona defined by a guild's admins with `/persona create`
+#[derive(Debug, Clone)]
+pub struct CustomPersona {
+    /// Key users pick it by, unique across all guilds
+    pub key: String,
+    pub guild_id: String,
+    pub name: String,
+    pub description: String,
+    pub system_prompt: String,
+    pub temperature: Option<f32>,
+    pub emoji: Option<String>,
+    /// Whether other guilds can pick it too
+    pub shared: bool,
+    pub created_by: String,
+}
+
+/// Read a `custom_personas` row selected in the column order used by the persona queries
+fn read_custom_persona(statement: &Statement) -> Result<CustomPersona> {
+    Ok(CustomPersona {
+        key: statement.read::<String, _>(0)?,
+        guild_id: statement.read::<String, _>(1)?,
+        name: statement.read::<String, _>(2)?,
+        description: statement.read::<String, _>(3)?,
+        system_prompt: statement.read::<String, _>(4)?,
+        temperature: statement.read::<Option<f64>, _>(5)?.map(|t| t as f32),
+        emoji: statement.read::<Option<String>, _>(6)?.filter(|e| !e.is_empty()),
+        shared: statement.read::<i64, _>(7)? == 1,
+        created_by: statement.read::<String, _>(8)?,
+    })
+}
+
+/// Read a `focus_sessions` row selected in the column order used by the focus session queries
+fn read_focus_session(statement: &Statement) -> Result<FocusSession> {
+    Ok(FocusSession {
+        id: statement.read::<i64, _>(0)?,
+        guild_id: statement.read::<String, _>(1)?,
+        channel_id: statement.read::<String, _>(2)?,
+        voice_channel_id: statement.read::<Option<String>, _>(3)?,
+        started_by: statement.read::<String, _>(4)?,
+        participants: statement.read::<String, _>(5)?,
+        focus_minutes: statement.read::<i64, _>(6)?,
+        break_minutes: statement.read::<i64, _>(7)?,
+        rounds: statement.read::<i64, _>(8)?,
+        current_round: statement.read::<i64, _>(9)?,
+        phase: statement.read::<String, _>(10)?,
+        phase_ends_at: statement.read::<String, _>(11)?.parse::<i64>()?,
+        silence: statement.read::<Option<String>, _>(12)?,
+    })
+}
+
+/// A text command defined for a guild, or for every guild when global
+#[derive(Debug, Clone)]
+pub struct CustomCommand {
+    pub name: String,
+    /// Response template with `{variable}` placeholders
+    pub response: String,
+    pub created_by: String,
+    pub is_global: bool,
+}
+
+/// An external system allowed to post into a channel through the webhook endpoint
+#[derive(Debug, Clone)]
+pub struct WebhookSource {
+    pub id: i64,
+    pub guild_id: String,
+    pub channel_id: String,
+    pub name: String,
+    /// Bearer token the source must send
+    pub token: String,
+    /// Persona the embeds are attributed to
+    pub persona: String,
+    /// Description template with `{{field}}` placeholders
+    pub template: Option<String>,
+    pub rate_limit_per_minute: i64,
+}
+
+/// A channel's subscription to an iCal feed
+#[derive(Debug, Clone)]
+pub struct CalendarSubscription {
+    pub id: i64,
+    pub guild_id: String,
+    pub channel_id: String,
+    pub url: String,
+    pub name: String,
+    /// Comma-separated keywords; `-word` excludes
+    pub filter: Option<String>,
+    /// Comma-separated reminder lead times, e.g. `24h,1h`
+    pub lead_times: String,
+    /// Offset used for zoneless times and the Monday agenda
+    pub utc_offset_minutes: i32,
+    pub last_agenda_date: Option<String>,
+}
@@ -0,0 +1,129 @@
+//! Per-guild database files.
+//!
+//! Guilds listed in `GUILD_DATABASES` keep their guild-resident data in a SQLite
+//! file of their own, `<GUILD_DATABASE_DIR>/guild_<id>.db`, instead of the main
+//! database, so it can be backed up, moved or deleted separately. Guild-resident
+//! data is what the `Database` methods take a guild ID for and nothing else
+//! needs: conversation memories, answered questions, emoji statistics, member
+//! activity and daily command counts. Settings, the guild registry and data keyed
+//! only by user or channel stay in the main database.
+//!
+//! Each file is opened on first use with the main database's pool size and
+//! pragmas, and gets the full schema and migrations. Methods that span guilds
+//! (cleanups, the guild overview) visit the main database and every listed
+//! guild's file.
+
+use super::backend::DatabaseBackend;
+use super::pool::ConnectionPool;
+use super::Database;
+use crate::core::{Clock, Result, SqlitePragmas};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// The guilds with their own database file, and the files opened so far
+pub struct GuildStores {
+    dir: PathBuf,
+    guilds: BTreeSet<String>,
+    pool_size: usize,
+    pragmas: SqlitePragmas,
+    open: Mutex<HashMap<String, Arc<ConnectionPool>>>,
+}
+
+impl GuildStores {
+    /// Files for `guilds` under `dir`; IDs that aren't numeric are ignored, since they name the files
+    pub fn new(dir: impl Into<PathBuf>, guilds: impl IntoIterator<Item = String>, pool_size: usize, pragmas: SqlitePragmas) -> Self {
+        GuildStores {
+            dir: dir.into(),
+            guilds: guilds
+                .into_iter()
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+                .collect(),
+            pool_size,
+            pragmas,
+            open: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `guild_id` has its own file
+    pub fn contains(&self, guild_id: &str) -> bool {
+        self.guilds.contains(guild_id)
+    }
+
+    /// Guilds with their own file, in ID order
+    pub fn guilds(&self) -> impl Iterator<Item = &str> {
+        self.guilds.iter().map(String::as_str)
+    }
+
+    /// Path of a guild's file
+    pub fn path(&self, guild_id: &str) -> PathBuf {
+        self.dir.join(format!("guild_{guild_id}.db"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The pool for a listed guild's file, opening it and creating its tables on first use
+    pub(super) async fn pool(&self, guild_id: &str, clock: &Arc<dyn Clock>) -> Result<Arc<ConnectionPool>> {
+        let mut open = self.open.lock().await;
+        if let Some(pool) = open.get(guild_id) {
+            return Ok(pool.clone());
+        }
+
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path(guild_id).to_string_lossy().into_owned();
+        let pool = Arc::new(ConnectionPool::open(&DatabaseBackend::Sqlite { path: path.clone() }, self.pool_size, &self.pragmas)?);
+        let db = Database { pool: pool.clone(), clock: clock.clone(), guild_stores: None, history_bot: None, history_partners: Vec::new() };
+        db.init_tables().await?;
+        db.migrate().await?;
+        info!("Guild database for {guild_id} opened at {path}");
+
+        open.insert(guild_id.to_string(), pool.clone());
+        Ok(pool)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::system_clock;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("persona-guild-stores-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_listed_guilds() {
+        let stores = GuildStores::new(
+            "data",
+            ["123".to_string(), " 456 ".to_string(), "../etc".to_string(), String::new()],
+            2,
+            SqlitePragmas::default(),
+        );
+        assert!(stores.contains("123") && stores.contains("456"));
+        assert!(!stores.contains("../etc") && !stores.contains("789"));
+        assert_eq!(stores.guilds().collect::<Vec<_>>(), vec!["123", "456"]);
+        assert_eq!(stores.path("123"), Path::new("data").join("guild_123.db"));
+    }
+
+    #[tokio::test]
+    async fn test_pool_opened_once_with_schema() {
+        let dir = temp_dir();
+        let stores = GuildStores::new(&dir, ["42".to_string()], 1, SqlitePragmas::default());
+        let clock = system_clock();
+
+        let pool = stores.pool("42", &clock).await.unwrap();
+        assert!(stores.path("42").exists());
+        let conn = pool.get().await.unwrap();
+        conn.execute("INSERT INTO emoji_stats (date, guild_id, emoji, source, count) VALUES ('2026-01-01', '42', '👍', 'message', 1)")
+            .unwrap();
+        drop(conn);
+
+        assert!(Arc::ptr_eq(&pool, &stores.pool("42", &clock).await.unwrap()));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
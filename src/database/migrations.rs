@@ -0,0 +1,281 @@
+//! Versioned schema migrations.
+//!
+//! `init_tables` creates the baseline schema with `CREATE TABLE IF NOT EXISTS`,
+//! which can't change tables that already exist. Changes to existing tables go
+//! here instead: each migration has a version number, runs once inside a
+//! transaction, and is recorded in `schema_version`. Pending migrations run in
+//! order on every startup (or with `bot --migrate-only`).
+//!
+//! Migrations must be idempotent against databases created by the current
+//! `init_tables` (fresh databases get new columns from both places), so column
+//! additions go through [`add_column`]. Never edit or renumber a released
+//! migration; add a new one.
+
+use crate::core::{BotError, Result};
+use log::info;
+use super::backend::{Connection, State};
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: fn(&Connection) -> Result<()>,
+}
+
+/// All migrations, in version order
+pub const MIGRATIONS: &[Migration] = &[
+    // 1-7 replace the ad-hoc `ALTER TABLE` statements that used to run (and fail silently) on every startup
+    Migration {
+        version: 1,
+        name: "conversation_history_message_id",
+        up: |conn| add_column(conn, "conversation_history", "message_id", "TEXT"),
+    },
+    Migration {
+        version: 2,
+        name: "reminder_message_context",
+        up: |conn| {
+            add_column(conn, "reminders", "source_message_link", "TEXT")?;
+            add_column(conn, "reminders", "source_snippet", "TEXT")?;
+            add_column(conn, "reminders", "guild_id", "TEXT")?;
+            add_column(conn, "reminders", "urgent", "BOOLEAN DEFAULT 0")
+        },
+    },
+    Migration {
+        version: 3,
+        name: "channel_settings_creativity",
+        up: |conn| add_column(conn, "channel_settings", "creativity", "TEXT"),
+    },
+    Migration {
+        version: 4,
+        name: "openai_usage_pricing_tiers",
+        up: |conn| {
+            add_column(conn, "openai_usage", "cached_input_tokens", "INTEGER DEFAULT 0")?;
+            add_column(conn, "openai_usage", "is_batch", "BOOLEAN DEFAULT 0")
+        },
+    },
+    Migration {
+        version: 5,
+        name: "openai_usage_feature",
+        up: |conn| add_column(conn, "openai_usage", "feature", "TEXT"),
+    },
+    Migration {
+        version: 6,
+        name: "ai_responses_creativity",
+        up: |conn| add_column(conn, "ai_responses", "creativity", "TEXT"),
+    },
+    Migration {
+        version: 7,
+        name: "answered_questions_export",
+        up: |conn| {
+            add_column(conn, "answered_questions", "answer", "TEXT")?;
+            add_column(conn, "answered_questions", "exported_at", "DATETIME")
+        },
+    },
+    Migration {
+        version: 8,
+        name: "performance_metrics_bot_id",
+        up: |conn| {
+            add_column(conn, "performance_metrics", "bot_id", "TEXT")?;
+            conn.execute("CREATE INDEX IF NOT EXISTS idx_metrics_bot ON performance_metrics(bot_id, metric_type, timestamp)")
+        },
+    },
+    Migration {
+        version: 9,
+        name: "conversation_history_thread_id",
+        up: |conn| {
+            add_column(conn, "conversation_history", "thread_id", "TEXT")?;
+            conn.execute("CREATE INDEX IF NOT EXISTS idx_history_thread ON conversation_history(thread_id, id)")
+        },
+    },
+    Migration {
+        version: 10,
+        name: "user_bookmarks_guild_id",
+        up: |conn| add_column(conn, "user_bookmarks", "guild_id", "TEXT"),
+    },
+    Migration {
+        version: 11,
+        name: "error_logs_guild_id",
+        up: |conn| {
+            add_column(conn, "error_logs", "guild_id", "TEXT")?;
+            conn.execute("CREATE INDEX IF NOT EXISTS idx_error_guild ON error_logs(guild_id, timestamp)")
+        },
+    },
+    Migration {
+        version: 12,
+        name: "mediation_history_approved_by",
+        up: |conn| add_column(conn, "mediation_history", "approved_by", "TEXT"),
+    },
+    Migration {
+        version: 13,
+        name: "conversation_history_message_index",
+        up: |conn| conn.execute("CREATE INDEX IF NOT EXISTS idx_history_message ON conversation_history(message_id)"),
+    },
+    Migration {
+        version: 14,
+        name: "conversation_history_bot_name",
+        up: |conn| add_column(conn, "conversation_history", "bot_name", "TEXT"),
+    },
+    Migration {
+        version: 15,
+        name: "mediation_history_decision",
+        up: |conn| add_column(conn, "mediation_history", "decision", "TEXT"),
+    },
+    Migration {
+        version: 16,
+        name: "quiet_hours_offset_to_timezone",
+        up: move_quiet_hours_offsets,
+    },
+];
+
+/// Add a column unless the table already has it (SQLite has no `ADD COLUMN IF NOT EXISTS`)
+pub fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    if !column_exists(conn, table, column)? {
+        conn.execute(format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"))?;
+    }
+    Ok(())
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut statement = if conn.is_postgres() {
+        conn.prepare("SELECT 1 FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = ? AND column_name = ?")?
+    } else {
+        conn.prepare("SELECT 1 FROM pragma_table_info(?) WHERE name = ?")?
+    };
+    statement.bind((1, table))?;
+    statement.bind((2, column))?;
+    Ok(matches!(statement.next()?, State::Row))
+}
+
+/// Quiet hours used to be stored as `HH:MM-HH:MM|offset_minutes`; they now follow
+/// `/timezone`. The old offset becomes the user's time zone unless they have one,
+/// and the window is kept without it.
+fn move_quiet_hours_offsets(conn: &Connection) -> Result<()> {
+    let mut legacy = Vec::new();
+    let mut statement = conn.prepare(
+        "SELECT user_id, preference_value FROM extended_user_preferences
+         WHERE preference_key = 'quiet_hours' AND preference_value LIKE '%|%'",
+    )?;
+    while let State::Row = statement.next()? {
+        legacy.push((statement.read::<String, _>(0)?, statement.read::<String, _>(1)?));
+    }
+
+    for (user_id, value) in legacy {
+        let (window, offset) = value.split_once('|').unwrap_or((&value, ""));
+        if let Ok(minutes) = offset.trim().parse::<i32>() {
+            let sign = if minutes < 0 { '-' } else { '+' };
+            let timezone = format!("UTC{sign}{:02}:{:02}", minutes.abs() / 60, minutes.abs() % 60);
+            let mut statement = conn.prepare(
+                "INSERT INTO extended_user_preferences (user_id, preference_key, preference_value, updated_at)
+                 VALUES (?, 'timezone', ?, CURRENT_TIMESTAMP)
+                 ON CONFLICT(user_id, preference_key) DO NOTHING",
+            )?;
+            statement.bind((1, user_id.as_str()))?;
+            statement.bind((2, timezone.as_str()))?;
+            statement.next()?;
+        }
+        let mut statement = conn.prepare(
+            "UPDATE extended_user_preferences SET preference_value = ? WHERE user_id = ? AND preference_key = 'quiet_hours'",
+        )?;
+        statement.bind((1, window))?;
+        statement.bind((2, user_id.as_str()))?;
+        statement.next()?;
+    }
+    Ok(())
+}
+
+/// Highest applied migration version, 0 for a database that has none
+pub fn current_version(conn: &Connection) -> Result<i64> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+    )?;
+    let mut statement = conn.prepare("SELECT COALESCE(MAX(version), 0) FROM schema_version")?;
+    statement.next()?;
+    statement.read::<i64, _>(0)
+}
+
+/// Apply pending migrations in order, each in its own transaction. Returns the versions applied.
+pub fn run_migrations(conn: &Connection) -> Result<Vec<i64>> {
+    let version = current_version(conn)?;
+    let mut applied = Vec::new();
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+        info!("Applying database migration {} ({})", migration.version, migration.name);
+        // SQLite takes its write lock up front; PostgreSQL locks rows as it goes
+        conn.execute(if conn.is_postgres() { "BEGIN" } else { "BEGIN IMMEDIATE" })?;
+        let result = (migration.up)(conn).and_then(|_| {
+            let mut statement = conn.prepare("INSERT INTO schema_version (version, name) VALUES (?, ?)")?;
+            statement.bind((1, migration.version))?;
+            statement.bind((2, migration.name))?;
+            statement.next()?;
+            Ok(())
+        });
+        match result {
+            Ok(()) => conn.execute("COMMIT")?,
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK");
+                return Err(BotError::database(format!("Migration {} ({}) failed: {}", migration.version, migration.name, e)));
+            }
+        }
+        applied.push(migration.version);
+    }
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_strictly_increasing() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+        assert_eq!(MIGRATIONS.first().map(|m| m.version), Some(1));
+    }
+
+    #[test]
+    fn test_add_column_is_idempotent() {
+        let conn = Connection::Sqlite(sqlite::open(":memory:").unwrap());
+        conn.execute("CREATE TABLE t (id INTEGER)").unwrap();
+        add_column(&conn, "t", "note", "TEXT").unwrap();
+        add_column(&conn, "t", "note", "TEXT").unwrap();
+        assert!(column_exists(&conn, "t", "note").unwrap());
+        assert!(!column_exists(&conn, "t", "missing").unwrap());
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        let conn = Connection::Sqlite(sqlite::open(":memory:").unwrap());
+        // No tables exist, so the first migration fails
+        let err = run_migrations(&conn).unwrap_err();
+        assert!(err.to_string().starts_with("Migration 1"));
+        assert_eq!(current_version(&conn).unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_startup_applies_all_then_nothing() {
+        let db = crate::database::Database::new(":memory:", 1).await.unwrap();
+        assert_eq!(db.schema_version().await.unwrap(), MIGRATIONS.last().unwrap().version);
+        assert!(db.migrate().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_quiet_hours_offset_becomes_timezone() {
+        let db = crate::database::Database::new(":memory:", 1).await.unwrap();
+        db.set_user_preference("1", "quiet_hours", "22:00-07:30|-300").await.unwrap();
+        db.set_user_preference("2", "quiet_hours", "23:00-06:00|120").await.unwrap();
+        db.set_user_preference("2", "timezone", "Europe/Berlin").await.unwrap();
+        db.set_user_preference("3", "quiet_hours", "12:00-13:00").await.unwrap();
+        db.pool.interact(move_quiet_hours_offsets).await.unwrap();
+
+        assert_eq!(db.get_user_preference("1", "timezone").await.unwrap().as_deref(), Some("UTC-05:00"));
+        assert_eq!(db.get_user_preference("1", "quiet_hours").await.unwrap().as_deref(), Some("22:00-07:30"));
+        // A zone the user picked wins over the old offset
+        assert_eq!(db.get_user_preference("2", "timezone").await.unwrap().as_deref(), Some("Europe/Berlin"));
+        assert_eq!(db.get_user_preference("2", "quiet_hours").await.unwrap().as_deref(), Some("23:00-06:00"));
+        assert_eq!(db.get_user_preference("3", "timezone").await.unwrap(), None);
+        assert_eq!(db.get_user_preference("3", "quiet_hours").await.unwrap().as_deref(), Some("12:00-13:00"));
+    }
+}
@@ -0,0 +1,401 @@
+//! Database connection pool.
+//!
+//! Replaces the single mutex-guarded connection so independent queries (slash
+//! commands, the reminder scheduler, metrics collection) no longer queue behind
+//! each other. SQLite connections open with the configured [`SqlitePragmas`]:
+//! by default file databases run in WAL mode, which lets readers proceed while
+//! a writer is active, and writers still take turns inside SQLite, waiting up to
+//! the busy timeout for the lock instead of failing with `SQLITE_BUSY`.
+//! PostgreSQL clients come from a `deadpool-postgres` pool of the same size,
+//! checked out behind the same permits, and run on the pool's own runtime.
+//!
+//! Both drivers are synchronous, so `Database` methods hand their whole query to
+//! [`ConnectionPool::interact`], which runs it on tokio's blocking thread pool
+//! with a checked-out connection: a slow query holds its connection and a
+//! blocking thread, never an async worker, and it works the same on a
+//! current-thread runtime. The connection goes back to the pool when the query
+//! finishes, even if the caller stopped waiting for it.
+//!
+//! The pool size comes from `DATABASE_POOL_SIZE`; a bot in `config.yaml` can set
+//! `database_pool_size` to get a pool of its own on the same database.
+//!
+//! A connection is held for the whole of a query closure, so multi-statement
+//! sequences like `INSERT` then `SELECT last_insert_rowid()` see the same connection.
+//! How long each checkout lasts is recorded in [`QueryLatency`], which is the
+//! latency of that method's queries as the Prometheus exporter reports it.
+//! Inside a tracing span (an interaction's, say) each checkout is also a child
+//! `db.query` span tagged with the line in `database.rs` that asked for it.
+
+use super::backend::{Connection, DatabaseBackend};
+use super::postgres::{self, PgConnection};
+use crate::core::{BotError, Result, SqlitePragmas};
+use std::future::Future;
+use std::ops::Deref;
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{info_span, Span};
+
+/// Upper bounds (seconds) of the query latency histogram buckets
+pub const QUERY_LATENCY_BUCKETS: [f64; 11] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+
+/// Histogram of how long connections were held, updated without locking
+#[derive(Default)]
+pub struct QueryLatency {
+    /// Per-bucket counts, with one more slot for samples above the last bound
+    buckets: [AtomicU64; QUERY_LATENCY_BUCKETS.len() + 1],
+    sum_micros: AtomicU64,
+}
+
+/// Counts read from a [`QueryLatency`], cumulative like a Prometheus histogram
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryLatencySnapshot {
+    /// `(upper bound in seconds, samples at or below it)` for each bucket
+    pub buckets: Vec<(f64, u64)>,
+    pub count: u64,
+    pub sum_seconds: f64,
+}
+
+impl QueryLatency {
+    pub fn observe(&self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let bucket = QUERY_LATENCY_BUCKETS.iter().position(|&bound| seconds <= bound).unwrap_or(QUERY_LATENCY_BUCKETS.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> QueryLatencySnapshot {
+        let mut cumulative = 0;
+        let buckets = QUERY_LATENCY_BUCKETS
+            .iter()
+            .zip(&self.buckets)
+            .map(|(&bound, count)| {
+                cumulative += count.load(Ordering::Relaxed);
+                (bound, cumulative)
+            })
+            .collect();
+        let count = cumulative + self.buckets[QUERY_LATENCY_BUCKETS.len()].load(Ordering::Relaxed);
+        QueryLatencySnapshot {
+            buckets,
+            count,
+            sum_seconds: self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+        }
+    }
+}
+
+pub struct ConnectionPool {
+    backend: DatabaseBackend,
+    pragmas: SqlitePragmas,
+    /// Idle SQLite connections
+    idle: Mutex<Vec<Connection>>,
+    /// Clients for a PostgreSQL backend, which connect on first use
+    postgres: Option<postgres::PgPool>,
+    permits: Arc<Semaphore>,
+    size: usize,
+    latency: QueryLatency,
+}
+
+impl ConnectionPool {
+    /// Open `size` connections to the database. In-memory databases get a single
+    /// connection, since each connection to `:memory:` would be a separate database.
+    pub fn open(backend: &DatabaseBackend, size: usize, pragmas: &SqlitePragmas) -> Result<Self> {
+        let (connections, postgres) = match backend {
+            DatabaseBackend::Sqlite { path } => (open_sqlite(path, size, pragmas)?, None),
+            DatabaseBackend::Postgres { url } => (Vec::new(), Some(postgres::pool(url, size)?)),
+        };
+        let size = if postgres.is_some() { size.max(1) } else { connections.len() };
+
+        Ok(ConnectionPool {
+            backend: backend.clone(),
+            pragmas: pragmas.clone(),
+            idle: Mutex::new(connections),
+            postgres,
+            permits: Arc::new(Semaphore::new(size)),
+            size,
+            latency: QueryLatency::default(),
+        })
+    }
+
+    /// Number of connections in the pool
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// A separate pool of `size` connections to the same database. An in-memory
+    /// database can't be reached from a second pool, so its pool size stays as is.
+    pub fn resized(&self, size: usize) -> Result<Option<Self>> {
+        if matches!(&self.backend, DatabaseBackend::Sqlite { path } if path == ":memory:") {
+            return Ok(None);
+        }
+        Self::open(&self.backend, size, &self.pragmas).map(Some)
+    }
+
+    /// How long connections have been held, i.e. how long `Database` methods spent on queries
+    pub fn latency(&self) -> &QueryLatency {
+        &self.latency
+    }
+
+    /// Wait for a free connection
+    #[track_caller]
+    pub fn get(self: &Arc<Self>) -> impl Future<Output = Result<PooledConnection>> {
+        let caller = Location::caller();
+        let pool = self.clone();
+        async move {
+            let permit = pool.permits.clone().acquire_owned().await.map_err(|_| BotError::database("Connection pool is closed"))?;
+            let connection = match &pool.postgres {
+                Some(clients) => Connection::Postgres(PgConnection::checkout(clients).await?),
+                None => pool
+                    .idle
+                    .lock()
+                    .map_err(|_| BotError::database("Connection pool lock poisoned"))?
+                    .pop()
+                    .ok_or_else(|| BotError::database("Connection pool is empty"))?,
+            };
+            Ok(PooledConnection {
+                pool,
+                connection: Some(connection),
+                checked_out: Instant::now(),
+                _span: query_span(caller),
+                _permit: permit,
+            })
+        }
+    }
+
+    /// Run `query` with a free connection on the blocking thread pool and return its result.
+    ///
+    /// The closure owns what it needs, so the query finishes (and the connection
+    /// goes back) even if the caller is dropped while waiting for it.
+    #[track_caller]
+    pub fn interact<T, F>(self: &Arc<Self>, query: F) -> impl Future<Output = Result<T>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+    {
+        let checkout = self.get();
+        async move {
+            let connection = checkout.await?;
+            tokio::task::spawn_blocking(move || query(&connection))
+                .await
+                .map_err(|e| BotError::database(format!("Query task failed: {e}")))?
+        }
+    }
+}
+
+/// Child span for a checkout, or none outside any span so background tasks don't start traces
+fn query_span(caller: &'static Location<'static>) -> Span {
+    if Span::current().is_none() {
+        return Span::none();
+    }
+    info_span!("db.query", code.filepath = caller.file(), code.lineno = caller.line())
+}
+
+fn open_sqlite(path: &str, size: usize, pragmas: &SqlitePragmas) -> Result<Vec<Connection>> {
+    let memory = path == ":memory:";
+    let size = if memory { 1 } else { size.max(1) };
+
+    let mut connections = Vec::with_capacity(size);
+    for _ in 0..size {
+        let mut connection = sqlite::open(path)?;
+        connection.set_busy_timeout(pragmas.busy_timeout_ms as usize)?;
+        // In-memory databases only support the MEMORY and OFF journal modes
+        if !memory {
+            connection.execute(format!("PRAGMA journal_mode = {}", pragmas.journal_mode))?;
+        }
+        connection.execute(format!("PRAGMA synchronous = {}", pragmas.synchronous))?;
+        if let Some(kib) = pragmas.cache_kib {
+            // A negative cache_size is in KiB rather than pages
+            connection.execute(format!("PRAGMA cache_size = -{kib}"))?;
+        }
+        connections.push(connection);
+    }
+    Ok(connections.into_iter().map(Connection::Sqlite).collect())
+}
+
+/// A checked-out connection, returned to the pool on drop
+pub struct PooledConnection {
+    pool: Arc<ConnectionPool>,
+    connection: Option<Connection>,
+    checked_out: Instant,
+    /// Closed, and so timed, when the connection goes back
+    _span: Span,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.connection.as_ref().expect("connection is present until drop")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        self.pool.latency.observe(self.checked_out.elapsed());
+        // Return the connection before the permit is released (fields drop after this body);
+        // a PostgreSQL client goes back to its own pool when dropped
+        if let (Some(connection @ Connection::Sqlite(_)), Ok(mut idle)) = (self.connection.take(), self.pool.idle.lock()) {
+            idle.push(connection);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_database_uses_single_connection() {
+        let pool = Arc::new(ConnectionPool::open(&DatabaseBackend::parse(":memory:"), 8, &SqlitePragmas::default()).unwrap());
+        let conn = pool.get().await.unwrap();
+        conn.execute("CREATE TABLE t (x INTEGER)").unwrap();
+        drop(conn);
+
+        // The same connection (and so the same in-memory database) comes back
+        let conn = pool.get().await.unwrap();
+        conn.execute("INSERT INTO t VALUES (1)").unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connections_checked_out_concurrently() {
+        let path = std::env::temp_dir().join(format!("persona-pool-test-{}.db", uuid::Uuid::new_v4()));
+        let pragmas = SqlitePragmas { cache_kib: Some(256), ..SqlitePragmas::default() };
+        let pool = Arc::new(ConnectionPool::open(&DatabaseBackend::parse(path.to_str().unwrap()), 3, &pragmas).unwrap());
+
+        let a = pool.get().await.unwrap();
+        let b = pool.get().await.unwrap();
+        let c = pool.get().await.unwrap();
+        a.execute("CREATE TABLE t (x INTEGER)").unwrap();
+        b.execute("INSERT INTO t VALUES (1)").unwrap();
+        assert_eq!(pool.permits.available_permits(), 0);
+
+        drop((a, b, c));
+        assert_eq!(pool.permits.available_permits(), 3);
+        assert_eq!(pool.idle.lock().unwrap().len(), 3);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_pragmas_applied_to_each_connection() {
+        let path = std::env::temp_dir().join(format!("persona-pool-test-{}.db", uuid::Uuid::new_v4()));
+        let pragmas = SqlitePragmas { synchronous: "FULL".to_string(), cache_kib: Some(128), ..SqlitePragmas::default() };
+        let pool = Arc::new(ConnectionPool::open(&DatabaseBackend::parse(path.to_str().unwrap()), 2, &pragmas).unwrap());
+
+        let read = |conn: &Connection, pragma: &str| -> String {
+            let mut statement = conn.prepare(format!("PRAGMA {pragma}")).unwrap();
+            statement.next().unwrap();
+            statement.read::<Option<String>, _>(0).unwrap().unwrap_or_default()
+        };
+        let a = pool.get().await.unwrap();
+        let b = pool.get().await.unwrap();
+        for conn in [&*a, &*b] {
+            assert_eq!(read(conn, "journal_mode"), "wal");
+            // FULL is 2
+            assert_eq!(read(conn, "synchronous"), "2");
+            assert_eq!(read(conn, "cache_size"), "-128");
+        }
+
+        drop((a, b));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_query_latency_buckets_are_cumulative() {
+        let latency = QueryLatency::default();
+        latency.observe(Duration::from_micros(500));
+        latency.observe(Duration::from_millis(20));
+        latency.observe(Duration::from_secs(10));
+
+        let snapshot = latency.snapshot();
+        assert_eq!(snapshot.count, 3);
+        assert_eq!(snapshot.buckets[0], (0.001, 1));
+        assert_eq!(snapshot.buckets[4], (0.025, 2));
+        assert_eq!(snapshot.buckets.last(), Some(&(2.5, 2)));
+        assert!((snapshot.sum_seconds - 10.0205).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_checkout_span_only_inside_a_span() {
+        use crate::features::telemetry::{SpanTracker, SpanValue};
+        use tracing::Instrument;
+
+        let (sender, mut finished) = tokio::sync::mpsc::unbounded_channel();
+        let _guard = tracing::subscriber::set_default(SpanTracker::new(Some(sender)));
+        let pool = Arc::new(ConnectionPool::open(&DatabaseBackend::parse(":memory:"), 1, &SqlitePragmas::default()).unwrap());
+
+        drop(pool.get().await.unwrap());
+        assert!(finished.try_recv().is_err(), "background checkouts shouldn't start traces");
+
+        async { drop(pool.get().await.unwrap()) }.instrument(info_span!("interaction")).await;
+        let query = finished.try_recv().unwrap();
+        let interaction = finished.try_recv().unwrap();
+        assert_eq!(query.name, "db.query");
+        assert_eq!(query.parent_span_id, Some(interaction.span_id));
+        assert!(query
+            .attributes
+            .iter()
+            .any(|(key, value)| *key == "code.filepath" && matches!(value, SpanValue::Str(file) if file.ends_with("pool.rs"))));
+    }
+
+    #[tokio::test]
+    async fn test_resized_pool_shares_the_database() {
+        let path = std::env::temp_dir().join(format!("persona-pool-test-{}.db", uuid::Uuid::new_v4()));
+        let pool = Arc::new(ConnectionPool::open(&DatabaseBackend::parse(path.to_str().unwrap()), 2, &SqlitePragmas::default()).unwrap());
+        pool.get().await.unwrap().execute("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (5)").unwrap();
+
+        let own = Arc::new(pool.resized(5).unwrap().unwrap());
+        assert_eq!(own.size(), 5);
+        let conn = own.get().await.unwrap();
+        let mut statement = conn.prepare("SELECT x FROM t").unwrap();
+        statement.next().unwrap();
+        assert_eq!(statement.read::<i64, _>(0).unwrap(), 5);
+        drop(statement);
+        drop(conn);
+
+        let memory = ConnectionPool::open(&DatabaseBackend::parse(":memory:"), 1, &SqlitePragmas::default()).unwrap();
+        assert!(memory.resized(4).unwrap().is_none());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_interact_on_current_thread_runtime() {
+        let pool = Arc::new(ConnectionPool::open(&DatabaseBackend::parse(":memory:"), 1, &SqlitePragmas::default()).unwrap());
+        let x = pool
+            .interact(|conn| {
+                conn.execute("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (9)")?;
+                let mut statement = conn.prepare("SELECT x FROM t")?;
+                statement.next()?;
+                statement.read::<i64, _>(0)
+            })
+            .await
+            .unwrap();
+        assert_eq!(x, 9);
+        assert_eq!(pool.permits.available_permits(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_interact_returns_connection_when_caller_stops_waiting() {
+        let pool = Arc::new(ConnectionPool::open(&DatabaseBackend::parse(":memory:"), 1, &SqlitePragmas::default()).unwrap());
+        let slow = pool.interact(|conn| {
+            std::thread::sleep(Duration::from_millis(200));
+            conn.execute("CREATE TABLE t (x INTEGER)")
+        });
+        assert!(tokio::time::timeout(Duration::from_millis(20), slow).await.is_err());
+
+        // The abandoned query still ran on the only connection, which came back afterwards
+        pool.interact(|conn| conn.execute("INSERT INTO t VALUES (1)")).await.unwrap();
+        assert_eq!(pool.idle.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_checkouts_recorded_on_release() {
+        let pool = Arc::new(ConnectionPool::open(&DatabaseBackend::parse(":memory:"), 1, &SqlitePragmas::default()).unwrap());
+        let conn = pool.get().await.unwrap();
+        assert_eq!(pool.latency().snapshot().count, 0);
+        drop(conn);
+        assert_eq!(pool.latency().snapshot().count, 1);
+    }
+}
@@ -0,0 +1,524 @@
+//! PostgreSQL backend.
+//!
+//! Connections come from a `deadpool-postgres` pool of `tokio-postgres`
+//! clients, which live on a small runtime of their own (one `postgres` worker
+//! thread per pool). The [`super::backend::Statement`] API is synchronous and
+//! runs on the blocking thread pool (see [`super::ConnectionPool::interact`]),
+//! so each call hands the client's future to that runtime and waits for it.
+//! Nothing depends on the caller's runtime, which may be current-thread.
+//!
+//! Statements are prepared (and cached per connection) with real `$n`
+//! parameters. A bound value is encoded for whatever type PostgreSQL infers
+//! for its parameter, which keeps SQLite's loose typing: an integer bound
+//! where a text column is compared goes over as text, and the other way
+//! round. Results come back as text, so reading coerces them the same way for
+//! both backends.
+//!
+//! `Database` queries are written once where the two dialects agree, and
+//! [`translate`] only respells what is word for word: `?` placeholders, the
+//! schema's column types, `AUTOINCREMENT` and `CURRENT_TIMESTAMP`. Where the
+//! SQL itself differs (SQLite's date functions, transactions, the last inserted
+//! ID, changed row counts) `Database` has a query for each dialect or asks the
+//! [`super::backend::Connection`]. An `INSERT`, `UPDATE` or `DELETE` with a
+//! `RETURNING` clause returns its rows like a `SELECT` and counts them as
+//! changed. Timestamps are stored as `YYYY-MM-DD HH:MM:SS` text in UTC,
+//! exactly as SQLite stores `CURRENT_TIMESTAMP`.
+
+use crate::core::{BotError, Result};
+use deadpool_postgres::{ManagerConfig, Object, Pool, PoolConfig, RecyclingMethod, Runtime};
+use sqlite::{State, Value};
+use std::error::Error;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+use tokio_postgres::NoTls;
+
+/// SQLite's `CURRENT_TIMESTAMP`, as text
+const NOW: &str = "to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS')";
+
+type BoxError = Box<dyn Error + Sync + Send>;
+
+/// Clients and the runtime their connections run on
+pub struct PgPool {
+    clients: Pool,
+    /// Shut down in the background when the pool goes, which is allowed from async code
+    runtime: Option<tokio::runtime::Runtime>,
+}
+
+impl PgPool {
+    fn handle(&self) -> &Handle {
+        self.runtime.as_ref().expect("runtime is present until drop").handle()
+    }
+}
+
+impl Drop for PgPool {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// A pool of up to `size` clients, each connecting on first use
+pub fn pool(url: &str, size: usize) -> Result<PgPool> {
+    let config = deadpool_postgres::Config {
+        url: Some(url.to_string()),
+        pool: Some(PoolConfig::new(size.max(1))),
+        manager: Some(ManagerConfig { recycling_method: RecyclingMethod::Fast }),
+        ..deadpool_postgres::Config::default()
+    };
+    let clients = config
+        .create_pool(Some(Runtime::Tokio1), NoTls)
+        .map_err(|e| BotError::database(format!("PostgreSQL pool: {e}")))?;
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("postgres")
+        .enable_all()
+        .build()?;
+    Ok(PgPool { clients, runtime: Some(runtime) })
+}
+
+/// Rows returned by one query, all values as text
+#[derive(Debug, Default)]
+struct QueryResult {
+    columns: Vec<String>,
+    rows: Vec<Vec<Option<String>>>,
+}
+
+pub struct PgConnection {
+    client: Arc<Object>,
+    /// The pool's runtime, where the client's futures run
+    runtime: Handle,
+    /// Rows changed by the last `INSERT`, `UPDATE` or `DELETE`, like SQLite's `changes()`
+    changes: AtomicU64,
+}
+
+impl PgConnection {
+    /// Check a client out of `pool`, connecting if none is idle
+    pub async fn checkout(pool: &PgPool) -> Result<Self> {
+        let clients = pool.clients.clone();
+        let client = pool
+            .handle()
+            .spawn(async move { clients.get().await })
+            .await
+            .map_err(|e| BotError::database(format!("PostgreSQL checkout failed: {e}")))?
+            .map_err(|e| BotError::database(format!("PostgreSQL connection failed: {e}")))?;
+        Ok(PgConnection { client: Arc::new(client), runtime: pool.handle().clone(), changes: AtomicU64::new(0) })
+    }
+
+    /// Run a client future on the pool's runtime and wait for it. Statements run
+    /// on blocking threads, so the wait never holds up an async worker.
+    fn wait<T: Send + 'static>(&self, future: impl Future<Output = Result<T>> + Send + 'static) -> Result<T> {
+        futures::executor::block_on(self.runtime.spawn(future))
+            .map_err(|e| BotError::database(format!("PostgreSQL query task failed: {e}")))?
+    }
+
+    /// Run one or more statements without parameters
+    pub fn execute(&self, sql: &str) -> Result<()> {
+        let (client, sql) = (self.client.clone(), translate(sql));
+        self.wait(async move { Ok(client.batch_execute(&sql).await?) })
+    }
+
+    pub fn prepare(&self, sql: &str) -> PgStatement<'_> {
+        PgStatement {
+            connection: self,
+            sql: translate(sql),
+            params: Vec::new(),
+            result: None,
+            row: None,
+        }
+    }
+
+    pub fn changes(&self) -> i64 {
+        self.changes.load(Ordering::Relaxed) as i64
+    }
+
+    /// Run a statement, recording how many rows it changed if it's an `INSERT`, `UPDATE` or `DELETE`
+    fn query(&self, sql: &str, values: &[Value]) -> Result<QueryResult> {
+        let (client, sql, values) = (self.client.clone(), sql.to_string(), values.to_vec());
+        let (result, changed) = self.wait(async move { run_query(&client, &sql, &values).await })?;
+        if let Some(changed) = changed {
+            self.changes.store(changed, Ordering::Relaxed);
+        }
+        Ok(result)
+    }
+}
+
+/// The rows a statement returns, and the rows it changed if it changes any
+async fn run_query(client: &Object, sql: &str, values: &[Value]) -> Result<(QueryResult, Option<u64>)> {
+    let statement = client.prepare_cached(sql).await?;
+    // Parameters left unbound are NULL, as in SQLite
+    let params: Vec<Param> = (0..statement.params().len()).map(|i| Param(values.get(i).unwrap_or(&Value::Null))).collect();
+    let params: Vec<&(dyn ToSql + Sync)> = params.iter().map(|p| p as &(dyn ToSql + Sync)).collect();
+
+    let changes = changes_rows(sql);
+    if changes && !has_returning(sql) {
+        let affected = client.execute(&statement, &params).await?;
+        return Ok((QueryResult::default(), Some(affected)));
+    }
+    let rows = client.query(&statement, &params).await?;
+    let mut result = QueryResult {
+        columns: statement.columns().iter().map(|c| c.name().to_string()).collect(),
+        rows: Vec::with_capacity(rows.len()),
+    };
+    for row in rows {
+        let values = (0..row.len())
+            .map(|i| row.try_get::<_, Option<Text>>(i).map(|v| v.map(|Text(text)| text)))
+            .collect::<std::result::Result<_, _>>()?;
+        result.rows.push(values);
+    }
+    let changed = changes.then_some(result.rows.len() as u64);
+    Ok((result, changed))
+}
+
+/// Whether a statement is an `INSERT`, `UPDATE` or `DELETE`, the ones SQLite's `changes()` counts
+fn changes_rows(sql: &str) -> bool {
+    let keyword = sql.split_whitespace().next().unwrap_or_default();
+    ["INSERT", "UPDATE", "DELETE"].iter().any(|k| keyword.eq_ignore_ascii_case(k))
+}
+
+/// Whether a statement has a `RETURNING` clause, looking past quoted strings and names
+fn has_returning(sql: &str) -> bool {
+    let mut quote = None;
+    let mut word = String::new();
+    for c in sql.chars().chain([' ']) {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                word.clear();
+            }
+            None if c.is_alphanumeric() || c == '_' => word.push(c),
+            None if word.eq_ignore_ascii_case("RETURNING") => return true,
+            None => word.clear(),
+        }
+    }
+    false
+}
+
+pub struct PgStatement<'conn> {
+    connection: &'conn PgConnection,
+    sql: String,
+    params: Vec<Value>,
+    result: Option<QueryResult>,
+    /// Index of the current row once `next` has returned `Row`
+    row: Option<usize>,
+}
+
+impl PgStatement<'_> {
+    pub fn bind(&mut self, index: usize, value: Value) -> Result<()> {
+        if index == 0 {
+            return Err(BotError::database("parameter indices start at 1"));
+        }
+        if self.params.len() < index {
+            self.params.resize(index, Value::Null);
+        }
+        self.params[index - 1] = value;
+        Ok(())
+    }
+
+    pub fn next(&mut self) -> Result<State> {
+        if self.result.is_none() {
+            let result = self.connection.query(&self.sql, &self.params)?;
+            self.result = Some(result);
+        }
+        let rows = self.result.as_ref().map_or(0, |r| r.rows.len());
+        let next = self.row.map_or(0, |i| i + 1);
+        self.row = Some(next.min(rows));
+        Ok(if next < rows { State::Row } else { State::Done })
+    }
+
+    pub fn column_names(&self) -> &[String] {
+        self.result.as_ref().map_or(&[], |r| &r.columns)
+    }
+
+    pub fn text(&self, column: usize) -> Result<Option<&str>> {
+        let row = self
+            .result
+            .as_ref()
+            .zip(self.row)
+            .and_then(|(result, i)| result.rows.get(i))
+            .ok_or_else(|| BotError::database("no current row"))?;
+        Ok(row.get(column).and_then(|v| v.as_deref()))
+    }
+}
+
+/// A bound value, encoded for the type PostgreSQL inferred for its parameter
+#[derive(Debug)]
+struct Param<'a>(&'a Value);
+
+impl Param<'_> {
+    fn integer(&self) -> std::result::Result<i64, BoxError> {
+        match self.0 {
+            Value::Integer(v) => Ok(*v),
+            Value::Float(v) => Ok(*v as i64),
+            Value::String(s) => {
+                let s = s.trim();
+                s.parse::<i64>()
+                    .or_else(|_| s.parse::<f64>().map(|v| v as i64))
+                    .map_err(|_| format!("'{s}' is not a number").into())
+            }
+            Value::Binary(_) | Value::Null => Err("expected a number".into()),
+        }
+    }
+
+    fn float(&self) -> std::result::Result<f64, BoxError> {
+        match self.0 {
+            Value::Float(v) => Ok(*v),
+            Value::Integer(v) => Ok(*v as f64),
+            Value::String(s) => s.trim().parse().map_err(|_| format!("'{s}' is not a number").into()),
+            Value::Binary(_) | Value::Null => Err("expected a number".into()),
+        }
+    }
+
+    fn text(&self) -> String {
+        match self.0 {
+            Value::String(s) => s.clone(),
+            Value::Integer(v) => v.to_string(),
+            Value::Float(v) => v.to_string(),
+            Value::Binary(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+            Value::Null => String::new(),
+        }
+    }
+}
+
+impl ToSql for Param<'_> {
+    fn to_sql(&self, ty: &Type, out: &mut bytes::BytesMut) -> std::result::Result<IsNull, BoxError> {
+        if matches!(self.0, Value::Null) {
+            return Ok(IsNull::Yes);
+        }
+        match *ty {
+            Type::INT8 => self.integer()?.to_sql(ty, out),
+            Type::INT4 => i32::try_from(self.integer()?)?.to_sql(ty, out),
+            Type::INT2 => i16::try_from(self.integer()?)?.to_sql(ty, out),
+            Type::FLOAT8 => self.float()?.to_sql(ty, out),
+            Type::FLOAT4 => (self.float()? as f32).to_sql(ty, out),
+            Type::BOOL => (self.integer()? != 0).to_sql(ty, out),
+            Type::BYTEA => match self.0 {
+                Value::Binary(bytes) => bytes.as_slice().to_sql(ty, out),
+                _ => self.text().as_bytes().to_sql(ty, out),
+            },
+            Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME | Type::UNKNOWN => self.text().as_str().to_sql(ty, out),
+            _ => Err(format!("can't bind a value to a {ty} parameter").into()),
+        }
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
+
+/// A result column as text, whatever its type. Booleans read as `1`/`0` like SQLite's.
+struct Text(String);
+
+impl<'a> FromSql<'a> for Text {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> std::result::Result<Self, BoxError> {
+        let text = match *ty {
+            Type::BOOL => if bool::from_sql(ty, raw)? { "1" } else { "0" }.to_string(),
+            Type::INT2 => i16::from_sql(ty, raw)?.to_string(),
+            Type::INT4 => i32::from_sql(ty, raw)?.to_string(),
+            Type::INT8 => i64::from_sql(ty, raw)?.to_string(),
+            Type::FLOAT4 => f32::from_sql(ty, raw)?.to_string(),
+            Type::FLOAT8 => f64::from_sql(ty, raw)?.to_string(),
+            Type::NUMERIC => numeric_text(raw)?,
+            Type::BYTEA => String::from_utf8_lossy(raw).into_owned(),
+            Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME | Type::UNKNOWN | Type::JSON => String::from_sql(ty, raw)?,
+            _ => return Err(format!("can't read a {ty} column").into()),
+        };
+        Ok(Text(text))
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+/// Decimal text for PostgreSQL's binary NUMERIC (what `SUM` and `AVG` of integers return):
+/// base-10000 digit groups with the weight of the first group, a sign and the display scale
+fn numeric_text(raw: &[u8]) -> std::result::Result<String, BoxError> {
+    let word = |i: usize| -> std::result::Result<u16, BoxError> {
+        raw.get(i * 2..i * 2 + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(|| "truncated numeric".into())
+    };
+    let (count, weight, sign, scale) = (word(0)? as usize, word(1)? as i16 as i32, word(2)?, word(3)? as usize);
+    match sign {
+        0xC000 => return Ok("NaN".to_string()),
+        0xD000 => return Ok("Infinity".to_string()),
+        0xF000 => return Ok("-Infinity".to_string()),
+        _ => {}
+    }
+    let digits = (0..count).map(|i| word(4 + i)).collect::<std::result::Result<Vec<_>, _>>()?;
+    let group = |i: i32| if i >= 0 { digits.get(i as usize).copied().unwrap_or(0) } else { 0 };
+
+    let mut text = if sign == 0x4000 { "-".to_string() } else { String::new() };
+    if weight < 0 {
+        text.push('0');
+    } else {
+        text.push_str(&group(0).to_string());
+        for i in 1..=weight {
+            text.push_str(&format!("{:04}", group(i)));
+        }
+    }
+    if scale > 0 {
+        let mut fraction = String::new();
+        let mut i = weight + 1;
+        while fraction.len() < scale {
+            fraction.push_str(&format!("{:04}", group(i)));
+            i += 1;
+        }
+        fraction.truncate(scale);
+        text.push('.');
+        text.push_str(&fraction);
+    }
+    Ok(text)
+}
+
+/// PostgreSQL's spelling of a word of SQLite's, for the words whose meaning is the same.
+/// Type names are matched in upper case only, so a column named `date` survives.
+fn respell(word: &str) -> Option<&'static str> {
+    match word {
+        "INTEGER" | "BOOLEAN" => Some("BIGINT"),
+        "REAL" => Some("DOUBLE PRECISION"),
+        "DATETIME" | "DATE" => Some("TEXT"),
+        "BLOB" => Some("BYTEA"),
+        "AUTOINCREMENT" => Some("GENERATED BY DEFAULT AS IDENTITY"),
+        _ if word.eq_ignore_ascii_case("CURRENT_TIMESTAMP") => Some(NOW),
+        _ => None,
+    }
+}
+
+/// Respell a statement in SQLite's dialect for PostgreSQL, word by word, leaving
+/// quoted literals and identifiers alone. `?` and `?N` placeholders become `$N`,
+/// numbered the way SQLite numbers them: a bare `?` takes the index after the
+/// largest one used so far.
+pub fn translate(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len() + 16);
+    let mut next_index = 1;
+    let mut chars = sql.char_indices().peekable();
+    let mut quote: Option<char> = None;
+
+    while let Some((i, c)) = chars.next() {
+        if let Some(q) = quote {
+            out.push(c);
+            if c == q {
+                quote = None;
+            }
+        } else if c == '\'' || c == '"' {
+            quote = Some(c);
+            out.push(c);
+        } else if c == '?' {
+            let mut digits = String::new();
+            while let Some(&(_, d)) = chars.peek().filter(|(_, d)| d.is_ascii_digit()) {
+                digits.push(d);
+                chars.next();
+            }
+            let index = digits.parse::<usize>().unwrap_or(next_index);
+            next_index = next_index.max(index + 1);
+            out.push_str(&format!("${index}"));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = i + c.len_utf8();
+            while let Some(&(j, d)) = chars.peek() {
+                if !(d.is_alphanumeric() || d == '_') {
+                    break;
+                }
+                end = j + d.len_utf8();
+                chars.next();
+            }
+            let word = &sql[i..end];
+            out.push_str(respell(word).unwrap_or(word));
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_schema() {
+        let sql = translate(
+            "CREATE TABLE IF NOT EXISTS t (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                cost REAL,
+                done BOOLEAN DEFAULT 0,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+        );
+        assert!(sql.contains("id BIGINT PRIMARY KEY GENERATED BY DEFAULT AS IDENTITY,"));
+        assert!(sql.contains("cost DOUBLE PRECISION,"));
+        assert!(sql.contains("done BIGINT DEFAULT 0,"));
+        assert!(sql.contains(&format!("created_at TEXT DEFAULT {NOW}")));
+    }
+
+    #[test]
+    fn test_translate_leaves_literals_and_names_alone() {
+        assert_eq!(translate("SELECT 'INTEGER ?' FROM t"), "SELECT 'INTEGER ?' FROM t");
+        assert_eq!(translate("SELECT \"REAL\", date, updated_date FROM t"), "SELECT \"REAL\", date, updated_date FROM t");
+    }
+
+    #[test]
+    fn test_translate_placeholders() {
+        assert_eq!(translate("SELECT ?, ?, '?'"), "SELECT $1, $2, '?'");
+        assert_eq!(translate("WHERE a = ?2 OR b = ?2 LIMIT ?"), "WHERE a = $2 OR b = $2 LIMIT $3");
+    }
+
+    #[test]
+    fn test_param_follows_inferred_type() {
+        let mut out = bytes::BytesMut::new();
+        let text = Value::String("42".to_string());
+        assert!(Param(&text).to_sql(&Type::INT8, &mut out).is_ok());
+        assert_eq!(&out[..], &42i64.to_be_bytes());
+
+        out.clear();
+        let number = Value::Integer(7);
+        assert!(Param(&number).to_sql(&Type::TEXT, &mut out).is_ok());
+        assert_eq!(&out[..], b"7");
+
+        // Quotes and backslashes are data, never SQL
+        out.clear();
+        let quoted = Value::String("it's \\ '; DROP TABLE t; --".to_string());
+        assert!(Param(&quoted).to_sql(&Type::TEXT, &mut out).is_ok());
+        assert_eq!(&out[..], b"it's \\ '; DROP TABLE t; --");
+
+        assert!(Param(&Value::String("abc".to_string())).to_sql(&Type::INT8, &mut out).is_err());
+        assert!(matches!(Param(&Value::Null).to_sql(&Type::INT8, &mut out), Ok(IsNull::Yes)));
+    }
+
+    #[test]
+    fn test_numeric_text() {
+        let numeric = |words: &[u16]| -> Vec<u8> { words.iter().flat_map(|w| w.to_be_bytes()).collect() };
+        // 12345.678: groups [1, 2345, 6780], weight 1, scale 3
+        assert_eq!(numeric_text(&numeric(&[3, 1, 0, 3, 1, 2345, 6780])).unwrap(), "12345.678");
+        // -0.05: group [500], weight -1, scale 2
+        assert_eq!(numeric_text(&numeric(&[1, 0xFFFF, 0x4000, 2, 500])).unwrap(), "-0.05");
+        // 20000: group [2], weight 1, scale 0
+        assert_eq!(numeric_text(&numeric(&[1, 1, 0, 0, 2])).unwrap(), "20000");
+        assert_eq!(numeric_text(&numeric(&[0, 0, 0, 0])).unwrap(), "0");
+    }
+
+    #[test]
+    fn test_changes_rows() {
+        assert!(changes_rows("\n  update t SET x = 1"));
+        assert!(changes_rows("INSERT INTO t VALUES ($1)"));
+        assert!(!changes_rows("SELECT lastval()"));
+    }
+
+    #[test]
+    fn test_has_returning() {
+        assert!(has_returning("INSERT INTO t (x) VALUES ($1) RETURNING id"));
+        assert!(has_returning("delete from t where id = $1\nreturning *"));
+        assert!(!has_returning("INSERT INTO t (x) VALUES ($1)"));
+        assert!(!has_returning("UPDATE t SET note = 'returning soon' WHERE id = $1"));
+        assert!(!has_returning("UPDATE t SET \"returning\" = 1"));
+        assert!(!has_returning("DELETE FROM t WHERE returning_at < $1"));
+    }
+}
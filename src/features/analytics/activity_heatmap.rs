@@ -0,0 +1,277 @@
+//! # Feature: Activity Heatmap
+//!
+//! Renders an hour × weekday heatmap of a channel's message volume as a PNG so
+//! admins can see when a channel is busiest (e.g. to pick event times).
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Shift hours into the requesting user's `/timezone`
+//! - 1.0.0: Initial release with PNG heatmap and busiest-slot summary
+
+use crate::core::{BotError, Result};
+
+/// Days of history included (four full weeks, so every weekday is counted equally)
+pub const ACTIVITY_WINDOW_DAYS: i64 = 28;
+
+/// Weekday labels, Monday first
+pub const WEEKDAYS: [&str; 7] = ["MON", "TUE", "WED", "THU", "FRI", "SAT", "SUN"];
+
+const CELL: u32 = 24;
+const GAP: u32 = 2;
+const SCALE: u32 = 2;
+const LEFT: u32 = 44;
+const TOP: u32 = 22;
+const PADDING: u32 = 8;
+
+const BACKGROUND: [u8; 3] = [43, 45, 49];
+const EMPTY: [u8; 3] = [54, 57, 63];
+const HOT: [u8; 3] = [87, 242, 135];
+const LABEL: [u8; 3] = [181, 186, 193];
+
+/// Message counts by weekday (Monday = 0) and hour, in UTC until [`HeatmapGrid::shifted`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeatmapGrid {
+    pub counts: [[u32; 24]; 7],
+}
+
+impl HeatmapGrid {
+    /// Build from (SQLite `%w` weekday where Sunday = 0, hour, count) rows
+    pub fn from_rows(rows: &[(u32, u32, u32)]) -> Self {
+        let mut grid = Self::default();
+        for &(weekday, hour, count) in rows {
+            if weekday < 7 && hour < 24 {
+                grid.counts[((weekday + 6) % 7) as usize][hour as usize] += count;
+            }
+        }
+        grid
+    }
+
+    /// Move every count by a UTC offset (rounded to whole hours), wrapping around the week
+    pub fn shifted(&self, offset_minutes: i32) -> Self {
+        let hours = (offset_minutes as f64 / 60.0).round() as i64;
+        let mut grid = Self::default();
+        for (day, counts) in self.counts.iter().enumerate() {
+            for (hour, count) in counts.iter().enumerate() {
+                let slot = (day as i64 * 24 + hour as i64 + hours).rem_euclid(7 * 24) as usize;
+                grid.counts[slot / 24][slot % 24] += count;
+            }
+        }
+        grid
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.iter().flatten().sum()
+    }
+
+    pub fn max(&self) -> u32 {
+        self.counts.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// Busiest (weekday, hour, count) slots, most active first
+    pub fn busiest(&self, limit: usize) -> Vec<(usize, usize, u32)> {
+        let mut slots: Vec<(usize, usize, u32)> = self
+            .counts
+            .iter()
+            .enumerate()
+            .flat_map(|(day, hours)| hours.iter().enumerate().map(move |(hour, count)| (day, hour, *count)))
+            .filter(|(_, _, count)| *count > 0)
+            .collect();
+        slots.sort_by(|a, b| b.2.cmp(&a.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+        slots.truncate(limit);
+        slots
+    }
+}
+
+/// Text summary accompanying the heatmap
+pub fn format_activity_summary(channel_id: &str, grid: &HeatmapGrid, zone_label: &str) -> String {
+    let total = grid.total();
+    if total == 0 {
+        return format!("📊 No stored messages in <#{channel_id}> in the last {ACTIVITY_WINDOW_DAYS} days.");
+    }
+    let busiest = grid
+        .busiest(3)
+        .iter()
+        .map(|(day, hour, count)| format!("{} {hour:02}:00 ({count})", title_case(WEEKDAYS[*day])))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "📊 **Activity in <#{channel_id}>** — {total} messages over the last {ACTIVITY_WINDOW_DAYS} days (times in {zone_label})\n\
+        Busiest hours: {busiest}"
+    )
+}
+
+fn title_case(label: &str) -> String {
+    let mut chars = label.chars();
+    match chars.next() {
+        Some(first) => first.to_string() + &chars.as_str().to_lowercase(),
+        None => String::new(),
+    }
+}
+
+/// 3×5 bitmap glyphs for the labels (rows top to bottom, 3 bits each)
+fn glyph(c: char) -> Option<[u8; 5]> {
+    Some(match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        _ => return None,
+    })
+}
+
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, color: [u8; 3]) -> Self {
+        let pixels = color.iter().copied().cycle().take((width * height * 3) as usize).collect();
+        Self { width, height, pixels }
+    }
+
+    fn fill_rect(&mut self, x: u32, y: u32, w: u32, h: u32, color: [u8; 3]) {
+        for py in y..(y + h).min(self.height) {
+            for px in x..(x + w).min(self.width) {
+                let i = ((py * self.width + px) * 3) as usize;
+                self.pixels[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    fn draw_text(&mut self, x: u32, y: u32, text: &str, color: [u8; 3]) {
+        for (n, c) in text.chars().enumerate() {
+            let Some(rows) = glyph(c) else { continue };
+            let gx = x + n as u32 * 4 * SCALE;
+            for (row, bits) in rows.iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        self.fill_rect(gx + col * SCALE, y + row as u32 * SCALE, SCALE, SCALE, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Color for a cell relative to the busiest cell
+fn cell_color(count: u32, max: u32) -> [u8; 3] {
+    if count == 0 || max == 0 {
+        return EMPTY;
+    }
+    // Square root keeps quieter hours visible next to a single very busy one
+    let t = (count as f64 / max as f64).sqrt();
+    std::array::from_fn(|i| (EMPTY[i] as f64 + (HOT[i] as f64 - EMPTY[i] as f64) * t).round() as u8)
+}
+
+/// Render the grid as a PNG image
+fn png_error(e: png::EncodingError) -> BotError {
+    BotError::internal(format!("PNG encoding failed: {e}"))
+}
+
+pub fn render_heatmap_png(grid: &HeatmapGrid) -> Result<Vec<u8>> {
+    let width = LEFT + 24 * (CELL + GAP) + PADDING;
+    let height = TOP + 7 * (CELL + GAP) + PADDING;
+    let mut canvas = Canvas::new(width, height, BACKGROUND);
+
+    // Hour labels every three hours
+    for hour in (0..24).step_by(3) {
+        canvas.draw_text(LEFT + hour * (CELL + GAP) + 2, PADDING, &format!("{hour:02}"), LABEL);
+    }
+
+    let max = grid.max();
+    for (day, hours) in grid.counts.iter().enumerate() {
+        let y = TOP + day as u32 * (CELL + GAP);
+        canvas.draw_text(PADDING, y + (CELL - 5 * SCALE) / 2, WEEKDAYS[day], LABEL);
+        for (hour, count) in hours.iter().enumerate() {
+            let x = LEFT + hour as u32 * (CELL + GAP);
+            canvas.fill_rect(x, y, CELL, CELL, cell_color(*count, max));
+        }
+    }
+
+    let mut encoded = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut encoded, canvas.width, canvas.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&canvas.pixels).map_err(png_error)?;
+    }
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_maps_sunday_last() {
+        let grid = HeatmapGrid::from_rows(&[(0, 10, 5), (1, 9, 2), (7, 0, 99), (3, 24, 99)]);
+        assert_eq!(grid.counts[6][10], 5); // Sunday
+        assert_eq!(grid.counts[0][9], 2); // Monday
+        assert_eq!(grid.total(), 7);
+        assert_eq!(grid.max(), 5);
+        assert_eq!(grid.busiest(1), vec![(6, 10, 5)]);
+    }
+
+    #[test]
+    fn test_summary() {
+        let grid = HeatmapGrid::from_rows(&[(3, 18, 42), (5, 20, 7)]);
+        let summary = format_activity_summary("123", &grid, "UTC");
+        assert!(summary.contains("49 messages"));
+        assert!(summary.contains("(times in UTC)"));
+        assert!(summary.contains("Wed 18:00 (42), Fri 20:00 (7)"));
+        assert!(format_activity_summary("123", &HeatmapGrid::default(), "UTC").contains("No stored messages"));
+    }
+
+    #[test]
+    fn test_shift_wraps_around_week() {
+        // Sunday 23:00 UTC is Monday 01:00 at UTC+2; Monday 01:00 UTC is Sunday 20:00 at UTC-05:00
+        let grid = HeatmapGrid::from_rows(&[(0, 23, 4), (1, 1, 3)]);
+        let east = grid.shifted(120);
+        assert_eq!(east.counts[0][1], 4);
+        assert_eq!(east.counts[0][3], 3);
+        let west = grid.shifted(-300);
+        assert_eq!(west.counts[6][20], 3);
+        assert_eq!(west.counts[6][18], 4);
+        assert_eq!(west.total(), grid.total());
+        assert_eq!(grid.shifted(0), grid);
+    }
+
+    #[test]
+    fn test_cell_color_scale() {
+        assert_eq!(cell_color(0, 10), EMPTY);
+        assert_eq!(cell_color(10, 10), HOT);
+    }
+
+    #[test]
+    fn test_render_png() {
+        let grid = HeatmapGrid::from_rows(&[(1, 12, 3)]);
+        let png = render_heatmap_png(&grid).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    }
+}
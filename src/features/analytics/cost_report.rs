@@ -0,0 +1,415 @@
+//! # Feature: Cost Reports
+//!
+//! Attributes OpenAI spend to bot features and renders invoice-style reports,
+//! both on demand (`/costs breakdown`) and as a weekly digest and monthly invoice
+//! sent to the bot owner by DM and/or email.
+//!
+//! - **Version**: 1.2.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.2.0: Weekly cost digest for the previous ISO week, delivered like the invoice
+//! - 1.1.0: Monthly invoice can also be emailed over SMTP
+//! - 1.0.0: Initial release with per-feature attribution and monthly owner invoice
+
+use super::email_delivery::EmailSender;
+use crate::core::BotError;
+use crate::core::discord_limits::{split_message, MESSAGE_CONTENT};
+use crate::database::{CostLine, Database};
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use log::{info, warn};
+use serenity::http::Http;
+use serenity::model::id::{GuildId, UserId};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Feature tags recorded with each OpenAI call
+pub mod cost_feature {
+    pub const CHAT: &str = "chat";
+    pub const MEDIATION: &str = "mediation";
+    pub const SUMMARIZATION: &str = "summarization";
+    pub const DIGEST: &str = "digest";
+    pub const IMAGE: &str = "image";
+    pub const TRANSCRIPTION: &str = "transcription";
+    pub const REMINDERS: &str = "reminders";
+    pub const INTROSPECTION: &str = "introspection";
+    pub const VERIFICATION: &str = "verification";
+    pub const VISION: &str = "vision";
+}
+
+/// Bot setting holding the last month (`YYYY-MM`) an invoice was sent for
+pub const LAST_INVOICE_SETTING: &str = "cost_invoice_last_month";
+
+/// Bot setting holding the last ISO week (`YYYY-Www`) a digest was sent for
+pub const LAST_DIGEST_SETTING: &str = "cost_digest_last_week";
+
+/// Human-readable label for a feature tag
+pub fn feature_label(feature: &str) -> String {
+    match feature {
+        cost_feature::CHAT => "Chat".to_string(),
+        cost_feature::MEDIATION => "Conflict mediation".to_string(),
+        cost_feature::SUMMARIZATION => "Summarization".to_string(),
+        cost_feature::DIGEST => "Digests".to_string(),
+        cost_feature::IMAGE => "Image generation".to_string(),
+        cost_feature::TRANSCRIPTION => "Audio transcription".to_string(),
+        cost_feature::REMINDERS => "Reminders".to_string(),
+        cost_feature::INTROSPECTION => "Introspection".to_string(),
+        cost_feature::VERIFICATION => "Verification questions".to_string(),
+        cost_feature::VISION => "Image understanding".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// First and last day (inclusive) of a month, as `YYYY-MM-DD`
+pub fn month_bounds(year: i32, month: u32) -> Option<(String, String)> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    let last = next.pred_opt()?;
+    Some((first.format("%Y-%m-%d").to_string(), last.format("%Y-%m-%d").to_string()))
+}
+
+/// The calendar month before the one containing `now`
+pub fn previous_month(now: DateTime<Utc>) -> (i32, u32) {
+    if now.month() == 1 {
+        (now.year() - 1, 12)
+    } else {
+        (now.year(), now.month() - 1)
+    }
+}
+
+/// Monday and Sunday of the ISO week before the one containing `now`
+pub fn previous_week(now: DateTime<Utc>) -> (NaiveDate, NaiveDate) {
+    let today = now.date_naive();
+    let this_monday = today - Days::new(u64::from(today.weekday().num_days_from_monday()));
+    (this_monday - Days::new(7), this_monday - Days::new(1))
+}
+
+/// `YYYY-Www` label for the ISO week containing `date`
+pub fn week_label(date: NaiveDate) -> String {
+    let week = date.iso_week();
+    format!("{:04}-W{:02}", week.year(), week.week())
+}
+
+/// Month selected by a `/costs` period choice (`this_month` or `last_month`)
+pub fn period_month(period: &str, now: DateTime<Utc>) -> (i32, u32) {
+    match period {
+        "last_month" => previous_month(now),
+        _ => (now.year(), now.month()),
+    }
+}
+
+/// `YYYY-MM` label for a month
+pub fn month_label(year: i32, month: u32) -> String {
+    format!("{year:04}-{month:02}")
+}
+
+/// Render cost lines as an invoice, grouped per guild with per-feature line items.
+///
+/// `guild_name` maps a guild ID to a display name; unknown IDs fall back to the ID,
+/// and the empty guild ID is shown as "Direct messages".
+pub fn format_invoice(title: &str, period: &str, lines: &[CostLine], guild_name: &dyn Fn(&str) -> Option<String>) -> String {
+    if lines.is_empty() {
+        return format!("**{title}**\n*{period}*\n\nNo AI usage recorded for this period.");
+    }
+
+    let mut by_guild: BTreeMap<&str, Vec<&CostLine>> = BTreeMap::new();
+    for line in lines {
+        by_guild.entry(line.guild_id.as_str()).or_default().push(line);
+    }
+
+    let mut out = vec![format!("**{title}**\n*{period}*")];
+    let mut grand_total = 0.0;
+    let mut feature_totals: BTreeMap<&str, f64> = BTreeMap::new();
+
+    for (guild_id, items) in &by_guild {
+        let name = if guild_id.is_empty() {
+            "Direct messages".to_string()
+        } else {
+            guild_name(guild_id).unwrap_or_else(|| format!("Guild {guild_id}"))
+        };
+        out.push(format!("\n__{name}__"));
+
+        let mut items = items.clone();
+        items.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+        let mut subtotal = 0.0;
+        for item in items {
+            out.push(format!(
+                "`{:<20}` {:>6} req  ${:>9.4}",
+                feature_label(&item.feature),
+                item.requests,
+                item.cost
+            ));
+            subtotal += item.cost;
+            *feature_totals.entry(item.feature.as_str()).or_default() += item.cost;
+        }
+        out.push(format!("Subtotal: **${subtotal:.4}**"));
+        grand_total += subtotal;
+    }
+
+    if by_guild.len() > 1 {
+        out.push("\n__By feature__".to_string());
+        let mut totals: Vec<_> = feature_totals.into_iter().collect();
+        totals.sort_by(|a, b| b.1.total_cmp(&a.1));
+        for (feature, cost) in totals {
+            out.push(format!("`{:<20}` ${:>9.4}", feature_label(feature), cost));
+        }
+    }
+
+    out.push(format!("\n**Total due: ${grand_total:.4}** (estimated)"));
+    out.join("\n")
+}
+
+/// Background task that sends the bot owner last month's invoice once per month,
+/// by DM and, when SMTP is configured, by email
+pub async fn monthly_invoice_loop(http: Arc<Http>, db: Arc<Database>, email: Option<EmailSender>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(3600));
+    info!("Monthly cost invoice task started (checks hourly)");
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = send_invoice_if_due(&http, &db, email.as_ref()).await {
+            warn!("Failed to send monthly cost invoice: {}", e);
+        }
+    }
+}
+
+/// Background task that sends the bot owner last week's costs once per week,
+/// by DM and, when SMTP is configured, by email
+pub async fn weekly_digest_loop(http: Arc<Http>, db: Arc<Database>, email: Option<EmailSender>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(3600));
+    info!("Weekly cost digest task started (checks hourly)");
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = send_digest_if_due(&http, &db, email.as_ref(), Utc::now()).await {
+            warn!("Failed to send weekly cost digest: {}", e);
+        }
+    }
+}
+
+async fn owner_id(db: &Database) -> crate::core::Result<Option<u64>> {
+    Ok(db
+        .get_bot_setting("startup_notify_owner_id")
+        .await?
+        .and_then(|id| id.parse::<u64>().ok()))
+}
+
+async fn send_digest_if_due(
+    http: &Http,
+    db: &Database,
+    email: Option<&EmailSender>,
+    now: DateTime<Utc>,
+) -> crate::core::Result<()> {
+    let owner_id = owner_id(db).await?;
+    if owner_id.is_none() && email.is_none() {
+        return Ok(());
+    }
+
+    let (monday, sunday) = previous_week(now);
+    let label = week_label(monday);
+    if db.get_bot_setting(LAST_DIGEST_SETTING).await?.as_deref() == Some(label.as_str()) {
+        return Ok(());
+    }
+
+    let (start, end) = (monday.format("%Y-%m-%d").to_string(), sunday.format("%Y-%m-%d").to_string());
+    let lines = db.get_cost_breakdown(None, &start, &end).await?;
+    let names = resolve_guild_names(http, &lines).await;
+    let digest = format_invoice(
+        &format!("📊 Weekly AI cost digest — {label}"),
+        &format!("{start} to {end}"),
+        &lines,
+        &|id| names.get(id).cloned(),
+    );
+
+    let delivered = deliver_to_owner(http, owner_id, email, &format!("Weekly AI cost digest — {label}"), &digest).await;
+    if delivered.is_empty() {
+        return Err(BotError::internal(format!("No delivery succeeded for the {label} digest")));
+    }
+
+    db.set_bot_setting(LAST_DIGEST_SETTING, &label).await?;
+    info!("Sent weekly cost digest for {} by {}", label, delivered.join(" and "));
+    Ok(())
+}
+
+async fn send_invoice_if_due(http: &Http, db: &Database, email: Option<&EmailSender>) -> crate::core::Result<()> {
+    let owner_id = owner_id(db).await?;
+    if owner_id.is_none() && email.is_none() {
+        return Ok(());
+    }
+
+    let (year, month) = previous_month(Utc::now());
+    let label = month_label(year, month);
+    if db.get_bot_setting(LAST_INVOICE_SETTING).await?.as_deref() == Some(label.as_str()) {
+        return Ok(());
+    }
+
+    let (start, end) = month_bounds(year, month).ok_or_else(|| BotError::validation(format!("Invalid month {label}")))?;
+    let lines = db.get_cost_breakdown(None, &start, &end).await?;
+    let names = resolve_guild_names(http, &lines).await;
+    let invoice = format_invoice(
+        &format!("🧾 AI cost invoice — {label}"),
+        &format!("{start} to {end}"),
+        &lines,
+        &|id| names.get(id).cloned(),
+    );
+
+    let delivered = deliver_to_owner(http, owner_id, email, &format!("AI cost invoice — {label}"), &invoice).await;
+    if delivered.is_empty() {
+        return Err(BotError::internal(format!("No delivery succeeded for the {label} invoice")));
+    }
+
+    db.set_bot_setting(LAST_INVOICE_SETTING, &label).await?;
+    info!("Sent monthly cost invoice for {} by {}", label, delivered.join(" and "));
+    Ok(())
+}
+
+/// Send a report to the owner by DM and/or email, returning the channels that succeeded.
+///
+/// Either channel reaching the owner counts; a failed one is only logged.
+async fn deliver_to_owner(
+    http: &Http,
+    owner_id: Option<u64>,
+    email: Option<&EmailSender>,
+    subject: &str,
+    report: &str,
+) -> Vec<&'static str> {
+    let mut delivered = Vec::new();
+    if let Some(owner_id) = owner_id {
+        match send_report_dm(http, owner_id, report).await {
+            Ok(()) => delivered.push("DM"),
+            Err(e) => warn!("Failed to DM \"{}\" to owner {}: {}", subject, owner_id, e),
+        }
+    }
+    if let Some(email) = email {
+        match email.send_report(subject, report).await {
+            Ok(()) => delivered.push("email"),
+            Err(e) => warn!("Failed to email \"{}\": {}", subject, e),
+        }
+    }
+    delivered
+}
+
+async fn send_report_dm(http: &Http, owner_id: u64, report: &str) -> crate::core::Result<()> {
+    let dm = UserId(owner_id).create_dm_channel(http).await?;
+    for chunk in split_message(report, MESSAGE_CONTENT) {
+        dm.say(http, chunk).await?;
+    }
+    Ok(())
+}
+
+/// Look up display names for the guilds in a breakdown; guilds the bot has left are omitted
+pub async fn resolve_guild_names(http: &Http, lines: &[CostLine]) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    for line in lines {
+        if names.contains_key(&line.guild_id) {
+            continue;
+        }
+        if let Ok(id) = line.guild_id.parse::<u64>() {
+            if let Ok(guild) = GuildId(id).to_partial_guild(http).await {
+                names.insert(line.guild_id.clone(), guild.name);
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn line(guild_id: &str, feature: &str, requests: i64, cost: f64) -> CostLine {
+        CostLine {
+            guild_id: guild_id.to_string(),
+            feature: feature.to_string(),
+            requests,
+            tokens: 0,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_month_helpers() {
+        assert_eq!(
+            month_bounds(2024, 2),
+            Some(("2024-02-01".to_string(), "2024-02-29".to_string()))
+        );
+        assert_eq!(
+            month_bounds(2025, 12),
+            Some(("2025-12-01".to_string(), "2025-12-31".to_string()))
+        );
+        assert_eq!(previous_month(Utc.with_ymd_and_hms(2026, 1, 15, 0, 0, 0).unwrap()), (2025, 12));
+        assert_eq!(month_label(2026, 3), "2026-03");
+
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 0, 0, 0).unwrap();
+        assert_eq!(period_month("this_month", now), (2026, 10));
+        assert_eq!(period_month("last_month", now), (2026, 9));
+    }
+
+    #[test]
+    fn test_week_helpers() {
+        // Thursday 2026-10-15 is in 2026-W42, so the previous week is W41
+        let (monday, sunday) = previous_week(Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap());
+        assert_eq!(monday, NaiveDate::from_ymd_opt(2026, 10, 5).unwrap());
+        assert_eq!(sunday, NaiveDate::from_ymd_opt(2026, 10, 11).unwrap());
+        assert_eq!(week_label(monday), "2026-W41");
+
+        // Early January belongs to the previous ISO year
+        let (monday, _) = previous_week(Utc.with_ymd_and_hms(2027, 1, 6, 0, 0, 0).unwrap());
+        assert_eq!(week_label(monday), "2026-W53");
+    }
+
+    #[tokio::test]
+    async fn test_digest_emailed_once_per_week_when_smtp_is_set() {
+        let db = Database::new(":memory:", 1).await.unwrap();
+        let http = Http::new("");
+        let (email, stub) = EmailSender::stub();
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap();
+
+        send_digest_if_due(&http, &db, Some(&email), now).await.unwrap();
+        send_digest_if_due(&http, &db, Some(&email), now).await.unwrap();
+
+        let messages = stub.messages().await;
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].1.contains("Weekly AI cost digest"));
+        assert_eq!(db.get_bot_setting(LAST_DIGEST_SETTING).await.unwrap().as_deref(), Some("2026-W41"));
+    }
+
+    #[tokio::test]
+    async fn test_digest_skipped_without_owner_or_smtp() {
+        let db = Database::new(":memory:", 1).await.unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap();
+
+        send_digest_if_due(&Http::new(""), &db, None, now).await.unwrap();
+        assert_eq!(db.get_bot_setting(LAST_DIGEST_SETTING).await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_format_invoice_groups_by_guild_and_feature() {
+        let lines = vec![
+            line("1", cost_feature::CHAT, 10, 0.5),
+            line("1", cost_feature::IMAGE, 2, 0.08),
+            line("", cost_feature::CHAT, 3, 0.02),
+        ];
+        let names = |id: &str| (id == "1").then(|| "Space Base".to_string());
+        let invoice = format_invoice("Invoice", "2026-09", &lines, &names);
+
+        assert!(invoice.contains("__Space Base__"));
+        assert!(invoice.contains("__Direct messages__"));
+        assert!(invoice.contains("Image generation"));
+        assert!(invoice.contains("__By feature__"));
+        assert!(invoice.contains("Total due: $0.6000"));
+    }
+
+    #[test]
+    fn test_format_invoice_empty() {
+        let invoice = format_invoice("Invoice", "2026-09", &[], &|_| None);
+        assert!(invoice.contains("No AI usage recorded"));
+    }
+}
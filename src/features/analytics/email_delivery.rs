@@ -0,0 +1,145 @@
+//! # Feature: Email Delivery
+//!
+//! Sends owner reports (the monthly invoice and weekly digest) and budget
+//! alerts by email over SMTP for owners who don't check Discord DMs regularly.
+//! Configured with the `SMTP_*` and `OWNER_EMAIL` variables; port 465 uses
+//! implicit TLS, any other port STARTTLS.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: `from_config` shared by every report and alert sender
+//! - 1.0.0: Initial release with plain-text SMTP delivery of the monthly invoice
+
+use crate::core::config::SmtpConfig;
+use crate::core::{BotError, Result};
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+#[cfg(test)]
+use lettre::transport::stub::AsyncStubTransport;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use log::warn;
+
+#[derive(Clone)]
+enum Transport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    /// Records messages instead of sending them
+    #[cfg(test)]
+    Stub(AsyncStubTransport),
+}
+
+#[derive(Clone)]
+pub struct EmailSender {
+    transport: Transport,
+    from: String,
+    to: String,
+}
+
+fn smtp_error(e: lettre::transport::smtp::Error) -> BotError {
+    BotError::internal(format!("SMTP error: {e}"))
+}
+
+fn address_error(e: lettre::address::AddressError) -> BotError {
+    BotError::validation(format!("Invalid email address: {e}"))
+}
+
+/// Plain-text version of a Discord-formatted report (drops bold, italics and code marks)
+pub fn strip_markdown(text: &str) -> String {
+    text.replace("**", "").replace("__", "").replace('`', "")
+}
+
+impl EmailSender {
+    pub fn new(config: &SmtpConfig) -> Result<Self> {
+        let builder = if config.port == 465 {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host).map_err(smtp_error)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host).map_err(smtp_error)?
+        };
+        let mut builder = builder.port(config.port);
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        Ok(EmailSender {
+            transport: Transport::Smtp(builder.build()),
+            from: config.from.clone(),
+            to: config.to.clone(),
+        })
+    }
+
+    /// The sender for the `SMTP_*` configuration, or `None` when email isn't configured
+    /// or the configuration is invalid (logged, so reports still go out by DM)
+    pub fn from_config(config: Option<&SmtpConfig>) -> Option<Self> {
+        match config.map(EmailSender::new) {
+            Some(Ok(sender)) => Some(sender),
+            Some(Err(e)) => {
+                warn!("SMTP configuration is invalid, owner reports and alerts won't be emailed: {e}");
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// A sender that records messages in the returned stub instead of sending them
+    #[cfg(test)]
+    pub fn stub() -> (Self, AsyncStubTransport) {
+        let stub = AsyncStubTransport::new_ok();
+        let sender = EmailSender {
+            transport: Transport::Stub(stub.clone()),
+            from: "bot@example.org".to_string(),
+            to: "owner@example.org".to_string(),
+        };
+        (sender, stub)
+    }
+
+    /// Email a Discord-formatted report to the owner as plain text
+    pub async fn send_report(&self, subject: &str, report: &str) -> Result<()> {
+        let email = Message::builder()
+            .from(self.from.parse().map_err(address_error)?)
+            .to(self.to.parse().map_err(address_error)?)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(strip_markdown(report))
+            .map_err(|e| BotError::internal(format!("Couldn't build email: {e}")))?;
+        match &self.transport {
+            Transport::Smtp(transport) => transport.send(email).await.map(drop).map_err(smtp_error),
+            #[cfg(test)]
+            Transport::Stub(stub) => stub.send(email).await.map_err(|e| BotError::internal(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_markdown() {
+        assert_eq!(strip_markdown("**Total due: $1.50** for `chat`"), "Total due: $1.50 for chat");
+    }
+
+    #[test]
+    fn test_from_config_only_with_smtp() {
+        assert!(EmailSender::from_config(None).is_none());
+        let config = SmtpConfig {
+            host: "smtp.example.org".to_string(),
+            port: 587,
+            username: None,
+            password: None,
+            from: "bot@example.org".to_string(),
+            to: "owner@example.org".to_string(),
+        };
+        assert!(EmailSender::from_config(Some(&config)).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_send_report_as_plain_text() {
+        let (sender, stub) = EmailSender::stub();
+        sender.send_report("Weekly digest", "**Total: $1.00**").await.unwrap();
+        let messages = stub.messages().await;
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].1.contains("Subject: Weekly digest"));
+        assert!(messages[0].1.contains("Total: $1.00"));
+    }
+}
@@ -2,20 +2,22 @@
 //!
 //! Tracks DM sessions, engagement metrics, and feature usage with event-driven architecture.
 //!
-//! - **Version**: 1.0.0
+//! - **Version**: 1.1.0
 //! - **Since**: 0.6.0
 //! - **Toggleable**: false
 //!
 //! ## Changelog
+//! - 1.1.0: Injectable clock and session ID source
 //! - 1.0.0: Initial release with async event-driven tracking
 
+use crate::core::clock::{random_ids, system_clock};
+use crate::core::{Clock, IdGen};
 use crate::database::Database;
 use chrono::{DateTime, Duration, Utc};
 use dashmap::DashMap;
 use log::{debug, error, warn};
 use std::sync::Arc;
 use tokio::sync::mpsc;
-use uuid::Uuid;
 
 /// Types of API calls tracked
 #[derive(Debug, Clone)]
@@ -116,8 +118,7 @@ struct SessionState {
 }
 
 impl SessionState {
-    fn new(session_id: String, user_id: String, channel_id: String) -> Self {
-        let now = Utc::now();
+    fn new(session_id: String, user_id: String, channel_id: String, now: DateTime<Utc>) -> Self {
         SessionState {
             session_id,
             user_id,
@@ -133,23 +134,19 @@ impl SessionState {
         }
     }
 
-    fn update_activity(&mut self) {
-        self.last_activity = Utc::now();
-    }
-
-    fn add_user_message(&mut self, chars: usize) {
+    fn add_user_message(&mut self, chars: usize, now: DateTime<Utc>) {
         self.message_count += 1;
         self.user_message_count += 1;
         self.total_user_chars += chars as i32;
-        self.update_activity();
+        self.last_activity = now;
     }
 
-    fn add_bot_message(&mut self, chars: usize, response_time_ms: u64) {
+    fn add_bot_message(&mut self, chars: usize, response_time_ms: u64, now: DateTime<Utc>) {
         self.message_count += 1;
         self.bot_message_count += 1;
         self.total_bot_chars += chars as i32;
         self.response_times.push(response_time_ms);
-        self.update_activity();
+        self.last_activity = now;
     }
 
     fn avg_response_time(&self) -> i32 {
@@ -160,9 +157,9 @@ impl SessionState {
         (sum / self.response_times.len() as u64) as i32
     }
 
-    fn is_timed_out(&self, timeout_minutes: i64) -> bool {
+    fn is_timed_out(&self, timeout_minutes: i64, now: DateTime<Utc>) -> bool {
         let timeout_duration = Duration::minutes(timeout_minutes);
-        Utc::now() - self.last_activity > timeout_duration
+        now - self.last_activity > timeout_duration
     }
 }
 
@@ -171,11 +168,18 @@ impl SessionState {
 pub struct InteractionTracker {
     sender: mpsc::UnboundedSender<TrackingEvent>,
     active_sessions: Arc<DashMap<String, SessionState>>,
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGen>,
 }
 
 impl InteractionTracker {
     /// Create a new InteractionTracker with background processing task
     pub fn new(database: Database) -> Self {
+        Self::with_sources(database, system_clock(), random_ids())
+    }
+
+    /// Like [`InteractionTracker::new`], taking the time and session IDs from `clock` and `ids`
+    pub fn with_sources(database: Database, clock: Arc<dyn Clock>, ids: Arc<dyn IdGen>) -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
         let active_sessions = Arc::new(DashMap::new());
 
@@ -184,6 +188,7 @@ impl InteractionTracker {
             database.clone(),
             receiver,
             active_sessions.clone(),
+            clock.clone(),
         ));
 
         // Spawn session timeout cleanup task
@@ -191,11 +196,14 @@ impl InteractionTracker {
             database,
             active_sessions.clone(),
             sender.clone(),
+            clock.clone(),
         ));
 
         InteractionTracker {
             sender,
             active_sessions,
+            clock,
+            ids,
         }
     }
 
@@ -204,15 +212,16 @@ impl InteractionTracker {
         let key = format!("{}:{}", user_id, channel_id);
 
         // Check if active session exists
+        let now = self.clock.now();
         if let Some(session) = self.active_sessions.get(&key) {
-            if !session.is_timed_out(30) {
+            if !session.is_timed_out(30, now) {
                 return session.session_id.clone();
             }
         }
 
         // Create new session
-        let session_id = Uuid::new_v4().to_string();
-        let session = SessionState::new(session_id.clone(), user_id.to_string(), channel_id.to_string());
+        let session_id = self.ids.new_id().to_string();
+        let session = SessionState::new(session_id.clone(), user_id.to_string(), channel_id.to_string(), now);
         self.active_sessions.insert(key, session);
 
         // Emit session start event
@@ -341,11 +350,12 @@ impl InteractionTracker {
         database: Database,
         mut receiver: mpsc::UnboundedReceiver<TrackingEvent>,
         active_sessions: Arc<DashMap<String, SessionState>>,
+        clock: Arc<dyn Clock>,
     ) {
         debug!("InteractionTracker event processor started");
 
         while let Some(event) = receiver.recv().await {
-            if let Err(e) = Self::process_event(&database, &active_sessions, event).await {
+            if let Err(e) = Self::process_event(&database, &active_sessions, clock.now(), event).await {
                 error!("Failed to process tracking event: {e}");
             }
         }
@@ -357,8 +367,9 @@ impl InteractionTracker {
     async fn process_event(
         database: &Database,
         active_sessions: &DashMap<String, SessionState>,
+        now: DateTime<Utc>,
         event: TrackingEvent,
-    ) -> anyhow::Result<()> {
+    ) -> crate::core::Result<()> {
         match event {
             TrackingEvent::SessionStart {
                 session_id,
@@ -408,7 +419,7 @@ impl InteractionTracker {
                 // Update active session state
                 let key = format!("{}:{}", user_id, channel_id);
                 if let Some(mut session) = active_sessions.get_mut(&key) {
-                    session.add_user_message(character_count);
+                    session.add_user_message(character_count, now);
                 }
 
                 // Log event
@@ -427,7 +438,7 @@ impl InteractionTracker {
                 // Update active session state
                 let key = format!("{}:{}", user_id, channel_id);
                 if let Some(mut session) = active_sessions.get_mut(&key) {
-                    session.add_bot_message(character_count, response_time_ms);
+                    session.add_bot_message(character_count, response_time_ms, now);
                 }
 
                 // Log event
@@ -482,6 +493,7 @@ impl InteractionTracker {
         _database: Database,
         active_sessions: Arc<DashMap<String, SessionState>>,
         sender: mpsc::UnboundedSender<TrackingEvent>,
+        clock: Arc<dyn Clock>,
     ) {
         debug!("InteractionTracker cleanup task started");
 
@@ -489,10 +501,11 @@ impl InteractionTracker {
             tokio::time::sleep(tokio::time::Duration::from_secs(300)).await; // Run every 5 minutes
 
             let mut timed_out_sessions = Vec::new();
+            let now = clock.now();
 
             // Find timed out sessions
             for entry in active_sessions.iter() {
-                if entry.value().is_timed_out(30) {
+                if entry.value().is_timed_out(30, now) {
                     timed_out_sessions.push(entry.value().session_id.clone());
                 }
             }
@@ -510,3 +523,24 @@ impl InteractionTracker {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::{FakeClock, SequentialIds};
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn test_session_times_out_after_inactivity() {
+        let database = Database::new(":memory:", 1).await.unwrap();
+        let clock = Arc::new(FakeClock::new(Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()));
+        let tracker = InteractionTracker::with_sources(database, clock.clone(), Arc::new(SequentialIds::default()));
+
+        let first = tracker.get_or_create_session("1", "2");
+        assert_eq!(first, uuid::Uuid::from_u128(1).to_string());
+        clock.advance(Duration::minutes(30));
+        assert_eq!(tracker.get_or_create_session("1", "2"), first);
+        clock.advance(Duration::minutes(1));
+        assert_eq!(tracker.get_or_create_session("1", "2"), uuid::Uuid::from_u128(2).to_string());
+    }
+}
@@ -1,18 +1,33 @@
 //! # Analytics Feature
 //!
-//! Usage tracking, interaction analytics, and system metrics.
+//! Usage tracking, cost reports with email delivery, Google Sheets and S3 exports, operator overview,
+//! activity heatmaps, interaction analytics, system metrics, prompt debugging, and spend summaries.
 //!
-//! - **Version**: 1.0.0
+//! - **Version**: 1.9.0
 //! - **Since**: 0.5.0
 //! - **Toggleable**: false
 
+pub mod activity_heatmap;
+pub mod cost_report;
+pub mod email_delivery;
 pub mod interaction_tracker;
+pub mod ops_overview;
+pub mod prompt_debug;
+pub mod s3_export;
+pub mod sheets_export;
+pub mod spend_summary;
 pub mod system_info;
 pub mod usage_tracker;
 
+pub use activity_heatmap::{format_activity_summary, render_heatmap_png, HeatmapGrid, ACTIVITY_WINDOW_DAYS};
+pub use cost_report::{monthly_invoice_loop, weekly_digest_loop};
+pub use email_delivery::EmailSender;
 pub use interaction_tracker::InteractionTracker;
+pub use prompt_debug::{PromptDebugLog, PromptDebugRecord};
+pub use s3_export::{s3_export_loop, S3Client};
+pub use sheets_export::{parse_sheet_id, sheets_export_loop, SheetsClient};
 pub use system_info::{
-    metrics_collection_loop, format_bytes, format_bytes_signed, format_duration,
-    format_history, get_db_file_size, CurrentMetrics, DiskInfo, HistoricalSummary,
+    gateway_latency, metrics_collection_loop, format_bytes, format_bytes_signed, format_duration,
+    format_history, get_db_file_size, shard_latencies, CurrentMetrics, DiskInfo, HistoricalSummary, ShardSlot,
 };
 pub use usage_tracker::UsageTracker;
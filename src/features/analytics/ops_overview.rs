@@ -0,0 +1,211 @@
+//! # Feature: Operator Overview
+//!
+//! Multi-guild overview for the bot owner (`/ops overview`): member counts,
+//! 30-day AI cost, command volume, error counts, and last activity per guild,
+//! paginated and sortable.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with sortable, paginated guild table
+
+use crate::database::GuildOverview;
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// Window used for cost, command, and error totals
+pub const OVERVIEW_DAYS: i64 = 30;
+
+/// Guilds shown per page
+pub const OVERVIEW_PAGE_SIZE: usize = 10;
+
+/// Custom ID prefix for the overview's page buttons
+pub const OPS_PAGE_PREFIX: &str = "opspage_";
+
+/// Column the overview is sorted by (always descending)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverviewSort {
+    Cost,
+    Commands,
+    Errors,
+    Members,
+    Activity,
+}
+
+impl OverviewSort {
+    pub const ALL: &'static [OverviewSort] = &[
+        OverviewSort::Cost,
+        OverviewSort::Commands,
+        OverviewSort::Errors,
+        OverviewSort::Members,
+        OverviewSort::Activity,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|s| s.as_str() == value)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverviewSort::Cost => "cost",
+            OverviewSort::Commands => "commands",
+            OverviewSort::Errors => "errors",
+            OverviewSort::Members => "members",
+            OverviewSort::Activity => "activity",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OverviewSort::Cost => "30-day cost",
+            OverviewSort::Commands => "command volume",
+            OverviewSort::Errors => "error count",
+            OverviewSort::Members => "member count",
+            OverviewSort::Activity => "last activity",
+        }
+    }
+}
+
+/// Sort rows in place, highest first; ties fall back to guild name
+pub fn sort_overview(rows: &mut [GuildOverview], sort: OverviewSort) {
+    rows.sort_by(|a, b| {
+        let order = match sort {
+            OverviewSort::Cost => b.cost_usd.total_cmp(&a.cost_usd),
+            OverviewSort::Commands => b.commands.cmp(&a.commands),
+            OverviewSort::Errors => b.errors.cmp(&a.errors),
+            OverviewSort::Members => b.member_count.cmp(&a.member_count),
+            // Timestamps are `YYYY-MM-DD HH:MM:SS`, so string order is chronological
+            OverviewSort::Activity => b.last_activity.cmp(&a.last_activity),
+        };
+        order.then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+}
+
+/// Number of pages needed for `total` rows (at least one)
+pub fn page_count(total: usize) -> usize {
+    total.div_ceil(OVERVIEW_PAGE_SIZE).max(1)
+}
+
+/// Custom ID for a page button
+pub fn page_custom_id(sort: OverviewSort, page: usize) -> String {
+    format!("{OPS_PAGE_PREFIX}{}_{page}", sort.as_str())
+}
+
+/// Parse a page button custom ID into (sort, zero-based page)
+pub fn parse_page_custom_id(custom_id: &str) -> Option<(OverviewSort, usize)> {
+    let rest = custom_id.strip_prefix(OPS_PAGE_PREFIX)?;
+    let (sort, page) = rest.rsplit_once('_')?;
+    Some((OverviewSort::parse(sort)?, page.parse().ok()?))
+}
+
+/// Render one page of the overview; `page` is zero-based and clamped to the last page
+pub fn format_overview_page(rows: &[GuildOverview], sort: OverviewSort, page: usize) -> String {
+    let pages = page_count(rows.len());
+    let page = page.min(pages - 1);
+
+    let total_cost: f64 = rows.iter().map(|r| r.cost_usd).sum();
+    let total_commands: i64 = rows.iter().map(|r| r.commands).sum();
+    let total_errors: i64 = rows.iter().map(|r| r.errors).sum();
+
+    let mut out = vec![format!(
+        "**🛰️ Guild overview** — {} guilds, sorted by {}\n\
+        Last {OVERVIEW_DAYS} days: ${total_cost:.2} cost, {total_commands} commands, {total_errors} errors",
+        rows.len(),
+        sort.label()
+    )];
+
+    if rows.is_empty() {
+        out.push("\nNo guilds recorded yet.".to_string());
+        return out.join("\n");
+    }
+
+    out.push(String::new());
+    for (i, row) in rows.iter().enumerate().skip(page * OVERVIEW_PAGE_SIZE).take(OVERVIEW_PAGE_SIZE) {
+        let members = row
+            .member_count
+            .map(|m| m.to_string())
+            .unwrap_or_else(|| "?".to_string());
+        let last = row
+            .last_activity
+            .as_deref()
+            .and_then(|ts| NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S").ok())
+            .map(|ts| format!("<t:{}:R>", DateTime::<Utc>::from_naive_utc_and_offset(ts, Utc).timestamp()))
+            .unwrap_or_else(|| "never".to_string());
+        out.push(format!(
+            "**{}. {}** (`{}`)\n👥 {} · 💰 ${:.2} · ⌨️ {} cmds · ⚠️ {} errors · 🕒 {}",
+            i + 1,
+            row.name,
+            row.guild_id,
+            members,
+            row.cost_usd,
+            row.commands,
+            row.errors,
+            last
+        ));
+    }
+
+    out.push(format!("\nPage {}/{}", page + 1, pages));
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(name: &str, members: Option<i64>, cost: f64, commands: i64, last: Option<&str>) -> GuildOverview {
+        GuildOverview {
+            guild_id: format!("{}", name.len()),
+            name: name.to_string(),
+            member_count: members,
+            cost_usd: cost,
+            commands,
+            errors: 0,
+            last_activity: last.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_sort_parse_and_custom_id_round_trip() {
+        for sort in OverviewSort::ALL {
+            assert_eq!(OverviewSort::parse(sort.as_str()), Some(*sort));
+            assert_eq!(parse_page_custom_id(&page_custom_id(*sort, 3)), Some((*sort, 3)));
+        }
+        assert_eq!(OverviewSort::parse("name"), None);
+        assert_eq!(parse_page_custom_id("page_next"), None);
+    }
+
+    #[test]
+    fn test_sort_overview() {
+        let mut rows = vec![
+            row("alpha", Some(10), 1.0, 5, Some("2026-10-01 10:00:00")),
+            row("beta", Some(500), 0.5, 50, None),
+            row("gamma", None, 3.0, 1, Some("2026-10-14 09:00:00")),
+        ];
+        sort_overview(&mut rows, OverviewSort::Cost);
+        assert_eq!(rows[0].name, "gamma");
+        sort_overview(&mut rows, OverviewSort::Members);
+        assert_eq!(rows[0].name, "beta");
+        assert_eq!(rows[2].name, "gamma");
+        sort_overview(&mut rows, OverviewSort::Activity);
+        assert_eq!(rows[0].name, "gamma");
+        assert_eq!(rows[2].name, "beta");
+    }
+
+    #[test]
+    fn test_format_overview_page_paginates() {
+        let rows: Vec<_> = (0..25).map(|i| row(&format!("guild{i:02}"), Some(i), 0.1, 1, None)).collect();
+        assert_eq!(page_count(rows.len()), 3);
+        assert_eq!(page_count(0), 1);
+
+        let page = format_overview_page(&rows, OverviewSort::Cost, 2);
+        assert!(page.contains("**21. guild20**"));
+        assert!(!page.contains("guild19"));
+        assert!(page.contains("Page 3/3"));
+        assert!(page.contains("25 guilds"));
+
+        // Out-of-range pages clamp to the last page
+        assert!(format_overview_page(&rows, OverviewSort::Cost, 9).contains("Page 3/3"));
+        assert!(format_overview_page(&[], OverviewSort::Cost, 0).contains("No guilds recorded"));
+    }
+}
@@ -0,0 +1,161 @@
+//! # Feature: Prompt Debugging
+//!
+//! Keeps the most recently assembled AI prompt per channel in memory so admins can
+//! inspect exactly what was sent to the model with `/debug_last`. Records expire
+//! after a short TTL and are never written to the database.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with per-channel last-prompt capture and text reports
+
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use std::sync::Arc;
+
+/// How long a captured prompt stays available
+pub const PROMPT_DEBUG_TTL_MINUTES: i64 = 30;
+
+/// Everything that went into (and came out of) a single AI request
+#[derive(Debug, Clone)]
+pub struct PromptDebugRecord {
+    pub request_id: String,
+    pub model: String,
+    pub system_prompt: String,
+    /// Included conversation history as (role, content), oldest first
+    pub history: Vec<(String, String)>,
+    pub user_message: String,
+    pub temperature: Option<f32>,
+    pub prompt_tokens: Option<u32>,
+    pub completion_tokens: Option<u32>,
+    pub total_tokens: Option<u32>,
+    pub latency_ms: u64,
+    pub captured_at: DateTime<Utc>,
+}
+
+impl PromptDebugRecord {
+    /// Short summary suitable for a Discord message
+    pub fn summary(&self) -> String {
+        let tokens = match (self.prompt_tokens, self.completion_tokens, self.total_tokens) {
+            (Some(p), Some(c), Some(t)) => format!("{p} prompt + {c} completion = {t} total"),
+            _ => "unavailable".to_string(),
+        };
+        let temperature = self
+            .temperature
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| "model default".to_string());
+        format!(
+            "**🔍 Last AI request in this channel**\n\
+            • Request: `{}`\n\
+            • Captured: <t:{}:R>\n\
+            • Model: `{}` (temperature: {})\n\
+            • Latency: {} ms\n\
+            • Tokens: {}\n\
+            • System prompt: {} chars\n\
+            • History messages: {}\n\
+            • User message: {} chars\n\n\
+            Full prompt attached.",
+            self.request_id,
+            self.captured_at.timestamp(),
+            self.model,
+            temperature,
+            self.latency_ms,
+            tokens,
+            self.system_prompt.chars().count(),
+            self.history.len(),
+            self.user_message.chars().count(),
+        )
+    }
+
+    /// Full plain-text dump of the assembled prompt
+    pub fn full_report(&self) -> String {
+        let mut report = format!(
+            "Request: {}\nModel: {}\nCaptured: {}\n\n=== SYSTEM ===\n{}\n",
+            self.request_id,
+            self.model,
+            self.captured_at.to_rfc3339(),
+            self.system_prompt
+        );
+        for (i, (role, content)) in self.history.iter().enumerate() {
+            report.push_str(&format!("\n=== HISTORY {} ({}) ===\n{}\n", i + 1, role.to_uppercase(), content));
+        }
+        report.push_str(&format!("\n=== USER ===\n{}\n", self.user_message));
+        report
+    }
+}
+
+/// Transient per-channel store of the last prompt; cheap to clone and share
+#[derive(Clone, Default)]
+pub struct PromptDebugLog {
+    records: Arc<DashMap<String, PromptDebugRecord>>,
+}
+
+impl PromptDebugLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the channel's last prompt, dropping any expired records
+    pub fn record(&self, channel_id: &str, record: PromptDebugRecord) {
+        let cutoff = record.captured_at - Duration::minutes(PROMPT_DEBUG_TTL_MINUTES);
+        self.records.retain(|_, r| r.captured_at > cutoff);
+        self.records.insert(channel_id.to_string(), record);
+    }
+
+    /// Get the channel's last prompt if it has not expired
+    pub fn latest(&self, channel_id: &str, now: DateTime<Utc>) -> Option<PromptDebugRecord> {
+        let cutoff = now - Duration::minutes(PROMPT_DEBUG_TTL_MINUTES);
+        self.records
+            .get(channel_id)
+            .filter(|r| r.captured_at > cutoff)
+            .map(|r| r.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_at(captured_at: DateTime<Utc>) -> PromptDebugRecord {
+        PromptDebugRecord {
+            request_id: "req-1".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            system_prompt: "You are helpful.".to_string(),
+            history: vec![("user".to_string(), "hi".to_string())],
+            user_message: "what now?".to_string(),
+            temperature: None,
+            prompt_tokens: Some(10),
+            completion_tokens: Some(5),
+            total_tokens: Some(15),
+            latency_ms: 420,
+            captured_at,
+        }
+    }
+
+    #[test]
+    fn test_latest_respects_ttl() {
+        let log = PromptDebugLog::new();
+        let now = Utc::now();
+        log.record("c1", record_at(now - Duration::minutes(PROMPT_DEBUG_TTL_MINUTES + 1)));
+        assert!(log.latest("c1", now).is_none());
+
+        log.record("c1", record_at(now));
+        assert_eq!(log.latest("c1", now).unwrap().request_id, "req-1");
+        assert!(log.latest("c2", now).is_none());
+    }
+
+    #[test]
+    fn test_reports_include_prompt_parts() {
+        let record = record_at(Utc::now());
+        let summary = record.summary();
+        assert!(summary.contains("10 prompt + 5 completion = 15 total"));
+        assert!(summary.contains("model default"));
+
+        let report = record.full_report();
+        assert!(report.contains("=== SYSTEM ===\nYou are helpful."));
+        assert!(report.contains("=== HISTORY 1 (USER) ===\nhi"));
+        assert!(report.ends_with("=== USER ===\nwhat now?\n"));
+    }
+}
@@ -0,0 +1,298 @@
+//! # Feature: Google Sheets Export
+//!
+//! Appends one row of daily analytics per guild (members, commands, errors, AI
+//! usage and cost) to the Google Sheet in the guild's `analytics_sheet_id`
+//! setting, authenticating as the service account in
+//! `GOOGLE_SERVICE_ACCOUNT_FILE`. The sheet must be shared with the service
+//! account's email. Missed days are backfilled (up to a month) after downtime.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with daily per-guild rows and a header on first export
+
+use crate::database::{Database, GuildDailyStats};
+use crate::core::{BotError, Result};
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const SHEETS_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+
+/// `bot_settings` key prefix recording the last exported date per guild
+const LAST_EXPORT_PREFIX: &str = "sheets_last_export:";
+
+/// Oldest missed day backfilled after downtime
+const MAX_BACKFILL_DAYS: i64 = 30;
+
+/// Column headers, written above the first exported row
+pub const SHEET_HEADER: &[&str] = &[
+    "Date",
+    "Members",
+    "New members",
+    "First-time posters",
+    "Commands",
+    "Errors",
+    "AI requests",
+    "AI tokens",
+    "AI cost (USD)",
+];
+
+/// The fields used from a Google service account key file
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServiceAccount {
+    pub client_email: String,
+    pub private_key: String,
+    #[serde(default = "default_token_uri")]
+    pub token_uri: String,
+}
+
+fn jwt_error(e: jsonwebtoken::errors::Error) -> BotError {
+    BotError::internal(format!("Couldn't sign the Google token request: {e}"))
+}
+
+fn default_token_uri() -> String {
+    "https://oauth2.googleapis.com/token".to_string()
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+/// Spreadsheet ID from a bare ID or a `docs.google.com/spreadsheets/d/<id>/...` URL
+pub fn parse_sheet_id(input: &str) -> Option<String> {
+    let input = input.trim();
+    let id = match input.split_once("/spreadsheets/d/") {
+        Some((_, rest)) => rest.split(['/', '?', '#']).next().unwrap_or_default(),
+        None => input,
+    };
+    let valid = id.len() >= 20 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| id.to_string())
+}
+
+/// Sheet row for a day's stats, in `SHEET_HEADER` order
+pub fn stats_row(stats: &GuildDailyStats) -> Vec<Value> {
+    vec![
+        json!(stats.date),
+        stats.member_count.map_or(json!(""), |count| json!(count)),
+        json!(stats.new_members),
+        json!(stats.first_time_posters),
+        json!(stats.commands),
+        json!(stats.errors),
+        json!(stats.ai_requests),
+        json!(stats.ai_tokens),
+        json!((stats.ai_cost_usd * 10000.0).round() / 10000.0),
+    ]
+}
+
+/// Days to export: the day after `last_export` (or yesterday on first run) through
+/// yesterday, never more than `MAX_BACKFILL_DAYS` back
+pub fn dates_to_export(last_export: Option<&str>, today: NaiveDate) -> Vec<NaiveDate> {
+    let yesterday = today - ChronoDuration::days(1);
+    let earliest = today - ChronoDuration::days(MAX_BACKFILL_DAYS);
+    let start = last_export
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .map(|d| d + ChronoDuration::days(1))
+        .unwrap_or(yesterday)
+        .max(earliest);
+    start.iter_days().take_while(|d| *d <= yesterday).collect()
+}
+
+#[derive(Clone)]
+pub struct SheetsClient {
+    account: ServiceAccount,
+    client: reqwest::Client,
+    /// Access token and when it stops being usable
+    token: Arc<Mutex<Option<(String, Instant)>>>,
+}
+
+impl SheetsClient {
+    /// Load the service account key file
+    pub fn from_file(path: &str) -> Result<Self> {
+        let account: ServiceAccount = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        Ok(SheetsClient {
+            account,
+            client: reqwest::Client::new(),
+            token: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    pub fn service_account_email(&self) -> &str {
+        &self.account.client_email
+    }
+
+    /// OAuth access token for the Sheets scope, refreshed shortly before it expires
+    async fn access_token(&self) -> Result<String> {
+        if let Some((token, expires)) = self.token.lock().unwrap().clone() {
+            if Instant::now() < expires {
+                return Ok(token);
+            }
+        }
+
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            iss: &self.account.client_email,
+            scope: SHEETS_SCOPE,
+            aud: &self.account.token_uri,
+            iat: now,
+            exp: now + 3600,
+        };
+        let key = EncodingKey::from_rsa_pem(self.account.private_key.as_bytes()).map_err(jwt_error)?;
+        let assertion = jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &key).map_err(jwt_error)?;
+
+        let response = self
+            .client
+            .post(&self.account.token_uri)
+            .form(&[("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"), ("assertion", assertion.as_str())])
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(BotError::internal(format!("Google token request failed (status {})", status)));
+        }
+        let body: Value = response.json().await?;
+        let token = body
+            .get("access_token")
+            .and_then(Value::as_str)
+            .ok_or_else(|| BotError::internal("No access_token in Google token response"))?
+            .to_string();
+        let lifetime = body.get("expires_in").and_then(Value::as_u64).unwrap_or(3600);
+        let expires = Instant::now() + Duration::from_secs(lifetime.saturating_sub(60));
+        *self.token.lock().unwrap() = Some((token.clone(), expires));
+        Ok(token)
+    }
+
+    /// Append rows after the last filled row of the first sheet
+    pub async fn append_rows(&self, sheet_id: &str, rows: Vec<Vec<Value>>) -> Result<()> {
+        let token = self.access_token().await?;
+        let response = self
+            .client
+            .post(format!(
+                "https://sheets.googleapis.com/v4/spreadsheets/{sheet_id}/values/A1:append"
+            ))
+            .bearer_auth(token)
+            .query(&[("valueInputOption", "USER_ENTERED"), ("insertDataOption", "INSERT_ROWS")])
+            .json(&json!({ "values": rows }))
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(BotError::internal(format!("Sheets append failed (status {})", status)));
+        }
+        Ok(())
+    }
+}
+
+/// Export any days not yet in a guild's sheet
+async fn export_guild(db: &Database, sheets: &SheetsClient, guild_id: &str, sheet_id: &str) -> Result<()> {
+    let key = format!("{LAST_EXPORT_PREFIX}{guild_id}");
+    let last_export = db.get_bot_setting(&key).await?;
+    let dates = dates_to_export(last_export.as_deref(), Utc::now().date_naive());
+    let Some(last) = dates.last() else {
+        return Ok(());
+    };
+
+    let mut rows = Vec::new();
+    if last_export.is_none() {
+        rows.push(SHEET_HEADER.iter().map(|h| json!(h)).collect());
+    }
+    for date in &dates {
+        let stats = db.get_guild_daily_stats(guild_id, &date.format("%Y-%m-%d").to_string()).await?;
+        rows.push(stats_row(&stats));
+    }
+
+    sheets.append_rows(sheet_id, rows).await?;
+    db.set_bot_setting(&key, &last.format("%Y-%m-%d").to_string()).await?;
+    info!("Exported {} day(s) of analytics for guild {} to Google Sheets", dates.len(), guild_id);
+    Ok(())
+}
+
+/// Background task that appends yesterday's analytics to each configured guild sheet
+pub async fn sheets_export_loop(db: Arc<Database>, sheets: SheetsClient) {
+    let mut interval = tokio::time::interval(Duration::from_secs(3600));
+    info!(
+        "Google Sheets export task started (checks hourly, service account {})",
+        sheets.service_account_email()
+    );
+
+    loop {
+        interval.tick().await;
+        let guilds = match db.get_guilds_with_settings().await {
+            Ok(guilds) => guilds,
+            Err(e) => {
+                warn!("Failed to load guilds for Sheets export: {}", e);
+                continue;
+            }
+        };
+
+        for guild_id in guilds {
+            let sheet_id = match db.get_guild_setting(&guild_id, "analytics_sheet_id").await {
+                Ok(Some(value)) => match parse_sheet_id(&value) {
+                    Some(id) => id,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if let Err(e) = export_guild(&db, &sheets, &guild_id, &sheet_id).await {
+                warn!("Failed to export analytics for guild {} to Google Sheets: {}", guild_id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sheet_id() {
+        let id = "1AbCdEfGhIjKlMnOpQrStUvWxYz0123456789_-";
+        assert_eq!(parse_sheet_id(id).as_deref(), Some(id));
+        assert_eq!(
+            parse_sheet_id(&format!("https://docs.google.com/spreadsheets/d/{id}/edit#gid=0")).as_deref(),
+            Some(id)
+        );
+        assert_eq!(parse_sheet_id("short"), None);
+        assert_eq!(parse_sheet_id("not a sheet id with spaces!!"), None);
+    }
+
+    #[test]
+    fn test_dates_to_export() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        assert_eq!(dates_to_export(None, today), vec![day(9)]);
+        assert_eq!(dates_to_export(Some("2026-03-07"), today), vec![day(8), day(9)]);
+        assert!(dates_to_export(Some("2026-03-09"), today).is_empty());
+        assert_eq!(dates_to_export(Some("2025-01-01"), today).len(), MAX_BACKFILL_DAYS as usize);
+    }
+
+    #[test]
+    fn test_stats_row_matches_header() {
+        let stats = GuildDailyStats {
+            date: "2026-03-09".into(),
+            member_count: None,
+            new_members: 2,
+            first_time_posters: 1,
+            commands: 40,
+            errors: 1,
+            ai_requests: 30,
+            ai_tokens: 12000,
+            ai_cost_usd: 0.123456,
+        };
+        let row = stats_row(&stats);
+        assert_eq!(row.len(), SHEET_HEADER.len());
+        assert_eq!(row[1], json!(""));
+        assert_eq!(row[8], json!(0.1235));
+    }
+}
@@ -0,0 +1,210 @@
+//! # Feature: Spend Summaries
+//!
+//! Renders OpenAI usage from `openai_usage_daily` for `/usage` (the caller's own
+//! spend) and `/costs server` (a guild's spend with a top-spenders leaderboard):
+//! one bar per service type, scaled to the most expensive one, plus totals.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with per-service bars, totals and leaderboard
+
+/// Width of a full bar, in blocks
+pub const BAR_WIDTH: usize = 12;
+
+/// Users listed in the top-spenders leaderboard
+pub const TOP_SPENDERS: i64 = 10;
+
+/// Time range offered by `/usage` and `/costs server`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageRange {
+    Today,
+    Week,
+    Month,
+}
+
+impl UsageRange {
+    pub const ALL: &'static [UsageRange] = &[UsageRange::Today, UsageRange::Week, UsageRange::Month];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|r| r.as_str() == value)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageRange::Today => "today",
+            UsageRange::Week => "7d",
+            UsageRange::Month => "30d",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            UsageRange::Today => "Today",
+            UsageRange::Week => "Last 7 days",
+            UsageRange::Month => "Last 30 days",
+        }
+    }
+
+    /// Days passed to the usage queries
+    pub fn days(&self) -> i64 {
+        match self {
+            UsageRange::Today => 1,
+            UsageRange::Week => 7,
+            UsageRange::Month => 30,
+        }
+    }
+}
+
+/// Display name of a service type
+pub fn service_label(service_type: &str) -> String {
+    match service_type {
+        "chat" => "Chat (GPT)".to_string(),
+        "whisper" => "Audio (Whisper)".to_string(),
+        "dalle" => "Images (DALL-E)".to_string(),
+        other => match other.strip_prefix("tool_") {
+            Some(tool) => format!("Tool: {tool}"),
+            None => other.to_string(),
+        },
+    }
+}
+
+/// A bar of `width` blocks, filled in proportion to `value / max`; any non-zero value shows at least one block
+pub fn spend_bar(value: f64, max: f64, width: usize) -> String {
+    let filled = if max <= 0.0 || value <= 0.0 {
+        0
+    } else {
+        ((value / max * width as f64).round() as usize).clamp(1, width)
+    };
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+/// One line per service type, most expensive first, from
+/// `(service_type, requests, tokens, audio_seconds, images, cost)` rows
+pub fn format_service_breakdown(stats: &[(String, i64, i64, f64, i64, f64)]) -> String {
+    if stats.is_empty() {
+        return "No usage recorded for this period.".to_string();
+    }
+
+    let mut rows: Vec<_> = stats.iter().collect();
+    rows.sort_by(|a, b| b.5.total_cmp(&a.5).then_with(|| a.0.cmp(&b.0)));
+    let max = rows.first().map(|row| row.5).unwrap_or(0.0);
+
+    rows.iter()
+        .map(|(service_type, requests, tokens, audio_secs, images, cost)| {
+            let detail = match service_type.as_str() {
+                "chat" => format!("{requests} requests, {tokens} tokens"),
+                "whisper" => format!("{requests} requests, {:.1} minutes", audio_secs / 60.0),
+                "dalle" => format!("{requests} requests, {images} images"),
+                _ => format!("{requests} requests"),
+            };
+            format!(
+                "**{}** — ${cost:.4}\n`{}` {detail}",
+                service_label(service_type),
+                spend_bar(*cost, max, BAR_WIDTH)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Totals across service types: requests and cost, then tokens, minutes and images where non-zero
+pub fn format_totals(stats: &[(String, i64, i64, f64, i64, f64)]) -> String {
+    let requests: i64 = stats.iter().map(|row| row.1).sum();
+    let cost: f64 = stats.iter().map(|row| row.5).sum();
+    let tokens: i64 = stats.iter().filter(|row| row.0 == "chat").map(|row| row.2).sum();
+    let audio_secs: f64 = stats.iter().filter(|row| row.0 == "whisper").map(|row| row.3).sum();
+    let images: i64 = stats.iter().filter(|row| row.0 == "dalle").map(|row| row.4).sum();
+
+    let mut lines = vec![format!("{requests} requests, ${cost:.4} estimated cost")];
+    if tokens > 0 {
+        lines.push(format!("📝 {tokens} tokens"));
+    }
+    if audio_secs > 0.0 {
+        lines.push(format!("🎤 {:.1} minutes transcribed", audio_secs / 60.0));
+    }
+    if images > 0 {
+        lines.push(format!("🎨 {images} images generated"));
+    }
+    lines.join("\n")
+}
+
+/// Leaderboard of `(user_id, requests, cost)` rows, already ordered by cost, with bars scaled to the top spender
+pub fn format_top_spenders(top: &[(String, i64, f64)], name: &dyn Fn(&str) -> String) -> String {
+    let max = top.first().map(|row| row.2).unwrap_or(0.0);
+    top.iter()
+        .enumerate()
+        .map(|(i, (user_id, requests, cost))| {
+            let rank = match i {
+                0 => "🥇".to_string(),
+                1 => "🥈".to_string(),
+                2 => "🥉".to_string(),
+                _ => format!("{}.", i + 1),
+            };
+            format!("{rank} {} — ${cost:.4} ({requests} requests)\n`{}`", name(user_id), spend_bar(*cost, max, BAR_WIDTH))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(service: &str, requests: i64, cost: f64) -> (String, i64, i64, f64, i64, f64) {
+        (service.to_string(), requests, requests * 100, 90.0, 2, cost)
+    }
+
+    #[test]
+    fn test_range_parse() {
+        for range in UsageRange::ALL {
+            assert_eq!(UsageRange::parse(range.as_str()), Some(*range));
+        }
+        assert_eq!(UsageRange::parse("1y"), None);
+        assert_eq!(UsageRange::Week.days(), 7);
+    }
+
+    #[test]
+    fn test_spend_bar() {
+        assert_eq!(spend_bar(1.0, 1.0, 4), "████");
+        assert_eq!(spend_bar(0.5, 1.0, 4), "██░░");
+        assert_eq!(spend_bar(0.001, 1.0, 4), "█░░░");
+        assert_eq!(spend_bar(0.0, 1.0, 4), "░░░░");
+        assert_eq!(spend_bar(0.0, 0.0, 4), "░░░░");
+    }
+
+    #[test]
+    fn test_service_breakdown_sorted_by_cost() {
+        let stats = vec![stat("whisper", 2, 0.01), stat("chat", 10, 0.04), stat("tool_calculator", 3, 0.0)];
+        let text = format_service_breakdown(&stats);
+        let chat = text.find("Chat (GPT)").unwrap();
+        let audio = text.find("Audio (Whisper)").unwrap();
+        assert!(chat < audio);
+        assert!(text.contains("Tool: calculator"));
+        assert!(text.contains(&format!("`{}` 10 requests, 1000 tokens", "█".repeat(BAR_WIDTH))));
+        assert!(text.contains("2 requests, 1.5 minutes"));
+
+        assert_eq!(format_service_breakdown(&[]), "No usage recorded for this period.");
+    }
+
+    #[test]
+    fn test_totals_only_count_their_service() {
+        let stats = vec![stat("chat", 10, 0.04), stat("whisper", 2, 0.01)];
+        let totals = format_totals(&stats);
+        assert!(totals.starts_with("12 requests, $0.0500"));
+        assert!(totals.contains("1000 tokens"));
+        assert!(totals.contains("1.5 minutes"));
+        assert!(!totals.contains("images"));
+    }
+
+    #[test]
+    fn test_top_spenders() {
+        let top = vec![("1".to_string(), 5, 0.2), ("2".to_string(), 3, 0.1), ("3".to_string(), 1, 0.05), ("4".to_string(), 1, 0.01)];
+        let text = format_top_spenders(&top, &|id| format!("user{id}"));
+        assert!(text.starts_with("🥇 user1 — $0.2000 (5 requests)"));
+        assert!(text.contains("4. user4"));
+        assert!(text.contains(&format!("`{}{}`", "█".repeat(6), "░".repeat(6))));
+    }
+}
@@ -2,11 +2,14 @@
 //!
 //! System diagnostics and historical metrics tracking for the /sysinfo command.
 //!
-//! - **Version**: 1.1.0
+//! - **Version**: 1.4.0
 //! - **Since**: 0.3.0
 //! - **Toggleable**: false
 //!
 //! ## Changelog
+//! - 1.4.0: Heartbeat latency per shard fed to the Prometheus exporter
+//! - 1.3.0: Gateway latency and reminder queue depth recorded, and fed to the Prometheus exporter
+//! - 1.2.0: Metrics tagged per bot, with a per-bot breakdown for bots sharing a database
 //! - 1.1.0: Added OpenAI usage data cleanup integration
 //! - 1.0.0: Initial implementation with current metrics and historical tracking
 
@@ -15,7 +18,16 @@ use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 use log::{info, warn, debug};
+use serenity::client::bridge::gateway::ShardManager;
+use serenity::http::Http;
+use tokio::sync::Mutex;
 use crate::database::Database;
+use crate::features::community_insights::MEMBER_ACTIVITY_RETENTION_DAYS;
+use crate::features::emoji_stats::EMOJI_STATS_RETENTION_DAYS;
+use crate::features::prometheus::Metrics;
+
+/// Shard manager of the current gateway connection, replaced on each restart
+pub type ShardSlot = Arc<Mutex<Option<Arc<Mutex<ShardManager>>>>>;
 
 /// Information about a disk/mount point
 pub struct DiskInfo {
@@ -51,7 +63,7 @@ impl CurrentMetrics {
         let load = System::load_average();
 
         // Get bot process memory
-        let bot_memory = if let Some(pid) = sysinfo::get_current_pid().ok() {
+        let bot_memory = if let Ok(pid) = sysinfo::get_current_pid() {
             sys.process(pid).map(|p| p.memory()).unwrap_or(0)
         } else {
             0
@@ -79,7 +91,7 @@ impl CurrentMetrics {
         CurrentMetrics {
             hostname: System::host_name().unwrap_or_else(|| "unknown".to_string()),
             os_name: System::name().unwrap_or_else(|| "unknown".to_string()),
-            os_version: System::os_version().unwrap_or_else(|| "".to_string()),
+            os_version: System::os_version().unwrap_or_default(),
             kernel: System::kernel_version().unwrap_or_else(|| "unknown".to_string()),
             architecture: std::env::consts::ARCH.to_string(),
             cpu_usage: sys.global_cpu_usage(),
@@ -273,6 +285,60 @@ pub fn format_history(
     output
 }
 
+/// One bot's recorded metrics over a period, for the per-bot breakdown
+pub struct BotMetricsSummary {
+    pub bot_id: String,
+    pub name: String,
+    pub bot_memory: HistoricalSummary,
+    pub system_memory: HistoricalSummary,
+    pub system_cpu: HistoricalSummary,
+}
+
+impl BotMetricsSummary {
+    /// Embed field value: process memory, then the host's RAM and CPU
+    pub fn format_field(&self) -> String {
+        if !self.bot_memory.has_data && !self.system_memory.has_data && !self.system_cpu.has_data {
+            return "No metrics recorded for this period".to_string();
+        }
+        let percent = |summary: &HistoricalSummary, value: f64| {
+            if summary.has_data { format!("{:.1}%", value) } else { "-".to_string() }
+        };
+        let bot_memory = if self.bot_memory.has_data {
+            format!(
+                "{} (avg {}, peak {})",
+                format_bytes(self.bot_memory.current as u64),
+                format_bytes(self.bot_memory.average as u64),
+                format_bytes(self.bot_memory.peak as u64)
+            )
+        } else {
+            "-".to_string()
+        };
+        format!(
+            "Memory: {}
+Host RAM: {} (peak {})
+Host CPU: {} (peak {})
+ID: `{}`",
+            bot_memory,
+            percent(&self.system_memory, self.system_memory.current),
+            percent(&self.system_memory, self.system_memory.peak),
+            percent(&self.system_cpu, self.system_cpu.current),
+            percent(&self.system_cpu, self.system_cpu.peak),
+            self.bot_id,
+        )
+    }
+}
+
+/// Combined line for the all-bots view: bot count and total current process memory
+pub fn format_bots_total(bots: &[BotMetricsSummary]) -> String {
+    let total_memory: f64 = bots
+        .iter()
+        .filter(|b| b.bot_memory.has_data)
+        .map(|b| b.bot_memory.current)
+        .sum();
+    let noun = if bots.len() == 1 { "bot" } else { "bots" };
+    format!("{} {} reporting | {} total bot memory", bots.len(), noun, format_bytes(total_memory as u64))
+}
+
 /// Get the size of the database file in bytes
 pub fn get_db_file_size(path: &str) -> u64 {
     Path::new(path)
@@ -325,17 +391,54 @@ pub fn format_duration(total_secs: u64) -> String {
     }
 }
 
-/// Background task that collects system metrics periodically
-pub async fn metrics_collection_loop(db: Arc<Database>, db_path: String) {
+/// Heartbeat latency of each running shard by shard ID, None for a shard with no acknowledged heartbeat
+pub async fn shard_latencies(shards: &ShardSlot) -> Vec<(u64, Option<Duration>)> {
+    let Some(manager) = shards.lock().await.clone() else {
+        return Vec::new();
+    };
+    let runners = manager.lock().await.runners.clone();
+    let mut latencies: Vec<_> = runners.lock().await.iter().map(|(id, runner)| (id.0, runner.latency)).collect();
+    latencies.sort_by_key(|&(id, _)| id);
+    latencies
+}
+
+/// Average heartbeat latency of the connected shards, None before any heartbeat is acknowledged
+pub async fn gateway_latency(shards: &ShardSlot) -> Option<Duration> {
+    let latencies: Vec<Duration> = shard_latencies(shards).await.into_iter().filter_map(|(_, latency)| latency).collect();
+    average_latency(&latencies)
+}
+
+fn average_latency(latencies: &[Duration]) -> Option<Duration> {
+    if latencies.is_empty() {
+        return None;
+    }
+    Some(latencies.iter().sum::<Duration>() / latencies.len() as u32)
+}
+
+/// Background task that collects system metrics periodically, also setting the exporter's gauges
+pub async fn metrics_collection_loop(db: Arc<Database>, db_path: String, http: Arc<Http>, shards: ShardSlot, metrics: Metrics) {
     let mut interval = tokio::time::interval(Duration::from_secs(300)); // 5 minutes
     let mut sys = System::new();
     let mut cleanup_counter = 0u32;
+    let mut bot: Option<(String, String)> = None;
 
     info!("System metrics collection task started (interval: 5 minutes)");
 
     loop {
         interval.tick().await;
 
+        // Metrics are tagged with this bot's user ID; skip the tick until it's known
+        if bot.is_none() {
+            match http.get_current_user().await {
+                Ok(user) => bot = Some((user.id.to_string(), user.name.clone())),
+                Err(e) => {
+                    warn!("Skipping metrics collection, couldn't look up the bot user: {}", e);
+                    continue;
+                }
+            }
+        }
+        let (bot_id, bot_name) = bot.as_ref().map(|(id, name)| (id.as_str(), name.as_str())).unwrap_or_default();
+
         debug!("Collecting system metrics...");
 
         // Refresh CPU (needs two calls for accurate reading)
@@ -346,7 +449,7 @@ pub async fn metrics_collection_loop(db: Arc<Database>, db_path: String) {
 
         // Record database size
         let db_size = get_db_file_size(&db_path);
-        if let Err(e) = db.store_system_metric("db_size_bytes", db_size as f64).await {
+        if let Err(e) = db.store_system_metric(bot_id, bot_name, "db_size_bytes", db_size as f64).await {
             warn!("Failed to store db_size metric: {}", e);
         }
 
@@ -358,7 +461,7 @@ pub async fn metrics_collection_loop(db: Arc<Database>, db_path: String) {
                 ProcessRefreshKind::new().with_memory()
             );
             if let Some(proc) = sys.process(pid) {
-                if let Err(e) = db.store_system_metric("bot_memory_bytes", proc.memory() as f64).await {
+                if let Err(e) = db.store_system_metric(bot_id, bot_name, "bot_memory_bytes", proc.memory() as f64).await {
                     warn!("Failed to store bot_memory metric: {}", e);
                 }
             }
@@ -368,16 +471,31 @@ pub async fn metrics_collection_loop(db: Arc<Database>, db_path: String) {
         let memory_total = sys.total_memory();
         if memory_total > 0 {
             let memory_percent = (sys.used_memory() as f64 / memory_total as f64) * 100.0;
-            if let Err(e) = db.store_system_metric("system_memory_percent", memory_percent).await {
+            if let Err(e) = db.store_system_metric(bot_id, bot_name, "system_memory_percent", memory_percent).await {
                 warn!("Failed to store system_memory metric: {}", e);
             }
         }
 
         // Record system CPU percentage
-        if let Err(e) = db.store_system_metric("system_cpu_percent", sys.global_cpu_usage() as f64).await {
+        if let Err(e) = db.store_system_metric(bot_id, bot_name, "system_cpu_percent", sys.global_cpu_usage() as f64).await {
             warn!("Failed to store system_cpu metric: {}", e);
         }
 
+        // Record gateway latency and reminder queue depth
+        let per_shard = shard_latencies(&shards).await;
+        metrics.set_shard_latencies(&per_shard);
+        let latency = average_latency(&per_shard.iter().filter_map(|&(_, latency)| latency).collect::<Vec<_>>());
+        metrics.set_gateway_latency(latency);
+        if let Some(latency) = latency {
+            if let Err(e) = db.store_system_metric(bot_id, bot_name, "gateway_latency_ms", latency.as_millis() as f64).await {
+                warn!("Failed to store gateway_latency metric: {}", e);
+            }
+        }
+        match db.count_open_reminders().await {
+            Ok(depth) => metrics.set_reminder_queue_depth(depth),
+            Err(e) => warn!("Failed to count open reminders: {}", e),
+        }
+
         debug!("System metrics recorded successfully");
 
         // Cleanup old metrics once per day (288 intervals at 5 min each)
@@ -401,6 +519,21 @@ pub async fn metrics_collection_loop(db: Arc<Database>, db_path: String) {
                 warn!("Failed to cleanup old OpenAI usage daily data: {}", e);
             }
 
+            // Cleanup per-guild command activity (90 days - for /ops overview)
+            if let Err(e) = db.cleanup_old_guild_activity(90).await {
+                warn!("Failed to cleanup old guild activity data: {}", e);
+            }
+
+            // Cleanup emoji statistics (90 days - for /emojistats)
+            if let Err(e) = db.cleanup_old_emoji_stats(EMOJI_STATS_RETENTION_DAYS).await {
+                warn!("Failed to cleanup old emoji stats: {}", e);
+            }
+
+            // Cleanup member activity (180 days - for /community_insights retention cohorts)
+            if let Err(e) = db.cleanup_old_member_activity(MEMBER_ACTIVITY_RETENTION_DAYS).await {
+                warn!("Failed to cleanup old member activity: {}", e);
+            }
+
             info!("Daily cleanup tasks completed");
         }
     }
@@ -410,6 +543,29 @@ pub async fn metrics_collection_loop(db: Arc<Database>, db_path: String) {
 mod tests {
     use super::*;
 
+    fn bot_summary(name: &str, memory: &[(i64, f64)]) -> BotMetricsSummary {
+        BotMetricsSummary {
+            bot_id: format!("{name}-id"),
+            name: name.to_string(),
+            bot_memory: HistoricalSummary::from_data(memory),
+            system_memory: HistoricalSummary::from_data(&[(0, 40.0), (1, 50.0)]),
+            system_cpu: HistoricalSummary::from_data(&[]),
+        }
+    }
+
+    #[test]
+    fn test_bot_breakdown_formatting() {
+        let muppet = bot_summary("muppet", &[(0, 100.0 * 1024.0 * 1024.0), (1, 200.0 * 1024.0 * 1024.0)]);
+        let field = muppet.format_field();
+        assert!(field.starts_with("Memory: 200.0 MB (avg 150.0 MB, peak 200.0 MB)"));
+        assert!(field.contains("Host RAM: 50.0% (peak 50.0%)"));
+        assert!(field.contains("Host CPU: - (peak -)"));
+        assert!(field.ends_with("ID: `muppet-id`"));
+
+        let chef = bot_summary("chef", &[(0, 50.0 * 1024.0 * 1024.0)]);
+        assert_eq!(format_bots_total(&[muppet, chef]), "2 bots reporting | 250.0 MB total bot memory");
+    }
+
     #[test]
     fn test_format_bytes() {
         assert_eq!(format_bytes(0), "0 B");
@@ -452,4 +608,16 @@ mod tests {
         assert_eq!(summary.peak, 200.0);
         assert!((summary.average - 150.0).abs() < 0.01);
     }
+
+    #[tokio::test]
+    async fn test_gateway_latency() {
+        assert_eq!(average_latency(&[]), None);
+        assert_eq!(
+            average_latency(&[Duration::from_millis(40), Duration::from_millis(60)]),
+            Some(Duration::from_millis(50))
+        );
+        // No gateway connection yet
+        assert_eq!(gateway_latency(&ShardSlot::default()).await, None);
+        assert!(shard_latencies(&ShardSlot::default()).await.is_empty());
+    }
 }
@@ -3,14 +3,20 @@
 //! Captures and stores OpenAI API usage metrics for cost analysis and monitoring.
 //! Supports ChatCompletion tokens, Whisper audio duration, and DALL-E image generation.
 //!
-//! - **Version**: 1.0.0
+//! - **Version**: 1.4.0
 //! - **Since**: 0.5.0
 //! - **Toggleable**: false
 //!
 //! ## Changelog
+//! - 1.4.0: Tokens and cost counted for the Prometheus exporter
+//! - 1.3.0: Each logged cost published to the live event stream
+//! - 1.2.0: Chat usage tagged with the originating feature for cost reports
+//! - 1.1.0: Cached input token and Batch API pricing tiers
 //! - 1.0.0: Initial release with async background logging
 
 use crate::database::Database;
+use crate::features::live_events::{LiveEventKind, LiveEvents};
+use crate::features::prometheus::Metrics;
 use log::{debug, error, warn};
 use tokio::sync::mpsc;
 
@@ -18,10 +24,12 @@ use tokio::sync::mpsc;
 pub mod pricing {
     // GPT-4o pricing (per 1K tokens)
     pub const GPT4O_INPUT_PER_1K: f64 = 0.0025; // $2.50/1M input
+    pub const GPT4O_CACHED_INPUT_PER_1K: f64 = 0.00125; // $1.25/1M cached input
     pub const GPT4O_OUTPUT_PER_1K: f64 = 0.01; // $10/1M output
 
     // GPT-4o-mini pricing (per 1K tokens)
     pub const GPT4O_MINI_INPUT_PER_1K: f64 = 0.00015; // $0.15/1M input
+    pub const GPT4O_MINI_CACHED_INPUT_PER_1K: f64 = 0.000075; // $0.075/1M cached input
     pub const GPT4O_MINI_OUTPUT_PER_1K: f64 = 0.0006; // $0.60/1M output
 
     // GPT-4 Turbo pricing (per 1K tokens)
@@ -36,6 +44,9 @@ pub mod pricing {
     pub const GPT35_TURBO_INPUT_PER_1K: f64 = 0.0005; // $0.50/1M input
     pub const GPT35_TURBO_OUTPUT_PER_1K: f64 = 0.0015; // $1.50/1M output
 
+    // Batch API requests are billed at half the synchronous rate
+    pub const BATCH_DISCOUNT: f64 = 0.5;
+
     // Whisper pricing (per minute)
     pub const WHISPER_PER_MINUTE: f64 = 0.006; // $0.006/minute
 
@@ -47,23 +58,46 @@ pub mod pricing {
 
     /// Calculate cost for ChatCompletion based on model
     pub fn calculate_chat_cost(model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+        calculate_chat_cost_with_tiers(model, input_tokens, 0, output_tokens, false)
+    }
+
+    /// Calculate ChatCompletion cost accounting for cached input tokens and Batch API pricing.
+    ///
+    /// `cached_input_tokens` is the portion of `input_tokens` served from the prompt cache.
+    /// Models without a cached rate bill cached tokens at the normal input rate.
+    pub fn calculate_chat_cost_with_tiers(
+        model: &str,
+        input_tokens: u32,
+        cached_input_tokens: u32,
+        output_tokens: u32,
+        batch: bool,
+    ) -> f64 {
         let model_lower = model.to_lowercase();
 
-        let (input_rate, output_rate) = if model_lower.contains("gpt-4o-mini") {
-            (GPT4O_MINI_INPUT_PER_1K, GPT4O_MINI_OUTPUT_PER_1K)
+        let (input_rate, cached_rate, output_rate) = if model_lower.contains("gpt-4o-mini") {
+            (GPT4O_MINI_INPUT_PER_1K, GPT4O_MINI_CACHED_INPUT_PER_1K, GPT4O_MINI_OUTPUT_PER_1K)
         } else if model_lower.contains("gpt-4o") {
-            (GPT4O_INPUT_PER_1K, GPT4O_OUTPUT_PER_1K)
+            (GPT4O_INPUT_PER_1K, GPT4O_CACHED_INPUT_PER_1K, GPT4O_OUTPUT_PER_1K)
         } else if model_lower.contains("gpt-4-turbo") {
-            (GPT4_TURBO_INPUT_PER_1K, GPT4_TURBO_OUTPUT_PER_1K)
+            (GPT4_TURBO_INPUT_PER_1K, GPT4_TURBO_INPUT_PER_1K, GPT4_TURBO_OUTPUT_PER_1K)
         } else if model_lower.contains("gpt-4") {
-            (GPT4_INPUT_PER_1K, GPT4_OUTPUT_PER_1K)
+            (GPT4_INPUT_PER_1K, GPT4_INPUT_PER_1K, GPT4_OUTPUT_PER_1K)
         } else {
             // Default to GPT-3.5 Turbo pricing
-            (GPT35_TURBO_INPUT_PER_1K, GPT35_TURBO_OUTPUT_PER_1K)
+            (GPT35_TURBO_INPUT_PER_1K, GPT35_TURBO_INPUT_PER_1K, GPT35_TURBO_OUTPUT_PER_1K)
         };
 
-        (input_tokens as f64 / 1000.0 * input_rate)
-            + (output_tokens as f64 / 1000.0 * output_rate)
+        let cached = cached_input_tokens.min(input_tokens);
+        let uncached = input_tokens - cached;
+        let cost = (uncached as f64 / 1000.0 * input_rate)
+            + (cached as f64 / 1000.0 * cached_rate)
+            + (output_tokens as f64 / 1000.0 * output_rate);
+
+        if batch {
+            cost * BATCH_DISCOUNT
+        } else {
+            cost
+        }
     }
 
     /// Calculate cost for Whisper transcription
@@ -94,12 +128,18 @@ pub enum UsageEvent {
     Chat {
         model: String,
         input_tokens: u32,
+        /// Portion of `input_tokens` served from the prompt cache
+        cached_input_tokens: u32,
         output_tokens: u32,
         total_tokens: u32,
+        /// Whether the request went through the Batch API
+        batch: bool,
         user_id: String,
         guild_id: Option<String>,
         channel_id: Option<String>,
         request_id: Option<String>,
+        /// Bot feature that made the call (see `cost_report::cost_feature`)
+        feature: String,
     },
     /// Whisper transcription API
     Whisper {
@@ -128,15 +168,26 @@ pub struct UsageTracker {
 impl UsageTracker {
     /// Create a new UsageTracker with a background logging task
     pub fn new(database: Database) -> Self {
+        Self::with_live_events(database, LiveEvents::new())
+    }
+
+    /// Create a UsageTracker that also publishes each logged cost to `live_events`
+    pub fn with_live_events(database: Database, live_events: LiveEvents) -> Self {
+        Self::with_observers(database, live_events, Metrics::new())
+    }
+
+    /// Create a UsageTracker that publishes each logged cost to `live_events` and counts it in `metrics`
+    pub fn with_observers(database: Database, live_events: LiveEvents, metrics: Metrics) -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
 
         // Spawn background task for non-blocking writes
-        tokio::spawn(Self::background_logger(database, receiver));
+        tokio::spawn(Self::background_logger(database, live_events, metrics, receiver));
 
         UsageTracker { sender }
     }
 
     /// Log a ChatCompletion usage event (non-blocking)
+    #[allow(clippy::too_many_arguments)]
     pub fn log_chat(
         &self,
         model: &str,
@@ -147,16 +198,51 @@ impl UsageTracker {
         guild_id: Option<&str>,
         channel_id: Option<&str>,
         request_id: Option<&str>,
+        feature: &str,
+    ) {
+        self.log_chat_with_tiers(
+            model,
+            input_tokens,
+            0,
+            output_tokens,
+            total_tokens,
+            false,
+            user_id,
+            guild_id,
+            channel_id,
+            request_id,
+            feature,
+        );
+    }
+
+    /// Log a ChatCompletion usage event with cached-input and batch pricing details (non-blocking)
+    #[allow(clippy::too_many_arguments)]
+    pub fn log_chat_with_tiers(
+        &self,
+        model: &str,
+        input_tokens: u32,
+        cached_input_tokens: u32,
+        output_tokens: u32,
+        total_tokens: u32,
+        batch: bool,
+        user_id: &str,
+        guild_id: Option<&str>,
+        channel_id: Option<&str>,
+        request_id: Option<&str>,
+        feature: &str,
     ) {
         let event = UsageEvent::Chat {
             model: model.to_string(),
             input_tokens,
+            cached_input_tokens,
             output_tokens,
             total_tokens,
+            batch,
             user_id: user_id.to_string(),
             guild_id: guild_id.map(String::from),
             channel_id: channel_id.map(String::from),
             request_id: request_id.map(String::from),
+            feature: feature.to_string(),
         };
 
         if let Err(e) = self.sender.send(event) {
@@ -211,48 +297,67 @@ impl UsageTracker {
     /// Background task that processes usage events
     async fn background_logger(
         database: Database,
+        live_events: LiveEvents,
+        metrics: Metrics,
         mut receiver: mpsc::UnboundedReceiver<UsageEvent>,
     ) {
         while let Some(event) = receiver.recv().await {
-            if let Err(e) = Self::store_event(&database, &event).await {
-                error!("Failed to store usage event: {e}");
+            match Self::store_event(&database, &event).await {
+                Ok(cost) => {
+                    record_metrics(&metrics, &event, cost);
+                    live_events.publish(cost_event(&event, cost));
+                }
+                Err(e) => error!("Failed to store usage event: {e}"),
             }
         }
     }
 
-    /// Store a usage event in the database
-    async fn store_event(database: &Database, event: &UsageEvent) -> anyhow::Result<()> {
-        match event {
+    /// Store a usage event in the database, returning its cost in USD
+    async fn store_event(database: &Database, event: &UsageEvent) -> crate::core::Result<f64> {
+        let cost = match event {
             UsageEvent::Chat {
                 model,
                 input_tokens,
+                cached_input_tokens,
                 output_tokens,
                 total_tokens,
+                batch,
                 user_id,
                 guild_id,
                 channel_id,
                 request_id,
+                feature,
             } => {
-                let cost = pricing::calculate_chat_cost(model, *input_tokens, *output_tokens);
+                let cost = pricing::calculate_chat_cost_with_tiers(
+                    model,
+                    *input_tokens,
+                    *cached_input_tokens,
+                    *output_tokens,
+                    *batch,
+                );
 
                 database
                     .log_openai_chat_usage(
                         model,
                         *input_tokens,
+                        *cached_input_tokens,
                         *output_tokens,
                         *total_tokens,
+                        *batch,
                         cost,
                         user_id,
                         guild_id.as_deref(),
                         channel_id.as_deref(),
                         request_id.as_deref(),
+                        feature,
                     )
                     .await?;
 
                 debug!(
-                    "Logged chat usage: {} tokens (model: {}, cost: ${:.6})",
-                    total_tokens, model, cost
+                    "Logged chat usage: {} tokens (model: {}, feature: {}, cost: ${:.6})",
+                    total_tokens, model, feature, cost
                 );
+                cost
             }
             UsageEvent::Whisper {
                 audio_duration_seconds,
@@ -276,6 +381,7 @@ impl UsageTracker {
                     "Logged Whisper usage: {:.1}s audio (cost: ${:.6})",
                     audio_duration_seconds, cost
                 );
+                cost
             }
             UsageEvent::DallE {
                 size,
@@ -302,8 +408,109 @@ impl UsageTracker {
                     "Logged DALL-E usage: {} image(s) at {} (cost: ${:.4})",
                     image_count, size, cost
                 );
+                cost
             }
+        };
+        Ok(cost)
+    }
+}
+
+/// Count a stored usage event's tokens and cost
+fn record_metrics(metrics: &Metrics, event: &UsageEvent, cost_usd: f64) {
+    let service = match event {
+        UsageEvent::Chat { model, input_tokens, output_tokens, .. } => {
+            metrics.record_openai_tokens(model, *input_tokens, *output_tokens);
+            "chat"
         }
-        Ok(())
+        UsageEvent::Whisper { .. } => "whisper",
+        UsageEvent::DallE { .. } => "dalle",
+    };
+    metrics.record_openai_cost(service, cost_usd);
+}
+
+/// Live event for a stored usage event
+fn cost_event(event: &UsageEvent, cost_usd: f64) -> LiveEventKind {
+    let (service, model, user_id, guild_id) = match event {
+        UsageEvent::Chat { model, user_id, guild_id, .. } => ("chat", Some(model), user_id, guild_id),
+        UsageEvent::Whisper { user_id, guild_id, .. } => ("whisper", None, user_id, guild_id),
+        UsageEvent::DallE { size, user_id, guild_id, .. } => ("dalle", Some(size), user_id, guild_id),
+    };
+    LiveEventKind::Cost {
+        service: service.to_string(),
+        model: model.cloned(),
+        user_id: user_id.clone(),
+        guild_id: guild_id.clone(),
+        cost_usd,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pricing::*;
+    use super::*;
+
+    #[test]
+    fn test_cached_input_tokens_use_cached_rate() {
+        let full = calculate_chat_cost_with_tiers("gpt-4o-mini", 2000, 0, 0, false);
+        let cached = calculate_chat_cost_with_tiers("gpt-4o-mini", 2000, 1000, 0, false);
+        let expected = GPT4O_MINI_INPUT_PER_1K + GPT4O_MINI_CACHED_INPUT_PER_1K;
+        assert!((cached - expected).abs() < 1e-12);
+        assert!(cached < full);
+
+        // Cached tokens can't exceed total input tokens
+        let clamped = calculate_chat_cost_with_tiers("gpt-4o", 1000, 5000, 0, false);
+        assert!((clamped - GPT4O_CACHED_INPUT_PER_1K).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_batch_discount_and_fallback_rates() {
+        let sync = calculate_chat_cost("gpt-4o", 1000, 1000);
+        let batch = calculate_chat_cost_with_tiers("gpt-4o", 1000, 0, 1000, true);
+        assert!((batch - sync * BATCH_DISCOUNT).abs() < 1e-12);
+
+        // Models without a cached rate bill cached tokens at the input rate
+        let gpt4 = calculate_chat_cost_with_tiers("gpt-4", 1000, 1000, 0, false);
+        assert!((gpt4 - GPT4_INPUT_PER_1K).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_cost_event() {
+        let event = UsageEvent::Whisper {
+            audio_duration_seconds: 60.0,
+            user_id: "42".to_string(),
+            guild_id: Some("7".to_string()),
+            channel_id: None,
+        };
+        let LiveEventKind::Cost { service, model, user_id, guild_id, cost_usd } = cost_event(&event, 0.006) else {
+            panic!("expected a cost event");
+        };
+        assert_eq!((service.as_str(), model, user_id.as_str()), ("whisper", None, "42"));
+        assert_eq!(guild_id.as_deref(), Some("7"));
+        assert!((cost_usd - 0.006).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_record_metrics() {
+        let metrics = Metrics::new();
+        let chat = UsageEvent::Chat {
+            model: "gpt-4o-mini".to_string(),
+            input_tokens: 100,
+            cached_input_tokens: 0,
+            output_tokens: 20,
+            total_tokens: 120,
+            batch: false,
+            user_id: "42".to_string(),
+            guild_id: None,
+            channel_id: None,
+            request_id: None,
+            feature: "chat".to_string(),
+        };
+        record_metrics(&metrics, &chat, 0.5);
+        record_metrics(&metrics, &chat, 0.25);
+
+        let text = metrics.render(&crate::database::QueryLatency::default().snapshot());
+        assert!(text.contains("persona_openai_tokens_total{model=\"gpt-4o-mini\",kind=\"input\"} 200\n"));
+        assert!(text.contains("persona_openai_tokens_total{model=\"gpt-4o-mini\",kind=\"output\"} 40\n"));
+        assert!(text.contains("persona_openai_cost_usd_total{service=\"chat\"} 0.75\n"));
     }
 }
@@ -0,0 +1,130 @@
+//! # Feature: ClamAV Scanner
+//!
+//! Streams attachments to a `clamd` daemon with the INSTREAM command, over TCP
+//! (`host:port`) or a unix socket (absolute path), configured by `CLAMAV_ADDRESS`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with INSTREAM over TCP and unix sockets
+
+use super::scanner::{AttachmentScanner, ScanSensitivity, ScanTarget, ScanVerdict};
+use crate::core::{BotError, Result};
+use serenity::async_trait;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Bytes sent per INSTREAM chunk
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Upper bound on one scan, including connecting
+const SCAN_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct ClamAvScanner {
+    address: String,
+}
+
+impl ClamAvScanner {
+    pub fn new(address: String) -> Self {
+        ClamAvScanner { address }
+    }
+
+    async fn scan_bytes(&self, data: &[u8]) -> Result<String> {
+        #[cfg(unix)]
+        if self.address.starts_with('/') {
+            let stream = tokio::net::UnixStream::connect(&self.address).await?;
+            return instream(stream, data).await;
+        }
+        let stream = tokio::net::TcpStream::connect(&self.address).await?;
+        instream(stream, data).await
+    }
+}
+
+/// Run the INSTREAM command and return clamd's reply
+async fn instream<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, data: &[u8]) -> Result<String> {
+    stream.write_all(b"zINSTREAM\0").await?;
+    for chunk in data.chunks(CHUNK_SIZE) {
+        stream.write_all(&(chunk.len() as u32).to_be_bytes()).await?;
+        stream.write_all(chunk).await?;
+    }
+    stream.write_all(&0u32.to_be_bytes()).await?;
+    stream.flush().await?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await?;
+    Ok(String::from_utf8_lossy(&reply).trim_end_matches('\0').trim().to_string())
+}
+
+/// Interpret a clamd reply such as `stream: OK` or `stream: Eicar-Signature FOUND`
+pub fn parse_clamd_reply(reply: &str) -> Result<ScanVerdict> {
+    let result = reply.strip_prefix("stream:").unwrap_or(reply).trim();
+    if result == "OK" {
+        Ok(ScanVerdict::Clean)
+    } else if let Some(signature) = result.strip_suffix("FOUND") {
+        Ok(ScanVerdict::Flagged {
+            scanner: "ClamAV",
+            reason: format!("malware detected ({})", signature.trim()),
+        })
+    } else {
+        Err(BotError::internal(format!("Unexpected clamd reply: {}", reply)))
+    }
+}
+
+#[async_trait]
+impl AttachmentScanner for ClamAvScanner {
+    fn name(&self) -> &'static str {
+        "ClamAV"
+    }
+
+    fn applies_to(&self, _target: &ScanTarget) -> bool {
+        true
+    }
+
+    fn needs_data(&self) -> bool {
+        true
+    }
+
+    async fn scan(&self, target: &ScanTarget, _sensitivity: ScanSensitivity) -> Result<ScanVerdict> {
+        let data = target.data.as_deref().unwrap_or_default();
+        let reply = tokio::time::timeout(SCAN_TIMEOUT, self.scan_bytes(data))
+            .await
+            .map_err(|_| BotError::internal("clamd scan timed out"))??;
+        parse_clamd_reply(&reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_clamd_reply() {
+        assert_eq!(parse_clamd_reply("stream: OK").unwrap(), ScanVerdict::Clean);
+        assert_eq!(
+            parse_clamd_reply("stream: Win.Test.EICAR_HDB-1 FOUND").unwrap(),
+            ScanVerdict::Flagged { scanner: "ClamAV", reason: "malware detected (Win.Test.EICAR_HDB-1)".to_string() }
+        );
+        assert!(parse_clamd_reply("INSTREAM size limit exceeded. ERROR").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_instream_framing() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let server_task = tokio::spawn(async move {
+            let mut received = vec![0u8; 10 + 4 + 3 + 4];
+            server.read_exact(&mut received).await.unwrap();
+            server.write_all(b"stream: OK\0").await.unwrap();
+            received
+        });
+
+        let reply = instream(client, b"abc").await.unwrap();
+        assert_eq!(reply, "stream: OK");
+        let received = server_task.await.unwrap();
+        assert_eq!(&received[..10], b"zINSTREAM\0");
+        assert_eq!(&received[10..14], &3u32.to_be_bytes());
+        assert_eq!(&received[14..17], b"abc");
+        assert_eq!(&received[17..], &[0, 0, 0, 0]);
+    }
+}
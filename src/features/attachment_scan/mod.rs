@@ -0,0 +1,20 @@
+//! # Attachment Scanning Feature
+//!
+//! Pluggable scanning pipeline for uploads in configured channels: ClamAV virus
+//! scanning and a vision-model NSFW check. Flagged uploads are quarantined by
+//! deleting the message and alerting the mod log.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod clamav;
+pub mod nsfw;
+pub mod scanner;
+
+pub use clamav::ClamAvScanner;
+pub use nsfw::NsfwScanner;
+pub use scanner::{
+    format_quarantine_alert, is_scan_channel, AttachmentScanner, ScanPipeline, ScanSensitivity, ScanTarget,
+    ScanVerdict, MAX_SCAN_BYTES,
+};
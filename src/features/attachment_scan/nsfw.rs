@@ -0,0 +1,132 @@
+//! # Feature: NSFW Image Scanner
+//!
+//! Checks uploaded images with OpenAI's vision-capable moderation model and
+//! flags sexual or graphic content at or above the guild's sensitivity threshold.
+//! Sexual content involving minors is always flagged.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release using omni-moderation-latest
+
+use super::scanner::{AttachmentScanner, ScanSensitivity, ScanTarget, ScanVerdict};
+use crate::core::{BotError, Result};
+use log::debug;
+use serde_json::{json, Value};
+use serenity::async_trait;
+
+const MODERATION_MODEL: &str = "omni-moderation-latest";
+
+/// Categories compared against the sensitivity threshold
+const NSFW_CATEGORIES: &[&str] = &["sexual", "violence/graphic"];
+
+pub struct NsfwScanner {
+    openai_api_key: String,
+    client: reqwest::Client,
+}
+
+impl NsfwScanner {
+    pub fn new(openai_api_key: String) -> Self {
+        NsfwScanner {
+            openai_api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+/// Verdict from a moderation API response body
+pub fn moderation_verdict(response: &Value, threshold: f64) -> Result<ScanVerdict> {
+    let result = response
+        .get("results")
+        .and_then(|r| r.get(0))
+        .ok_or_else(|| BotError::openai("No results in moderation response"))?;
+
+    if result.pointer("/categories/sexual~1minors").and_then(Value::as_bool) == Some(true) {
+        return Ok(ScanVerdict::Flagged {
+            scanner: "NSFW",
+            reason: "sexual/minors".to_string(),
+        });
+    }
+
+    let scores = result.get("category_scores");
+    for category in NSFW_CATEGORIES {
+        let score = scores.and_then(|s| s.get(*category)).and_then(Value::as_f64).unwrap_or(0.0);
+        if score >= threshold {
+            return Ok(ScanVerdict::Flagged {
+                scanner: "NSFW",
+                reason: format!("{category} (score {score:.2})"),
+            });
+        }
+    }
+    Ok(ScanVerdict::Clean)
+}
+
+#[async_trait]
+impl AttachmentScanner for NsfwScanner {
+    fn name(&self) -> &'static str {
+        "NSFW"
+    }
+
+    fn applies_to(&self, target: &ScanTarget) -> bool {
+        target.is_image()
+    }
+
+    fn needs_data(&self) -> bool {
+        false
+    }
+
+    async fn scan(&self, target: &ScanTarget, sensitivity: ScanSensitivity) -> Result<ScanVerdict> {
+        debug!("Checking {} with {}", target.filename, MODERATION_MODEL);
+        let response = self
+            .client
+            .post("https://api.openai.com/v1/moderations")
+            .header("Authorization", format!("Bearer {}", self.openai_api_key))
+            .json(&json!({
+                "model": MODERATION_MODEL,
+                "input": [{ "type": "image_url", "image_url": { "url": target.url } }],
+            }))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(BotError::openai(format!("Moderation API error (status {})", status)));
+        }
+        let body: Value = response.json().await?;
+        moderation_verdict(&body, sensitivity.nsfw_threshold())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(sexual: f64, minors: bool) -> Value {
+        json!({
+            "results": [{
+                "categories": { "sexual": sexual > 0.5, "sexual/minors": minors },
+                "category_scores": { "sexual": sexual, "violence/graphic": 0.01 }
+            }]
+        })
+    }
+
+    #[test]
+    fn test_moderation_verdict_threshold() {
+        assert_eq!(moderation_verdict(&response(0.5, false), 0.7).unwrap(), ScanVerdict::Clean);
+        assert_eq!(
+            moderation_verdict(&response(0.5, false), 0.4).unwrap(),
+            ScanVerdict::Flagged { scanner: "NSFW", reason: "sexual (score 0.50)".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_moderation_verdict_minors_always_flagged() {
+        assert!(matches!(
+            moderation_verdict(&response(0.0, true), 0.9).unwrap(),
+            ScanVerdict::Flagged { .. }
+        ));
+        assert!(moderation_verdict(&json!({ "results": [] }), 0.7).is_err());
+    }
+}
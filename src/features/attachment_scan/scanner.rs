@@ -0,0 +1,209 @@
+//! # Feature: Attachment Scanning Pipeline
+//!
+//! Runs every configured [`AttachmentScanner`] over a message's attachments and
+//! reports the first one that flags an upload. Scanners are independent, so new
+//! checks can be added without touching the message handler.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with ClamAV and NSFW scanners
+
+use crate::core::Result;
+use log::{debug, warn};
+use serenity::async_trait;
+use serenity::model::channel::Attachment;
+use std::sync::Arc;
+
+/// Attachments larger than this are not downloaded for scanning (Discord's free upload limit)
+pub const MAX_SCAN_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Per-guild scanning sensitivity (`attachment_scan_sensitivity` setting)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanSensitivity {
+    Low,
+    Medium,
+    High,
+}
+
+impl ScanSensitivity {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "low" => Some(ScanSensitivity::Low),
+            "medium" => Some(ScanSensitivity::Medium),
+            "high" => Some(ScanSensitivity::High),
+            _ => None,
+        }
+    }
+
+    /// Moderation score at or above which an image is treated as NSFW
+    pub fn nsfw_threshold(&self) -> f64 {
+        match self {
+            ScanSensitivity::Low => 0.9,
+            ScanSensitivity::Medium => 0.7,
+            ScanSensitivity::High => 0.4,
+        }
+    }
+}
+
+/// An uploaded file handed to the scanners
+#[derive(Debug, Clone)]
+pub struct ScanTarget {
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub url: String,
+    /// File contents, when a scanner that needs them is configured
+    pub data: Option<Vec<u8>>,
+}
+
+impl ScanTarget {
+    pub fn is_image(&self) -> bool {
+        self.content_type.as_deref().is_some_and(|t| t.starts_with("image/"))
+    }
+}
+
+/// Outcome of scanning one attachment
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanVerdict {
+    Clean,
+    Flagged { scanner: &'static str, reason: String },
+}
+
+/// A single check in the pipeline
+#[async_trait]
+pub trait AttachmentScanner: Send + Sync {
+    /// Short name shown in mod alerts
+    fn name(&self) -> &'static str;
+
+    /// Whether this scanner handles the attachment (e.g. images only)
+    fn applies_to(&self, target: &ScanTarget) -> bool;
+
+    /// Whether the attachment must be downloaded before `scan`
+    fn needs_data(&self) -> bool;
+
+    async fn scan(&self, target: &ScanTarget, sensitivity: ScanSensitivity) -> Result<ScanVerdict>;
+}
+
+/// Ordered set of scanners applied to uploads
+#[derive(Clone, Default)]
+pub struct ScanPipeline {
+    scanners: Vec<Arc<dyn AttachmentScanner>>,
+}
+
+impl ScanPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_scanner(mut self, scanner: Arc<dyn AttachmentScanner>) -> Self {
+        self.scanners.push(scanner);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scanners.is_empty()
+    }
+
+    pub fn scanner_names(&self) -> Vec<&'static str> {
+        self.scanners.iter().map(|s| s.name()).collect()
+    }
+
+    /// Scan a message's attachments, returning the first flagged (filename, verdict).
+    /// Scanner errors are logged and treated as clean so an outage never deletes messages.
+    pub async fn scan_attachments(
+        &self,
+        attachments: &[Attachment],
+        sensitivity: ScanSensitivity,
+    ) -> Option<(String, ScanVerdict)> {
+        for attachment in attachments {
+            let mut target = ScanTarget {
+                filename: attachment.filename.clone(),
+                content_type: attachment.content_type.clone(),
+                url: attachment.url.clone(),
+                data: None,
+            };
+            let applicable: Vec<&Arc<dyn AttachmentScanner>> =
+                self.scanners.iter().filter(|s| s.applies_to(&target)).collect();
+            if applicable.is_empty() {
+                continue;
+            }
+
+            if applicable.iter().any(|s| s.needs_data()) {
+                if attachment.size > MAX_SCAN_BYTES {
+                    debug!("Skipping download of {} ({} bytes) for scanning", attachment.filename, attachment.size);
+                } else {
+                    match attachment.download().await {
+                        Ok(data) => target.data = Some(data),
+                        Err(e) => warn!("Failed to download {} for scanning: {}", attachment.filename, e),
+                    }
+                }
+            }
+
+            for scanner in applicable {
+                if scanner.needs_data() && target.data.is_none() {
+                    continue;
+                }
+                match scanner.scan(&target, sensitivity).await {
+                    Ok(ScanVerdict::Clean) => {}
+                    Ok(verdict) => return Some((target.filename, verdict)),
+                    Err(e) => warn!("{} scan of {} failed: {}", scanner.name(), target.filename, e),
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Whether a channel is listed in the `attachment_scan_channels` setting
+pub fn is_scan_channel(setting: Option<&str>, channel_id: &str) -> bool {
+    match setting {
+        Some(value) if value != "disabled" => value.split(',').any(|id| id.trim() == channel_id),
+        _ => false,
+    }
+}
+
+/// Mod log alert for a quarantined message
+pub fn format_quarantine_alert(user_id: &str, channel_id: &str, filename: &str, verdict: &ScanVerdict) -> String {
+    let (scanner, reason) = match verdict {
+        ScanVerdict::Flagged { scanner, reason } => (*scanner, reason.as_str()),
+        ScanVerdict::Clean => ("none", "clean"),
+    };
+    format!(
+        "🛡️ **Attachment quarantined** in <#{channel_id}>: message from <@{user_id}> deleted.\n\
+        File: `{filename}` · Scanner: {scanner} · Reason: {reason}"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensitivity() {
+        assert_eq!(ScanSensitivity::parse("high"), Some(ScanSensitivity::High));
+        assert_eq!(ScanSensitivity::parse("ultra"), None);
+        assert!(ScanSensitivity::High.nsfw_threshold() < ScanSensitivity::Low.nsfw_threshold());
+    }
+
+    #[test]
+    fn test_is_scan_channel() {
+        assert!(is_scan_channel(Some("1, 2,3"), "2"));
+        assert!(!is_scan_channel(Some("1,2"), "4"));
+        assert!(!is_scan_channel(Some("disabled"), "1"));
+        assert!(!is_scan_channel(None, "1"));
+    }
+
+    #[test]
+    fn test_quarantine_alert() {
+        let alert = format_quarantine_alert(
+            "7",
+            "42",
+            "invoice.exe",
+            &ScanVerdict::Flagged { scanner: "ClamAV", reason: "Win.Trojan.Agent FOUND".to_string() },
+        );
+        assert!(alert.contains("<#42>: message from <@7> deleted"));
+        assert!(alert.contains("`invoice.exe` · Scanner: ClamAV · Reason: Win.Trojan.Agent FOUND"));
+    }
+}
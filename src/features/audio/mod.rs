@@ -1,11 +1,14 @@
 //! # Audio Feature
 //!
-//! Whisper-powered audio transcription with configurable output modes.
+//! Whisper-powered audio transcription with configurable output modes, and a
+//! streaming path with live partial transcripts for short voice messages.
 //!
-//! - **Version**: 1.3.0
+//! - **Version**: 1.5.0
 //! - **Since**: 0.1.0
 //! - **Toggleable**: true
 
+pub mod streaming;
 pub mod transcriber;
 
+pub use streaming::{choose_path, StreamingTranscriber, TranscriptionPath, PARTIAL_EDIT_INTERVAL, STREAMING_MAX_SECONDS, STREAMING_MODEL};
 pub use transcriber::{AudioTranscriber, TranscriptionResult};
@@ -0,0 +1,199 @@
+//! # Feature: Streaming Transcription
+//!
+//! Faster transcription for short recordings such as Discord voice messages:
+//! the audio is sent to OpenAI's streaming transcription endpoint and text
+//! deltas arrive as server-sent events while the model is still listening, so
+//! the bot can post a partial transcript and edit it live. Longer recordings,
+//! formats the streaming models don't take, and recordings of unknown length
+//! use the batch Whisper flow.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with live partial transcripts and latency metrics
+
+use crate::core::{BotError, Result};
+use tracing::{debug, error};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Model used on the streaming path (Whisper itself doesn't stream)
+pub const STREAMING_MODEL: &str = "gpt-4o-mini-transcribe";
+
+/// Recordings at most this long (seconds) take the streaming path
+pub const STREAMING_MAX_SECONDS: f64 = 60.0;
+
+/// Least time between edits of the live transcript message, to stay clear of Discord's rate limits
+pub const PARTIAL_EDIT_INTERVAL: Duration = Duration::from_millis(1200);
+
+/// Formats the streaming models accept as uploaded, including Discord's Ogg/Opus voice messages
+const STREAMING_FORMATS: &[&str] = &[".ogg", ".oga", ".mp3", ".m4a", ".wav", ".webm", ".mp4", ".mpeg", ".mpga", ".flac"];
+
+/// How a recording gets transcribed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptionPath {
+    Streaming,
+    Batch,
+}
+
+impl TranscriptionPath {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TranscriptionPath::Streaming => "streaming",
+            TranscriptionPath::Batch => "batch",
+        }
+    }
+}
+
+/// Streaming for short recordings in a format the streaming model takes; a duration
+/// of 0 means ffprobe couldn't tell, so those go to the batch path
+pub fn choose_path(filename: &str, duration_seconds: f64) -> TranscriptionPath {
+    let lower = filename.to_lowercase();
+    let streamable = STREAMING_FORMATS.iter().any(|ext| lower.ends_with(ext));
+    if streamable && duration_seconds > 0.0 && duration_seconds <= STREAMING_MAX_SECONDS {
+        TranscriptionPath::Streaming
+    } else {
+        TranscriptionPath::Batch
+    }
+}
+
+/// A transcription server-sent event
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranscriptEvent {
+    /// More text for the running transcript
+    Delta(String),
+    /// The complete transcript
+    Done(String),
+}
+
+/// Take the complete events out of `buffer`, leaving any partial event for the next chunk
+pub fn drain_events(buffer: &mut String) -> Vec<TranscriptEvent> {
+    let mut events = Vec::new();
+    while let Some(end) = buffer.find("\n\n") {
+        let block: String = buffer.drain(..end + 2).collect();
+        for data in block.lines().filter_map(|line| line.strip_prefix("data:")) {
+            let Ok(json) = serde_json::from_str::<Value>(data.trim()) else {
+                continue;
+            };
+            match json.get("type").and_then(Value::as_str) {
+                Some("transcript.text.delta") => {
+                    if let Some(delta) = json.get("delta").and_then(Value::as_str) {
+                        events.push(TranscriptEvent::Delta(delta.to_string()));
+                    }
+                }
+                Some("transcript.text.done") => {
+                    if let Some(text) = json.get("text").and_then(Value::as_str) {
+                        events.push(TranscriptEvent::Done(text.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+/// Result of the streaming path, with how long the first text took to arrive
+#[derive(Debug)]
+pub struct StreamedTranscript {
+    pub text: String,
+    pub first_partial_ms: Option<u64>,
+}
+
+#[derive(Clone)]
+pub struct StreamingTranscriber {
+    openai_api_key: String,
+    client: reqwest::Client,
+}
+
+impl StreamingTranscriber {
+    pub fn new(openai_api_key: String) -> Self {
+        StreamingTranscriber { openai_api_key, client: reqwest::Client::new() }
+    }
+
+    /// Stream a recording's transcript, publishing the text so far to `partials` as it grows
+    pub async fn transcribe(&self, audio: Vec<u8>, filename: &str, partials: &watch::Sender<String>) -> Result<StreamedTranscript> {
+        let start = Instant::now();
+        let part = reqwest::multipart::Part::bytes(audio).file_name(filename.to_string());
+        let form = reqwest::multipart::Form::new()
+            .part("file", part)
+            .text("model", STREAMING_MODEL)
+            .text("stream", "true");
+        let mut response = self
+            .client
+            .post("https://api.openai.com/v1/audio/transcriptions")
+            .bearer_auth(&self.openai_api_key)
+            .multipart(form)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            error!("Streaming transcription failed ({status}): {body}");
+            return Err(BotError::openai(format!("Streaming transcription failed (status {status})")));
+        }
+
+        // Bytes are only decoded up to the end of a complete event, so characters split across chunks survive
+        let mut pending: Vec<u8> = Vec::new();
+        let mut buffer = String::new();
+        let mut text = String::new();
+        let mut first_partial_ms = None;
+        while let Some(chunk) = response.chunk().await? {
+            pending.extend_from_slice(&chunk);
+            let Some(end) = pending.windows(2).rposition(|w| w == b"\n\n") else {
+                continue;
+            };
+            buffer.push_str(&String::from_utf8_lossy(&pending.drain(..end + 2).collect::<Vec<_>>()));
+            for event in drain_events(&mut buffer) {
+                match event {
+                    TranscriptEvent::Delta(delta) => {
+                        first_partial_ms.get_or_insert(start.elapsed().as_millis() as u64);
+                        text.push_str(&delta);
+                        partials.send_replace(text.clone());
+                    }
+                    TranscriptEvent::Done(done) => text = done,
+                }
+            }
+        }
+        debug!("Streaming transcription finished in {:?} ({} chars)", start.elapsed(), text.len());
+        Ok(StreamedTranscript { text, first_partial_ms })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_path() {
+        assert_eq!(choose_path("voice-message.ogg", 12.0), TranscriptionPath::Streaming);
+        assert_eq!(choose_path("memo.M4A", 60.0), TranscriptionPath::Streaming);
+        assert_eq!(choose_path("podcast.mp3", 600.0), TranscriptionPath::Batch);
+        assert_eq!(choose_path("clip.mkv", 5.0), TranscriptionPath::Batch);
+        assert_eq!(choose_path("voice-message.ogg", 0.0), TranscriptionPath::Batch);
+    }
+
+    #[test]
+    fn test_drain_events_across_chunks() {
+        let mut buffer = String::from(
+            "data: {\"type\":\"transcript.text.delta\",\"delta\":\"Hello\"}\n\ndata: {\"type\":\"transcript.text.del",
+        );
+        assert_eq!(drain_events(&mut buffer), vec![TranscriptEvent::Delta("Hello".to_string())]);
+
+        buffer.push_str("ta\",\"delta\":\" there\"}\n\ndata: {\"type\":\"transcript.text.done\",\"text\":\"Hello there.\"}\n\n");
+        assert_eq!(
+            drain_events(&mut buffer),
+            vec![TranscriptEvent::Delta(" there".to_string()), TranscriptEvent::Done("Hello there.".to_string())]
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_drain_events_ignores_other_events() {
+        let mut buffer = String::from("event: ping\ndata: {\"type\":\"ping\"}\n\ndata: [DONE]\n\n");
+        assert!(drain_events(&mut buffer).is_empty());
+    }
+}
@@ -3,28 +3,40 @@
 //! Whisper-powered transcription of audio attachments with automatic format conversion.
 //! Supports a wide range of audio and video formats via ffmpeg conversion.
 //!
-//! - **Version**: 1.4.0
+//! - **Version**: 1.7.0
 //! - **Since**: 0.1.0
 //! - **Toggleable**: true
 //!
 //! ## Changelog
+//! - 1.7.0: Downloads and transcriptions run in an `openai.transcription` tracing span
+//! - 1.6.0: Short recordings take the streaming path with live partial transcripts, with latency recorded per path
+//! - 1.5.0: Formats needing ffmpeg, and transcription without curl, are refused up front when startup checks found the tool missing
 //! - 1.4.0: Added audio duration tracking for usage metrics via ffprobe
 //! - 1.3.0: Fixed double-posting bug, added configurable output mode (transcription_only/with_commentary)
 //! - 1.2.0: Added ffmpeg conversion for broader format support
 //! - 1.1.0: Added configurable transcription modes (always/mention_only/disabled)
 //! - 1.0.0: Initial release with Whisper API integration
 
-use anyhow::Result;
-use log::{debug, error, info, warn};
+use super::streaming::{choose_path, StreamingTranscriber, TranscriptionPath};
+use crate::core::{BotError, Result};
+use tracing::{debug, error, info, instrument, warn};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 use tokio::fs;
+use tokio::sync::watch;
 
 /// Result of audio transcription with duration for usage tracking
 #[derive(Debug)]
 pub struct TranscriptionResult {
     pub text: String,
     pub duration_seconds: f64,
+    /// Path that produced the text (batch after a failed streaming attempt)
+    pub path: TranscriptionPath,
+    /// Time from the request to the first partial text, on the streaming path
+    pub first_partial_ms: Option<u64>,
+    /// Time spent transcribing, excluding the download
+    pub latency_ms: u64,
 }
 
 /// Formats that OpenAI Whisper supports natively (no conversion needed)
@@ -38,25 +50,43 @@ const SUPPORTED_FORMATS: &[&str] = &[
     ".flac", ".ogg", ".aac", ".wma", ".mov", ".avi", ".mkv", ".opus", ".m4v",
 ];
 
+/// Cleared by the startup checks when ffmpeg is missing
+static FFMPEG_AVAILABLE: AtomicBool = AtomicBool::new(true);
+
+/// Cleared by the startup checks when curl (used for downloads and uploads) is missing
+static CURL_AVAILABLE: AtomicBool = AtomicBool::new(true);
+
 #[derive(Clone)]
 pub struct AudioTranscriber {
     openai_api_key: String,
+    streaming: StreamingTranscriber,
 }
 
 impl AudioTranscriber {
     pub fn new(openai_api_key: String) -> Self {
-        AudioTranscriber { openai_api_key }
+        AudioTranscriber { streaming: StreamingTranscriber::new(openai_api_key.clone()), openai_api_key }
+    }
+
+    /// Record whether the external tools were found at startup
+    pub fn set_tools_available(ffmpeg: bool, curl: bool) {
+        FFMPEG_AVAILABLE.store(ffmpeg, Ordering::Relaxed);
+        CURL_AVAILABLE.store(curl, Ordering::Relaxed);
+    }
+
+    /// Whether transcription can run at all on this host
+    pub fn is_available() -> bool {
+        CURL_AVAILABLE.load(Ordering::Relaxed)
     }
 
     pub async fn transcribe_file(&self, file_path: &str) -> Result<String> {
         info!("Transcribing audio file: {file_path}");
 
         if !self.is_audio_file(file_path) {
-            return Err(anyhow::anyhow!("File is not a supported audio format"));
+            return Err(BotError::validation("File is not a supported audio format"));
         }
 
         if fs::metadata(file_path).await.is_err() {
-            return Err(anyhow::anyhow!("Audio file not found: {}", file_path));
+            return Err(BotError::internal(format!("Audio file not found: {}", file_path)));
         }
 
         let output = Command::new("curl")
@@ -78,15 +108,15 @@ impl AudioTranscriber {
                 Ok(text.to_string())
             } else if let Some(error) = json.get("error") {
                 error!("OpenAI API error: {error}");
-                Err(anyhow::anyhow!("OpenAI API error: {}", error))
+                Err(BotError::openai(format!("OpenAI API error: {}", error)))
             } else {
                 error!("Unexpected response format: {response}");
-                Err(anyhow::anyhow!("Unexpected response format"))
+                Err(BotError::openai("Unexpected response format"))
             }
         } else {
             let error_msg = String::from_utf8_lossy(&output.stderr);
             error!("Transcription failed: {error_msg}");
-            Err(anyhow::anyhow!("Transcription failed: {}", error_msg))
+            Err(BotError::openai(format!("Transcription failed: {}", error_msg)))
         }
     }
 
@@ -136,13 +166,11 @@ impl AudioTranscriber {
             // Check if ffmpeg is not installed
             if stderr.contains("not found") || stderr.contains("No such file") {
                 error!("FFmpeg not found. Install with: apt install ffmpeg");
-                return Err(anyhow::anyhow!(
-                    "FFmpeg is required for this format but not installed. Install with: apt install ffmpeg"
-                ));
+                return Err(BotError::internal("FFmpeg is required for this format but not installed. Install with: apt install ffmpeg"));
             }
 
             error!("FFmpeg conversion failed: {}", stderr);
-            Err(anyhow::anyhow!("FFmpeg conversion failed: {}", stderr))
+            Err(BotError::internal(format!("FFmpeg conversion failed: {}", stderr)))
         }
     }
 
@@ -155,6 +183,15 @@ impl AudioTranscriber {
             .unwrap_or(false)
     }
 
+    /// Check if curl is available on the system
+    pub fn is_curl_available() -> bool {
+        Command::new("curl")
+            .arg("--version")
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
     /// Get audio duration in seconds using ffprobe
     fn get_audio_duration(file_path: &str) -> f64 {
         let output = Command::new("ffprobe")
@@ -185,62 +222,109 @@ impl AudioTranscriber {
         }
     }
 
-    /// Download and transcribe with duration tracking
-    pub async fn download_and_transcribe_with_duration(&self, url: &str, filename: &str) -> Result<TranscriptionResult> {
-        let temp_file = format!("/tmp/discord_audio_{filename}");
-        let mut converted_file: Option<String> = None;
+    /// Download an attachment to a temp file, refusing what this host can't transcribe
+    fn download(&self, url: &str, filename: &str) -> Result<String> {
+        if !Self::is_available() {
+            return Err(BotError::internal("Audio transcription is unavailable: curl is not installed on the bot's host"));
+        }
+        if self.needs_conversion(filename) && !FFMPEG_AVAILABLE.load(Ordering::Relaxed) {
+            return Err(BotError::internal("This format needs ffmpeg, which is not installed on the bot's host. Send mp3, m4a, wav or webm instead"));
+        }
 
+        let temp_file = format!("/tmp/discord_audio_{filename}");
         info!("Downloading audio attachment: {filename}");
 
-        // Download the file
         let output = Command::new("curl")
             .args(["-o", &temp_file, url])
             .output()?;
 
         if !output.status.success() {
-            return Err(anyhow::anyhow!("Failed to download audio file"));
+            return Err(BotError::internal("Failed to download audio file"));
+        }
+        Ok(temp_file)
+    }
+
+    /// Transcribe a downloaded file with Whisper, converting it first if needed
+    async fn transcribe_batch(&self, temp_file: &str, filename: &str) -> Result<String> {
+        if !self.needs_conversion(filename) {
+            return self.transcribe_file(temp_file).await;
+        }
+        info!("Format requires conversion: {}", filename);
+        let mp3_path = self.convert_to_mp3(temp_file)?;
+        let transcription = self.transcribe_file(&mp3_path).await;
+        if let Err(e) = fs::remove_file(&mp3_path).await {
+            warn!("Failed to cleanup converted file {mp3_path}: {e}");
         }
+        transcription
+    }
+
+    /// Download and transcribe with duration tracking
+    pub async fn download_and_transcribe_with_duration(&self, url: &str, filename: &str) -> Result<TranscriptionResult> {
+        let (partials, _) = watch::channel(String::new());
+        self.transcribe_attachment(url, filename, &partials, false).await
+    }
 
-        // Check if conversion is needed
-        let file_to_transcribe = if self.needs_conversion(filename) {
-            info!("Format requires conversion: {}", filename);
+    /// Download and transcribe, streaming short recordings and publishing the text so far
+    /// to `partials`. Long recordings, and streaming failures, use the batch Whisper flow.
+    pub async fn download_and_transcribe_live(
+        &self,
+        url: &str,
+        filename: &str,
+        partials: &watch::Sender<String>,
+    ) -> Result<TranscriptionResult> {
+        self.transcribe_attachment(url, filename, partials, true).await
+    }
+
+    #[instrument(name = "openai.transcription", skip(self, url, partials))]
+    async fn transcribe_attachment(
+        &self,
+        url: &str,
+        filename: &str,
+        partials: &watch::Sender<String>,
+        allow_streaming: bool,
+    ) -> Result<TranscriptionResult> {
+        let temp_file = self.download(url, filename)?;
+
+        // Get audio duration before transcription (for usage tracking and picking a path)
+        let duration_seconds = Self::get_audio_duration(&temp_file);
+        info!("Audio duration: {:.1}s", duration_seconds);
 
-            match self.convert_to_mp3(&temp_file) {
-                Ok(mp3_path) => {
-                    converted_file = Some(mp3_path.clone());
-                    mp3_path
+        let start = Instant::now();
+        let mut path = if allow_streaming { choose_path(filename, duration_seconds) } else { TranscriptionPath::Batch };
+        let mut first_partial_ms = None;
+        let mut streamed = None;
+        if path == TranscriptionPath::Streaming {
+            let streaming = match fs::read(&temp_file).await {
+                Ok(audio) => self.streaming.transcribe(audio, filename, partials).await,
+                Err(e) => Err(e.into()),
+            };
+            match streaming {
+                Ok(transcript) => {
+                    first_partial_ms = transcript.first_partial_ms;
+                    streamed = Some(transcript.text);
                 }
                 Err(e) => {
-                    // Cleanup original file before returning error
-                    let _ = fs::remove_file(&temp_file).await;
-                    return Err(e);
+                    warn!("Streaming transcription failed, falling back to Whisper: {e}");
+                    path = TranscriptionPath::Batch;
                 }
             }
-        } else {
-            temp_file.clone()
+        }
+        let transcription = match streamed {
+            Some(text) => Ok(text),
+            None => self.transcribe_batch(&temp_file, filename).await,
         };
 
-        // Get audio duration before transcription (for usage tracking)
-        let duration_seconds = Self::get_audio_duration(&file_to_transcribe);
-        info!("Audio duration: {:.1}s", duration_seconds);
-
-        // Transcribe the file
-        let transcription = self.transcribe_file(&file_to_transcribe).await;
-
         // Cleanup temp files
         if let Err(e) = fs::remove_file(&temp_file).await {
             warn!("Failed to cleanup temp file {temp_file}: {e}");
         }
 
-        if let Some(ref converted) = converted_file {
-            if let Err(e) = fs::remove_file(converted).await {
-                warn!("Failed to cleanup converted file {converted}: {e}");
-            }
-        }
-
         transcription.map(|text| TranscriptionResult {
             text,
             duration_seconds,
+            path,
+            first_partial_ms,
+            latency_ms: start.elapsed().as_millis() as u64,
         })
     }
 
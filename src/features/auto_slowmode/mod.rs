@@ -0,0 +1,16 @@
+//! # Auto Slowmode Feature
+//!
+//! Watches message velocity in opted-in channels and temporarily raises Discord
+//! slowmode during spikes (raids, drama), reverting it automatically and logging
+//! each action to the guild's mod log channel.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod monitor;
+
+pub use monitor::{
+    apply_auto_slowmode, slowmode_revert_loop, validate_config, VelocityMonitor,
+    DEFAULT_SLOWMODE_CONFIG,
+};
@@ -0,0 +1,240 @@
+//! # Feature: Auto Slowmode
+//!
+//! Tracks per-channel message timestamps in memory and, when a channel exceeds
+//! its configured messages-per-window threshold, sets Discord slowmode for a
+//! limited time. Applied slowmodes are stored so the revert survives restarts;
+//! a background loop restores the channel's previous slowmode when they expire.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with per-channel thresholds, automatic revert and mod log
+
+use crate::database::{ActiveSlowmode, Database, SlowmodeConfig};
+use crate::features::moderation::post_mod_log;
+use crate::core::Result;
+use dashmap::DashMap;
+use log::{info, warn};
+use serenity::http::Http;
+use serenity::model::channel::Channel;
+use serenity::model::id::ChannelId;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often expired slowmodes are reverted
+pub const REVERT_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Discord's maximum slowmode (6 hours)
+pub const MAX_SLOWMODE_SECONDS: i64 = 21600;
+
+/// Thresholds used when `/auto_slowmode enable` is given no options
+pub const DEFAULT_SLOWMODE_CONFIG: SlowmodeConfig = SlowmodeConfig {
+    messages: 20,
+    window_seconds: 30,
+    slowmode_seconds: 10,
+    duration_minutes: 10,
+};
+
+/// Check thresholds are usable, returning a user-facing error otherwise
+pub fn validate_config(config: &SlowmodeConfig) -> Result<(), &'static str> {
+    if !(3..=500).contains(&config.messages) {
+        return Err("Message threshold must be between 3 and 500.");
+    }
+    if !(5..=600).contains(&config.window_seconds) {
+        return Err("Window must be between 5 and 600 seconds.");
+    }
+    if !(1..=MAX_SLOWMODE_SECONDS).contains(&config.slowmode_seconds) {
+        return Err("Slowmode must be between 1 and 21600 seconds.");
+    }
+    if !(1..=1440).contains(&config.duration_minutes) {
+        return Err("Duration must be between 1 and 1440 minutes.");
+    }
+    Ok(())
+}
+
+/// Sliding-window message counter per channel, shared between handler clones
+#[derive(Clone, Default)]
+pub struct VelocityMonitor {
+    recent: Arc<DashMap<String, Vec<Instant>>>,
+}
+
+impl VelocityMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a message and return how many messages the channel has seen within the window
+    pub fn record(&self, channel_id: &str, now: Instant, window: Duration) -> usize {
+        let mut entry = self.recent.entry(channel_id.to_string()).or_default();
+        entry.retain(|&time| now.duration_since(time) < window);
+        entry.push(now);
+        entry.len()
+    }
+
+    /// Forget a channel's history (after a spike has been handled)
+    pub fn reset(&self, channel_id: &str) {
+        self.recent.remove(channel_id);
+    }
+}
+
+/// Mod log line for an applied slowmode
+pub fn format_applied(channel_id: &str, count: usize, config: &SlowmodeConfig) -> String {
+    format!(
+        "🐢 **Auto slowmode** enabled in <#{channel_id}>: {count} messages in {}s (threshold {}). \
+        Slowmode set to {}s for {} minutes.",
+        config.window_seconds, config.messages, config.slowmode_seconds, config.duration_minutes
+    )
+}
+
+/// Mod log line for a reverted slowmode
+pub fn format_reverted(active: &ActiveSlowmode) -> String {
+    let previous = if active.previous_seconds == 0 {
+        "off".to_string()
+    } else {
+        format!("{}s", active.previous_seconds)
+    };
+    format!(
+        "✅ **Auto slowmode** ended in <#{}>: slowmode restored to {previous}.",
+        active.channel_id
+    )
+}
+
+/// Raise slowmode in a channel that crossed its threshold. Returns whether slowmode was applied.
+pub async fn apply_auto_slowmode(
+    http: &Http,
+    db: &Database,
+    guild_id: &str,
+    channel_id: &str,
+    count: usize,
+    config: &SlowmodeConfig,
+) -> Result<bool> {
+    if db.is_auto_slowmode_active(channel_id).await? {
+        return Ok(false);
+    }
+
+    let channel = ChannelId(channel_id.parse::<u64>()?);
+    let previous = match channel.to_channel(http).await? {
+        Channel::Guild(guild_channel) => guild_channel.rate_limit_per_user.unwrap_or(0) as i64,
+        _ => return Ok(false),
+    };
+    // Moderators already slowed the channel down at least this much
+    if previous >= config.slowmode_seconds {
+        return Ok(false);
+    }
+
+    if !db
+        .start_auto_slowmode(guild_id, channel_id, previous, config.slowmode_seconds, config.duration_minutes)
+        .await?
+    {
+        return Ok(false);
+    }
+    if let Err(e) = channel
+        .edit(http, |c| c.rate_limit_per_user(config.slowmode_seconds as u64))
+        .await
+    {
+        db.finish_auto_slowmode(channel_id).await?;
+        return Err(e.into());
+    }
+
+    info!(
+        "🐢 Auto slowmode applied in channel {channel_id}: {count} messages in {}s, slowmode {}s for {} minutes",
+        config.window_seconds, config.slowmode_seconds, config.duration_minutes
+    );
+    if let Err(e) = post_mod_log(http, db, guild_id, &format_applied(channel_id, count, config)).await {
+        warn!("Failed to post auto slowmode to mod log for guild {guild_id}: {e}");
+    }
+    Ok(true)
+}
+
+async fn revert_auto_slowmode(http: &Http, db: &Database, active: &ActiveSlowmode) -> Result<()> {
+    let channel = ChannelId(active.channel_id.parse::<u64>()?);
+    let current = match channel.to_channel(http).await? {
+        Channel::Guild(guild_channel) => guild_channel.rate_limit_per_user.unwrap_or(0) as i64,
+        _ => active.applied_seconds,
+    };
+    // Only restore if a moderator hasn't changed slowmode in the meantime
+    if current == active.applied_seconds {
+        channel
+            .edit(http, |c| c.rate_limit_per_user(active.previous_seconds as u64))
+            .await?;
+    }
+    db.finish_auto_slowmode(&active.channel_id).await?;
+
+    info!("🐢 Auto slowmode reverted in channel {}", active.channel_id);
+    if let Err(e) = post_mod_log(http, db, &active.guild_id, &format_reverted(active)).await {
+        warn!("Failed to post auto slowmode revert to mod log for guild {}: {e}", active.guild_id);
+    }
+    Ok(())
+}
+
+/// Background task that reverts automatic slowmodes once they expire
+pub async fn slowmode_revert_loop(http: Arc<Http>, db: Arc<Database>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(REVERT_CHECK_INTERVAL_SECS));
+    info!("Auto slowmode revert task started (checks every {REVERT_CHECK_INTERVAL_SECS}s)");
+
+    loop {
+        interval.tick().await;
+        let due = match db.get_due_auto_slowmodes().await {
+            Ok(due) => due,
+            Err(e) => {
+                warn!("Failed to load expired auto slowmodes: {}", e);
+                continue;
+            }
+        };
+
+        for active in due {
+            if let Err(e) = revert_auto_slowmode(&http, &db, &active).await {
+                warn!("Failed to revert auto slowmode in channel {}: {}", active.channel_id, e);
+                // Deleted channel or missing permission - don't retry forever
+                if let Err(e) = db.finish_auto_slowmode(&active.channel_id).await {
+                    warn!("Failed to clear auto slowmode for channel {}: {}", active.channel_id, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_velocity_window() {
+        let monitor = VelocityMonitor::new();
+        let start = Instant::now();
+        let window = Duration::from_secs(10);
+        assert_eq!(monitor.record("1", start, window), 1);
+        assert_eq!(monitor.record("1", start + Duration::from_secs(5), window), 2);
+        assert_eq!(monitor.record("2", start + Duration::from_secs(5), window), 1);
+        // The first message has left the window
+        assert_eq!(monitor.record("1", start + Duration::from_secs(12), window), 2);
+        monitor.reset("1");
+        assert_eq!(monitor.record("1", start + Duration::from_secs(13), window), 1);
+    }
+
+    #[test]
+    fn test_validate_config() {
+        assert!(validate_config(&DEFAULT_SLOWMODE_CONFIG).is_ok());
+        let too_slow = SlowmodeConfig { slowmode_seconds: 30000, ..DEFAULT_SLOWMODE_CONFIG };
+        assert!(validate_config(&too_slow).is_err());
+        let no_messages = SlowmodeConfig { messages: 1, ..DEFAULT_SLOWMODE_CONFIG };
+        assert!(validate_config(&no_messages).is_err());
+    }
+
+    #[test]
+    fn test_mod_log_lines() {
+        let applied = format_applied("42", 25, &DEFAULT_SLOWMODE_CONFIG);
+        assert!(applied.contains("<#42>: 25 messages in 30s (threshold 20)"));
+        assert!(applied.contains("10s for 10 minutes"));
+
+        let reverted = format_reverted(&ActiveSlowmode {
+            channel_id: "42".to_string(),
+            guild_id: "1".to_string(),
+            previous_seconds: 0,
+            applied_seconds: 10,
+        });
+        assert!(reverted.contains("restored to off"));
+    }
+}
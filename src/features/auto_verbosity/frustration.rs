@@ -0,0 +1,165 @@
+//! # Feature: Frustration Detection
+//!
+//! Spots a frustrated user from their latest message: a short message that
+//! reads as angry (frustrated phrases, shouting, `!!`/`?!`), or a question they
+//! already asked a few turns ago. With the `auto_verbosity` guild setting
+//! enabled, such an exchange is answered concisely and directly, opening with
+//! an apology, whatever the channel's verbosity.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with short-angry and repeated-question signals
+
+use std::collections::HashSet;
+
+/// Longest message, in characters, still counted as short
+pub const SHORT_MESSAGE_CHARS: usize = 120;
+
+/// Earlier user turns compared against for a repeated question
+pub const REPEAT_LOOKBACK: usize = 6;
+
+/// Word overlap (Jaccard) from which two messages are the same question
+pub const REPEAT_SIMILARITY: f64 = 0.7;
+
+/// Verbosity used for the exchange instead of the channel's
+pub const FRUSTRATED_VERBOSITY: &str = "concise";
+
+/// System prompt suffix for a frustrated user
+pub const FRUSTRATION_INSTRUCTION: &str = "\n\n## Response Adjustment\nThe user seems frustrated with the previous answers. \
+Open with one short, sincere sentence of apology, then answer the question directly in as few sentences as possible. \
+Skip greetings, filler, caveats and offers to elaborate.";
+
+/// Phrases that read as frustration, matched case-insensitively as substrings
+const FRUSTRATED_PHRASES: &[&str] = &[
+    "useless", "not what i asked", "that's not what", "thats not what", "not helpful", "doesn't help",
+    "doesnt help", "didn't help", "didnt help", "doesn't work", "doesnt work", "didn't work", "didnt work",
+    "still not", "still doesn't", "still doesnt", "i already said", "i already told", "i just said",
+    "you're not listening", "youre not listening", "not listening", "answer the question",
+    "just tell me", "just answer", "for the last time", "how many times", "again?!", "wtf", "ffs",
+    "omg", "ugh", "seriously", "come on", "are you kidding", "stupid bot", "dumb bot",
+];
+
+/// Why a message was taken as frustrated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrustrationSignal {
+    /// Short and angry: frustrated phrasing, shouting or stacked `!`/`?`
+    ShortAngry,
+    /// Same question as one of the user's recent messages
+    RepeatedQuestion,
+}
+
+impl FrustrationSignal {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FrustrationSignal::ShortAngry => "short_angry",
+            FrustrationSignal::RepeatedQuestion => "repeated_question",
+        }
+    }
+}
+
+/// Signals in `message`, given the user's earlier messages oldest first (the current one excluded)
+pub fn detect_frustration(message: &str, earlier: &[&str]) -> Vec<FrustrationSignal> {
+    let mut signals = Vec::new();
+    if is_short_angry(message) {
+        signals.push(FrustrationSignal::ShortAngry);
+    }
+    let asked = words(message);
+    if asked.len() >= 3
+        && earlier
+            .iter()
+            .rev()
+            .take(REPEAT_LOOKBACK)
+            .any(|previous| similarity(&asked, &words(previous)) >= REPEAT_SIMILARITY)
+    {
+        signals.push(FrustrationSignal::RepeatedQuestion);
+    }
+    signals
+}
+
+/// The user turns of `(role, content)` history, oldest first, without a trailing copy of the current message
+pub fn earlier_user_messages<'a>(history: &'a [(String, String)], current: &str) -> Vec<&'a str> {
+    let mut turns: Vec<&str> = history.iter().filter(|(role, _)| role == "user").map(|(_, content)| content.as_str()).collect();
+    if turns.last().is_some_and(|last| last.trim() == current.trim()) {
+        turns.pop();
+    }
+    turns
+}
+
+fn is_short_angry(message: &str) -> bool {
+    let message = message.trim();
+    if message.is_empty() || message.chars().count() > SHORT_MESSAGE_CHARS {
+        return false;
+    }
+    let lower = message.to_lowercase();
+    let phrase = FRUSTRATED_PHRASES.iter().any(|p| lower.contains(p));
+    let punctuation = ["!!", "?!", "!?", "???"].iter().any(|p| message.contains(p));
+    let letters: Vec<char> = message.chars().filter(|c| c.is_alphabetic()).collect();
+    let shouting = letters.len() >= 6 && letters.iter().filter(|c| c.is_uppercase()).count() * 10 >= letters.len() * 7;
+    phrase || punctuation || shouting
+}
+
+/// Lowercase words of a message, ignoring punctuation
+fn words(message: &str) -> HashSet<String> {
+    message
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_angry() {
+        assert_eq!(detect_frustration("that's not what I asked", &[]), vec![FrustrationSignal::ShortAngry]);
+        assert_eq!(detect_frustration("WHY IS THIS BROKEN", &[]), vec![FrustrationSignal::ShortAngry]);
+        assert_eq!(detect_frustration("why?!", &[]), vec![FrustrationSignal::ShortAngry]);
+        assert!(detect_frustration("How do I reset my password?", &[]).is_empty());
+        assert!(detect_frustration("OK", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_long_messages_are_not_short_angry() {
+        let long = format!("ugh {}", "this is a long and detailed explanation of the problem ".repeat(3));
+        assert!(detect_frustration(&long, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_repeated_question() {
+        let earlier = ["How do I reset my password?", "thanks"];
+        assert_eq!(
+            detect_frustration("how do I reset my password", &earlier),
+            vec![FrustrationSignal::RepeatedQuestion]
+        );
+        assert!(detect_frustration("How do I change my avatar?", &earlier).is_empty());
+        assert!(detect_frustration("thanks", &["thanks"]).is_empty());
+    }
+
+    #[test]
+    fn test_earlier_user_messages() {
+        let turn = |role: &str, content: &str| (role.to_string(), content.to_string());
+        let history = vec![turn("user", "How do I reset my password?"), turn("assistant", "Go to settings."), turn("user", "how do I reset my password")];
+        assert_eq!(earlier_user_messages(&history, "how do I reset my password"), vec!["How do I reset my password?"]);
+        assert_eq!(earlier_user_messages(&history, "something new").len(), 2);
+    }
+
+    #[test]
+    fn test_repeat_lookback() {
+        let mut earlier = vec!["How do I reset my password?"];
+        earlier.extend(["something else entirely"; REPEAT_LOOKBACK]);
+        assert!(detect_frustration("How do I reset my password?", &earlier).is_empty());
+    }
+}
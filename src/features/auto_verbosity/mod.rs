@@ -0,0 +1,11 @@
+//! # Auto-Verbosity Feature
+//!
+//! Switches to short, direct answers with an apology when a user seems frustrated.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod frustration;
+
+pub use frustration::{detect_frustration, earlier_user_messages, FrustrationSignal, FRUSTRATED_VERBOSITY, FRUSTRATION_INSTRUCTION};
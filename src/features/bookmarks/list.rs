@@ -0,0 +1,138 @@
+//! # Feature: Bookmark List
+//!
+//! Paging and embed rendering for `/bookmarks list`. Each entry shows the
+//! snippet saved with the bookmark, a jump link to the original message, when
+//! it was saved and the id `/bookmarks delete` takes.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Pages are turned by the shared `Paginator`
+//! - 1.0.0: Initial release with paginated embeds and jump links
+
+use crate::core::discord_limits::fit_embed;
+use crate::database::Bookmark;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serenity::builder::CreateEmbed;
+
+/// Bookmarks shown per page
+pub const BOOKMARK_PAGE_SIZE: usize = 5;
+
+/// Most bookmarks one user can keep
+pub const MAX_BOOKMARKS: usize = 200;
+
+/// Number of pages needed for `total` bookmarks (at least one)
+pub fn bookmark_page_count(total: usize) -> usize {
+    total.div_ceil(BOOKMARK_PAGE_SIZE).max(1)
+}
+
+/// Link that opens the bookmarked message in Discord
+pub fn jump_link(bookmark: &Bookmark) -> String {
+    format!(
+        "https://discord.com/channels/{}/{}/{}",
+        bookmark.guild_id.as_deref().unwrap_or("@me"),
+        bookmark.channel_id,
+        bookmark.message_id
+    )
+}
+
+/// Render one page of a user's bookmarks; `page` is zero-based and clamped to the last page
+pub fn build_bookmarks_embed(bookmarks: &[Bookmark], page: usize) -> CreateEmbed {
+    let pages = bookmark_page_count(bookmarks.len());
+    let page = page.min(pages - 1);
+
+    let description = if bookmarks.is_empty() {
+        "No bookmarks yet. Right-click a message and choose **Apps → Bookmark Message** to save it.".to_string()
+    } else {
+        bookmarks
+            .iter()
+            .skip(page * BOOKMARK_PAGE_SIZE)
+            .take(BOOKMARK_PAGE_SIZE)
+            .map(|bookmark| {
+                let saved = NaiveDateTime::parse_from_str(&bookmark.created_at, "%Y-%m-%d %H:%M:%S")
+                    .map(|ts| format!(" · saved <t:{}:R>", DateTime::<Utc>::from_naive_utc_and_offset(ts, Utc).timestamp()))
+                    .unwrap_or_default();
+                let snippet = if bookmark.name.is_empty() { "*(no preview)*" } else { bookmark.name.as_str() };
+                let mut entry = format!("`#{}` {}\n[Jump to message]({}){}", bookmark.id, snippet, jump_link(bookmark), saved);
+                if let Some(note) = &bookmark.note {
+                    entry.push_str(&format!("\n📝 {note}"));
+                }
+                entry
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title("🔖 Your bookmarks")
+        .description(description)
+        .footer(|f| {
+            f.text(format!(
+                "Page {}/{} · {} saved · /bookmarks delete <id> removes one",
+                page + 1,
+                pages,
+                bookmarks.len()
+            ))
+        })
+        .color(0xF1C40F);
+    fit_embed(&mut embed);
+    embed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(id: i64, guild_id: Option<&str>) -> Bookmark {
+        Bookmark {
+            id,
+            guild_id: guild_id.map(str::to_string),
+            channel_id: "20".to_string(),
+            message_id: format!("{}", 300 + id),
+            name: format!("**alice:** message {id}"),
+            note: None,
+            created_at: "2024-05-01 12:00:00".to_string(),
+        }
+    }
+
+    fn description(embed: &CreateEmbed) -> String {
+        embed.0.get("description").and_then(|d| d.as_str()).unwrap_or_default().to_string()
+    }
+
+    #[test]
+    fn test_page_count() {
+        assert_eq!(bookmark_page_count(0), 1);
+        assert_eq!(bookmark_page_count(BOOKMARK_PAGE_SIZE), 1);
+        assert_eq!(bookmark_page_count(BOOKMARK_PAGE_SIZE + 1), 2);
+    }
+
+    #[test]
+    fn test_jump_link_uses_me_for_dms() {
+        assert_eq!(jump_link(&bookmark(1, Some("10"))), "https://discord.com/channels/10/20/301");
+        assert_eq!(jump_link(&bookmark(1, None)), "https://discord.com/channels/@me/20/301");
+    }
+
+    #[test]
+    fn test_embed_pages_and_clamps() {
+        let bookmarks: Vec<Bookmark> = (1..=7).map(|id| bookmark(id, Some("10"))).collect();
+
+        let first = description(&build_bookmarks_embed(&bookmarks, 0));
+        assert!(first.contains("`#1`") && first.contains("`#5`"));
+        assert!(!first.contains("`#6`"));
+        assert!(first.contains("<t:1714564800:R>"));
+
+        let last = description(&build_bookmarks_embed(&bookmarks, 9));
+        assert!(last.contains("`#6`") && last.contains("`#7`"));
+    }
+
+    #[test]
+    fn test_embed_shows_notes_and_empty_state() {
+        let mut noted = bookmark(1, None);
+        noted.note = Some("read later".to_string());
+        assert!(description(&build_bookmarks_embed(&[noted], 0)).contains("📝 read later"));
+        assert!(description(&build_bookmarks_embed(&[], 0)).contains("No bookmarks yet"));
+    }
+}
@@ -0,0 +1,14 @@
+//! # Bookmarks Feature
+//!
+//! Personal message bookmarks. Anyone saves a message with the "Bookmark
+//! Message" context-menu action, browses their saved messages with
+//! `/bookmarks list` (paginated, with jump links) and removes them with
+//! `/bookmarks delete`. Bookmarks are private to the user who made them.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod list;
+
+pub use list::{bookmark_page_count, build_bookmarks_embed, jump_link, BOOKMARK_PAGE_SIZE, MAX_BOOKMARKS};
@@ -0,0 +1,156 @@
+//! # Feature: Budget Alerts
+//!
+//! Background task that posts recorded budget alerts: a warning embed in the
+//! channel of the request that crossed 80% of a budget, and a notification to
+//! the guild's `mod_log_channel` (or its owner by DM) when a budget is used up.
+//! When SMTP is configured every alert is also emailed to the bot owner.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Alerts are emailed to the bot owner when SMTP is configured
+//! - 1.0.1: Alert embeds are fitted to Discord's limits with `fit_embed`
+//! - 1.0.0: Initial release
+
+use super::guard::budget_fallback_model;
+use super::limits::{exceeded_text, warning_text, BudgetLevel, BudgetScope};
+use crate::core::discord_limits::fit_embed;
+use crate::core::Result;
+use crate::database::{BudgetAlert, Database};
+use crate::features::analytics::EmailSender;
+use crate::features::moderation::mod_log::mod_log_channel;
+use log::{info, warn};
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, GuildId};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often recorded alerts are posted
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Alerts posted per check
+const ALERTS_PER_CHECK: i64 = 20;
+
+/// Post recorded budget alerts, forever, emailing each one too when `email` is set
+pub async fn budget_alert_loop(database: Database, http: Arc<Http>, email: Option<EmailSender>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    info!("Budget alert task started (checks every 30s)");
+
+    loop {
+        interval.tick().await;
+        let alerts = match database.get_pending_budget_alerts(ALERTS_PER_CHECK).await {
+            Ok(alerts) => alerts,
+            Err(e) => {
+                warn!("Failed to load pending budget alerts: {e}");
+                continue;
+            }
+        };
+        for alert in alerts {
+            if let Some(email) = &email {
+                if let Err(e) = email_alert(&database, email, &alert).await {
+                    warn!("Failed to email budget alert {} for guild {}: {e}", alert.id, alert.guild_id);
+                }
+            }
+            if let Err(e) = post_alert(&database, &http, &alert).await {
+                warn!("Failed to post budget alert {} for guild {}: {e}", alert.id, alert.guild_id);
+            }
+            // Marked either way, so an unreachable channel doesn't retry forever
+            if let Err(e) = database.mark_budget_alert_sent(alert.id).await {
+                warn!("Failed to mark budget alert {} sent: {e}", alert.id);
+            }
+        }
+    }
+}
+
+/// Title and description of an alert; `None` for an unknown scope
+async fn alert_text(database: &Database, alert: &BudgetAlert) -> Result<Option<(String, String)>> {
+    let Some(scope) = BudgetScope::parse(&alert.scope) else {
+        return Ok(None);
+    };
+    if alert.level == BudgetLevel::Warning.as_str() {
+        return Ok(Some(warning_text(scope, &alert.user_id, alert.spent_usd, alert.budget_usd)));
+    }
+    let fallback = budget_fallback_model(database, &alert.guild_id).await?;
+    Ok(Some(exceeded_text(scope, &alert.user_id, alert.spent_usd, alert.budget_usd, fallback.as_deref())))
+}
+
+async fn email_alert(database: &Database, email: &EmailSender, alert: &BudgetAlert) -> Result<()> {
+    let Some((title, description)) = alert_text(database, alert).await? else {
+        return Ok(());
+    };
+    let subject = format!("Budget alert for guild {}", alert.guild_id);
+    email.send_report(&subject, &format!("{title}\n\n{description}")).await
+}
+
+async fn post_alert(database: &Database, http: &Http, alert: &BudgetAlert) -> Result<()> {
+    let Some((title, description)) = alert_text(database, alert).await? else {
+        return Ok(());
+    };
+
+    if alert.level == BudgetLevel::Warning.as_str() {
+        let Ok(channel) = alert.channel_id.parse::<u64>() else {
+            return Ok(());
+        };
+        ChannelId(channel)
+            .send_message(http, |m| {
+                m.embed(|e| {
+                    e.title(title).description(description).color(0xF1C40F);
+                    fit_embed(e)
+                })
+            })
+            .await?;
+        return Ok(());
+    }
+
+    let channel = match mod_log_channel(database, &alert.guild_id).await? {
+        Some(channel) => channel,
+        None => {
+            let owner = GuildId(alert.guild_id.parse()?).to_partial_guild(http).await?.owner_id;
+            owner.create_dm_channel(http).await?.id
+        }
+    };
+    channel
+        .send_message(http, |m| {
+            m.embed(|e| {
+                e.title(title).description(description).color(0xE74C3C);
+                fit_embed(e)
+            })
+        })
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(level: BudgetLevel) -> BudgetAlert {
+        BudgetAlert {
+            id: 1,
+            guild_id: "100".to_string(),
+            scope: "guild".to_string(),
+            user_id: String::new(),
+            level: level.as_str().to_string(),
+            channel_id: "200".to_string(),
+            spent_usd: 9.5,
+            budget_usd: 10.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_alerts_emailed_when_smtp_is_set() {
+        let database = Database::new(":memory:", 1).await.unwrap();
+        let (email, stub) = EmailSender::stub();
+
+        email_alert(&database, &email, &alert(BudgetLevel::Warning)).await.unwrap();
+        email_alert(&database, &email, &alert(BudgetLevel::Exceeded)).await.unwrap();
+
+        let messages = stub.messages().await;
+        assert_eq!(messages.len(), 2);
+        assert!(messages.iter().all(|(_, body)| body.contains("Subject: Budget alert for guild 100")));
+        assert!(messages[0].1.contains("95% of this server's monthly AI budget used"));
+        assert!(messages[1].1.contains("Monthly AI budget used up"));
+    }
+}
@@ -0,0 +1,85 @@
+//! # Feature: Budget Guard
+//!
+//! Checked before each AI request in a guild. Compares the guild's spend this
+//! month and the requesting member's spend today, from the `openai_usage_daily`
+//! aggregates, with the guild's budgets. Crossing 80% or 100% of a budget
+//! records an alert (once per period) for the alert task to post. Over budget,
+//! the request switches to `budget_fallback_model` when one is set and is
+//! refused otherwise.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use super::limits::{budget_level, parse_budget_usd, refusal_message, BudgetDecision, BudgetLevel, BudgetScope};
+use crate::core::Result;
+use crate::database::Database;
+use log::info;
+
+/// The guild's `budget_fallback_model`, if one is set
+pub async fn budget_fallback_model(database: &Database, guild_id: &str) -> Result<Option<String>> {
+    Ok(database
+        .get_guild_setting(guild_id, "budget_fallback_model")
+        .await?
+        .filter(|model| !model.is_empty() && model != "disabled"))
+}
+
+/// Enforces guild and per-member AI budgets
+#[derive(Clone)]
+pub struct BudgetGuard {
+    database: Database,
+}
+
+impl BudgetGuard {
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+
+    /// Decide whether a request may go ahead. DMs and guilds without budgets are always allowed.
+    pub async fn check(&self, guild_id: Option<&str>, user_id: Option<&str>, channel_id: Option<&str>) -> Result<BudgetDecision> {
+        let Some(guild_id) = guild_id else {
+            return Ok(BudgetDecision::Allow);
+        };
+        let now = chrono::Utc::now();
+
+        let mut exceeded = None;
+        for scope in [BudgetScope::Guild, BudgetScope::User] {
+            let Some(budget) = self.database.get_guild_setting(guild_id, scope.setting_key()).await?.as_deref().and_then(parse_budget_usd) else {
+                continue;
+            };
+            let since = scope.period_start(now);
+            let (spent, alert_user) = match (scope, user_id) {
+                (BudgetScope::Guild, _) => (self.database.get_guild_spend_since(guild_id, &since).await?, ""),
+                (BudgetScope::User, Some(uid)) => (self.database.get_user_guild_spend_since(uid, guild_id, &since).await?, uid),
+                (BudgetScope::User, None) => continue,
+            };
+            let level = budget_level(spent, budget);
+            if level == BudgetLevel::Ok {
+                continue;
+            }
+
+            let recorded = self
+                .database
+                .record_budget_alert(guild_id, scope.as_str(), alert_user, &scope.period(now), level.as_str(), channel_id.unwrap_or(""), spent, budget)
+                .await?;
+            if recorded {
+                info!("💸 Guild {guild_id} reached {} of its {} budget (${spent:.2} of ${budget:.2})", level.as_str(), scope.as_str());
+            }
+            if level == BudgetLevel::Exceeded && exceeded.is_none() {
+                exceeded = Some((scope, budget));
+            }
+        }
+
+        let Some((scope, budget)) = exceeded else {
+            return Ok(BudgetDecision::Allow);
+        };
+        let reason = refusal_message(scope, budget);
+        Ok(match budget_fallback_model(&self.database, guild_id).await? {
+            Some(model) => BudgetDecision::Downgrade { model, reason },
+            None => BudgetDecision::Refuse(reason),
+        })
+    }
+}
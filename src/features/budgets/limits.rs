@@ -0,0 +1,217 @@
+//! # Feature: Budget Limits
+//!
+//! Parsing of the `monthly_budget_usd` and `per_user_daily_budget_usd` guild
+//! settings, how close a spend is to its budget, the period each budget covers,
+//! and the texts shown when a budget nears or hits its limit.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use chrono::{DateTime, Utc};
+
+/// Share of a budget at which the warning is shown
+pub const WARNING_RATIO: f64 = 0.8;
+
+/// Largest budget accepted, in USD
+pub const MAX_BUDGET_USD: f64 = 100_000.0;
+
+/// Which budget a spend counts against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetScope {
+    /// The guild's spend this calendar month (UTC), `monthly_budget_usd`
+    Guild,
+    /// One member's spend in the guild today (UTC), `per_user_daily_budget_usd`
+    User,
+}
+
+impl BudgetScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetScope::Guild => "guild",
+            BudgetScope::User => "user",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "guild" => Some(BudgetScope::Guild),
+            "user" => Some(BudgetScope::User),
+            _ => None,
+        }
+    }
+
+    /// Guild setting holding this budget
+    pub fn setting_key(&self) -> &'static str {
+        match self {
+            BudgetScope::Guild => "monthly_budget_usd",
+            BudgetScope::User => "per_user_daily_budget_usd",
+        }
+    }
+
+    /// Key of the period the budget covers at `now`: the month for guilds, the day for users
+    pub fn period(&self, now: DateTime<Utc>) -> String {
+        match self {
+            BudgetScope::Guild => now.format("%Y-%m").to_string(),
+            BudgetScope::User => now.format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    /// First `openai_usage_daily` date counted against the budget at `now`
+    pub fn period_start(&self, now: DateTime<Utc>) -> String {
+        match self {
+            BudgetScope::Guild => now.format("%Y-%m-01").to_string(),
+            BudgetScope::User => now.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// How much of a budget is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BudgetLevel {
+    Ok,
+    /// At least [`WARNING_RATIO`] of the budget
+    Warning,
+    /// The whole budget or more
+    Exceeded,
+}
+
+impl BudgetLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetLevel::Ok => "ok",
+            BudgetLevel::Warning => "warning",
+            BudgetLevel::Exceeded => "exceeded",
+        }
+    }
+}
+
+/// What to do with an AI request given the budgets
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetDecision {
+    Allow,
+    /// Over budget: answer chat with this cheaper model instead; requests with no cheaper model are refused with `reason`
+    Downgrade { model: String, reason: String },
+    /// Over budget with no fallback model: refuse, telling the user why
+    Refuse(String),
+}
+
+/// A budget in USD from a setting value; `disabled`, zero, negative or unparsable values mean no budget
+pub fn parse_budget_usd(value: &str) -> Option<f64> {
+    let amount = value.trim().trim_start_matches('$').parse::<f64>().ok()?;
+    (amount.is_finite() && amount > 0.0 && amount <= MAX_BUDGET_USD).then_some(amount)
+}
+
+pub fn budget_level(spent: f64, budget: f64) -> BudgetLevel {
+    if spent >= budget {
+        BudgetLevel::Exceeded
+    } else if spent >= budget * WARNING_RATIO {
+        BudgetLevel::Warning
+    } else {
+        BudgetLevel::Ok
+    }
+}
+
+fn percent(spent: f64, budget: f64) -> u64 {
+    (spent / budget * 100.0).floor().max(0.0) as u64
+}
+
+/// Shown to the user when a request is refused
+pub fn refusal_message(scope: BudgetScope, budget: f64) -> String {
+    match scope {
+        BudgetScope::Guild => format!(
+            "This server has used its ${budget:.2} monthly AI budget. AI replies are paused until next month; an admin can raise `monthly_budget_usd`."
+        ),
+        BudgetScope::User => format!(
+            "You've used your ${budget:.2} daily AI budget in this server. Try again tomorrow (UTC)."
+        ),
+    }
+}
+
+/// Title and description of the warning posted in the channel at [`WARNING_RATIO`]
+pub fn warning_text(scope: BudgetScope, user_id: &str, spent: f64, budget: f64) -> (String, String) {
+    let pct = percent(spent, budget);
+    match scope {
+        BudgetScope::Guild => (
+            format!("⚠️ {pct}% of this server's monthly AI budget used"),
+            format!("This server has used ${spent:.2} of its ${budget:.2} AI budget this month. AI features stop or switch to a cheaper model once it's used up."),
+        ),
+        BudgetScope::User => (
+            format!("⚠️ {pct}% of your daily AI budget used"),
+            format!("<@{user_id}>, you've used ${spent:.2} of your ${budget:.2} daily AI budget in this server. It resets at midnight UTC."),
+        ),
+    }
+}
+
+/// Title and description of the admin notification when a budget is used up
+pub fn exceeded_text(scope: BudgetScope, user_id: &str, spent: f64, budget: f64, fallback_model: Option<&str>) -> (String, String) {
+    let effect = match fallback_model {
+        Some(model) => format!("Requests now use `{model}` (`budget_fallback_model`)."),
+        None => "Requests are now refused. Set `budget_fallback_model` to switch to a cheaper model instead.".to_string(),
+    };
+    match scope {
+        BudgetScope::Guild => (
+            "🛑 Monthly AI budget used up".to_string(),
+            format!("This server has spent ${spent:.2} of its ${budget:.2} `monthly_budget_usd` this month. {effect}"),
+        ),
+        BudgetScope::User => (
+            "🛑 Member's daily AI budget used up".to_string(),
+            format!("<@{user_id}> has spent ${spent:.2} of the ${budget:.2} `per_user_daily_budget_usd` today. {effect}"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_budget_usd() {
+        assert_eq!(parse_budget_usd("10"), Some(10.0));
+        assert_eq!(parse_budget_usd(" $2.50 "), Some(2.5));
+        assert_eq!(parse_budget_usd("0"), None);
+        assert_eq!(parse_budget_usd("-5"), None);
+        assert_eq!(parse_budget_usd("disabled"), None);
+        assert_eq!(parse_budget_usd("NaN"), None);
+        assert_eq!(parse_budget_usd("1e9"), None);
+    }
+
+    #[test]
+    fn test_budget_level() {
+        assert_eq!(budget_level(0.0, 10.0), BudgetLevel::Ok);
+        assert_eq!(budget_level(7.99, 10.0), BudgetLevel::Ok);
+        assert_eq!(budget_level(8.0, 10.0), BudgetLevel::Warning);
+        assert_eq!(budget_level(10.0, 10.0), BudgetLevel::Exceeded);
+        assert!(BudgetLevel::Exceeded > BudgetLevel::Warning);
+    }
+
+    #[test]
+    fn test_periods() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 23, 59, 0).unwrap();
+        assert_eq!(BudgetScope::Guild.period(now), "2026-10");
+        assert_eq!(BudgetScope::Guild.period_start(now), "2026-10-01");
+        assert_eq!(BudgetScope::User.period(now), "2026-10-16");
+        assert_eq!(BudgetScope::User.period_start(now), "2026-10-16");
+        for scope in [BudgetScope::Guild, BudgetScope::User] {
+            assert_eq!(BudgetScope::parse(scope.as_str()), Some(scope));
+        }
+    }
+
+    #[test]
+    fn test_texts() {
+        let (title, _) = warning_text(BudgetScope::Guild, "1", 8.5, 10.0);
+        assert!(title.starts_with("⚠️ 85%"));
+        let (_, description) = warning_text(BudgetScope::User, "42", 0.4, 0.5);
+        assert!(description.contains("<@42>") && description.contains("$0.40 of your $0.50"));
+
+        let (_, description) = exceeded_text(BudgetScope::Guild, "1", 10.2, 10.0, Some("gpt-4o-mini"));
+        assert!(description.contains("now use `gpt-4o-mini`"));
+        let (_, description) = exceeded_text(BudgetScope::User, "42", 0.5, 0.5, None);
+        assert!(description.contains("refused"));
+        assert!(refusal_message(BudgetScope::Guild, 10.0).contains("$10.00 monthly"));
+    }
+}
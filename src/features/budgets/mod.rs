@@ -0,0 +1,22 @@
+//! # Budgets Feature
+//!
+//! Monthly guild and daily per-member caps on AI spend, set with the
+//! `monthly_budget_usd` and `per_user_daily_budget_usd` guild settings. Each
+//! AI request is checked against them first; over budget it is refused, or
+//! answered with `budget_fallback_model` when one is set. A warning is posted
+//! in the channel at 80% and admins are notified at 100%; with SMTP configured
+//! both are emailed to the bot owner too.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod alerts;
+pub mod guard;
+pub mod limits;
+
+pub use alerts::budget_alert_loop;
+pub use guard::{budget_fallback_model, BudgetGuard};
+pub use limits::{
+    budget_level, parse_budget_usd, refusal_message, BudgetDecision, BudgetLevel, BudgetScope, MAX_BUDGET_USD, WARNING_RATIO,
+};
@@ -0,0 +1,409 @@
+//! # Feature: Expression Evaluator
+//!
+//! Deterministic arithmetic for `/calc` and the `calculate` AI tool. Supports
+//! `+ - * / % ^`, factorial `!`, parentheses, implicit multiplication by
+//! parentheses/constants (`2(3+4)`, `2pi`), the constants `pi`, `e`, `tau`,
+//! and common functions (`sqrt`, `cbrt`, `abs`, `exp`, `ln`, `log`, `log2`,
+//! trigonometry in radians, `floor`, `ceil`, `round`, `min`, `max`).
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with a recursive-descent parser over f64
+
+use crate::core::{BotError, Result};
+
+/// Longest expression accepted, to bound parsing work
+const MAX_EXPRESSION_LEN: usize = 500;
+
+/// Deepest nesting of parentheses and unary operators
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\n' | '_' => i += 1,
+            '0'..='9' | '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == ',' && is_digit_group(&chars, i)) {
+                    i += 1;
+                }
+                // Scientific notation: 1.5e3, 2E-4
+                if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                    let mut j = i + 1;
+                    if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_ascii_digit() {
+                        i = j;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
+                let text: String = chars[start..i].iter().filter(|c| **c != ',').collect();
+                let value = text.parse::<f64>().map_err(|_| BotError::validation(format!("Invalid number `{}`", text)))?;
+                tokens.push(Token::Number(value));
+            }
+            'a'..='z' | 'A'..='Z' | 'π' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == 'π') {
+                    i += 1;
+                }
+                let ident: String = chars[start..i].iter().collect::<String>().to_lowercase();
+                tokens.push(Token::Ident(if ident == "π" { "pi".to_string() } else { ident }));
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' | '!' => {
+                tokens.push(Token::Op(c));
+                i += 1;
+            }
+            '×' | '·' => {
+                tokens.push(Token::Op('*'));
+                i += 1;
+            }
+            '÷' => {
+                tokens.push(Token::Op('/'));
+                i += 1;
+            }
+            '−' => {
+                tokens.push(Token::Op('-'));
+                i += 1;
+            }
+            '(' | '[' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' | ']' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' | ';' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            other => return Err(BotError::validation(format!("Unexpected character `{}`", other))),
+        }
+    }
+    Ok(tokens)
+}
+
+/// A comma inside a number is a thousands separator only when exactly three digits follow
+fn is_digit_group(chars: &[char], comma: usize) -> bool {
+    let digits = chars[comma + 1..].iter().take_while(|c| c.is_ascii_digit()).count();
+    digits == 3 && comma > 0 && chars[comma - 1].is_ascii_digit()
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn enter(&mut self) -> Result<()> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(BotError::validation("Expression is nested too deeply"));
+        }
+        Ok(())
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<f64> {
+        let mut value = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    // term := unary (('*' | '/' | '%') unary | implicit-multiplication)*
+    fn term(&mut self) -> Result<f64> {
+        let mut value = self.unary()?;
+        loop {
+            match self.peek().cloned() {
+                Some(Token::Op(op @ ('*' | '/' | '%'))) => {
+                    self.pos += 1;
+                    let rhs = self.unary()?;
+                    value = match op {
+                        '*' => value * rhs,
+                        '/' if rhs == 0.0 => return Err(BotError::validation("Division by zero")),
+                        '/' => value / rhs,
+                        _ if rhs == 0.0 => return Err(BotError::validation("Modulo by zero")),
+                        _ => value % rhs,
+                    };
+                }
+                // `2(3+4)`, `2pi`, `3 sqrt(2)`
+                Some(Token::LParen | Token::Ident(_)) => value *= self.unary()?,
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    // unary := ('-' | '+') unary | power
+    fn unary(&mut self) -> Result<f64> {
+        match self.peek() {
+            Some(Token::Op('-')) => {
+                self.pos += 1;
+                self.enter()?;
+                let value = -self.unary()?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            Some(Token::Op('+')) => {
+                self.pos += 1;
+                self.enter()?;
+                let value = self.unary()?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            _ => self.power(),
+        }
+    }
+
+    // power := postfix ('^' unary)?   (right-associative, binds tighter than unary minus on the left)
+    fn power(&mut self) -> Result<f64> {
+        let base = self.postfix()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.pos += 1;
+            self.enter()?;
+            let exponent = self.unary()?;
+            self.depth -= 1;
+            return Ok(base.powf(exponent));
+        }
+        Ok(base)
+    }
+
+    // postfix := primary '!'*
+    fn postfix(&mut self) -> Result<f64> {
+        let mut value = self.primary()?;
+        while let Some(Token::Op('!')) = self.peek() {
+            self.pos += 1;
+            value = factorial(value)?;
+        }
+        Ok(value)
+    }
+
+    fn primary(&mut self) -> Result<f64> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(n),
+            Some(Token::LParen) => {
+                self.enter()?;
+                let value = self.expr()?;
+                self.depth -= 1;
+                match self.next() {
+                    Some(Token::RParen) => Ok(value),
+                    _ => Err(BotError::validation("Missing closing parenthesis")),
+                }
+            }
+            Some(Token::Ident(name)) => match name.as_str() {
+                "pi" => Ok(std::f64::consts::PI),
+                "e" => Ok(std::f64::consts::E),
+                "tau" => Ok(std::f64::consts::TAU),
+                _ => {
+                    let args = self.arguments(&name)?;
+                    apply_function(&name, &args)
+                }
+            },
+            Some(token) => Err(BotError::validation(format!("Unexpected {:?}", token))),
+            None => Err(BotError::validation("Expression ended unexpectedly")),
+        }
+    }
+
+    fn arguments(&mut self, name: &str) -> Result<Vec<f64>> {
+        if self.next() != Some(Token::LParen) {
+            return Err(BotError::validation(format!("Unknown name `{}`", name)));
+        }
+        self.enter()?;
+        let mut args = vec![self.expr()?];
+        loop {
+            match self.next() {
+                Some(Token::Comma) => args.push(self.expr()?),
+                Some(Token::RParen) => break,
+                _ => return Err(BotError::validation(format!("Missing closing parenthesis after `{}(`", name))),
+            }
+        }
+        self.depth -= 1;
+        Ok(args)
+    }
+}
+
+fn factorial(n: f64) -> Result<f64> {
+    if n < 0.0 || n.fract() != 0.0 {
+        return Err(BotError::validation("Factorial needs a non-negative whole number"));
+    }
+    if n > 170.0 {
+        return Err(BotError::validation("Factorial is too large"));
+    }
+    Ok((1..=n as u64).map(|k| k as f64).product())
+}
+
+fn apply_function(name: &str, args: &[f64]) -> Result<f64> {
+    let one = |f: fn(f64) -> f64| -> Result<f64> {
+        match args {
+            [x] => Ok(f(*x)),
+            _ => Err(BotError::validation(format!("`{}` takes one argument", name))),
+        }
+    };
+    match name {
+        "sqrt" => match args {
+            [x] if *x < 0.0 => Err(BotError::validation("Square root of a negative number")),
+            _ => one(f64::sqrt),
+        },
+        "cbrt" => one(f64::cbrt),
+        "abs" => one(f64::abs),
+        "exp" => one(f64::exp),
+        "ln" => one(f64::ln),
+        "log" | "log10" => one(f64::log10),
+        "log2" => one(f64::log2),
+        "sin" => one(f64::sin),
+        "cos" => one(f64::cos),
+        "tan" => one(f64::tan),
+        "asin" => one(f64::asin),
+        "acos" => one(f64::acos),
+        "atan" => one(f64::atan),
+        "floor" => one(f64::floor),
+        "ceil" => one(f64::ceil),
+        "round" => one(f64::round),
+        "min" if !args.is_empty() => Ok(args.iter().copied().fold(f64::INFINITY, f64::min)),
+        "max" if !args.is_empty() => Ok(args.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        _ => Err(BotError::validation(format!("Unknown function `{}`", name))),
+    }
+}
+
+/// Evaluate an arithmetic expression
+pub fn evaluate(expression: &str) -> Result<f64> {
+    let expression = expression.trim().trim_end_matches('=').trim();
+    if expression.is_empty() {
+        return Err(BotError::validation("Empty expression"));
+    }
+    if expression.chars().count() > MAX_EXPRESSION_LEN {
+        return Err(BotError::validation(format!("Expression is longer than {} characters", MAX_EXPRESSION_LEN)));
+    }
+    let mut parser = Parser { tokens: tokenize(expression)?, pos: 0, depth: 0 };
+    let value = parser.expr()?;
+    if let Some(token) = parser.peek() {
+        return Err(BotError::validation(format!("Unexpected {:?}", token)));
+    }
+    if value.is_nan() {
+        return Err(BotError::validation("Result is undefined"));
+    }
+    if value.is_infinite() {
+        return Err(BotError::validation("Result is too large"));
+    }
+    Ok(value)
+}
+
+/// Format a result: whole numbers without decimals (with thousands separators),
+/// others to 12 significant digits, very large or small magnitudes in scientific notation
+pub fn format_number(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let magnitude = value.abs();
+    if !(1e-6..1e15).contains(&magnitude) {
+        let text = format!("{:.10e}", value);
+        let (mantissa, exponent) = text.split_once('e').unwrap_or((&text, "0"));
+        return format!("{}e{}", mantissa.trim_end_matches('0').trim_end_matches('.'), exponent);
+    }
+    if value.fract() == 0.0 {
+        let digits = format!("{}", magnitude as u64);
+        let mut grouped = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(c);
+        }
+        return if value < 0.0 { format!("-{grouped}") } else { grouped };
+    }
+    let decimals = (11 - magnitude.log10().floor() as i32).clamp(0, 15) as usize;
+    let text = format!("{:.*}", decimals, value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(expression: &str) -> f64 {
+        evaluate(expression).unwrap()
+    }
+
+    #[test]
+    fn test_precedence_and_associativity() {
+        assert_eq!(eval("2 + 3 * 4"), 14.0);
+        assert_eq!(eval("(2 + 3) * 4"), 20.0);
+        assert_eq!(eval("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(eval("-2 ^ 2"), -4.0);
+        assert_eq!(eval("10 - 4 - 3"), 3.0);
+        assert_eq!(eval("17 % 5"), 2.0);
+        assert_eq!(eval("5!"), 120.0);
+    }
+
+    #[test]
+    fn test_implicit_multiplication_and_symbols() {
+        assert_eq!(eval("2(3 + 4)"), 14.0);
+        assert!((eval("2pi") - std::f64::consts::TAU).abs() < 1e-12);
+        assert_eq!(eval("6 × 7"), 42.0);
+        assert_eq!(eval("1,234,567 + 1"), 1_234_568.0);
+        assert_eq!(eval("1.5e3 ="), 1500.0);
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(eval("sqrt(144)"), 12.0);
+        assert_eq!(eval("max(3, 9, 4)"), 9.0);
+        assert_eq!(eval("log(1000)"), 3.0);
+        assert!((eval("sin(pi / 2)") - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(evaluate("1 / 0").is_err());
+        assert!(evaluate("2 +").is_err());
+        assert!(evaluate("(1 + 2").is_err());
+        assert!(evaluate("foo(2)").is_err());
+        assert!(evaluate("sqrt(-1)").is_err());
+        assert!(evaluate("2.5!").is_err());
+        assert!(evaluate(&"(".repeat(100)).is_err());
+        assert!(evaluate("rm -rf").is_err());
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(1234567.0), "1,234,567");
+        assert_eq!(format_number(-42.0), "-42");
+        assert_eq!(format_number(0.1 + 0.2), "0.3");
+        assert_eq!(format_number(1.0 / 3.0), "0.333333333333");
+        assert_eq!(format_number(2.5e20), "2.5e20");
+    }
+}
@@ -0,0 +1,17 @@
+//! # Calculator Feature
+//!
+//! Deterministic math via `/calc` and as a tool the chat model calls instead of
+//! doing arithmetic itself. Optional Wolfram Alpha fallback (`WOLFRAM_APP_ID`)
+//! for unit conversions and anything beyond plain arithmetic.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod expression;
+pub mod tools;
+pub mod wolfram;
+
+pub use expression::{evaluate, format_number};
+pub use tools::CalculatorTools;
+pub use wolfram::WolframClient;
@@ -0,0 +1,133 @@
+//! # Feature: AI Calculator Tools
+//!
+//! Function definitions offered to the chat model so it delegates arithmetic
+//! instead of guessing: `calculate` (the local evaluator) and, when configured,
+//! `wolfram_alpha`. Tool results are always returned as text for the model to
+//! phrase, including errors, so a bad expression never fails the whole reply.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Registered in the chat [`ToolRegistry`](crate::features::tools::ToolRegistry)
+//! - 1.0.0: Initial release with `calculate` and `wolfram_alpha`
+
+use super::expression::{evaluate, format_number};
+use super::wolfram::WolframClient;
+use log::warn;
+use crate::features::llm::FunctionDefinition;
+use crate::features::tools::{Tool, ToolContext};
+use serde_json::{json, Value};
+use serenity::async_trait;
+
+const CALCULATE: &str = "calculate";
+const WOLFRAM_ALPHA: &str = "wolfram_alpha";
+
+#[derive(Clone, Default)]
+pub struct CalculatorTools {
+    wolfram: Option<WolframClient>,
+}
+
+impl CalculatorTools {
+    pub fn new(wolfram: Option<WolframClient>) -> Self {
+        CalculatorTools { wolfram }
+    }
+
+    pub fn wolfram(&self) -> Option<&WolframClient> {
+        self.wolfram.as_ref()
+    }
+
+    /// Run a tool call from the model and return the text result
+    pub async fn call(&self, name: &str, arguments: &str) -> String {
+        let args: Value = serde_json::from_str(arguments).unwrap_or(Value::Null);
+        match name {
+            CALCULATE => match args.get("expression").and_then(|v| v.as_str()) {
+                Some(expression) => calculate(expression),
+                None => "Error: missing `expression` argument".to_string(),
+            },
+            WOLFRAM_ALPHA => match (&self.wolfram, args.get("query").and_then(|v| v.as_str())) {
+                (Some(wolfram), Some(query)) => match wolfram.query(query).await {
+                    Ok(Some(answer)) => answer,
+                    Ok(None) => "Wolfram Alpha has no answer for that query".to_string(),
+                    Err(e) => {
+                        warn!("Wolfram Alpha tool call failed: {e}");
+                        "Error: Wolfram Alpha is unavailable right now".to_string()
+                    }
+                },
+                (None, _) => "Error: Wolfram Alpha is not configured".to_string(),
+                (_, None) => "Error: missing `query` argument".to_string(),
+            },
+            other => format!("Error: unknown tool `{other}`"),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for CalculatorTools {
+    fn definitions(&self) -> Vec<FunctionDefinition> {
+        let mut definitions = vec![FunctionDefinition {
+            name: CALCULATE.to_string(),
+            description: "Evaluate an arithmetic expression exactly. Always use this instead of doing math yourself. \
+                 Supports + - * / % ^ !, parentheses, pi, e, sqrt, abs, exp, ln, log, log2, sin, cos, tan \
+                 (radians), asin, acos, atan, floor, ceil, round, min, max."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "expression": { "type": "string", "description": "Expression to evaluate, e.g. (17.5 * 3) / 4" }
+                },
+                "required": ["expression"]
+            }),
+        }];
+        if self.wolfram.is_some() {
+            definitions.push(FunctionDefinition {
+                name: WOLFRAM_ALPHA.to_string(),
+                description: "Ask Wolfram Alpha for things the calculator can't do: unit conversions, equations, \
+                              calculus, or math involving real-world quantities."
+                    .to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string", "description": "Natural-language or math query" }
+                    },
+                    "required": ["query"]
+                }),
+            });
+        }
+        definitions
+    }
+
+    async fn run(&self, name: &str, arguments: &str, _context: &ToolContext) -> String {
+        self.call(name, arguments).await
+    }
+}
+
+/// Evaluate an expression for the model, reporting errors as text
+fn calculate(expression: &str) -> String {
+    match evaluate(expression) {
+        Ok(value) => format_number(value),
+        Err(e) => format!("Error: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_calculate_tool_call() {
+        let tools = CalculatorTools::default();
+        assert_eq!(tools.call(CALCULATE, r#"{"expression": "12.5 * 8"}"#).await, "100");
+        assert!(tools.call(CALCULATE, r#"{"expression": "1/0"}"#).await.starts_with("Error"));
+        assert!(tools.call(CALCULATE, "not json").await.starts_with("Error"));
+        assert!(tools.call(WOLFRAM_ALPHA, r#"{"query": "2+2"}"#).await.contains("not configured"));
+    }
+
+    #[test]
+    fn test_definitions_without_wolfram() {
+        let definitions = CalculatorTools::default().definitions();
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].name, CALCULATE);
+    }
+}
@@ -0,0 +1,57 @@
+//! # Feature: Wolfram Alpha Client
+//!
+//! Optional fallback for questions the local evaluator can't handle (unit
+//! conversions, symbolic math, "population of France / 3"). Uses the Short
+//! Answers API, which returns a single plain-text line. Enabled by setting
+//! `WOLFRAM_APP_ID`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with the Short Answers API
+
+use crate::core::{BotError, Result};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+const SHORT_ANSWERS_API: &str = "https://api.wolframalpha.com/v1/result";
+
+#[derive(Clone)]
+pub struct WolframClient {
+    client: reqwest::Client,
+    app_id: String,
+}
+
+impl WolframClient {
+    pub fn new(app_id: String) -> Self {
+        WolframClient {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(15))
+                .build()
+                .unwrap_or_default(),
+            app_id,
+        }
+    }
+
+    /// Ask Wolfram Alpha for a short plain-text answer. Ok(None) when it has no answer.
+    pub async fn query(&self, input: &str) -> Result<Option<String>> {
+        let response = self
+            .client
+            .get(SHORT_ANSWERS_API)
+            .query(&[("appid", self.app_id.as_str()), ("i", input), ("units", "metric")])
+            .send()
+            .await?;
+
+        match response.status() {
+            // 501 means the input couldn't be interpreted
+            StatusCode::NOT_IMPLEMENTED => Ok(None),
+            status if status.is_success() => {
+                let answer = response.text().await?.trim().to_string();
+                Ok((!answer.is_empty()).then_some(answer))
+            }
+            status => Err(BotError::internal(format!("Wolfram Alpha returned {}", status))),
+        }
+    }
+}
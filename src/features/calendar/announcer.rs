@@ -0,0 +1,355 @@
+//! # Feature: Calendar Announcements
+//!
+//! Posts reminders for upcoming events from subscribed iCal feeds (by default 24
+//! hours and 1 hour before) and a weekly agenda every Monday from 09:00 in the
+//! subscription's UTC offset. Runs on the reminder scheduler's tick; feeds are
+//! re-fetched every 15 minutes and keyword filters are applied per subscription.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with lead-time reminders, Monday agendas and keyword filters
+
+use super::ical::{parse_calendar, Calendar, CalendarEvent};
+use crate::core::discord_limits::{fit_embed, truncate, EMBED_DESCRIPTION, EMBED_FIELD_VALUE, EMBED_TITLE};
+use crate::database::{CalendarSubscription, Database};
+use crate::features::reminders::parse_duration;
+use crate::core::{BotError, Result};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, FixedOffset, Timelike, Utc, Weekday};
+use dashmap::DashMap;
+use log::{info, warn};
+use serenity::http::Http;
+use serenity::model::id::ChannelId;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Reminder lead times for new subscriptions
+pub const DEFAULT_LEAD_TIMES: &str = "24h,1h";
+
+/// How far ahead events are expanded and listed in the agenda
+const LOOKAHEAD_DAYS: i64 = 8;
+
+/// How often a feed is downloaded again
+const REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Largest feed accepted
+const MAX_FEED_BYTES: usize = 5 * 1024 * 1024;
+
+/// Local hour on Mondays from which the weekly agenda is posted
+const AGENDA_HOUR: u32 = 9;
+
+/// Most events listed in one agenda
+const MAX_AGENDA_EVENTS: usize = 40;
+
+/// Subscription ID -> when its feed was fetched and the events it had
+pub type CalendarCache = DashMap<i64, (Instant, Vec<CalendarEvent>)>;
+
+/// Accept `http(s)://` and `webcal://` feed URLs, returning the URL to fetch
+pub fn normalize_feed_url(input: &str) -> Option<String> {
+    let input = input.trim();
+    if let Some(rest) = input.strip_prefix("webcal://") {
+        return Some(format!("https://{rest}"));
+    }
+    (input.starts_with("https://") || input.starts_with("http://")).then(|| input.to_string())
+}
+
+/// Parse comma-separated lead times like `24h,1h` into seconds, longest first (at most 4, each up to a week)
+pub fn parse_lead_times(input: &str) -> Option<Vec<i64>> {
+    let mut leads = input
+        .split(',')
+        .map(|part| parse_duration(part).filter(|s| *s <= 7 * 86400))
+        .collect::<Option<Vec<i64>>>()?;
+    leads.sort_unstable_by(|a, b| b.cmp(a));
+    leads.dedup();
+    (!leads.is_empty() && leads.len() <= 4).then_some(leads)
+}
+
+/// Format a lead time in seconds compactly (`1d`, `1h30m`), in a form `parse_lead_times` reads back
+pub fn format_lead_time(seconds: i64) -> String {
+    let mut text = String::new();
+    let mut rest = seconds;
+    for (unit, size) in [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)] {
+        if rest >= size {
+            text.push_str(&format!("{}{}", rest / size, unit));
+            rest %= size;
+        }
+    }
+    text
+}
+
+/// Keyword filter: an event matches if it contains any plain keyword (or there are none)
+/// and none of the `-excluded` ones, checked case-insensitively against summary, description and location
+pub fn matches_filter(filter: Option<&str>, event: &CalendarEvent) -> bool {
+    let Some(filter) = filter else { return true };
+    let haystack = format!("{}\n{}\n{}", event.summary, event.description, event.location).to_lowercase();
+    let terms: Vec<String> = filter.split(',').map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()).collect();
+    let (excluded, included): (Vec<&String>, Vec<&String>) = terms.iter().partition(|t| t.starts_with('-'));
+
+    let excluded_hit = excluded.iter().any(|t| haystack.contains(t.trim_start_matches('-')));
+    let included_hit = included.is_empty() || included.iter().any(|t| haystack.contains(t.as_str()));
+    included_hit && !excluded_hit
+}
+
+/// The lead time whose reminder is due now: the shortest one already reached, if the event hasn't started.
+/// Reminders missed while offline collapse into the latest one.
+pub fn due_lead(start: DateTime<Utc>, now: DateTime<Utc>, leads: &[i64]) -> Option<i64> {
+    if now >= start {
+        return None;
+    }
+    leads.iter().copied().filter(|lead| start - ChronoDuration::seconds(*lead) <= now).min()
+}
+
+/// The local date to record if the weekly agenda should be posted now
+pub fn agenda_due(now: DateTime<Utc>, offset_minutes: i32, last_agenda_date: Option<&str>) -> Option<String> {
+    let offset = FixedOffset::east_opt(offset_minutes * 60)?;
+    let local = now.with_timezone(&offset);
+    let today = local.format("%Y-%m-%d").to_string();
+    let due = local.weekday() == Weekday::Mon
+        && local.hour() >= AGENDA_HOUR
+        && last_agenda_date != Some(today.as_str());
+    due.then_some(today)
+}
+
+/// Agenda text for events, grouped by day in the subscription's offset.
+/// Times use Discord timestamps so readers see their own time zone.
+pub fn format_agenda(events: &[&CalendarEvent], offset_minutes: i32) -> String {
+    if events.is_empty() {
+        return "No events this week.".to_string();
+    }
+    let offset = FixedOffset::east_opt(offset_minutes * 60).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+    let mut text = String::new();
+    let mut current_day = String::new();
+    for event in events.iter().take(MAX_AGENDA_EVENTS) {
+        let day = event.start.with_timezone(&offset).format("%A %-d %B").to_string();
+        if day != current_day {
+            text.push_str(&format!("\n**{day}**\n"));
+            current_day = day;
+        }
+        let when = if event.all_day {
+            "All day".to_string()
+        } else {
+            format!("<t:{}:t>", event.start.timestamp())
+        };
+        text.push_str(&format!("• {when} — {}\n", event.summary));
+    }
+    if events.len() > MAX_AGENDA_EVENTS {
+        text.push_str(&format!("\n…and {} more", events.len() - MAX_AGENDA_EVENTS));
+    }
+    text.trim().to_string()
+}
+
+/// Download and parse a feed, expanding events up to `LOOKAHEAD_DAYS` ahead
+pub async fn fetch_calendar(client: &reqwest::Client, url: &str, offset_minutes: i32) -> Result<Calendar> {
+    let response = client.get(url).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(BotError::internal(format!("Calendar download failed (status {})", status)));
+    }
+    if response.content_length().is_some_and(|len| len as usize > MAX_FEED_BYTES) {
+        return Err(BotError::internal(format!("Calendar is larger than {} MB", MAX_FEED_BYTES / 1024 / 1024)));
+    }
+    let body = response.text().await?;
+    if !body.contains("BEGIN:VCALENDAR") {
+        return Err(BotError::internal("Not an iCalendar feed"));
+    }
+    Ok(parse_calendar(&body, offset_minutes, Utc::now() + ChronoDuration::days(LOOKAHEAD_DAYS)))
+}
+
+async fn post_reminder(http: &Http, channel: ChannelId, subscription: &CalendarSubscription, event: &CalendarEvent) -> Result<()> {
+    let when = if event.all_day {
+        format!("All day <t:{0}:D> (<t:{0}:R>)", event.start.timestamp())
+    } else {
+        format!("<t:{0}:F> (<t:{0}:R>)", event.start.timestamp())
+    };
+    channel
+        .send_message(http, |m| {
+            m.embed(|e| {
+                e.title(truncate(&format!("📅 {}", event.summary), EMBED_TITLE))
+                    .description(&when)
+                    .color(0x3498DB)
+                    .footer(|f| f.text(&subscription.name));
+                if let Some(url) = event.url.as_deref().filter(|u| u.starts_with("http")) {
+                    e.url(url);
+                }
+                if !event.location.is_empty() {
+                    e.field("Where", truncate(&event.location, EMBED_FIELD_VALUE), false);
+                }
+                if !event.description.is_empty() {
+                    e.field("Details", truncate(&event.description, 300), false);
+                }
+                fit_embed(e)
+            })
+            .allowed_mentions(|a| a.empty_parse())
+        })
+        .await?;
+    Ok(())
+}
+
+async fn process_subscription(
+    http: &Http,
+    db: &Database,
+    client: &reqwest::Client,
+    cache: &CalendarCache,
+    subscription: &CalendarSubscription,
+) -> Result<()> {
+    let stale = cache.get(&subscription.id).is_none_or(|entry| entry.0.elapsed() >= REFRESH_INTERVAL);
+    if stale {
+        match fetch_calendar(client, &subscription.url, subscription.utc_offset_minutes).await {
+            Ok(calendar) => {
+                cache.insert(subscription.id, (Instant::now(), calendar.events));
+            }
+            // Keep announcing from the last good copy while the feed is unreachable
+            Err(e) => warn!("Failed to refresh calendar {} ({}): {}", subscription.id, subscription.name, e),
+        }
+    }
+    let Some(entry) = cache.get(&subscription.id) else { return Ok(()) };
+    let events: Vec<CalendarEvent> = entry
+        .1
+        .iter()
+        .filter(|e| matches_filter(subscription.filter.as_deref(), e))
+        .cloned()
+        .collect();
+    drop(entry);
+
+    let channel = ChannelId(subscription.channel_id.parse::<u64>()?);
+    let now = Utc::now();
+    let leads = parse_lead_times(&subscription.lead_times).unwrap_or_else(|| vec![86400, 3600]);
+
+    for event in &events {
+        let Some(lead) = due_lead(event.start, now, &leads) else { continue };
+        if db.record_calendar_announcement(subscription.id, &event.key(), lead).await? {
+            post_reminder(http, channel, subscription, event).await?;
+            info!("📅 Announced {} from calendar {} in channel {}", event.summary, subscription.id, channel);
+        }
+    }
+
+    if let Some(date) = agenda_due(now, subscription.utc_offset_minutes, subscription.last_agenda_date.as_deref()) {
+        let week_end = now + ChronoDuration::days(7);
+        let upcoming: Vec<&CalendarEvent> = events.iter().filter(|e| e.start >= now && e.start < week_end).collect();
+        let agenda = format_agenda(&upcoming, subscription.utc_offset_minutes);
+        channel
+            .send_message(http, |m| {
+                m.embed(|e| {
+                    e.title(format!("🗓️ This week — {}", subscription.name))
+                        .description(truncate(&agenda, EMBED_DESCRIPTION))
+                        .color(0x3498DB);
+                    fit_embed(e)
+                })
+                .allowed_mentions(|a| a.empty_parse())
+            })
+            .await?;
+        db.set_calendar_agenda_date(subscription.id, &date).await?;
+        // Weekly housekeeping: reminders older than the lookahead can't be sent again
+        db.prune_calendar_announcements(30).await?;
+        info!("🗓️ Posted weekly agenda for calendar {} in channel {}", subscription.id, channel);
+    }
+    Ok(())
+}
+
+/// Announce due event reminders and weekly agendas for every subscription
+pub async fn process_calendars(http: &Arc<Http>, db: &Database, client: &reqwest::Client, cache: &CalendarCache) -> Result<()> {
+    let subscriptions = db.get_calendar_subscriptions(None).await?;
+    // Drop cached feeds of removed subscriptions
+    cache.retain(|id, _| subscriptions.iter().any(|s| s.id == *id));
+
+    for subscription in &subscriptions {
+        if let Err(e) = process_subscription(http, db, client, cache, subscription).await {
+            warn!("⚠️ Failed to process calendar {} for guild {}: {}", subscription.id, subscription.guild_id, e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(summary: &str, start: DateTime<Utc>) -> CalendarEvent {
+        CalendarEvent {
+            uid: summary.to_lowercase(),
+            summary: summary.to_string(),
+            description: String::new(),
+            location: "Voice channel".to_string(),
+            url: None,
+            start,
+            all_day: false,
+        }
+    }
+
+    fn at(d: u32, h: u32, m: u32) -> DateTime<Utc> {
+        // March 2026: the 2nd is a Monday
+        Utc.with_ymd_and_hms(2026, 3, d, h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_normalize_feed_url() {
+        assert_eq!(
+            normalize_feed_url("webcal://calendar.example.org/feed.ics").as_deref(),
+            Some("https://calendar.example.org/feed.ics")
+        );
+        assert!(normalize_feed_url("https://calendar.google.com/calendar/ical/x/basic.ics").is_some());
+        assert!(normalize_feed_url("ftp://example.org/feed.ics").is_none());
+    }
+
+    #[test]
+    fn test_parse_lead_times() {
+        assert_eq!(parse_lead_times("1h, 24h"), Some(vec![86400, 3600]));
+        assert_eq!(parse_lead_times("30m,30m"), Some(vec![1800]));
+        assert_eq!(parse_lead_times("2w"), None);
+        assert_eq!(parse_lead_times("soon"), None);
+        assert_eq!(parse_lead_times("1h,2h,3h,4h,5h"), None);
+    }
+
+    #[test]
+    fn test_format_lead_time() {
+        assert_eq!(format_lead_time(86400), "1d");
+        assert_eq!(format_lead_time(5400), "1h30m");
+        for lead in [86400, 5400, 90] {
+            assert_eq!(parse_lead_times(&format_lead_time(lead)), Some(vec![lead]));
+        }
+    }
+
+    #[test]
+    fn test_matches_filter() {
+        let game = event("Game night", at(5, 18, 0));
+        assert!(matches_filter(None, &game));
+        assert!(matches_filter(Some("game, movie"), &game));
+        assert!(!matches_filter(Some("movie"), &game));
+        assert!(!matches_filter(Some("-voice"), &game));
+        assert!(matches_filter(Some("-cancelled"), &game));
+    }
+
+    #[test]
+    fn test_due_lead() {
+        let start = at(5, 18, 0);
+        let leads = [86400, 3600];
+        assert_eq!(due_lead(start, at(4, 17, 0), &leads), None);
+        assert_eq!(due_lead(start, at(4, 18, 0), &leads), Some(86400));
+        assert_eq!(due_lead(start, at(5, 17, 30), &leads), Some(3600));
+        assert_eq!(due_lead(start, at(5, 18, 0), &leads), None);
+    }
+
+    #[test]
+    fn test_agenda_due() {
+        assert_eq!(agenda_due(at(2, 9, 0), 0, None).as_deref(), Some("2026-03-02"));
+        assert_eq!(agenda_due(at(2, 8, 59), 0, None), None);
+        assert_eq!(agenda_due(at(2, 9, 0), 0, Some("2026-03-02")), None);
+        assert_eq!(agenda_due(at(3, 9, 0), 0, None), None);
+        // 08:00 UTC Monday is 09:00 at UTC+1
+        assert_eq!(agenda_due(at(2, 8, 0), 60, None).as_deref(), Some("2026-03-02"));
+    }
+
+    #[test]
+    fn test_format_agenda() {
+        let a = event("Game night", at(2, 18, 0));
+        let b = event("Movie night", at(5, 19, 0));
+        let agenda = format_agenda(&[&a, &b], 0);
+        assert!(agenda.starts_with("**Monday 2 March**\n• <t:"));
+        assert!(agenda.contains("**Thursday 5 March**"));
+        assert!(agenda.contains("— Movie night"));
+        assert_eq!(format_agenda(&[], 0), "No events this week.");
+    }
+}
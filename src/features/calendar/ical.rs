@@ -0,0 +1,333 @@
+//! # Feature: iCalendar Parsing
+//!
+//! Minimal RFC 5545 reader for calendar subscriptions (Google Calendar, Outlook,
+//! Nextcloud "secret address" feeds). Reads VEVENTs with their summary, location,
+//! URL and start time, and expands simple `RRULE`s (daily, weekly with `BYDAY`,
+//! monthly, yearly; `INTERVAL`, `COUNT`, `UNTIL`, `EXDATE`). Times with a `TZID`
+//! or no zone are read in the subscription's UTC offset, since no time zone
+//! database is bundled.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with VEVENT parsing and RRULE expansion
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Months, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use std::collections::{HashMap, HashSet};
+
+/// Most occurrences generated per recurring event, as a guard against runaway rules
+const MAX_OCCURRENCES: usize = 1000;
+
+/// One occurrence of a calendar event
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: String,
+    pub location: String,
+    pub url: Option<String>,
+    pub start: DateTime<Utc>,
+    pub all_day: bool,
+}
+
+impl CalendarEvent {
+    /// Identifies this occurrence across fetches
+    pub fn key(&self) -> String {
+        format!("{}@{}", self.uid, self.start.timestamp())
+    }
+}
+
+/// A parsed calendar
+#[derive(Debug, Clone, Default)]
+pub struct Calendar {
+    /// `X-WR-CALNAME`, when the feed has one
+    pub name: Option<String>,
+    pub events: Vec<CalendarEvent>,
+}
+
+#[derive(Debug, Default)]
+struct RawEvent {
+    uid: String,
+    summary: String,
+    description: String,
+    location: String,
+    url: Option<String>,
+    start: Option<(DateTime<Utc>, bool)>,
+    rrule: Option<String>,
+    exdates: Vec<DateTime<Utc>>,
+    recurrence_id: Option<DateTime<Utc>>,
+    cancelled: bool,
+}
+
+/// Join folded lines (continuations start with a space or tab)
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Undo TEXT escaping (`\n`, `\,`, `\;`, `\\`)
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n' | 'N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Parse a DATE or DATE-TIME value; returns the instant and whether it's an all-day date
+fn parse_time(value: &str, params: &str, offset: FixedOffset) -> Option<(DateTime<Utc>, bool)> {
+    let value = value.trim();
+    if (params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME")) || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        let local = offset.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).single()?;
+        return Some((local.with_timezone(&Utc), true));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&naive), false));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some((offset.from_local_datetime(&naive).single()?.with_timezone(&Utc), false))
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    // BYDAY entries may carry an ordinal (e.g. `1MO`); only the weekday is used
+    match code.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-' || c == '+') {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Occurrence start times of a recurring event that fall before `until`
+fn expand_rrule(rule: &str, start: DateTime<Utc>, offset: FixedOffset, until: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let parts: HashMap<&str, &str> = rule.split(';').filter_map(|p| p.split_once('=')).collect();
+    let interval = parts.get("INTERVAL").and_then(|i| i.parse::<u32>().ok()).unwrap_or(1).max(1);
+    let count = parts.get("COUNT").and_then(|c| c.parse::<usize>().ok()).unwrap_or(MAX_OCCURRENCES).min(MAX_OCCURRENCES);
+    let rule_until = parts
+        .get("UNTIL")
+        .and_then(|u| parse_time(u, "", offset))
+        .map(|(t, _)| t)
+        .unwrap_or(until)
+        .min(until);
+
+    let mut occurrences = Vec::new();
+    match parts.get("FREQ").copied() {
+        Some("WEEKLY") => {
+            let local_start = start.with_timezone(&offset);
+            let mut days: Vec<Weekday> = parts
+                .get("BYDAY")
+                .map(|d| d.split(',').filter_map(parse_weekday).collect())
+                .unwrap_or_default();
+            if days.is_empty() {
+                days.push(local_start.weekday());
+            }
+            let week_start = local_start - Duration::days(local_start.weekday().num_days_from_monday() as i64);
+            let mut week = 0i64;
+            'weeks: while occurrences.len() < count {
+                let base = week_start + Duration::weeks(week * interval as i64);
+                if base.with_timezone(&Utc) > rule_until {
+                    break;
+                }
+                for day in 0..7 {
+                    let candidate = base + Duration::days(day as i64);
+                    if !days.contains(&candidate.weekday()) || candidate < local_start {
+                        continue;
+                    }
+                    let candidate = candidate.with_timezone(&Utc);
+                    if candidate > rule_until || occurrences.len() >= count {
+                        break 'weeks;
+                    }
+                    occurrences.push(candidate);
+                }
+                week += 1;
+            }
+        }
+        Some(freq @ ("DAILY" | "MONTHLY" | "YEARLY")) => {
+            let local_start = start.with_timezone(&offset);
+            for n in 0..count as u32 {
+                let step = n * interval;
+                let candidate = match freq {
+                    "DAILY" => Some(local_start + Duration::days(step as i64)),
+                    "MONTHLY" => local_start.checked_add_months(Months::new(step)),
+                    _ => local_start.checked_add_months(Months::new(step * 12)),
+                };
+                // Months without the start day (e.g. the 31st) are clamped to their last day
+                let Some(candidate) = candidate.map(|c| c.with_timezone(&Utc)) else { break };
+                if candidate > rule_until {
+                    break;
+                }
+                occurrences.push(candidate);
+            }
+        }
+        _ => occurrences.push(start),
+    }
+    occurrences
+}
+
+/// Parse a calendar, expanding recurring events that start before `until`
+pub fn parse_calendar(ics: &str, offset_minutes: i32, until: DateTime<Utc>) -> Calendar {
+    let offset = FixedOffset::east_opt(offset_minutes * 60).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+    let mut calendar = Calendar::default();
+    let mut raw_events = Vec::new();
+    let mut current: Option<RawEvent> = None;
+
+    for line in unfold(ics) {
+        let Some((name_params, value)) = line.split_once(':') else { continue };
+        let (name, params) = name_params.split_once(';').unwrap_or((name_params, ""));
+        match (name.to_ascii_uppercase().as_str(), current.as_mut()) {
+            ("BEGIN", _) if value.eq_ignore_ascii_case("VEVENT") => current = Some(RawEvent::default()),
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => raw_events.extend(current.take()),
+            ("X-WR-CALNAME", None) => calendar.name = Some(unescape(value)),
+            ("UID", Some(event)) => event.uid = value.to_string(),
+            ("SUMMARY", Some(event)) => event.summary = unescape(value),
+            ("DESCRIPTION", Some(event)) => event.description = unescape(value),
+            ("LOCATION", Some(event)) => event.location = unescape(value),
+            ("URL", Some(event)) => event.url = Some(value.to_string()),
+            ("DTSTART", Some(event)) => event.start = parse_time(value, params, offset),
+            ("RRULE", Some(event)) => event.rrule = Some(value.to_string()),
+            ("EXDATE", Some(event)) => {
+                event.exdates.extend(value.split(',').filter_map(|v| parse_time(v, params, offset)).map(|(t, _)| t))
+            }
+            ("RECURRENCE-ID", Some(event)) => event.recurrence_id = parse_time(value, params, offset).map(|(t, _)| t),
+            ("STATUS", Some(event)) => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+
+    // Occurrences moved or cancelled by a RECURRENCE-ID override are replaced by the override
+    let overridden: HashSet<(String, DateTime<Utc>)> = raw_events
+        .iter()
+        .filter_map(|e| e.recurrence_id.map(|r| (e.uid.clone(), r)))
+        .collect();
+
+    for raw in raw_events {
+        let Some((start, all_day)) = raw.start else { continue };
+        if raw.cancelled {
+            continue;
+        }
+        let starts = match (&raw.rrule, raw.recurrence_id) {
+            (Some(rule), None) => expand_rrule(rule, start, offset, until),
+            _ => vec![start],
+        };
+        for occurrence in starts {
+            if raw.exdates.contains(&occurrence)
+                || (raw.recurrence_id.is_none() && overridden.contains(&(raw.uid.clone(), occurrence)))
+            {
+                continue;
+            }
+            calendar.events.push(CalendarEvent {
+                uid: raw.uid.clone(),
+                summary: raw.summary.clone(),
+                description: raw.description.clone(),
+                location: raw.location.clone(),
+                url: raw.url.clone(),
+                start: occurrence,
+                all_day,
+            });
+        }
+    }
+    calendar.events.sort_by_key(|e| e.start);
+    calendar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    const FEED: &str = "BEGIN:VCALENDAR\r\n\
+X-WR-CALNAME:Community Events\r\n\
+BEGIN:VEVENT\r\n\
+UID:game-night\r\n\
+SUMMARY:Game night\\, bring snacks\r\n\
+DESCRIPTION:Line one\\nLine two with a very long text that is\r\n  folded\r\n\
+LOCATION:Voice channel\r\n\
+DTSTART;TZID=Europe/Berlin:20260302T190000\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,TH;COUNT=4\r\n\
+EXDATE;TZID=Europe/Berlin:20260305T190000\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:game-night\r\n\
+RECURRENCE-ID;TZID=Europe/Berlin:20260309T190000\r\n\
+SUMMARY:Game night (moved)\r\n\
+DTSTART;TZID=Europe/Berlin:20260310T190000\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:launch\r\n\
+SUMMARY:Launch day\r\n\
+DTSTART;VALUE=DATE:20260320\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:cancelled\r\n\
+SUMMARY:Cancelled\r\n\
+STATUS:CANCELLED\r\n\
+DTSTART:20260304T120000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_calendar() {
+        let calendar = parse_calendar(FEED, 60, at(2026, 4, 1, 0, 0));
+        assert_eq!(calendar.name.as_deref(), Some("Community Events"));
+
+        let starts: Vec<(String, DateTime<Utc>)> =
+            calendar.events.iter().map(|e| (e.summary.clone(), e.start)).collect();
+        assert_eq!(
+            starts,
+            vec![
+                ("Game night, bring snacks".to_string(), at(2026, 3, 2, 18, 0)),
+                ("Game night (moved)".to_string(), at(2026, 3, 10, 18, 0)),
+                ("Game night, bring snacks".to_string(), at(2026, 3, 12, 18, 0)),
+                ("Launch day".to_string(), at(2026, 3, 19, 23, 0)),
+            ]
+        );
+        assert_eq!(calendar.events[0].description, "Line one\nLine two with a very long text that is folded");
+        assert!(calendar.events[3].all_day);
+    }
+
+    #[test]
+    fn test_expand_rrule_respects_until_and_interval() {
+        let offset = FixedOffset::east_opt(0).unwrap();
+        let start = at(2026, 1, 31, 10, 0);
+        let daily = expand_rrule("FREQ=DAILY;INTERVAL=2;UNTIL=20260205T100000Z", start, offset, at(2027, 1, 1, 0, 0));
+        assert_eq!(daily, vec![at(2026, 1, 31, 10, 0), at(2026, 2, 2, 10, 0), at(2026, 2, 4, 10, 0)]);
+
+        let monthly = expand_rrule("FREQ=MONTHLY;COUNT=3", at(2026, 1, 15, 10, 0), offset, at(2027, 1, 1, 0, 0));
+        assert_eq!(monthly, vec![at(2026, 1, 15, 10, 0), at(2026, 2, 15, 10, 0), at(2026, 3, 15, 10, 0)]);
+
+        let open_ended = expand_rrule("FREQ=WEEKLY", start, offset, at(2026, 2, 15, 0, 0));
+        assert_eq!(open_ended.len(), 3);
+    }
+
+    #[test]
+    fn test_event_key() {
+        let calendar = parse_calendar(FEED, 0, at(2026, 4, 1, 0, 0));
+        assert_eq!(calendar.events[0].key(), format!("game-night@{}", at(2026, 3, 2, 19, 0).timestamp()));
+    }
+}
@@ -0,0 +1,17 @@
+//! # Calendar Feature
+//!
+//! iCal/Google Calendar subscriptions that post upcoming-event reminders and a
+//! weekly agenda into a channel, managed with `/calendar`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod announcer;
+pub mod ical;
+
+pub use announcer::{
+    agenda_due, due_lead, fetch_calendar, format_agenda, format_lead_time, matches_filter, normalize_feed_url, parse_lead_times,
+    process_calendars, CalendarCache, DEFAULT_LEAD_TIMES,
+};
+pub use ical::{parse_calendar, Calendar, CalendarEvent};
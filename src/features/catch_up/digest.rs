@@ -0,0 +1,178 @@
+//! # Feature: Channel Digest
+//!
+//! Parses which messages `/summarize` should cover and builds the summarizer
+//! prompt from them. The transcript keeps the newest messages when it has to
+//! be cut to fit.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with count, message link and time window ranges
+
+use crate::features::reminders::{parse_duration, parse_message_link};
+
+/// Messages summarized when neither `count` nor `since` is given
+pub const DEFAULT_CATCH_UP_MESSAGES: u64 = 50;
+
+/// Most messages one `/summarize` will read
+pub const MAX_CATCH_UP_MESSAGES: u64 = 500;
+
+/// Transcript sent to the model at most, in characters
+pub const MAX_CATCH_UP_CHARS: usize = 16_000;
+
+/// Which channel messages to summarize
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatchUpRange {
+    /// The most recent messages
+    Last(u64),
+    /// Messages after this message ID
+    AfterMessage(u64),
+    /// Messages from the last this many seconds
+    Window(i64),
+}
+
+impl CatchUpRange {
+    /// Read the `count` and `since` options; `since` is a message link or a duration like `2h`
+    pub fn from_options(count: Option<i64>, since: Option<&str>, channel_id: u64) -> Result<Self, String> {
+        if let Some(since) = since.map(str::trim).filter(|s| !s.is_empty()) {
+            if let Some((_, link_channel, message_id)) = parse_message_link(since) {
+                if link_channel != channel_id {
+                    return Err("That message link is from a different channel.".to_string());
+                }
+                return Ok(CatchUpRange::AfterMessage(message_id));
+            }
+            return parse_duration(since).map(CatchUpRange::Window).ok_or_else(|| {
+                format!("Couldn't read `{since}`. Use a message link from this channel or a duration like `30m`, `2h` or `1d`.")
+            });
+        }
+        let count = count.map_or(DEFAULT_CATCH_UP_MESSAGES, |c| c.max(1) as u64);
+        Ok(CatchUpRange::Last(count.min(MAX_CATCH_UP_MESSAGES)))
+    }
+
+    /// Message ID to page forward from, or None when paging back from the newest
+    pub fn start_after(&self, now: i64) -> Option<u64> {
+        match self {
+            CatchUpRange::Last(_) => None,
+            CatchUpRange::AfterMessage(id) => Some(*id),
+            CatchUpRange::Window(secs) => Some(snowflake_at(now - secs)),
+        }
+    }
+
+    /// Short description for the reply header
+    pub fn describe(&self) -> String {
+        match self {
+            CatchUpRange::Last(count) => format!("the last {count} messages"),
+            CatchUpRange::AfterMessage(_) => "messages since the linked one".to_string(),
+            CatchUpRange::Window(secs) => format!("the last {}", describe_window(*secs)),
+        }
+    }
+}
+
+/// Earliest possible message ID at a Unix time, for paging by time window
+pub fn snowflake_at(unix_secs: i64) -> u64 {
+    const DISCORD_EPOCH_MS: i64 = 1_420_070_400_000;
+    ((unix_secs * 1000 - DISCORD_EPOCH_MS).max(0) as u64) << 22
+}
+
+fn describe_window(secs: i64) -> String {
+    let (value, unit) = match secs {
+        s if s % 86_400 == 0 => (s / 86_400, "day"),
+        s if s % 3_600 == 0 => (s / 3_600, "hour"),
+        s if s % 60 == 0 => (s / 60, "minute"),
+        s => (s, "second"),
+    };
+    if value == 1 {
+        unit.to_string()
+    } else {
+        format!("{value} {unit}s")
+    }
+}
+
+/// Format `(author, content)` pairs, oldest first, keeping the newest lines within `max_chars`
+pub fn format_channel_transcript(messages: &[(String, String)], max_chars: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut total = 0;
+
+    for (author, content) in messages.iter().rev() {
+        let line = format!("{author}: {}", content.trim());
+        let len = line.chars().count() + 1;
+        if total + len > max_chars && !lines.is_empty() {
+            break;
+        }
+        total += len;
+        lines.push(line);
+    }
+
+    lines.reverse();
+    lines.join("\n")
+}
+
+/// Instructions appended to the persona's system prompt for a catch-up summary
+pub fn catch_up_system_prompt(persona_prompt: &str) -> String {
+    format!(
+        "{persona_prompt}\n\n## Channel Catch-Up\nSomeone is catching up on a Discord channel. Summarize the \
+conversation below in your own voice: the main topics, decisions, open questions and anything addressed to people \
+by name. Attribute points to speakers where it matters. Don't invent anything that isn't in the messages."
+    )
+}
+
+/// User message carrying the transcript to summarize
+pub fn catch_up_request(transcript: &str) -> String {
+    format!("Summarize these channel messages:\n\n{transcript}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_from_options() {
+        assert_eq!(CatchUpRange::from_options(None, None, 1), Ok(CatchUpRange::Last(DEFAULT_CATCH_UP_MESSAGES)));
+        assert_eq!(CatchUpRange::from_options(Some(20), None, 1), Ok(CatchUpRange::Last(20)));
+        assert_eq!(CatchUpRange::from_options(Some(10_000), None, 1), Ok(CatchUpRange::Last(MAX_CATCH_UP_MESSAGES)));
+        assert_eq!(CatchUpRange::from_options(Some(20), Some("2h"), 1), Ok(CatchUpRange::Window(7200)));
+        assert_eq!(
+            CatchUpRange::from_options(None, Some("https://discord.com/channels/1/2/3"), 2),
+            Ok(CatchUpRange::AfterMessage(3))
+        );
+        assert!(CatchUpRange::from_options(None, Some("https://discord.com/channels/1/2/3"), 9).is_err());
+        assert!(CatchUpRange::from_options(None, Some("yesterday-ish"), 1).is_err());
+    }
+
+    #[test]
+    fn test_start_after() {
+        let now = 1_700_000_000;
+        assert_eq!(CatchUpRange::Last(10).start_after(now), None);
+        assert_eq!(CatchUpRange::AfterMessage(42).start_after(now), Some(42));
+        assert_eq!(CatchUpRange::Window(60).start_after(now), Some(snowflake_at(now - 60)));
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(CatchUpRange::Last(50).describe(), "the last 50 messages");
+        assert_eq!(CatchUpRange::Window(3600).describe(), "the last hour");
+        assert_eq!(CatchUpRange::Window(5400).describe(), "the last 90 minutes");
+        assert_eq!(CatchUpRange::Window(172_800).describe(), "the last 2 days");
+    }
+
+    #[test]
+    fn test_snowflake_at() {
+        // 2015-01-01T00:00:00Z is the Discord epoch
+        assert_eq!(snowflake_at(1_420_070_400), 0);
+        assert_eq!(snowflake_at(1_420_070_401), 1000 << 22);
+        assert_eq!(snowflake_at(0), 0);
+    }
+
+    #[test]
+    fn test_transcript_keeps_newest() {
+        let messages = vec![
+            ("alice".to_string(), "first message".to_string()),
+            ("bob".to_string(), "second".to_string()),
+            ("carol".to_string(), " third ".to_string()),
+        ];
+        assert_eq!(format_channel_transcript(&messages, 1000), "alice: first message\nbob: second\ncarol: third");
+        assert_eq!(format_channel_transcript(&messages, 25), "bob: second\ncarol: third");
+    }
+}
@@ -0,0 +1,141 @@
+//! # Feature: Linked Message Summaries
+//!
+//! When someone mentions the bot with a Discord message link and a question
+//! like "what happened here?", the linked message, the replies it answers and
+//! the messages around it are read and summarized. Links to another channel are
+//! only followed when the guild's `cross_channel_summaries` setting is enabled
+//! and both the asker and the bot can read that channel.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with reply chains, surrounding context and a cross-channel toggle
+
+use crate::features::reminders::parse_message_link;
+
+/// Messages read before the linked message
+pub const LINKED_CONTEXT_BEFORE: u8 = 15;
+
+/// Messages read after the linked message
+pub const LINKED_CONTEXT_AFTER: u8 = 10;
+
+/// Replies followed upward from the linked message
+pub const MAX_REPLY_CHAIN: usize = 5;
+
+/// Phrases that turn a linked message into a summary request
+const SUMMARY_CUES: &[&str] = &[
+    "what happened",
+    "what's going on",
+    "whats going on",
+    "what is going on",
+    "what was going on",
+    "catch me up",
+    "summarize",
+    "summarise",
+    "tl;dr",
+    "tldr",
+];
+
+/// A message link the user wants explained: `(guild_id, channel_id, message_id)`
+pub type LinkedMessage = (Option<u64>, u64, u64);
+
+/// The first message link in `content`, if the message also asks what happened
+pub fn linked_summary_target(content: &str) -> Option<LinkedMessage> {
+    let lower = content.to_lowercase();
+    if !SUMMARY_CUES.iter().any(|cue| lower.contains(cue)) {
+        return None;
+    }
+    content.split_whitespace().find_map(parse_message_link)
+}
+
+/// Why a linked message can't be read, before asking Discord about permissions
+pub fn check_link_scope(link: LinkedMessage, guild_id: u64, channel_id: u64, cross_channel: bool) -> Result<(), &'static str> {
+    let (link_guild, link_channel, _) = link;
+    if link_guild != Some(guild_id) {
+        return Err("I can only summarize messages from this server.");
+    }
+    if link_channel != channel_id && !cross_channel {
+        return Err("Summaries of messages in other channels are disabled here. An admin can turn them on with the `cross_channel_summaries` setting.");
+    }
+    Ok(())
+}
+
+/// Order fetched `(message_id, author, content)` triples oldest first, dropping
+/// duplicates and empty messages and marking the linked one for the model
+pub fn merge_linked_messages(mut messages: Vec<(u64, String, String)>, linked_id: u64) -> Vec<(String, String)> {
+    messages.sort_by_key(|(id, _, _)| *id);
+    messages.dedup_by_key(|(id, _, _)| *id);
+    messages
+        .into_iter()
+        .filter(|(_, _, content)| !content.trim().is_empty())
+        .map(|(id, author, content)| {
+            if id == linked_id {
+                (author, format!("[linked message] {}", content.trim()))
+            } else {
+                (author, content)
+            }
+        })
+        .collect()
+}
+
+/// Instructions appended to the persona's system prompt for a linked message summary
+pub fn linked_summary_system_prompt(persona_prompt: &str) -> String {
+    format!(
+        "{persona_prompt}\n\n## Linked Message\nSomeone linked a Discord message and asked what happened. The \
+transcript below has the messages around it; the linked one is marked `[linked message]`. Explain in your own voice \
+what led up to it, what it says and how people responded. Keep it short and attribute points to speakers. Don't \
+invent anything that isn't in the messages."
+    )
+}
+
+/// User message carrying the question and the transcript around the link
+pub fn linked_summary_request(question: &str, transcript: &str) -> String {
+    format!("{question}\n\nMessages around the link:\n\n{transcript}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linked_summary_target() {
+        assert_eq!(
+            linked_summary_target("@bot what happened here? https://discord.com/channels/1/2/3"),
+            Some((Some(1), 2, 3))
+        );
+        assert_eq!(linked_summary_target("TL;DR <https://discord.com/channels/1/2/3>"), Some((Some(1), 2, 3)));
+        // A link without a question is just a link
+        assert_eq!(linked_summary_target("see https://discord.com/channels/1/2/3"), None);
+        assert_eq!(linked_summary_target("what happened here?"), None);
+    }
+
+    #[test]
+    fn test_check_link_scope() {
+        assert!(check_link_scope((Some(1), 2, 3), 1, 2, false).is_ok());
+        assert!(check_link_scope((Some(1), 9, 3), 1, 2, false).is_err());
+        assert!(check_link_scope((Some(1), 9, 3), 1, 2, true).is_ok());
+        assert!(check_link_scope((Some(7), 2, 3), 1, 2, true).is_err());
+        assert!(check_link_scope((None, 2, 3), 1, 2, true).is_err());
+    }
+
+    #[test]
+    fn test_merge_linked_messages() {
+        let messages = vec![
+            (30, "carol".to_string(), "agreed".to_string()),
+            (10, "alice".to_string(), "should we ship?".to_string()),
+            (20, "bob".to_string(), " yes, tonight ".to_string()),
+            (10, "alice".to_string(), "should we ship?".to_string()),
+            (25, "dave".to_string(), "  ".to_string()),
+        ];
+        assert_eq!(
+            merge_linked_messages(messages, 20),
+            vec![
+                ("alice".to_string(), "should we ship?".to_string()),
+                ("bob".to_string(), "[linked message] yes, tonight".to_string()),
+                ("carol".to_string(), "agreed".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,23 @@
+//! # Catch-Up Feature
+//!
+//! `/summarize` reads recent channel messages (the last N, everything after a
+//! message link, or a time window) and has the user's persona summarize them,
+//! replying ephemerally so only the person catching up sees it. Mentioning
+//! the bot with a message link and "what happened here?" summarizes the
+//! exchange around that message.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod digest;
+pub mod linked;
+
+pub use digest::{
+    catch_up_request, catch_up_system_prompt, format_channel_transcript, snowflake_at, CatchUpRange,
+    DEFAULT_CATCH_UP_MESSAGES, MAX_CATCH_UP_CHARS, MAX_CATCH_UP_MESSAGES,
+};
+pub use linked::{
+    check_link_scope, linked_summary_request, linked_summary_system_prompt, linked_summary_target, merge_linked_messages,
+    LinkedMessage, LINKED_CONTEXT_AFTER, LINKED_CONTEXT_BEFORE, MAX_REPLY_CHAIN,
+};
@@ -0,0 +1,178 @@
+//! # Feature: Channel Archive
+//!
+//! `/archive_channel` turns a channel's history into a plain-text log, asks the
+//! model for a summary of what was notable in it, and posts both to an archive
+//! channel. The log holds every message, bots and system messages included;
+//! only the summary is limited to the newest part of the channel.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use serenity::model::channel::Message;
+
+/// Most messages read from a channel being archived
+pub const MAX_ARCHIVE_MESSAGES: usize = 20_000;
+
+/// Transcript sent to the model for the summary, newest messages kept
+pub const MAX_ARCHIVE_SUMMARY_CHARS: usize = 24_000;
+
+/// Largest log file attached per message, under Discord's upload limit
+pub const MAX_ARCHIVE_FILE_BYTES: usize = 7 * 1024 * 1024;
+
+/// One message as it appears in the archive log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// `YYYY-MM-DD HH:MM:SS` in UTC
+    pub timestamp: String,
+    pub author: String,
+    pub author_bot: bool,
+    pub content: String,
+    pub attachment_urls: Vec<String>,
+}
+
+impl ArchiveEntry {
+    pub fn from_message(message: &Message) -> Self {
+        ArchiveEntry {
+            timestamp: chrono::DateTime::from_timestamp(message.timestamp.unix_timestamp(), 0)
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default(),
+            author: message.author.tag(),
+            author_bot: message.author.bot,
+            content: message.content.clone(),
+            attachment_urls: message.attachments.iter().map(|a| a.url.clone()).collect(),
+        }
+    }
+}
+
+/// The full log, oldest first, one line per message with continuation lines indented
+pub fn format_archive_log(channel_name: &str, entries: &[ArchiveEntry], truncated: bool) -> String {
+    let mut log = format!("# #{channel_name} — {} messages (times in UTC)\n", entries.len());
+    if truncated {
+        log.push_str(&format!("# Only the newest {MAX_ARCHIVE_MESSAGES} messages were archived\n"));
+    }
+    log.push('\n');
+    for entry in entries {
+        let bot = if entry.author_bot { " [bot]" } else { "" };
+        let content = entry.content.trim().replace('\n', "\n    ");
+        log.push_str(&format!("[{}] {}{bot}: {content}\n", entry.timestamp, entry.author));
+        for url in &entry.attachment_urls {
+            log.push_str(&format!("    📎 {url}\n"));
+        }
+    }
+    log
+}
+
+/// Split a log on line boundaries into parts of at most `max_bytes` (a longer single line gets a part of its own)
+pub fn split_archive_log(log: &str, max_bytes: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    for line in log.split_inclusive('\n') {
+        if !current.is_empty() && current.len() + line.len() > max_bytes {
+            parts.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() || parts.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Attachment name for part `part` (from 1) of `parts`, e.g. `general-2026-10-16.txt` or `general-2026-10-16-part2.txt`
+pub fn archive_filename(channel_name: &str, date: &str, part: usize, parts: usize) -> String {
+    let name: String = channel_name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    if parts > 1 {
+        format!("{name}-{date}-part{part}.txt")
+    } else {
+        format!("{name}-{date}.txt")
+    }
+}
+
+/// Instructions for summarizing a channel that is being archived
+pub fn archive_summary_system_prompt() -> String {
+    "You are writing the archive record of a Discord channel that is being closed. From the messages below, summarize \
+what is worth remembering: what the channel was used for, the main discussions, decisions and their reasons, useful \
+links or resources shared, and questions left open. Use short sections with bullet points and name people where it \
+matters. Don't invent anything that isn't in the messages."
+        .to_string()
+}
+
+/// User message carrying the transcript to summarize
+pub fn archive_summary_request(channel_name: &str, transcript: &str) -> String {
+    format!("Messages from #{channel_name}:\n\n{transcript}")
+}
+
+/// The archive post's header, followed by the summary when there is one
+pub fn format_archive_post(
+    channel_name: &str,
+    message_count: usize,
+    span: Option<(&str, &str)>,
+    archived_by: u64,
+    summary: Option<&str>,
+) -> String {
+    let span = match span {
+        Some((first, last)) => format!(", {first} to {last} UTC"),
+        None => String::new(),
+    };
+    let summary = summary.unwrap_or("*No summary: the AI service was unavailable. The full log is attached.*");
+    format!("🗄️ **Archive of #{channel_name}** ({message_count} messages{span})\nArchived by <@{archived_by}>\n\n{summary}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(author: &str, content: &str) -> ArchiveEntry {
+        ArchiveEntry {
+            timestamp: "2026-10-16 12:00:00".to_string(),
+            author: author.to_string(),
+            author_bot: false,
+            content: content.to_string(),
+            attachment_urls: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_format_archive_log() {
+        let mut bot = entry("persona#0001", "hello");
+        bot.author_bot = true;
+        bot.attachment_urls = vec!["https://cdn.example.org/a.png".to_string()];
+        let log = format_archive_log("general", &[entry("alice", "first\nsecond"), bot], false);
+        assert_eq!(
+            log,
+            "# #general — 2 messages (times in UTC)\n\n[2026-10-16 12:00:00] alice: first\n    second\n\
+[2026-10-16 12:00:00] persona#0001 [bot]: hello\n    📎 https://cdn.example.org/a.png\n"
+        );
+        assert!(format_archive_log("general", &[], true).contains("Only the newest"));
+    }
+
+    #[test]
+    fn test_split_archive_log() {
+        assert_eq!(split_archive_log("a\nb\nc\n", 4), vec!["a\nb\n", "c\n"]);
+        assert_eq!(split_archive_log("long line\nx\n", 4), vec!["long line\n", "x\n"]);
+        assert_eq!(split_archive_log("", 4), vec![""]);
+    }
+
+    #[test]
+    fn test_archive_filename() {
+        assert_eq!(archive_filename("general", "2026-10-16", 1, 1), "general-2026-10-16.txt");
+        assert_eq!(archive_filename("café chat", "2026-10-16", 2, 3), "café-chat-2026-10-16-part2.txt");
+    }
+
+    #[test]
+    fn test_format_archive_post() {
+        let post = format_archive_post("general", 42, Some(("2026-01-01 00:00:00", "2026-10-16 12:00:00")), 7, Some("- Decided X"));
+        assert_eq!(
+            post,
+            "🗄️ **Archive of #general** (42 messages, 2026-01-01 00:00:00 to 2026-10-16 12:00:00 UTC)\nArchived by <@7>\n\n- Decided X"
+        );
+        assert!(format_archive_post("general", 0, None, 7, None).contains("No summary"));
+    }
+}
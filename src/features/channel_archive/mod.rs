@@ -0,0 +1,16 @@
+//! # Channel Archive Feature
+//!
+//! `/archive_channel` for admins: summarizes a channel's notable history,
+//! exports the full message log as a file, posts both to an archive channel
+//! and, when asked, deletes the original channel afterwards.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod archive;
+
+pub use archive::{
+    archive_filename, archive_summary_request, archive_summary_system_prompt, format_archive_log, format_archive_post,
+    split_archive_log, ArchiveEntry, MAX_ARCHIVE_FILE_BYTES, MAX_ARCHIVE_MESSAGES, MAX_ARCHIVE_SUMMARY_CHARS,
+};
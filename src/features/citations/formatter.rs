@@ -0,0 +1,128 @@
+//! # Feature: Answer Citations
+//!
+//! Numbers stored conversation history so the model can cite earlier messages
+//! as `[n]`, then resolves those markers into jump links to the original Discord
+//! messages. Enabled per guild with the `cite_sources` setting.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with numbered history and footnote link formatting
+
+use crate::database::HistoryEntry;
+use crate::features::reminders::build_message_link;
+
+/// System prompt suffix explaining the citation markers
+pub const CITATION_INSTRUCTION: &str = "\n\nSome earlier messages in this conversation are prefixed \
+with a reference number like [3]. When your answer relies on something said in one of those \
+messages, cite it by writing its number in square brackets, e.g. \"as you mentioned earlier [3]\". \
+Only cite numbers that appear in the conversation, and don't invent new ones.";
+
+/// A numbered reference to a stored Discord message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CitationRef {
+    pub number: usize,
+    pub message_id: String,
+}
+
+/// Convert history entries into model messages, numbering those that can be linked.
+///
+/// Entries without a message ID (e.g. bot replies) and the message being answered
+/// (`exclude_message_id`) are passed through without a reference number.
+pub fn annotate_history(
+    entries: Vec<HistoryEntry>,
+    exclude_message_id: Option<&str>,
+) -> (Vec<(String, String)>, Vec<CitationRef>) {
+    let mut history = Vec::with_capacity(entries.len());
+    let mut refs = Vec::new();
+
+    for entry in entries {
+        match entry.message_id {
+            Some(message_id) if entry.role == "user" && Some(message_id.as_str()) != exclude_message_id => {
+                let number = refs.len() + 1;
+                history.push((entry.role, format!("[{number}] {}", entry.content)));
+                refs.push(CitationRef { number, message_id });
+            }
+            _ => history.push((entry.role, entry.content)),
+        }
+    }
+
+    (history, refs)
+}
+
+/// Append a "Sources" footer linking every reference number cited in `response`
+pub fn append_source_links(
+    response: &str,
+    refs: &[CitationRef],
+    guild_id: Option<u64>,
+    channel_id: u64,
+) -> String {
+    let links: Vec<String> = refs
+        .iter()
+        .filter(|r| response.contains(&format!("[{}]", r.number)))
+        .filter_map(|r| {
+            let message_id = r.message_id.parse::<u64>().ok()?;
+            Some(format!(
+                "[{}]({})",
+                r.number,
+                build_message_link(guild_id, channel_id, message_id)
+            ))
+        })
+        .collect();
+
+    if links.is_empty() {
+        response.to_string()
+    } else {
+        format!("{response}\n\n📎 **Sources:** {}", links.join(" · "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(role: &str, content: &str, message_id: Option<&str>) -> HistoryEntry {
+        HistoryEntry {
+            role: role.to_string(),
+            content: content.to_string(),
+            message_id: message_id.map(|s| s.to_string()),
+            timestamp: "2025-01-01 00:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_annotate_history_numbers_linkable_user_messages() {
+        let entries = vec![
+            entry("user", "I like tea", Some("100")),
+            entry("assistant", "Noted!", None),
+            entry("user", "And coffee?", Some("101")),
+            entry("user", "current question", Some("102")),
+        ];
+        let (history, refs) = annotate_history(entries, Some("102"));
+        assert_eq!(history[0].1, "[1] I like tea");
+        assert_eq!(history[1].1, "Noted!");
+        assert_eq!(history[2].1, "[2] And coffee?");
+        assert_eq!(history[3].1, "current question");
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[1].message_id, "101");
+    }
+
+    #[test]
+    fn test_append_source_links_only_cited() {
+        let refs = vec![
+            CitationRef { number: 1, message_id: "100".to_string() },
+            CitationRef { number: 2, message_id: "101".to_string() },
+        ];
+        let out = append_source_links("You said you like tea [1].", &refs, Some(5), 6);
+        assert!(out.contains("[1](https://discord.com/channels/5/6/100)"));
+        assert!(!out.contains("/101)"));
+    }
+
+    #[test]
+    fn test_append_source_links_without_citations() {
+        let refs = vec![CitationRef { number: 1, message_id: "100".to_string() }];
+        assert_eq!(append_source_links("No refs here.", &refs, None, 6), "No refs here.");
+    }
+}
@@ -0,0 +1,11 @@
+//! # Citations Feature
+//!
+//! Footnote-style links from AI answers back to the Discord messages they reference.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod formatter;
+
+pub use formatter::{annotate_history, append_source_links, CitationRef, CITATION_INSTRUCTION};
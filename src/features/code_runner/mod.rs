@@ -0,0 +1,16 @@
+//! # Code Runner Feature
+//!
+//! `/run` executes code snippets in a sandbox (a Piston instance or local
+//! firejail) and replies with stdout/stderr. Runs are time-limited and
+//! rate-limited per user.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod runner;
+
+pub use runner::{
+    find_language, format_run_output, strip_code_fence, CodeRunner, Language, RunOutput, LANGUAGES,
+    MAX_CODE_LEN, RUNS_PER_MINUTE, RUN_MODAL_PREFIX,
+};
@@ -0,0 +1,349 @@
+//! # Feature: Code Runner
+//!
+//! Executes snippets for `/run` in a sandbox. Two backends, picked with
+//! `CODE_RUNNER`:
+//! - `piston`: a [Piston](https://github.com/engineer-man/piston) instance at
+//!   `PISTON_URL` (compiled languages supported)
+//! - `firejail`: local interpreters under firejail with no network, a private
+//!   home, dropped capabilities and memory/process limits
+//!
+//! Code is piped over stdin so nothing is written to the host filesystem.
+//! Output is capped and runs are killed after `CODE_RUN_TIMEOUT_SECS` (default
+//! 3, which is Piston's default maximum `run_timeout`).
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with Piston and firejail backends
+
+use crate::core::{BotError, Result};
+use serde_json::{json, Value};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+use tokio::time::timeout;
+
+/// Custom ID prefix for the code entry modal: `run_code_modal:<language>`
+pub const RUN_MODAL_PREFIX: &str = "run_code_modal:";
+
+/// Runs allowed per user per minute
+pub const RUNS_PER_MINUTE: usize = 5;
+
+/// Longest snippet accepted (the modal's input limit)
+pub const MAX_CODE_LEN: usize = 4000;
+
+/// Bytes kept from each of stdout and stderr
+const MAX_OUTPUT_BYTES: u64 = 16 * 1024;
+
+/// A language offered by `/run`
+#[derive(Debug, PartialEq)]
+pub struct Language {
+    /// Choice value used in the slash command
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Language name understood by Piston
+    pub piston: &'static str,
+    /// Interpreter command reading the program from stdin, for the firejail backend
+    pub local: Option<&'static [&'static str]>,
+    /// Code block language for output formatting
+    pub highlight: &'static str,
+}
+
+pub const LANGUAGES: &[Language] = &[
+    Language { id: "python", name: "Python", piston: "python", local: Some(&["python3", "-"]), highlight: "py" },
+    Language { id: "javascript", name: "JavaScript", piston: "javascript", local: Some(&["node", "-"]), highlight: "js" },
+    Language { id: "typescript", name: "TypeScript", piston: "typescript", local: None, highlight: "ts" },
+    Language { id: "bash", name: "Bash", piston: "bash", local: Some(&["bash", "-s"]), highlight: "bash" },
+    Language { id: "ruby", name: "Ruby", piston: "ruby", local: Some(&["ruby", "-"]), highlight: "rb" },
+    Language { id: "lua", name: "Lua", piston: "lua", local: Some(&["lua", "-"]), highlight: "lua" },
+    Language { id: "rust", name: "Rust", piston: "rust", local: None, highlight: "rs" },
+    Language { id: "go", name: "Go", piston: "go", local: None, highlight: "go" },
+    Language { id: "c", name: "C", piston: "c", local: None, highlight: "c" },
+    Language { id: "cpp", name: "C++", piston: "c++", local: None, highlight: "cpp" },
+    Language { id: "java", name: "Java", piston: "java", local: None, highlight: "java" },
+];
+
+/// Look up a language by its choice value
+pub fn find_language(id: &str) -> Option<&'static Language> {
+    LANGUAGES.iter().find(|l| l.id == id)
+}
+
+/// Result of running a snippet
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i64>,
+    pub timed_out: bool,
+}
+
+#[derive(Clone)]
+enum Backend {
+    Piston { client: reqwest::Client, url: String },
+    Firejail,
+}
+
+#[derive(Clone)]
+pub struct CodeRunner {
+    backend: Backend,
+    timeout: Duration,
+}
+
+impl CodeRunner {
+    /// Build the configured backend. Ok(None) when `CODE_RUNNER` is unset.
+    pub fn from_config(backend: Option<&str>, piston_url: Option<String>, timeout_secs: u64) -> Result<Option<Self>> {
+        let backend = match backend {
+            None => return Ok(None),
+            Some("piston") => {
+                let url = piston_url.ok_or_else(|| BotError::validation("CODE_RUNNER=piston needs PISTON_URL"))?;
+                Backend::Piston {
+                    client: reqwest::Client::builder()
+                        .timeout(Duration::from_secs(timeout_secs + 20))
+                        .build()?,
+                    url: url.trim_end_matches('/').trim_end_matches("/execute").to_string(),
+                }
+            }
+            Some("firejail") => Backend::Firejail,
+            Some(other) => return Err(BotError::validation(format!("Unknown CODE_RUNNER `{}` (expected piston or firejail)", other))),
+        };
+        Ok(Some(CodeRunner { backend, timeout: Duration::from_secs(timeout_secs.max(1)) }))
+    }
+
+    pub fn timeout_secs(&self) -> u64 {
+        self.timeout.as_secs()
+    }
+
+    /// Whether this backend can run the language
+    pub fn supports(&self, language: &Language) -> bool {
+        match self.backend {
+            Backend::Piston { .. } => true,
+            Backend::Firejail => language.local.is_some(),
+        }
+    }
+
+    pub async fn run(&self, language: &Language, code: &str) -> Result<RunOutput> {
+        match &self.backend {
+            Backend::Piston { client, url } => self.run_piston(client, url, language, code).await,
+            Backend::Firejail => self.run_firejail(language, code).await,
+        }
+    }
+
+    async fn run_piston(&self, client: &reqwest::Client, url: &str, language: &Language, code: &str) -> Result<RunOutput> {
+        let timeout_ms = self.timeout.as_millis() as u64;
+        let response = client
+            .post(format!("{url}/execute"))
+            .json(&json!({
+                "language": language.piston,
+                "version": "*",
+                "files": [{ "content": code }],
+                "run_timeout": timeout_ms,
+                "compile_timeout": timeout_ms.max(10_000),
+            }))
+            .send()
+            .await?;
+
+        let status = response.status();
+        let body: Value = response.json().await?;
+        if !status.is_success() {
+            let message = body.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+            return Err(BotError::internal(format!("Piston returned {}: {}", status, message)));
+        }
+        Ok(parse_piston_response(&body))
+    }
+
+    async fn run_firejail(&self, language: &Language, code: &str) -> Result<RunOutput> {
+        let command = language
+            .local
+            .ok_or_else(|| BotError::validation(format!("{} isn't available on the local runner", language.name)))?;
+
+        let mut child = Command::new("firejail")
+            .args([
+                "--quiet",
+                "--noprofile",
+                "--private",
+                "--net=none",
+                "--nosound",
+                "--no3d",
+                "--caps.drop=all",
+                "--nonewprivs",
+                "--noroot",
+                "--seccomp",
+                "--rlimit-as=536870912",
+                "--rlimit-nproc=64",
+                "--rlimit-fsize=1048576",
+                "--",
+            ])
+            .args(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| BotError::internal(format!("Couldn't start firejail: {}", e)))?;
+
+        let mut stdin = child.stdin.take().ok_or_else(|| BotError::internal("No stdin for sandbox"))?;
+        let stdout = child.stdout.take().ok_or_else(|| BotError::internal("No stdout for sandbox"))?;
+        let stderr = child.stderr.take().ok_or_else(|| BotError::internal("No stderr for sandbox"))?;
+
+        let run = async {
+            stdin.write_all(code.as_bytes()).await?;
+            drop(stdin);
+            let (stdout, stderr) = tokio::join!(read_capped(stdout), read_capped(stderr));
+            let status = child.wait().await?;
+            Ok::<_, BotError>(RunOutput {
+                stdout,
+                stderr,
+                exit_code: status.code().map(i64::from),
+                timed_out: false,
+            })
+        };
+
+        // Dropping the future on timeout drops the child, which kills it
+        match timeout(self.timeout, run).await {
+            Ok(output) => output,
+            Err(_) => Ok(RunOutput { timed_out: true, ..Default::default() }),
+        }
+    }
+}
+
+/// Read a pipe to the end, keeping at most `MAX_OUTPUT_BYTES`. Stops reading past the cap,
+/// so a program flooding output blocks until the timeout kills it.
+async fn read_capped(pipe: impl AsyncRead + Unpin) -> String {
+    let mut buffer = Vec::new();
+    let _ = pipe.take(MAX_OUTPUT_BYTES).read_to_end(&mut buffer).await;
+    String::from_utf8_lossy(&buffer).into_owned()
+}
+
+/// Map Piston's `{compile, run}` stages to a single output; compile failures are reported as the run
+pub fn parse_piston_response(body: &Value) -> RunOutput {
+    let stage_output = |stage: &Value| RunOutput {
+        stdout: stage.get("stdout").and_then(|s| s.as_str()).unwrap_or_default().to_string(),
+        stderr: stage.get("stderr").and_then(|s| s.as_str()).unwrap_or_default().to_string(),
+        exit_code: stage.get("code").and_then(|c| c.as_i64()),
+        // Piston kills runs that exceed run_timeout with SIGKILL
+        timed_out: stage.get("signal").and_then(|s| s.as_str()) == Some("SIGKILL"),
+    };
+
+    if let Some(compile) = body.get("compile") {
+        let output = stage_output(compile);
+        if output.exit_code.is_some_and(|code| code != 0) || output.timed_out {
+            return output;
+        }
+    }
+    body.get("run").map(stage_output).unwrap_or_default()
+}
+
+/// Strip a surrounding Markdown code fence (```py ... ```) from pasted code
+pub fn strip_code_fence(code: &str) -> &str {
+    let trimmed = code.trim();
+    let Some(inner) = trimmed.strip_prefix("```").and_then(|s| s.strip_suffix("```")) else {
+        return trimmed;
+    };
+    // Drop a language tag on the opening line
+    match inner.split_once('\n') {
+        Some((tag, rest)) if tag.chars().all(|c| c.is_ascii_alphanumeric() || "+#-".contains(c)) => rest.trim_matches('\n'),
+        _ => inner.trim_matches('\n'),
+    }
+}
+
+/// Keep the last `max_chars` characters, since errors and final results are usually at the end
+fn tail(text: &str, max_chars: usize) -> String {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().skip(count - max_chars).collect();
+    format!("…{kept}")
+}
+
+/// Render a run as a Discord message, staying under 2000 characters
+pub fn format_run_output(language: &Language, output: &RunOutput, timeout_secs: u64) -> String {
+    let status = if output.timed_out {
+        format!("⏱️ timed out after {timeout_secs}s")
+    } else {
+        match output.exit_code {
+            Some(0) => "✅ exit 0".to_string(),
+            Some(code) => format!("❌ exit {code}"),
+            None => "❌ killed".to_string(),
+        }
+    };
+
+    // Keep output from closing the code block early
+    let clean = |s: &str| s.trim_end().replace("```", "`\u{200b}``");
+    let stdout = clean(&output.stdout);
+    let stderr = clean(&output.stderr);
+
+    let budget = 1700;
+    let (stdout_budget, stderr_budget) = match (stdout.is_empty(), stderr.is_empty()) {
+        (false, false) => (budget / 2, budget / 2),
+        (false, true) => (budget, 0),
+        _ => (0, budget),
+    };
+
+    let mut message = format!("**{}** · {}", language.name, status);
+    if !stdout.is_empty() {
+        message.push_str(&format!("\n```\n{}\n```", tail(&stdout, stdout_budget)));
+    }
+    if !stderr.is_empty() {
+        message.push_str(&format!("\n**stderr**\n```\n{}\n```", tail(&stderr, stderr_budget)));
+    }
+    if stdout.is_empty() && stderr.is_empty() && !output.timed_out {
+        message.push_str("\n*(no output)*");
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_code_fence() {
+        assert_eq!(strip_code_fence("```py\nprint(1)\n```"), "print(1)");
+        assert_eq!(strip_code_fence("```\nprint(1)\n```"), "print(1)");
+        assert_eq!(strip_code_fence("  print(1)  "), "print(1)");
+        assert_eq!(strip_code_fence("```print(1)```"), "print(1)");
+    }
+
+    #[test]
+    fn test_parse_piston_response() {
+        let ok = json!({"run": {"stdout": "hi\n", "stderr": "", "code": 0, "signal": null}});
+        assert_eq!(parse_piston_response(&ok), RunOutput { stdout: "hi\n".into(), stderr: String::new(), exit_code: Some(0), timed_out: false });
+
+        let compile_error = json!({
+            "compile": {"stdout": "", "stderr": "error[E0425]", "code": 1, "signal": null},
+            "run": {"stdout": "", "stderr": "", "code": null, "signal": null}
+        });
+        assert_eq!(parse_piston_response(&compile_error).stderr, "error[E0425]");
+
+        let killed = json!({"run": {"stdout": "", "stderr": "", "code": null, "signal": "SIGKILL"}});
+        assert!(parse_piston_response(&killed).timed_out);
+    }
+
+    #[test]
+    fn test_format_run_output_fits_discord() {
+        let python = find_language("python").unwrap();
+        let output = RunOutput { stdout: "x".repeat(10_000), stderr: "```boom".repeat(500), exit_code: Some(1), timed_out: false };
+        let message = format_run_output(python, &output, 3);
+        assert!(message.chars().count() <= 2000);
+        assert!(message.starts_with("**Python** · ❌ exit 1"));
+        assert_eq!(message.matches("```").count(), 4);
+
+        let empty = format_run_output(python, &RunOutput { exit_code: Some(0), ..Default::default() }, 3);
+        assert!(empty.ends_with("*(no output)*"));
+    }
+
+    #[test]
+    fn test_backend_config() {
+        assert!(CodeRunner::from_config(None, None, 3).unwrap().is_none());
+        assert!(CodeRunner::from_config(Some("piston"), None, 3).is_err());
+        assert!(CodeRunner::from_config(Some("docker"), None, 3).is_err());
+
+        let local = CodeRunner::from_config(Some("firejail"), None, 3).unwrap().unwrap();
+        assert!(local.supports(find_language("python").unwrap()));
+        assert!(!local.supports(find_language("rust").unwrap()));
+    }
+}
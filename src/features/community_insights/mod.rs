@@ -0,0 +1,14 @@
+//! # Community Insights Feature
+//!
+//! Growth analytics for community managers, reported with `/community_insights`.
+//! Currently covers new-member retention per weekly join cohort.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod retention;
+
+pub use retention::{
+    compute_cohorts, format_retention_report, CohortRetention, COHORT_WEEKS, MEMBER_ACTIVITY_RETENTION_DAYS,
+};
@@ -0,0 +1,175 @@
+//! # Feature: New-Member Retention
+//!
+//! Correlates each member's join date (from the member data Discord attaches to
+//! guild messages) with their last day of activity to compute 7- and 30-day
+//! retention per weekly join cohort.
+//!
+//! A member counts as retained at N days if they posted on or after day N since
+//! joining. Only members the bot has seen post are tracked.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with weekly cohorts and 7/30-day retention
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// Weekly join cohorts shown in `/community_insights`
+pub const COHORT_WEEKS: i64 = 12;
+
+/// Members inactive for longer than this are dropped by the daily cleanup
+pub const MEMBER_ACTIVITY_RETENTION_DAYS: i64 = 180;
+
+/// Retention of the members who joined in one week
+#[derive(Debug, Clone, PartialEq)]
+pub struct CohortRetention {
+    /// Monday of the join week
+    pub week_start: NaiveDate,
+    pub members: usize,
+    /// Members still active after 7 days; `None` until the whole cohort is 7 days old
+    pub retained_7d: Option<usize>,
+    /// Members still active after 30 days; `None` until the whole cohort is 30 days old
+    pub retained_30d: Option<usize>,
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn retained(members: &[(NaiveDate, NaiveDate)], week: NaiveDate, days: i64, today: NaiveDate) -> Option<usize> {
+    // Measurable once the last day of the join week is `days` old
+    if week + Duration::days(6 + days) > today {
+        return None;
+    }
+    Some(
+        members
+            .iter()
+            .filter(|(joined, last_active)| *last_active >= *joined + Duration::days(days))
+            .count(),
+    )
+}
+
+/// Group (joined, last active) dates into weekly cohorts, newest first,
+/// covering the last `COHORT_WEEKS` weeks up to `today`
+pub fn compute_cohorts(members: &[(NaiveDate, NaiveDate)], today: NaiveDate) -> Vec<CohortRetention> {
+    let current = week_start(today);
+    (0..COHORT_WEEKS)
+        .map(|i| current - Duration::weeks(i))
+        .filter_map(|week| {
+            let cohort: Vec<(NaiveDate, NaiveDate)> = members
+                .iter()
+                .copied()
+                .filter(|(joined, _)| week_start(*joined) == week)
+                .collect();
+            if cohort.is_empty() {
+                return None;
+            }
+            Some(CohortRetention {
+                week_start: week,
+                members: cohort.len(),
+                retained_7d: retained(&cohort, week, 7, today),
+                retained_30d: retained(&cohort, week, 30, today),
+            })
+        })
+        .collect()
+}
+
+fn format_rate(retained: Option<usize>, members: usize) -> String {
+    match retained {
+        Some(count) if members > 0 => format!("{}% ({count})", count * 100 / members),
+        _ => "—".to_string(),
+    }
+}
+
+fn overall(cohorts: &[CohortRetention], pick: fn(&CohortRetention) -> Option<usize>) -> String {
+    let (retained, members) = cohorts
+        .iter()
+        .filter_map(|c| pick(c).map(|r| (r, c.members)))
+        .fold((0, 0), |(r, m), (cr, cm)| (r + cr, m + cm));
+    if members == 0 {
+        "—".to_string()
+    } else {
+        format_rate(Some(retained), members)
+    }
+}
+
+/// Render the retention section of `/community_insights`
+pub fn format_retention_report(cohorts: &[CohortRetention]) -> String {
+    let mut lines = vec![format!("**📈 New-member retention** — weekly join cohorts, last {COHORT_WEEKS} weeks")];
+
+    if cohorts.is_empty() {
+        lines.push("No new members seen yet. Members are tracked once they post in the server.".to_string());
+        return lines.join("\n");
+    }
+
+    for cohort in cohorts {
+        lines.push(format!(
+            "`{}` 👋 {} joined · 7d {} · 30d {}",
+            cohort.week_start,
+            cohort.members,
+            format_rate(cohort.retained_7d, cohort.members),
+            format_rate(cohort.retained_30d, cohort.members)
+        ));
+    }
+
+    lines.push(format!(
+        "\n**Overall**: 7d {} · 30d {}",
+        overall(cohorts, |c| c.retained_7d),
+        overall(cohorts, |c| c.retained_30d)
+    ));
+    lines.push("_Retained = posted again on or after day 7/30. Only members who have posted are counted; — means the cohort is too recent._".to_string());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_compute_cohorts() {
+        let today = date("2026-10-15"); // Thursday
+        let members = vec![
+            // Week of 2026-08-31: both measurable for 7 and 30 days
+            (date("2026-09-01"), date("2026-10-10")),
+            (date("2026-09-02"), date("2026-09-05")),
+            // Week of 2026-09-28: measurable for 7 days only
+            (date("2026-09-29"), date("2026-10-14")),
+            // Current week: not measurable yet
+            (date("2026-10-13"), date("2026-10-13")),
+            // Outside the window
+            (date("2026-01-01"), date("2026-10-01")),
+        ];
+        let cohorts = compute_cohorts(&members, today);
+        assert_eq!(cohorts.len(), 3);
+
+        assert_eq!(cohorts[0].week_start, date("2026-10-12"));
+        assert_eq!(cohorts[0].retained_7d, None);
+
+        assert_eq!(cohorts[1].week_start, date("2026-09-28"));
+        assert_eq!(cohorts[1].retained_7d, Some(1));
+        assert_eq!(cohorts[1].retained_30d, None);
+
+        assert_eq!(cohorts[2].week_start, date("2026-08-31"));
+        assert_eq!(cohorts[2].members, 2);
+        assert_eq!(cohorts[2].retained_7d, Some(1));
+        assert_eq!(cohorts[2].retained_30d, Some(1));
+    }
+
+    #[test]
+    fn test_format_retention_report() {
+        assert!(format_retention_report(&[]).contains("No new members seen yet"));
+
+        let report = format_retention_report(&[
+            CohortRetention { week_start: date("2026-10-05"), members: 4, retained_7d: Some(3), retained_30d: None },
+            CohortRetention { week_start: date("2026-09-07"), members: 4, retained_7d: Some(1), retained_30d: Some(1) },
+        ]);
+        assert!(report.contains("`2026-10-05` 👋 4 joined · 7d 75% (3) · 30d —"));
+        assert!(report.contains("**Overall**: 7d 50% (4) · 30d 25% (1)"));
+    }
+}
@@ -2,15 +2,20 @@
 //!
 //! Detects heated discussions using keyword analysis, caps detection, and
 //! punctuation patterns. Provides confidence scoring for conflict intensity.
+//! The heuristic score is the cheap first stage: each sensitivity level has a
+//! confidence at which the LLM is asked to confirm the conflict, and a higher
+//! one at which the heuristics are trusted on their own.
 //!
-//! - **Version**: 1.0.0
+//! - **Version**: 1.1.0
 //! - **Since**: 0.1.0
 //! - **Toggleable**: true
 //!
 //! ## Changelog
+//! - 1.1.0: Per-sensitivity stage thresholds for LLM verification
 //! - 1.0.0: Initial release with 50+ hostile keywords and pattern detection
 
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 /// Hostile keywords that indicate potential conflict
 /// These are matched case-insensitively using substring matching
@@ -62,6 +67,126 @@ const HOSTILE_KEYWORDS: &[&str] = &[
     "noob", "scrub",
 ];
 
+/// Sensitivity levels, from least to most eager to intervene
+pub const SENSITIVITY_LEVELS: &[&str] = &["low", "medium", "high", "ultra"];
+
+/// Which stage of detection decided there is a conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectionStage {
+    /// The heuristic score alone was high enough
+    Heuristic,
+    /// The heuristics were unsure and the LLM confirmed it
+    LlmVerified,
+}
+
+impl DetectionStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DetectionStage::Heuristic => "heuristic",
+            DetectionStage::LlmVerified => "llm_verified",
+        }
+    }
+
+    /// `conflict_detection.detection_type`: the stage, then the heuristic reasons
+    pub fn detection_type(&self, reasons: &str) -> String {
+        if reasons.is_empty() {
+            self.as_str().to_string()
+        } else {
+            format!("{}: {reasons}", self.as_str())
+        }
+    }
+}
+
+/// What to do with a heuristic score
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StageDecision {
+    /// Below the verification threshold
+    Clear,
+    /// Ask the LLM to confirm
+    Verify,
+    /// Confident enough without asking
+    Detected,
+}
+
+/// Heuristic confidence cut-offs for one sensitivity level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StageThresholds {
+    /// Ask the LLM to confirm at or above this
+    pub verify: f32,
+    /// Treat as a conflict without asking at or above this
+    pub detect: f32,
+}
+
+impl StageThresholds {
+    pub fn decide(&self, is_conflict: bool, confidence: f32) -> StageDecision {
+        if !is_conflict || confidence < self.verify {
+            StageDecision::Clear
+        } else if confidence >= self.detect {
+            StageDecision::Detected
+        } else {
+            StageDecision::Verify
+        }
+    }
+}
+
+/// Stage thresholds for every sensitivity level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConflictThresholds {
+    pub low: StageThresholds,
+    pub medium: StageThresholds,
+    pub high: StageThresholds,
+    pub ultra: StageThresholds,
+}
+
+impl Default for ConflictThresholds {
+    fn default() -> Self {
+        ConflictThresholds {
+            low: StageThresholds { verify: 0.7, detect: 0.95 },
+            medium: StageThresholds { verify: 0.5, detect: 0.85 },
+            high: StageThresholds { verify: 0.35, detect: 0.7 },
+            ultra: StageThresholds { verify: 0.3, detect: 0.6 },
+        }
+    }
+}
+
+impl ConflictThresholds {
+    /// Thresholds for a sensitivity name (`medium` for unknown names)
+    pub fn for_sensitivity(&self, sensitivity: &str) -> StageThresholds {
+        match sensitivity.to_lowercase().as_str() {
+            "low" => self.low,
+            "high" => self.high,
+            "ultra" => self.ultra,
+            _ => self.medium,
+        }
+    }
+
+    /// The defaults with the levels in `spec` overridden, e.g. `high=0.4:0.8,low=0.75:1.0`
+    /// (verify, then detect). Errors name the part that didn't parse.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut thresholds = ConflictThresholds::default();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let invalid = || format!("invalid conflict threshold '{part}' (expected level=verify:detect, e.g. high=0.4:0.8)");
+            let (level, values) = part.split_once('=').ok_or_else(invalid)?;
+            let (verify, detect) = values.split_once(':').ok_or_else(invalid)?;
+            let (Ok(verify), Ok(detect)) = (verify.trim().parse::<f32>(), detect.trim().parse::<f32>()) else {
+                return Err(invalid());
+            };
+            if !(0.0..=1.0).contains(&verify) || !(verify..=1.0).contains(&detect) {
+                return Err(format!("conflict threshold '{part}' needs 0 <= verify <= detect <= 1"));
+            }
+            let stage = StageThresholds { verify, detect };
+            match level.trim().to_lowercase().as_str() {
+                "low" => thresholds.low = stage,
+                "medium" => thresholds.medium = stage,
+                "high" => thresholds.high = stage,
+                "ultra" => thresholds.ultra = stage,
+                _ => return Err(format!("unknown sensitivity '{}' in conflict thresholds", level.trim())),
+            }
+        }
+        Ok(thresholds)
+    }
+}
+
 /// Detector for identifying heated arguments and conflicts in conversations
 #[derive(Clone)]
 pub struct ConflictDetector {
@@ -293,6 +418,34 @@ impl Default for ConflictDetector {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_stage_decisions() {
+        let high = ConflictThresholds::default().for_sensitivity("HIGH");
+        assert_eq!(high.decide(true, 0.3), StageDecision::Clear);
+        assert_eq!(high.decide(true, 0.5), StageDecision::Verify);
+        assert_eq!(high.decide(true, 0.7), StageDecision::Detected);
+        assert_eq!(high.decide(false, 0.9), StageDecision::Clear);
+        assert_eq!(ConflictThresholds::default().for_sensitivity("unknown"), ConflictThresholds::default().medium);
+    }
+
+    #[test]
+    fn test_parse_thresholds() {
+        let thresholds = ConflictThresholds::parse("high=0.4:0.8, low = 0.75:1").unwrap();
+        assert_eq!(thresholds.high, StageThresholds { verify: 0.4, detect: 0.8 });
+        assert_eq!(thresholds.low, StageThresholds { verify: 0.75, detect: 1.0 });
+        assert_eq!(thresholds.medium, ConflictThresholds::default().medium);
+        assert_eq!(ConflictThresholds::parse("").unwrap(), ConflictThresholds::default());
+        assert!(ConflictThresholds::parse("high=0.8:0.4").is_err());
+        assert!(ConflictThresholds::parse("extreme=0.1:0.2").is_err());
+        assert!(ConflictThresholds::parse("high=0.4").is_err());
+    }
+
+    #[test]
+    fn test_detection_type_names_the_stage() {
+        assert_eq!(DetectionStage::LlmVerified.detection_type("hostile_language"), "llm_verified: hostile_language");
+        assert_eq!(DetectionStage::Heuristic.detection_type(""), "heuristic");
+    }
+
     #[test]
     fn test_conflict_score_hostile_keywords() {
         let detector = ConflictDetector::new();
@@ -0,0 +1,245 @@
+//! # Feature: Conflict Escalation
+//!
+//! With `conflict_mod_alerts` enabled, a conflict detected at or above the
+//! guild's `conflict_alert_threshold` is held for a moderator instead of being
+//! mediated by the bot on its own. The alert goes to `conflict_alert_channel`
+//! (or the mod log) with an embed showing the participants, an excerpt and the
+//! confidence, and three buttons: mediate now, dismiss, or escalate by timing
+//! the participants out. Whatever the moderator picks is kept in
+//! `mediation_history` with an `effectiveness_rating`, so their verdicts on
+//! detections can be counted against the sensitivity settings.
+//!
+//! - **Version**: 1.0.1
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.1: Excerpt lines are cut with the shared `discord_limits::truncate`
+//! - 1.0.0: Initial release
+
+use crate::core::discord_limits::truncate;
+use std::collections::HashMap;
+
+/// Button custom ID prefix, followed by `<conflict_id>:<decision>`
+pub const CONFLICT_ACTION_PREFIX: &str = "conflict_action:";
+
+/// Confidence in percent from which conflicts wait for a moderator, when the guild hasn't set one
+pub const DEFAULT_ALERT_THRESHOLD: u32 = 70;
+
+/// How long escalated participants are timed out, when the guild hasn't set it
+pub const DEFAULT_ESCALATION_TIMEOUT_MINUTES: i64 = 10;
+
+/// Messages shown in the alert excerpt, newest last
+const EXCERPT_MESSAGES: usize = 5;
+
+/// Longest message kept in the excerpt
+const MAX_EXCERPT_LINE_CHARS: usize = 200;
+
+/// What a moderator did about a detected conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictDecision {
+    /// Had the bot write and post a mediation
+    Mediate,
+    /// Sent one of the suggested replies
+    Suggestion,
+    /// Judged it not worth stepping in
+    Dismiss,
+    /// Timed the participants out
+    Escalate,
+}
+
+impl ConflictDecision {
+    /// Decisions offered as buttons on the alert, in display order
+    pub const ACTIONS: [ConflictDecision; 3] = [ConflictDecision::Mediate, ConflictDecision::Dismiss, ConflictDecision::Escalate];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictDecision::Mediate => "mediate",
+            ConflictDecision::Suggestion => "suggestion",
+            ConflictDecision::Dismiss => "dismiss",
+            ConflictDecision::Escalate => "escalate",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "mediate" => Some(ConflictDecision::Mediate),
+            "suggestion" => Some(ConflictDecision::Suggestion),
+            "dismiss" => Some(ConflictDecision::Dismiss),
+            "escalate" => Some(ConflictDecision::Escalate),
+            _ => None,
+        }
+    }
+
+    /// Button label on the alert
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConflictDecision::Mediate => "Mediate now",
+            ConflictDecision::Suggestion => "Send suggestion",
+            ConflictDecision::Dismiss => "Dismiss",
+            ConflictDecision::Escalate => "Escalate (timeout users)",
+        }
+    }
+
+    /// How much the detection called for stepping in, from 1 (false alarm) to 5 (words weren't enough)
+    pub fn effectiveness_rating(&self) -> i64 {
+        match self {
+            ConflictDecision::Dismiss => 1,
+            ConflictDecision::Mediate | ConflictDecision::Suggestion => 4,
+            ConflictDecision::Escalate => 5,
+        }
+    }
+}
+
+pub fn action_custom_id(conflict_id: i64, decision: ConflictDecision) -> String {
+    format!("{CONFLICT_ACTION_PREFIX}{conflict_id}:{}", decision.as_str())
+}
+
+/// `(conflict_id, decision)` from an alert button's custom ID; suggestions have buttons of their own
+pub fn parse_action_custom_id(custom_id: &str) -> Option<(i64, ConflictDecision)> {
+    let (conflict_id, decision) = custom_id.strip_prefix(CONFLICT_ACTION_PREFIX)?.split_once(':')?;
+    let decision = ConflictDecision::parse(decision).filter(|d| ConflictDecision::ACTIONS.contains(d))?;
+    Some((conflict_id.parse().ok()?, decision))
+}
+
+/// The `conflict_alert_threshold` guild setting as a confidence from 0 to 1
+pub fn alert_threshold(value: Option<&str>) -> f32 {
+    let percent = value
+        .and_then(|v| v.parse::<u32>().ok())
+        .filter(|v| (1..=100).contains(v))
+        .unwrap_or(DEFAULT_ALERT_THRESHOLD);
+    percent as f32 / 100.0
+}
+
+/// The `conflict_escalation_timeout` guild setting in minutes
+pub fn escalation_timeout_minutes(value: Option<&str>) -> i64 {
+    value
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| (1..=1440).contains(v))
+        .unwrap_or(DEFAULT_ESCALATION_TIMEOUT_MINUTES)
+}
+
+/// The last few messages as `**name**: text` lines for the alert embed.
+/// `messages` are `(user_id, content, timestamp)`, oldest first.
+pub fn alert_excerpt(messages: &[(String, String, String)], names: &HashMap<String, String>) -> String {
+    let start = messages.len().saturating_sub(EXCERPT_MESSAGES);
+    messages[start..]
+        .iter()
+        .map(|(user_id, content, _)| {
+            let name = names.get(user_id).unwrap_or(user_id);
+            let content = truncate(&content.replace('\n', " "), MAX_EXCERPT_LINE_CHARS);
+            format!("**{name}**: {content}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Line appended to the alert once a moderator has decided
+pub fn format_decision_taken(decision: ConflictDecision, moderator_id: &str, detail: Option<&str>) -> String {
+    let outcome = match decision {
+        ConflictDecision::Mediate => "✅ Mediation posted",
+        ConflictDecision::Suggestion => "✅ Suggested reply sent",
+        ConflictDecision::Dismiss => "🚫 Dismissed",
+        ConflictDecision::Escalate => "⛔ Escalated",
+    };
+    match detail {
+        Some(detail) => format!("{outcome} by <@{moderator_id}>: {detail}"),
+        None => format!("{outcome} by <@{moderator_id}>."),
+    }
+}
+
+/// Why a second decision on the same conflict is refused; suggestions approved before
+/// decisions were recorded have none
+pub fn format_already_decided(moderator_id: &str, decision: Option<&str>) -> String {
+    match decision.and_then(ConflictDecision::parse) {
+        Some(ConflictDecision::Dismiss) => format!("<@{moderator_id}> already dismissed this conflict."),
+        Some(ConflictDecision::Escalate) => format!("<@{moderator_id}> already escalated this conflict."),
+        _ => format!("<@{moderator_id}> already sent a reply for this conflict."),
+    }
+}
+
+/// Moderator decisions over a period, for `/settings`; `counts` are `(decision, count)` pairs
+pub fn format_decision_feedback(counts: &[(String, i64)]) -> String {
+    let count = |decision: ConflictDecision| {
+        counts.iter().filter(|(d, _)| d == decision.as_str()).map(|(_, n)| n).sum::<i64>()
+    };
+    let mediated = count(ConflictDecision::Mediate) + count(ConflictDecision::Suggestion);
+    let escalated = count(ConflictDecision::Escalate);
+    let dismissed = count(ConflictDecision::Dismiss);
+    let total = mediated + escalated + dismissed;
+    if total == 0 {
+        return "no decisions yet".to_string();
+    }
+    format!(
+        "{mediated} mediated, {escalated} escalated, {dismissed} dismissed ({:.0}% false alarms)",
+        dismissed as f64 * 100.0 / total as f64
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_id_round_trip() {
+        for decision in ConflictDecision::ACTIONS {
+            let id = action_custom_id(17, decision);
+            assert!(id.len() <= 100);
+            assert_eq!(parse_action_custom_id(&id), Some((17, decision)));
+        }
+        assert_eq!(parse_action_custom_id("conflict_action:17:suggestion"), None);
+        assert_eq!(parse_action_custom_id("conflict_action:x:dismiss"), None);
+        assert_eq!(parse_action_custom_id("conflict_suggest:17:1"), None);
+    }
+
+    #[test]
+    fn test_effectiveness_rating_orders_decisions() {
+        assert!(ConflictDecision::Dismiss.effectiveness_rating() < ConflictDecision::Mediate.effectiveness_rating());
+        assert!(ConflictDecision::Mediate.effectiveness_rating() < ConflictDecision::Escalate.effectiveness_rating());
+        assert_eq!(ConflictDecision::parse("escalate"), Some(ConflictDecision::Escalate));
+    }
+
+    #[test]
+    fn test_settings_defaults() {
+        assert_eq!(alert_threshold(None), 0.7);
+        assert_eq!(alert_threshold(Some("85")), 0.85);
+        assert_eq!(alert_threshold(Some("0")), 0.7);
+        assert_eq!(escalation_timeout_minutes(Some("30")), 30);
+        assert_eq!(escalation_timeout_minutes(Some("forever")), DEFAULT_ESCALATION_TIMEOUT_MINUTES);
+    }
+
+    #[test]
+    fn test_alert_excerpt() {
+        let names = HashMap::from([("1".to_string(), "Ann".to_string())]);
+        let mut messages: Vec<(String, String, String)> =
+            (0..6).map(|i| ("2".to_string(), format!("message {i}"), "0".to_string())).collect();
+        messages.push(("1".to_string(), "multi\nline".to_string(), "0".to_string()));
+        let excerpt = alert_excerpt(&messages, &names);
+        assert_eq!(excerpt.lines().count(), EXCERPT_MESSAGES);
+        assert!(excerpt.starts_with("**2**: message 2"));
+        assert!(excerpt.ends_with("**Ann**: multi line"));
+    }
+
+    #[test]
+    fn test_format_decision_messages() {
+        assert_eq!(format_decision_taken(ConflictDecision::Dismiss, "9", None), "🚫 Dismissed by <@9>.");
+        assert_eq!(
+            format_decision_taken(ConflictDecision::Escalate, "9", Some("<@1> timed out for 10 minutes")),
+            "⛔ Escalated by <@9>: <@1> timed out for 10 minutes"
+        );
+        assert_eq!(format_already_decided("9", Some("dismiss")), "<@9> already dismissed this conflict.");
+        assert_eq!(format_already_decided("9", None), "<@9> already sent a reply for this conflict.");
+    }
+
+    #[test]
+    fn test_format_decision_feedback() {
+        assert_eq!(format_decision_feedback(&[]), "no decisions yet");
+        let counts = vec![
+            ("mediate".to_string(), 2),
+            ("suggestion".to_string(), 1),
+            ("escalate".to_string(), 1),
+            ("dismiss".to_string(), 4),
+        ];
+        assert_eq!(format_decision_feedback(&counts), "3 mediated, 1 escalated, 4 dismissed (50% false alarms)");
+    }
+}
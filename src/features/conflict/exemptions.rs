@@ -0,0 +1,155 @@
+//! # Feature: Conflict Exemptions
+//!
+//! `/conflict exempt` keeps chosen users, roles or whole channels out of
+//! conflict detection, either everywhere in the guild or in one channel.
+//! Exempt members' messages are dropped before the detector sees them, so
+//! they never count as participants or in interaction patterns; an exempt
+//! channel isn't checked at all. Typical uses are other bots, moderators
+//! who argue for a living, and roleplay channels.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use crate::database::ConflictExemption;
+use std::collections::HashSet;
+
+/// What an exemption applies to, as stored in `conflict_exemptions.target_type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExemptionTarget {
+    User,
+    Role,
+    /// Everyone in the channel
+    Channel,
+}
+
+impl ExemptionTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExemptionTarget::User => "user",
+            ExemptionTarget::Role => "role",
+            ExemptionTarget::Channel => "channel",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "user" => Some(ExemptionTarget::User),
+            "role" => Some(ExemptionTarget::Role),
+            "channel" => Some(ExemptionTarget::Channel),
+            _ => None,
+        }
+    }
+
+    /// How the target is written in Discord, e.g. `<@&123>` for a role
+    pub fn mention(&self, id: &str) -> String {
+        match self {
+            ExemptionTarget::User => format!("<@{id}>"),
+            ExemptionTarget::Role => format!("<@&{id}>"),
+            ExemptionTarget::Channel => format!("<#{id}>"),
+        }
+    }
+}
+
+/// The exemptions that apply in `channel_id`: the guild-wide ones and the channel's own
+pub fn applicable<'a>(exemptions: &'a [ConflictExemption], channel_id: &'a str) -> impl Iterator<Item = &'a ConflictExemption> {
+    exemptions
+        .iter()
+        .filter(move |e| e.channel_id.as_deref().is_none_or(|scope| scope == channel_id))
+}
+
+/// Whether conflict detection is off for the whole of `channel_id`
+pub fn channel_exempt(exemptions: &[ConflictExemption], channel_id: &str) -> bool {
+    applicable(exemptions, channel_id)
+        .any(|e| e.target_type == ExemptionTarget::Channel.as_str() && e.target_id == channel_id)
+}
+
+/// IDs of the users or roles exempt in `channel_id`
+pub fn exempt_ids(exemptions: &[ConflictExemption], channel_id: &str, target: ExemptionTarget) -> HashSet<String> {
+    applicable(exemptions, channel_id)
+        .filter(|e| e.target_type == target.as_str())
+        .map(|e| e.target_id.clone())
+        .collect()
+}
+
+/// Detector input without the messages of exempt users; `messages` are `(user_id, content, timestamp)`
+pub fn without_exempt(messages: Vec<(String, String, String)>, exempt_users: &HashSet<String>) -> Vec<(String, String, String)> {
+    messages.into_iter().filter(|(user_id, _, _)| !exempt_users.contains(user_id)).collect()
+}
+
+/// One exemption as a line of `/conflict exempt list`
+pub fn format_exemption(exemption: &ConflictExemption) -> String {
+    let target = ExemptionTarget::parse(&exemption.target_type)
+        .map(|t| t.mention(&exemption.target_id))
+        .unwrap_or_else(|| exemption.target_id.clone());
+    match (&exemption.channel_id, exemption.target_type.as_str()) {
+        (_, "channel") => format!("• {target}: whole channel"),
+        (Some(channel), _) => format!("• {target} in <#{channel}>"),
+        (None, _) => format!("• {target} everywhere"),
+    }
+}
+
+/// The `/conflict exempt list` reply
+pub fn format_exemption_list(exemptions: &[ConflictExemption]) -> String {
+    if exemptions.is_empty() {
+        return "ℹ️ Nobody is exempt from conflict detection in this server.".to_string();
+    }
+    let lines: Vec<String> = exemptions.iter().map(format_exemption).collect();
+    format!("**🕊️ Conflict detection exemptions**\n{}", lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exemption(channel: Option<&str>, target: ExemptionTarget, id: &str) -> ConflictExemption {
+        ConflictExemption {
+            channel_id: channel.map(str::to_string),
+            target_type: target.as_str().to_string(),
+            target_id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_exempt_ids_by_scope() {
+        let exemptions = vec![
+            exemption(None, ExemptionTarget::User, "1"),
+            exemption(Some("10"), ExemptionTarget::User, "2"),
+            exemption(Some("10"), ExemptionTarget::Role, "7"),
+        ];
+        assert_eq!(exempt_ids(&exemptions, "10", ExemptionTarget::User), HashSet::from(["1".to_string(), "2".to_string()]));
+        assert_eq!(exempt_ids(&exemptions, "11", ExemptionTarget::User), HashSet::from(["1".to_string()]));
+        assert!(exempt_ids(&exemptions, "11", ExemptionTarget::Role).is_empty());
+    }
+
+    #[test]
+    fn test_channel_exempt() {
+        let exemptions = vec![exemption(Some("10"), ExemptionTarget::Channel, "10")];
+        assert!(channel_exempt(&exemptions, "10"));
+        assert!(!channel_exempt(&exemptions, "11"));
+    }
+
+    #[test]
+    fn test_without_exempt() {
+        let message = |user: &str| (user.to_string(), "text".to_string(), "0".to_string());
+        let kept = without_exempt(vec![message("1"), message("2"), message("1")], &HashSet::from(["1".to_string()]));
+        assert_eq!(kept, vec![message("2")]);
+    }
+
+    #[test]
+    fn test_format_exemption_list() {
+        assert!(format_exemption_list(&[]).contains("Nobody is exempt"));
+        let list = format_exemption_list(&[
+            exemption(None, ExemptionTarget::User, "1"),
+            exemption(Some("10"), ExemptionTarget::Role, "7"),
+            exemption(Some("10"), ExemptionTarget::Channel, "10"),
+        ]);
+        assert!(list.contains("• <@1> everywhere"));
+        assert!(list.contains("• <@&7> in <#10>"));
+        assert!(list.contains("• <#10>: whole channel"));
+    }
+}
@@ -69,9 +69,14 @@ impl ConflictMediator {
 
     /// Check if mediation is allowed in this channel right now
     pub fn can_intervene(&self, channel_id: &str) -> bool {
+        self.can_intervene_within(channel_id, self.mediation_cooldown)
+    }
+
+    /// [`Self::can_intervene`] with a cooldown other than the one given at construction
+    pub fn can_intervene_within(&self, channel_id: &str, cooldown: Duration) -> bool {
         // Check cooldown
         if let Some(last_time) = self.channel_interventions.get(channel_id) {
-            if last_time.elapsed() < self.mediation_cooldown {
+            if last_time.elapsed() < cooldown {
                 return false;
             }
         }
@@ -1,13 +1,32 @@
 //! # Conflict Feature
 //!
-//! Detects heated discussions and provides Obi-Wan themed mediation.
+//! Detects heated discussions and provides Obi-Wan themed mediation, and
+//! can alert moderators with suggested replies they send with one click.
+//! Borderline heuristic detections are confirmed by the model first, and
+//! confident ones can be held for a moderator to mediate, dismiss or escalate.
+//! Chosen users, roles and channels can be exempted from detection.
 //!
-//! - **Version**: 1.0.0
+//! - **Version**: 1.4.0
 //! - **Since**: 0.1.0
 //! - **Toggleable**: true
 
 pub mod detector;
+pub mod escalation;
+pub mod exemptions;
 pub mod mediator;
+pub mod suggestions;
+pub mod verification;
 
-pub use detector::ConflictDetector;
+pub use detector::{ConflictDetector, ConflictThresholds, DetectionStage, StageDecision, StageThresholds, SENSITIVITY_LEVELS};
+pub use escalation::{
+    action_custom_id, alert_excerpt, alert_threshold, escalation_timeout_minutes, format_already_decided, format_decision_feedback,
+    format_decision_taken, parse_action_custom_id, ConflictDecision, CONFLICT_ACTION_PREFIX, DEFAULT_ALERT_THRESHOLD,
+    DEFAULT_ESCALATION_TIMEOUT_MINUTES,
+};
+pub use exemptions::{channel_exempt, exempt_ids, format_exemption_list, without_exempt, ExemptionTarget};
 pub use mediator::ConflictMediator;
+pub use suggestions::{
+    format_conflict_alert, format_suggestion_sent, parse_suggestion_custom_id, parse_suggestions,
+    suggestion_custom_id, suggestion_from_alert, suggestions_prompt, CONFLICT_SUGGEST_PREFIX, MAX_SUGGESTIONS,
+};
+pub use verification::{anonymized_transcript, parse_verification, verification_prompt};
@@ -0,0 +1,161 @@
+//! # Feature: Suggested Mediation Replies
+//!
+//! With the `conflict_mod_alerts` guild setting enabled, a detected conflict
+//! is reported to the `mod_log_channel` along with a few de-escalation replies
+//! the model suggests. Each has a button; the moderator who clicks it has that
+//! reply posted in the conflict channel as the bot, and the approval is kept in
+//! `mediation_history`. The suggestions live in the alert's own text, so the
+//! buttons keep working across restarts.
+//!
+//! - **Version**: 1.0.1
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.1: Long suggestions are cut with the shared `discord_limits::truncate`
+//! - 1.0.0: Initial release
+
+use crate::core::discord_limits::truncate;
+
+/// Button custom ID prefix, followed by `<conflict_id>:<suggestion number>`
+pub const CONFLICT_SUGGEST_PREFIX: &str = "conflict_suggest:";
+
+/// Most suggestions offered per alert
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Longest suggestion kept, so the alert stays within a Discord message
+const MAX_SUGGESTION_CHARS: usize = 300;
+
+/// Used when the model is unavailable or returns nothing usable
+const FALLBACK_SUGGESTIONS: &[&str] = &[
+    "Let's take a breath for a moment. You both clearly care about this, so let's keep it about the ideas, not each other.",
+    "It sounds like there are two good points being talked past. Could each of you sum up the other's view before going on?",
+    "Friendly reminder to keep things respectful. If this needs more room, a thread or DM might be a better place for it.",
+];
+
+/// Instructions for generating suggestions from the recent conversation
+pub fn suggestions_prompt(conflict_type: &str, conversation: &str) -> String {
+    format!(
+        "A conversation in a Discord server has become heated (detected: {conflict_type}). A moderator will pick one \
+reply to post as the server's bot to calm it down.\n\nRecent conversation:\n{conversation}\n\nWrite {MAX_SUGGESTIONS} \
+different short de-escalation replies, one per line, each a single sentence or two. Be neutral and kind, don't take \
+sides or lecture, and don't mention that you are a bot or a moderator. Output only the replies, no numbering."
+    )
+}
+
+/// Turn the model's reply into at most [`MAX_SUGGESTIONS`] one-line suggestions,
+/// filling up with the built-in ones when it gave fewer
+pub fn parse_suggestions(reply: &str) -> Vec<String> {
+    let mut suggestions: Vec<String> = reply
+        .lines()
+        .map(|line| {
+            line.trim()
+                .trim_start_matches(|c: char| c.is_ascii_digit() || matches!(c, '.' | ')' | '-' | '*' | '•'))
+                .trim()
+                .trim_matches('"')
+                .trim()
+        })
+        .filter(|line| !line.is_empty())
+        .map(|line| truncate(line, MAX_SUGGESTION_CHARS))
+        .take(MAX_SUGGESTIONS)
+        .collect();
+    for fallback in FALLBACK_SUGGESTIONS {
+        if suggestions.len() >= MAX_SUGGESTIONS {
+            break;
+        }
+        if !suggestions.iter().any(|s| s == fallback) {
+            suggestions.push(fallback.to_string());
+        }
+    }
+    suggestions
+}
+
+/// The mod channel alert; suggestions are numbered from 1 and read back by [`suggestion_from_alert`]
+pub fn format_conflict_alert(
+    channel_id: &str,
+    conflict_type: &str,
+    confidence: f32,
+    participants: &[String],
+    suggestions: &[String],
+) -> String {
+    let people = participants.iter().map(|id| format!("<@{id}>")).collect::<Vec<_>>().join(", ");
+    let mut alert = format!(
+        "⚠️ **Conflict detected** in <#{channel_id}> ({conflict_type}, {:.0}% confidence)\nParticipants: {people}\n\n\
+Suggested replies, posted as the bot when you click one:",
+        confidence * 100.0
+    );
+    for (i, suggestion) in suggestions.iter().enumerate() {
+        alert.push_str(&format!("\n**{}.** {suggestion}", i + 1));
+    }
+    alert
+}
+
+/// Suggestion `number` (from 1) in an alert made by [`format_conflict_alert`]
+pub fn suggestion_from_alert(alert: &str, number: usize) -> Option<String> {
+    let marker = format!("**{number}.** ");
+    alert
+        .lines()
+        .find_map(|line| line.strip_prefix(&marker))
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+pub fn suggestion_custom_id(conflict_id: i64, number: usize) -> String {
+    format!("{CONFLICT_SUGGEST_PREFIX}{conflict_id}:{number}")
+}
+
+/// `(conflict_id, suggestion number)` from a suggestion button's custom ID
+pub fn parse_suggestion_custom_id(custom_id: &str) -> Option<(i64, usize)> {
+    let (conflict_id, number) = custom_id.strip_prefix(CONFLICT_SUGGEST_PREFIX)?.split_once(':')?;
+    Some((conflict_id.parse().ok()?, number.parse().ok()?))
+}
+
+/// Line appended to the alert once a suggestion has been sent
+pub fn format_suggestion_sent(number: usize, moderator_id: &str) -> String {
+    format!("✅ Suggestion {number} was sent by <@{moderator_id}>.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_suggestions() {
+        let reply = "1. \"Let's slow down a little.\"\n\n2) Both of you make fair points.\n- Maybe take this to a thread?\n4. Extra one";
+        assert_eq!(
+            parse_suggestions(reply),
+            vec![
+                "Let's slow down a little.".to_string(),
+                "Both of you make fair points.".to_string(),
+                "Maybe take this to a thread?".to_string(),
+            ]
+        );
+
+        let filled = parse_suggestions("Only one idea.");
+        assert_eq!(filled.len(), MAX_SUGGESTIONS);
+        assert_eq!(filled[0], "Only one idea.");
+        assert_eq!(parse_suggestions("").len(), MAX_SUGGESTIONS);
+    }
+
+    #[test]
+    fn test_alert_round_trip() {
+        let suggestions = parse_suggestions("First reply.\nSecond reply.\nThird reply.");
+        let alert = format_conflict_alert("42", "heated_argument", 0.72, &["1".to_string(), "2".to_string()], &suggestions);
+        assert!(alert.contains("<#42>") && alert.contains("72%") && alert.contains("<@1>, <@2>"));
+        assert_eq!(suggestion_from_alert(&alert, 2).as_deref(), Some("Second reply."));
+        assert_eq!(suggestion_from_alert(&alert, 4), None);
+
+        // Still readable after the sent line is appended
+        let updated = format!("{alert}\n\n{}", format_suggestion_sent(3, "9"));
+        assert_eq!(suggestion_from_alert(&updated, 3).as_deref(), Some("Third reply."));
+    }
+
+    #[test]
+    fn test_custom_id_round_trip() {
+        let id = suggestion_custom_id(17, 2);
+        assert!(id.len() <= 100);
+        assert_eq!(parse_suggestion_custom_id(&id), Some((17, 2)));
+        assert_eq!(parse_suggestion_custom_id("conflict_suggest:x:1"), None);
+        assert_eq!(parse_suggestion_custom_id("regen_1"), None);
+    }
+}
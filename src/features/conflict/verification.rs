@@ -0,0 +1,107 @@
+//! # Feature: Conflict Verification
+//!
+//! Second stage of conflict detection. When the keyword heuristics score a
+//! conversation between a sensitivity level's `verify` and `detect`
+//! thresholds, the model is shown the recent messages, with speakers
+//! anonymized, and asked whether it is really a heated argument. Banter that
+//! happens to use hostile words is then left alone without paying for a
+//! mediation.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Messages shown to the model, newest last
+const TRANSCRIPT_MESSAGES: usize = 10;
+
+/// The recent messages as `A: text` lines, oldest first, speakers lettered in order of appearance.
+/// `messages` are `(user_id, content, timestamp)`, newest first as the database returns them.
+pub fn anonymized_transcript(messages: &[(String, String, String)]) -> String {
+    let mut speakers: HashMap<&str, String> = HashMap::new();
+    let mut transcript = String::new();
+    for (user_id, content, _) in messages.iter().take(TRANSCRIPT_MESSAGES).rev() {
+        let next = speakers.len();
+        let speaker = speakers.entry(user_id.as_str()).or_insert_with(|| speaker_label(next));
+        transcript.push_str(&format!("{speaker}: {}\n", content.replace('\n', " ")));
+    }
+    transcript
+}
+
+fn speaker_label(index: usize) -> String {
+    let letter = (b'A' + (index % 26) as u8) as char;
+    if index < 26 {
+        letter.to_string()
+    } else {
+        format!("{letter}{}", index / 26)
+    }
+}
+
+/// Instructions for confirming a heuristic detection
+pub fn verification_prompt(reasons: &str, transcript: &str) -> String {
+    format!(
+        "You review Discord conversations flagged by a keyword filter as possibly heated (signals: {reasons}). \
+Decide whether the participants are actually in a hostile argument that a moderator would want calmed down, \
+as opposed to friendly banter, jokes, quoting, or strong but civil disagreement.\n\nConversation:\n{transcript}\n\
+Answer with only JSON: {{\"conflict\": true or false, \"confidence\": number from 0 to 1}}"
+    )
+}
+
+/// Read the model's answer as `(is_conflict, confidence)`; None when it is neither JSON nor a plain yes/no
+pub fn parse_verification(reply: &str) -> Option<(bool, f32)> {
+    let reply = reply.trim();
+    let json = reply
+        .find('{')
+        .zip(reply.rfind('}'))
+        .and_then(|(start, end)| serde_json::from_str::<Value>(&reply[start..=end]).ok());
+    if let Some(value) = json {
+        let conflict = value.get("conflict").and_then(Value::as_bool)?;
+        let confidence = value
+            .get("confidence")
+            .and_then(Value::as_f64)
+            .map(|c| (c as f32).clamp(0.0, 1.0))
+            .unwrap_or(if conflict { 1.0 } else { 0.0 });
+        return Some((conflict, confidence));
+    }
+    let word = reply
+        .split(|c: char| !c.is_alphabetic())
+        .find(|w| !w.is_empty())?
+        .to_lowercase();
+    match word.as_str() {
+        "yes" | "true" => Some((true, 1.0)),
+        "no" | "false" => Some((false, 0.0)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(user: &str, content: &str) -> (String, String, String) {
+        (user.to_string(), content.to_string(), "0".to_string())
+    }
+
+    #[test]
+    fn test_anonymized_transcript_oldest_first() {
+        let messages = vec![message("2", "no YOU are"), message("1", "you're an idiot"), message("2", "hi\nthere")];
+        assert_eq!(anonymized_transcript(&messages), "A: hi there\nB: you're an idiot\nA: no YOU are\n");
+    }
+
+    #[test]
+    fn test_parse_verification() {
+        assert_eq!(parse_verification(r#"{"conflict": true, "confidence": 0.8}"#), Some((true, 0.8)));
+        assert_eq!(parse_verification("```json\n{\"conflict\": false, \"confidence\": 0.9}\n```"), Some((false, 0.9)));
+        assert_eq!(parse_verification(r#"{"conflict": true, "confidence": 3}"#), Some((true, 1.0)));
+        assert_eq!(parse_verification(r#"{"conflict": true}"#), Some((true, 1.0)));
+        assert_eq!(parse_verification("No."), Some((false, 0.0)));
+        assert_eq!(parse_verification("Yes, they are arguing"), Some((true, 1.0)));
+        assert_eq!(parse_verification("Hard to say"), None);
+        assert_eq!(parse_verification(r#"{"verdict": "yes"}"#), None);
+    }
+}
@@ -0,0 +1,109 @@
+//! # Feature: Moderation Alerts
+//!
+//! Background task that DMs each moderator listed in the guild's
+//! `content_moderation_notify` setting about recorded moderation events.
+//! Guilds without moderators listed only keep the events for review.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use super::policy::parse_moderator_ids;
+use crate::core::Result;
+use crate::database::{Database, ModerationEvent};
+use log::{info, warn};
+use serenity::http::Http;
+use serenity::model::id::UserId;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often recorded events are sent
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Events sent per check
+const EVENTS_PER_CHECK: i64 = 20;
+
+/// DM moderators about recorded moderation events, forever
+pub async fn moderation_alert_loop(database: Database, http: Arc<Http>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    info!("Moderation alert task started (checks every 30s)");
+
+    loop {
+        interval.tick().await;
+        let events = match database.get_pending_moderation_events(EVENTS_PER_CHECK).await {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("Failed to load pending moderation events: {e}");
+                continue;
+            }
+        };
+        for event in events {
+            if let Err(e) = notify_moderators(&database, &http, &event).await {
+                warn!("Failed to send moderation event {} for guild {}: {e}", event.id, event.guild_id);
+            }
+            // Marked either way, so a moderator with closed DMs doesn't retry forever
+            if let Err(e) = database.mark_moderation_event_notified(event.id).await {
+                warn!("Failed to mark moderation event {} notified: {e}", event.id);
+            }
+        }
+    }
+}
+
+async fn notify_moderators(database: &Database, http: &Http, event: &ModerationEvent) -> Result<()> {
+    let moderators = database
+        .get_guild_setting(&event.guild_id, "content_moderation_notify")
+        .await?
+        .map(|value| parse_moderator_ids(&value))
+        .unwrap_or_default();
+    let alert = format_moderation_alert(event);
+    for moderator in moderators {
+        let dm = UserId(moderator).create_dm_channel(http).await?;
+        dm.say(http, &alert).await?;
+    }
+    Ok(())
+}
+
+/// The DM sent to moderators about a flagged message or reply
+pub fn format_moderation_alert(event: &ModerationEvent) -> String {
+    let reply = event.direction == "output";
+    let what = if reply { format!("A reply to <@{}>", event.user_id) } else { format!("A message from <@{}>", event.user_id) };
+    let outcome = match (event.action.as_str(), reply) {
+        ("block", true) => "withheld",
+        ("block", false) => "blocked",
+        ("warn", true) => "posted with a warning",
+        ("warn", false) => "answered with a warning",
+        _ => "logged",
+    };
+    let channel = if event.channel_id.is_empty() { String::new() } else { format!(" in <#{}>", event.channel_id) };
+    let excerpt = event.excerpt.replace('\n', "\n> ");
+    format!("🛡️ **Content flagged**\n{what}{channel} was {outcome} for: {}\n> {excerpt}", event.categories)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_moderation_alert() {
+        let event = ModerationEvent {
+            id: 1,
+            guild_id: "10".to_string(),
+            channel_id: "20".to_string(),
+            user_id: "30".to_string(),
+            direction: "input".to_string(),
+            action: "block".to_string(),
+            categories: "harassment, violence".to_string(),
+            excerpt: "first line\nsecond line".to_string(),
+        };
+        assert_eq!(
+            format_moderation_alert(&event),
+            "🛡️ **Content flagged**\nA message from <@30> in <#20> was blocked for: harassment, violence\n> first line\n> second line"
+        );
+
+        let reply = ModerationEvent { direction: "output".to_string(), action: "warn".to_string(), channel_id: String::new(), ..event };
+        assert!(format_moderation_alert(&reply).contains("A reply to <@30> was posted with a warning"));
+    }
+}
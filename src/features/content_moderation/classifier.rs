@@ -0,0 +1,114 @@
+//! # Feature: Text Moderation Classifier
+//!
+//! Sends text to OpenAI's moderation endpoint and returns the categories it
+//! flagged, e.g. `harassment` or `self-harm/intent`. The endpoint is free, but
+//! it needs `OPENAI_API_KEY` whichever chat provider is configured.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release using omni-moderation-latest
+
+use crate::core::{BotError, Result};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const MODERATION_URL: &str = "https://api.openai.com/v1/moderations";
+const MODERATION_MODEL: &str = "omni-moderation-latest";
+
+pub struct ModerationClient {
+    openai_api_key: String,
+    client: reqwest::Client,
+    /// Stands in for the endpoint in tests: text containing it is flagged for `harassment`
+    #[cfg(test)]
+    flag_phrase: Option<&'static str>,
+}
+
+impl ModerationClient {
+    pub fn new(openai_api_key: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        ModerationClient {
+            openai_api_key,
+            client,
+            #[cfg(test)]
+            flag_phrase: None,
+        }
+    }
+
+    /// A client that flags any text containing `phrase`, without calling the endpoint
+    #[cfg(test)]
+    pub fn flagging(phrase: &'static str) -> Self {
+        ModerationClient { flag_phrase: Some(phrase), ..ModerationClient::new("test".to_string()) }
+    }
+
+    /// Whether there is a key to call the endpoint with
+    pub fn is_configured(&self) -> bool {
+        !self.openai_api_key.is_empty()
+    }
+
+    /// Categories the text was flagged for, empty when it's fine
+    pub async fn classify(&self, text: &str) -> Result<Vec<String>> {
+        #[cfg(test)]
+        if let Some(phrase) = self.flag_phrase {
+            return Ok(if text.contains(phrase) { vec!["harassment".to_string()] } else { Vec::new() });
+        }
+        let response = self
+            .client
+            .post(MODERATION_URL)
+            .bearer_auth(&self.openai_api_key)
+            .json(&json!({ "model": MODERATION_MODEL, "input": text }))
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(BotError::openai(format!("Moderation API error (status {status})")));
+        }
+        let body: Value = response.json().await?;
+        flagged_categories(&body)
+    }
+}
+
+/// Flagged categories from a moderation response body, sorted
+pub fn flagged_categories(body: &Value) -> Result<Vec<String>> {
+    let result = body
+        .pointer("/results/0")
+        .ok_or_else(|| BotError::openai("No results in moderation response"))?;
+    let mut categories: Vec<String> = result
+        .get("categories")
+        .and_then(Value::as_object)
+        .map(|categories| {
+            categories
+                .iter()
+                .filter(|(_, flagged)| flagged.as_bool() == Some(true))
+                .map(|(name, _)| name.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    categories.sort();
+    Ok(categories)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flagged_categories() {
+        let body = json!({
+            "results": [{
+                "flagged": true,
+                "categories": { "violence": true, "harassment": true, "sexual": false, "hate": null }
+            }]
+        });
+        assert_eq!(flagged_categories(&body).unwrap(), vec!["harassment", "violence"]);
+
+        let clean = json!({ "results": [{ "flagged": false, "categories": { "harassment": false } }] });
+        assert!(flagged_categories(&clean).unwrap().is_empty());
+        assert!(flagged_categories(&json!({ "results": [] })).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! # Feature: Moderation Guard
+//!
+//! Checked on both sides of every AI reply in a guild, whichever feature asks
+//! for it: the member's message before it goes to the model, and the generated
+//! reply before it is posted. Flagged content is recorded in `moderation_events` and handled per
+//! the guild's `content_moderation` setting. The check fails open: when the
+//! moderation endpoint can't be reached, the reply goes ahead.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.1.0: Covers every AI feature (stories, summaries, error explanations), not just chat
+//! - 1.0.0: Initial release
+
+use super::classifier::ModerationClient;
+use super::policy::{excerpt, Direction, ModerationAction, ModerationOutcome};
+use crate::core::Result;
+use crate::database::Database;
+use log::{info, warn};
+use std::sync::Arc;
+
+/// Applies each guild's content moderation policy
+#[derive(Clone)]
+pub struct ModerationGuard {
+    database: Database,
+    client: Arc<ModerationClient>,
+}
+
+impl ModerationGuard {
+    pub fn new(database: Database, openai_api_key: String) -> Self {
+        ModerationGuard { database, client: Arc::new(ModerationClient::new(openai_api_key)) }
+    }
+
+    #[cfg(test)]
+    pub fn with_client(database: Database, client: ModerationClient) -> Self {
+        ModerationGuard { database, client: Arc::new(client) }
+    }
+
+    /// The guild's moderation action, or None when moderation is off there
+    pub async fn action(&self, guild_id: &str) -> Result<Option<ModerationAction>> {
+        Ok(self
+            .database
+            .get_guild_setting(guild_id, "content_moderation")
+            .await?
+            .as_deref()
+            .and_then(ModerationAction::parse))
+    }
+
+    /// Check `text` against the guild's policy, recording it when flagged
+    pub async fn check(
+        &self,
+        guild_id: &str,
+        channel_id: Option<&str>,
+        user_id: Option<&str>,
+        direction: Direction,
+        text: &str,
+    ) -> Result<ModerationOutcome> {
+        let Some(action) = self.action(guild_id).await? else {
+            return Ok(ModerationOutcome::Allow);
+        };
+        if !self.client.is_configured() || text.trim().is_empty() {
+            return Ok(ModerationOutcome::Allow);
+        }
+        let categories = match self.client.classify(text).await {
+            Ok(categories) => categories,
+            Err(e) => {
+                warn!("Content moderation check failed, allowing {}: {e}", direction.as_str());
+                return Ok(ModerationOutcome::Allow);
+            }
+        };
+        if categories.is_empty() {
+            return Ok(ModerationOutcome::Allow);
+        }
+
+        info!("🛡️ Flagged {} in guild {guild_id} ({}), action {}", direction.as_str(), categories.join(", "), action.as_str());
+        if let Err(e) = self
+            .database
+            .log_moderation_event(
+                guild_id,
+                channel_id.unwrap_or_default(),
+                user_id.unwrap_or_default(),
+                direction.as_str(),
+                action.as_str(),
+                &categories.join(", "),
+                &excerpt(text),
+            )
+            .await
+        {
+            warn!("Failed to record moderation event: {e}");
+        }
+        Ok(ModerationOutcome::for_action(action, direction, &categories))
+    }
+}
@@ -0,0 +1,24 @@
+//! # Content Moderation Feature
+//!
+//! Runs members' messages and generated replies, from chat and every other AI
+//! feature, through OpenAI's moderation endpoint in guilds that set
+//! `content_moderation` to `log_only`, `warn` or `block`. Every hit is recorded
+//! in `moderation_events`, and moderators listed in `content_moderation_notify`
+//! are told by DM.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod alerts;
+pub mod classifier;
+pub mod guard;
+pub mod policy;
+
+pub use alerts::{format_moderation_alert, moderation_alert_loop};
+pub use classifier::{flagged_categories, ModerationClient};
+pub use guard::ModerationGuard;
+pub use policy::{
+    blocked_message, excerpt, parse_moderator_ids, warning_notice, Direction, ModerationAction, ModerationOutcome, EXCERPT_CHARS,
+    MODERATION_SETTING_VALUES,
+};
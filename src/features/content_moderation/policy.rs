@@ -0,0 +1,154 @@
+//! # Feature: Moderation Policy
+//!
+//! What a guild's `content_moderation` setting does with flagged content, and
+//! the notices shown to members. `log_only` records the hit, `warn` answers
+//! with a notice on top of the reply, and `block` refuses to answer a flagged
+//! message or to post a flagged reply.
+//!
+//! - **Version**: 1.0.1
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.1: Excerpts are cut with the shared `discord_limits::truncate`
+//! - 1.0.0: Initial release
+
+use crate::core::discord_limits::truncate;
+
+/// Values accepted by the `content_moderation` setting
+pub const MODERATION_SETTING_VALUES: &[&str] = &["disabled", "log_only", "warn", "block"];
+
+/// Characters of flagged text kept in `moderation_events`
+pub const EXCERPT_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationAction {
+    LogOnly,
+    Warn,
+    Block,
+}
+
+impl ModerationAction {
+    /// The action for a `content_moderation` value; None when moderation is off
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "log_only" => Some(ModerationAction::LogOnly),
+            "warn" => Some(ModerationAction::Warn),
+            "block" => Some(ModerationAction::Block),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationAction::LogOnly => "log_only",
+            ModerationAction::Warn => "warn",
+            ModerationAction::Block => "block",
+        }
+    }
+}
+
+/// Which side of the exchange was checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// A member's message, before it goes to the model
+    Input,
+    /// A generated reply, before it's posted
+    Output,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Input => "input",
+            Direction::Output => "output",
+        }
+    }
+}
+
+/// What to do with a checked message or reply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModerationOutcome {
+    Allow,
+    /// Answer, with this notice above the reply
+    Warn(String),
+    /// Don't answer; show this instead
+    Block(String),
+}
+
+impl ModerationOutcome {
+    pub fn for_action(action: ModerationAction, direction: Direction, categories: &[String]) -> Self {
+        match action {
+            ModerationAction::LogOnly => ModerationOutcome::Allow,
+            ModerationAction::Warn => ModerationOutcome::Warn(warning_notice(direction, categories)),
+            ModerationAction::Block => ModerationOutcome::Block(blocked_message(direction, categories)),
+        }
+    }
+}
+
+/// Notice shown above a reply to flagged content
+pub fn warning_notice(direction: Direction, categories: &[String]) -> String {
+    let subject = match direction {
+        Direction::Input => "Your message",
+        Direction::Output => "This reply",
+    };
+    format!("⚠️ *{subject} was flagged by this server's content filter ({}).*", categories.join(", "))
+}
+
+/// Shown instead of a reply when flagged content is blocked
+pub fn blocked_message(direction: Direction, categories: &[String]) -> String {
+    let categories = categories.join(", ");
+    match direction {
+        Direction::Input => format!("That message was flagged by this server's content filter ({categories}), so I won't answer it."),
+        Direction::Output => {
+            format!("My reply was flagged by this server's content filter ({categories}), so I didn't post it. Try asking another way.")
+        }
+    }
+}
+
+/// The start of flagged text, for the event log
+pub fn excerpt(text: &str) -> String {
+    truncate(text.trim(), EXCERPT_CHARS)
+}
+
+/// Moderators listed in `content_moderation_notify`; empty when it's `disabled` or unset
+pub fn parse_moderator_ids(value: &str) -> Vec<u64> {
+    value.split(',').filter_map(|id| id.trim().parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_round_trip() {
+        for value in &MODERATION_SETTING_VALUES[1..] {
+            assert_eq!(ModerationAction::parse(value).unwrap().as_str(), *value);
+        }
+        assert_eq!(ModerationAction::parse("disabled"), None);
+    }
+
+    #[test]
+    fn test_outcome_for_action() {
+        let categories = vec!["harassment".to_string(), "violence".to_string()];
+        assert_eq!(ModerationOutcome::for_action(ModerationAction::LogOnly, Direction::Input, &categories), ModerationOutcome::Allow);
+        assert_eq!(
+            ModerationOutcome::for_action(ModerationAction::Warn, Direction::Input, &categories),
+            ModerationOutcome::Warn("⚠️ *Your message was flagged by this server's content filter (harassment, violence).*".to_string())
+        );
+        let ModerationOutcome::Block(message) = ModerationOutcome::for_action(ModerationAction::Block, Direction::Output, &categories) else {
+            panic!("not blocked");
+        };
+        assert!(message.starts_with("My reply was flagged"));
+    }
+
+    #[test]
+    fn test_excerpt_and_moderator_ids() {
+        assert_eq!(excerpt("  short  "), "short");
+        let long = "a".repeat(EXCERPT_CHARS + 50);
+        assert_eq!(excerpt(&long).chars().count(), EXCERPT_CHARS);
+        assert!(excerpt(&long).ends_with('…'));
+        assert_eq!(parse_moderator_ids("123, 456,x"), vec![123, 456]);
+        assert!(parse_moderator_ids("disabled").is_empty());
+    }
+}
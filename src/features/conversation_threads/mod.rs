@@ -0,0 +1,20 @@
+//! # Conversation Threads Feature
+//!
+//! With the `thread_conversations` guild setting enabled, replying to one of
+//! the bot's messages in a channel opens a public thread on that message. The
+//! persona answers every message in the thread without needing a mention, and
+//! the thread keeps its own history, shared by everyone in it and separate
+//! from the parent channel's. `/thread_settings` gives a thread its own
+//! persona, verbosity and reply language.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod settings;
+pub mod threads;
+
+pub use settings::{
+    format_thread_settings, language_instruction, normalize_language, THREAD_SETTING_NAMES, THREAD_VERBOSITY_VALUES,
+};
+pub use threads::{attributed_turn, thread_name, THREAD_AUTO_ARCHIVE_MINUTES, THREAD_INSTRUCTION};
@@ -0,0 +1,57 @@
+//! # Feature: Thread Conversations
+//!
+//! Naming for the threads the bot opens and the prompt pieces for answering
+//! inside them. User turns are stored with the speaker's name because several
+//! people can talk to the persona in one thread.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use crate::core::discord_limits::truncate;
+
+/// Archive a conversation thread after a day without messages
+pub const THREAD_AUTO_ARCHIVE_MINUTES: u16 = 1440;
+
+/// Discord's limit on thread names
+const MAX_THREAD_NAME: usize = 100;
+
+/// Added to the persona's system prompt inside a conversation thread
+pub const THREAD_INSTRUCTION: &str = "\n\n## Thread Conversation\nThis conversation happens in a Discord thread that \
+anyone can join. User messages start with the speaker's name; address people by name when several are talking.";
+
+/// Thread name from the reply that opened it, without mentions, falling back to the persona's name
+pub fn thread_name(reply: &str, persona_name: &str) -> String {
+    let words: Vec<&str> = reply
+        .split_whitespace()
+        .filter(|word| !(word.starts_with("<@") && word.ends_with('>')))
+        .collect();
+    let name = if words.is_empty() { format!("Chat with {persona_name}") } else { words.join(" ") };
+    truncate(&name, MAX_THREAD_NAME)
+}
+
+/// A user turn as stored in thread history
+pub fn attributed_turn(author: &str, content: &str) -> String {
+    format!("{author}: {}", content.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::discord_limits::char_len;
+
+    #[test]
+    fn test_thread_name() {
+        assert_eq!(thread_name("<@123> what about lightsabers?", "Obi-Wan"), "what about lightsabers?");
+        assert_eq!(thread_name("<@!123>", "Obi-Wan"), "Chat with Obi-Wan");
+        assert!(char_len(&thread_name(&"word ".repeat(60), "Obi-Wan")) <= MAX_THREAD_NAME);
+    }
+
+    #[test]
+    fn test_attributed_turn() {
+        assert_eq!(attributed_turn("alice", " hi there \n"), "alice: hi there");
+    }
+}
@@ -0,0 +1,18 @@
+//! # Custom Commands Feature
+//!
+//! Server-defined text commands. Admins (Manage Server or the bot admin role)
+//! add them with `/command add`, and anyone runs them with `/c <name>` or by
+//! typing the server's `custom_command_prefix` before the name. Responses are
+//! templates with `{user}`, `{channel}`, `{server}`, `{args}` and `{arg1}`...
+//! variables.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod template;
+
+pub use template::{
+    normalize_command_name, parse_prefixed, render_command, split_args, valid_prefix, TemplateVars, MAX_COMMAND_NAME_LEN,
+    MAX_TEMPLATE_LEN,
+};
@@ -0,0 +1,177 @@
+//! # Feature: Custom Command Templates
+//!
+//! Command name rules, argument splitting and variable substitution for custom
+//! commands. Variables are single-brace names; unknown ones are left as typed
+//! and missing arguments render empty.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with user, channel, server and argument variables
+
+/// Longest command name, matching Discord's limit for command names
+pub const MAX_COMMAND_NAME_LEN: usize = 32;
+
+/// Longest response template, leaving room for variables to expand within a message
+pub const MAX_TEMPLATE_LEN: usize = 1500;
+
+/// Longest prefix a server can set for running commands
+const MAX_PREFIX_LEN: usize = 5;
+
+/// Values substituted into a response
+#[derive(Debug, Clone, Default)]
+pub struct TemplateVars {
+    pub user_id: String,
+    pub user_name: String,
+    pub channel_id: String,
+    pub server_name: String,
+    pub args: Vec<String>,
+}
+
+/// Lowercased name if it is 1-32 letters, digits, `-` or `_`
+pub fn normalize_command_name(name: &str) -> Option<String> {
+    let name = name.trim().to_lowercase();
+    let valid = (1..=MAX_COMMAND_NAME_LEN).contains(&name.len())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then_some(name)
+}
+
+/// Prefixes are 1-5 non-space characters that don't clash with `/` commands or mentions
+pub fn valid_prefix(prefix: &str) -> bool {
+    (1..=MAX_PREFIX_LEN).contains(&prefix.chars().count())
+        && !prefix.chars().any(char::is_whitespace)
+        && !prefix.starts_with('/')
+        && !prefix.starts_with('<')
+}
+
+/// Split arguments on whitespace, keeping "double-quoted phrases" together
+pub fn split_args(input: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut has_arg = false;
+    for c in input.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                has_arg = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// Command name and argument text when `content` starts with `prefix` directly followed by a name
+pub fn parse_prefixed<'a>(content: &'a str, prefix: &str) -> Option<(String, &'a str)> {
+    let rest = content.strip_prefix(prefix)?;
+    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let name = normalize_command_name(&rest[..end]).filter(|_| end > 0)?;
+    Some((name, rest[end..].trim()))
+}
+
+fn variable(name: &str, vars: &TemplateVars) -> Option<String> {
+    match name {
+        "user" => Some(format!("<@{}>", vars.user_id)),
+        "user_name" => Some(vars.user_name.clone()),
+        "channel" => Some(format!("<#{}>", vars.channel_id)),
+        "server" => Some(vars.server_name.clone()),
+        "args" => Some(vars.args.join(" ")),
+        _ => {
+            let index: usize = name.strip_prefix("arg")?.parse().ok().filter(|i| *i >= 1)?;
+            Some(vars.args.get(index - 1).cloned().unwrap_or_default())
+        }
+    }
+}
+
+/// Replace `{variable}` placeholders in a response template
+pub fn render_command(template: &str, vars: &TemplateVars) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}').and_then(|end| variable(&after[..end], vars).map(|value| (end, value))) {
+            Some((end, value)) => {
+                output.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_command_name() {
+        assert_eq!(normalize_command_name(" Rules "), Some("rules".to_string()));
+        assert_eq!(normalize_command_name("deploy-status_2"), Some("deploy-status_2".to_string()));
+        assert_eq!(normalize_command_name(""), None);
+        assert_eq!(normalize_command_name("two words"), None);
+        assert_eq!(normalize_command_name(&"a".repeat(33)), None);
+    }
+
+    #[test]
+    fn test_valid_prefix() {
+        assert!(valid_prefix("!"));
+        assert!(valid_prefix("?cmd"));
+        assert!(!valid_prefix("/"));
+        assert!(!valid_prefix("<@"));
+        assert!(!valid_prefix("! "));
+        assert!(!valid_prefix("toolong"));
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(split_args(r#"  one "two words"  three "#), vec!["one", "two words", "three"]);
+        assert_eq!(split_args(r#"empty "" arg"#), vec!["empty", "", "arg"]);
+        assert!(split_args("   ").is_empty());
+    }
+
+    #[test]
+    fn test_parse_prefixed() {
+        assert_eq!(parse_prefixed("!Rules please", "!"), Some(("rules".to_string(), "please")));
+        assert_eq!(parse_prefixed("!faq", "!"), Some(("faq".to_string(), "")));
+        assert_eq!(parse_prefixed("! faq", "!"), None);
+        assert_eq!(parse_prefixed("!!!", "!"), None);
+        assert_eq!(parse_prefixed("hello !faq", "!"), None);
+    }
+
+    #[test]
+    fn test_render_command() {
+        let vars = TemplateVars {
+            user_id: "42".to_string(),
+            user_name: "Ada".to_string(),
+            channel_id: "7".to_string(),
+            server_name: "Rustaceans".to_string(),
+            args: vec!["docs".to_string(), "two words".to_string()],
+        };
+        assert_eq!(
+            render_command("Hi {user} ({user_name}), see {arg1} in {channel} on {server}: {arg2}{arg3}", &vars),
+            "Hi <@42> (Ada), see docs in <#7> on Rustaceans: two words"
+        );
+        assert_eq!(render_command("{args} {unknown} {arg0} {", &vars), "docs two words {unknown} {arg0} {");
+    }
+}
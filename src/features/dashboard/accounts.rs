@@ -0,0 +1,191 @@
+//! # Feature: Account Linking
+//!
+//! Discord accounts linked through the dashboard login, stored in
+//! `linked_accounts` with their OAuth2 tokens. Access tokens are refreshed
+//! shortly before they expire, and each login's admin guilds are re-verified
+//! with Discord every [`ADMIN_RECHECK_MINUTES`](super::sessions::ADMIN_RECHECK_MINUTES).
+//! A grant the user revoked on Discord's side unlinks the account.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use super::oauth::{DiscordOAuth, DiscordUser, OAuthTokens};
+use super::sessions::DashboardGuild;
+use crate::core::clock::sql_timestamp;
+use crate::core::{Clock, Result};
+use crate::database::{Database, LinkedAccount};
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use std::sync::Arc;
+
+/// Refresh access tokens this long before Discord says they expire
+pub const REFRESH_MARGIN_MINUTES: i64 = 5;
+
+/// The account row for freshly issued tokens, not yet verified
+pub fn linked_account(user: &DiscordUser, tokens: &OAuthTokens, now: DateTime<Utc>) -> LinkedAccount {
+    LinkedAccount {
+        user_id: user.id.clone(),
+        username: user.username.clone(),
+        access_token: tokens.access_token.clone(),
+        refresh_token: tokens.refresh_token.clone(),
+        scope: tokens.scope.clone(),
+        expires_at: sql_timestamp(now + Duration::seconds(tokens.expires_in)),
+        admin_guild_ids: Vec::new(),
+        verified_at: None,
+        linked_at: sql_timestamp(now),
+    }
+}
+
+/// Whether the account's access token expires within the refresh margin
+pub fn needs_refresh(account: &LinkedAccount, now: DateTime<Utc>) -> bool {
+    account.expires_at <= sql_timestamp(now + Duration::minutes(REFRESH_MARGIN_MINUTES))
+}
+
+#[derive(Clone)]
+pub struct AccountLinker {
+    oauth: DiscordOAuth,
+    database: Database,
+    clock: Arc<dyn Clock>,
+}
+
+impl AccountLinker {
+    pub fn new(oauth: DiscordOAuth, database: Database, clock: Arc<dyn Clock>) -> Self {
+        AccountLinker { oauth, database, clock }
+    }
+
+    /// Link (or re-link) the user who just logged in, returning the guilds they administer
+    pub async fn link(&self, user: &DiscordUser, tokens: &OAuthTokens) -> Result<Vec<DashboardGuild>> {
+        let account = linked_account(user, tokens, self.clock.now());
+        let guilds = self.verify(account).await?.unwrap_or_default();
+        info!("Linked Discord account {} ({}) with {} admin guild(s)", user.username, user.id, guilds.len());
+        Ok(guilds)
+    }
+
+    /// Ask Discord again which guilds a linked user administers.
+    /// None if the account isn't linked, or the user revoked access.
+    pub async fn verify_admin_guilds(&self, user_id: &str) -> Result<Option<Vec<DashboardGuild>>> {
+        match self.database.get_linked_account(user_id).await? {
+            Some(account) => self.verify(account).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Unlink an account, revoking its grant with Discord. Returns false if it wasn't linked.
+    pub async fn unlink(&self, user_id: &str) -> Result<bool> {
+        let Some(account) = self.database.get_linked_account(user_id).await? else {
+            return Ok(false);
+        };
+        if let Err(e) = self.oauth.revoke(&account.refresh_token).await {
+            warn!("Failed to revoke Discord grant for {user_id}, forgetting it anyway: {e}");
+        }
+        info!("Unlinked Discord account {user_id}");
+        self.database.delete_linked_account(user_id).await
+    }
+
+    async fn verify(&self, account: LinkedAccount) -> Result<Option<Vec<DashboardGuild>>> {
+        let Some(mut account) = self.refreshed(account).await? else {
+            return Ok(None);
+        };
+        let mut guilds = Vec::new();
+        for guild in self.oauth.current_user_guilds(&account.access_token).await?.into_iter().filter(|g| g.is_admin()) {
+            if let Some(name) = self.database.get_known_guild_name(&guild.id).await? {
+                guilds.push(DashboardGuild { id: guild.id, name });
+            }
+        }
+        guilds.sort_by_key(|g| g.name.to_lowercase());
+
+        account.admin_guild_ids = guilds.iter().map(|g| g.id.clone()).collect();
+        account.verified_at = Some(sql_timestamp(self.clock.now()));
+        self.database.save_linked_account(&account).await?;
+        Ok(Some(guilds))
+    }
+
+    /// The account with a usable access token, or None (and unlinked) if Discord refused to refresh it
+    async fn refreshed(&self, mut account: LinkedAccount) -> Result<Option<LinkedAccount>> {
+        let now = self.clock.now();
+        if !needs_refresh(&account, now) {
+            return Ok(Some(account));
+        }
+        match self.oauth.refresh(&account.refresh_token).await? {
+            Some(tokens) => {
+                let renewed = linked_account(
+                    &DiscordUser { id: account.user_id.clone(), username: account.username.clone() },
+                    &tokens,
+                    now,
+                );
+                account.access_token = renewed.access_token;
+                account.refresh_token = renewed.refresh_token;
+                account.scope = renewed.scope;
+                account.expires_at = renewed.expires_at;
+                Ok(Some(account))
+            }
+            None => {
+                info!("Discord grant for {} was revoked, unlinking the account", account.user_id);
+                self.database.delete_linked_account(&account.user_id).await?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn account(now: DateTime<Utc>) -> LinkedAccount {
+        let user = DiscordUser { id: "42".to_string(), username: "obi".to_string() };
+        let tokens = OAuthTokens {
+            access_token: "a".to_string(),
+            refresh_token: "r".to_string(),
+            expires_in: 3600,
+            scope: "identify guilds".to_string(),
+        };
+        linked_account(&user, &tokens, now)
+    }
+
+    #[test]
+    fn test_linked_account_expiry_and_refresh() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let account = account(now);
+        assert_eq!(account.expires_at, "2024-05-01 13:00:00");
+        assert_eq!(account.linked_at, "2024-05-01 12:00:00");
+        assert!(account.verified_at.is_none());
+
+        assert!(!needs_refresh(&account, now));
+        assert!(!needs_refresh(&account, now + Duration::minutes(54)));
+        assert!(needs_refresh(&account, now + Duration::minutes(55)));
+        assert!(needs_refresh(&account, now + Duration::hours(2)));
+    }
+
+    #[tokio::test]
+    async fn test_linked_account_round_trip() {
+        let database = Database::new(":memory:", 1).await.unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let mut account = account(now);
+        account.admin_guild_ids = vec!["7".to_string(), "8".to_string()];
+        account.verified_at = Some(sql_timestamp(now));
+        database.save_linked_account(&account).await.unwrap();
+
+        let stored = database.get_linked_account("42").await.unwrap().unwrap();
+        assert_eq!(stored.admin_guild_ids, account.admin_guild_ids);
+        assert_eq!(stored.expires_at, account.expires_at);
+
+        // Re-linking replaces the tokens but keeps when it was first linked
+        account.access_token = "b".to_string();
+        account.admin_guild_ids.clear();
+        database.save_linked_account(&account).await.unwrap();
+        let relinked = database.get_linked_account("42").await.unwrap().unwrap();
+        assert_eq!(relinked.access_token, "b");
+        assert!(relinked.admin_guild_ids.is_empty());
+        assert_eq!(relinked.linked_at, stored.linked_at);
+
+        assert!(database.delete_linked_account("42").await.unwrap());
+        assert!(!database.delete_linked_account("42").await.unwrap());
+        assert!(database.get_linked_account("42").await.unwrap().is_none());
+    }
+}
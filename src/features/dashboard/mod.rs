@@ -0,0 +1,27 @@
+//! # Dashboard Feature
+//!
+//! Optional web dashboard served under `/dashboard` on `HEALTH_LISTEN_ADDR`:
+//! a small single-page app showing a guild's OpenAI usage chart, cost by
+//! feature, conflict history, settings and recent errors, and switching its
+//! toggleable features on and off. Users log in with Discord OAuth2 and only
+//! see guilds they own or administer. Logging in links the Discord account,
+//! keeping its OAuth2 tokens in `linked_accounts` so admin status can be
+//! re-verified and tokens refreshed. Enabled by setting `DASHBOARD_URL`,
+//! `DISCORD_CLIENT_ID` and `DISCORD_CLIENT_SECRET`.
+//!
+//! - **Version**: 1.2.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod accounts;
+pub mod oauth;
+pub mod server;
+pub mod sessions;
+
+pub use accounts::{AccountLinker, REFRESH_MARGIN_MINUTES};
+pub use oauth::{DiscordOAuth, DiscordUser, OAuthTokens, UserGuild};
+pub use server::{
+    costs_json, dashboard_router, features_json, usage_json, BOT_METRICS_HOURS, DASHBOARD_CONFLICT_LIMIT,
+    DASHBOARD_ERROR_LIMIT, DEFAULT_USAGE_DAYS, MAX_USAGE_DAYS,
+};
+pub use sessions::{DashboardGuild, DashboardSession, DashboardSessions, ADMIN_RECHECK_MINUTES, SESSION_TTL_HOURS};
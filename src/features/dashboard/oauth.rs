@@ -0,0 +1,205 @@
+//! # Feature: Dashboard Login
+//!
+//! Discord OAuth2 authorization code flow with the `identify` and `guilds`
+//! scopes. After login only guilds the user owns or administers, and that the
+//! bot is in, are offered on the dashboard. Tokens are refreshed with the
+//! `refresh_token` grant and revoked when the user unlinks.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Keep refresh tokens, refresh and revoke grants
+//! - 1.0.0: Initial release
+
+use crate::core::{BotError, DashboardConfig, Result};
+use reqwest::Url;
+use serde::Deserialize;
+
+const AUTHORIZE_URL: &str = "https://discord.com/oauth2/authorize";
+const API_BASE: &str = "https://discord.com/api/v10";
+
+/// The `ADMINISTRATOR` permission bit
+pub const ADMINISTRATOR: u64 = 1 << 3;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscordUser {
+    pub id: String,
+    pub username: String,
+}
+
+/// A guild from `GET /users/@me/guilds`
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserGuild {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub owner: bool,
+    /// Permission bits as a decimal string
+    #[serde(default)]
+    pub permissions: String,
+}
+
+impl UserGuild {
+    pub fn is_admin(&self) -> bool {
+        self.owner || self.permissions.parse::<u64>().map(|p| p & ADMINISTRATOR != 0).unwrap_or(false)
+    }
+}
+
+/// Tokens from Discord's token endpoint
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Seconds until the access token expires
+    pub expires_in: i64,
+    #[serde(default)]
+    pub scope: String,
+}
+
+#[derive(Clone)]
+pub struct DiscordOAuth {
+    config: DashboardConfig,
+    client: reqwest::Client,
+}
+
+impl DiscordOAuth {
+    pub fn new(config: DashboardConfig) -> Self {
+        DiscordOAuth { config, client: reqwest::Client::new() }
+    }
+
+    /// Discord's consent page, returning to the dashboard with `state`
+    pub fn authorize_url(&self, state: &str) -> String {
+        Url::parse_with_params(
+            AUTHORIZE_URL,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("scope", "identify guilds"),
+                ("redirect_uri", self.config.redirect_uri().as_str()),
+                ("state", state),
+                ("prompt", "none"),
+            ],
+        )
+        .map(String::from)
+        .unwrap_or_default()
+    }
+
+    /// Trade the code from the redirect for tokens
+    pub async fn exchange_code(&self, code: &str) -> Result<OAuthTokens> {
+        let redirect_uri = self.config.redirect_uri();
+        let response = self
+            .token_request(&[("grant_type", "authorization_code"), ("code", code), ("redirect_uri", redirect_uri.as_str())])
+            .await?;
+        if !response.status().is_success() {
+            return Err(BotError::validation(format!("Discord rejected the login code ({})", response.status())));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// New tokens for a refresh token, or None if the user revoked the grant
+    pub async fn refresh(&self, refresh_token: &str) -> Result<Option<OAuthTokens>> {
+        let response = self
+            .token_request(&[("grant_type", "refresh_token"), ("refresh_token", refresh_token)])
+            .await?;
+        match response.status() {
+            status if status.is_success() => Ok(Some(response.json().await?)),
+            // invalid_grant: revoked, already used or expired
+            reqwest::StatusCode::BAD_REQUEST | reqwest::StatusCode::UNAUTHORIZED => Ok(None),
+            status => Err(BotError::internal(format!("Discord token refresh returned {status}"))),
+        }
+    }
+
+    /// Revoke a token, ending the grant for both the access and refresh token
+    pub async fn revoke(&self, token: &str) -> Result<()> {
+        let response = self
+            .client
+            .post(format!("{API_BASE}/oauth2/token/revoke"))
+            .form(&[
+                ("token", token),
+                ("client_id", self.config.client_id.as_str()),
+                ("client_secret", self.config.client_secret.as_str()),
+            ])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(BotError::internal(format!("Discord token revoke returned {}", response.status())));
+        }
+        Ok(())
+    }
+
+    async fn token_request(&self, params: &[(&str, &str)]) -> Result<reqwest::Response> {
+        let credentials = [("client_id", self.config.client_id.as_str()), ("client_secret", self.config.client_secret.as_str())];
+        Ok(self
+            .client
+            .post(format!("{API_BASE}/oauth2/token"))
+            .form(&params.iter().chain(credentials.iter()).collect::<Vec<_>>())
+            .send()
+            .await?)
+    }
+
+    pub async fn current_user(&self, access_token: &str) -> Result<DiscordUser> {
+        self.get(access_token, "/users/@me").await
+    }
+
+    pub async fn current_user_guilds(&self, access_token: &str) -> Result<Vec<UserGuild>> {
+        self.get(access_token, "/users/@me/guilds").await
+    }
+
+    async fn get<T: for<'de> Deserialize<'de>>(&self, access_token: &str, path: &str) -> Result<T> {
+        let response = self
+            .client
+            .get(format!("{API_BASE}{path}"))
+            .bearer_auth(access_token)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(BotError::internal(format!("Discord API {path} returned {}", response.status())));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guild(owner: bool, permissions: &str) -> UserGuild {
+        UserGuild { id: "1".to_string(), name: "Guild".to_string(), owner, permissions: permissions.to_string() }
+    }
+
+    #[test]
+    fn test_is_admin() {
+        assert!(guild(true, "0").is_admin());
+        assert!(guild(false, "8").is_admin());
+        assert!(guild(false, "2147483656").is_admin());
+        assert!(!guild(false, "32").is_admin());
+        assert!(!guild(false, "").is_admin());
+    }
+
+    #[test]
+    fn test_token_response() {
+        let body = r#"{"access_token":"a","token_type":"Bearer","expires_in":604800,"refresh_token":"r","scope":"identify guilds"}"#;
+        let tokens: OAuthTokens = serde_json::from_str(body).unwrap();
+        assert_eq!(tokens.access_token, "a");
+        assert_eq!(tokens.refresh_token, "r");
+        assert_eq!(tokens.expires_in, 604800);
+        assert_eq!(tokens.scope, "identify guilds");
+    }
+
+    #[test]
+    fn test_authorize_url() {
+        let oauth = DiscordOAuth::new(DashboardConfig {
+            public_url: "https://bot.example.org".to_string(),
+            client_id: "123".to_string(),
+            client_secret: "secret".to_string(),
+        });
+        let url = oauth.authorize_url("abc");
+        assert!(url.starts_with("https://discord.com/oauth2/authorize?response_type=code&client_id=123"));
+        assert!(url.contains("scope=identify+guilds"));
+        assert!(url.contains("redirect_uri=https%3A%2F%2Fbot.example.org%2Fdashboard%2Fcallback"));
+        assert!(url.contains("state=abc"));
+        assert!(!url.contains("secret"));
+    }
+}
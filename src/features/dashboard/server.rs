@@ -0,0 +1,509 @@
+//! # Feature: Dashboard Server
+//!
+//! Routes under `/dashboard`: the single-page app, the OAuth2 login and
+//! logout, and JSON for the guilds the user administers (usage, cost by
+//! feature, conflict history, settings, feature flags and recent errors), plus
+//! feature toggles. Every `/dashboard/api/guilds/<id>` request is checked
+//! against the session's admin guilds, re-verified with Discord when they're
+//! more than a few minutes old. `/dashboard/api/bot` reports this bot's own
+//! system metrics only, keyed by its application ID, when several bots share a
+//! database.
+//!
+//! - **Version**: 1.2.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.2.0: Cost by feature, conflict history, feature toggles and per-bot system metrics
+//! - 1.1.0: Logins link the Discord account; admin guilds re-verified, `POST /dashboard/unlink`
+//! - 1.0.0: Initial release
+
+use super::accounts::AccountLinker;
+use super::oauth::DiscordOAuth;
+use super::sessions::{
+    cookie, set_cookie, DashboardGuild, DashboardSession, DashboardSessions, LOGIN_STATE_TTL_MINUTES, SESSION_COOKIE,
+    SESSION_TTL_HOURS, STATE_COOKIE,
+};
+use crate::core::{Clock, DashboardConfig, IdGen};
+use crate::database::{ConflictRecord, CostLine, Database, ErrorLogEntry};
+use crate::features::feature_gate::FeatureGate;
+use crate::features::{get_feature, get_toggleable_features};
+use chrono::Duration;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Days of usage shown unless the page asks for another range
+pub const DEFAULT_USAGE_DAYS: i64 = 30;
+
+/// Longest usage range the API serves
+pub const MAX_USAGE_DAYS: i64 = 90;
+
+/// Recent errors listed per guild
+pub const DASHBOARD_ERROR_LIMIT: i64 = 50;
+
+/// Recent conflicts listed per guild
+pub const DASHBOARD_CONFLICT_LIMIT: i64 = 50;
+
+/// Hours of system metrics served by `/bot`
+pub const BOT_METRICS_HOURS: i64 = 24;
+
+const INDEX_HTML: &str = include_str!("static/index.html");
+
+#[derive(Clone)]
+struct DashboardState {
+    database: Database,
+    oauth: DiscordOAuth,
+    accounts: AccountLinker,
+    sessions: DashboardSessions,
+    disabled_features: Arc<HashSet<String>>,
+    /// Toggles from the dashboard reach the bot's handlers within the gate's cache TTL
+    gate: FeatureGate,
+    clock: Arc<dyn Clock>,
+    /// This bot's user ID (its application's client ID), which its system metrics are stored under
+    bot_id: String,
+    /// Mark cookies `Secure` when the dashboard is served over HTTPS
+    secure_cookies: bool,
+}
+
+type ApiResult = std::result::Result<Json<Value>, (StatusCode, Json<Value>)>;
+
+fn api_error(status: StatusCode, message: &str) -> (StatusCode, Json<Value>) {
+    (status, Json(json!({ "error": message })))
+}
+
+fn internal_error(e: impl std::fmt::Display) -> (StatusCode, Json<Value>) {
+    warn!("Dashboard query failed: {e}");
+    api_error(StatusCode::INTERNAL_SERVER_ERROR, "internal error")
+}
+
+impl DashboardState {
+    /// The request's session, with its admin guilds re-verified if they're due
+    async fn session(&self, headers: &HeaderMap) -> std::result::Result<DashboardSession, (StatusCode, Json<Value>)> {
+        let not_logged_in = || api_error(StatusCode::UNAUTHORIZED, "not logged in");
+        let token = cookie(headers, SESSION_COOKIE).ok_or_else(not_logged_in)?;
+        let session = self.sessions.get(token).ok_or_else(not_logged_in)?;
+        if !self.sessions.needs_verification(&session) {
+            return Ok(session);
+        }
+        match self.accounts.verify_admin_guilds(&session.user_id).await {
+            Ok(Some(guilds)) => self.sessions.set_guilds(token, guilds).ok_or_else(not_logged_in),
+            Ok(None) => {
+                // Unlinked, or access revoked on Discord's side
+                self.sessions.remove_user(&session.user_id);
+                Err(not_logged_in())
+            }
+            Err(e) => {
+                warn!("Couldn't re-verify dashboard guilds for {}: {e}", session.user_id);
+                Err(api_error(StatusCode::BAD_GATEWAY, "couldn't verify your servers with Discord, try again"))
+            }
+        }
+    }
+
+    /// The session, if it may view `guild_id`
+    async fn guild_session(
+        &self,
+        headers: &HeaderMap,
+        guild_id: &str,
+    ) -> std::result::Result<DashboardSession, (StatusCode, Json<Value>)> {
+        let session = self.session(headers).await?;
+        if !session.can_view(guild_id) {
+            return Err(api_error(StatusCode::FORBIDDEN, "not an administrator of this guild"));
+        }
+        Ok(session)
+    }
+}
+
+async fn index() -> Html<&'static str> {
+    Html(INDEX_HTML)
+}
+
+async fn login(State(state): State<DashboardState>) -> Response {
+    let login_state = state.sessions.begin_login();
+    let cookie = set_cookie(STATE_COOKIE, &login_state, LOGIN_STATE_TTL_MINUTES * 60, state.secure_cookies);
+    ([(header::SET_COOKIE, cookie)], Redirect::to(&state.oauth.authorize_url(&login_state))).into_response()
+}
+
+#[derive(Deserialize)]
+struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+}
+
+async fn callback(State(state): State<DashboardState>, headers: HeaderMap, Query(query): Query<CallbackQuery>) -> Response {
+    let (Some(code), Some(login_state)) = (query.code, query.state) else {
+        return (StatusCode::BAD_REQUEST, "Login was cancelled").into_response();
+    };
+    // The state must be one we issued, to this browser
+    if cookie(&headers, STATE_COOKIE) != Some(login_state.as_str()) || !state.sessions.finish_login(&login_state) {
+        return (StatusCode::BAD_REQUEST, "Login expired, please try again").into_response();
+    }
+
+    let (user, guilds) = match complete_login(&state, &code).await {
+        Ok(login) => login,
+        Err(e) => {
+            warn!("Dashboard login failed: {e}");
+            return (StatusCode::BAD_GATEWAY, "Discord login failed, please try again").into_response();
+        }
+    };
+    info!("Dashboard login by {} ({}) for {} guild(s)", user.username, user.id, guilds.len());
+
+    let token = state.sessions.create(user.id, user.username, guilds);
+    let cookies = [
+        (header::SET_COOKIE, set_cookie(SESSION_COOKIE, &token, SESSION_TTL_HOURS * 3600, state.secure_cookies)),
+        (header::SET_COOKIE, set_cookie(STATE_COOKIE, "", 0, state.secure_cookies)),
+    ];
+    (cookies, Redirect::to("/dashboard")).into_response()
+}
+
+/// Exchange the code, link the account and find the guilds the user administers that the bot is in
+async fn complete_login(
+    state: &DashboardState,
+    code: &str,
+) -> crate::core::Result<(super::oauth::DiscordUser, Vec<DashboardGuild>)> {
+    let tokens = state.oauth.exchange_code(code).await?;
+    let user = state.oauth.current_user(&tokens.access_token).await?;
+    let guilds = state.accounts.link(&user, &tokens).await?;
+    Ok((user, guilds))
+}
+
+async fn logout(State(state): State<DashboardState>, headers: HeaderMap) -> Response {
+    if let Some(token) = cookie(&headers, SESSION_COOKIE) {
+        state.sessions.remove(token);
+    }
+    let cookie = set_cookie(SESSION_COOKIE, "", 0, state.secure_cookies);
+    ([(header::SET_COOKIE, cookie)], StatusCode::NO_CONTENT).into_response()
+}
+
+/// Unlink the account: revoke the grant with Discord, forget its tokens and end its sessions
+async fn unlink(State(state): State<DashboardState>, headers: HeaderMap) -> Response {
+    let Some(session) = cookie(&headers, SESSION_COOKIE).and_then(|token| state.sessions.get(token)) else {
+        return api_error(StatusCode::UNAUTHORIZED, "not logged in").into_response();
+    };
+    if let Err(e) = state.accounts.unlink(&session.user_id).await {
+        return internal_error(e).into_response();
+    }
+    state.sessions.remove_user(&session.user_id);
+    let cookie = set_cookie(SESSION_COOKIE, "", 0, state.secure_cookies);
+    ([(header::SET_COOKIE, cookie)], StatusCode::NO_CONTENT).into_response()
+}
+
+async fn me(State(state): State<DashboardState>, headers: HeaderMap) -> ApiResult {
+    let session = state.session(&headers).await?;
+    Ok(Json(json!({
+        "user_id": session.user_id,
+        "username": session.username,
+        "guilds": session.guilds,
+    })))
+}
+
+/// Usage by service and per day, as served by `/usage`
+pub fn usage_json(days: i64, by_service: &[(String, i64, i64, f64, i64, f64)], daily: &[(String, i64, f64)]) -> Value {
+    let services: Vec<Value> = by_service
+        .iter()
+        .map(|(service, requests, tokens, audio_seconds, images, cost)| {
+            json!({
+                "service": service,
+                "requests": requests,
+                "tokens": tokens,
+                "audio_seconds": audio_seconds,
+                "images": images,
+                "cost_usd": cost,
+            })
+        })
+        .collect();
+    let daily: Vec<Value> = daily
+        .iter()
+        .map(|(date, requests, cost)| json!({ "date": date, "requests": requests, "cost_usd": cost }))
+        .collect();
+    let total: f64 = by_service.iter().map(|row| row.5).sum();
+    json!({ "days": days, "total_cost_usd": total, "by_service": services, "daily": daily })
+}
+
+async fn usage(
+    State(state): State<DashboardState>,
+    headers: HeaderMap,
+    Path(guild_id): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+) -> ApiResult {
+    state.guild_session(&headers, &guild_id).await?;
+    let days = query
+        .get("days")
+        .and_then(|d| d.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_USAGE_DAYS)
+        .clamp(1, MAX_USAGE_DAYS);
+    let by_service = state.database.get_guild_usage_stats(&guild_id, days).await.map_err(internal_error)?;
+    let daily = state.database.get_guild_daily_usage(&guild_id, days).await.map_err(internal_error)?;
+    Ok(Json(usage_json(days, &by_service, &daily)))
+}
+
+async fn settings(State(state): State<DashboardState>, headers: HeaderMap, Path(guild_id): Path<String>) -> ApiResult {
+    state.guild_session(&headers, &guild_id).await?;
+    let settings: Vec<Value> = state
+        .database
+        .get_guild_settings(&guild_id)
+        .await
+        .map_err(internal_error)?
+        .into_iter()
+        .map(|(key, value)| json!({ "key": key, "value": value }))
+        .collect();
+    Ok(Json(json!({ "settings": settings })))
+}
+
+/// Toggleable features and whether each is on in the guild, as served by `/features`
+pub fn features_json(flags: &HashMap<String, bool>, disabled: &HashSet<String>) -> Value {
+    let features: Vec<Value> = get_toggleable_features()
+        .map(|feature| {
+            let enabled = !disabled.contains(feature.id) && flags.get(feature.id).copied().unwrap_or(true);
+            json!({
+                "id": feature.id,
+                "name": feature.name,
+                "version": feature.version,
+                "description": feature.description,
+                "enabled": enabled,
+                "disabled_by_host": disabled.contains(feature.id),
+            })
+        })
+        .collect();
+    json!({ "features": features })
+}
+
+async fn features(State(state): State<DashboardState>, headers: HeaderMap, Path(guild_id): Path<String>) -> ApiResult {
+    state.guild_session(&headers, &guild_id).await?;
+    let flags = state.database.get_guild_feature_flags(&guild_id).await.map_err(internal_error)?;
+    Ok(Json(features_json(&flags, &state.disabled_features)))
+}
+
+#[derive(Deserialize)]
+struct ToggleRequest {
+    enabled: bool,
+}
+
+async fn toggle_feature(
+    State(state): State<DashboardState>,
+    headers: HeaderMap,
+    Path((guild_id, feature_id)): Path<(String, String)>,
+    Json(request): Json<ToggleRequest>,
+) -> ApiResult {
+    let session = state.guild_session(&headers, &guild_id).await?;
+    let feature = get_feature(&feature_id)
+        .filter(|f| f.toggleable)
+        .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "not a toggleable feature"))?;
+    if state.gate.disabled_by_host(feature.id) {
+        return Err(api_error(StatusCode::CONFLICT, "disabled for every server by the bot's host"));
+    }
+
+    state.gate.set(feature.id, &guild_id, request.enabled).await.map_err(internal_error)?;
+    state
+        .database
+        .record_feature_toggle(feature.id, feature.version, Some(&guild_id), &session.user_id, request.enabled)
+        .await
+        .map_err(internal_error)?;
+    info!("Dashboard: {} set {} to {} in guild {}", session.user_id, feature.id, request.enabled, guild_id);
+
+    let flags = state.database.get_guild_feature_flags(&guild_id).await.map_err(internal_error)?;
+    Ok(Json(features_json(&flags, &state.disabled_features)))
+}
+
+/// Cost per feature over `days`, as served by `/costs`
+pub fn costs_json(days: i64, lines: &[CostLine]) -> Value {
+    let features: Vec<Value> = lines
+        .iter()
+        .map(|line| {
+            json!({
+                "feature": line.feature,
+                "requests": line.requests,
+                "tokens": line.tokens,
+                "cost_usd": line.cost,
+            })
+        })
+        .collect();
+    let total: f64 = lines.iter().map(|line| line.cost).sum();
+    json!({ "days": days, "total_cost_usd": total, "by_feature": features })
+}
+
+async fn costs(
+    State(state): State<DashboardState>,
+    headers: HeaderMap,
+    Path(guild_id): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+) -> ApiResult {
+    state.guild_session(&headers, &guild_id).await?;
+    let days = query
+        .get("days")
+        .and_then(|d| d.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_USAGE_DAYS)
+        .clamp(1, MAX_USAGE_DAYS);
+    let today = state.clock.now().date_naive();
+    let start = (today - Duration::days(days - 1)).format("%Y-%m-%d").to_string();
+    let end = today.format("%Y-%m-%d").to_string();
+    let lines = state.database.get_cost_breakdown(Some(&guild_id), &start, &end).await.map_err(internal_error)?;
+    Ok(Json(costs_json(days, &lines)))
+}
+
+fn conflict_json(conflict: &ConflictRecord) -> Value {
+    let participants: Vec<String> = serde_json::from_str(&conflict.participants).unwrap_or_default();
+    json!({
+        "id": conflict.id,
+        "channel_id": conflict.channel_id,
+        "participants": participants,
+        "type": conflict.detection_type,
+        "confidence": conflict.confidence,
+        "mediated": conflict.mediation_triggered,
+        "mediations": conflict.mediations,
+        "first_detected": conflict.first_detected,
+        "last_detected": conflict.last_detected,
+        "resolved_at": conflict.resolved_at,
+    })
+}
+
+async fn conflicts(State(state): State<DashboardState>, headers: HeaderMap, Path(guild_id): Path<String>) -> ApiResult {
+    state.guild_session(&headers, &guild_id).await?;
+    let conflicts = state.database.get_guild_conflicts(&guild_id, DASHBOARD_CONFLICT_LIMIT).await.map_err(internal_error)?;
+    Ok(Json(json!({ "conflicts": conflicts.iter().map(conflict_json).collect::<Vec<_>>() })))
+}
+
+/// This bot's memory and CPU history; other bots sharing the database are left out
+async fn bot_metrics(State(state): State<DashboardState>, headers: HeaderMap) -> ApiResult {
+    state.session(&headers).await?;
+    let mut series = serde_json::Map::new();
+    for metric in ["bot_memory_bytes", "system_cpu_percent"] {
+        let points = state
+            .database
+            .get_metrics_history(metric, BOT_METRICS_HOURS, Some(&state.bot_id))
+            .await
+            .map_err(internal_error)?;
+        series.insert(metric.to_string(), json!(points));
+    }
+    Ok(Json(json!({ "bot_id": state.bot_id, "hours": BOT_METRICS_HOURS, "metrics": series })))
+}
+
+fn error_json(entry: &ErrorLogEntry) -> Value {
+    json!({
+        "id": entry.id,
+        "type": entry.error_type,
+        "message": entry.message,
+        "command": entry.command,
+        "timestamp": entry.timestamp,
+    })
+}
+
+async fn errors(State(state): State<DashboardState>, headers: HeaderMap, Path(guild_id): Path<String>) -> ApiResult {
+    state.guild_session(&headers, &guild_id).await?;
+    let errors = state.database.get_guild_errors(&guild_id, DASHBOARD_ERROR_LIMIT).await.map_err(internal_error)?;
+    Ok(Json(json!({ "errors": errors.iter().map(error_json).collect::<Vec<_>>() })))
+}
+
+/// Routes for the dashboard, merged into the health endpoint's router
+pub fn dashboard_router(
+    config: &DashboardConfig,
+    database: Database,
+    disabled_features: HashSet<String>,
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGen>,
+) -> Router {
+    let oauth = DiscordOAuth::new(config.clone());
+    let state = DashboardState {
+        accounts: AccountLinker::new(oauth.clone(), database.clone(), clock.clone()),
+        gate: FeatureGate::new(database.clone(), disabled_features.clone()),
+        database,
+        oauth,
+        sessions: DashboardSessions::new(clock.clone(), ids),
+        disabled_features: Arc::new(disabled_features),
+        clock,
+        bot_id: config.client_id.clone(),
+        secure_cookies: config.public_url.starts_with("https://"),
+    };
+    Router::new()
+        .route("/dashboard", get(index))
+        .route("/dashboard/login", get(login))
+        .route("/dashboard/callback", get(callback))
+        .route("/dashboard/logout", post(logout))
+        .route("/dashboard/unlink", post(unlink))
+        .route("/dashboard/api/me", get(me))
+        .route("/dashboard/api/bot", get(bot_metrics))
+        .route("/dashboard/api/guilds/:guild_id/usage", get(usage))
+        .route("/dashboard/api/guilds/:guild_id/costs", get(costs))
+        .route("/dashboard/api/guilds/:guild_id/conflicts", get(conflicts))
+        .route("/dashboard/api/guilds/:guild_id/settings", get(settings))
+        .route("/dashboard/api/guilds/:guild_id/features", get(features))
+        .route("/dashboard/api/guilds/:guild_id/features/:feature", post(toggle_feature))
+        .route("/dashboard/api/guilds/:guild_id/errors", get(errors))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_json() {
+        let by_service = vec![
+            ("chat".to_string(), 10, 5000, 0.0, 0, 0.25),
+            ("dalle".to_string(), 2, 0, 0.0, 2, 0.08),
+        ];
+        let daily = vec![("2024-05-01".to_string(), 12, 0.33)];
+        let body = usage_json(30, &by_service, &daily);
+        assert_eq!(body["days"], 30);
+        assert!((body["total_cost_usd"].as_f64().unwrap() - 0.33).abs() < 1e-9);
+        assert_eq!(body["by_service"][1]["images"], 2);
+        assert_eq!(body["daily"][0]["date"], "2024-05-01");
+    }
+
+    #[test]
+    fn test_costs_json() {
+        let line = |feature: &str, cost| CostLine {
+            guild_id: "1".to_string(),
+            feature: feature.to_string(),
+            requests: 3,
+            tokens: 900,
+            cost,
+        };
+        let body = costs_json(7, &[line("chat", 0.5), line("vision", 0.25)]);
+        assert_eq!(body["days"], 7);
+        assert!((body["total_cost_usd"].as_f64().unwrap() - 0.75).abs() < 1e-9);
+        assert_eq!(body["by_feature"][1]["feature"], "vision");
+    }
+
+    #[test]
+    fn test_conflict_json() {
+        let conflict = ConflictRecord {
+            id: 4,
+            channel_id: "9".to_string(),
+            participants: r#"["1","2"]"#.to_string(),
+            detection_type: "hostile".to_string(),
+            confidence: 0.8,
+            mediation_triggered: true,
+            first_detected: "2024-05-01 12:00:00".to_string(),
+            last_detected: "2024-05-01 12:05:00".to_string(),
+            resolved_at: None,
+            mediations: 1,
+        };
+        let body = conflict_json(&conflict);
+        assert_eq!(body["participants"], json!(["1", "2"]));
+        assert_eq!(body["mediated"], true);
+        assert!(body["resolved_at"].is_null());
+    }
+
+    #[test]
+    fn test_features_json() {
+        let toggleable: Vec<&str> = get_toggleable_features().map(|f| f.id).take(2).collect();
+        let flags = HashMap::from([(toggleable[0].to_string(), false)]);
+        let disabled = HashSet::from([toggleable[1].to_string()]);
+        let body = features_json(&flags, &disabled);
+        let features = body["features"].as_array().unwrap();
+        assert_eq!(features.len(), get_toggleable_features().count());
+        assert_eq!(features[0]["enabled"], false);
+        assert_eq!(features[0]["disabled_by_host"], false);
+        assert_eq!(features[1]["enabled"], false);
+        assert_eq!(features[1]["disabled_by_host"], true);
+        assert!(features[2..].iter().all(|f| f["enabled"] == true));
+    }
+}
@@ -0,0 +1,220 @@
+//! # Feature: Dashboard Sessions
+//!
+//! Logged-in dashboard users, kept in memory and identified by a random token
+//! in an `HttpOnly` cookie, plus the one-time `state` values of logins in
+//! progress. Sessions end after [`SESSION_TTL_HOURS`] or on restart, and their
+//! guilds are re-verified every [`ADMIN_RECHECK_MINUTES`].
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Sessions record when their admin guilds were last verified
+//! - 1.0.0: Initial release
+
+use crate::core::{Clock, IdGen};
+use axum::http::HeaderMap;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Cookie holding the session token
+pub const SESSION_COOKIE: &str = "persona_dashboard";
+
+/// Cookie binding a login's `state` to the browser that started it
+pub const STATE_COOKIE: &str = "persona_dashboard_state";
+
+/// How long a login lasts
+pub const SESSION_TTL_HOURS: i64 = 12;
+
+/// How long a session trusts its admin guilds before asking Discord again
+pub const ADMIN_RECHECK_MINUTES: i64 = 15;
+
+/// How long the user has to finish Discord's consent page
+pub const LOGIN_STATE_TTL_MINUTES: i64 = 10;
+
+/// A guild the logged-in user may view
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DashboardGuild {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DashboardSession {
+    pub user_id: String,
+    pub username: String,
+    pub guilds: Vec<DashboardGuild>,
+    /// When `guilds` was last checked with Discord
+    pub verified_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl DashboardSession {
+    pub fn can_view(&self, guild_id: &str) -> bool {
+        self.guilds.iter().any(|g| g.id == guild_id)
+    }
+}
+
+#[derive(Clone)]
+pub struct DashboardSessions {
+    sessions: Arc<DashMap<String, DashboardSession>>,
+    /// Login state -> when it stops being accepted
+    pending: Arc<DashMap<String, DateTime<Utc>>>,
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGen>,
+}
+
+impl DashboardSessions {
+    pub fn new(clock: Arc<dyn Clock>, ids: Arc<dyn IdGen>) -> Self {
+        DashboardSessions { sessions: Arc::new(DashMap::new()), pending: Arc::new(DashMap::new()), clock, ids }
+    }
+
+    fn token(&self) -> String {
+        self.ids.new_id().simple().to_string()
+    }
+
+    /// A fresh `state` for a login
+    pub fn begin_login(&self) -> String {
+        let now = self.clock.now();
+        self.pending.retain(|_, expires| *expires > now);
+        let state = self.token();
+        self.pending.insert(state.clone(), now + Duration::minutes(LOGIN_STATE_TTL_MINUTES));
+        state
+    }
+
+    /// Accept a login `state` once, if it was issued here and hasn't expired
+    pub fn finish_login(&self, state: &str) -> bool {
+        self.pending.remove(state).is_some_and(|(_, expires)| expires > self.clock.now())
+    }
+
+    /// Start a session, returning its token
+    pub fn create(&self, user_id: String, username: String, guilds: Vec<DashboardGuild>) -> String {
+        let now = self.clock.now();
+        self.sessions.retain(|_, session| session.expires_at > now);
+        let token = self.token();
+        let expires_at = now + Duration::hours(SESSION_TTL_HOURS);
+        self.sessions.insert(token.clone(), DashboardSession { user_id, username, guilds, verified_at: now, expires_at });
+        token
+    }
+
+    /// Whether the session's guilds are due to be verified again
+    pub fn needs_verification(&self, session: &DashboardSession) -> bool {
+        session.verified_at + Duration::minutes(ADMIN_RECHECK_MINUTES) <= self.clock.now()
+    }
+
+    /// Replace a session's guilds with a freshly verified list, returning the updated session
+    pub fn set_guilds(&self, token: &str, guilds: Vec<DashboardGuild>) -> Option<DashboardSession> {
+        let mut session = self.sessions.get_mut(token)?;
+        session.guilds = guilds;
+        session.verified_at = self.clock.now();
+        Some(session.clone())
+    }
+
+    pub fn get(&self, token: &str) -> Option<DashboardSession> {
+        let session = self.sessions.get(token)?.clone();
+        if session.expires_at <= self.clock.now() {
+            self.sessions.remove(token);
+            return None;
+        }
+        Some(session)
+    }
+
+    pub fn remove(&self, token: &str) {
+        self.sessions.remove(token);
+    }
+
+    /// End every session of a user, e.g. when they unlink their account
+    pub fn remove_user(&self, user_id: &str) {
+        self.sessions.retain(|_, session| session.user_id != user_id);
+    }
+}
+
+/// Value of the cookie `name` from a request's `Cookie` headers
+pub fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all("cookie")
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// `Set-Cookie` value scoped to the dashboard; a `max_age` of 0 deletes the cookie
+pub fn set_cookie(name: &str, value: &str, max_age_secs: i64, secure: bool) -> String {
+    let secure = if secure { "; Secure" } else { "" };
+    format!("{name}={value}; Path=/dashboard; HttpOnly; SameSite=Lax; Max-Age={max_age_secs}{secure}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::{FakeClock, SequentialIds};
+    use chrono::TimeZone;
+
+    fn sessions() -> (Arc<FakeClock>, DashboardSessions) {
+        let clock = Arc::new(FakeClock::new(Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()));
+        let sessions = DashboardSessions::new(clock.clone(), Arc::new(SequentialIds::default()));
+        (clock, sessions)
+    }
+
+    #[test]
+    fn test_login_state_is_single_use_and_expires() {
+        let (clock, sessions) = sessions();
+        let state = sessions.begin_login();
+        assert!(sessions.finish_login(&state));
+        assert!(!sessions.finish_login(&state));
+        assert!(!sessions.finish_login("forged"));
+
+        let state = sessions.begin_login();
+        clock.advance(Duration::minutes(LOGIN_STATE_TTL_MINUTES + 1));
+        assert!(!sessions.finish_login(&state));
+    }
+
+    #[test]
+    fn test_session_expires() {
+        let (clock, sessions) = sessions();
+        let guild = DashboardGuild { id: "7".to_string(), name: "Guild".to_string() };
+        let token = sessions.create("42".to_string(), "obi".to_string(), vec![guild]);
+        let session = sessions.get(&token).unwrap();
+        assert!(session.can_view("7"));
+        assert!(!session.can_view("8"));
+
+        clock.advance(Duration::hours(SESSION_TTL_HOURS));
+        assert!(sessions.get(&token).is_none());
+    }
+
+    #[test]
+    fn test_guilds_reverified() {
+        let (clock, sessions) = sessions();
+        let guild = DashboardGuild { id: "7".to_string(), name: "Guild".to_string() };
+        let token = sessions.create("42".to_string(), "obi".to_string(), vec![guild]);
+        assert!(!sessions.needs_verification(&sessions.get(&token).unwrap()));
+
+        clock.advance(Duration::minutes(ADMIN_RECHECK_MINUTES));
+        assert!(sessions.needs_verification(&sessions.get(&token).unwrap()));
+        let session = sessions.set_guilds(&token, Vec::new()).unwrap();
+        assert!(!session.can_view("7"));
+        assert!(!sessions.needs_verification(&session));
+
+        sessions.remove_user("42");
+        assert!(sessions.get(&token).is_none());
+    }
+
+    #[test]
+    fn test_cookies() {
+        let mut headers = HeaderMap::new();
+        headers.insert("cookie", "theme=dark; persona_dashboard=abc123".parse().unwrap());
+        assert_eq!(cookie(&headers, SESSION_COOKIE), Some("abc123"));
+        assert_eq!(cookie(&headers, STATE_COOKIE), None);
+
+        assert_eq!(
+            set_cookie(SESSION_COOKIE, "abc", 60, true),
+            "persona_dashboard=abc; Path=/dashboard; HttpOnly; SameSite=Lax; Max-Age=60; Secure"
+        );
+    }
+}
@@ -0,0 +1,15 @@
+//! # Duplicate Questions Feature
+//!
+//! Embedding-based detection of questions that were already answered in a guild's
+//! support channels, so the earlier answer can be linked before generating a new one.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod similarity;
+
+pub use similarity::{
+    best_match, cosine_similarity, decode_embedding, encode_embedding, normalize_question,
+    DuplicateMatch, DUPLICATE_THRESHOLD, EMBEDDING_MODEL,
+};
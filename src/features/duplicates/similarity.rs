@@ -0,0 +1,122 @@
+//! # Feature: Duplicate Question Matching
+//!
+//! Pure helpers for comparing question embeddings: normalization, cosine
+//! similarity, best-match search and compact storage encoding.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with cosine similarity matching
+
+/// OpenAI embedding model used for question vectors
+pub const EMBEDDING_MODEL: &str = "text-embedding-3-small";
+
+/// Minimum cosine similarity for two questions to count as duplicates
+pub const DUPLICATE_THRESHOLD: f32 = 0.92;
+
+/// Questions shorter than this (after normalization) are never checked
+pub const MIN_QUESTION_CHARS: usize = 15;
+
+/// A previously answered question that closely matches a new one
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateMatch {
+    /// Index of the matching candidate
+    pub index: usize,
+    pub similarity: f32,
+}
+
+/// Normalize a question for embedding: drop Discord mentions, collapse whitespace.
+///
+/// Returns `None` when too little text remains to be worth comparing.
+pub fn normalize_question(content: &str) -> Option<String> {
+    let cleaned = content
+        .split_whitespace()
+        .filter(|word| !(word.starts_with("<@") && word.ends_with('>')))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if cleaned.chars().count() < MIN_QUESTION_CHARS {
+        None
+    } else {
+        Some(cleaned)
+    }
+}
+
+/// Cosine similarity between two vectors; 0.0 for mismatched or zero-length input
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Find the most similar candidate at or above `threshold`
+pub fn best_match<'a, I>(query: &[f32], candidates: I, threshold: f32) -> Option<DuplicateMatch>
+where
+    I: IntoIterator<Item = &'a [f32]>,
+{
+    candidates
+        .into_iter()
+        .enumerate()
+        .map(|(index, candidate)| DuplicateMatch {
+            index,
+            similarity: cosine_similarity(query, candidate),
+        })
+        .filter(|m| m.similarity >= threshold)
+        .max_by(|a, b| a.similarity.total_cmp(&b.similarity))
+}
+
+/// Encode an embedding for storage as a JSON array
+pub fn encode_embedding(embedding: &[f32]) -> String {
+    serde_json::to_string(embedding).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Decode a stored embedding, returning `None` if it is malformed
+pub fn decode_embedding(value: &str) -> Option<Vec<f32>> {
+    serde_json::from_str(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_question_strips_mentions() {
+        assert_eq!(
+            normalize_question("<@123>  how do I   reset my password?"),
+            Some("how do I reset my password?".to_string())
+        );
+        assert_eq!(normalize_question("<@!123> hi"), None);
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_best_match_respects_threshold() {
+        let candidates: Vec<Vec<f32>> = vec![vec![0.0, 1.0], vec![0.9, 0.1], vec![1.0, 0.01]];
+        let found = best_match(&[1.0, 0.0], candidates.iter().map(|c| c.as_slice()), 0.95).unwrap();
+        assert_eq!(found.index, 2);
+        assert!(best_match(&[1.0, 0.0], candidates.iter().map(|c| c.as_slice()), 1.1).is_none());
+    }
+
+    #[test]
+    fn test_embedding_roundtrip() {
+        let encoded = encode_embedding(&[0.5, -0.25]);
+        assert_eq!(decode_embedding(&encoded), Some(vec![0.5, -0.25]));
+        assert_eq!(decode_embedding("not json"), None);
+    }
+}
@@ -0,0 +1,204 @@
+//! # Feature: Emoji Statistics
+//!
+//! Counts emoji used in guild messages and as reactions, stored as daily
+//! per-guild aggregates in `emoji_stats` and reported by `/emojistats`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with message/reaction counts and weekly trend
+
+/// Days of emoji statistics kept by the daily cleanup
+pub const EMOJI_STATS_RETENTION_DAYS: i64 = 90;
+
+/// Emoji shown per ranking in `/emojistats`
+pub const TOP_EMOJI_LIMIT: usize = 10;
+
+/// Where an emoji was used
+pub mod emoji_source {
+    pub const MESSAGE: &str = "message";
+    pub const REACTION: &str = "reaction";
+}
+
+/// Zero-width joiner used in multi-part emoji sequences
+const ZWJ: char = '\u{200D}';
+/// Variation selector-16 (emoji presentation)
+const VS16: char = '\u{FE0F}';
+/// Combining enclosing keycap
+const KEYCAP: char = '\u{20E3}';
+
+fn is_emoji_base(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF // pictographs, emoticons, transport, supplemental symbols
+        | 0x2600..=0x27BF // misc symbols and dingbats
+        | 0x2B00..=0x2BFF // arrows, stars, circles
+        | 0x2300..=0x23FF // misc technical (⌚, ⏰, ...)
+    )
+}
+
+fn is_modifier(c: char) -> bool {
+    c == VS16 || c == KEYCAP || (0x1F3FB..=0x1F3FF).contains(&(c as u32)) || (0xE0020..=0xE007F).contains(&(c as u32))
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    (0x1F1E6..=0x1F1FF).contains(&(c as u32))
+}
+
+/// Extract the emoji used in a message: custom emoji (`<:name:id>`, `<a:name:id>`)
+/// and Unicode emoji, including ZWJ sequences, skin tones and flags.
+pub fn extract_emoji(content: &str) -> Vec<String> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut found = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // Custom emoji: <:name:id> or <a:name:id>
+        if c == '<' {
+            if let Some(len) = custom_emoji_len(&chars[i..]) {
+                found.push(chars[i..i + len].iter().collect());
+                i += len;
+                continue;
+            }
+        }
+
+        // Flags are pairs of regional indicators
+        if is_regional_indicator(c) && chars.get(i + 1).is_some_and(|n| is_regional_indicator(*n)) {
+            found.push(chars[i..i + 2].iter().collect());
+            i += 2;
+            continue;
+        }
+
+        if is_emoji_base(c) && !is_modifier(c) {
+            let start = i;
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some(m) if is_modifier(*m) => i += 1,
+                    Some(&ZWJ) if chars.get(i + 1).is_some_and(|n| is_emoji_base(*n)) => i += 2,
+                    _ => break,
+                }
+            }
+            found.push(chars[start..i].iter().collect());
+            continue;
+        }
+
+        i += 1;
+    }
+
+    found
+}
+
+/// Length of a custom emoji token at the start of `chars`, if there is one
+fn custom_emoji_len(chars: &[char]) -> Option<usize> {
+    let end = chars.iter().position(|c| *c == '>')?;
+    let inner: String = chars[1..end].iter().collect();
+    let mut parts = inner.split(':');
+    let prefix = parts.next()?;
+    let name = parts.next()?;
+    let id = parts.next()?;
+    let valid = (prefix.is_empty() || prefix == "a")
+        && !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !id.is_empty()
+        && id.chars().all(|c| c.is_ascii_digit())
+        && parts.next().is_none();
+    valid.then_some(end + 1)
+}
+
+/// Storage key for a custom emoji, renderable as-is in Discord
+pub fn custom_emoji_key(name: &str, id: u64, animated: bool) -> String {
+    if animated {
+        format!("<a:{name}:{id}>")
+    } else {
+        format!("<:{name}:{id}>")
+    }
+}
+
+/// Text bar for a count relative to the maximum
+fn bar(count: i64, max: i64) -> String {
+    let width = if max > 0 { ((count * 10 + max - 1) / max).clamp(1, 10) } else { 0 };
+    "█".repeat(width as usize)
+}
+
+fn format_ranking(title: &str, ranking: &[(String, i64)]) -> String {
+    if ranking.is_empty() {
+        return format!("**{title}**\nNothing recorded yet.");
+    }
+    let max = ranking.iter().map(|(_, c)| *c).max().unwrap_or(0);
+    let mut lines = vec![format!("**{title}**")];
+    for (i, (emoji, count)) in ranking.iter().enumerate() {
+        lines.push(format!("{}. {emoji} `{}` {count}", i + 1, bar(*count, max)));
+    }
+    lines.join("\n")
+}
+
+/// Render the `/emojistats` report.
+///
+/// `weekly` holds (week start date, message count, reaction count), oldest first.
+pub fn format_emoji_report(
+    days: i64,
+    top_messages: &[(String, i64)],
+    top_reactions: &[(String, i64)],
+    weekly: &[(String, i64, i64)],
+) -> String {
+    let mut sections = vec![
+        format!("**😀 Emoji statistics** — last {days} days"),
+        format_ranking("Most used in messages", top_messages),
+        format_ranking("Most used reactions", top_reactions),
+    ];
+
+    if !weekly.is_empty() {
+        let mut lines = vec!["**Over time** (per week: messages / reactions)".to_string()];
+        for (week, messages, reactions) in weekly {
+            lines.push(format!("`{week}` 💬 {messages} · 👍 {reactions}"));
+        }
+        sections.push(lines.join("\n"));
+    }
+
+    sections.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_unicode_emoji() {
+        assert_eq!(extract_emoji("great job 🎉🎉"), vec!["🎉", "🎉"]);
+        assert_eq!(extract_emoji("👍🏽 ok"), vec!["👍🏽"]);
+        assert_eq!(extract_emoji("❤️ love"), vec!["❤️"]);
+        assert_eq!(extract_emoji("family 👨‍👩‍👧"), vec!["👨‍👩‍👧"]);
+        assert_eq!(extract_emoji("go 🇳🇱!"), vec!["🇳🇱"]);
+        assert!(extract_emoji("plain text, no emoji: <3").is_empty());
+    }
+
+    #[test]
+    fn test_extract_custom_emoji() {
+        assert_eq!(
+            extract_emoji("hi <:blobwave:123456> and <a:party_parrot:789>"),
+            vec!["<:blobwave:123456>", "<a:party_parrot:789>"]
+        );
+        // Mentions and channel links are not emoji
+        assert!(extract_emoji("<@123> <#456> <@&789>").is_empty());
+        assert_eq!(custom_emoji_key("parrot", 1, true), "<a:parrot:1>");
+    }
+
+    #[test]
+    fn test_format_emoji_report() {
+        let report = format_emoji_report(
+            30,
+            &[("🎉".to_string(), 12), ("😂".to_string(), 3)],
+            &[],
+            &[("2026-10-05".to_string(), 15, 0)],
+        );
+        assert!(report.contains("last 30 days"));
+        assert!(report.contains("1. 🎉 `██████████` 12"));
+        assert!(report.contains("2. 😂 `███` 3"));
+        assert!(report.contains("**Most used reactions**\nNothing recorded yet."));
+        assert!(report.contains("`2026-10-05` 💬 15 · 👍 0"));
+    }
+}
@@ -0,0 +1,15 @@
+//! # Emoji Statistics Feature
+//!
+//! Tracks emoji used in guild messages and reactions and reports the most-used
+//! ones with `/emojistats`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod extractor;
+
+pub use extractor::{
+    custom_emoji_key, emoji_source, extract_emoji, format_emoji_report, EMOJI_STATS_RETENTION_DAYS,
+    TOP_EMOJI_LIMIT,
+};
@@ -0,0 +1,176 @@
+//! # Feature: Error Explanation
+//!
+//! Prompt building for `/explain_error`, plus the formatting helpers that keep
+//! stack traces readable: head-and-tail truncation of long input, and reply
+//! splitting that closes and reopens code fences across message boundaries.
+//!
+//! - **Version**: 1.0.1
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.1: Traces made of a few huge lines are cut with the shared `discord_limits::truncate`
+//! - 1.0.0: Initial release
+
+use crate::core::discord_limits::{char_len, split_message, truncate};
+use crate::features::code_runner::strip_code_fence;
+
+/// Personas offered by `/explain_error`; the first is the default
+pub const ERROR_PERSONAS: &[&str] = &["teacher", "analyst"];
+
+/// Modal custom ID prefix, followed by the persona
+pub const EXPLAIN_ERROR_MODAL_PREFIX: &str = "explain_error_modal:";
+
+/// Longest trace accepted in the `error` option or the paste form
+pub const MAX_ERROR_PASTE_LEN: usize = 4000;
+
+/// Largest attached log file that is downloaded
+pub const MAX_ERROR_ATTACHMENT_BYTES: u64 = 512 * 1024;
+
+/// Characters of the trace sent to the model
+pub const MAX_TRACE_CHARS: usize = 12_000;
+
+/// Room kept in each chunk for a closing fence and the reopened one
+const FENCE_RESERVE: usize = 24;
+
+/// Trim a trace to `max_chars`, keeping its first and last lines. The exception
+/// type is usually at the top (Java, Rust) or the bottom (Python), so both ends
+/// are kept and the middle frames are replaced with a marker.
+pub fn truncate_trace(text: &str, max_chars: usize) -> String {
+    let text = strip_code_fence(&text.replace("\r\n", "\n")).to_string();
+    if char_len(&text) <= max_chars {
+        return text;
+    }
+
+    let lines: Vec<&str> = text.lines().collect();
+    let head_budget = max_chars / 3;
+    let tail_budget = max_chars.saturating_sub(head_budget + 40);
+
+    let mut head = 0;
+    let mut used = 0;
+    for line in &lines {
+        let len = line.chars().count() + 1;
+        if used + len > head_budget {
+            break;
+        }
+        used += len;
+        head += 1;
+    }
+    let mut tail = lines.len();
+    used = 0;
+    while tail > head {
+        let len = lines[tail - 1].chars().count() + 1;
+        if used + len > tail_budget {
+            break;
+        }
+        used += len;
+        tail -= 1;
+    }
+
+    if head == 0 && tail == lines.len() {
+        // A few enormous lines: fall back to characters
+        return truncate(&text, max_chars);
+    }
+    format!("{}\n… {} lines omitted …\n{}", lines[..head].join("\n"), tail - head, lines[tail..].join("\n"))
+}
+
+/// Instructions appended to the persona's system prompt
+pub fn explain_error_system_prompt(persona_prompt: &str) -> String {
+    format!(
+        "{persona_prompt}\n\n## Error Explanation\nA developer pasted an error, stack trace or log. Answer in three \
+short sections: **What it means** (the error in plain words, pointing at the line or frame that matters), **Likely \
+causes** (most likely first) and **Next steps** (concrete things to check or try, with any code in fenced blocks). \
+If lines were omitted from the middle and that matters, say so. Don't guess at code you can't see; ask for it instead."
+    )
+}
+
+/// User message carrying the trace, fenced so the model reads it verbatim
+pub fn explain_error_request(trace: &str) -> String {
+    // Keep the trace from closing the fence early
+    format!("Explain this error:\n\n```\n{}\n```", trace.replace("```", "`\u{200b}``"))
+}
+
+/// Split a reply into messages of at most `max` characters, closing a code
+/// block at the end of a chunk and reopening it (with its language tag) at the
+/// start of the next so each message renders on its own
+pub fn split_code_aware(text: &str, max: usize) -> Vec<String> {
+    let mut open: Option<String> = None;
+    let mut chunks = Vec::new();
+    for chunk in split_message(text, max.saturating_sub(FENCE_RESERVE)) {
+        let mut out = match &open {
+            Some(fence) => format!("{fence}\n{chunk}"),
+            None => chunk.clone(),
+        };
+        for line in chunk.lines() {
+            let line = line.trim_start();
+            // ```inline``` on one line opens and closes itself
+            if !line.starts_with("```") || line[3..].contains("```") {
+                continue;
+            }
+            open = match open {
+                Some(_) => None,
+                None => Some(line.chars().take(FENCE_RESERVE / 2).collect()),
+            };
+        }
+        if open.is_some() {
+            out.push_str("\n```");
+        }
+        chunks.push(out);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_trace_keeps_both_ends() {
+        assert_eq!(truncate_trace("```\nKeyError: 'x'\n```", 100), "KeyError: 'x'");
+
+        let mut trace = vec!["Traceback (most recent call last):".to_string()];
+        trace.extend((0..200).map(|i| format!("  File \"app.py\", line {i}, in handler")));
+        trace.push("KeyError: 'user_id'".to_string());
+        let truncated = truncate_trace(&trace.join("\r\n"), 1000);
+
+        assert!(truncated.chars().count() <= 1000);
+        assert!(truncated.starts_with("Traceback (most recent call last):\n"));
+        assert!(truncated.ends_with("\nKeyError: 'user_id'"));
+        assert!(truncated.contains(" lines omitted …\n"));
+        assert!(!truncated.contains('\r'));
+    }
+
+    #[test]
+    fn test_truncate_trace_single_long_line() {
+        let truncated = truncate_trace(&"x".repeat(500), 100);
+        assert_eq!(truncated.chars().count(), 100);
+        assert!(truncated.ends_with('…'));
+    }
+
+    #[test]
+    fn test_request_neutralizes_fences() {
+        let request = explain_error_request("panic at ```main```");
+        assert_eq!(request.matches("```").count(), 2);
+    }
+
+    #[test]
+    fn test_split_code_aware_balances_fences() {
+        let code: Vec<String> = (0..60).map(|i| format!("let value_{i} = compute({i});")).collect();
+        let text = format!("**What it means**\nThe call fails.\n\n```rust\n{}\n```\nThat's it.", code.join("\n"));
+        let chunks = split_code_aware(&text, 500);
+
+        assert!(chunks.len() > 2);
+        for chunk in &chunks {
+            assert!(chunk.chars().count() <= 500);
+            assert_eq!(chunk.matches("```").count() % 2, 0, "unbalanced chunk: {chunk}");
+        }
+        assert!(chunks[1].starts_with("```rust\n"));
+        assert!(chunks.last().unwrap().ends_with("That's it."));
+    }
+
+    #[test]
+    fn test_split_code_aware_inline_fence() {
+        let chunks = split_code_aware("Run ```cargo clean``` first.\nThen rebuild.", 2000);
+        assert_eq!(chunks, vec!["Run ```cargo clean``` first.\nThen rebuild.".to_string()]);
+    }
+}
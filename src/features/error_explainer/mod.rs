@@ -0,0 +1,17 @@
+//! # Error Explainer Feature
+//!
+//! `/explain_error` takes a pasted or attached stack trace or log excerpt and
+//! has the teacher or analyst persona explain what it means, the likely causes
+//! and what to try next. Long traces keep their first and last lines, and
+//! replies are split without breaking code blocks.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod explainer;
+
+pub use explainer::{
+    explain_error_request, explain_error_system_prompt, split_code_aware, truncate_trace, ERROR_PERSONAS,
+    EXPLAIN_ERROR_MODAL_PREFIX, MAX_ERROR_ATTACHMENT_BYTES, MAX_ERROR_PASTE_LEN, MAX_TRACE_CHARS,
+};
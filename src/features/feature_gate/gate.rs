@@ -0,0 +1,198 @@
+//! # Feature: Feature Gate
+//!
+//! One service answering "may this feature run here?" for every handler. A
+//! feature is off when the program embedding the bot disabled it for every
+//! guild, or when a guild admin switched it off with `/toggle`. Guild flags are
+//! read from `feature_flags` and cached for [`FEATURE_GATE_TTL`]; `/toggle`
+//! goes through [`FeatureGate::set`], which updates the cache immediately, and
+//! toggles written by another bot on the same database are seen once the cached
+//! entry expires. In DMs only the host's switches apply.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with chat, transcription, imaging, mediation and reminder paths
+
+use crate::core::Result;
+use crate::database::Database;
+use dashmap::DashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How long a guild's flag is trusted before it is read from the database again
+pub const FEATURE_GATE_TTL: Duration = Duration::from_secs(5);
+
+/// Feature paths that check the gate before doing any work
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GatedPath {
+    /// Persona replies to mentions, DMs, conversation threads and chat commands
+    Chat,
+    /// Voice message and audio attachment transcription
+    Transcription,
+    /// `/imagine`
+    Imaging,
+    /// Conflict detection and mediation on channel messages
+    Mediation,
+    /// `/remind`, `/reminders` and the "Remind me" context menu
+    Reminders,
+}
+
+impl GatedPath {
+    pub const ALL: [GatedPath; 5] = [
+        GatedPath::Chat,
+        GatedPath::Transcription,
+        GatedPath::Imaging,
+        GatedPath::Mediation,
+        GatedPath::Reminders,
+    ];
+
+    /// Registry ID of the feature switching this path on and off
+    pub fn feature_id(self) -> &'static str {
+        match self {
+            GatedPath::Chat => "chat",
+            GatedPath::Transcription => "audio_transcription",
+            GatedPath::Imaging => "image_generation",
+            GatedPath::Mediation => "conflict_mediation",
+            GatedPath::Reminders => "reminders",
+        }
+    }
+
+    /// Reply for a command refused because its feature is off
+    pub fn disabled_message(self) -> &'static str {
+        match self {
+            GatedPath::Chat => "❌ AI chat is disabled on this server.",
+            GatedPath::Transcription => "❌ Audio transcription is disabled on this server.",
+            GatedPath::Imaging => "❌ Image generation is disabled on this server.",
+            GatedPath::Mediation => "❌ Conflict mediation is disabled on this server.",
+            GatedPath::Reminders => "❌ Reminders are disabled on this server.",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct FeatureGate {
+    database: Database,
+    /// Features switched off for every guild by the program embedding the bot
+    disabled_by_host: Arc<HashSet<String>>,
+    /// (guild, feature) -> enabled, and when it was read
+    cache: Arc<DashMap<(String, String), (bool, Instant)>>,
+    ttl: Duration,
+}
+
+impl FeatureGate {
+    pub fn new(database: Database, disabled_by_host: HashSet<String>) -> Self {
+        FeatureGate {
+            database,
+            disabled_by_host: Arc::new(disabled_by_host),
+            cache: Arc::new(DashMap::new()),
+            ttl: FEATURE_GATE_TTL,
+        }
+    }
+
+    /// Trust cached flags for this long instead of [`FEATURE_GATE_TTL`]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Whether the host disabled a feature for every guild
+    pub fn disabled_by_host(&self, feature: &str) -> bool {
+        self.disabled_by_host.contains(feature)
+    }
+
+    /// Whether a feature is on in a guild (or a DM when `guild_id` is None)
+    pub async fn is_enabled(&self, feature: &str, guild_id: Option<&str>) -> Result<bool> {
+        if self.disabled_by_host(feature) {
+            return Ok(false);
+        }
+        let Some(guild_id) = guild_id else {
+            return Ok(true);
+        };
+
+        let key = (guild_id.to_string(), feature.to_string());
+        if let Some(entry) = self.cache.get(&key) {
+            let (enabled, read_at) = *entry;
+            if read_at.elapsed() < self.ttl {
+                return Ok(enabled);
+            }
+        }
+        let enabled = self.database.is_feature_enabled(feature, None, Some(guild_id)).await?;
+        self.cache.insert(key, (enabled, Instant::now()));
+        Ok(enabled)
+    }
+
+    /// Whether a gated path may run in a guild (or a DM)
+    pub async fn allows(&self, path: GatedPath, guild_id: Option<&str>) -> Result<bool> {
+        self.is_enabled(path.feature_id(), guild_id).await
+    }
+
+    /// Switch a feature on or off in a guild, effective on this bot immediately
+    pub async fn set(&self, feature: &str, guild_id: &str, enabled: bool) -> Result<()> {
+        self.database.set_feature_flag(feature, enabled, None, Some(guild_id)).await?;
+        self.cache.insert((guild_id.to_string(), feature.to_string()), (enabled, Instant::now()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::get_feature;
+
+    async fn gate(disabled: &[&str]) -> FeatureGate {
+        let database = Database::new(":memory:", 1).await.unwrap();
+        FeatureGate::new(database, disabled.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn test_every_gated_path_is_a_toggleable_feature() {
+        for path in GatedPath::ALL {
+            let feature = get_feature(path.feature_id()).unwrap_or_else(|| panic!("{path:?} has no registered feature"));
+            assert!(feature.toggleable, "{path:?} can't be switched off with /toggle");
+            assert!(path.disabled_message().starts_with('❌'));
+        }
+        let ids: HashSet<_> = GatedPath::ALL.iter().map(|p| p.feature_id()).collect();
+        assert_eq!(ids.len(), GatedPath::ALL.len());
+    }
+
+    #[tokio::test]
+    async fn test_toggle_closes_each_path_in_that_guild_only() {
+        let gate = gate(&[]).await;
+        for path in GatedPath::ALL {
+            assert!(gate.allows(path, Some("1")).await.unwrap());
+            gate.set(path.feature_id(), "1", false).await.unwrap();
+            assert!(!gate.allows(path, Some("1")).await.unwrap(), "{path:?} still open after /toggle");
+            assert!(gate.allows(path, Some("2")).await.unwrap());
+            assert!(gate.allows(path, None).await.unwrap());
+            gate.set(path.feature_id(), "1", true).await.unwrap();
+            assert!(gate.allows(path, Some("1")).await.unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_host_switch_closes_each_path_everywhere() {
+        let ids: Vec<_> = GatedPath::ALL.iter().map(|p| p.feature_id()).collect();
+        let gate = gate(&ids).await;
+        for path in GatedPath::ALL {
+            assert!(!gate.allows(path, Some("1")).await.unwrap());
+            assert!(!gate.allows(path, None).await.unwrap(), "{path:?} open in DMs despite the host switch");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flags_written_elsewhere_are_seen_after_ttl() {
+        let gate = gate(&[]).await;
+        let database = gate.database.clone();
+        assert!(gate.allows(GatedPath::Chat, Some("1")).await.unwrap());
+
+        // Another bot on the same database toggles chat off: cached until the TTL passes
+        database.set_feature_flag("chat", false, None, Some("1")).await.unwrap();
+        assert!(gate.allows(GatedPath::Chat, Some("1")).await.unwrap());
+
+        let fresh = gate.with_ttl(Duration::ZERO);
+        assert!(!fresh.allows(GatedPath::Chat, Some("1")).await.unwrap());
+    }
+}
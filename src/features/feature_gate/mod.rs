@@ -0,0 +1,14 @@
+//! # Feature Gate
+//!
+//! Per-feature kill switches consulted at the top of every gated feature path
+//! (chat, transcription, image generation, mediation, reminders). Combines the
+//! host's compile-time switches with each guild's `/toggle` state, cached for a
+//! few seconds so a toggle on any bot sharing the database takes effect quickly.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod gate;
+
+pub use gate::{FeatureGate, GatedPath, FEATURE_GATE_TTL};
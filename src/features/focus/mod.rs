@@ -0,0 +1,19 @@
+//! # Focus Feature
+//!
+//! `/pomodoro` focus sessions with breaks, pings and optional muting of a study
+//! voice channel, and the `/focus_stats` leaderboard of completed intervals.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod roster;
+pub mod timer;
+
+pub use roster::VoiceRoster;
+pub use timer::{
+    focus_timer_loop, format_focus_leaderboard, format_session_start, format_session_status, join_custom_id,
+    parse_join_custom_id, parse_participants, participants_json, release_voice, silence_voice, validate_session,
+    VoiceSilence, DEFAULT_BREAK_MINUTES, DEFAULT_FOCUS_MINUTES, DEFAULT_ROUNDS, FOCUS_JOIN_PREFIX,
+    FOCUS_LEADERBOARD_SIZE, FOCUS_STATS_DAYS, MAX_ROUNDS,
+};
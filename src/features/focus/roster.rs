@@ -0,0 +1,91 @@
+//! # Feature: Voice Roster
+//!
+//! Who sits in which voice channel, per guild. The bot is built without the
+//! serenity cache, so the roster is seeded from each guild's voice states on
+//! `guild_create` and kept current by `voice_state_update` events (both need
+//! the GUILD_VOICE_STATES intent). In interactions endpoint mode no gateway
+//! events arrive and the roster stays empty.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use dashmap::DashMap;
+use std::sync::Arc;
+
+/// Voice channel of each connected member, keyed by `(guild_id, user_id)`; shared between handler clones
+#[derive(Clone, Default)]
+pub struct VoiceRoster {
+    channels: Arc<DashMap<(u64, u64), u64>>,
+}
+
+impl VoiceRoster {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace a guild's roster with the voice states it was delivered with
+    pub fn seed(&self, guild_id: u64, states: impl IntoIterator<Item = (u64, Option<u64>)>) {
+        self.channels.retain(|(guild, _), _| *guild != guild_id);
+        for (user_id, channel_id) in states {
+            self.update(guild_id, user_id, channel_id);
+        }
+    }
+
+    /// Record a member joining, moving between or (with `None`) leaving voice channels
+    pub fn update(&self, guild_id: u64, user_id: u64, channel_id: Option<u64>) {
+        match channel_id {
+            Some(channel_id) => {
+                self.channels.insert((guild_id, user_id), channel_id);
+            }
+            None => {
+                self.channels.remove(&(guild_id, user_id));
+            }
+        }
+    }
+
+    /// Members currently in `channel_id`, in ascending ID order
+    pub fn members_in(&self, guild_id: u64, channel_id: u64) -> Vec<u64> {
+        let mut members: Vec<u64> = self
+            .channels
+            .iter()
+            .filter(|entry| entry.key().0 == guild_id && *entry.value() == channel_id)
+            .map(|entry| entry.key().1)
+            .collect();
+        members.sort_unstable();
+        members
+    }
+
+    /// Forget a guild the bot has left
+    pub fn forget_guild(&self, guild_id: u64) {
+        self.channels.retain(|(guild, _), _| *guild != guild_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roster_tracks_moves() {
+        let roster = VoiceRoster::new();
+        roster.seed(1, [(10, Some(100)), (11, Some(100)), (12, None)]);
+        roster.update(2, 13, Some(100));
+        assert_eq!(roster.members_in(1, 100), vec![10, 11]);
+
+        roster.update(1, 11, Some(200));
+        roster.update(1, 10, None);
+        assert!(roster.members_in(1, 100).is_empty());
+        assert_eq!(roster.members_in(1, 200), vec![11]);
+
+        roster.seed(1, [(14, Some(100))]);
+        assert_eq!(roster.members_in(1, 100), vec![14]);
+        assert!(roster.members_in(1, 200).is_empty());
+
+        roster.forget_guild(2);
+        assert!(roster.members_in(2, 100).is_empty());
+    }
+}
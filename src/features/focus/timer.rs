@@ -0,0 +1,424 @@
+//! # Feature: Focus Sessions
+//!
+//! `/pomodoro start` runs alternating focus and break intervals in a channel,
+//! pinging the participants whenever one ends. Members of a study voice channel
+//! join automatically and can be server-muted (or muted and deafened) while
+//! focusing; anyone else joins with the button on the start message. Sessions
+//! live in `focus_sessions`, so they carry on across restarts, and every
+//! completed focus interval is credited per participant in `focus_completions`
+//! for the `/focus_stats` leaderboard.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use crate::core::Result;
+use crate::database::{Database, FocusSession};
+use crate::features::focus::roster::VoiceRoster;
+use log::{debug, info, warn};
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often sessions are checked for a finished interval
+pub const FOCUS_CHECK_INTERVAL_SECS: u64 = 15;
+
+pub const DEFAULT_FOCUS_MINUTES: i64 = 25;
+pub const DEFAULT_BREAK_MINUTES: i64 = 5;
+pub const DEFAULT_ROUNDS: i64 = 4;
+pub const MAX_ROUNDS: i64 = 12;
+
+/// Period covered by `/focus_stats`
+pub const FOCUS_STATS_DAYS: i64 = 30;
+
+/// Members listed on the `/focus_stats` leaderboard
+pub const FOCUS_LEADERBOARD_SIZE: i64 = 10;
+
+/// Join button custom ID prefix, followed by the session ID
+pub const FOCUS_JOIN_PREFIX: &str = "focus_join:";
+
+/// Which interval a session is in, as stored in `focus_sessions.phase`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusPhase {
+    Focus,
+    Break,
+}
+
+impl FocusPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FocusPhase::Focus => "focus",
+            FocusPhase::Break => "break",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "focus" => Some(FocusPhase::Focus),
+            "break" => Some(FocusPhase::Break),
+            _ => None,
+        }
+    }
+}
+
+/// How participants in the study voice channel are silenced while focusing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceSilence {
+    Mute,
+    /// Muted and deafened
+    Deafen,
+}
+
+impl VoiceSilence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VoiceSilence::Mute => "mute",
+            VoiceSilence::Deafen => "deafen",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "mute" => Some(VoiceSilence::Mute),
+            "deafen" => Some(VoiceSilence::Deafen),
+            _ => None,
+        }
+    }
+}
+
+/// What happens when the current interval runs out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusStep {
+    /// Break after focus interval `round`
+    Break { round: i64 },
+    /// Focus interval `round` begins
+    Focus { round: i64 },
+    /// The last focus interval is over; there's no break after it
+    Done,
+}
+
+pub fn next_step(phase: FocusPhase, round: i64, rounds: i64) -> FocusStep {
+    match phase {
+        FocusPhase::Focus if round >= rounds => FocusStep::Done,
+        FocusPhase::Focus => FocusStep::Break { round },
+        FocusPhase::Break => FocusStep::Focus { round: round + 1 },
+    }
+}
+
+/// Check `/pomodoro start` lengths, returning a user-facing error otherwise
+pub fn validate_session(focus_minutes: i64, break_minutes: i64, rounds: i64) -> Result<(), &'static str> {
+    if !(1..=180).contains(&focus_minutes) {
+        return Err("Focus intervals must be between 1 and 180 minutes.");
+    }
+    if !(1..=60).contains(&break_minutes) {
+        return Err("Breaks must be between 1 and 60 minutes.");
+    }
+    if !(1..=MAX_ROUNDS).contains(&rounds) {
+        return Err("Rounds must be between 1 and 12.");
+    }
+    Ok(())
+}
+
+pub fn join_custom_id(session_id: i64) -> String {
+    format!("{FOCUS_JOIN_PREFIX}{session_id}")
+}
+
+pub fn parse_join_custom_id(custom_id: &str) -> Option<i64> {
+    custom_id.strip_prefix(FOCUS_JOIN_PREFIX)?.parse().ok()
+}
+
+/// User IDs from `focus_sessions.participants`
+pub fn parse_participants(json: &str) -> Vec<String> {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+pub fn participants_json(participants: &[String]) -> String {
+    serde_json::to_string(participants).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn mentions(participants: &[String]) -> String {
+    participants.iter().map(|id| format!("<@{id}>")).collect::<Vec<_>>().join(" ")
+}
+
+/// The `/pomodoro start` announcement
+pub fn format_session_start(session: &FocusSession, participants: &[String]) -> String {
+    let mut lines = vec![format!(
+        "🍅 **Focus session started** by <@{}>: {} × {} minutes of focus with {}-minute breaks.",
+        session.started_by, session.rounds, session.focus_minutes, session.break_minutes
+    )];
+    if let Some(voice) = &session.voice_channel_id {
+        let silence = match session.silence.as_deref().and_then(VoiceSilence::parse) {
+            Some(VoiceSilence::Mute) => ", muted while focusing",
+            Some(VoiceSilence::Deafen) => ", muted and deafened while focusing",
+            None => "",
+        };
+        lines.push(format!("🎧 Studying together in <#{voice}>{silence}."));
+    }
+    lines.push(format!("👥 {}", mentions(participants)));
+    lines.push(format!(
+        "Round 1 of {} ends <t:{}:R>. Press **Join** to take part.",
+        session.rounds, session.phase_ends_at
+    ));
+    lines.join("\n")
+}
+
+/// Ping when a focus interval ends and a break begins
+pub fn format_break_start(participants: &[String], round: i64, rounds: i64, break_minutes: i64, ends_at: i64) -> String {
+    format!(
+        "☕ {} Round {round} of {rounds} done! Take a {break_minutes}-minute break; focus resumes <t:{ends_at}:R>.",
+        mentions(participants)
+    )
+}
+
+/// Ping when a break ends and the next focus interval begins
+pub fn format_focus_start(participants: &[String], round: i64, rounds: i64, focus_minutes: i64, ends_at: i64) -> String {
+    format!(
+        "🍅 {} Back to work: round {round} of {rounds}, {focus_minutes} minutes of focus until <t:{ends_at}:t>.",
+        mentions(participants)
+    )
+}
+
+/// Ping when the last focus interval ends
+pub fn format_session_done(participants: &[String], rounds: i64, focus_minutes: i64) -> String {
+    format!(
+        "🎉 {} Session complete: {rounds} rounds, {} minutes of focus in all. See `/focus_stats` for the leaderboard.",
+        mentions(participants),
+        rounds * focus_minutes
+    )
+}
+
+/// The `/pomodoro status` reply
+pub fn format_session_status(session: &FocusSession) -> String {
+    let participants = parse_participants(&session.participants);
+    let phase = match FocusPhase::parse(&session.phase) {
+        Some(FocusPhase::Break) => format!("☕ On a break after round {} of {}", session.current_round, session.rounds),
+        _ => format!("🍅 Focusing: round {} of {}", session.current_round, session.rounds),
+    };
+    format!(
+        "{phase}, ends <t:{}:R>.\n{} × {} minutes of focus with {}-minute breaks, started by <@{}>.\n👥 {}",
+        session.phase_ends_at,
+        session.rounds,
+        session.focus_minutes,
+        session.break_minutes,
+        session.started_by,
+        mentions(&participants)
+    )
+}
+
+/// The `/focus_stats` reply; `rows` are `(user_id, completed intervals, minutes)`, most focused first
+pub fn format_focus_leaderboard(rows: &[(String, i64, i64)], days: i64, own: (i64, i64)) -> String {
+    let mut out = format!("**🍅 Focus leaderboard (last {days} days)**\n");
+    if rows.is_empty() {
+        out.push_str("Nobody has finished a focus interval yet. Start one with `/pomodoro start`.");
+    } else {
+        let lines: Vec<String> = rows
+            .iter()
+            .enumerate()
+            .map(|(i, (user_id, completed, minutes))| {
+                let rank = match i {
+                    0 => "🥇".to_string(),
+                    1 => "🥈".to_string(),
+                    2 => "🥉".to_string(),
+                    _ => format!("{}.", i + 1),
+                };
+                format!("{rank} <@{user_id}> — {} ({})", format_focus_time(*minutes), pomodoros(*completed))
+            })
+            .collect();
+        out.push_str(&lines.join("\n"));
+    }
+    let (completed, minutes) = own;
+    out.push_str(&format!("\n\nYou: {} ({})", format_focus_time(minutes), pomodoros(completed)));
+    out
+}
+
+fn pomodoros(count: i64) -> String {
+    if count == 1 {
+        "1 pomodoro".to_string()
+    } else {
+        format!("{count} pomodoros")
+    }
+}
+
+/// Minutes as `1h 40m`, or `40m` under an hour
+fn format_focus_time(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{m}m"),
+        (h, m) => format!("{h}h {m}m"),
+    }
+}
+
+/// Server-mute (or mute and deafen) the participants who are in the session's voice channel
+pub async fn silence_voice(http: &Http, session: &FocusSession, roster: &VoiceRoster) {
+    let (Some(silence), Some(voice)) = (session.silence.as_deref().and_then(VoiceSilence::parse), &session.voice_channel_id) else {
+        return;
+    };
+    let (Ok(guild), Ok(voice)) = (session.guild_id.parse::<u64>(), voice.parse::<u64>()) else {
+        return;
+    };
+    let in_voice = roster.members_in(guild, voice);
+    for user_id in parse_participants(&session.participants) {
+        let Some(user) = user_id.parse::<u64>().ok().filter(|id| in_voice.contains(id)) else {
+            continue;
+        };
+        let deafen = silence == VoiceSilence::Deafen;
+        if let Err(e) = GuildId(guild).edit_member(http, UserId(user), |m| m.mute(true).deafen(deafen)).await {
+            warn!("Failed to silence {user_id} for focus session {}: {e}", session.id);
+        }
+    }
+}
+
+/// Undo [`silence_voice`] for every participant. Members who left the voice channel can't be
+/// unmuted until they're back, so failures are only logged.
+pub async fn release_voice(http: &Http, session: &FocusSession) {
+    let Some(silence) = session.silence.as_deref().and_then(VoiceSilence::parse) else {
+        return;
+    };
+    let Ok(guild) = session.guild_id.parse::<u64>() else {
+        return;
+    };
+    for user_id in parse_participants(&session.participants) {
+        let Ok(user) = user_id.parse::<u64>() else {
+            continue;
+        };
+        let result = match silence {
+            VoiceSilence::Mute => GuildId(guild).edit_member(http, UserId(user), |m| m.mute(false)).await,
+            VoiceSilence::Deafen => GuildId(guild).edit_member(http, UserId(user), |m| m.mute(false).deafen(false)).await,
+        };
+        if let Err(e) = result {
+            debug!("Couldn't unmute {user_id} after focus session {}: {e}", session.id);
+        }
+    }
+}
+
+/// Move a session whose interval has run out on to the next one, or finish it
+async fn advance_session(http: &Http, db: &Database, roster: &VoiceRoster, session: &FocusSession) -> Result<()> {
+    let channel = ChannelId(session.channel_id.parse::<u64>()?);
+    let participants = parse_participants(&session.participants);
+    let phase = FocusPhase::parse(&session.phase).unwrap_or(FocusPhase::Focus);
+    let ends_at = |minutes: i64| chrono::Utc::now().timestamp() + minutes * 60;
+
+    match next_step(phase, session.current_round, session.rounds) {
+        FocusStep::Break { round } => {
+            db.record_focus_completions(&session.guild_id, &session.channel_id, &participants, session.focus_minutes)
+                .await?;
+            db.advance_focus_session(session.id, FocusPhase::Break.as_str(), round, session.break_minutes)
+                .await?;
+            release_voice(http, session).await;
+            let ping = format_break_start(&participants, round, session.rounds, session.break_minutes, ends_at(session.break_minutes));
+            channel.say(http, ping).await?;
+        }
+        FocusStep::Focus { round } => {
+            db.advance_focus_session(session.id, FocusPhase::Focus.as_str(), round, session.focus_minutes)
+                .await?;
+            silence_voice(http, session, roster).await;
+            let ping = format_focus_start(&participants, round, session.rounds, session.focus_minutes, ends_at(session.focus_minutes));
+            channel.say(http, ping).await?;
+        }
+        FocusStep::Done => {
+            db.record_focus_completions(&session.guild_id, &session.channel_id, &participants, session.focus_minutes)
+                .await?;
+            db.end_focus_session(session.id).await?;
+            release_voice(http, session).await;
+            channel.say(http, format_session_done(&participants, session.rounds, session.focus_minutes)).await?;
+            info!("🍅 Focus session {} in channel {} complete", session.id, session.channel_id);
+        }
+    }
+    Ok(())
+}
+
+/// Background task that moves focus sessions between intervals and pings their participants
+pub async fn focus_timer_loop(http: Arc<Http>, db: Arc<Database>, roster: VoiceRoster) {
+    let mut interval = tokio::time::interval(Duration::from_secs(FOCUS_CHECK_INTERVAL_SECS));
+    info!("Focus session timer started (checks every {FOCUS_CHECK_INTERVAL_SECS}s)");
+
+    loop {
+        interval.tick().await;
+        let due = match db.get_due_focus_sessions().await {
+            Ok(due) => due,
+            Err(e) => {
+                warn!("Failed to load due focus sessions: {}", e);
+                continue;
+            }
+        };
+
+        for session in due {
+            if let Err(e) = advance_session(&http, &db, &roster, &session).await {
+                warn!("Failed to advance focus session {} in channel {}: {}", session.id, session.channel_id, e);
+                // Deleted channel or missing permission - don't retry forever
+                release_voice(&http, &session).await;
+                if let Err(e) = db.end_focus_session(session.id).await {
+                    warn!("Failed to end focus session {}: {}", session.id, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_step() {
+        assert_eq!(next_step(FocusPhase::Focus, 1, 4), FocusStep::Break { round: 1 });
+        assert_eq!(next_step(FocusPhase::Break, 1, 4), FocusStep::Focus { round: 2 });
+        assert_eq!(next_step(FocusPhase::Focus, 4, 4), FocusStep::Done);
+        assert_eq!(next_step(FocusPhase::Focus, 1, 1), FocusStep::Done);
+    }
+
+    #[test]
+    fn test_validate_session() {
+        assert!(validate_session(DEFAULT_FOCUS_MINUTES, DEFAULT_BREAK_MINUTES, DEFAULT_ROUNDS).is_ok());
+        assert!(validate_session(0, 5, 4).is_err());
+        assert!(validate_session(25, 61, 4).is_err());
+        assert!(validate_session(25, 5, MAX_ROUNDS + 1).is_err());
+    }
+
+    #[test]
+    fn test_join_custom_id_round_trip() {
+        assert_eq!(parse_join_custom_id(&join_custom_id(42)), Some(42));
+        assert_eq!(parse_join_custom_id("focus_join:x"), None);
+        assert_eq!(parse_join_custom_id("conflict_action:42:dismiss"), None);
+    }
+
+    #[test]
+    fn test_participants_round_trip() {
+        let participants = vec!["1".to_string(), "2".to_string()];
+        assert_eq!(parse_participants(&participants_json(&participants)), participants);
+        assert!(parse_participants("not json").is_empty());
+    }
+
+    #[test]
+    fn test_format_focus_leaderboard() {
+        let empty = format_focus_leaderboard(&[], 30, (0, 0));
+        assert!(empty.contains("Nobody has finished"));
+        assert!(empty.ends_with("You: 0m (0 pomodoros)"));
+
+        let rows = vec![("1".to_string(), 4, 100), ("2".to_string(), 1, 25)];
+        let board = format_focus_leaderboard(&rows, 30, (1, 25));
+        assert!(board.contains("🥇 <@1> — 1h 40m (4 pomodoros)"));
+        assert!(board.contains("🥈 <@2> — 25m (1 pomodoro)"));
+    }
+
+    #[test]
+    fn test_format_session_status() {
+        let session = FocusSession {
+            started_by: "9".to_string(),
+            participants: participants_json(&["9".to_string()]),
+            focus_minutes: 25,
+            break_minutes: 5,
+            rounds: 4,
+            current_round: 2,
+            phase: "break".to_string(),
+            phase_ends_at: 1_700_000_000,
+            ..Default::default()
+        };
+        let status = format_session_status(&session);
+        assert!(status.starts_with("☕ On a break after round 2 of 4, ends <t:1700000000:R>."));
+        assert!(status.ends_with("👥 <@9>"));
+    }
+}
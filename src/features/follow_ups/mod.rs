@@ -0,0 +1,14 @@
+//! # Follow-up Suggestions Feature
+//!
+//! Suggested follow-up questions attached to AI replies as buttons, requested
+//! as structured output where the provider supports it.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod suggestions;
+
+pub use suggestions::{
+    follow_up_schema, split_follow_ups, split_structured_follow_ups, FOLLOW_UP_INSTRUCTION, FOLLOW_UP_SCHEMA_INSTRUCTION, MAX_FOLLOW_UPS,
+};
@@ -0,0 +1,170 @@
+//! # Feature: Follow-up Suggestions
+//!
+//! Asks the model for up to three follow-up questions alongside its answer so
+//! they can be shown as buttons. Providers with structured output return both
+//! as JSON matching [`follow_up_schema`]; others append a marker-delimited
+//! block that is split off the answer. Enabled per guild with the
+//! `follow_up_suggestions` setting.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Structured JSON output where the provider supports it, marker block as fallback
+//! - 1.0.0: Initial release with structured follow-up block parsing
+
+use crate::core::discord_limits::truncate;
+use crate::features::llm::ResponseSchema;
+use serde::Deserialize;
+use serde_json::json;
+
+/// Maximum number of follow-up questions shown
+pub const MAX_FOLLOW_UPS: usize = 3;
+
+/// Discord button labels are limited to 80 characters
+pub const MAX_FOLLOW_UP_CHARS: usize = 80;
+
+/// Marker separating the answer from the follow-up block
+const FOLLOW_UP_MARKER: &str = "<<<FOLLOWUPS>>>";
+
+/// System prompt suffix requesting follow-ups in the same completion, for providers without structured output
+pub const FOLLOW_UP_INSTRUCTION: &str = "\n\nAfter your answer, suggest up to 3 short follow-up questions \
+the user might ask next, written from the user's point of view (max 80 characters each). \
+Put them on the final lines in exactly this format and nothing after it:\n\
+<<<FOLLOWUPS>>>\n[\"First question?\", \"Second question?\", \"Third question?\"]";
+
+/// System prompt suffix requesting follow-ups as part of a [`follow_up_schema`] reply
+pub const FOLLOW_UP_SCHEMA_INSTRUCTION: &str = "\n\nPut your full answer in `answer`. In `follow_ups`, suggest up to 3 \
+short follow-up questions the user might ask next, written from the user's point of view (max 80 characters each).";
+
+/// Structured reply carrying the answer and its follow-up questions
+#[derive(Deserialize)]
+struct StructuredReply {
+    answer: String,
+    #[serde(default)]
+    follow_ups: Vec<String>,
+}
+
+/// JSON Schema for a reply with follow-ups, sent as the request's response format
+pub fn follow_up_schema() -> ResponseSchema {
+    ResponseSchema {
+        name: "answer_with_follow_ups".to_string(),
+        schema: json!({
+            "type": "object",
+            "properties": {
+                "answer": { "type": "string" },
+                "follow_ups": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["answer", "follow_ups"],
+            "additionalProperties": false
+        }),
+    }
+}
+
+/// Split a reply requested with [`follow_up_schema`] into the answer and its follow-ups.
+///
+/// Replies that aren't valid JSON (a truncated completion, or a provider that
+/// ignored the format) go through [`split_follow_ups`], so a marker block the
+/// model wrote anyway never reaches the user.
+pub fn split_structured_follow_ups(response: &str) -> (String, Vec<String>) {
+    match serde_json::from_str::<StructuredReply>(response.trim()) {
+        Ok(reply) => {
+            let (answer, _) = split_follow_ups(&reply.answer);
+            (answer, clean_questions(reply.follow_ups))
+        }
+        Err(_) => split_follow_ups(response),
+    }
+}
+
+/// Split a model response into the answer and its suggested follow-up questions.
+///
+/// If no well-formed follow-up block is present the whole response is returned
+/// as the answer and the question list is empty.
+pub fn split_follow_ups(response: &str) -> (String, Vec<String>) {
+    let Some((answer, block)) = response.split_once(FOLLOW_UP_MARKER) else {
+        return (response.trim().to_string(), Vec::new());
+    };
+
+    let block = block.trim().trim_start_matches("```json").trim_start_matches("```").trim_end_matches("```").trim();
+    let questions = serde_json::from_str::<Vec<String>>(block).unwrap_or_default();
+
+    (answer.trim().to_string(), clean_questions(questions))
+}
+
+/// Trim questions, drop empty ones and cap their count and length for buttons
+fn clean_questions(questions: Vec<String>) -> Vec<String> {
+    questions
+        .into_iter()
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty())
+        .map(|q| truncate(&q, MAX_FOLLOW_UP_CHARS))
+        .take(MAX_FOLLOW_UPS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_with_follow_ups() {
+        let response = "Paris is the capital.\n<<<FOLLOWUPS>>>\n[\"What about Lyon?\", \"How old is Paris?\"]";
+        let (answer, questions) = split_follow_ups(response);
+        assert_eq!(answer, "Paris is the capital.");
+        assert_eq!(questions, vec!["What about Lyon?", "How old is Paris?"]);
+    }
+
+    #[test]
+    fn test_split_without_block() {
+        let (answer, questions) = split_follow_ups("  Just an answer.  ");
+        assert_eq!(answer, "Just an answer.");
+        assert!(questions.is_empty());
+    }
+
+    #[test]
+    fn test_malformed_block_is_dropped() {
+        let (answer, questions) = split_follow_ups("Answer\n<<<FOLLOWUPS>>>\nnot json");
+        assert_eq!(answer, "Answer");
+        assert!(questions.is_empty());
+    }
+
+    #[test]
+    fn test_repeated_marker_is_not_shown() {
+        let (answer, questions) = split_follow_ups("Answer\n<<<FOLLOWUPS>>>\n<<<FOLLOWUPS>>>\n[\"Why?\"]");
+        assert_eq!(answer, "Answer");
+        assert!(questions.is_empty());
+    }
+
+    #[test]
+    fn test_structured_reply() {
+        let response = r#"{"answer": "Paris is the capital.", "follow_ups": ["What about Lyon?", "  "]}"#;
+        let (answer, questions) = split_structured_follow_ups(response);
+        assert_eq!(answer, "Paris is the capital.");
+        assert_eq!(questions, vec!["What about Lyon?"]);
+    }
+
+    #[test]
+    fn test_malformed_structured_reply_hides_marker() {
+        // Truncated JSON with the model also writing the fallback block
+        let response = "Paris is the capital.\n<<<FOLLOWUPS>>>\n[\"What about Lyon?\"";
+        let (answer, questions) = split_structured_follow_ups(response);
+        assert_eq!(answer, "Paris is the capital.");
+        assert!(!answer.contains(FOLLOW_UP_MARKER));
+        assert!(questions.is_empty());
+
+        // A marker inside the structured answer is stripped as well
+        let response = r#"{"answer": "Paris.\n<<<FOLLOWUPS>>>\n[\"Lyon?\"]", "follow_ups": []}"#;
+        let (answer, _) = split_structured_follow_ups(response);
+        assert_eq!(answer, "Paris.");
+    }
+
+    #[test]
+    fn test_limits_count_and_length() {
+        let long = "x".repeat(200);
+        let response = format!("A\n<<<FOLLOWUPS>>>\n```json\n[\"{long}\", \"b\", \"c\", \"d\"]\n```");
+        let (_, questions) = split_follow_ups(&response);
+        assert_eq!(questions.len(), MAX_FOLLOW_UPS);
+        assert_eq!(questions[0].chars().count(), MAX_FOLLOW_UP_CHARS);
+    }
+}
@@ -0,0 +1,176 @@
+//! # Feature: Unanswered Question Digest Loop
+//!
+//! Background task that checks the `support_channels` of each guild with a
+//! `help_digest_channel` every hour. Once a day at most, questions that went
+//! `help_digest_hours` without a reply are posted to that staff channel with
+//! jump links, plus an AI draft answer for each when `help_digest_drafts` is
+//! enabled. Listed questions are recorded so they appear in one digest only.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use super::questions::{
+    draft_answer_prompt, find_unanswered, format_digest, HelpMessage, DEFAULT_DIGEST_HOURS, MAX_DIGEST_HOURS,
+    MAX_DIGEST_QUESTIONS, MAX_QUESTION_AGE_HOURS,
+};
+use crate::core::Result;
+use crate::database::Database;
+use crate::features::analytics::cost_report::cost_feature;
+use crate::features::analytics::UsageTracker;
+use crate::features::hot_reload::LiveSettings;
+use crate::features::llm::{ChatMessage, ChatRequest, LlmProvider};
+use log::{info, warn};
+use serenity::http::Http;
+use serenity::model::id::ChannelId;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often guilds are checked for a due digest
+const CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Least time between two digests for a guild
+const DIGEST_PERIOD_SECS: i64 = 24 * 3600;
+
+/// Recent messages read from each help channel
+const MESSAGES_PER_CHANNEL: u64 = 100;
+
+/// Posts unanswered question digests to staff channels
+pub struct HelpDigester {
+    database: Database,
+    llm: Arc<dyn LlmProvider>,
+    live_settings: LiveSettings,
+    usage_tracker: UsageTracker,
+}
+
+impl HelpDigester {
+    pub fn new(database: Database, llm: Arc<dyn LlmProvider>, live_settings: LiveSettings, usage_tracker: UsageTracker) -> Self {
+        Self { database, llm, live_settings, usage_tracker }
+    }
+
+    /// Check every guild once an hour, forever
+    pub async fn run(&self, http: Arc<Http>) {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        info!("Unanswered question digest task started (checks every hour)");
+
+        loop {
+            interval.tick().await;
+            let guilds = match self.database.get_guilds_with_settings().await {
+                Ok(guilds) => guilds,
+                Err(e) => {
+                    warn!("Failed to load guilds for the unanswered question digest: {e}");
+                    continue;
+                }
+            };
+            for guild_id in guilds {
+                match self.digest_guild(&http, &guild_id).await {
+                    Ok(0) => {}
+                    Ok(count) => info!("Posted a digest of {count} unanswered questions for guild {guild_id}"),
+                    Err(e) => warn!("Failed to post the unanswered question digest for guild {guild_id}: {e}"),
+                }
+            }
+        }
+    }
+
+    async fn setting(&self, guild_id: &str, key: &str) -> Result<Option<String>> {
+        Ok(self.database.get_guild_setting(guild_id, key).await?.filter(|v| v != "disabled"))
+    }
+
+    /// Post the guild's digest if one is due; returns how many questions it listed
+    async fn digest_guild(&self, http: &Http, guild_id: &str) -> Result<usize> {
+        let Some(staff_channel) = self.setting(guild_id, "help_digest_channel").await?.and_then(|v| v.parse::<u64>().ok()) else {
+            return Ok(0);
+        };
+        let Some(help_channels) = self.setting(guild_id, "support_channels").await? else {
+            return Ok(0);
+        };
+        let now = chrono::Utc::now().timestamp();
+        if self.database.get_last_help_digest(guild_id).await?.is_some_and(|last| now - last < DIGEST_PERIOD_SECS) {
+            return Ok(0);
+        }
+        let hours = self
+            .setting(guild_id, "help_digest_hours")
+            .await?
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|h| (1..=MAX_DIGEST_HOURS).contains(h))
+            .unwrap_or(DEFAULT_DIGEST_HOURS);
+        let drafts = self.setting(guild_id, "help_digest_drafts").await?.is_some_and(|v| v == "enabled");
+
+        let mut messages = Vec::new();
+        for channel_id in help_channels.split(',').filter_map(|id| id.trim().parse::<u64>().ok()) {
+            match ChannelId(channel_id).messages(http, |r| r.limit(MESSAGES_PER_CHANNEL)).await {
+                Ok(fetched) => messages.extend(fetched.into_iter().map(|m| HelpMessage {
+                    id: m.id.0,
+                    channel_id,
+                    author_id: m.author.id.0,
+                    author_bot: m.author.bot,
+                    content: m.content,
+                    timestamp: m.timestamp.unix_timestamp(),
+                    reply_to: m.message_reference.and_then(|r| r.message_id).map(|id| id.0),
+                    mentions: m.mentions.iter().map(|u| u.id.0).collect(),
+                })),
+                Err(e) => warn!("Failed to read help channel {channel_id} in guild {guild_id}: {e}"),
+            }
+        }
+
+        let mut questions = Vec::new();
+        for question in find_unanswered(&messages, now, hours, MAX_QUESTION_AGE_HOURS) {
+            if questions.len() >= MAX_DIGEST_QUESTIONS {
+                break;
+            }
+            if !self.database.is_help_question_reported(&question.id.to_string()).await? {
+                questions.push(question.clone());
+            }
+        }
+        if questions.is_empty() {
+            return Ok(0);
+        }
+
+        let mut entries = Vec::new();
+        for question in questions {
+            let draft = if drafts { self.draft_answer(guild_id, &question).await } else { None };
+            entries.push((question, draft));
+        }
+        let guild = guild_id.parse::<u64>()?;
+        for chunk in format_digest(guild, now, &entries) {
+            ChannelId(staff_channel).say(http, chunk).await?;
+        }
+
+        let listed: Vec<(String, String)> =
+            entries.iter().map(|(q, _)| (q.channel_id.to_string(), q.id.to_string())).collect();
+        self.database.record_help_digest(guild_id, &listed, now).await?;
+        Ok(listed.len())
+    }
+
+    /// A draft answer for a question, or `None` if the model call fails
+    async fn draft_answer(&self, guild_id: &str, question: &HelpMessage) -> Option<String> {
+        let model = self.live_settings.model();
+        let request = ChatRequest::new(&model, vec![ChatMessage::system(draft_answer_prompt(&question.content))]);
+        match self.llm.chat(&request).await {
+            Ok(completion) => {
+                if let Some(usage) = &completion.usage {
+                    let channel_id = question.channel_id.to_string();
+                    self.usage_tracker.log_chat(
+                        &model,
+                        usage.prompt_tokens,
+                        usage.completion_tokens,
+                        usage.total_tokens,
+                        "system_help_digest",
+                        Some(guild_id),
+                        Some(&channel_id),
+                        None,
+                        cost_feature::DIGEST,
+                    );
+                }
+                completion.content
+            }
+            Err(e) => {
+                warn!("Failed to draft an answer for question {} in guild {guild_id}: {e}", question.id);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,17 @@
+//! # Help Digest Feature
+//!
+//! A daily digest, posted to a staff channel, of questions in the guild's
+//! help (`support_channels`) channels that nobody has replied to for a few
+//! hours, with jump links and optional AI draft answers.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod digest;
+pub mod questions;
+
+pub use digest::HelpDigester;
+pub use questions::{
+    find_unanswered, format_digest, looks_like_question, HelpMessage, DEFAULT_DIGEST_HOURS, MAX_DIGEST_HOURS,
+};
@@ -0,0 +1,197 @@
+//! # Feature: Unanswered Question Detection
+//!
+//! Finds messages in a help channel that look like questions and got no
+//! answer: no later message from someone else replies to them or mentions the
+//! asker. Also formats the digest posted to the staff channel.
+//!
+//! - **Version**: 1.0.1
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.1: Quotes are cut with the shared `discord_limits::truncate`
+//! - 1.0.0: Initial release
+
+use crate::core::discord_limits::{split_message, truncate};
+use crate::features::reminders::build_message_link;
+
+/// Hours a question waits for a reply before it is listed, when `help_digest_hours` is unset
+pub const DEFAULT_DIGEST_HOURS: u64 = 6;
+
+/// Largest accepted `help_digest_hours`
+pub const MAX_DIGEST_HOURS: u64 = 168;
+
+/// Questions older than this are no longer listed
+pub const MAX_QUESTION_AGE_HOURS: u64 = 7 * 24;
+
+/// Questions listed per digest; the rest wait for the next one
+pub const MAX_DIGEST_QUESTIONS: usize = 15;
+
+/// Characters of each question quoted in the digest
+const QUOTE_CHARS: usize = 200;
+
+/// Words that open a question even without a question mark
+const QUESTION_OPENERS: &[&str] = &[
+    "how", "what", "why", "when", "where", "which", "who", "is", "are", "can", "could", "does", "do", "should",
+    "would", "will", "has", "have", "anyone", "any",
+];
+
+/// A help channel message, reduced to what the detection needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct HelpMessage {
+    pub id: u64,
+    pub channel_id: u64,
+    pub author_id: u64,
+    pub author_bot: bool,
+    pub content: String,
+    /// Unix seconds
+    pub timestamp: i64,
+    /// Message this one replies to
+    pub reply_to: Option<u64>,
+    /// Users mentioned in it
+    pub mentions: Vec<u64>,
+}
+
+/// Whether a message reads like a question someone should answer
+pub fn looks_like_question(content: &str) -> bool {
+    let content = content.trim();
+    if content.chars().count() < 10 {
+        return false;
+    }
+    if content.contains('?') {
+        return true;
+    }
+    let first = content
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase();
+    QUESTION_OPENERS.contains(&first.as_str())
+}
+
+/// Questions in `messages` (any order) asked between `max_age_hours` and
+/// `min_age_hours` ago that nobody else has replied to, oldest first
+pub fn find_unanswered(messages: &[HelpMessage], now: i64, min_age_hours: u64, max_age_hours: u64) -> Vec<&HelpMessage> {
+    let newest = now - (min_age_hours * 3600) as i64;
+    let oldest = now - (max_age_hours * 3600) as i64;
+    let mut unanswered: Vec<&HelpMessage> = messages
+        .iter()
+        .filter(|m| !m.author_bot && m.timestamp <= newest && m.timestamp >= oldest && looks_like_question(&m.content))
+        .filter(|question| {
+            !messages.iter().any(|m| {
+                m.author_id != question.author_id
+                    && m.timestamp >= question.timestamp
+                    && m.channel_id == question.channel_id
+                    && (m.reply_to == Some(question.id) || m.mentions.contains(&question.author_id))
+            })
+        })
+        .collect();
+    unanswered.sort_by_key(|m| (m.timestamp, m.id));
+    unanswered
+}
+
+/// Instructions for a draft answer a staff member can adapt
+pub fn draft_answer_prompt(question: &str) -> String {
+    format!(
+        "A member asked this in a Discord help channel and nobody has answered yet. Write a short draft answer (2-4 \
+sentences) a staff member could post. If the question can't be answered without more details, say what to ask for \
+instead. Don't invent product specifics you can't know.\n\nQuestion: {question}"
+    )
+}
+
+fn quote(content: &str) -> String {
+    let flat = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate(&flat, QUOTE_CHARS)
+}
+
+/// The digest as Discord-sized messages: one entry per question with its jump
+/// link and age, followed by the draft answer when there is one
+pub fn format_digest(guild_id: u64, now: i64, questions: &[(HelpMessage, Option<String>)]) -> Vec<String> {
+    let mut text = format!(
+        "📋 **Unanswered questions** ({})\nThese questions in help channels haven't had a reply yet:\n",
+        questions.len()
+    );
+    for (i, (question, draft)) in questions.iter().enumerate() {
+        let hours = ((now - question.timestamp).max(0) / 3600).max(1);
+        let link = build_message_link(Some(guild_id), question.channel_id, question.id);
+        text.push_str(&format!(
+            "\n**{}.** <@{}> in <#{}>, {hours}h ago: {link}\n> {}\n",
+            i + 1,
+            question.author_id,
+            question.channel_id,
+            quote(&question.content)
+        ));
+        if let Some(draft) = draft.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+            text.push_str(&format!("💡 *Draft answer:* {}\n", quote(draft)));
+        }
+    }
+    split_message(&text, 2000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: u64, author_id: u64, content: &str, timestamp: i64) -> HelpMessage {
+        HelpMessage {
+            id,
+            channel_id: 7,
+            author_id,
+            author_bot: false,
+            content: content.to_string(),
+            timestamp,
+            reply_to: None,
+            mentions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_looks_like_question() {
+        assert!(looks_like_question("Does the bot work in DMs"));
+        assert!(looks_like_question("my build fails with E0382?"));
+        assert!(looks_like_question("Anyone know how to reset my key"));
+        assert!(!looks_like_question("thanks!"));
+        assert!(!looks_like_question("I fixed it by updating the config file"));
+    }
+
+    #[test]
+    fn test_find_unanswered() {
+        let now = 100 * 3600;
+        let replied = message(2, 11, "How do I reset my password?", now - 10 * 3600);
+        let mut reply = message(3, 12, "Use /reset", now - 9 * 3600);
+        reply.reply_to = Some(2);
+        let mentioned = message(4, 13, "Why is the bot offline?", now - 8 * 3600);
+        let mut mention = message(5, 12, "it was restarted", now - 7 * 3600);
+        mention.mentions = vec![13];
+        let mut bot = message(9, 99, "What can I help with?", now - 10 * 3600);
+        bot.author_bot = true;
+
+        let messages = vec![
+            message(1, 10, "Can I change the prefix?", now - 20 * 3600),
+            replied,
+            reply,
+            mentioned,
+            mention,
+            // The asker following up on their own question doesn't answer it
+            message(6, 10, "@here can I change the prefix??", now - 19 * 3600),
+            // Too recent, and too old
+            message(7, 14, "Where are the logs?", now - 3600),
+            message(8, 15, "Is there an API?", now - 200 * 3600),
+            bot,
+        ];
+        let ids: Vec<u64> = find_unanswered(&messages, now, 6, MAX_QUESTION_AGE_HOURS).iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![1, 6]);
+    }
+
+    #[test]
+    fn test_format_digest() {
+        let now = 100 * 3600;
+        let question = message(42, 10, "How do I\nreset my key?", now - 8 * 3600);
+        let chunks = format_digest(1, now, &[(question, Some("Run /reset in DMs.".to_string()))]);
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].contains("<@10> in <#7>, 8h ago: https://discord.com/channels/1/7/42"));
+        assert!(chunks[0].contains("> How do I reset my key?"));
+        assert!(chunks[0].contains("*Draft answer:* Run /reset in DMs."));
+    }
+}
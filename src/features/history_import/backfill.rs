@@ -0,0 +1,155 @@
+//! # Feature: History Backfill Rules
+//!
+//! What `/import_history` stores and how it asks for consent: which past
+//! Discord messages count as conversation history, the Confirm/Cancel button
+//! IDs, and the notices shown before and after an import.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use chrono::{DateTime, Duration, Utc};
+use crate::core::clock::sql_timestamp;
+
+/// Days imported when the admin doesn't say
+pub const DEFAULT_IMPORT_DAYS: i64 = 30;
+
+/// Furthest back an import may reach
+pub const MAX_IMPORT_DAYS: i64 = 90;
+
+/// Custom ID prefix of the consent buttons: `histimport:<channel>:<days>` confirms, `histimport:cancel` cancels
+pub const IMPORT_HISTORY_PREFIX: &str = "histimport:";
+
+/// Custom ID of the Cancel button
+pub const IMPORT_CANCEL_ID: &str = "histimport:cancel";
+
+/// A past channel message, as read from the Discord API
+#[derive(Debug, Clone)]
+pub struct PastMessage {
+    pub id: u64,
+    pub author_id: u64,
+    pub author_bot: bool,
+    /// Regular message or reply, as opposed to joins, pins and other system messages
+    pub from_user: bool,
+    pub content: String,
+    pub attachment_urls: Vec<String>,
+    /// Unix seconds
+    pub timestamp: i64,
+}
+
+impl PastMessage {
+    /// Whether the message would have been stored had the bot been there: text written by a person, not a command
+    pub fn importable(&self) -> bool {
+        let content = self.content.trim();
+        self.from_user && !self.author_bot && !content.is_empty() && !content.starts_with('/')
+    }
+}
+
+/// Clamp a requested day count to `1..=MAX_IMPORT_DAYS`, defaulting to `DEFAULT_IMPORT_DAYS`
+pub fn import_days(requested: Option<i64>) -> i64 {
+    requested.unwrap_or(DEFAULT_IMPORT_DAYS).clamp(1, MAX_IMPORT_DAYS)
+}
+
+/// Oldest message time to import, in the `conversation_history` timestamp layout
+pub fn import_since(now: DateTime<Utc>, days: i64) -> String {
+    sql_timestamp(now - Duration::days(days))
+}
+
+pub fn confirm_custom_id(channel_id: u64, days: i64) -> String {
+    format!("{IMPORT_HISTORY_PREFIX}{channel_id}:{days}")
+}
+
+/// `(channel_id, days)` from a Confirm button ID
+pub fn parse_confirm_custom_id(custom_id: &str) -> Option<(u64, i64)> {
+    let (channel, days) = custom_id.strip_prefix(IMPORT_HISTORY_PREFIX)?.split_once(':')?;
+    let days = days.parse::<i64>().ok().filter(|d| (1..=MAX_IMPORT_DAYS).contains(d))?;
+    Some((channel.parse().ok()?, days))
+}
+
+/// What the admin agrees to before an import is queued
+pub fn consent_notice(channel_id: u64, days: i64) -> String {
+    format!(
+        "📥 **Import history from <#{channel_id}>?**\n\n\
+         Messages members posted there in the last {days} days will be stored as conversation history, \
+         with their attachment links, as if the bot had been present. Summaries, citations and other \
+         history-based features will use them. Bot messages, system messages and commands are skipped, \
+         as are messages already stored.\n\n\
+         Only confirm if your members have agreed to their messages being processed this way. \
+         The import runs in the background and may take a while on busy channels."
+    )
+}
+
+/// Reply once an import is queued
+pub fn queued_notice(channel_id: u64, days: i64, ahead: i64) -> String {
+    let wait = match ahead {
+        0 => "It starts shortly.".to_string(),
+        1 => "It starts after 1 other import.".to_string(),
+        n => format!("It starts after {n} other imports."),
+    };
+    format!("✅ Import of the last {days} days of <#{channel_id}> queued. {wait} You'll be told in this channel when it's done.")
+}
+
+/// Message posted to the requesting channel when an import ends
+pub fn finished_notice(requested_by: &str, channel_id: &str, imported: i64, error: Option<&str>) -> String {
+    match error {
+        None => format!("📥 <@{requested_by}> History import of <#{channel_id}> finished: {imported} messages imported."),
+        Some(e) => format!(
+            "⚠️ <@{requested_by}> History import of <#{channel_id}> stopped after {imported} messages: {e}"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn message(content: &str) -> PastMessage {
+        PastMessage {
+            id: 1,
+            author_id: 2,
+            author_bot: false,
+            from_user: true,
+            content: content.to_string(),
+            attachment_urls: Vec::new(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_importable() {
+        assert!(message("How do I reset my password?").importable());
+        assert!(!message("   ").importable());
+        assert!(!message("/help").importable());
+        assert!(!PastMessage { author_bot: true, ..message("Hello") }.importable());
+        assert!(!PastMessage { from_user: false, ..message("pinned a message") }.importable());
+    }
+
+    #[test]
+    fn test_import_days_and_since() {
+        assert_eq!(import_days(None), DEFAULT_IMPORT_DAYS);
+        assert_eq!(import_days(Some(0)), 1);
+        assert_eq!(import_days(Some(365)), MAX_IMPORT_DAYS);
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        assert_eq!(import_since(now, 7), "2026-10-09 12:00:00");
+    }
+
+    #[test]
+    fn test_confirm_custom_id_round_trip() {
+        assert_eq!(parse_confirm_custom_id(&confirm_custom_id(123, 30)), Some((123, 30)));
+        assert_eq!(parse_confirm_custom_id(IMPORT_CANCEL_ID), None);
+        assert_eq!(parse_confirm_custom_id("histimport:123:365"), None);
+        assert_eq!(parse_confirm_custom_id("gateverify:123"), None);
+    }
+
+    #[test]
+    fn test_notices() {
+        assert!(queued_notice(5, 30, 0).contains("starts shortly"));
+        assert!(queued_notice(5, 30, 2).contains("after 2 other imports"));
+        assert!(finished_notice("1", "5", 42, None).contains("42 messages imported"));
+        assert!(finished_notice("1", "5", 3, Some("Missing Access")).contains("stopped after 3 messages: Missing Access"));
+    }
+}
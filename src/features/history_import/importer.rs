@@ -0,0 +1,172 @@
+//! # Feature: History Import Queue
+//!
+//! Background task that works through queued `/import_history` jobs one at a
+//! time. Each job reads its channel newest-first, a page of 100 messages at a
+//! time with a pause between pages to stay well inside Discord's rate limits,
+//! and stops at the job's cutoff. Progress is saved after every page, so a
+//! restart resumes where it left off instead of starting over.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use super::backfill::{finished_notice, PastMessage};
+use crate::core::clock::{sql_timestamp, SQL_TIMESTAMP_FORMAT};
+use crate::core::{BotError, Result};
+use crate::database::{Database, HistoryImportJob};
+use chrono::NaiveDateTime;
+use log::{info, warn};
+use serenity::http::Http;
+use serenity::model::channel::MessageType;
+use serenity::model::id::{ChannelId, MessageId};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the queue is checked for new jobs
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Pause between two pages of a channel
+const PAGE_DELAY: Duration = Duration::from_secs(2);
+
+/// Messages per page, Discord's maximum
+const PAGE_SIZE: u64 = 100;
+
+/// Works through the history import queue
+pub struct HistoryImporter {
+    database: Database,
+}
+
+impl HistoryImporter {
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+
+    /// Run queued imports one after another, forever
+    pub async fn run(&self, http: Arc<Http>) {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        info!("History import task started (checks the queue every 15s)");
+
+        loop {
+            interval.tick().await;
+            loop {
+                let job = match self.database.next_history_import().await {
+                    Ok(Some(job)) => job,
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!("Failed to load the next history import: {e}");
+                        break;
+                    }
+                };
+                let id = job.id;
+                let (status, imported, error) = match self.import(&http, &job).await {
+                    Ok(imported) => {
+                        info!("History import {id} of channel {} finished: {imported} messages", job.channel_id);
+                        ("done", imported, None)
+                    }
+                    Err((imported, e)) => {
+                        warn!("History import {id} of channel {} failed: {e}", job.channel_id);
+                        ("failed", imported, Some(e.to_string()))
+                    }
+                };
+                if let Err(e) = self.database.finish_history_import(id, status, error.as_deref()).await {
+                    warn!("Failed to finish history import {id}: {e}");
+                    break;
+                }
+                let notice = finished_notice(&job.requested_by, &job.channel_id, imported, error.as_deref());
+                if let Ok(channel) = job.notify_channel_id.parse::<u64>() {
+                    if let Err(e) = ChannelId(channel).say(&http, notice).await {
+                        warn!("Failed to announce the end of history import {id}: {e}");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Import a job's remaining pages; returns the total imported, or that total and the error that stopped it
+    async fn import(&self, http: &Http, job: &HistoryImportJob) -> std::result::Result<i64, (i64, BotError)> {
+        let mut imported = job.imported;
+        let mut before = job.before_message_id.as_deref().and_then(|id| id.parse::<u64>().ok());
+        let since = NaiveDateTime::parse_from_str(&job.since, SQL_TIMESTAMP_FORMAT)
+            .map_err(|e| (imported, BotError::internal(format!("Invalid import cutoff {}: {e}", job.since))))?
+            .and_utc()
+            .timestamp();
+        let channel = job
+            .channel_id
+            .parse::<u64>()
+            .map(ChannelId)
+            .map_err(|e| (imported, BotError::internal(format!("Invalid channel {}: {e}", job.channel_id))))?;
+
+        loop {
+            let page = channel
+                .messages(http, |r| {
+                    if let Some(id) = before {
+                        r.before(MessageId(id));
+                    }
+                    r.limit(PAGE_SIZE)
+                })
+                .await
+                .map_err(|e| (imported, e.into()))?;
+
+            let (added, reached_cutoff) = self.store_page(&job.channel_id, &page, since).await.map_err(|e| (imported, e))?;
+            imported += added;
+            before = page.last().map(|m| m.id.0).or(before);
+            let before_id = before.map(|id| id.to_string());
+            self.database
+                .update_history_import_progress(job.id, before_id.as_deref(), imported)
+                .await
+                .map_err(|e| (imported, e))?;
+
+            if reached_cutoff || page.len() < PAGE_SIZE as usize {
+                return Ok(imported);
+            }
+            tokio::time::sleep(PAGE_DELAY).await;
+        }
+    }
+
+    /// Store a newest-first page's messages up to the cutoff; returns how many were new and whether the cutoff was reached
+    async fn store_page(&self, channel_id: &str, page: &[serenity::model::channel::Message], since: i64) -> Result<(i64, bool)> {
+        let mut added = 0;
+        for m in page {
+            let message = PastMessage {
+                id: m.id.0,
+                author_id: m.author.id.0,
+                author_bot: m.author.bot,
+                from_user: matches!(m.kind, MessageType::Regular | MessageType::InlineReply),
+                content: m.content.clone(),
+                attachment_urls: m.attachments.iter().map(|a| a.url.clone()).collect(),
+                timestamp: m.timestamp.unix_timestamp(),
+            };
+            if message.timestamp < since {
+                return Ok((added, true));
+            }
+            if !message.importable() {
+                continue;
+            }
+            let Some(at) = chrono::DateTime::from_timestamp(message.timestamp, 0) else {
+                continue;
+            };
+            let attachments = match message.attachment_urls.is_empty() {
+                true => None,
+                false => Some(serde_json::to_string(&message.attachment_urls)?),
+            };
+            if self
+                .database
+                .import_history_message(
+                    &message.id.to_string(),
+                    &message.author_id.to_string(),
+                    channel_id,
+                    message.content.trim(),
+                    attachments.as_deref(),
+                    &sql_timestamp(at),
+                )
+                .await?
+            {
+                added += 1;
+            }
+        }
+        Ok((added, false))
+    }
+}
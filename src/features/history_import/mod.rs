@@ -0,0 +1,21 @@
+//! # History Import Feature
+//!
+//! `/import_history [channel] [days]` backfills stored conversation history
+//! from a channel's past Discord messages, so summaries, citations and other
+//! history-based features work in servers that just added the bot. An admin
+//! confirms that members have consented before the import is queued; a
+//! background task works through the queue at a pace that respects Discord's
+//! rate limits.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod backfill;
+pub mod importer;
+
+pub use backfill::{
+    confirm_custom_id, consent_notice, import_days, import_since, parse_confirm_custom_id, queued_notice, PastMessage,
+    DEFAULT_IMPORT_DAYS, IMPORT_CANCEL_ID, IMPORT_HISTORY_PREFIX, MAX_IMPORT_DAYS,
+};
+pub use importer::HistoryImporter;
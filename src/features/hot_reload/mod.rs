@@ -0,0 +1,16 @@
+//! # Hot Reload Feature
+//!
+//! A process started with `BOTS_CONFIG` runs every bot listed in that
+//! `config.yaml` and watches the file. Editing it starts added bots, stops
+//! removed ones, reconnects bots whose token changed and applies new model,
+//! conflict sensitivity, mediation cooldown and command allowlist values to
+//! running bots without dropping their gateway connection. An invalid file is
+//! logged and ignored, leaving the running bots as they were.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod settings;
+
+pub use settings::{sensitivity_threshold, LiveSettings};
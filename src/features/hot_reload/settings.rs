@@ -0,0 +1,161 @@
+//! # Feature: Live Bot Settings
+//!
+//! The settings a config reload may change while a bot is connected: chat
+//! model, default conflict sensitivity, mediation cooldown, the command
+//! allowlist and the bots it shares conversation history with. The command handler reads them through a shared handle on each
+//! use instead of copying them at startup.
+//!
+//! - **Version**: 1.2.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.2.0: Conflict stage thresholds
+//! - 1.1.0: Memory partners
+//! - 1.0.0: Initial release
+
+use crate::core::Config;
+use crate::features::conflict::{ConflictThresholds, StageThresholds};
+use log::warn;
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Confidence a conflict needs before mediation, by sensitivity name (`medium` for unknown names)
+pub fn sensitivity_threshold(sensitivity: &str) -> f32 {
+    match sensitivity.to_lowercase().as_str() {
+        "low" => 0.7,   // Only very high confidence conflicts
+        "high" => 0.35, // More sensitive - catches single keywords + context
+        "ultra" => 0.3, // Maximum sensitivity - triggers on single hostile keyword
+        _ => 0.5,       // Medium (default)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Values {
+    model: String,
+    sensitivity: String,
+    sensitivity_threshold: f32,
+    conflict_thresholds: ConflictThresholds,
+    mediation_cooldown_minutes: u64,
+    commands: Option<HashSet<String>>,
+    memory_partners: Vec<String>,
+}
+
+/// Shared handle to a bot's reloadable settings; clones see the same values
+#[derive(Debug, Clone)]
+pub struct LiveSettings {
+    values: Arc<RwLock<Values>>,
+}
+
+impl LiveSettings {
+    pub fn new(model: String, conflict_sensitivity: &str, mediation_cooldown_minutes: u64, commands: Option<&[String]>) -> Self {
+        LiveSettings {
+            values: Arc::new(RwLock::new(Values {
+                model,
+                sensitivity: conflict_sensitivity.to_string(),
+                sensitivity_threshold: sensitivity_threshold(conflict_sensitivity),
+                conflict_thresholds: ConflictThresholds::default(),
+                mediation_cooldown_minutes,
+                commands: commands.map(|c| c.iter().cloned().collect()),
+                memory_partners: Vec::new(),
+            })),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        let mut values = Self::new(
+            config.openai_model.clone(),
+            &config.conflict_sensitivity,
+            config.mediation_cooldown_minutes,
+            config.command_allowlist.as_deref(),
+        )
+        .read(Clone::clone);
+        values.memory_partners = config.memory_partners.clone();
+        if let Some(spec) = &config.conflict_thresholds {
+            match ConflictThresholds::parse(spec) {
+                Ok(thresholds) => values.conflict_thresholds = thresholds,
+                Err(e) => warn!("Ignoring CONFLICT_THRESHOLDS: {e}"),
+            }
+        }
+        LiveSettings { values: Arc::new(RwLock::new(values)) }
+    }
+
+    /// Replace every value with the one in `config`
+    pub fn apply(&self, config: &Config) {
+        let next = Self::from_config(config).read(Clone::clone);
+        if let Ok(mut values) = self.values.write() {
+            *values = next;
+        }
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&Values) -> T) -> T {
+        match self.values.read() {
+            Ok(values) => f(&values),
+            Err(poisoned) => f(&poisoned.into_inner()),
+        }
+    }
+
+    /// Chat model for text-only requests
+    pub fn model(&self) -> String {
+        self.read(|v| v.model.clone())
+    }
+
+    /// Conflict confidence threshold for guilds without their own sensitivity
+    pub fn sensitivity_threshold(&self) -> f32 {
+        self.read(|v| v.sensitivity_threshold)
+    }
+
+    /// Conflict stage thresholds for a guild's sensitivity, or the default sensitivity when it has none
+    pub fn conflict_thresholds(&self, guild_sensitivity: Option<&str>) -> StageThresholds {
+        self.read(|v| v.conflict_thresholds.for_sensitivity(guild_sensitivity.unwrap_or(&v.sensitivity)))
+    }
+
+    pub fn mediation_cooldown_minutes(&self) -> u64 {
+        self.read(|v| v.mediation_cooldown_minutes)
+    }
+
+    pub fn mediation_cooldown(&self) -> Duration {
+        Duration::from_secs(self.mediation_cooldown_minutes() * 60)
+    }
+
+    /// Bots in `config.yaml` whose conversation history this bot may read
+    pub fn memory_partners(&self) -> Vec<String> {
+        self.read(|v| v.memory_partners.clone())
+    }
+
+    /// Whether the bot answers this slash or context menu command
+    pub fn command_allowed(&self, name: &str) -> bool {
+        self.read(|v| v.commands.as_ref().is_none_or(|commands| commands.contains(name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_see_updates() {
+        let settings = LiveSettings::new("gpt-4o-mini".to_string(), "medium", 5, None);
+        let shared = settings.clone();
+        assert!(shared.command_allowed("imagine"));
+
+        let commands = vec!["hey".to_string(), "help".to_string()];
+        *settings.values.write().unwrap() = LiveSettings::new("gpt-4o".to_string(), "high", 10, Some(&commands)).read(Clone::clone);
+
+        assert_eq!(shared.model(), "gpt-4o");
+        assert_eq!(shared.sensitivity_threshold(), 0.35);
+        assert_eq!(shared.mediation_cooldown(), Duration::from_secs(600));
+        assert!(shared.command_allowed("hey"));
+        assert!(!shared.command_allowed("imagine"));
+        assert_eq!(shared.conflict_thresholds(None), ConflictThresholds::default().high);
+        assert_eq!(shared.conflict_thresholds(Some("low")), ConflictThresholds::default().low);
+    }
+
+    #[test]
+    fn test_sensitivity_threshold() {
+        assert_eq!(sensitivity_threshold("LOW"), 0.7);
+        assert_eq!(sensitivity_threshold("ultra"), 0.3);
+        assert_eq!(sensitivity_threshold("whatever"), 0.5);
+    }
+}
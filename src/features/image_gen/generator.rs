@@ -3,15 +3,16 @@
 //! DALL-E 3 powered image creation with configurable size (square, landscape, portrait)
 //! and style (vivid, natural) options.
 //!
-//! - **Version**: 1.0.0
+//! - **Version**: 1.1.0
 //! - **Since**: 0.2.0
 //! - **Toggleable**: true
 //!
 //! ## Changelog
+//! - 1.1.0: Each generation runs in an `openai.image` tracing span
 //! - 1.0.0: Initial release with DALL-E 3 integration
 
-use anyhow::Result;
-use log::{debug, error, info};
+use crate::core::{BotError, Result};
+use tracing::{debug, error, info, instrument};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone)]
@@ -118,6 +119,7 @@ impl ImageGenerator {
     }
 
     /// Generate an image using DALL-E 3
+    #[instrument(name = "openai.image", skip_all, fields(size = size.as_str(), style = style.as_str()))]
     pub async fn generate_image(
         &self,
         prompt: &str,
@@ -150,7 +152,7 @@ impl ImageGenerator {
 
         if status.is_success() {
             let dalle_response: DalleResponse = serde_json::from_str(&response_text)
-                .map_err(|e| anyhow::anyhow!("Failed to parse DALL-E response: {}", e))?;
+                .map_err(|e| BotError::openai(format!("Failed to parse DALL-E response: {}", e)))?;
 
             if let Some(image_data) = dalle_response.data.first() {
                 if let Some(url) = &image_data.url {
@@ -163,17 +165,17 @@ impl ImageGenerator {
             }
 
             error!("No image data in response: {response_text}");
-            Err(anyhow::anyhow!("No image data in DALL-E response"))
+            Err(BotError::openai("No image data in DALL-E response"))
         } else {
             // Try to parse error response
             if let Ok(error_response) = serde_json::from_str::<DalleError>(&response_text) {
                 error!("DALL-E API error: {} (type: {:?})",
                        error_response.error.message,
                        error_response.error.error_type);
-                Err(anyhow::anyhow!("DALL-E error: {}", error_response.error.message))
+                Err(BotError::openai(format!("DALL-E error: {}", error_response.error.message)))
             } else {
                 error!("DALL-E API error (status {status}): {response_text}");
-                Err(anyhow::anyhow!("DALL-E API error (status {})", status))
+                Err(BotError::openai(format!("DALL-E API error (status {})", status)))
             }
         }
     }
@@ -192,7 +194,7 @@ impl ImageGenerator {
             Ok(bytes.to_vec())
         } else {
             error!("Failed to download image: {}", response.status());
-            Err(anyhow::anyhow!("Failed to download image: {}", response.status()))
+            Err(BotError::internal(format!("Failed to download image: {}", response.status())))
         }
     }
 }
@@ -0,0 +1,19 @@
+//! # Interactions Endpoint Feature
+//!
+//! Lets a bot take slash commands, context menus and component clicks from
+//! Discord over HTTP instead of holding a gateway connection. Discord posts
+//! each interaction to `POST /interactions` on `INTERACTIONS_LISTEN_ADDR`
+//! (set that URL as the Interactions Endpoint URL in the Developer Portal);
+//! requests are checked against `DISCORD_PUBLIC_KEY` and handed to the same
+//! handlers the gateway uses. Messages, reactions and member events are gateway
+//! only, so features built on them don't run in this mode.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod server;
+pub mod verify;
+
+pub use server::{http_context, serve_interactions};
+pub use verify::{decode_hex, verify_signature};
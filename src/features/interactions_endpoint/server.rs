@@ -0,0 +1,520 @@
+//! # Feature: Interactions Endpoint Server
+//!
+//! HTTP listener for `POST /interactions`. Requests without a valid Discord
+//! signature get 401, pings are answered with a pong, and anything else is
+//! parsed as an interaction and passed to the event handler as if it came from
+//! the gateway. Handlers answer through the interaction callback endpoint as
+//! usual, and the request gets a 202 once they have. A handler that hasn't
+//! answered in time gets a deferred response (type 5, or 6 for component
+//! clicks) as the request's 200 body instead, and its answer is sent later as
+//! a follow-up or an edit of the deferred message.
+//!
+//! To see those answers, handlers get an [`Http`] whose requests go through a
+//! loopback proxy: callbacks for pending interactions are tracked or turned
+//! into follow-ups, and everything else is passed on to Discord unchanged.
+//! Serenity only uses a proxy with its rate limiter off, so the proxy keeps
+//! Discord's rate limits itself: one bucket per route, as serenity does, read
+//! from the `X-RateLimit-*` headers, plus the global limit after a global 429.
+//!
+//! - **Version**: 1.2.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.2.0: Per-route and global rate limits for handler requests going through the proxy
+//! - 1.1.0: Slow handlers get a deferred 200 response and their answer is sent as a follow-up
+//! - 1.0.0: Initial release
+
+use super::verify::verify_signature;
+use crate::core::{BotError, Result};
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::header::{CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, HOST, RETRY_AFTER, TRANSFER_ENCODING};
+use axum::http::{HeaderMap, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use dashmap::DashMap;
+use futures::channel::mpsc;
+use log::{info, warn};
+use serde_json::{json, Value};
+use serenity::client::bridge::gateway::ShardMessenger;
+use serenity::http::{Http, HttpBuilder};
+use serenity::model::application::interaction::Interaction;
+use serenity::prelude::*;
+use std::future::IntoFuture;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{oneshot, Mutex as AsyncMutex};
+use tokio::time::Instant;
+
+/// How long a request waits for its handler to answer before it is deferred;
+/// Discord gives up on the request after three seconds
+const HANDLER_WAIT: Duration = Duration::from_millis(2500);
+
+/// Where proxied requests are sent
+const DISCORD: &str = "https://discord.com";
+
+/// Longest `Retry-After` a proxied request waits out before giving up
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(10);
+
+/// Interaction types Discord sends
+const PING: u64 = 1;
+const MESSAGE_COMPONENT: u64 = 3;
+const AUTOCOMPLETE: u64 = 4;
+
+/// Interaction response types
+const CHANNEL_MESSAGE: u64 = 4;
+const DEFERRED_CHANNEL_MESSAGE: u64 = 5;
+const DEFERRED_UPDATE_MESSAGE: u64 = 6;
+const UPDATE_MESSAGE: u64 = 7;
+const AUTOCOMPLETE_RESULT: u64 = 8;
+
+/// Message flag for replies only the user sees
+const EPHEMERAL: u64 = 1 << 6;
+
+/// Where an interaction's initial response stands
+enum Reply {
+    /// The handler hasn't answered; the sender tells the waiting request when it does
+    Waiting(oneshot::Sender<()>),
+    /// The handler answered through the callback endpoint
+    Answered,
+    /// The request was answered with this deferred response type
+    Deferred(u64),
+}
+
+#[derive(Clone)]
+struct EndpointState {
+    public_key: Arc<str>,
+    handler: Arc<dyn EventHandler>,
+    /// Context handed to handlers; its requests go through the proxy
+    ctx: Context,
+    /// Client for follow-ups, talking to Discord directly
+    http: Arc<Http>,
+    /// Initial responses of interactions whose handlers are still running, by interaction ID
+    replies: Arc<DashMap<String, Reply>>,
+    forward: reqwest::Client,
+    limits: RateLimits,
+}
+
+/// A route's rate limit as Discord last reported it
+#[derive(Debug, Default)]
+struct Bucket {
+    remaining: Option<u64>,
+    reset: Option<Instant>,
+}
+
+impl Bucket {
+    /// How long the next request must wait, when the bucket is used up
+    fn wait(&self, now: Instant) -> Option<Duration> {
+        match (self.remaining, self.reset) {
+            (Some(0), Some(reset)) if reset > now => Some(reset - now),
+            _ => None,
+        }
+    }
+
+    /// Take in the `X-RateLimit-Remaining` and `X-RateLimit-Reset-After` headers of a response
+    fn update(&mut self, headers: &HeaderMap, now: Instant) {
+        if let Ok(remaining) = header(headers, "x-ratelimit-remaining").parse() {
+            self.remaining = Some(remaining);
+        }
+        if let Some(after) = seconds(header(headers, "x-ratelimit-reset-after")) {
+            self.reset = Some(now + after);
+        }
+    }
+}
+
+/// Discord's rate limits for requests going through the proxy
+#[derive(Clone, Default)]
+struct RateLimits {
+    /// By [`route_key`]; a route's requests take turns so each sees the last one's headers
+    routes: Arc<DashMap<String, Arc<AsyncMutex<Bucket>>>>,
+    /// No requests until then, after a global 429
+    global: Arc<std::sync::Mutex<Option<Instant>>>,
+}
+
+impl RateLimits {
+    fn bucket(&self, route: String) -> Arc<AsyncMutex<Bucket>> {
+        self.routes.entry(route).or_default().clone()
+    }
+
+    fn global_wait(&self, now: Instant) -> Option<Duration> {
+        let until = (*self.global.lock().ok()?)?;
+        (until > now).then(|| until - now)
+    }
+
+    fn hold_global(&self, until: Instant) {
+        if let Ok(mut global) = self.global.lock() {
+            *global = Some(global.map_or(until, |held| held.max(until)));
+        }
+    }
+}
+
+/// The rate limit route of a request: its method and path with IDs replaced,
+/// except the channel, guild and webhook IDs Discord limits separately
+fn route_key(method: &Method, path: &str) -> String {
+    let mut key = method.as_str().to_string();
+    let mut previous = "";
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        let major = matches!(previous, "channels" | "guilds" | "webhooks");
+        let id = segment.bytes().all(|b| b.is_ascii_digit());
+        key.push('/');
+        key.push_str(if id && !major { ":id" } else { segment });
+        previous = segment;
+    }
+    key
+}
+
+/// A non-negative number of seconds, as in `Retry-After` and `X-RateLimit-Reset-After`
+fn seconds(value: &str) -> Option<Duration> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+/// A [`Context`] for handlers run without a gateway connection. Its shard
+/// messenger isn't connected, so presence updates and the like are dropped.
+pub fn http_context(http: Arc<Http>) -> Context {
+    let (shard_tx, _) = mpsc::unbounded();
+    Context {
+        data: Arc::new(RwLock::new(TypeMap::new())),
+        shard: ShardMessenger::new(shard_tx),
+        shard_id: 0,
+        http,
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> &'a str {
+    headers.get(name).and_then(|h| h.to_str().ok()).unwrap_or_default()
+}
+
+/// The response a request gets when its handler is slow: a deferral, or no
+/// choices for autocomplete, which can't be deferred
+fn deferred_response(interaction_type: u64) -> Value {
+    match interaction_type {
+        MESSAGE_COMPONENT => json!({ "type": DEFERRED_UPDATE_MESSAGE }),
+        AUTOCOMPLETE => json!({ "type": AUTOCOMPLETE_RESULT, "data": { "choices": [] } }),
+        _ => json!({ "type": DEFERRED_CHANNEL_MESSAGE }),
+    }
+}
+
+async fn interactions(State(state): State<EndpointState>, headers: HeaderMap, body: Bytes) -> Response {
+    let signature = header(&headers, "x-signature-ed25519");
+    let timestamp = header(&headers, "x-signature-timestamp");
+    if !verify_signature(&state.public_key, signature, timestamp, &body) {
+        return (StatusCode::UNAUTHORIZED, "invalid request signature").into_response();
+    }
+
+    let payload: Value = match serde_json::from_slice(&body) {
+        Ok(payload) => payload,
+        Err(_) => return (StatusCode::BAD_REQUEST, "body must be JSON").into_response(),
+    };
+    let interaction_type = payload.get("type").and_then(Value::as_u64).unwrap_or_default();
+    if interaction_type == PING {
+        return Json(json!({ "type": PING })).into_response();
+    }
+    let id = payload.get("id").and_then(Value::as_str).unwrap_or_default().to_string();
+    let interaction: Interaction = match serde_json::from_value(payload) {
+        Ok(interaction) => interaction,
+        Err(e) => {
+            warn!("Failed to parse interaction request: {e}");
+            return (StatusCode::BAD_REQUEST, "unsupported interaction").into_response();
+        }
+    };
+
+    let (answered_tx, answered_rx) = oneshot::channel();
+    state.replies.insert(id.clone(), Reply::Waiting(answered_tx));
+    let (handler, ctx, replies, task_id) = (state.handler.clone(), state.ctx.clone(), state.replies.clone(), id.clone());
+    // Slow handlers keep running after the request is answered
+    tokio::spawn(async move {
+        handler.interaction_create(ctx, interaction).await;
+        replies.remove(&task_id);
+    });
+    // Ends when the handler answers, finishes without answering, or runs out of time
+    let _ = tokio::time::timeout(HANDLER_WAIT, answered_rx).await;
+
+    let deferred = deferred_response(interaction_type);
+    match state.replies.get_mut(&id) {
+        Some(mut reply) if matches!(*reply, Reply::Waiting(_)) => {
+            *reply = Reply::Deferred(deferred["type"].as_u64().unwrap_or_default());
+            info!("Deferred interaction {id}; its handler is still running");
+            Json(deferred).into_response()
+        }
+        _ => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+/// Interaction ID and token from a callback path (`/api/v10/interactions/<id>/<token>/callback`)
+fn callback_route(path: &str) -> Option<(&str, &str)> {
+    let rest = &path[path.find("/interactions/")? + "/interactions/".len()..];
+    let mut parts = rest.split('/');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(id), Some(token), Some("callback"), None) => Some((id, token)),
+        _ => None,
+    }
+}
+
+/// Requests made by handlers: callbacks for interactions still being handled
+/// are tracked, everything else goes to Discord as is
+async fn proxy(State(state): State<EndpointState>, method: Method, uri: Uri, headers: HeaderMap, body: Bytes) -> Response {
+    if let Some((id, token)) = callback_route(uri.path()) {
+        let previous = state
+            .replies
+            .get_mut(id)
+            .map(|mut reply| std::mem::replace(&mut *reply, Reply::Answered));
+        match previous {
+            Some(Reply::Waiting(answered)) => {
+                let response = forward(&state.forward, &state.limits, method, &uri, headers, body).await;
+                if response.status().is_success() {
+                    let _ = answered.send(());
+                } else if let Some(mut reply) = state.replies.get_mut(id) {
+                    // Let a retry, or the deferral, take its place
+                    *reply = Reply::Waiting(answered);
+                }
+                return response;
+            }
+            Some(Reply::Deferred(deferred)) => {
+                if let Some(mut reply) = state.replies.get_mut(id) {
+                    *reply = Reply::Deferred(deferred);
+                }
+                return follow_up(&state.http, deferred, token, &headers, &body).await;
+            }
+            // Not ours, or answered twice: Discord has the final word
+            Some(Reply::Answered) | None => {}
+        }
+    }
+    forward(&state.forward, &state.limits, method, &uri, headers, body).await
+}
+
+/// How an answer that arrives after the request was deferred is delivered
+#[derive(Debug, PartialEq, Eq)]
+enum LateReply {
+    /// Edit the deferred message, or the clicked message after a deferred update
+    EditOriginal,
+    /// Post a new message
+    FollowUp,
+    /// Post an ephemeral follow-up and remove the public "thinking" message
+    ReplaceOriginal,
+    /// Another deferral, or autocomplete choices that were already answered
+    Nothing,
+    /// Modals can only be the first response
+    Unsupported,
+}
+
+fn late_reply(deferred: u64, callback: &Value) -> LateReply {
+    let ephemeral = callback["data"]["flags"].as_u64().unwrap_or_default() & EPHEMERAL != 0;
+    match (deferred, callback["type"].as_u64().unwrap_or_default()) {
+        (DEFERRED_UPDATE_MESSAGE, CHANNEL_MESSAGE) => LateReply::FollowUp,
+        (_, CHANNEL_MESSAGE) if ephemeral => LateReply::ReplaceOriginal,
+        (_, CHANNEL_MESSAGE | UPDATE_MESSAGE) => LateReply::EditOriginal,
+        (_, DEFERRED_CHANNEL_MESSAGE | DEFERRED_UPDATE_MESSAGE | AUTOCOMPLETE_RESULT) => LateReply::Nothing,
+        _ => LateReply::Unsupported,
+    }
+}
+
+/// Deliver a handler's callback after the request was deferred, answering the
+/// handler as the callback endpoint would
+async fn follow_up(http: &Http, deferred: u64, token: &str, headers: &HeaderMap, body: &[u8]) -> Response {
+    let callback = match serde_json::from_slice::<Value>(body) {
+        Ok(callback) if header(headers, CONTENT_TYPE.as_str()).starts_with("application/json") => callback,
+        _ => return discord_error(StatusCode::BAD_REQUEST, "only JSON answers can be sent after the interaction was deferred"),
+    };
+    let data = callback.get("data").cloned().unwrap_or_else(|| json!({}));
+    let result = match late_reply(deferred, &callback) {
+        LateReply::EditOriginal => http.edit_original_interaction_response(token, &data).await.map(drop),
+        LateReply::FollowUp => http.create_followup_message(token, &data).await.map(drop),
+        LateReply::ReplaceOriginal => match http.create_followup_message(token, &data).await {
+            Ok(_) => http.delete_original_interaction_response(token).await,
+            Err(e) => Err(e),
+        },
+        LateReply::Nothing => Ok(()),
+        LateReply::Unsupported => {
+            return discord_error(StatusCode::BAD_REQUEST, "this response type can't be sent after the interaction was deferred")
+        }
+    };
+    match result {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => {
+            warn!("Failed to send a deferred interaction's answer: {e}");
+            discord_error(StatusCode::BAD_REQUEST, &e.to_string())
+        }
+    }
+}
+
+/// An error shaped like Discord's, so the handler sees a failed request
+fn discord_error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "code": 0, "message": message }))).into_response()
+}
+
+/// Send a handler's request on to Discord once its route's bucket and the
+/// global limit allow it, waiting out 429s, and return Discord's response
+async fn forward(client: &reqwest::Client, limits: &RateLimits, method: Method, uri: &Uri, mut headers: HeaderMap, body: Bytes) -> Response {
+    headers.remove(HOST);
+    headers.remove(CONTENT_LENGTH);
+    let url = format!("{DISCORD}{}", uri.path_and_query().map_or("/", |p| p.as_str()));
+    let bucket = limits.bucket(route_key(&method, uri.path()));
+    let mut bucket = bucket.lock().await;
+    loop {
+        let now = Instant::now();
+        if let Some(wait) = limits.global_wait(now).max(bucket.wait(now)) {
+            tokio::time::sleep(wait).await;
+        }
+        let response = match client.request(method.clone(), &url).headers(headers.clone()).body(body.clone()).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to pass a request on to Discord: {e}");
+                return discord_error(StatusCode::BAD_GATEWAY, &e.to_string());
+            }
+        };
+        let now = Instant::now();
+        bucket.update(response.headers(), now);
+        let retry_after = seconds(header(response.headers(), RETRY_AFTER.as_str()));
+        let limited = response.status() == StatusCode::TOO_MANY_REQUESTS;
+        if let Some(wait) = retry_after.filter(|_| limited && header(response.headers(), "x-ratelimit-global") == "true") {
+            limits.hold_global(now + wait);
+        }
+        match retry_after {
+            Some(wait) if limited && wait <= MAX_RETRY_WAIT => {
+                tokio::time::sleep(wait).await;
+            }
+            _ => {
+                let status = response.status();
+                let mut headers = response.headers().clone();
+                for name in [CONTENT_LENGTH, TRANSFER_ENCODING, CONNECTION] {
+                    headers.remove(name);
+                }
+                let body = response.bytes().await.unwrap_or_default();
+                return (status, headers, body).into_response();
+            }
+        }
+    }
+}
+
+/// Serve the interactions endpoint on `addr` (e.g. `0.0.0.0:8790`) until the process exits,
+/// accepting requests signed for `public_key` (hex). Handlers get a client for `token`
+/// going through the proxy; `http` sends follow-ups.
+pub async fn serve_interactions(
+    addr: &str,
+    public_key: &str,
+    token: &str,
+    http: Arc<Http>,
+    handler: Arc<dyn EventHandler>,
+) -> Result<()> {
+    let proxy_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_url = format!("http://{}/", proxy_listener.local_addr()?);
+    // Serenity only applies a proxy with its own rate limiter off; the proxy keeps the rate limits instead
+    let mut handler_http = HttpBuilder::new(token).proxy(proxy_url)?.ratelimiter_disabled(true);
+    if let Some(application_id) = http.application_id() {
+        handler_http = handler_http.application_id(application_id);
+    }
+    let forward = reqwest::Client::builder()
+        .build()
+        .map_err(|e| BotError::internal(format!("Failed to build the interactions proxy client: {e}")))?;
+
+    let state = EndpointState {
+        public_key: public_key.into(),
+        handler,
+        ctx: http_context(Arc::new(handler_http.build())),
+        http,
+        replies: Arc::new(DashMap::new()),
+        forward,
+        limits: RateLimits::default(),
+    };
+    let app = Router::new().route("/interactions", post(interactions)).with_state(state.clone());
+    let proxy_app = Router::new().fallback(proxy).with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Interactions endpoint listening on {}", addr);
+    tokio::select! {
+        result = axum::serve(listener, app).into_future() => result?,
+        result = axum::serve(proxy_listener, proxy_app).into_future() => result?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_route() {
+        assert_eq!(callback_route("/api/v10/interactions/123/abc.def/callback"), Some(("123", "abc.def")));
+        assert_eq!(callback_route("/api/v10/webhooks/1/abc/messages/@original"), None);
+        assert_eq!(callback_route("/api/v10/interactions/123/abc/callback/extra"), None);
+    }
+
+    #[test]
+    fn test_route_key() {
+        let get = |path| route_key(&Method::GET, path);
+        assert_eq!(get("/api/v10/channels/11/messages/22"), "GET/api/v10/channels/11/messages/:id");
+        assert_eq!(get("/api/v10/channels/11/messages/33"), get("/api/v10/channels/11/messages/22"));
+        assert_ne!(get("/api/v10/channels/12/messages/22"), get("/api/v10/channels/11/messages/22"));
+        assert_eq!(get("/api/v10/guilds/5/members/6"), "GET/api/v10/guilds/5/members/:id");
+        assert_eq!(route_key(&Method::PATCH, "/api/v10/webhooks/7/tok/messages/@original"), "PATCH/api/v10/webhooks/7/tok/messages/@original");
+        assert_ne!(route_key(&Method::POST, "/api/v10/channels/11/messages"), get("/api/v10/channels/11/messages"));
+    }
+
+    #[test]
+    fn test_bucket_waits_when_used_up() {
+        let now = Instant::now();
+        let mut bucket = Bucket::default();
+        assert_eq!(bucket.wait(now), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", "1".parse().unwrap());
+        headers.insert("x-ratelimit-reset-after", "2.5".parse().unwrap());
+        bucket.update(&headers, now);
+        assert_eq!(bucket.wait(now), None);
+
+        headers.insert("x-ratelimit-remaining", "0".parse().unwrap());
+        bucket.update(&headers, now);
+        assert_eq!(bucket.wait(now), Some(Duration::from_millis(2500)));
+        assert_eq!(bucket.wait(now + Duration::from_secs(3)), None);
+
+        // Responses without limit headers leave the bucket as it was
+        bucket.update(&HeaderMap::new(), now);
+        assert_eq!(bucket.remaining, Some(0));
+    }
+
+    #[test]
+    fn test_global_limit() {
+        let limits = RateLimits::default();
+        let now = Instant::now();
+        assert_eq!(limits.global_wait(now), None);
+        limits.hold_global(now + Duration::from_secs(2));
+        limits.hold_global(now + Duration::from_secs(1));
+        assert_eq!(limits.global_wait(now), Some(Duration::from_secs(2)));
+        assert_eq!(limits.global_wait(now + Duration::from_secs(2)), None);
+    }
+
+    #[test]
+    fn test_seconds() {
+        assert_eq!(seconds("0.25"), Some(Duration::from_millis(250)));
+        assert_eq!(seconds("-1"), None);
+        assert_eq!(seconds(""), None);
+    }
+
+    #[test]
+    fn test_deferred_response() {
+        assert_eq!(deferred_response(2)["type"], DEFERRED_CHANNEL_MESSAGE);
+        assert_eq!(deferred_response(5)["type"], DEFERRED_CHANNEL_MESSAGE);
+        assert_eq!(deferred_response(MESSAGE_COMPONENT)["type"], DEFERRED_UPDATE_MESSAGE);
+        assert_eq!(deferred_response(AUTOCOMPLETE)["data"]["choices"], json!([]));
+    }
+
+    #[test]
+    fn test_late_reply() {
+        let message = json!({ "type": CHANNEL_MESSAGE, "data": { "content": "done" } });
+        let ephemeral = json!({ "type": CHANNEL_MESSAGE, "data": { "content": "done", "flags": EPHEMERAL } });
+        let update = json!({ "type": UPDATE_MESSAGE, "data": { "content": "done" } });
+        assert_eq!(late_reply(DEFERRED_CHANNEL_MESSAGE, &message), LateReply::EditOriginal);
+        assert_eq!(late_reply(DEFERRED_CHANNEL_MESSAGE, &ephemeral), LateReply::ReplaceOriginal);
+        assert_eq!(late_reply(DEFERRED_UPDATE_MESSAGE, &message), LateReply::FollowUp);
+        assert_eq!(late_reply(DEFERRED_UPDATE_MESSAGE, &ephemeral), LateReply::FollowUp);
+        assert_eq!(late_reply(DEFERRED_UPDATE_MESSAGE, &update), LateReply::EditOriginal);
+        assert_eq!(late_reply(DEFERRED_CHANNEL_MESSAGE, &json!({ "type": DEFERRED_CHANNEL_MESSAGE })), LateReply::Nothing);
+        assert_eq!(late_reply(DEFERRED_CHANNEL_MESSAGE, &json!({ "type": 9, "data": {} })), LateReply::Unsupported);
+    }
+}
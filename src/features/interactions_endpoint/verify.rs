@@ -0,0 +1,74 @@
+//! # Feature: Interaction Signature Verification
+//!
+//! Discord signs every interaction request with the application's Ed25519
+//! key: `X-Signature-Ed25519` is the signature of `X-Signature-Timestamp`
+//! followed by the raw body, and the public key is shown in the Developer
+//! Portal. Requests that fail the check must be answered with 401.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use ring::signature::{UnparsedPublicKey, ED25519};
+
+/// Bytes from a hex string, or `None` if it isn't valid hex
+pub fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.trim();
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect()
+}
+
+/// Whether `signature_hex` is a valid signature of `timestamp` + `body` by `public_key_hex`
+pub fn verify_signature(public_key_hex: &str, signature_hex: &str, timestamp: &str, body: &[u8]) -> bool {
+    let (Some(public_key), Some(signature)) = (decode_hex(public_key_hex), decode_hex(signature_hex)) else {
+        return false;
+    };
+    if public_key.len() != 32 || signature.len() != 64 {
+        return false;
+    }
+    let mut message = Vec::with_capacity(timestamp.len() + body.len());
+    message.extend_from_slice(timestamp.as_bytes());
+    message.extend_from_slice(body);
+    UnparsedPublicKey::new(&ED25519, public_key).verify(&message, &signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("00ff7A"), Some(vec![0x00, 0xff, 0x7a]));
+        assert_eq!(decode_hex(""), Some(vec![]));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+        assert_eq!(decode_hex("é0"), None);
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let key = Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap();
+        let public_key = hex(key.public_key().as_ref());
+        let body = br#"{"type":1}"#;
+        let signature = hex(key.sign(&[b"1700000000".as_slice(), body].concat()).as_ref());
+
+        assert!(verify_signature(&public_key, &signature, "1700000000", body));
+        assert!(!verify_signature(&public_key, &signature, "1700000001", body));
+        assert!(!verify_signature(&public_key, &signature, "1700000000", br#"{"type":2}"#));
+        assert!(!verify_signature(&public_key, "not hex", "1700000000", body));
+        assert!(!verify_signature(&signature, &signature, "1700000000", body));
+    }
+}
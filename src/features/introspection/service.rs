@@ -35,7 +35,7 @@ pub struct CommandHandler {
 
 // I start up like this:
 async fn main() {
-    let database = Database::new(&config.database_path).await?;
+    let database = Database::new(&config.database_path, config.database_pool_size).await?;
     let persona_manager = PersonaManager::new();
     let command_handler = CommandHandler::new(database, ...);
 
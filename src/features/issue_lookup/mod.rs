@@ -0,0 +1,13 @@
+//! # Issue Lookup Feature
+//!
+//! `/issue KEY-123` and optional inline linking of issue keys, backed by Jira
+//! Cloud (`JIRA_BASE_URL`, `JIRA_EMAIL`, `JIRA_API_TOKEN`) and/or Linear
+//! (`LINEAR_API_KEY`). Lookups are cached for a few minutes.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod tracker;
+
+pub use tracker::{build_issue_embed, extract_issue_keys, normalize_issue_key, Issue, IssueTracker, MAX_INLINE_ISSUES};
@@ -0,0 +1,261 @@
+//! # Feature: Issue Tracker Lookup
+//!
+//! Fetches an issue's title, status and assignee from Jira Cloud (REST v2 with
+//! basic auth) or Linear (GraphQL, which accepts `TEAM-123` identifiers). When
+//! both are configured Jira is asked first. Results, including "not found", are
+//! cached for 5 minutes so busy channels don't hammer the APIs.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with Jira and Linear lookups and a shared cache
+
+use crate::core::discord_limits::fit_embed;
+use crate::core::{BotError, Result};
+use dashmap::DashMap;
+use regex::Regex;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use serenity::builder::CreateEmbed;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+const CACHE_TTL: Duration = Duration::from_secs(300);
+const LINEAR_API: &str = "https://api.linear.app/graphql";
+
+/// Most issue keys linked from a single message
+pub const MAX_INLINE_ISSUES: usize = 3;
+
+/// An issue as shown in the lookup embed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub key: String,
+    pub title: String,
+    pub status: String,
+    pub assignee: Option<String>,
+    pub url: String,
+    /// "Jira" or "Linear"
+    pub tracker: &'static str,
+}
+
+fn issue_key_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b([A-Z][A-Z0-9]{1,9}-[1-9][0-9]{0,6})\b").unwrap())
+}
+
+/// Uppercase and validate a key like `eng-42`
+pub fn normalize_issue_key(input: &str) -> Option<String> {
+    let key = input.trim().to_uppercase();
+    let found = issue_key_regex().find(&key)?;
+    (found.as_str() == key).then_some(key)
+}
+
+/// Distinct issue keys mentioned in a message, skipping code blocks, inline code and links
+pub fn extract_issue_keys(content: &str) -> Vec<String> {
+    let mut text = String::new();
+    for (i, block) in content.split("```").enumerate() {
+        if i % 2 == 0 {
+            for (j, span) in block.split('`').enumerate() {
+                if j % 2 == 0 {
+                    text.push_str(span);
+                    text.push(' ');
+                }
+            }
+        }
+    }
+    let words: Vec<&str> = text.split_whitespace().filter(|w| !w.contains("://")).collect();
+
+    let mut keys: Vec<String> = Vec::new();
+    for word in words {
+        for found in issue_key_regex().find_iter(word) {
+            let key = found.as_str().to_string();
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    keys.truncate(MAX_INLINE_ISSUES);
+    keys
+}
+
+fn json_str(value: &Value, pointer: &str) -> Option<String> {
+    value.pointer(pointer).and_then(Value::as_str).map(str::to_string)
+}
+
+/// Compact embed for an issue
+pub fn build_issue_embed(issue: &Issue) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(format!("{}: {}", issue.key, issue.title))
+        .field("Status", &issue.status, true)
+        .field("Assignee", issue.assignee.as_deref().unwrap_or("Unassigned"), true)
+        .footer(|f| f.text(issue.tracker))
+        .color(match issue.tracker {
+            "Linear" => 0x5E6AD2,
+            _ => 0x0052CC,
+        });
+    if issue.url.starts_with("http") {
+        embed.url(&issue.url);
+    }
+    fit_embed(&mut embed);
+    embed
+}
+
+struct JiraConfig {
+    base_url: String,
+    email: String,
+    api_token: String,
+}
+
+#[derive(Clone)]
+pub struct IssueTracker {
+    client: reqwest::Client,
+    jira: Option<Arc<JiraConfig>>,
+    linear_api_key: Option<String>,
+    /// Issue key -> when it was looked up and the result
+    cache: Arc<DashMap<String, (Instant, Option<Issue>)>>,
+}
+
+impl IssueTracker {
+    /// None unless Jira (base URL, email and token) or Linear is configured
+    pub fn new(
+        jira_base_url: Option<String>,
+        jira_email: Option<String>,
+        jira_api_token: Option<String>,
+        linear_api_key: Option<String>,
+    ) -> Option<Self> {
+        let jira = match (jira_base_url, jira_email, jira_api_token) {
+            (Some(base_url), Some(email), Some(api_token)) => Some(Arc::new(JiraConfig {
+                base_url: base_url.trim_end_matches('/').to_string(),
+                email,
+                api_token,
+            })),
+            _ => None,
+        };
+        if jira.is_none() && linear_api_key.is_none() {
+            return None;
+        }
+        Some(IssueTracker {
+            client: reqwest::Client::new(),
+            jira,
+            linear_api_key,
+            cache: Arc::new(DashMap::new()),
+        })
+    }
+
+    /// Look up an issue by key, from the cache when fresh. Ok(None) if no tracker knows it.
+    pub async fn lookup(&self, key: &str) -> Result<Option<Issue>> {
+        if let Some(entry) = self.cache.get(key) {
+            if entry.0.elapsed() < CACHE_TTL {
+                return Ok(entry.1.clone());
+            }
+        }
+
+        let mut issue = None;
+        if let Some(jira) = &self.jira {
+            issue = self.lookup_jira(jira, key).await?;
+        }
+        if issue.is_none() {
+            if let Some(api_key) = &self.linear_api_key {
+                issue = self.lookup_linear(api_key, key).await?;
+            }
+        }
+
+        self.cache.retain(|_, (at, _)| at.elapsed() < CACHE_TTL);
+        self.cache.insert(key.to_string(), (Instant::now(), issue.clone()));
+        Ok(issue)
+    }
+
+    async fn lookup_jira(&self, jira: &JiraConfig, key: &str) -> Result<Option<Issue>> {
+        let response = self
+            .client
+            .get(format!("{}/rest/api/2/issue/{}", jira.base_url, key))
+            .basic_auth(&jira.email, Some(&jira.api_token))
+            .query(&[("fields", "summary,status,assignee")])
+            .send()
+            .await?;
+        // Jira answers 404 for unknown keys and for projects the token can't see
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let status = response.status();
+        if !status.is_success() {
+            return Err(BotError::internal(format!("Jira lookup failed (status {})", status)));
+        }
+        let body: Value = response.json().await?;
+        Ok(Some(Issue {
+            key: json_str(&body, "/key").unwrap_or_else(|| key.to_string()),
+            title: json_str(&body, "/fields/summary").unwrap_or_default(),
+            status: json_str(&body, "/fields/status/name").unwrap_or_else(|| "Unknown".to_string()),
+            assignee: json_str(&body, "/fields/assignee/displayName"),
+            url: format!("{}/browse/{}", jira.base_url, key),
+            tracker: "Jira",
+        }))
+    }
+
+    async fn lookup_linear(&self, api_key: &str, key: &str) -> Result<Option<Issue>> {
+        let query = json!({
+            "query": "query($id: String!) { issue(id: $id) { identifier title url state { name } assignee { name } } }",
+            "variables": { "id": key },
+        });
+        let response = self
+            .client
+            .post(LINEAR_API)
+            .header("Authorization", api_key)
+            .json(&query)
+            .send()
+            .await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        let Some(issue) = body.pointer("/data/issue").filter(|i| !i.is_null()) else {
+            // Unknown identifiers come back as a GraphQL "Entity not found" error
+            let not_found = body
+                .pointer("/errors/0/message")
+                .and_then(Value::as_str)
+                .is_some_and(|m| m.contains("not found"));
+            if not_found || status.is_success() {
+                return Ok(None);
+            }
+            return Err(BotError::internal(format!("Linear lookup failed (status {})", status)));
+        };
+        Ok(Some(Issue {
+            key: json_str(issue, "/identifier").unwrap_or_else(|| key.to_string()),
+            title: json_str(issue, "/title").unwrap_or_default(),
+            status: json_str(issue, "/state/name").unwrap_or_else(|| "Unknown".to_string()),
+            assignee: json_str(issue, "/assignee/name"),
+            url: json_str(issue, "/url").unwrap_or_default(),
+            tracker: "Linear",
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_issue_key() {
+        assert_eq!(normalize_issue_key(" eng-42 ").as_deref(), Some("ENG-42"));
+        assert_eq!(normalize_issue_key("PROJ2-1").as_deref(), Some("PROJ2-1"));
+        assert_eq!(normalize_issue_key("ENG-0"), None);
+        assert_eq!(normalize_issue_key("ENG42"), None);
+        assert_eq!(normalize_issue_key("see ENG-42"), None);
+    }
+
+    #[test]
+    fn test_extract_issue_keys() {
+        assert_eq!(extract_issue_keys("Fixed in ENG-42, see also OPS-7 and ENG-42."), vec!["ENG-42", "OPS-7"]);
+        assert!(extract_issue_keys("`ENG-1` and ```\nOPS-2\n``` and https://x.atlassian.net/browse/ENG-3").is_empty());
+        assert_eq!(extract_issue_keys("A-1 B-2 C-3 D-4 E-5").len(), 0);
+        assert_eq!(extract_issue_keys("AB-1 BC-2 CD-3 DE-4").len(), MAX_INLINE_ISSUES);
+    }
+
+    #[test]
+    fn test_tracker_requires_configuration() {
+        assert!(IssueTracker::new(None, None, None, None).is_none());
+        assert!(IssueTracker::new(Some("https://x.atlassian.net".into()), None, Some("t".into()), None).is_none());
+        assert!(IssueTracker::new(None, None, None, Some("lin_api_x".into())).is_some());
+    }
+}
@@ -0,0 +1,19 @@
+//! # Join Screening Feature
+//!
+//! Scores new members for alt/scam signals (account age, default avatar,
+//! username patterns) when the GUILD_MEMBERS intent is enabled. Depending on the
+//! `join_screening` guild setting, flagged joins are logged to the mod log or
+//! held in an unverified role until they pass a button captcha.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod scoring;
+pub mod verification;
+
+pub use scoring::{format_screening_log, score_join, JoinScore, JoinSignals, ScreeningMode, DEFAULT_SCREENING_THRESHOLD};
+pub use verification::{
+    format_captcha_prompt, new_captcha, parse_verify_custom_id, screen_new_member, CAPTCHA_CHOICES,
+    JOIN_VERIFY_PREFIX, MAX_VERIFICATION_ATTEMPTS,
+};
@@ -0,0 +1,156 @@
+//! # Feature: Join Scoring
+//!
+//! Heuristic suspicion score for a new member based on account age, a default
+//! avatar, and username patterns common to alt and scam accounts. Scores run
+//! from 0 to 100; joins at or above the guild's threshold are acted on.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with account age, avatar and username signals
+
+/// Score at which a join is flagged when `join_screening_threshold` is unset
+pub const DEFAULT_SCREENING_THRESHOLD: u32 = 50;
+
+/// Words scam and impersonation accounts put in their names
+const SUSPICIOUS_KEYWORDS: &[&str] = &[
+    "nitro", "gift", "airdrop", "giveaway", "support", "admin", "moderator", "official", "steam",
+];
+
+/// What the guild does with flagged joins (`join_screening` setting)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreeningMode {
+    Disabled,
+    /// Post flagged joins to the mod log
+    Log,
+    /// Also hold them in `unverified_role` until they pass a button captcha
+    Verify,
+}
+
+impl ScreeningMode {
+    pub fn from_setting(value: Option<&str>) -> Self {
+        match value {
+            Some("log") => ScreeningMode::Log,
+            Some("verify") => ScreeningMode::Verify,
+            _ => ScreeningMode::Disabled,
+        }
+    }
+}
+
+/// What's known about a new member
+#[derive(Debug, Clone)]
+pub struct JoinSignals<'a> {
+    pub account_age_days: i64,
+    pub default_avatar: bool,
+    pub username: &'a str,
+}
+
+/// Suspicion score with the reasons that contributed to it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JoinScore {
+    pub score: u32,
+    pub reasons: Vec<String>,
+}
+
+impl JoinScore {
+    fn add(&mut self, points: u32, reason: &str) {
+        self.score += points;
+        self.reasons.push(format!("{reason} (+{points})"));
+    }
+}
+
+/// Score a join from 0 (ordinary) to 100 (very likely an alt or scam account)
+pub fn score_join(signals: &JoinSignals) -> JoinScore {
+    let mut score = JoinScore::default();
+
+    match signals.account_age_days {
+        days if days < 1 => score.add(40, "account created less than a day ago"),
+        days if days < 7 => score.add(25, "account younger than a week"),
+        days if days < 30 => score.add(10, "account younger than a month"),
+        _ => {}
+    }
+    if signals.default_avatar {
+        score.add(20, "default avatar");
+    }
+
+    let name = signals.username.to_lowercase();
+    if let Some(keyword) = SUSPICIOUS_KEYWORDS.iter().find(|k| name.contains(*k)) {
+        score.add(25, &format!("username contains \"{keyword}\""));
+    }
+    let trailing_digits = name.chars().rev().take_while(|c| c.is_ascii_digit()).count();
+    let alphanumeric = name.chars().filter(|c| c.is_ascii_alphanumeric()).count();
+    let digits = name.chars().filter(|c| c.is_ascii_digit()).count();
+    if trailing_digits >= 4 {
+        score.add(15, "username ends in a long number");
+    } else if alphanumeric > 0 && digits * 2 >= alphanumeric {
+        score.add(10, "username is mostly digits");
+    }
+    if longest_consonant_run(&name) >= 5 {
+        score.add(10, "username looks randomly generated");
+    }
+
+    score.score = score.score.min(100);
+    score
+}
+
+fn longest_consonant_run(name: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in name.chars() {
+        if c.is_ascii_alphabetic() && !"aeiouy".contains(c) {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    longest
+}
+
+/// Mod log entry for a flagged join
+pub fn format_screening_log(user_id: &str, score: &JoinScore, outcome: &str) -> String {
+    format!(
+        "🕵️ <@{user_id}> joined with suspicion score **{}/100** — {outcome}\n{}",
+        score.score,
+        score.reasons.iter().map(|r| format!("• {r}")).collect::<Vec<_>>().join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_established_account_scores_zero() {
+        let score = score_join(&JoinSignals { account_age_days: 900, default_avatar: false, username: "maria" });
+        assert_eq!(score, JoinScore::default());
+    }
+
+    #[test]
+    fn test_fresh_scam_account_is_capped() {
+        let score = score_join(&JoinSignals { account_age_days: 0, default_avatar: true, username: "FreeNitro_Gift88231" });
+        assert_eq!(score.score, 100);
+        assert!(score.reasons.iter().any(|r| r.starts_with("username contains \"nitro\"")));
+        assert!(score.reasons.iter().any(|r| r.starts_with("username ends in a long number")));
+    }
+
+    #[test]
+    fn test_username_patterns() {
+        let digits = score_join(&JoinSignals { account_age_days: 400, default_avatar: false, username: "a1b2c3" });
+        assert_eq!(digits.reasons, vec!["username is mostly digits (+10)"]);
+        let random = score_join(&JoinSignals { account_age_days: 400, default_avatar: false, username: "xkrtvqa" });
+        assert_eq!(random.score, 10);
+    }
+
+    #[test]
+    fn test_mode_and_log() {
+        assert_eq!(ScreeningMode::from_setting(Some("verify")), ScreeningMode::Verify);
+        assert_eq!(ScreeningMode::from_setting(None), ScreeningMode::Disabled);
+        let score = score_join(&JoinSignals { account_age_days: 3, default_avatar: true, username: "sam" });
+        let log = format_screening_log("1", &score, "logged");
+        assert!(log.starts_with("🕵️ <@1> joined with suspicion score **45/100** — logged"));
+        assert!(log.contains("• default avatar (+20)"));
+    }
+}
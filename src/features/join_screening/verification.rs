@@ -0,0 +1,168 @@
+//! # Feature: Join Verification
+//!
+//! Screens members as they join (needs the GUILD_MEMBERS intent). Flagged joins
+//! are posted to the mod log; in `verify` mode they are also given the
+//! `unverified_role` and must press the named emoji button in the
+//! `verification_channel` before the role is removed.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with scored joins, mod log entries and button captcha
+
+use super::scoring::{format_screening_log, score_join, JoinSignals, ScreeningMode, DEFAULT_SCREENING_THRESHOLD};
+use crate::database::Database;
+use crate::features::moderation::post_mod_log;
+use crate::message_components::MessageComponentHandler;
+use crate::core::Result;
+use log::{debug, info, warn};
+use rand::seq::{IndexedRandom, SliceRandom};
+use serenity::http::Http;
+use serenity::model::guild::Member;
+use serenity::model::id::ChannelId;
+
+/// Captcha buttons: (emoji, name the member is asked for)
+pub const CAPTCHA_CHOICES: &[(&str, &str)] = &[
+    ("🍎", "apple"),
+    ("🚗", "car"),
+    ("🐶", "dog"),
+    ("🌙", "moon"),
+    ("⚽", "ball"),
+    ("🎸", "guitar"),
+    ("🌵", "cactus"),
+    ("🚀", "rocket"),
+];
+
+/// Buttons shown per captcha
+pub const CAPTCHA_OPTIONS: usize = 4;
+
+/// Wrong presses allowed before the check is marked failed
+pub const MAX_VERIFICATION_ATTEMPTS: i64 = 3;
+
+/// Captcha button: `joinverify:<screening_id>:<choice>`
+pub const JOIN_VERIFY_PREFIX: &str = "joinverify:";
+
+/// Parse a captcha button into (screening ID, chosen `CAPTCHA_CHOICES` index)
+pub fn parse_verify_custom_id(custom_id: &str) -> Option<(i64, usize)> {
+    let (id, choice) = custom_id.strip_prefix(JOIN_VERIFY_PREFIX)?.split_once(':')?;
+    let choice = choice.parse::<usize>().ok().filter(|c| *c < CAPTCHA_CHOICES.len())?;
+    Some((id.parse().ok()?, choice))
+}
+
+/// Pick the buttons (indexes into `CAPTCHA_CHOICES`, shuffled) and the correct one
+pub fn new_captcha() -> (Vec<usize>, usize) {
+    let mut rng = rand::rng();
+    let mut options: Vec<usize> = (0..CAPTCHA_CHOICES.len()).collect();
+    options.shuffle(&mut rng);
+    options.truncate(CAPTCHA_OPTIONS);
+    let answer = *options.choose(&mut rng).unwrap_or(&0);
+    (options, answer)
+}
+
+/// Message posted in the verification channel
+pub fn format_captcha_prompt(user_id: &str, answer: usize) -> String {
+    let (_, name) = CAPTCHA_CHOICES[answer.min(CAPTCHA_CHOICES.len() - 1)];
+    format!("👋 Welcome <@{user_id}>! To confirm you're human, press the **{name}** button below.")
+}
+
+/// Screen a new member and act according to the guild's `join_screening` mode
+pub async fn screen_new_member(http: &Http, db: &Database, member: &Member) -> Result<()> {
+    if member.user.bot {
+        return Ok(());
+    }
+    let guild_id = member.guild_id.to_string();
+    let mode = ScreeningMode::from_setting(db.get_guild_setting(&guild_id, "join_screening").await?.as_deref());
+    if mode == ScreeningMode::Disabled {
+        return Ok(());
+    }
+    let threshold = db
+        .get_guild_setting(&guild_id, "join_screening_threshold")
+        .await?
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_SCREENING_THRESHOLD);
+
+    let account_age_days = (chrono::Utc::now().timestamp() - member.user.created_at().unix_timestamp()) / 86400;
+    let score = score_join(&JoinSignals {
+        account_age_days,
+        default_avatar: member.user.avatar.is_none(),
+        username: &member.user.name,
+    });
+    let user_id = member.user.id.to_string();
+    debug!("Join screening for {user_id} in guild {guild_id}: score {}", score.score);
+    if score.score < threshold {
+        return Ok(());
+    }
+
+    let channel = setting_id(db, &guild_id, "verification_channel").await?;
+    let role = setting_id(db, &guild_id, "unverified_role").await?;
+    let reasons = score.reasons.join("; ");
+    let outcome = match (mode, channel, role) {
+        (ScreeningMode::Verify, Some(channel), Some(role)) => {
+            let (options, answer) = new_captcha();
+            let id = db
+                .record_join_screening(&guild_id, &user_id, score.score, &reasons, "pending", answer as i64)
+                .await?;
+            http.add_member_role(member.guild_id.0, member.user.id.0, role, Some("Suspicious join: verification required"))
+                .await?;
+            ChannelId(channel)
+                .send_message(http, |m| {
+                    m.content(format_captcha_prompt(&user_id, answer))
+                        .set_components(MessageComponentHandler::create_join_captcha_buttons(id, &options))
+                })
+                .await?;
+            format!("held for verification in <#{channel}>")
+        }
+        (ScreeningMode::Verify, _, _) => {
+            db.record_join_screening(&guild_id, &user_id, score.score, &reasons, "logged", -1).await?;
+            "logged only (set `verification_channel` and `unverified_role` to require verification)".to_string()
+        }
+        _ => {
+            db.record_join_screening(&guild_id, &user_id, score.score, &reasons, "logged", -1).await?;
+            "logged".to_string()
+        }
+    };
+
+    info!("🕵️ Flagged join of {user_id} in guild {guild_id} (score {}): {outcome}", score.score);
+    if let Err(e) = post_mod_log(http, db, &guild_id, &format_screening_log(&user_id, &score, &outcome)).await {
+        warn!("Failed to post join screening to mod log for guild {guild_id}: {e}");
+    }
+    Ok(())
+}
+
+async fn setting_id(db: &Database, guild_id: &str, key: &str) -> Result<Option<u64>> {
+    Ok(db.get_guild_setting(guild_id, key).await?.and_then(|v| v.parse::<u64>().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_verify_custom_id() {
+        assert_eq!(parse_verify_custom_id("joinverify:12:3"), Some((12, 3)));
+        assert_eq!(parse_verify_custom_id("joinverify:12:99"), None);
+        assert_eq!(parse_verify_custom_id("joinverify:x:1"), None);
+        assert_eq!(parse_verify_custom_id("appeal_open:1"), None);
+    }
+
+    #[test]
+    fn test_new_captcha() {
+        let (options, answer) = new_captcha();
+        assert_eq!(options.len(), CAPTCHA_OPTIONS);
+        assert!(options.contains(&answer));
+        let mut unique = options.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), CAPTCHA_OPTIONS);
+    }
+
+    #[test]
+    fn test_format_captcha_prompt() {
+        assert_eq!(
+            format_captcha_prompt("5", 2),
+            "👋 Welcome <@5>! To confirm you're human, press the **dog** button below."
+        );
+    }
+}
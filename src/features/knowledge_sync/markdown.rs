@@ -0,0 +1,164 @@
+//! # Feature: Markdown Knowledge Base Export
+//!
+//! Writes each knowledge base entry to `<KNOWLEDGE_BASE_DIR>/<guild_id>/<id>-<slug>.md`.
+//! When the folder is inside a git repository the new files are committed, and
+//! pushed too with `KNOWLEDGE_BASE_GIT_PUSH=true`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with one file per entry and git commit/push
+
+use crate::database::KnowledgeEntry;
+use crate::core::{BotError, Result};
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Longest slug used in file names
+const MAX_SLUG_LEN: usize = 60;
+
+/// Lowercase, dash-separated file name slug of a question
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.chars().count() >= MAX_SLUG_LEN {
+            break;
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "question".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// File name for an entry; the ID prefix keeps names unique and in answer order
+pub fn entry_file_name(entry: &KnowledgeEntry) -> String {
+    format!("{:05}-{}.md", entry.id, slugify(&entry.question))
+}
+
+/// Markdown document for an entry
+pub fn render_entry(entry: &KnowledgeEntry) -> String {
+    let title = entry.question.lines().next().unwrap_or_default().trim();
+    let mut doc = format!("# {title}\n\n");
+    if entry.question.trim() != title {
+        doc.push_str(&format!("> {}\n\n", entry.question.trim().replace('\n', "\n> ")));
+    }
+    match &entry.answer {
+        Some(answer) => doc.push_str(&format!("{}\n\n", answer.trim())),
+        None => doc.push_str("_The answer was given before answers were stored; see the Discord link below._\n\n"),
+    }
+    doc.push_str(&format!("---\nAnswered {} UTC in Discord: {}\n", entry.created_at, entry.answer_link));
+    doc
+}
+
+#[derive(Clone)]
+pub struct MarkdownExporter {
+    root: PathBuf,
+    push: bool,
+}
+
+impl MarkdownExporter {
+    pub fn new(root: impl Into<PathBuf>, push: bool) -> Self {
+        MarkdownExporter { root: root.into(), push }
+    }
+
+    /// Write entries into the guild's folder, then commit them if the folder is in a git repo
+    pub fn export(&self, guild_id: &str, entries: &[KnowledgeEntry]) -> Result<()> {
+        let dir = self.root.join(guild_id);
+        std::fs::create_dir_all(&dir)?;
+        for entry in entries {
+            std::fs::write(dir.join(entry_file_name(entry)), render_entry(entry))?;
+        }
+
+        if self.root.join(".git").exists() {
+            self.commit(guild_id, entries.len())?;
+        }
+        Ok(())
+    }
+
+    fn commit(&self, guild_id: &str, count: usize) -> Result<()> {
+        git(&self.root, &["add", guild_id])?;
+        let message = format!("Sync {count} knowledge base entr{} for guild {guild_id}", if count == 1 { "y" } else { "ies" });
+        git(&self.root, &["commit", "-m", &message])?;
+        info!("Committed {} knowledge base entries for guild {} to git", count, guild_id);
+
+        if self.push {
+            if let Err(e) = git(&self.root, &["push"]) {
+                // The commit stays local and goes out with the next successful push
+                warn!("Failed to push knowledge base repository: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(BotError::internal(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(question: &str, answer: Option<&str>) -> KnowledgeEntry {
+        KnowledgeEntry {
+            id: 42,
+            question: question.to_string(),
+            answer: answer.map(str::to_string),
+            answer_link: "https://discord.com/channels/1/2/3".to_string(),
+            created_at: "2026-03-01 12:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("How do I reset my password?"), "how-do-i-reset-my-password");
+        assert_eq!(slugify("  ¿¡!!  "), "question");
+        assert!(slugify(&"word ".repeat(50)).len() <= MAX_SLUG_LEN);
+    }
+
+    #[test]
+    fn test_entry_file_name() {
+        assert_eq!(entry_file_name(&entry("Where are the docs?", None)), "00042-where-are-the-docs.md");
+    }
+
+    #[test]
+    fn test_render_entry() {
+        let doc = render_entry(&entry("Where are the docs?", Some("See the wiki.")));
+        assert!(doc.starts_with("# Where are the docs?\n\nSee the wiki.\n"));
+        assert!(doc.contains("https://discord.com/channels/1/2/3"));
+
+        let doc = render_entry(&entry("Where are the docs?", None));
+        assert!(doc.contains("see the Discord link below"));
+    }
+
+    #[test]
+    fn test_export_writes_guild_folder() {
+        let root = std::env::temp_dir().join(format!("persona-kb-test-{}", std::process::id()));
+        let exporter = MarkdownExporter::new(&root, false);
+        exporter.export("123", &[entry("Where are the docs?", Some("See the wiki."))]).unwrap();
+
+        let written = std::fs::read_to_string(root.join("123").join("00042-where-are-the-docs.md")).unwrap();
+        assert!(written.contains("See the wiki."));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
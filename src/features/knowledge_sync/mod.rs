@@ -0,0 +1,17 @@
+//! # Knowledge Base Sync Feature
+//!
+//! One-way export of the guild knowledge base (support questions the bot has
+//! answered) to a Notion database or a git-backed markdown folder, chosen with
+//! the `knowledge_base_export` guild setting.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod markdown;
+pub mod notion;
+pub mod sync;
+
+pub use markdown::{entry_file_name, render_entry, slugify, MarkdownExporter};
+pub use notion::{entry_blocks, parse_notion_database_id, NotionClient};
+pub use sync::{knowledge_sync_loop, KnowledgeExporters};
@@ -0,0 +1,192 @@
+//! # Feature: Notion Knowledge Base Export
+//!
+//! Minimal Notion API client that adds each knowledge base entry as a page in a
+//! database, authenticated with an internal integration token (`NOTION_TOKEN`).
+//! The database must be shared with the integration.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release creating one page per entry with the answer as paragraphs
+
+use crate::database::KnowledgeEntry;
+use crate::core::{BotError, Result};
+use dashmap::DashMap;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const NOTION_API: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+
+/// Notion's limit on the text of one rich text object
+const MAX_TEXT_LEN: usize = 2000;
+
+/// Notion's limit on blocks per page creation request
+const MAX_BLOCKS: usize = 100;
+
+/// Database ID (32 hex digits, dashes optional) from a bare ID or a notion.so URL
+pub fn parse_notion_database_id(input: &str) -> Option<String> {
+    let path = input.trim().split(['?', '#']).next().unwrap_or_default();
+    let segment = path.trim_end_matches('/').rsplit('/').next().unwrap_or_default();
+    let compact: String = segment.chars().filter(|c| *c != '-').collect();
+    let id = compact.get(compact.len().checked_sub(32)?..)?;
+    id.chars().all(|c| c.is_ascii_hexdigit()).then(|| id.to_ascii_lowercase())
+}
+
+/// Split text into chunks of at most `MAX_TEXT_LEN` characters, preferring paragraph breaks
+fn chunk_text(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let chars: Vec<char> = paragraph.chars().collect();
+        chunks.extend(chars.chunks(MAX_TEXT_LEN).map(|c| c.iter().collect::<String>()));
+    }
+    chunks
+}
+
+fn paragraph(content: &str, link: Option<&str>) -> Value {
+    let mut text = json!({ "content": content });
+    if let Some(url) = link {
+        text["link"] = json!({ "url": url });
+    }
+    json!({
+        "object": "block",
+        "type": "paragraph",
+        "paragraph": { "rich_text": [{ "type": "text", "text": text }] }
+    })
+}
+
+/// Page body blocks for an entry: the full question if it's longer than the title,
+/// the answer, and a link back to Discord
+pub fn entry_blocks(entry: &KnowledgeEntry) -> Vec<Value> {
+    let mut blocks = Vec::new();
+    let question = entry.question.trim();
+    if question.chars().count() > MAX_TEXT_LEN || question.contains('\n') {
+        blocks.extend(chunk_text(question).iter().map(|c| paragraph(c, None)));
+    }
+    match &entry.answer {
+        Some(answer) => blocks.extend(chunk_text(answer).iter().map(|c| paragraph(c, None))),
+        None => blocks.push(paragraph("The answer was given before answers were stored; see the Discord link.", None)),
+    }
+    blocks.truncate(MAX_BLOCKS - 1);
+    blocks.push(paragraph(&format!("Answered {} UTC in Discord", entry.created_at), Some(&entry.answer_link)));
+    blocks
+}
+
+#[derive(Clone)]
+pub struct NotionClient {
+    token: String,
+    client: reqwest::Client,
+    /// Database ID -> name of its title property
+    title_properties: Arc<DashMap<String, String>>,
+}
+
+impl NotionClient {
+    pub fn new(token: String) -> Self {
+        NotionClient {
+            token,
+            client: reqwest::Client::new(),
+            title_properties: Arc::new(DashMap::new()),
+        }
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Value> {
+        let response = request
+            .bearer_auth(&self.token)
+            .header("Notion-Version", NOTION_VERSION)
+            .send()
+            .await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if status.is_success() {
+            Ok(body)
+        } else {
+            let message = body.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+            Err(BotError::internal(format!("Notion request failed (status {}): {}", status, message)))
+        }
+    }
+
+    /// Name of the database's title property (every Notion database has exactly one)
+    async fn title_property(&self, database_id: &str) -> Result<String> {
+        if let Some(name) = self.title_properties.get(database_id) {
+            return Ok(name.clone());
+        }
+        let body = self
+            .send(self.client.get(format!("{NOTION_API}/databases/{database_id}")))
+            .await?;
+        let name = body
+            .get("properties")
+            .and_then(Value::as_object)
+            .and_then(|props| {
+                props
+                    .iter()
+                    .find(|(_, prop)| prop.get("type").and_then(Value::as_str) == Some("title"))
+                    .map(|(name, _)| name.clone())
+            })
+            .ok_or_else(|| BotError::internal(format!("Notion database {} has no title property", database_id)))?;
+        self.title_properties.insert(database_id.to_string(), name.clone());
+        Ok(name)
+    }
+
+    /// Add an entry as a new page in the database
+    pub async fn create_entry_page(&self, database_id: &str, entry: &KnowledgeEntry) -> Result<()> {
+        let title_property = self.title_property(database_id).await?;
+        let title: String = entry
+            .question
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .chars()
+            .take(MAX_TEXT_LEN)
+            .collect();
+        let mut properties = serde_json::Map::new();
+        properties.insert(title_property, json!({ "title": [{ "type": "text", "text": { "content": title } }] }));
+
+        let page = json!({
+            "parent": { "database_id": database_id },
+            "properties": properties,
+            "children": entry_blocks(entry),
+        });
+        self.send(self.client.post(format!("{NOTION_API}/pages")).json(&page)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notion_database_id() {
+        let id = "0123456789abcdef0123456789abcdef";
+        assert_eq!(parse_notion_database_id(id).as_deref(), Some(id));
+        assert_eq!(
+            parse_notion_database_id("01234567-89ab-cdef-0123-456789ABCDEF").as_deref(),
+            Some(id)
+        );
+        assert_eq!(
+            parse_notion_database_id(&format!("https://www.notion.so/acme/Server-FAQ-{id}?v=abc")).as_deref(),
+            Some(id)
+        );
+        assert_eq!(parse_notion_database_id("markdown"), None);
+        assert_eq!(parse_notion_database_id("0123456789abcdef0123456789abcdeg"), None);
+    }
+
+    #[test]
+    fn test_entry_blocks() {
+        let entry = KnowledgeEntry {
+            id: 1,
+            question: "Where are the docs?".to_string(),
+            answer: Some(format!("First paragraph.\n\n{}", "x".repeat(MAX_TEXT_LEN + 10))),
+            answer_link: "https://discord.com/channels/1/2/3".to_string(),
+            created_at: "2026-03-01 12:00:00".to_string(),
+        };
+        let blocks = entry_blocks(&entry);
+        // One paragraph, the long one split in two, then the link
+        assert_eq!(blocks.len(), 4);
+        let link = &blocks[3]["paragraph"]["rich_text"][0]["text"]["link"]["url"];
+        assert_eq!(link, "https://discord.com/channels/1/2/3");
+    }
+}
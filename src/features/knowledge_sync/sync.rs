@@ -0,0 +1,89 @@
+//! # Feature: Knowledge Base Sync Loop
+//!
+//! Background task that exports newly answered support questions for each guild
+//! whose `knowledge_base_export` setting is `markdown` or a Notion database.
+//! Entries are marked exported only once written, so failures are retried.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release polling for unexported entries every 5 minutes
+
+use super::markdown::MarkdownExporter;
+use super::notion::{parse_notion_database_id, NotionClient};
+use crate::database::Database;
+use crate::core::{BotError, Result};
+use log::{info, warn};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Entries exported per guild per pass
+const BATCH_SIZE: i64 = 50;
+
+/// The export targets configured by the operator
+#[derive(Clone)]
+pub struct KnowledgeExporters {
+    pub markdown: Option<MarkdownExporter>,
+    pub notion: Option<NotionClient>,
+}
+
+/// Export a guild's pending entries; returns how many were exported
+async fn sync_guild(db: &Database, exporters: &KnowledgeExporters, guild_id: &str, target: &str) -> Result<usize> {
+    let entries = db.get_unexported_answered_questions(guild_id, BATCH_SIZE).await?;
+    if entries.is_empty() {
+        return Ok(0);
+    }
+
+    if target == "markdown" {
+        let Some(markdown) = &exporters.markdown else {
+            return Err(BotError::validation("markdown export is selected but KNOWLEDGE_BASE_DIR is not set"));
+        };
+        markdown.export(guild_id, &entries)?;
+        for entry in &entries {
+            db.mark_answered_question_exported(guild_id, entry.id).await?;
+        }
+    } else {
+        let Some(database_id) = parse_notion_database_id(target) else {
+            return Err(BotError::validation(format!("knowledge_base_export is neither markdown nor a Notion database: {}", target)));
+        };
+        let Some(notion) = &exporters.notion else {
+            return Err(BotError::validation("Notion export is selected but NOTION_TOKEN is not set"));
+        };
+        for entry in &entries {
+            notion.create_entry_page(&database_id, entry).await?;
+            db.mark_answered_question_exported(guild_id, entry.id).await?;
+        }
+    }
+    Ok(entries.len())
+}
+
+/// Background task that exports new knowledge base entries every 5 minutes
+pub async fn knowledge_sync_loop(db: Arc<Database>, exporters: KnowledgeExporters) {
+    let mut interval = tokio::time::interval(Duration::from_secs(300));
+    info!("Knowledge base sync task started (checks every 5 minutes)");
+
+    loop {
+        interval.tick().await;
+        let guilds = match db.get_guilds_with_settings().await {
+            Ok(guilds) => guilds,
+            Err(e) => {
+                warn!("Failed to load guilds for knowledge base sync: {}", e);
+                continue;
+            }
+        };
+
+        for guild_id in guilds {
+            let target = match db.get_guild_setting(&guild_id, "knowledge_base_export").await {
+                Ok(Some(target)) if target != "disabled" => target,
+                _ => continue,
+            };
+            match sync_guild(&db, &exporters, &guild_id, &target).await {
+                Ok(0) => {}
+                Ok(count) => info!("Exported {} knowledge base entries for guild {}", count, guild_id),
+                Err(e) => warn!("Failed to sync knowledge base for guild {}: {}", guild_id, e),
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! # Feature: Live Event Bus
+//!
+//! A broadcast channel that the command handler, usage tracker and conflict
+//! detection publish to. Publishing never blocks and is free when nobody is
+//! listening; a subscriber that falls more than [`LIVE_EVENT_BUFFER`] events
+//! behind skips ahead rather than slowing the bot down.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with command, cost, conflict and error events
+
+use crate::core::clock::system_clock;
+use crate::core::Clock;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Events held for each subscriber before the slowest one starts missing them
+pub const LIVE_EVENT_BUFFER: usize = 256;
+
+/// What happened, serialized with a `type` tag
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEventKind {
+    /// A slash command finished, successfully or not
+    CommandHandled {
+        command: String,
+        user_id: String,
+        guild_id: Option<String>,
+        ok: bool,
+        duration_ms: u64,
+    },
+    /// An OpenAI call was priced and logged
+    Cost {
+        /// `chat`, `whisper` or `dalle`
+        service: String,
+        /// Model for chat, size for DALL-E, none for Whisper
+        model: Option<String>,
+        user_id: String,
+        guild_id: Option<String>,
+        cost_usd: f64,
+    },
+    /// A heated argument crossed the guild's sensitivity threshold
+    Conflict {
+        guild_id: Option<String>,
+        channel_id: String,
+        conflict_type: String,
+        confidence: f32,
+    },
+    /// A handler returned an error
+    Error {
+        /// `command`, `component` or `modal`
+        source: String,
+        name: String,
+        message: String,
+    },
+}
+
+/// An event with the time it was published
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LiveEvent {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: LiveEventKind,
+}
+
+impl LiveEvent {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Handle for publishing and subscribing; clones share the same channel
+#[derive(Clone)]
+pub struct LiveEvents {
+    sender: broadcast::Sender<LiveEvent>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for LiveEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiveEvents {
+    pub fn new() -> Self {
+        Self::with_clock(system_clock())
+    }
+
+    /// Stamp events with this clock instead of the system clock
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        let (sender, _) = broadcast::channel(LIVE_EVENT_BUFFER);
+        LiveEvents { sender, clock }
+    }
+
+    /// Send an event to every current subscriber (a no-op when there are none)
+    pub fn publish(&self, kind: LiveEventKind) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        let _ = self.sender.send(LiveEvent { at: self.clock.now(), kind });
+    }
+
+    /// Receive events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::FakeClock;
+    use chrono::TimeZone;
+
+    fn conflict() -> LiveEventKind {
+        LiveEventKind::Conflict {
+            guild_id: Some("1".to_string()),
+            channel_id: "2".to_string(),
+            conflict_type: "hostile_language".to_string(),
+            confidence: 0.75,
+        }
+    }
+
+    #[test]
+    fn test_event_json_is_flat_and_tagged() {
+        let event = LiveEvent {
+            at: Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+            kind: LiveEventKind::CommandHandled {
+                command: "ping".to_string(),
+                user_id: "42".to_string(),
+                guild_id: None,
+                ok: true,
+                duration_ms: 12,
+            },
+        };
+        let json: serde_json::Value = serde_json::from_str(&event.to_json()).unwrap();
+        assert_eq!(json["type"], "command_handled");
+        assert_eq!(json["at"], "2024-05-01T12:00:00Z");
+        assert_eq!(json["command"], "ping");
+        assert!(json["guild_id"].is_null());
+        assert_eq!(json["duration_ms"], 12);
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_published_events() {
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let events = LiveEvents::with_clock(Arc::new(FakeClock::new(start)));
+
+        // Nobody listening: dropped without error
+        events.publish(conflict());
+
+        let mut receiver = events.subscribe();
+        assert_eq!(events.subscriber_count(), 1);
+        events.publish(conflict());
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.at, start);
+        assert_eq!(event.kind, conflict());
+        assert!(receiver.try_recv().is_err());
+    }
+}
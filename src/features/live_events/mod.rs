@@ -0,0 +1,17 @@
+//! # Live Events Feature
+//!
+//! In-process broadcast of what the bot is doing — slash commands handled,
+//! OpenAI costs, detected conflicts and command errors — streamed as JSON over
+//! an authenticated WebSocket (`GET /events` on `HEALTH_LISTEN_ADDR`) so an ops
+//! dashboard can update in real time instead of polling. Enabled by setting
+//! `LIVE_EVENTS_TOKEN`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod bus;
+pub mod server;
+
+pub use bus::{LiveEvent, LiveEventKind, LiveEvents, LIVE_EVENT_BUFFER};
+pub use server::{live_events_router, request_token};
@@ -0,0 +1,108 @@
+//! # Feature: Live Event Stream
+//!
+//! `GET /events` upgrades to a WebSocket that sends each [`LiveEvent`] as a JSON
+//! text frame. The token is read from `Authorization: Bearer <token>`, or from
+//! `?token=` for browsers, whose WebSocket API cannot set headers. Served on
+//! the health endpoint's listener.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with bearer and query-string tokens
+
+use super::bus::LiveEvents;
+use crate::features::webhook_ingest::tokens_match;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+#[derive(Clone)]
+struct StreamState {
+    events: LiveEvents,
+    token: Arc<str>,
+}
+
+/// Token presented by a client: the bearer header first, then the `token` query parameter
+pub fn request_token<'a>(headers: &'a HeaderMap, query: &'a HashMap<String, String>) -> Option<&'a str> {
+    headers
+        .get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(str::trim)
+        .or_else(|| query.get("token").map(String::as_str))
+}
+
+async fn stream(
+    State(state): State<StreamState>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let provided = request_token(&headers, &query).unwrap_or_default();
+    if !tokens_match(&state.token, provided) {
+        return (StatusCode::UNAUTHORIZED, "invalid token").into_response();
+    }
+    upgrade.on_upgrade(move |socket| forward_events(socket, state.events))
+}
+
+/// Send events to the client until it disconnects
+async fn forward_events(mut socket: WebSocket, events: LiveEvents) {
+    let mut receiver = events.subscribe();
+    info!("Live event subscriber connected ({} open)", events.subscriber_count());
+    loop {
+        tokio::select! {
+            event = receiver.recv() => match event {
+                Ok(event) => {
+                    if socket.send(Message::Text(event.to_json())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => warn!("Live event subscriber fell behind, skipped {skipped} events"),
+                Err(RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                // Pings are answered by axum; anything else from the client is ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    debug!("Live event subscriber disconnected");
+}
+
+/// Routes for the event stream, merged into the health endpoint's router
+pub fn live_events_router(events: LiveEvents, token: &str) -> Router {
+    Router::new()
+        .route("/events", get(stream))
+        .with_state(StreamState { events, token: Arc::from(token) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_token() {
+        let mut headers = HeaderMap::new();
+        let mut query = HashMap::new();
+        assert_eq!(request_token(&headers, &query), None);
+
+        query.insert("token".to_string(), "from-query".to_string());
+        assert_eq!(request_token(&headers, &query), Some("from-query"));
+
+        headers.insert("authorization", "Bearer from-header ".parse().unwrap());
+        assert_eq!(request_token(&headers, &query), Some("from-header"));
+
+        headers.insert("authorization", "Basic abc".parse().unwrap());
+        assert_eq!(request_token(&headers, &query), Some("from-query"));
+    }
+}
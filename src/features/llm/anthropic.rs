@@ -0,0 +1,249 @@
+//! # Feature: Anthropic Provider
+//!
+//! Chat over the Anthropic Messages API. System messages become the top-level
+//! `system` field, function calls map to `tool_use` / `tool_result` content
+//! blocks, and a leading assistant turn is dropped because the conversation
+//! must open with the user.
+//!
+//! - **Version**: 1.3.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.3.0: HTTP 5xx is reported as `Unavailable`, so it is retried
+//! - 1.2.0: Errors are `BotError`s; HTTP 429 is reported as rate limiting
+//! - 1.1.0: Images as base64 `image` content blocks
+//! - 1.0.0: Initial release with tool use
+
+use super::provider::{rate_limited, ChatRequest, ChatResponse, ChatRole, FunctionCall, LlmProvider, TokenUsage};
+use crate::core::{BotError, Result};
+use serde_json::{json, Value};
+use serenity::async_trait;
+use std::time::Duration;
+
+const MESSAGES_URL: &str = "https://api.anthropic.com/v1/messages";
+const API_VERSION: &str = "2023-06-01";
+
+/// `max_tokens` is required by the API; used when the request doesn't set one
+const DEFAULT_MAX_TOKENS: u32 = 2048;
+
+pub struct AnthropicProvider {
+    client: reqwest::Client,
+    api_key: String,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_key: &str) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .unwrap_or_default();
+        AnthropicProvider { client, api_key: api_key.to_string() }
+    }
+}
+
+/// Request body for the Messages API
+pub fn request_body(request: &ChatRequest) -> Value {
+    let system: Vec<&str> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == ChatRole::System)
+        .filter_map(|m| m.content.as_deref())
+        .collect();
+
+    let mut messages: Vec<Value> = Vec::new();
+    // tool_result blocks must reference the tool_use they answer
+    let mut last_call_id = String::new();
+    for (index, message) in request.messages.iter().enumerate() {
+        let entry = match (message.role, &message.function_call) {
+            (ChatRole::System, _) => continue,
+            (ChatRole::Assistant, Some(call)) => {
+                last_call_id = call.id.clone().unwrap_or_else(|| format!("call_{index}"));
+                let input: Value = serde_json::from_str(&call.arguments).unwrap_or_else(|_| json!({}));
+                json!({
+                    "role": "assistant",
+                    "content": [{ "type": "tool_use", "id": last_call_id, "name": call.name, "input": input }]
+                })
+            }
+            (ChatRole::Function, _) => json!({
+                "role": "user",
+                "content": [{
+                    "type": "tool_result",
+                    "tool_use_id": last_call_id,
+                    "content": message.content.clone().unwrap_or_default()
+                }]
+            }),
+            (ChatRole::User, _) if !message.images.is_empty() => {
+                let mut blocks: Vec<Value> = message
+                    .images
+                    .iter()
+                    .map(|i| json!({ "type": "image", "source": { "type": "base64", "media_type": i.media_type, "data": i.data } }))
+                    .collect();
+                blocks.push(json!({ "type": "text", "text": message.content.clone().unwrap_or_default() }));
+                json!({ "role": "user", "content": blocks })
+            }
+            (role, _) => {
+                let role = if role == ChatRole::Assistant { "assistant" } else { "user" };
+                json!({ "role": role, "content": message.content.clone().unwrap_or_default() })
+            }
+        };
+        if messages.is_empty() && entry["role"] == "assistant" {
+            continue;
+        }
+        messages.push(entry);
+    }
+
+    let mut body = json!({
+        "model": request.model,
+        "max_tokens": request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        "messages": messages,
+    });
+    if !system.is_empty() {
+        body["system"] = json!(system.join("\n\n"));
+    }
+    if let Some(temperature) = request.temperature {
+        // Anthropic accepts 0.0-1.0
+        body["temperature"] = json!(temperature.clamp(0.0, 1.0));
+    }
+    if !request.functions.is_empty() {
+        let tools: Vec<Value> = request
+            .functions
+            .iter()
+            .map(|f| json!({ "name": f.name, "description": f.description, "input_schema": f.parameters }))
+            .collect();
+        body["tools"] = json!(tools);
+    }
+    body
+}
+
+/// Read text, the first tool call and usage from a Messages API response
+pub fn parse_response(body: &Value) -> Result<ChatResponse> {
+    if let Some(error) = body.get("error") {
+        let message = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+        return Err(BotError::openai(message));
+    }
+    let blocks = body
+        .get("content")
+        .and_then(Value::as_array)
+        .ok_or_else(|| BotError::openai("response has no content"))?;
+
+    let text: Vec<&str> = blocks
+        .iter()
+        .filter(|b| b["type"] == "text")
+        .filter_map(|b| b["text"].as_str())
+        .collect();
+    let function_call = blocks.iter().find(|b| b["type"] == "tool_use").and_then(|b| {
+        Some(FunctionCall {
+            id: b["id"].as_str().map(str::to_string),
+            name: b["name"].as_str()?.to_string(),
+            arguments: b["input"].to_string(),
+        })
+    });
+    let usage = body.get("usage").map(|u| {
+        let input = u["input_tokens"].as_u64().unwrap_or(0) as u32;
+        let output = u["output_tokens"].as_u64().unwrap_or(0) as u32;
+        TokenUsage { prompt_tokens: input, completion_tokens: output, total_tokens: input + output }
+    });
+
+    Ok(ChatResponse {
+        content: (!text.is_empty()).then(|| text.concat()),
+        function_call,
+        usage,
+    })
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let response = self
+            .client
+            .post(MESSAGES_URL)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+            .json(&request_body(request))
+            .send()
+            .await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(rate_limited(response.headers()));
+        }
+        if status.is_server_error() {
+            return Err(BotError::unavailable(format!("HTTP {status}")));
+        }
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() && body.get("error").is_none() {
+            return Err(BotError::openai(format!("HTTP {status}")));
+        }
+        parse_response(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::llm::{ChatImage, ChatMessage};
+
+    #[test]
+    fn test_request_body_maps_system_and_tools() {
+        let call = FunctionCall { id: None, name: "calculate".to_string(), arguments: r#"{"expression":"6*7"}"#.to_string() };
+        let request = ChatRequest::new(
+            "claude-sonnet-4-5",
+            vec![
+                ChatMessage::system("You are Obi-Wan"),
+                ChatMessage::assistant("Hello there"),
+                ChatMessage::user("What is 6*7?"),
+                ChatMessage::function_call(call.clone()),
+                ChatMessage::function_result(&call, "42"),
+            ],
+        )
+        .temperature(1.5);
+
+        let body = request_body(&request);
+        assert_eq!(body["system"], "You are Obi-Wan");
+        assert_eq!(body["max_tokens"], DEFAULT_MAX_TOKENS);
+        assert_eq!(body["temperature"], 1.0);
+        let messages = body["messages"].as_array().unwrap();
+        // The leading assistant turn is dropped
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["role"], "user");
+        assert_eq!(messages[1]["content"][0]["type"], "tool_use");
+        assert_eq!(messages[1]["content"][0]["input"]["expression"], "6*7");
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], messages[1]["content"][0]["id"]);
+    }
+
+    #[test]
+    fn test_request_body_with_images() {
+        let image = ChatImage { media_type: "image/png".to_string(), data: "iVBORw0KGgo=".to_string() };
+        let request = ChatRequest::new("claude-sonnet-4-5", vec![ChatMessage::user("Describe this").with_images(vec![image])]);
+
+        let body = request_body(&request);
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[0]["type"], "image");
+        assert_eq!(content[0]["source"]["media_type"], "image/png");
+        assert_eq!(content[1]["text"], "Describe this");
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let body = json!({
+            "content": [
+                { "type": "text", "text": "Let me work that out." },
+                { "type": "tool_use", "id": "toolu_1", "name": "calculate", "input": { "expression": "6*7" } }
+            ],
+            "usage": { "input_tokens": 20, "output_tokens": 10 }
+        });
+        let response = parse_response(&body).unwrap();
+        assert_eq!(response.content.as_deref(), Some("Let me work that out."));
+        let call = response.function_call.unwrap();
+        assert_eq!(call.id.as_deref(), Some("toolu_1"));
+        assert_eq!(call.arguments, r#"{"expression":"6*7"}"#);
+        assert_eq!(response.usage.unwrap().total_tokens, 30);
+
+        let error = json!({ "type": "error", "error": { "type": "authentication_error", "message": "invalid x-api-key" } });
+        assert!(parse_response(&error).is_err());
+    }
+}
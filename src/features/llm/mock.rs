@@ -0,0 +1,91 @@
+//! # Feature: Mock Provider
+//!
+//! A chat backend that answers locally after a fixed delay, for load tests and
+//! benchmarks that should exercise the pipeline without paying for API calls.
+//! Replies echo the last user message and report token counts estimated at
+//! four characters per token.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use super::provider::{ChatRequest, ChatResponse, ChatRole, LlmProvider, TokenUsage};
+use crate::core::Result;
+use serenity::async_trait;
+use std::time::Duration;
+
+pub struct MockProvider {
+    latency: Duration,
+}
+
+impl MockProvider {
+    /// `latency` stands in for the model's response time
+    pub fn new(latency: Duration) -> Self {
+        MockProvider { latency }
+    }
+}
+
+/// Rough token count for usage logging
+fn estimate_tokens(text: &str) -> u32 {
+    (text.chars().count() as u32).div_ceil(4)
+}
+
+/// The canned answer to a request
+pub fn mock_response(request: &ChatRequest) -> ChatResponse {
+    let question = request
+        .messages
+        .iter()
+        .rev()
+        .find(|m| m.role == ChatRole::User)
+        .and_then(|m| m.content.as_deref())
+        .unwrap_or("");
+    let content = format!("[mock] {}", question.trim());
+    let prompt_tokens: u32 = request
+        .messages
+        .iter()
+        .filter_map(|m| m.content.as_deref())
+        .map(estimate_tokens)
+        .sum();
+    let completion_tokens = estimate_tokens(&content);
+    ChatResponse {
+        content: Some(content),
+        function_call: None,
+        usage: Some(TokenUsage { prompt_tokens, completion_tokens, total_tokens: prompt_tokens + completion_tokens }),
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+        Ok(mock_response(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::llm::ChatMessage;
+
+    #[test]
+    fn test_mock_response() {
+        let request = ChatRequest::new(
+            "mock",
+            vec![ChatMessage::system("Be brief"), ChatMessage::user("Hello there "), ChatMessage::assistant("Hi")],
+        );
+        let response = mock_response(&request);
+        assert_eq!(response.content.as_deref(), Some("[mock] Hello there"));
+        let usage = response.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, 2 + 3 + 1);
+        assert_eq!(usage.total_tokens, usage.prompt_tokens + usage.completion_tokens);
+    }
+}
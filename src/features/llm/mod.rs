@@ -0,0 +1,36 @@
+//! # LLM Feature
+//!
+//! Pluggable chat backends behind the [`LlmProvider`] trait: OpenAI, Azure
+//! OpenAI, Anthropic and a local Ollama server, selected with `LLM_PROVIDER`.
+//! Image generation, Whisper transcription and image moderation still call
+//! OpenAI directly. User messages can carry images for vision-capable models.
+//! A local mock backend serves load tests and benchmarks. Every backend is
+//! wrapped in [`TracedProvider`], so each completion is a tracing span.
+//! Prompts are measured with tiktoken and trimmed to the model's window.
+//! [`ResilientProvider`] retries rate limits and outages and trips a circuit
+//! breaker when the backend keeps failing.
+//!
+//! - **Version**: 1.4.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod anthropic;
+pub mod mock;
+pub mod ollama;
+pub mod openai;
+pub mod provider;
+pub mod resilient;
+pub mod tokens;
+pub mod traced;
+
+pub use anthropic::AnthropicProvider;
+pub use mock::MockProvider;
+pub use ollama::OllamaProvider;
+pub use openai::OpenAiProvider;
+pub use provider::{
+    build_provider, ChatImage, ChatMessage, ChatRequest, ChatResponse, ChatRole, FunctionCall, FunctionDefinition, LlmProvider,
+    ResponseSchema, TokenUsage,
+};
+pub use resilient::{CircuitBreaker, CircuitState, ResilientProvider, RetryPolicy};
+pub use tokens::{fit_to_window, measured_usage, PromptBudget};
+pub use traced::TracedProvider;
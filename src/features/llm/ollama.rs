@@ -0,0 +1,188 @@
+//! # Feature: Ollama Provider
+//!
+//! Chat against a local Ollama server through its native `/api/chat` endpoint,
+//! without streaming. Function calls use Ollama's `tools` support, which needs
+//! a model that was trained for tool calling, and images need a vision model
+//! such as `llava`.
+//!
+//! - **Version**: 1.3.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.3.0: HTTP 5xx is reported as `Unavailable`, so it is retried
+//! - 1.2.0: Errors are `BotError`s; HTTP 429 is reported as rate limiting
+//! - 1.1.0: Images on user messages
+//! - 1.0.0: Initial release with tool calls
+
+use super::provider::{rate_limited, ChatRequest, ChatResponse, ChatRole, FunctionCall, LlmProvider, TokenUsage};
+use crate::core::{BotError, Result};
+use serde_json::{json, Value};
+use serenity::async_trait;
+use std::time::Duration;
+
+pub struct OllamaProvider {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl OllamaProvider {
+    pub fn new(base_url: &str) -> Self {
+        // Local models can be slow to load and generate
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(300))
+            .build()
+            .unwrap_or_default();
+        OllamaProvider { client, base_url: base_url.trim_end_matches('/').to_string() }
+    }
+}
+
+/// Request body for `/api/chat`
+pub fn request_body(request: &ChatRequest) -> Value {
+    let messages: Vec<Value> = request
+        .messages
+        .iter()
+        .map(|m| {
+            let role = match m.role {
+                ChatRole::System => "system",
+                ChatRole::User => "user",
+                ChatRole::Assistant => "assistant",
+                ChatRole::Function => "tool",
+            };
+            let mut message = json!({ "role": role, "content": m.content.clone().unwrap_or_default() });
+            if !m.images.is_empty() {
+                message["images"] = json!(m.images.iter().map(|i| i.data.as_str()).collect::<Vec<_>>());
+            }
+            if let Some(call) = &m.function_call {
+                let arguments: Value = serde_json::from_str(&call.arguments).unwrap_or_else(|_| json!({}));
+                message["tool_calls"] = json!([{ "function": { "name": call.name, "arguments": arguments } }]);
+            }
+            message
+        })
+        .collect();
+
+    let mut body = json!({ "model": request.model, "messages": messages, "stream": false });
+    let mut options = serde_json::Map::new();
+    if let Some(temperature) = request.temperature {
+        options.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(max_tokens) = request.max_tokens {
+        options.insert("num_predict".to_string(), json!(max_tokens));
+    }
+    if !options.is_empty() {
+        body["options"] = Value::Object(options);
+    }
+    if !request.functions.is_empty() {
+        let tools: Vec<Value> = request
+            .functions
+            .iter()
+            .map(|f| {
+                json!({
+                    "type": "function",
+                    "function": { "name": f.name, "description": f.description, "parameters": f.parameters }
+                })
+            })
+            .collect();
+        body["tools"] = json!(tools);
+    }
+    body
+}
+
+/// Read the reply, the first tool call and token counts from an `/api/chat` response
+pub fn parse_response(body: &Value) -> Result<ChatResponse> {
+    if let Some(error) = body.get("error").and_then(Value::as_str) {
+        return Err(BotError::openai(error));
+    }
+    let message = body.get("message").ok_or_else(|| BotError::openai("response has no message"))?;
+    let function_call = message.pointer("/tool_calls/0/function").and_then(|f| {
+        Some(FunctionCall {
+            id: None,
+            name: f.get("name")?.as_str()?.to_string(),
+            arguments: f.get("arguments").map(Value::to_string).unwrap_or_else(|| "{}".to_string()),
+        })
+    });
+    let prompt = body.get("prompt_eval_count").and_then(Value::as_u64);
+    let completion = body.get("eval_count").and_then(Value::as_u64);
+    let usage = (prompt.is_some() || completion.is_some()).then(|| {
+        let (prompt, completion) = (prompt.unwrap_or(0) as u32, completion.unwrap_or(0) as u32);
+        TokenUsage { prompt_tokens: prompt, completion_tokens: completion, total_tokens: prompt + completion }
+    });
+    let content = message
+        .get("content")
+        .and_then(Value::as_str)
+        .filter(|c| !c.is_empty())
+        .map(str::to_string);
+
+    Ok(ChatResponse { content, function_call, usage })
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let response = self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&request_body(request))
+            .send()
+            .await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(rate_limited(response.headers()));
+        }
+        if status.is_server_error() {
+            return Err(BotError::unavailable(format!("HTTP {status}")));
+        }
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() && body.get("error").is_none() {
+            return Err(BotError::openai(format!("HTTP {status}")));
+        }
+        parse_response(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::llm::{ChatImage, ChatMessage};
+
+    #[test]
+    fn test_request_body() {
+        let call = FunctionCall { id: None, name: "calculate".to_string(), arguments: r#"{"expression":"1+1"}"#.to_string() };
+        let mut request = ChatRequest::new(
+            "llama3.1",
+            vec![ChatMessage::user("1+1?"), ChatMessage::function_call(call.clone()), ChatMessage::function_result(&call, "2")],
+        )
+        .temperature(0.2);
+        request.max_tokens = Some(100);
+
+        let body = request_body(&request);
+        assert_eq!(body["stream"], false);
+        assert_eq!(body["options"]["num_predict"], 100);
+        assert_eq!(body["messages"][1]["tool_calls"][0]["function"]["arguments"]["expression"], "1+1");
+        assert_eq!(body["messages"][2]["role"], "tool");
+        assert!(body.get("tools").is_none());
+
+        let image = ChatImage { media_type: "image/png".to_string(), data: "iVBORw0KGgo=".to_string() };
+        let body = request_body(&ChatRequest::new("llava", vec![ChatMessage::user("What is this?").with_images(vec![image])]));
+        assert_eq!(body["messages"][0]["images"][0], "iVBORw0KGgo=");
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let body = json!({
+            "message": { "role": "assistant", "content": "", "tool_calls": [{ "function": { "name": "calculate", "arguments": { "expression": "1+1" } } }] },
+            "prompt_eval_count": 30,
+            "eval_count": 5
+        });
+        let response = parse_response(&body).unwrap();
+        assert_eq!(response.content, None);
+        assert_eq!(response.function_call.unwrap().arguments, r#"{"expression":"1+1"}"#);
+        assert_eq!(response.usage.unwrap().total_tokens, 35);
+
+        assert_eq!(parse_response(&json!({ "error": "model 'x' not found" })).unwrap_err().to_string(), "model 'x' not found");
+    }
+}
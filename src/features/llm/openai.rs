@@ -0,0 +1,259 @@
+//! # Feature: OpenAI and Azure OpenAI Provider
+//!
+//! Chat completions over the OpenAI wire format. The same request and response
+//! shapes serve api.openai.com (bearer key, model in the body) and Azure OpenAI
+//! (`api-key` header, model taken as the deployment name in the URL).
+//!
+//! - **Version**: 1.4.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.4.0: `response_format` with a strict JSON Schema for structured replies
+//! - 1.3.0: HTTP 5xx is reported as `Unavailable`, so it is retried
+//! - 1.2.0: Errors are `BotError`s; HTTP 429 is reported as rate limiting
+//! - 1.1.0: Images as `image_url` content parts
+//! - 1.0.0: Initial release with OpenAI and Azure OpenAI endpoints
+
+use super::provider::{rate_limited, ChatRequest, ChatResponse, ChatRole, FunctionCall, LlmProvider, TokenUsage};
+use crate::core::{BotError, Result};
+use serde_json::{json, Value};
+use serenity::async_trait;
+use std::time::Duration;
+
+const OPENAI_CHAT_URL: &str = "https://api.openai.com/v1/chat/completions";
+
+enum Endpoint {
+    OpenAi { api_key: String },
+    Azure { endpoint: String, api_key: String, api_version: String },
+}
+
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    endpoint: Endpoint,
+}
+
+impl OpenAiProvider {
+    fn with_endpoint(endpoint: Endpoint) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .unwrap_or_default();
+        OpenAiProvider { client, endpoint }
+    }
+
+    pub fn openai(api_key: &str) -> Self {
+        Self::with_endpoint(Endpoint::OpenAi { api_key: api_key.to_string() })
+    }
+
+    /// `endpoint` is the resource URL, e.g. `https://my-resource.openai.azure.com`
+    pub fn azure(endpoint: &str, api_key: &str, api_version: &str) -> Self {
+        Self::with_endpoint(Endpoint::Azure {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+            api_version: api_version.to_string(),
+        })
+    }
+}
+
+fn role_name(role: ChatRole) -> &'static str {
+    match role {
+        ChatRole::System => "system",
+        ChatRole::User => "user",
+        ChatRole::Assistant => "assistant",
+        ChatRole::Function => "function",
+    }
+}
+
+/// Request body in the chat completions format
+pub fn request_body(request: &ChatRequest) -> Value {
+    let messages: Vec<Value> = request
+        .messages
+        .iter()
+        .map(|m| {
+            let mut message = json!({ "role": role_name(m.role), "content": m.content });
+            if !m.images.is_empty() {
+                let mut parts = vec![json!({ "type": "text", "text": m.content.clone().unwrap_or_default() })];
+                parts.extend(m.images.iter().map(|i| json!({ "type": "image_url", "image_url": { "url": i.data_url() } })));
+                message["content"] = json!(parts);
+            }
+            if let Some(name) = &m.name {
+                message["name"] = json!(name);
+            }
+            if let Some(call) = &m.function_call {
+                message["function_call"] = json!({ "name": call.name, "arguments": call.arguments });
+            }
+            message
+        })
+        .collect();
+
+    let mut body = json!({ "model": request.model, "messages": messages });
+    if let Some(temperature) = request.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if let Some(max_tokens) = request.max_tokens {
+        body["max_completion_tokens"] = json!(max_tokens);
+    }
+    if !request.functions.is_empty() {
+        let functions: Vec<Value> = request
+            .functions
+            .iter()
+            .map(|f| json!({ "name": f.name, "description": f.description, "parameters": f.parameters }))
+            .collect();
+        body["functions"] = json!(functions);
+    }
+    if let Some(format) = &request.response_schema {
+        body["response_format"] = json!({
+            "type": "json_schema",
+            "json_schema": { "name": format.name, "strict": true, "schema": format.schema }
+        });
+    }
+    body
+}
+
+/// Read the first choice and token usage from a chat completions response
+pub fn parse_response(body: &Value) -> Result<ChatResponse> {
+    if let Some(error) = body.get("error") {
+        let message = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+        return Err(BotError::openai(message));
+    }
+    let message = body
+        .pointer("/choices/0/message")
+        .ok_or_else(|| BotError::openai("response has no choices"))?;
+    let function_call = message.get("function_call").and_then(|call| {
+        Some(FunctionCall {
+            id: None,
+            name: call.get("name")?.as_str()?.to_string(),
+            arguments: call.get("arguments").and_then(Value::as_str).unwrap_or("{}").to_string(),
+        })
+    });
+    let usage = body.get("usage").map(|u| {
+        let read = |key: &str| u.get(key).and_then(Value::as_u64).unwrap_or(0) as u32;
+        TokenUsage {
+            prompt_tokens: read("prompt_tokens"),
+            completion_tokens: read("completion_tokens"),
+            total_tokens: read("total_tokens"),
+        }
+    });
+    Ok(ChatResponse {
+        content: message.get("content").and_then(Value::as_str).map(str::to_string),
+        function_call,
+        usage,
+    })
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        match self.endpoint {
+            Endpoint::OpenAi { .. } => "openai",
+            Endpoint::Azure { .. } => "azure",
+        }
+    }
+
+    fn supports_response_schema(&self) -> bool {
+        true
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let body = request_body(request);
+        let builder = match &self.endpoint {
+            Endpoint::OpenAi { api_key } => self.client.post(OPENAI_CHAT_URL).bearer_auth(api_key),
+            Endpoint::Azure { endpoint, api_key, api_version } => self
+                .client
+                .post(format!("{endpoint}/openai/deployments/{}/chat/completions", request.model))
+                .query(&[("api-version", api_version)])
+                .header("api-key", api_key),
+        };
+        let response = builder.json(&body).send().await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(rate_limited(response.headers()));
+        }
+        if status.is_server_error() {
+            return Err(BotError::unavailable(format!("HTTP {status}")));
+        }
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() && body.get("error").is_none() {
+            return Err(BotError::openai(format!("HTTP {status}")));
+        }
+        parse_response(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::llm::{ChatImage, ChatMessage, FunctionDefinition, ResponseSchema};
+
+    #[test]
+    fn test_request_body() {
+        let call = FunctionCall { id: None, name: "calculate".to_string(), arguments: r#"{"expression":"2+2"}"#.to_string() };
+        let request = ChatRequest::new(
+            "gpt-5.1",
+            vec![
+                ChatMessage::system("Be brief"),
+                ChatMessage::function_call(call.clone()),
+                ChatMessage::function_result(&call, "4"),
+            ],
+        )
+        .temperature(0.5)
+        .functions(vec![FunctionDefinition {
+            name: "calculate".to_string(),
+            description: "Math".to_string(),
+            parameters: json!({ "type": "object" }),
+        }]);
+
+        let body = request_body(&request);
+        assert_eq!(body["model"], "gpt-5.1");
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["messages"][1]["function_call"]["name"], "calculate");
+        assert!(body["messages"][1]["content"].is_null());
+        assert_eq!(body["messages"][2]["role"], "function");
+        assert_eq!(body["messages"][2]["name"], "calculate");
+        assert_eq!(body["functions"][0]["name"], "calculate");
+    }
+
+    #[test]
+    fn test_request_body_with_response_schema() {
+        let schema = ResponseSchema { name: "reply".to_string(), schema: json!({ "type": "object" }) };
+        let request = ChatRequest::new("gpt-5.1", vec![ChatMessage::user("Hi")]).response_schema(schema);
+
+        let body = request_body(&request);
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert_eq!(body["response_format"]["json_schema"]["name"], "reply");
+        assert_eq!(body["response_format"]["json_schema"]["strict"], true);
+        assert!(request_body(&ChatRequest::new("gpt-5.1", Vec::new())).get("response_format").is_none());
+    }
+
+    #[test]
+    fn test_request_body_with_images() {
+        let image = ChatImage { media_type: "image/jpeg".to_string(), data: "/9j/".to_string() };
+        let request = ChatRequest::new("gpt-5.1", vec![ChatMessage::user("What is this?").with_images(vec![image])]);
+
+        let body = request_body(&request);
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[0]["text"], "What is this?");
+        assert_eq!(content[1]["type"], "image_url");
+        assert_eq!(content[1]["image_url"]["url"], "data:image/jpeg;base64,/9j/");
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let body = json!({
+            "choices": [{ "message": { "role": "assistant", "content": "Hello there" } }],
+            "usage": { "prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15 }
+        });
+        let response = parse_response(&body).unwrap();
+        assert_eq!(response.content.as_deref(), Some("Hello there"));
+        assert_eq!(response.usage.unwrap().total_tokens, 15);
+
+        let call = json!({
+            "choices": [{ "message": { "content": null, "function_call": { "name": "calculate", "arguments": "{}" } } }]
+        });
+        assert_eq!(parse_response(&call).unwrap().function_call.unwrap().name, "calculate");
+
+        let error = json!({ "error": { "message": "Incorrect API key provided" } });
+        assert_eq!(parse_response(&error).unwrap_err().to_string(), "Incorrect API key provided");
+    }
+}
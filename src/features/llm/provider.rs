@@ -0,0 +1,255 @@
+//! # Feature: LLM Provider Abstraction
+//!
+//! Chat completion types shared by every backend and the [`LlmProvider`] trait
+//! they implement. Callers build a [`ChatRequest`] once and the provider
+//! translates it to its own wire format, including function (tool) calls, so
+//! the bot can run on OpenAI, Azure OpenAI, Anthropic or a local Ollama
+//! server. The backend is chosen with `LLM_PROVIDER`.
+//!
+//! - **Version**: 1.3.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.3.0: Optional JSON Schema response format for structured replies
+//! - 1.2.0: Providers return `BotError`, with a shared rate-limit error
+//! - 1.1.0: Image inputs on user messages
+//! - 1.0.0: Initial release with OpenAI, Azure OpenAI, Anthropic and Ollama backends
+
+use super::anthropic::AnthropicProvider;
+use super::ollama::OllamaProvider;
+use super::openai::OpenAiProvider;
+use crate::core::{LlmConfig, ProviderKind};
+use crate::core::{BotError, Result};
+use serde_json::Value;
+use serenity::async_trait;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatRole {
+    System,
+    User,
+    Assistant,
+    /// The result of a function call, sent back to the model
+    Function,
+}
+
+/// A function call requested by the model
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionCall {
+    /// Provider-assigned call ID, when the provider uses one
+    pub id: Option<String>,
+    pub name: String,
+    /// JSON-encoded arguments
+    pub arguments: String,
+}
+
+/// An image sent alongside a user message
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatImage {
+    /// MIME type, e.g. `image/png`
+    pub media_type: String,
+    /// Base64-encoded image bytes
+    pub data: String,
+}
+
+impl ChatImage {
+    /// `data:` URL form, as the OpenAI API takes it
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.media_type, self.data)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: Option<String>,
+    /// Function name, on `Function` messages
+    pub name: Option<String>,
+    /// The call an `Assistant` message made instead of answering
+    pub function_call: Option<FunctionCall>,
+    /// Images attached to a `User` message, for vision-capable models
+    pub images: Vec<ChatImage>,
+}
+
+impl ChatMessage {
+    fn text(role: ChatRole, content: impl Into<String>) -> Self {
+        ChatMessage { role, content: Some(content.into()), name: None, function_call: None, images: Vec::new() }
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::text(ChatRole::System, content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::text(ChatRole::User, content)
+    }
+
+    pub fn with_images(mut self, images: Vec<ChatImage>) -> Self {
+        self.images = images;
+        self
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::text(ChatRole::Assistant, content)
+    }
+
+    /// The assistant turn that made `call`
+    pub fn function_call(call: FunctionCall) -> Self {
+        ChatMessage { role: ChatRole::Assistant, content: None, name: None, function_call: Some(call), images: Vec::new() }
+    }
+
+    /// The result of `call`, sent back to the model
+    pub fn function_result(call: &FunctionCall, result: impl Into<String>) -> Self {
+        ChatMessage {
+            role: ChatRole::Function,
+            content: Some(result.into()),
+            name: Some(call.name.clone()),
+            function_call: None,
+            images: Vec::new(),
+        }
+    }
+}
+
+/// A function the model may call, with JSON Schema parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+}
+
+/// A JSON Schema the reply must follow, for providers with structured output
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseSchema {
+    pub name: String,
+    pub schema: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub temperature: Option<f32>,
+    pub functions: Vec<FunctionDefinition>,
+    /// Reply length cap; providers that require one use their default when None
+    pub max_tokens: Option<u32>,
+    /// Structured reply format; ignored by providers without structured output
+    pub response_schema: Option<ResponseSchema>,
+}
+
+impl ChatRequest {
+    pub fn new(model: &str, messages: Vec<ChatMessage>) -> Self {
+        ChatRequest {
+            model: model.to_string(),
+            messages,
+            temperature: None,
+            functions: Vec::new(),
+            max_tokens: None,
+            response_schema: None,
+        }
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn functions(mut self, functions: Vec<FunctionDefinition>) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    pub fn response_schema(mut self, schema: ResponseSchema) -> Self {
+        self.response_schema = Some(schema);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TokenUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ChatResponse {
+    pub content: Option<String>,
+    pub function_call: Option<FunctionCall>,
+    pub usage: Option<TokenUsage>,
+}
+
+/// A chat completion backend
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Short name for logs, e.g. `anthropic`
+    fn name(&self) -> &'static str;
+
+    /// Whether the backend enforces [`ChatRequest::response_schema`]
+    fn supports_response_schema(&self) -> bool {
+        false
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse>;
+}
+
+/// The error for an HTTP 429, with the `Retry-After` delay when the backend sent one in seconds
+pub fn rate_limited(headers: &reqwest::header::HeaderMap) -> BotError {
+    let retry_after = headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .map(|secs| std::time::Duration::from_secs_f64(secs.max(0.0)));
+    BotError::RateLimited { retry_after }
+}
+
+/// Build the configured backend, failing when its credentials are missing
+pub fn build_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>> {
+    let provider: Arc<dyn LlmProvider> = match config.provider {
+        ProviderKind::OpenAi => Arc::new(OpenAiProvider::openai(&config.openai_api_key)),
+        ProviderKind::Azure => {
+            let (Some(endpoint), Some(api_key)) = (&config.azure_openai_endpoint, &config.azure_openai_api_key) else {
+                return Err(BotError::validation("LLM_PROVIDER=azure needs AZURE_OPENAI_ENDPOINT and AZURE_OPENAI_API_KEY"));
+            };
+            Arc::new(OpenAiProvider::azure(endpoint, api_key, &config.azure_openai_api_version))
+        }
+        ProviderKind::Anthropic => {
+            let Some(api_key) = &config.anthropic_api_key else {
+                return Err(BotError::validation("LLM_PROVIDER=anthropic needs ANTHROPIC_API_KEY"));
+            };
+            Arc::new(AnthropicProvider::new(api_key))
+        }
+        ProviderKind::Ollama => Arc::new(OllamaProvider::new(&config.ollama_url)),
+    };
+    Ok(provider)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_result_message() {
+        let call = FunctionCall { id: Some("call_1".to_string()), name: "calculate".to_string(), arguments: "{}".to_string() };
+        let result = ChatMessage::function_result(&call, "42");
+        assert_eq!(result.role, ChatRole::Function);
+        assert_eq!(result.name.as_deref(), Some("calculate"));
+        assert_eq!(result.content.as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn test_rate_limited_reads_retry_after() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert!(matches!(rate_limited(&headers), BotError::RateLimited { retry_after: None }));
+        headers.insert(reqwest::header::RETRY_AFTER, "20".parse().unwrap());
+        let BotError::RateLimited { retry_after } = rate_limited(&headers) else { panic!("not a rate limit") };
+        assert_eq!(retry_after, Some(std::time::Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn test_image_data_url() {
+        let image = ChatImage { media_type: "image/png".to_string(), data: "iVBORw0KGgo=".to_string() };
+        assert_eq!(image.data_url(), "data:image/png;base64,iVBORw0KGgo=");
+    }
+}
@@ -0,0 +1,403 @@
+//! # Feature: Resilient Provider
+//!
+//! Wraps any [`LlmProvider`] so rate limits, outages (HTTP 5xx) and timeouts
+//! are retried with exponential backoff and jitter before the user sees an
+//! error. Each attempt is cut off after `LLM_TIMEOUT_SECS`. A [`CircuitBreaker`]
+//! counts requests that still failed; after `LLM_CIRCUIT_THRESHOLD` in a row it
+//! stops calling the model for `LLM_CIRCUIT_COOLDOWN_SECS`, answering with
+//! `LLM_FALLBACK_MODEL` when one is set and with a "service degraded" message
+//! otherwise. Then a single probe request decides whether it closes again.
+//! The breaker's state is shown in `/status`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with retries, per-attempt timeouts, circuit breaker and fallback model
+
+use super::provider::{ChatRequest, ChatResponse, LlmProvider};
+use crate::core::{BotError, LlmConfig, Result};
+use log::{info, warn};
+use rand::Rng;
+use serenity::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// When and how often a failed chat request is tried again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    pub base: Duration,
+    /// Longest wait between attempts; a `Retry-After` beyond it is not waited out
+    pub max: Duration,
+    /// How long one attempt may take
+    pub attempt_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 2,
+            base: Duration::from_millis(500),
+            max: Duration::from_secs(8),
+            attempt_timeout: Duration::from_secs(45),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &LlmConfig) -> Self {
+        RetryPolicy {
+            max_retries: config.max_retries,
+            attempt_timeout: Duration::from_secs(config.timeout_secs),
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Backoff before retry number `retry` (1-based), without jitter
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u32 << retry.saturating_sub(1).min(16);
+        self.base.saturating_mul(factor).min(self.max)
+    }
+
+    /// Wait before retry number `retry` after `error`: the backend's `Retry-After` when it sent
+    /// one, otherwise the backoff with equal jitter (`jitter` in `[0, 1)`). None when the backend
+    /// asked for a longer wait than [`RetryPolicy::max`], so the user is told when to come back.
+    pub fn delay(&self, retry: u32, error: &BotError, jitter: f64) -> Option<Duration> {
+        if let BotError::RateLimited { retry_after: Some(after) } = error {
+            return (*after <= self.max).then_some(*after);
+        }
+        let backoff = self.backoff(retry);
+        Some(backoff / 2 + backoff.div_f64(2.0).mul_f64(jitter.clamp(0.0, 1.0)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests go to the model
+    Closed,
+    /// Requests are refused until the cooldown ends
+    Open { until: Instant },
+    /// One probe request is in flight; others are refused until it reports back or `until` passes
+    HalfOpen { until: Instant },
+}
+
+struct Breaker {
+    state: CircuitState,
+    /// Failed requests since the last success
+    failures: u32,
+}
+
+/// Stops calling a failing backend for a while; shared by every clone
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    fallback_model: Option<String>,
+    inner: Arc<Mutex<Breaker>>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        CircuitBreaker::new(5, Duration::from_secs(60))
+    }
+}
+
+impl CircuitBreaker {
+    /// Opens after `threshold` failed requests in a row and stays open for `cooldown`
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        CircuitBreaker {
+            threshold: threshold.max(1),
+            cooldown,
+            fallback_model: None,
+            inner: Arc::new(Mutex::new(Breaker { state: CircuitState::Closed, failures: 0 })),
+        }
+    }
+
+    pub fn from_config(config: &LlmConfig) -> Self {
+        CircuitBreaker::new(config.circuit_threshold, Duration::from_secs(config.circuit_cooldown_secs))
+            .with_fallback(config.fallback_model.clone())
+    }
+
+    /// Answer with this model while the circuit is open
+    pub fn with_fallback(mut self, model: Option<String>) -> Self {
+        self.fallback_model = model;
+        self
+    }
+
+    pub fn fallback_model(&self) -> Option<&str> {
+        self.fallback_model.as_deref()
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.lock().state
+    }
+
+    /// Failed requests since the last success
+    pub fn failures(&self) -> u32 {
+        self.lock().failures
+    }
+
+    /// Whether a request may go to the model now. Once the cooldown is over the first caller is
+    /// let through as the probe.
+    pub fn allow(&self, now: Instant) -> bool {
+        let mut breaker = self.lock();
+        match breaker.state {
+            CircuitState::Closed => true,
+            CircuitState::Open { until } | CircuitState::HalfOpen { until } if now < until => false,
+            _ => {
+                breaker.state = CircuitState::HalfOpen { until: now + self.cooldown };
+                true
+            }
+        }
+    }
+
+    /// The backend answered, even if with an error that isn't its fault
+    pub fn record_success(&self) {
+        let mut breaker = self.lock();
+        breaker.state = CircuitState::Closed;
+        breaker.failures = 0;
+    }
+
+    /// A request failed after its retries; returns whether this opened the circuit
+    pub fn record_failure(&self, now: Instant) -> bool {
+        let mut breaker = self.lock();
+        breaker.failures = breaker.failures.saturating_add(1);
+        let probe_failed = matches!(breaker.state, CircuitState::HalfOpen { .. });
+        let was_closed = breaker.state == CircuitState::Closed;
+        if probe_failed || (was_closed && breaker.failures >= self.threshold) {
+            breaker.state = CircuitState::Open { until: now + self.cooldown };
+            return true;
+        }
+        false
+    }
+
+    /// One line for `/status`
+    pub fn status_line(&self, now: Instant) -> String {
+        let (state, failures) = {
+            let breaker = self.lock();
+            (breaker.state, breaker.failures)
+        };
+        let fallback = match &self.fallback_model {
+            Some(model) => format!("answering with `{model}`"),
+            None => "replies paused".to_string(),
+        };
+        match state {
+            CircuitState::Closed if failures == 0 => "✅ Healthy".to_string(),
+            CircuitState::Closed => format!("🟡 Healthy, {failures} failed request{} in a row", if failures == 1 { "" } else { "s" }),
+            CircuitState::Open { until } => {
+                format!("🔴 Degraded, {fallback} · retrying in {}s", until.saturating_duration_since(now).as_secs())
+            }
+            CircuitState::HalfOpen { .. } => format!("🟡 Recovering, {fallback} while one request tests the service"),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Breaker> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+pub struct ResilientProvider {
+    inner: Arc<dyn LlmProvider>,
+    retry: RetryPolicy,
+    breaker: CircuitBreaker,
+}
+
+impl ResilientProvider {
+    pub fn wrap(inner: Arc<dyn LlmProvider>, retry: RetryPolicy, breaker: CircuitBreaker) -> Arc<dyn LlmProvider> {
+        Arc::new(ResilientProvider { inner, retry, breaker })
+    }
+
+    async fn attempt(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        match tokio::time::timeout(self.retry.attempt_timeout, self.inner.chat(request)).await {
+            Ok(result) => result,
+            Err(_) => Err(BotError::openai(format!(
+                "{} API request timed out after {} seconds",
+                self.inner.name(),
+                self.retry.attempt_timeout.as_secs()
+            ))),
+        }
+    }
+
+    async fn attempt_with_retries(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let mut retries = 0;
+        loop {
+            match self.attempt(request).await {
+                Err(e) if e.is_transient() && retries < self.retry.max_retries => {
+                    retries += 1;
+                    let Some(delay) = self.retry.delay(retries, &e, rand::rng().random::<f64>()) else {
+                        return Err(e);
+                    };
+                    warn!("{} request failed ({e}), retry {retries}/{} in {delay:?}", self.inner.name(), self.retry.max_retries);
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Answer with the fallback model while the circuit is open
+    async fn fallback(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let Some(model) = self.breaker.fallback_model().filter(|model| *model != request.model) else {
+            return Err(BotError::unavailable(format!("{} is paused after repeated failures", self.inner.name())));
+        };
+        let mut request = request.clone();
+        request.model = model.to_string();
+        self.attempt(&request).await.map_err(|e| {
+            if e.is_transient() {
+                BotError::unavailable(format!("fallback model {model} failed: {e}"))
+            } else {
+                e
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ResilientProvider {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn supports_response_schema(&self) -> bool {
+        self.inner.supports_response_schema()
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        if !self.breaker.allow(Instant::now()) {
+            return self.fallback(request).await;
+        }
+        let result = self.attempt_with_retries(request).await;
+        match &result {
+            Err(e) if e.is_transient() => {
+                if self.breaker.record_failure(Instant::now()) {
+                    warn!("{} circuit opened after {} failed requests: {e}", self.inner.name(), self.breaker.failures());
+                }
+            }
+            _ => {
+                if self.breaker.state() != CircuitState::Closed {
+                    info!("{} circuit closed", self.inner.name());
+                }
+                self.breaker.record_success();
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::llm::ChatMessage;
+    use std::collections::VecDeque;
+
+    /// Fails with the queued errors, then answers with the model it was asked for
+    struct Flaky {
+        errors: Mutex<VecDeque<BotError>>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl Flaky {
+        fn new(errors: Vec<BotError>) -> Arc<Self> {
+            Arc::new(Flaky { errors: Mutex::new(errors.into()), calls: Mutex::new(Vec::new()) })
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for Flaky {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+            self.calls.lock().unwrap().push(request.model.clone());
+            match self.errors.lock().unwrap().pop_front() {
+                Some(e) => Err(e),
+                None => Ok(ChatResponse { content: Some(request.model.clone()), ..ChatResponse::default() }),
+            }
+        }
+    }
+
+    fn quick_retries(max_retries: u32) -> RetryPolicy {
+        RetryPolicy { max_retries, base: Duration::from_millis(1), max: Duration::from_millis(5), ..RetryPolicy::default() }
+    }
+
+    fn request() -> ChatRequest {
+        ChatRequest::new("primary", vec![ChatMessage::user("Hello")])
+    }
+
+    #[test]
+    fn test_delay_backs_off_with_jitter() {
+        let policy = RetryPolicy::default();
+        let outage = BotError::unavailable("HTTP 503");
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(10), Duration::from_secs(8));
+        assert_eq!(policy.delay(2, &outage, 0.0), Some(Duration::from_millis(500)));
+        assert_eq!(policy.delay(2, &outage, 1.0), Some(Duration::from_secs(1)));
+
+        let short = BotError::RateLimited { retry_after: Some(Duration::from_secs(3)) };
+        assert_eq!(policy.delay(1, &short, 0.5), Some(Duration::from_secs(3)));
+        let long = BotError::RateLimited { retry_after: Some(Duration::from_secs(60)) };
+        assert_eq!(policy.delay(1, &long, 0.5), None);
+    }
+
+    #[test]
+    fn test_breaker_opens_probes_and_closes() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        let now = Instant::now();
+        assert!(!breaker.record_failure(now));
+        assert!(breaker.record_failure(now));
+        assert!(!breaker.allow(now + Duration::from_secs(30)));
+        assert!(breaker.status_line(now + Duration::from_secs(30)).contains("retrying in 30s"));
+
+        // One probe after the cooldown; a failed probe reopens the circuit at once
+        assert!(breaker.allow(now + Duration::from_secs(61)));
+        assert!(!breaker.allow(now + Duration::from_secs(62)));
+        assert!(breaker.record_failure(now + Duration::from_secs(62)));
+        assert!(!breaker.allow(now + Duration::from_secs(100)));
+
+        assert!(breaker.allow(now + Duration::from_secs(123)));
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.status_line(now), "✅ Healthy");
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        let flaky = Flaky::new(vec![BotError::RateLimited { retry_after: None }, BotError::unavailable("HTTP 502")]);
+        let provider = ResilientProvider::wrap(flaky.clone(), quick_retries(2), CircuitBreaker::default());
+        assert_eq!(provider.chat(&request()).await.unwrap().content.as_deref(), Some("primary"));
+        assert_eq!(flaky.calls().len(), 3);
+
+        // Bad requests won't get better by asking again
+        let flaky = Flaky::new(vec![BotError::openai("Invalid model")]);
+        let provider = ResilientProvider::wrap(flaky.clone(), quick_retries(2), CircuitBreaker::default());
+        assert!(provider.chat(&request()).await.is_err());
+        assert_eq!(flaky.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_uses_fallback_model() {
+        let outage = || BotError::unavailable("HTTP 503");
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+        let flaky = Flaky::new(vec![outage(), outage()]);
+        let provider = ResilientProvider::wrap(flaky.clone(), quick_retries(1), breaker.clone());
+        assert!(matches!(provider.chat(&request()).await, Err(BotError::Unavailable(_))));
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+
+        // Without a fallback the model isn't called at all
+        assert!(matches!(provider.chat(&request()).await, Err(BotError::Unavailable(_))));
+        assert_eq!(flaky.calls().len(), 2);
+
+        let provider = ResilientProvider::wrap(flaky.clone(), quick_retries(1), breaker.with_fallback(Some("backup".to_string())));
+        assert_eq!(provider.chat(&request()).await.unwrap().content.as_deref(), Some("backup"));
+        assert_eq!(flaky.calls(), vec!["primary", "primary", "backup"]);
+    }
+}
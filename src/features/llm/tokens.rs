@@ -0,0 +1,183 @@
+//! # Feature: Token Budgeting
+//!
+//! Counts prompt tokens with the model's own tokenizer (tiktoken) and trims
+//! the oldest conversation history until the prompt fits the model's context
+//! window minus [`REPLY_RESERVE_TOKENS`], so long histories no longer end in a
+//! 400 from the API. Models tiktoken doesn't know, such as Claude or local
+//! Ollama models, are counted with `o200k_base`, which is close enough to keep
+//! the budget honest. The same counts stand in for the provider's usage report
+//! when a backend doesn't send one.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use super::provider::{ChatMessage, ChatRole, FunctionDefinition, TokenUsage};
+use tiktoken_rs::model::get_context_size;
+use tiktoken_rs::{bpe_for_model, o200k_base_singleton, CoreBPE};
+
+/// Tokens kept free for the reply
+pub const REPLY_RESERVE_TOKENS: usize = 1024;
+
+/// Context window assumed for models with no known size
+pub const DEFAULT_CONTEXT_WINDOW: usize = 8192;
+
+/// Context window of Claude models
+const CLAUDE_CONTEXT_WINDOW: usize = 200_000;
+
+/// Framing tokens around every message (role and separators)
+const TOKENS_PER_MESSAGE: usize = 3;
+
+/// Tokens that prime the reply
+const REPLY_PRIMING_TOKENS: usize = 3;
+
+/// Flat cost of an attached image, a high-detail 1024px tile set on OpenAI
+pub const IMAGE_TOKENS: usize = 765;
+
+/// A prompt after fitting it into the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptBudget {
+    /// Measured prompt size, function definitions included
+    pub prompt_tokens: usize,
+    /// Prompt tokens the window allows
+    pub limit: usize,
+    /// Oldest history messages removed to fit
+    pub dropped: usize,
+}
+
+fn tokenizer(model: &str) -> &'static CoreBPE {
+    bpe_for_model(model).unwrap_or_else(|_| o200k_base_singleton())
+}
+
+/// Context window of `model`, in tokens
+pub fn context_window(model: &str) -> usize {
+    if let Some(size) = get_context_size(model) {
+        return size;
+    }
+    if model.starts_with("claude") {
+        return CLAUDE_CONTEXT_WINDOW;
+    }
+    DEFAULT_CONTEXT_WINDOW
+}
+
+/// Tokens in a piece of text
+pub fn count_text(model: &str, text: &str) -> usize {
+    tokenizer(model).encode_with_special_tokens(text).len()
+}
+
+fn count_message(bpe: &CoreBPE, message: &ChatMessage) -> usize {
+    let text = |s: &str| bpe.encode_with_special_tokens(s).len();
+    let mut tokens = TOKENS_PER_MESSAGE;
+    tokens += message.content.as_deref().map(text).unwrap_or(0);
+    tokens += message.name.as_deref().map(|name| text(name) + 1).unwrap_or(0);
+    if let Some(call) = &message.function_call {
+        tokens += text(&call.name) + text(&call.arguments);
+    }
+    tokens + message.images.len() * IMAGE_TOKENS
+}
+
+/// Tokens a request's messages and function definitions take up
+pub fn count_prompt(model: &str, messages: &[ChatMessage], functions: &[FunctionDefinition]) -> usize {
+    let bpe = tokenizer(model);
+    let messages: usize = messages.iter().map(|m| count_message(bpe, m)).sum();
+    let functions: usize = functions
+        .iter()
+        .map(|f| bpe.encode_with_special_tokens(&format!("{} {} {}", f.name, f.description, f.parameters)).len())
+        .sum();
+    messages + functions + REPLY_PRIMING_TOKENS
+}
+
+/// Drop the oldest messages between the system prompt and the current user
+/// message until the prompt fits `model`'s window less the reply reserve.
+/// The system prompt, the current message and anything after it (tool calls
+/// and results) are always kept, even if they alone are too long.
+pub fn fit_to_window(model: &str, messages: &mut Vec<ChatMessage>, functions: &[FunctionDefinition]) -> PromptBudget {
+    let limit = context_window(model).saturating_sub(REPLY_RESERVE_TOKENS);
+    let bpe = tokenizer(model);
+    let sizes: Vec<usize> = messages.iter().map(|m| count_message(bpe, m)).collect();
+    let fixed = count_prompt(model, &[], functions);
+    let mut prompt_tokens = fixed + sizes.iter().sum::<usize>();
+
+    let first = usize::from(messages.first().is_some_and(|m| m.role == ChatRole::System));
+    let current = messages.iter().rposition(|m| m.role == ChatRole::User).unwrap_or(messages.len());
+    let droppable = current.saturating_sub(first);
+    let mut dropped = 0;
+    while prompt_tokens > limit && dropped < droppable {
+        prompt_tokens -= sizes[first + dropped];
+        dropped += 1;
+    }
+    messages.drain(first..first + dropped);
+    PromptBudget { prompt_tokens, limit, dropped }
+}
+
+/// Usage measured locally, for backends that don't report it
+pub fn measured_usage(model: &str, prompt_tokens: usize, completion: &str) -> TokenUsage {
+    let prompt_tokens = prompt_tokens as u32;
+    let completion_tokens = count_text(model, completion) as u32;
+    TokenUsage { prompt_tokens, completion_tokens, total_tokens: prompt_tokens + completion_tokens }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_window() {
+        assert_eq!(context_window("gpt-4o-mini"), 128_000);
+        assert_eq!(context_window("claude-sonnet-4-5"), CLAUDE_CONTEXT_WINDOW);
+        assert_eq!(context_window("llama3.1"), DEFAULT_CONTEXT_WINDOW);
+    }
+
+    #[test]
+    fn test_count_text() {
+        assert_eq!(count_text("gpt-4o", "hello world"), 2);
+        assert_eq!(count_text("unknown-model", "hello world"), 2);
+        assert_eq!(count_text("gpt-4o", ""), 0);
+    }
+
+    #[test]
+    fn test_fit_keeps_short_prompts() {
+        let mut messages = vec![ChatMessage::system("Be brief."), ChatMessage::assistant("Hi"), ChatMessage::user("Hello")];
+        let budget = fit_to_window("gpt-4o", &mut messages, &[]);
+        assert_eq!(budget.dropped, 0);
+        assert_eq!(messages.len(), 3);
+        assert_eq!(budget.prompt_tokens, count_prompt("gpt-4o", &messages, &[]));
+    }
+
+    #[test]
+    fn test_fit_drops_oldest_history_first() {
+        // Each turn is about 5000 tokens, so only one fits an unknown model's 8192 window
+        let long = "word ".repeat(5000);
+        let mut messages = vec![
+            ChatMessage::system("Be brief."),
+            ChatMessage::user(format!("first {long}")),
+            ChatMessage::assistant(format!("second {long}")),
+            ChatMessage::user("What now?"),
+        ];
+        let budget = fit_to_window("llama3.1", &mut messages, &[]);
+        assert_eq!(budget.dropped, 1);
+        assert_eq!(budget.limit, DEFAULT_CONTEXT_WINDOW - REPLY_RESERVE_TOKENS);
+        assert!(budget.prompt_tokens <= budget.limit);
+        assert_eq!(messages.len(), 3);
+        assert!(messages[1].content.as_deref().unwrap().starts_with("second"));
+        assert_eq!(messages[2].content.as_deref(), Some("What now?"));
+    }
+
+    #[test]
+    fn test_fit_never_drops_the_current_message() {
+        let mut messages = vec![ChatMessage::system("Be brief."), ChatMessage::user("word ".repeat(10_000))];
+        let budget = fit_to_window("llama3.1", &mut messages, &[]);
+        assert_eq!(budget.dropped, 0);
+        assert_eq!(messages.len(), 2);
+        assert!(budget.prompt_tokens > budget.limit);
+    }
+
+    #[test]
+    fn test_measured_usage() {
+        let usage = measured_usage("gpt-4o", 100, "hello world");
+        assert_eq!(usage, TokenUsage { prompt_tokens: 100, completion_tokens: 2, total_tokens: 102 });
+    }
+}
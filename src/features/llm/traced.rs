@@ -0,0 +1,93 @@
+//! # Feature: Traced Provider
+//!
+//! Wraps any [`LlmProvider`] so each chat completion runs in an `llm.chat`
+//! span: a child of the interaction that asked for it, tagged with the
+//! provider, model, token counts and whether it failed.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use super::provider::{ChatRequest, ChatResponse, LlmProvider};
+use crate::core::Result;
+use serenity::async_trait;
+use std::sync::Arc;
+use tracing::{field, info_span, Instrument};
+
+pub struct TracedProvider {
+    inner: Arc<dyn LlmProvider>,
+}
+
+impl TracedProvider {
+    pub fn wrap(inner: Arc<dyn LlmProvider>) -> Arc<dyn LlmProvider> {
+        Arc::new(TracedProvider { inner })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for TracedProvider {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn supports_response_schema(&self) -> bool {
+        self.inner.supports_response_schema()
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let span = info_span!(
+            "llm.chat",
+            provider = self.inner.name(),
+            model = request.model.as_str(),
+            messages = request.messages.len(),
+            input_tokens = field::Empty,
+            output_tokens = field::Empty,
+            error = field::Empty,
+        );
+        let result = self.inner.chat(request).instrument(span.clone()).await;
+        match &result {
+            Ok(response) => {
+                if let Some(usage) = &response.usage {
+                    span.record("input_tokens", usage.prompt_tokens);
+                    span.record("output_tokens", usage.completion_tokens);
+                }
+            }
+            Err(e) => {
+                span.record("error", field::display(e));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::llm::{ChatMessage, MockProvider};
+    use crate::features::telemetry::{SpanTracker, SpanValue};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_chat_span_records_usage() {
+        let (sender, mut finished) = mpsc::unbounded_channel();
+        let _guard = tracing::subscriber::set_default(SpanTracker::new(Some(sender)));
+        let provider = TracedProvider::wrap(Arc::new(MockProvider::new(Duration::ZERO)));
+
+        let request = ChatRequest::new("mock-model", vec![ChatMessage::user("Hello there, how are you?")]);
+        let response = provider.chat(&request).await.unwrap();
+
+        let span = finished.try_recv().unwrap();
+        let attribute = |key| span.attributes.iter().find(|(name, _)| *name == key).map(|(_, value)| value.clone());
+        assert_eq!(span.name, "llm.chat");
+        assert_eq!(attribute("provider"), Some(SpanValue::Str("mock".to_string())));
+        assert_eq!(attribute("model"), Some(SpanValue::Str("mock-model".to_string())));
+        let usage = response.usage.unwrap();
+        assert_eq!(attribute("input_tokens"), Some(SpanValue::Int(usage.prompt_tokens as i64)));
+        assert_eq!(attribute("output_tokens"), Some(SpanValue::Int(usage.completion_tokens as i64)));
+        assert_eq!(attribute("error"), None);
+    }
+}
@@ -0,0 +1,272 @@
+//! # Feature: Load Test Harness
+//!
+//! Drives the mention pipeline without Discord: each synthetic request looks
+//! up the user's persona, stores the message, reads channel history, builds
+//! the system prompt, logs usage, asks the mock model and stores the reply,
+//! the same database and model calls a real mention makes. Requests run on a
+//! fixed number of concurrent workers spread over synthetic users and channels.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with throughput and p50/p95/p99 latency
+
+use crate::command_handler::CommandHandler;
+use crate::core::MemoryLimits;
+use crate::database::Database;
+use crate::features::analytics::{InteractionTracker, UsageTracker};
+use crate::features::llm::MockProvider;
+use crate::features::personas::PersonaManager;
+use crate::features::supervisor::{RestartPolicy, Supervisor};
+use crate::core::Result;
+use log::warn;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+/// Questions cycled through by the synthetic users
+const QUESTIONS: &[&str] = &[
+    "What's a good way to learn Rust?",
+    "Can you explain how a hash map works?",
+    "Give me a quick pasta recipe",
+    "Why is the sky blue?",
+    "How do I undo my last git commit?",
+];
+
+/// History messages read per request, as the default `max_context_messages`
+const HISTORY_LIMIT: i64 = 40;
+
+#[derive(Debug, Clone, Copy)]
+pub struct LoadTestOptions {
+    pub requests: usize,
+    /// Requests in flight at once
+    pub concurrency: usize,
+    pub users: usize,
+    pub channels: usize,
+}
+
+impl Default for LoadTestOptions {
+    fn default() -> Self {
+        LoadTestOptions { requests: 1000, concurrency: 16, users: 50, channels: 10 }
+    }
+}
+
+/// Latency distribution of the successful requests
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LatencyStats {
+    pub count: usize,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return LatencyStats::default();
+        }
+        samples.sort();
+        let total: Duration = samples.iter().sum();
+        LatencyStats {
+            count: samples.len(),
+            mean: total / samples.len() as u32,
+            p50: percentile(&samples, 50.0),
+            p95: percentile(&samples, 95.0),
+            p99: percentile(&samples, 99.0),
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadReport {
+    pub options: LoadTestOptions,
+    pub failures: usize,
+    pub elapsed: Duration,
+    pub latency: LatencyStats,
+}
+
+impl LoadReport {
+    /// Completed requests per second
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.latency.count as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    pub fn format(&self) -> String {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        format!(
+            "Load test: {} requests, {} concurrent, {} users in {} channels\n\
+            Completed: {} ({} failed) in {:.2}s\n\
+            Throughput: {:.1} req/s\n\
+            Latency: mean {:.1} ms | p50 {:.1} ms | p95 {:.1} ms | p99 {:.1} ms | max {:.1} ms\n",
+            self.options.requests,
+            self.options.concurrency,
+            self.options.users,
+            self.options.channels,
+            self.latency.count,
+            self.failures,
+            self.elapsed.as_secs_f64(),
+            self.throughput(),
+            ms(self.latency.mean),
+            ms(self.latency.p50),
+            ms(self.latency.p95),
+            ms(self.latency.p99),
+            ms(self.latency.max),
+        )
+    }
+}
+
+/// A `CommandHandler` on the mock LLM with every optional integration off
+pub fn mock_command_handler(database: Database, persona_manager: PersonaManager, llm_latency: Duration) -> CommandHandler {
+    CommandHandler::new(
+        database.clone(),
+        String::new(),
+        "mock".to_string(),
+        false,
+        "medium",
+        5,
+        UsageTracker::new(database.clone()),
+        InteractionTracker::new(database),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        persona_manager,
+        Supervisor::new(RestartPolicy::new(2, 300, None)),
+        Arc::new(MockProvider::new(llm_latency)),
+        "mock".to_string(),
+        MemoryLimits::defaults(false),
+    )
+}
+
+/// Run request number `index` through the mention pipeline
+pub async fn simulate_mention(
+    handler: &CommandHandler,
+    database: &Database,
+    persona_manager: &PersonaManager,
+    options: &LoadTestOptions,
+    index: usize,
+) -> Result<String> {
+    let user_id = format!("{}", 100_000 + index % options.users.max(1));
+    let channel_id = format!("{}", 200_000 + index % options.channels.max(1));
+    let guild_id = "300000";
+    let question = QUESTIONS[index % QUESTIONS.len()];
+    let request_id = Uuid::new_v4();
+
+    let persona = database.get_user_persona_with_guild(&user_id, Some(guild_id)).await?;
+    database
+        .store_message_with_id(&user_id, &channel_id, "user", question, Some(&persona), Some(&index.to_string()))
+        .await?;
+    let history = database.get_conversation_history(&user_id, &channel_id, HISTORY_LIMIT).await?;
+    let system_prompt = persona_manager.get_system_prompt_with_verbosity(&persona, None, "concise");
+    database.log_usage(&user_id, "mention_chat", Some(&persona)).await?;
+
+    let reply = handler
+        .get_ai_response_with_context(&system_prompt, question, history, request_id, Some(&user_id), Some(guild_id), Some(&channel_id))
+        .await?;
+    database.store_message(&user_id, &channel_id, "assistant", &reply, Some(&persona)).await?;
+    Ok(reply)
+}
+
+/// Run `options.requests` synthetic mentions on `options.concurrency` workers
+pub async fn run_load_test(
+    handler: Arc<CommandHandler>,
+    database: Database,
+    persona_manager: PersonaManager,
+    options: LoadTestOptions,
+) -> LoadReport {
+    let next = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+    let mut workers = JoinSet::new();
+    for _ in 0..options.concurrency.max(1) {
+        let (handler, database, persona_manager, next) =
+            (handler.clone(), database.clone(), persona_manager.clone(), next.clone());
+        workers.spawn(async move {
+            let mut samples = Vec::new();
+            let mut failures = 0;
+            loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= options.requests {
+                    break;
+                }
+                let request_started = Instant::now();
+                match simulate_mention(&handler, &database, &persona_manager, &options, index).await {
+                    Ok(_) => samples.push(request_started.elapsed()),
+                    Err(e) => {
+                        warn!("Load test request {index} failed: {e}");
+                        failures += 1;
+                    }
+                }
+            }
+            (samples, failures)
+        });
+    }
+
+    let mut samples = Vec::with_capacity(options.requests);
+    let mut failures = 0;
+    while let Some(result) = workers.join_next().await {
+        match result {
+            Ok((worker_samples, worker_failures)) => {
+                samples.extend(worker_samples);
+                failures += worker_failures;
+            }
+            Err(e) => {
+                warn!("Load test worker crashed: {e}");
+                failures += 1;
+            }
+        }
+    }
+
+    LoadReport { options, failures, elapsed: started.elapsed(), latency: LatencyStats::from_samples(samples) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_stats() {
+        let samples: Vec<Duration> = (1..=100).rev().map(Duration::from_millis).collect();
+        let stats = LatencyStats::from_samples(samples);
+        assert_eq!(stats.count, 100);
+        assert_eq!(stats.p50, Duration::from_millis(50));
+        assert_eq!(stats.p99, Duration::from_millis(99));
+        assert_eq!(stats.max, Duration::from_millis(100));
+        assert_eq!(stats.mean, Duration::from_micros(50_500));
+        assert_eq!(LatencyStats::from_samples(Vec::new()).count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_run_load_test_with_mock() {
+        let database = Database::new(":memory:", 1).await.unwrap();
+        let persona_manager = PersonaManager::new();
+        let handler = Arc::new(mock_command_handler(database.clone(), persona_manager.clone(), Duration::ZERO));
+        let options = LoadTestOptions { requests: 20, concurrency: 4, users: 3, channels: 2 };
+
+        let report = run_load_test(handler, database.clone(), persona_manager, options).await;
+        assert_eq!(report.failures, 0);
+        assert_eq!(report.latency.count, 20);
+        assert!(report.format().contains("Throughput"));
+        let history = database.get_conversation_history("100000", "200000", 100).await.unwrap();
+        assert!(history.iter().any(|(role, content)| role == "assistant" && content.starts_with("[mock]")));
+    }
+}
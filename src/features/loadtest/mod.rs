@@ -0,0 +1,13 @@
+//! # Load Test Feature
+//!
+//! `bot loadtest` replays synthetic mentions through `CommandHandler` against
+//! the mock LLM and a scratch database, reporting throughput and latency
+//! percentiles, so database and cache changes can be measured before they ship.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod harness;
+
+pub use harness::{mock_command_handler, run_load_test, simulate_mention, LatencyStats, LoadReport, LoadTestOptions};
@@ -0,0 +1,276 @@
+//! # Feature: Lockdown
+//!
+//! Locks every channel in the configured categories (and the categories
+//! themselves) by denying @everyone the send permissions. Each channel's
+//! previous @everyone overwrite is stored before it is changed, so ending the
+//! lockdown restores it exactly - or removes the overwrite if there wasn't one -
+//! even after a restart.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with category lockdown, stored overwrites and channel notices
+
+use crate::database::{Database, LockdownOverwrite};
+use crate::features::moderation::post_mod_log;
+use crate::core::Result;
+use log::{info, warn};
+use serenity::http::Http;
+use serenity::model::channel::{ChannelType, GuildChannel, PermissionOverwrite, PermissionOverwriteType};
+use serenity::model::id::{ChannelId, GuildId, RoleId};
+use serenity::model::Permissions;
+
+/// Permissions denied to @everyone while locked
+pub const LOCKED_PERMISSIONS: Permissions = Permissions::SEND_MESSAGES
+    .union(Permissions::SEND_MESSAGES_IN_THREADS)
+    .union(Permissions::CREATE_PUBLIC_THREADS)
+    .union(Permissions::CREATE_PRIVATE_THREADS);
+
+/// Outcome of starting or ending a lockdown
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LockdownReport {
+    /// Channels locked (start) or restored (end)
+    pub changed: usize,
+    /// Channels whose permissions couldn't be changed
+    pub failed: Vec<u64>,
+}
+
+/// Parse the comma-separated `lockdown_categories` setting
+pub fn parse_category_ids(value: &str) -> Vec<u64> {
+    value
+        .split(',')
+        .filter_map(|id| id.trim().parse::<u64>().ok())
+        .collect()
+}
+
+/// @everyone (allow, deny) bits to apply while locked, keeping everything else
+pub fn locked_bits(allow: u64, deny: u64) -> (u64, u64) {
+    let locked = LOCKED_PERMISSIONS.bits();
+    (allow & !locked, deny | locked)
+}
+
+/// Channels to lock: the categories plus every channel inside them
+pub fn select_lockdown_channels(channels: &[(u64, Option<u64>)], categories: &[u64]) -> Vec<u64> {
+    let mut selected: Vec<u64> = channels
+        .iter()
+        .filter(|(id, parent)| categories.contains(id) || parent.is_some_and(|p| categories.contains(&p)))
+        .map(|(id, _)| *id)
+        .collect();
+    selected.sort_unstable();
+    selected
+}
+
+/// Text channels that receive the lockdown notices
+fn is_notice_channel(channel: &GuildChannel) -> bool {
+    matches!(channel.kind, ChannelType::Text | ChannelType::News)
+}
+
+/// The channel's current @everyone overwrite, if any
+fn everyone_overwrite(channel: &GuildChannel, guild: GuildId) -> Option<&PermissionOverwrite> {
+    channel
+        .permission_overwrites
+        .iter()
+        .find(|o| o.kind == PermissionOverwriteType::Role(RoleId(guild.0)))
+}
+
+/// Notice posted in locked channels
+pub fn format_lockdown_notice(reason: Option<&str>) -> String {
+    match reason {
+        Some(reason) => format!("🔒 **This server is in lockdown.** Sending messages is paused while moderators respond.\n**Reason:** {reason}"),
+        None => "🔒 **This server is in lockdown.** Sending messages is paused while moderators respond.".to_string(),
+    }
+}
+
+/// Summary for the moderator (and mod log)
+pub fn format_lockdown_report(starting: bool, moderator_id: &str, report: &LockdownReport) -> String {
+    let mut text = if starting {
+        format!("🔒 Lockdown started by <@{moderator_id}>: {} channels locked.", report.changed)
+    } else {
+        format!("🔓 Lockdown ended by <@{moderator_id}>: {} channels restored.", report.changed)
+    };
+    if !report.failed.is_empty() {
+        let failed = report.failed.iter().map(|id| format!("<#{id}>")).collect::<Vec<_>>().join(", ");
+        text.push_str(&format!("\n⚠️ Couldn't update: {failed} (check the bot's Manage Roles permission there)"));
+        if !starting {
+            text.push_str("\nRun `/lockdown end` again to retry.");
+        }
+    }
+    text
+}
+
+/// Lock every channel in `categories`, storing previous overwrites first.
+/// Returns `None` if the guild is already in lockdown.
+pub async fn start_lockdown(
+    http: &Http,
+    db: &Database,
+    guild: GuildId,
+    categories: &[u64],
+    moderator_id: &str,
+    reason: Option<&str>,
+) -> Result<Option<LockdownReport>> {
+    let guild_id = guild.to_string();
+    if !db.begin_lockdown(&guild_id, moderator_id, reason.unwrap_or_default()).await? {
+        return Ok(None);
+    }
+
+    let channels = guild.channels(http).await?;
+    let parents: Vec<(u64, Option<u64>)> = channels.values().map(|c| (c.id.0, c.parent_id.map(|p| p.0))).collect();
+    let targets = select_lockdown_channels(&parents, categories);
+
+    // Post notices while members (and the bot) can still send
+    let notice = format_lockdown_notice(reason);
+    for id in &targets {
+        if let Some(channel) = channels.get(&ChannelId(*id)).filter(|c| is_notice_channel(c)) {
+            if let Err(e) = channel.say(http, &notice).await {
+                warn!("Failed to post lockdown notice in channel {id}: {e}");
+            }
+        }
+    }
+
+    let mut report = LockdownReport::default();
+    for id in targets {
+        let Some(channel) = channels.get(&ChannelId(id)) else { continue };
+        let previous = everyone_overwrite(channel, guild);
+        let saved = LockdownOverwrite {
+            channel_id: id.to_string(),
+            had_overwrite: previous.is_some(),
+            allow_bits: previous.map(|o| o.allow.bits()).unwrap_or(0) as i64,
+            deny_bits: previous.map(|o| o.deny.bits()).unwrap_or(0) as i64,
+        };
+        // Stored before changing so a crash mid-lockdown can still be undone
+        db.save_lockdown_overwrite(&guild_id, &saved).await?;
+
+        let (allow, deny) = locked_bits(saved.allow_bits as u64, saved.deny_bits as u64);
+        let overwrite = PermissionOverwrite {
+            allow: Permissions::from_bits_truncate(allow),
+            deny: Permissions::from_bits_truncate(deny),
+            kind: PermissionOverwriteType::Role(RoleId(guild.0)),
+        };
+        match channel.id.create_permission(http, &overwrite).await {
+            Ok(()) => report.changed += 1,
+            Err(e) => {
+                warn!("Failed to lock channel {id} in guild {guild_id}: {e}");
+                db.remove_lockdown_overwrite(&guild_id, &saved.channel_id).await?;
+                report.failed.push(id);
+            }
+        }
+    }
+
+    if report.changed == 0 {
+        db.finish_lockdown(&guild_id).await?;
+    }
+    info!("🔒 Lockdown started in guild {guild_id}: {} locked, {} failed", report.changed, report.failed.len());
+    if let Err(e) = post_mod_log(http, db, &guild_id, &format_lockdown_report(true, moderator_id, &report)).await {
+        warn!("Failed to post lockdown to mod log for guild {guild_id}: {e}");
+    }
+    Ok(Some(report))
+}
+
+/// Restore every stored overwrite. Channels that fail stay recorded so the
+/// lockdown can be ended again; returns `None` if there was no lockdown.
+pub async fn end_lockdown(http: &Http, db: &Database, guild: GuildId, moderator_id: &str) -> Result<Option<LockdownReport>> {
+    let guild_id = guild.to_string();
+    if db.get_lockdown(&guild_id).await?.is_none() {
+        return Ok(None);
+    }
+
+    let channels = guild.channels(http).await?;
+    let mut report = LockdownReport::default();
+    let mut restored_notice_channels = Vec::new();
+    for saved in db.get_lockdown_overwrites(&guild_id).await? {
+        let id = saved.channel_id.parse::<u64>()?;
+        let Some(channel) = channels.get(&ChannelId(id)) else {
+            // Deleted during the lockdown - nothing left to restore
+            db.remove_lockdown_overwrite(&guild_id, &saved.channel_id).await?;
+            continue;
+        };
+
+        let result = if saved.had_overwrite {
+            let overwrite = PermissionOverwrite {
+                allow: Permissions::from_bits_truncate(saved.allow_bits as u64),
+                deny: Permissions::from_bits_truncate(saved.deny_bits as u64),
+                kind: PermissionOverwriteType::Role(RoleId(guild.0)),
+            };
+            channel.id.create_permission(http, &overwrite).await
+        } else {
+            channel
+                .id
+                .delete_permission(http, PermissionOverwriteType::Role(RoleId(guild.0)))
+                .await
+        };
+        match result {
+            Ok(()) => {
+                db.remove_lockdown_overwrite(&guild_id, &saved.channel_id).await?;
+                report.changed += 1;
+                if is_notice_channel(channel) {
+                    restored_notice_channels.push(channel.id);
+                }
+            }
+            Err(e) => {
+                warn!("Failed to restore channel {id} in guild {guild_id}: {e}");
+                report.failed.push(id);
+            }
+        }
+    }
+
+    if report.failed.is_empty() {
+        db.finish_lockdown(&guild_id).await?;
+    }
+    for channel in restored_notice_channels {
+        if let Err(e) = channel.say(http, "🔓 **Lockdown lifted.** Thanks for your patience!").await {
+            warn!("Failed to post lockdown end notice in channel {channel}: {e}");
+        }
+    }
+
+    info!("🔓 Lockdown ended in guild {guild_id}: {} restored, {} failed", report.changed, report.failed.len());
+    if let Err(e) = post_mod_log(http, db, &guild_id, &format_lockdown_report(false, moderator_id, &report)).await {
+        warn!("Failed to post lockdown end to mod log for guild {guild_id}: {e}");
+    }
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_category_ids() {
+        assert_eq!(parse_category_ids("1, 2,x,3"), vec![1, 2, 3]);
+        assert!(parse_category_ids("disabled").is_empty());
+    }
+
+    #[test]
+    fn test_locked_bits_keep_other_permissions() {
+        let send = Permissions::SEND_MESSAGES.bits();
+        let view = Permissions::VIEW_CHANNEL.bits();
+        let (allow, deny) = locked_bits(send | view, 0);
+        assert_eq!(allow, view);
+        assert_eq!(deny, LOCKED_PERMISSIONS.bits());
+    }
+
+    #[test]
+    fn test_select_lockdown_channels() {
+        let channels = [(10, None), (11, Some(10)), (12, Some(10)), (20, None), (21, Some(20)), (30, None)];
+        assert_eq!(select_lockdown_channels(&channels, &[10, 30]), vec![10, 11, 12, 30]);
+        assert!(select_lockdown_channels(&channels, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_format_lockdown_report() {
+        let report = LockdownReport { changed: 3, failed: vec![] };
+        assert_eq!(format_lockdown_report(true, "1", &report), "🔒 Lockdown started by <@1>: 3 channels locked.");
+
+        let report = LockdownReport { changed: 1, failed: vec![9] };
+        let text = format_lockdown_report(false, "1", &report);
+        assert!(text.contains("Couldn't update: <#9>"));
+        assert!(text.ends_with("Run `/lockdown end` again to retry."));
+    }
+
+    #[test]
+    fn test_format_lockdown_notice() {
+        assert!(format_lockdown_notice(Some("Raid")).ends_with("**Reason:** Raid"));
+        assert!(!format_lockdown_notice(None).contains("Reason"));
+    }
+}
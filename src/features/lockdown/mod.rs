@@ -0,0 +1,15 @@
+//! # Lockdown Feature
+//!
+//! Emergency `/lockdown start|end`: removes send-message permissions for
+//! @everyone across the guild's `lockdown_categories` in one action, then
+//! restores the exact previous overwrites when the lockdown ends.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod channels;
+
+pub use channels::{
+    end_lockdown, format_lockdown_report, parse_category_ids, start_lockdown, LockdownReport,
+};
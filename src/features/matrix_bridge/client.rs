@@ -0,0 +1,299 @@
+//! # Feature: Matrix Client
+//!
+//! Minimal Matrix client-server API client used by the bridge. With a plain
+//! access token the bot posts as itself; with an appservice `as_token` and a
+//! puppet prefix it registers one virtual user per Discord author
+//! (`@<prefix><discord id>:<server>`) and posts as them with their display name.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with sync, sending, room joins, media downloads and puppets
+
+use crate::core::{BotError, Result};
+use dashmap::{DashMap, DashSet};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+/// A timeline event from `/sync`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MatrixEvent {
+    #[serde(rename = "type", default)]
+    pub kind: String,
+    #[serde(default)]
+    pub sender: String,
+    #[serde(default)]
+    pub event_id: String,
+    #[serde(default)]
+    pub content: Value,
+}
+
+/// New timeline events per joined room, and the token to sync from next
+#[derive(Debug, Clone, Default)]
+pub struct SyncBatch {
+    pub next_batch: String,
+    pub rooms: HashMap<String, Vec<MatrixEvent>>,
+}
+
+#[derive(Clone)]
+pub struct MatrixClient {
+    homeserver: String,
+    token: String,
+    client: reqwest::Client,
+    /// Localpart prefix for puppets; None posts everything as the bot user
+    puppet_prefix: Option<String>,
+    /// The bot's own user ID, from whoami
+    bot_user: Arc<OnceLock<String>>,
+    /// Matrix user ID -> display name
+    display_names: Arc<DashMap<String, String>>,
+    /// Puppets already registered and named: Discord user ID -> display name set
+    puppets: Arc<DashMap<u64, String>>,
+    /// (puppet, room) pairs already joined
+    joined: Arc<DashSet<(String, String)>>,
+}
+
+/// Percent-encode a path segment (room IDs and aliases contain `!`, `#` and `:`)
+pub fn encode_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Server name part of a Matrix ID (`@bot:example.org` -> `example.org`)
+pub fn server_name(matrix_id: &str) -> Option<&str> {
+    matrix_id.split_once(':').map(|(_, server)| server).filter(|s| !s.is_empty())
+}
+
+/// Parse the sync response body into per-room timeline events
+pub fn parse_sync(body: &Value) -> SyncBatch {
+    let mut rooms = HashMap::new();
+    if let Some(joined) = body.pointer("/rooms/join").and_then(Value::as_object) {
+        for (room_id, room) in joined {
+            let events: Vec<MatrixEvent> = room
+                .pointer("/timeline/events")
+                .cloned()
+                .and_then(|events| serde_json::from_value(events).ok())
+                .unwrap_or_default();
+            if !events.is_empty() {
+                rooms.insert(room_id.clone(), events);
+            }
+        }
+    }
+    SyncBatch {
+        next_batch: body.get("next_batch").and_then(Value::as_str).unwrap_or_default().to_string(),
+        rooms,
+    }
+}
+
+impl MatrixClient {
+    pub fn new(homeserver: String, token: String, puppet_prefix: Option<String>) -> Self {
+        MatrixClient {
+            homeserver: homeserver.trim_end_matches('/').to_string(),
+            token,
+            client: reqwest::Client::new(),
+            puppet_prefix,
+            bot_user: Arc::new(OnceLock::new()),
+            display_names: Arc::new(DashMap::new()),
+            puppets: Arc::new(DashMap::new()),
+            joined: Arc::new(DashSet::new()),
+        }
+    }
+
+    async fn request(&self, method: reqwest::Method, path: &str, as_user: Option<&str>, body: Option<Value>) -> Result<Value> {
+        let mut request = self
+            .client
+            .request(method, format!("{}/_matrix/client/v3/{path}", self.homeserver))
+            .bearer_auth(&self.token);
+        if let Some(user) = as_user {
+            request = request.query(&[("user_id", user)]);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let code = body.get("errcode").and_then(Value::as_str).unwrap_or("unknown");
+            return Err(BotError::internal(format!("Matrix {} failed ({}): {}", path, status, code)));
+        }
+        Ok(body)
+    }
+
+    /// The bot's own Matrix user ID (looked up once with whoami)
+    pub async fn bot_user(&self) -> Result<String> {
+        if let Some(user) = self.bot_user.get() {
+            return Ok(user.clone());
+        }
+        let body = self.request(reqwest::Method::GET, "account/whoami", None, None).await?;
+        let user = body
+            .get("user_id")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| BotError::internal("No user_id in whoami response"))?;
+        let _ = self.bot_user.set(user.clone());
+        Ok(user)
+    }
+
+    /// Whether a Matrix user is the bot or one of its puppets
+    pub fn is_own_user(&self, bot_user: &str, user_id: &str) -> bool {
+        user_id == bot_user
+            || self
+                .puppet_prefix
+                .as_deref()
+                .is_some_and(|prefix| user_id.trim_start_matches('@').starts_with(prefix))
+    }
+
+    /// Join a room by ID or alias, returning its room ID
+    pub async fn join_room(&self, room: &str, as_user: Option<&str>) -> Result<String> {
+        let path = format!("join/{}", encode_segment(room));
+        let body = self.request(reqwest::Method::POST, &path, as_user, Some(json!({}))).await?;
+        Ok(body.get("room_id").and_then(Value::as_str).unwrap_or(room).to_string())
+    }
+
+    /// Long-poll for new events. Without `since`, only the current position is useful.
+    pub async fn sync(&self, since: Option<&str>, timeout_ms: u64) -> Result<SyncBatch> {
+        let mut path = format!("sync?timeout={timeout_ms}");
+        if let Some(since) = since {
+            path.push_str(&format!("&since={}", encode_segment(since)));
+        }
+        let body = self.request(reqwest::Method::GET, &path, None, None).await?;
+        Ok(parse_sync(&body))
+    }
+
+    /// Send an `m.text` message with an HTML body, optionally as a puppet
+    pub async fn send_text(&self, room_id: &str, body: &str, html: &str, as_user: Option<&str>) -> Result<()> {
+        let path = format!(
+            "rooms/{}/send/m.room.message/{}",
+            encode_segment(room_id),
+            uuid::Uuid::new_v4()
+        );
+        let content = json!({
+            "msgtype": "m.text",
+            "body": body,
+            "format": "org.matrix.custom.html",
+            "formatted_body": html,
+        });
+        self.request(reqwest::Method::PUT, &path, as_user, Some(content)).await.map(|_| ())
+    }
+
+    /// Display name of a Matrix user, cached for the life of the process
+    pub async fn display_name(&self, user_id: &str) -> String {
+        if let Some(name) = self.display_names.get(user_id) {
+            return name.clone();
+        }
+        let path = format!("profile/{}/displayname", encode_segment(user_id));
+        let name = match self.request(reqwest::Method::GET, &path, None, None).await {
+            Ok(body) => body.get("displayname").and_then(Value::as_str).map(str::to_string),
+            Err(_) => None,
+        }
+        .unwrap_or_else(|| user_id.trim_start_matches('@').split(':').next().unwrap_or(user_id).to_string());
+        self.display_names.insert(user_id.to_string(), name.clone());
+        name
+    }
+
+    /// Make sure a Discord author's puppet exists, carries their display name and is in the room.
+    /// Returns the puppet's user ID, or None when puppeting is off.
+    pub async fn ensure_puppet(&self, discord_id: u64, name: &str, room_id: &str) -> Result<Option<String>> {
+        let Some(prefix) = &self.puppet_prefix else {
+            return Ok(None);
+        };
+        let bot_user = self.bot_user().await?;
+        let server = server_name(&bot_user).ok_or_else(|| BotError::internal(format!("Bad Matrix user ID: {}", bot_user)))?;
+        let localpart = format!("{prefix}{discord_id}");
+        let puppet = format!("@{localpart}:{server}");
+
+        let known_name = self.puppets.get(&discord_id).map(|n| n.clone());
+        if known_name.is_none() {
+            let registration = json!({ "type": "m.login.application_service", "username": localpart });
+            if let Err(e) = self.request(reqwest::Method::POST, "register", None, Some(registration)).await {
+                // Already registered on an earlier run
+                if !e.to_string().contains("M_USER_IN_USE") {
+                    return Err(e);
+                }
+            }
+        }
+        if known_name.as_deref() != Some(name) {
+            let path = format!("profile/{}/displayname", encode_segment(&puppet));
+            let body = json!({ "displayname": format!("{name} (Discord)") });
+            self.request(reqwest::Method::PUT, &path, Some(&puppet), Some(body)).await?;
+            self.puppets.insert(discord_id, name.to_string());
+        }
+
+        let key = (puppet.clone(), room_id.to_string());
+        if !self.joined.contains(&key) {
+            self.join_room(room_id, Some(&puppet)).await?;
+            self.joined.insert(key);
+        }
+        Ok(Some(puppet))
+    }
+
+    /// Download `mxc://server/id` media
+    pub async fn download(&self, mxc: &str) -> Result<Vec<u8>> {
+        let media = mxc
+            .strip_prefix("mxc://")
+            .ok_or_else(|| BotError::internal(format!("Not an mxc URL: {}", mxc)))?;
+        let response = self
+            .client
+            .get(format!("{}/_matrix/client/v1/media/download/{media}", self.homeserver))
+            .bearer_auth(&self.token)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(BotError::internal(format!("Matrix media download failed (status {})", status)));
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_segment() {
+        assert_eq!(encode_segment("!abc:example.org"), "%21abc%3Aexample.org");
+        assert_eq!(encode_segment("#room:ex.org"), "%23room%3Aex.org");
+    }
+
+    #[test]
+    fn test_server_name() {
+        assert_eq!(server_name("@bot:example.org"), Some("example.org"));
+        assert_eq!(server_name("@bot"), None);
+    }
+
+    #[test]
+    fn test_is_own_user() {
+        let client = MatrixClient::new("https://ex.org/".into(), "t".into(), Some("discord_".into()));
+        assert!(client.is_own_user("@bot:ex.org", "@bot:ex.org"));
+        assert!(client.is_own_user("@bot:ex.org", "@discord_42:ex.org"));
+        assert!(!client.is_own_user("@bot:ex.org", "@ana:ex.org"));
+        assert_eq!(client.homeserver, "https://ex.org");
+    }
+
+    #[test]
+    fn test_parse_sync() {
+        let body = json!({
+            "next_batch": "s72",
+            "rooms": { "join": {
+                "!a:ex.org": { "timeline": { "events": [
+                    { "type": "m.room.message", "sender": "@ana:ex.org", "event_id": "$1", "content": { "msgtype": "m.text", "body": "hi" } }
+                ] } },
+                "!b:ex.org": { "timeline": { "events": [] } }
+            } }
+        });
+        let batch = parse_sync(&body);
+        assert_eq!(batch.next_batch, "s72");
+        assert_eq!(batch.rooms.len(), 1);
+        assert_eq!(batch.rooms["!a:ex.org"][0].sender, "@ana:ex.org");
+    }
+}
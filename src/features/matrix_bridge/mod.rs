@@ -0,0 +1,19 @@
+//! # Matrix Bridge Feature
+//!
+//! Mirrors Discord channels into Matrix rooms for self-hosted communities,
+//! mapped with `/matrix link`. Uses `MATRIX_HOMESERVER_URL` and
+//! `MATRIX_ACCESS_TOKEN`; with an appservice token and `MATRIX_PUPPET_PREFIX`
+//! each Discord author gets a puppet user carrying their display name.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod client;
+pub mod relay;
+
+pub use client::{MatrixClient, MatrixEvent, SyncBatch};
+pub use relay::{
+    discord_to_matrix, format_from_matrix, matrix_bridge_loop, parse_matrix_room, relay_to_matrix, should_relay,
+    strip_reply_fallback, MAX_RELAY_FILE_BYTES,
+};
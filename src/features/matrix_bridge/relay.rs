@@ -0,0 +1,296 @@
+//! # Feature: Matrix Relay
+//!
+//! Mirrors messages between Discord channels and Matrix rooms. Discord messages
+//! are sent to the room by the author's puppet (or by the bot with the author's
+//! name in front); Matrix messages arrive through `/sync` and are posted to
+//! Discord with the sender's display name, re-uploading images and files. The
+//! sync position is kept in `bot_settings` so nothing is replayed on restart.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with two-way relay, display name puppeting and media
+
+use super::client::{MatrixClient, MatrixEvent};
+use crate::core::discord_limits::{truncate, MESSAGE_CONTENT};
+use crate::database::Database;
+use crate::core::Result;
+use log::{debug, info, warn};
+use serde_json::Value;
+use serenity::http::Http;
+use serenity::model::channel::{AttachmentType, Message};
+use serenity::model::id::ChannelId;
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// `bot_settings` key holding the `/sync` position
+const SYNC_TOKEN_KEY: &str = "matrix_sync_token";
+
+/// How long one `/sync` long-poll waits for events
+const SYNC_TIMEOUT_MS: u64 = 30_000;
+
+/// Pause after a failed sync
+const RETRY_DELAY_SECS: u64 = 15;
+
+/// Largest Matrix file re-uploaded to Discord
+pub const MAX_RELAY_FILE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Message types relayed to Discord (`m.notice` is left out, it's what bots send)
+const RELAYED_MSGTYPES: &[&str] = &["m.text", "m.emote", "m.image", "m.file", "m.video", "m.audio"];
+
+/// Parse a room ID (`!abc:server`), alias (`#room:server`) or matrix.to link
+pub fn parse_matrix_room(input: &str) -> Option<String> {
+    let input = input.trim();
+    let room = input.strip_prefix("https://matrix.to/#/").unwrap_or(input);
+    let room = room.split('?').next()?;
+    let (local, server) = room.split_once(':')?;
+    let valid = (local.starts_with('!') || local.starts_with('#'))
+        && local.len() > 1
+        && !server.is_empty()
+        && !room.contains(char::is_whitespace);
+    valid.then(|| room.to_string())
+}
+
+/// Whether a timeline event should be relayed to Discord
+pub fn should_relay(event: &MatrixEvent) -> bool {
+    let msgtype = event.content.get("msgtype").and_then(Value::as_str).unwrap_or_default();
+    let is_edit = event.content.pointer("/m.relates_to/rel_type").and_then(Value::as_str) == Some("m.replace");
+    event.kind == "m.room.message" && RELAYED_MSGTYPES.contains(&msgtype) && !is_edit
+}
+
+/// Drop the quoted `> ` fallback Matrix clients put at the start of replies
+pub fn strip_reply_fallback(body: &str) -> &str {
+    if !body.starts_with("> ") {
+        return body;
+    }
+    match body.find("\n\n") {
+        Some(end) => &body[end + 2..],
+        None => body,
+    }
+}
+
+/// Discord message for a relayed Matrix message, within the 2000 character limit
+pub fn format_from_matrix(name: &str, event: &MatrixEvent) -> String {
+    let body = event.content.get("body").and_then(Value::as_str).unwrap_or_default();
+    let body = strip_reply_fallback(body);
+    let message = match event.content.get("msgtype").and_then(Value::as_str) {
+        Some("m.emote") => format!("**{name}** · Matrix\n*{name} {body}*"),
+        Some("m.text") => format!("**{name}** · Matrix\n{body}"),
+        // Media: the body is the filename, the file itself is attached
+        _ => format!("**{name}** · Matrix"),
+    };
+    truncate(&message, MESSAGE_CONTENT)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Plain and HTML bodies for a Discord message. `mentions` maps Discord user IDs to names,
+/// `attachments` are (filename, url) pairs, and `author` is prefixed when not puppeting.
+pub fn discord_to_matrix(
+    content: &str,
+    mentions: &[(u64, String)],
+    attachments: &[(String, String)],
+    author: Option<&str>,
+) -> (String, String) {
+    let mut text = content.to_string();
+    for (id, name) in mentions {
+        text = text
+            .replace(&format!("<@{id}>"), &format!("@{name}"))
+            .replace(&format!("<@!{id}>"), &format!("@{name}"));
+    }
+    let mut body = text.clone();
+    let mut html = escape_html(&text).replace('\n', "<br>");
+    for (filename, url) in attachments {
+        if !body.is_empty() {
+            body.push('\n');
+            html.push_str("<br>");
+        }
+        body.push_str(&format!("📎 {filename}: {url}"));
+        html.push_str(&format!("📎 <a href=\"{}\">{}</a>", escape_html(url), escape_html(filename)));
+    }
+    match author {
+        Some(author) if !body.is_empty() => (
+            format!("{author}: {body}"),
+            format!("<strong>{}</strong>: {html}", escape_html(author)),
+        ),
+        _ => (body, html),
+    }
+}
+
+/// Relay a Discord message if its channel is mapped to a room. Returns whether it was relayed.
+pub async fn relay_to_matrix(matrix: &MatrixClient, db: &Database, msg: &Message) -> Result<bool> {
+    let Some(link) = db.get_matrix_link(&msg.channel_id.to_string()).await? else {
+        return Ok(false);
+    };
+    let name = msg
+        .member
+        .as_ref()
+        .and_then(|m| m.nick.clone())
+        .unwrap_or_else(|| msg.author.name.clone());
+    let puppet = matrix.ensure_puppet(msg.author.id.0, &name, &link.matrix_room_id).await?;
+
+    let mentions: Vec<(u64, String)> = msg.mentions.iter().map(|u| (u.id.0, u.name.clone())).collect();
+    let attachments: Vec<(String, String)> =
+        msg.attachments.iter().map(|a| (a.filename.clone(), a.url.clone())).collect();
+    let author = if puppet.is_some() { None } else { Some(name.as_str()) };
+    let (body, html) = discord_to_matrix(&msg.content, &mentions, &attachments, author);
+    if body.is_empty() {
+        return Ok(false);
+    }
+
+    matrix.send_text(&link.matrix_room_id, &body, &html, puppet.as_deref()).await?;
+    debug!("Relayed message {} to Matrix room {}", msg.id, link.matrix_room_id);
+    Ok(true)
+}
+
+/// Post one Matrix event to a Discord channel
+async fn relay_event(http: &Http, matrix: &MatrixClient, channel: ChannelId, event: &MatrixEvent) -> Result<()> {
+    let name = matrix.display_name(&event.sender).await;
+    let mut text = format_from_matrix(&name, event);
+
+    let mut file = None;
+    if let Some(mxc) = event.content.get("url").and_then(Value::as_str) {
+        let filename = event.content.get("body").and_then(Value::as_str).unwrap_or("file").to_string();
+        let size = event.content.pointer("/info/size").and_then(Value::as_u64).unwrap_or(0);
+        if size > MAX_RELAY_FILE_BYTES {
+            text.push_str(&format!("\n📎 *{filename} (too large to relay)*"));
+        } else {
+            match matrix.download(mxc).await {
+                Ok(data) => file = Some((filename, data)),
+                Err(e) => {
+                    warn!("Failed to download Matrix media {mxc}: {e}");
+                    text.push_str(&format!("\n📎 *{filename} (couldn't be relayed)*"));
+                }
+            }
+        }
+    }
+
+    channel
+        .send_message(http, |m| {
+            m.content(text).allowed_mentions(|a| a.empty_parse());
+            if let Some((filename, data)) = file {
+                m.add_file(AttachmentType::Bytes { data: Cow::Owned(data), filename });
+            }
+            m
+        })
+        .await?;
+    Ok(())
+}
+
+/// Background task that syncs with the homeserver and relays new room messages to Discord
+pub async fn matrix_bridge_loop(http: Arc<Http>, db: Arc<Database>, matrix: MatrixClient) {
+    info!("Matrix bridge task started");
+    let mut since = db.get_bot_setting(SYNC_TOKEN_KEY).await.ok().flatten();
+
+    loop {
+        let batch = match matrix.sync(since.as_deref(), SYNC_TIMEOUT_MS).await {
+            Ok(batch) => batch,
+            Err(e) => {
+                warn!("Matrix sync failed: {}", e);
+                tokio::time::sleep(Duration::from_secs(RETRY_DELAY_SECS)).await;
+                continue;
+            }
+        };
+        let bot_user = match matrix.bot_user().await {
+            Ok(user) => user,
+            Err(e) => {
+                warn!("Matrix whoami failed: {}", e);
+                tokio::time::sleep(Duration::from_secs(RETRY_DELAY_SECS)).await;
+                continue;
+            }
+        };
+
+        // The first sync only establishes a position; its timeline is history
+        if since.is_some() {
+            let links = db.get_matrix_links(None).await.unwrap_or_else(|e| {
+                warn!("Failed to load Matrix bridges: {}", e);
+                Vec::new()
+            });
+            for (room_id, events) in &batch.rooms {
+                for link in links.iter().filter(|l| &l.matrix_room_id == room_id) {
+                    let Ok(channel) = link.discord_channel_id.parse::<u64>() else {
+                        continue;
+                    };
+                    for event in events.iter().filter(|e| should_relay(e) && !matrix.is_own_user(&bot_user, &e.sender)) {
+                        if let Err(e) = relay_event(&http, &matrix, ChannelId(channel), event).await {
+                            warn!("Failed to relay Matrix event {} to channel {}: {}", event.event_id, channel, e);
+                        }
+                    }
+                }
+            }
+        }
+
+        if !batch.next_batch.is_empty() {
+            if let Err(e) = db.set_bot_setting(SYNC_TOKEN_KEY, &batch.next_batch).await {
+                warn!("Failed to store Matrix sync token: {}", e);
+            }
+            since = Some(batch.next_batch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(content: Value) -> MatrixEvent {
+        MatrixEvent {
+            kind: "m.room.message".into(),
+            sender: "@ana:ex.org".into(),
+            event_id: "$1".into(),
+            content,
+        }
+    }
+
+    #[test]
+    fn test_parse_matrix_room() {
+        assert_eq!(parse_matrix_room("!abc:ex.org"), Some("!abc:ex.org".to_string()));
+        assert_eq!(
+            parse_matrix_room("https://matrix.to/#/#general:ex.org?via=ex.org"),
+            Some("#general:ex.org".to_string())
+        );
+        assert_eq!(parse_matrix_room("general"), None);
+        assert_eq!(parse_matrix_room("@ana:ex.org"), None);
+    }
+
+    #[test]
+    fn test_should_relay() {
+        assert!(should_relay(&event(json!({"msgtype": "m.text", "body": "hi"}))));
+        assert!(!should_relay(&event(json!({"msgtype": "m.notice", "body": "bot"}))));
+        assert!(!should_relay(&event(
+            json!({"msgtype": "m.text", "body": "* hi", "m.relates_to": {"rel_type": "m.replace"}})
+        )));
+    }
+
+    #[test]
+    fn test_format_from_matrix() {
+        let reply = event(json!({"msgtype": "m.text", "body": "> <@bo:ex.org> hey\n\nhello"}));
+        assert_eq!(format_from_matrix("ana", &reply), "**ana** · Matrix\nhello");
+        let emote = event(json!({"msgtype": "m.emote", "body": "waves"}));
+        assert_eq!(format_from_matrix("ana", &emote), "**ana** · Matrix\n*ana waves*");
+        let image = event(json!({"msgtype": "m.image", "body": "cat.png", "url": "mxc://ex.org/x"}));
+        assert_eq!(format_from_matrix("ana", &image), "**ana** · Matrix");
+    }
+
+    #[test]
+    fn test_discord_to_matrix() {
+        let (body, html) = discord_to_matrix(
+            "hi <@42> <3",
+            &[(42, "sam".to_string())],
+            &[("a.png".to_string(), "https://cdn/a.png".to_string())],
+            None,
+        );
+        assert_eq!(body, "hi @sam <3\n📎 a.png: https://cdn/a.png");
+        assert_eq!(html, "hi @sam &lt;3<br>📎 <a href=\"https://cdn/a.png\">a.png</a>");
+
+        let (body, html) = discord_to_matrix("yo", &[], &[], Some("kim"));
+        assert_eq!(body, "kim: yo");
+        assert_eq!(html, "<strong>kim</strong>: yo");
+    }
+}
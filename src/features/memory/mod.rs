@@ -0,0 +1,16 @@
+//! # Memory Feature
+//!
+//! Long-term conversation memory: each exchange is stored with an embedding of
+//! the user's message, and the most similar earlier exchanges are recalled into
+//! the chat context. Users can search and forget their memories with `/memory`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod recall;
+
+pub use recall::{
+    format_memory_context, format_memory_search, rank_memories, recall_memories, MAX_RECALLED, MEMORY_SCAN_LIMIT,
+    MEMORY_THRESHOLD, SEARCH_RESULTS,
+};
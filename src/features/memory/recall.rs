@@ -0,0 +1,170 @@
+//! # Feature: Memory Recall
+//!
+//! Pure helpers for long-term memory: ranking stored exchanges by similarity to
+//! a new message, skipping ones already in the recent history, and formatting
+//! them for the system prompt and `/memory search`.
+//!
+//! - **Version**: 1.0.1
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.1: Snippets are cut with the shared `discord_limits::truncate`
+//! - 1.0.0: Initial release with cosine similarity recall
+
+use crate::core::discord_limits::truncate;
+use crate::database::MemoryEntry;
+use crate::features::duplicates::{cosine_similarity, decode_embedding};
+
+/// Minimum cosine similarity for an earlier exchange to be recalled into context
+pub const MEMORY_THRESHOLD: f32 = 0.5;
+
+/// Earlier exchanges added to the chat context at most
+pub const MAX_RECALLED: usize = 3;
+
+/// Results shown by `/memory search`
+pub const SEARCH_RESULTS: usize = 5;
+
+/// Most recent memories scored per lookup by default (`EMBEDDING_SCAN_LIMIT`)
+pub const MEMORY_SCAN_LIMIT: i64 = 1000;
+
+/// Characters kept from each side of a recalled exchange
+const SNIPPET_CHARS: usize = 300;
+
+/// Memories at or above `threshold`, most similar first, at most `limit`
+pub fn rank_memories<'a, I>(query: &[f32], memories: I, threshold: f32, limit: usize) -> Vec<(&'a MemoryEntry, f32)>
+where
+    I: IntoIterator<Item = &'a MemoryEntry>,
+{
+    let mut ranked: Vec<(&MemoryEntry, f32)> = memories
+        .into_iter()
+        .filter_map(|m| decode_embedding(&m.embedding).map(|e| (m, cosine_similarity(query, &e))))
+        .filter(|(_, similarity)| *similarity >= threshold)
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Memories worth recalling for a message: similar enough, and not already part
+/// of the recent `(role, content)` history sent with it
+pub fn recall_memories<'a>(query: &[f32], memories: &'a [MemoryEntry], history: &[(String, String)]) -> Vec<(&'a MemoryEntry, f32)> {
+    let recent: Vec<&str> = history
+        .iter()
+        .filter(|(role, _)| role == "user")
+        .map(|(_, content)| content.trim())
+        .collect();
+    let fresh = memories.iter().filter(|m| !recent.contains(&m.user_text.trim()));
+    rank_memories(query, fresh, MEMORY_THRESHOLD, MAX_RECALLED)
+}
+
+fn snippet(text: &str) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate(&flat, SNIPPET_CHARS)
+}
+
+/// `YYYY-MM-DD` from a stored `YYYY-MM-DD HH:MM:SS` timestamp
+fn date_of(created_at: &str) -> &str {
+    created_at.get(..10).unwrap_or(created_at)
+}
+
+/// System prompt addition listing recalled exchanges, empty when there are none
+pub fn format_memory_context(recalled: &[(&MemoryEntry, f32)]) -> String {
+    if recalled.is_empty() {
+        return String::new();
+    }
+    let mut context = String::from(
+        "\n\nEarlier conversations with this user that may be relevant (bring them up only if they help):",
+    );
+    for (memory, _) in recalled {
+        context.push_str(&format!(
+            "\n- On {} they said: \"{}\" and you replied: \"{}\"",
+            date_of(&memory.created_at),
+            snippet(&memory.user_text),
+            snippet(&memory.reply_text)
+        ));
+    }
+    context
+}
+
+/// `/memory search` reply
+pub fn format_memory_search(query: &str, results: &[(&MemoryEntry, f32)]) -> String {
+    if results.is_empty() {
+        return format!("🧠 I don't remember anything about \"{}\" here.", snippet(query));
+    }
+    let mut reply = format!("🧠 **Memories matching \"{}\"**\n", snippet(query));
+    for (memory, similarity) in results {
+        let said = truncate(&memory.user_text, 150);
+        reply.push_str(&format!(
+            "\n`#{}` · {} · <#{}> · {:.0}% match\n> {}\n",
+            memory.id,
+            date_of(&memory.created_at),
+            memory.channel_id,
+            similarity * 100.0,
+            said.replace('\n', " ")
+        ));
+    }
+    reply.push_str("\nUse `/memory forget id:<number>` to remove one, or `/memory forget` to remove them all.");
+    reply
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::duplicates::encode_embedding;
+
+    fn memory(id: i64, text: &str, embedding: &[f32]) -> MemoryEntry {
+        MemoryEntry {
+            id,
+            channel_id: "42".to_string(),
+            user_text: text.to_string(),
+            reply_text: format!("reply to {text}"),
+            embedding: encode_embedding(embedding),
+            created_at: "2026-03-02 10:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rank_memories_orders_and_limits() {
+        let memories = vec![
+            memory(1, "unrelated", &[0.0, 1.0]),
+            memory(2, "close", &[0.9, 0.1]),
+            memory(3, "closest", &[1.0, 0.0]),
+            MemoryEntry { embedding: "garbage".to_string(), ..memory(4, "broken", &[]) },
+        ];
+        let ranked = rank_memories(&[1.0, 0.0], &memories, 0.5, 1);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].0.id, 3);
+        assert_eq!(rank_memories(&[1.0, 0.0], &memories, 0.5, 5).len(), 2);
+    }
+
+    #[test]
+    fn test_recall_skips_messages_in_history() {
+        let memories = vec![memory(1, "what is rust?", &[1.0, 0.0]), memory(2, "how do lifetimes work?", &[0.95, 0.05])];
+        let history = vec![("user".to_string(), "what is rust?".to_string())];
+        let recalled = recall_memories(&[1.0, 0.0], &memories, &history);
+        assert_eq!(recalled.iter().map(|(m, _)| m.id).collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_format_memory_context() {
+        assert_eq!(format_memory_context(&[]), "");
+        let entry = memory(7, "my cat is called Miso", &[1.0]);
+        let context = format_memory_context(&[(&entry, 0.8)]);
+        assert!(context.contains("On 2026-03-02 they said: \"my cat is called Miso\""));
+        assert!(context.contains("you replied: \"reply to my cat is called Miso\""));
+
+        let text = "word ".repeat(100);
+        let long = memory(8, &text, &[1.0]);
+        let context = format_memory_context(&[(&long, 0.8)]);
+        assert!(context.contains(&format!("they said: \"{}…\"", &text[..SNIPPET_CHARS - 1])));
+    }
+
+    #[test]
+    fn test_format_memory_search() {
+        assert!(format_memory_search("cats", &[]).contains("don't remember"));
+        let entry = memory(7, "my cat is called Miso", &[1.0]);
+        let reply = format_memory_search("cats", &[(&entry, 0.81)]);
+        assert!(reply.contains("`#7` · 2026-03-02 · <#42> · 81% match"));
+    }
+}
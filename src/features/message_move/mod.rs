@@ -0,0 +1,14 @@
+//! # Message Move Feature
+//!
+//! "Move to…" message context-menu action for moderators: reposts a message as
+//! an attributed embed in another channel, deletes the original and leaves a
+//! pointer behind. Handy for redirecting off-topic AI conversations to the bot
+//! channel.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod mover;
+
+pub use mover::{format_move_notice, move_message, parse_channel_input, MOVE_MODAL_PREFIX};
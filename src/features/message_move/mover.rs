@@ -0,0 +1,105 @@
+//! # Feature: Message Move
+//!
+//! Reposts a message in another channel as an embed carrying the original
+//! author, channel and timestamp, then deletes the original and posts a short
+//! notice where it used to be.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: `truncate_for_embed` removed; use `discord_limits::truncate`
+//! - 1.0.0: Initial release with embed reposts, attachment links and move notices
+
+use crate::core::discord_limits::{fit_embed, truncate, EMBED_DESCRIPTION};
+use crate::core::Result;
+use log::warn;
+use serenity::http::Http;
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, UserId};
+
+/// Target channel modal: `move_message_modal:<channel>:<message>`
+pub const MOVE_MODAL_PREFIX: &str = "move_message_modal:";
+
+/// Parse a channel mention (`<#123>`) or raw channel ID
+pub fn parse_channel_input(input: &str) -> Option<u64> {
+    let input = input.trim();
+    let id = input
+        .strip_prefix("<#")
+        .and_then(|rest| rest.strip_suffix('>'))
+        .unwrap_or(input);
+    id.parse::<u64>().ok().filter(|id| *id != 0)
+}
+
+/// Notice left in the original channel
+pub fn format_move_notice(author_id: u64, target: u64, moderator_id: u64) -> String {
+    format!("📦 A message from <@{author_id}> was moved to <#{target}> by <@{moderator_id}>. Please continue there!")
+}
+
+/// Repost `source` in `target`, delete the original and leave a notice. Returns the new message.
+pub async fn move_message(http: &Http, source: &Message, target: ChannelId, moderator: UserId) -> Result<Message> {
+    let image = source
+        .attachments
+        .iter()
+        .find(|a| a.content_type.as_deref().is_some_and(|t| t.starts_with("image/")))
+        .map(|a| a.url.clone());
+    let files: Vec<String> = source
+        .attachments
+        .iter()
+        .filter(|a| Some(&a.url) != image.as_ref())
+        .map(|a| format!("[{}]({})", a.filename, a.url))
+        .collect();
+    let description = if source.content.is_empty() {
+        "*(no text)*".to_string()
+    } else {
+        truncate(&source.content, EMBED_DESCRIPTION)
+    };
+
+    let moved = target
+        .send_message(http, |m| {
+            m.embed(|e| {
+                e.author(|a| a.name(&source.author.name).icon_url(source.author.face()))
+                    .description(description)
+                    .field("Moved from", format!("<#{}>", source.channel_id), true)
+                    .field("Moved by", format!("<@{moderator}>"), true)
+                    .timestamp(source.timestamp);
+                if !files.is_empty() {
+                    e.field("Attachments", files.join("\n"), false);
+                }
+                if let Some(image) = &image {
+                    e.image(image);
+                }
+                fit_embed(e)
+            })
+        })
+        .await?;
+
+    source.delete(http).await?;
+    let notice = format_move_notice(source.author.id.0, target.0, moderator.0);
+    if let Err(e) = source.channel_id.say(http, notice).await {
+        warn!("Failed to post move notice in channel {}: {e}", source.channel_id);
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_channel_input() {
+        assert_eq!(parse_channel_input("<#123>"), Some(123));
+        assert_eq!(parse_channel_input(" 456 "), Some(456));
+        assert_eq!(parse_channel_input("#general"), None);
+        assert_eq!(parse_channel_input("0"), None);
+    }
+
+    #[test]
+    fn test_format_move_notice() {
+        assert_eq!(
+            format_move_notice(1, 2, 3),
+            "📦 A message from <@1> was moved to <#2> by <@3>. Please continue there!"
+        );
+    }
+}
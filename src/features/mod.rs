@@ -13,28 +13,82 @@
 
 // Feature submodules
 pub mod analytics;
+pub mod attachment_scan;
+pub mod auto_slowmode;
+pub mod auto_verbosity;
+pub mod bookmarks;
+pub mod budgets;
 pub mod audio;
+pub mod calculator;
+pub mod calendar;
+pub mod catch_up;
+pub mod channel_archive;
+pub mod citations;
+pub mod code_runner;
+pub mod community_insights;
 pub mod conflict;
+pub mod content_moderation;
+pub mod conversation_threads;
+pub mod custom_commands;
+pub mod dashboard;
+pub mod duplicates;
+pub mod emoji_stats;
+pub mod error_explainer;
+pub mod feature_gate;
+pub mod focus;
+pub mod follow_ups;
+pub mod help_digest;
+pub mod history_import;
+pub mod hot_reload;
 pub mod image_gen;
+pub mod interactions_endpoint;
 pub mod introspection;
+pub mod issue_lookup;
+pub mod join_screening;
+pub mod knowledge_sync;
+pub mod live_events;
+pub mod llm;
+pub mod loadtest;
+pub mod lockdown;
+pub mod matrix_bridge;
+pub mod memory;
+pub mod message_move;
+pub mod panic_capture;
+pub mod moderation;
 pub mod personas;
+pub mod prometheus;
 pub mod rate_limiting;
 pub mod reminders;
+pub mod response_cache;
+pub mod slack_bridge;
+pub mod stale_settings;
 pub mod startup;
+pub mod story;
+pub mod summaries;
+pub mod supervisor;
+pub mod telemetry;
+pub mod tools;
+pub mod user_names;
+pub mod verification_gate;
+pub mod vision;
+pub mod webhook_ingest;
 
 // Re-export commonly used items from submodules
 pub use analytics::{
-    metrics_collection_loop, InteractionTracker, UsageTracker, CurrentMetrics,
+    metrics_collection_loop, monthly_invoice_loop, InteractionTracker, UsageTracker, CurrentMetrics,
     format_bytes, format_bytes_signed, format_duration, format_history,
     get_db_file_size, DiskInfo, HistoricalSummary,
 };
 pub use audio::{AudioTranscriber, TranscriptionResult};
 pub use conflict::{ConflictDetector, ConflictMediator};
+pub use feature_gate::{FeatureGate, GatedPath};
+pub use follow_ups::split_follow_ups;
 pub use image_gen::{ImageGenerator, ImageSize, ImageStyle, GeneratedImage};
 pub use introspection::get_component_snippet;
-pub use personas::{Persona, PersonaManager};
+pub use personas::{Creativity, Persona, PersonaManager};
 pub use rate_limiting::RateLimiter;
 pub use reminders::ReminderScheduler;
+pub use stale_settings::stale_settings_loop;
 pub use startup::StartupNotifier;
 
 // ============================================================================
@@ -63,15 +117,15 @@ pub const FEATURES: &[Feature] = &[
     Feature {
         id: "personas",
         name: "Persona System",
-        version: "1.0.0",
+        version: "1.7.0",
         since: "0.1.0",
         toggleable: false,
-        description: "Multi-personality AI responses with 5 distinct personas",
+        description: "Multi-personality AI responses with 5 built-in personas, plus custom personas guild admins define and share with /persona, one-off /as questions, /persona blend and per-guild response pacing",
     },
     Feature {
         id: "reminders",
         name: "Reminders",
-        version: "1.0.0",
+        version: "1.7.0",
         since: "0.1.0",
         toggleable: true,
         description: "Scheduled reminder system with persona-aware delivery",
@@ -79,18 +133,18 @@ pub const FEATURES: &[Feature] = &[
     Feature {
         id: "conflict_detection",
         name: "Conflict Detection",
-        version: "1.0.0",
+        version: "1.2.0",
         since: "0.1.0",
         toggleable: true,
-        description: "Detects heated discussions using keyword and pattern analysis",
+        description: "Detects heated discussions using keyword and pattern analysis, with borderline cases confirmed by the AI and /conflict exempt for users, roles and channels to leave out",
     },
     Feature {
         id: "conflict_mediation",
         name: "Conflict Mediation",
-        version: "1.0.0",
+        version: "1.2.0",
         since: "0.1.0",
         toggleable: true,
-        description: "Obi-Wan themed interventions for heated conversations",
+        description: "Obi-Wan themed interventions for heated conversations, plus mod channel alerts with suggested replies and mediate, dismiss or escalate buttons whose decisions are kept as feedback",
     },
     Feature {
         id: "image_generation",
@@ -103,10 +157,10 @@ pub const FEATURES: &[Feature] = &[
     Feature {
         id: "audio_transcription",
         name: "Audio Transcription",
-        version: "1.3.0",
+        version: "1.5.0",
         since: "0.1.0",
         toggleable: true,
-        description: "Whisper-powered transcription with configurable output modes",
+        description: "Whisper-powered transcription with configurable output modes, streaming live partial transcripts for short voice messages",
     },
     Feature {
         id: "introspection",
@@ -151,7 +205,7 @@ pub const FEATURES: &[Feature] = &[
     Feature {
         id: "startup_notification",
         name: "Startup Notification",
-        version: "1.1.0",
+        version: "1.2.0",
         since: "0.4.0",
         toggleable: true,
         description: "Rich notifications when bot comes online, configured via /set_guild_setting",
@@ -159,7 +213,7 @@ pub const FEATURES: &[Feature] = &[
     Feature {
         id: "usage_tracking",
         name: "Usage Tracking",
-        version: "1.0.0",
+        version: "1.2.0",
         since: "0.5.0",
         toggleable: false,
         description: "OpenAI API usage and cost tracking with /usage command",
@@ -172,6 +226,502 @@ pub const FEATURES: &[Feature] = &[
         toggleable: false,
         description: "Comprehensive DM session and engagement metrics with user-facing analytics",
     },
+    Feature {
+        id: "answer_citations",
+        name: "Answer Citations",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Footnote links to earlier Discord messages cited in answers, enabled via cite_sources",
+    },
+    Feature {
+        id: "follow_up_suggestions",
+        name: "Follow-up Suggestions",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Suggested follow-up questions as buttons on AI replies, enabled via /set_guild_setting",
+    },
+    Feature {
+        id: "auto_verbosity",
+        name: "Frustration-Aware Verbosity",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Short, direct answers with an apology when a user seems frustrated, logged for review, enabled via /set_guild_setting",
+    },
+    Feature {
+        id: "persona_webhooks",
+        name: "Persona Webhooks",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Replies posted through channel webhooks under each persona's name and avatar, enabled via /set_guild_setting",
+    },
+    Feature {
+        id: "duplicate_questions",
+        name: "Duplicate Question Detection",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Links earlier answers to repeated questions in support channels, enabled via support_channels",
+    },
+    Feature {
+        id: "response_cache",
+        name: "Response Cache",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "Answers repeated standalone questions from an in-memory LRU and SQLite cache for 24 hours, with hit and miss counts in performance_metrics, disabled via response_cache",
+    },
+    Feature {
+        id: "vision",
+        name: "Image Understanding",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "Images attached to a mention are sent to a vision-capable model so the persona can answer about them",
+    },
+    Feature {
+        id: "conversation_summaries",
+        name: "Conversation Summaries",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Folds turns that fall out of the context window into a stored persona-aware summary, shown with /summary",
+    },
+    Feature {
+        id: "thread_conversations",
+        name: "Thread Conversations",
+        version: "1.1.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Replying to the bot opens a thread whose history is kept apart from the channel (thread_conversations setting), with its own persona, verbosity and language via /thread_settings",
+    },
+    Feature {
+        id: "channel_catch_up",
+        name: "Channel Catch-Up",
+        version: "1.1.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "/summarize sums up recent channel messages in the user's persona, ephemerally; a mention with a message link and \"what happened here?\" summarizes the exchange around it",
+    },
+    Feature {
+        id: "bookmarks",
+        name: "Bookmarks",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Save messages with the Bookmark Message action and browse them with /bookmarks, with jump links",
+    },
+    Feature {
+        id: "custom_commands",
+        name: "Custom Commands",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "Admin-defined text commands run with /c or a server prefix, with {user}, {channel} and {arg1} template variables",
+    },
+    Feature {
+        id: "long_term_memory",
+        name: "Long-Term Memory",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "Recalls similar earlier exchanges into chat context, with /memory search and forget",
+    },
+    Feature {
+        id: "prompt_debugging",
+        name: "Prompt Debugging",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Admin /debug_last view of the last assembled AI prompt per channel (kept in memory only)",
+    },
+    Feature {
+        id: "cost_reports",
+        name: "Cost Reports",
+        version: "1.2.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Per-feature AI cost attribution with /costs breakdown, a weekly owner digest and a monthly owner invoice by DM and optional SMTP email",
+    },
+    Feature {
+        id: "budgets",
+        name: "AI Budgets",
+        version: "1.1.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Monthly guild and daily per-member AI spend caps that refuse or downgrade requests, with a warning at 80% and an admin notification at 100%, also emailed to the owner when SMTP is configured",
+    },
+    Feature {
+        id: "sheets_export",
+        name: "Google Sheets Export",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Appends daily per-guild analytics and AI usage rows to a Google Sheet via a service account",
+    },
+    Feature {
+        id: "s3_export",
+        name: "S3 Analytics Export",
+        version: "1.1.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Nightly CSV or Parquet export of the analytics tables to an S3-compatible bucket for BI tooling",
+    },
+    Feature {
+        id: "ops_overview",
+        name: "Operator Overview",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Owner-only /ops overview of every guild with members, cost, commands, errors and last activity",
+    },
+    Feature {
+        id: "spend_summary",
+        name: "Spend Summaries",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Personal /usage and admin /costs server embeds with per-service spend bars and a top-spenders leaderboard",
+    },
+    Feature {
+        id: "stale_settings",
+        name: "Stale Settings Detection",
+        version: "1.6.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Periodic check that settings still point at existing channels and roles, flagged in /settings",
+    },
+    Feature {
+        id: "emoji_stats",
+        name: "Emoji Statistics",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "Counts emoji in messages and reactions per guild, reported with /emojistats",
+    },
+    Feature {
+        id: "activity_heatmap",
+        name: "Activity Heatmap",
+        version: "1.1.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Hour-by-weekday heatmap image of a channel's message volume via /activity",
+    },
+    Feature {
+        id: "community_insights",
+        name: "Community Insights",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "New-member 7/30-day retention per weekly join cohort, reported with /community_insights",
+    },
+    Feature {
+        id: "auto_slowmode",
+        name: "Auto Slowmode",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "Temporarily raises slowmode during message spikes in configured channels and logs it to the mod log",
+    },
+    Feature {
+        id: "attachment_scanning",
+        name: "Attachment Scanning",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "ClamAV and NSFW scanning of uploads in attachment_scan_channels; flagged messages are deleted and reported to the mod log",
+    },
+    Feature {
+        id: "content_moderation",
+        name: "Content Moderation",
+        version: "1.1.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "Checks members' messages and AI replies from every AI feature with the OpenAI moderation endpoint, logging, warning or blocking per content_moderation and DMing moderators",
+    },
+    Feature {
+        id: "moderation",
+        name: "Moderation Commands",
+        version: "1.1.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "/timeout, /kick and /ban with required reasons and templates, DM notices, mod log entries, scheduled un-timeouts and DM appeals reviewed in appeal_review_channel",
+    },
+    Feature {
+        id: "lockdown",
+        name: "Lockdown",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "/lockdown start|end denies @everyone sending across lockdown_categories and restores the exact previous overwrites",
+    },
+    Feature {
+        id: "channel_archive",
+        name: "Channel Archive",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "/archive_channel posts an AI summary and the full message log to an archive channel, optionally deleting the original",
+    },
+    Feature {
+        id: "story_mode",
+        name: "Story Mode",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "/story runs a collaborative story in a thread narrated by a persona, tracking characters, inventory and chapter summaries",
+    },
+    Feature {
+        id: "focus_sessions",
+        name: "Focus Sessions",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "/pomodoro focus and break intervals with pings, optional muting of a study voice channel, and a /focus_stats leaderboard",
+    },
+    Feature {
+        id: "join_screening",
+        name: "Join Screening",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Scores new members by account age, avatar and username (needs GUILD_MEMBERS_INTENT); logs or holds flagged joins for a button captcha",
+    },
+    Feature {
+        id: "verification_gate",
+        name: "Verification Gate",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "New members press Verify (optionally answering an AI-generated question) in verification_channel to get member_role; unverified members are kicked after a timeout",
+    },
+    Feature {
+        id: "message_move",
+        name: "Message Move",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Moderator \"Move to…\" context menu that reposts a message as an attributed embed in another channel and deletes the original",
+    },
+    Feature {
+        id: "slack_bridge",
+        name: "Slack Bridge",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "/bridge link relays messages both ways between Discord and Slack channels with author attribution and attachments (needs SLACK_BOT_TOKEN)",
+    },
+    Feature {
+        id: "matrix_bridge",
+        name: "Matrix Bridge",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "/matrix link mirrors Discord channels into Matrix rooms, with display name puppets when using an appservice token",
+    },
+    Feature {
+        id: "knowledge_sync",
+        name: "Knowledge Base Sync",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "One-way export of answered support questions to a Notion database or git-backed markdown folder",
+    },
+    Feature {
+        id: "webhook_ingest",
+        name: "Webhook Ingest",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Authenticated HTTP endpoint for CI and monitoring to post persona-styled embeds, managed with /webhook",
+    },
+    Feature {
+        id: "calendar",
+        name: "Calendar Announcements",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Posts iCal/Google Calendar event reminders (24h/1h before) and a Monday agenda, with per-calendar keyword filters",
+    },
+    Feature {
+        id: "issue_lookup",
+        name: "Issue Lookup",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "/issue and optional inline linking of Jira/Linear issue keys with title, status and assignee",
+    },
+    Feature {
+        id: "calculator",
+        name: "Calculator",
+        version: "1.1.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "/calc and a calculate tool the AI calls for exact math, with optional Wolfram Alpha fallback",
+    },
+    Feature {
+        id: "code_runner",
+        name: "Code Runner",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "/run executes code snippets in a Piston or firejail sandbox with timeouts and per-user rate limits",
+    },
+    Feature {
+        id: "user_names",
+        name: "Display Name Resolution",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Cached guild nickname and avatar lookup for reports and mediation prompts, with fallbacks for members who have left",
+    },
+    Feature {
+        id: "llm_providers",
+        name: "LLM Providers",
+        version: "1.4.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Chat through OpenAI, Azure OpenAI, Anthropic or a local Ollama server, selected with LLM_PROVIDER, with prompts trimmed to the model's token window, retries with backoff and a circuit breaker with an optional fallback model",
+    },
+    Feature {
+        id: "loadtest",
+        name: "Load Test",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "bot loadtest replays synthetic mentions against the mock LLM and reports throughput and p99 latency",
+    },
+    Feature {
+        id: "panic_capture",
+        name: "Panic Capture",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Panics in command handlers are logged to error_logs, DMed to the owner and answered with an error embed",
+    },
+    Feature {
+        id: "chat_tools",
+        name: "Chat Tools",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Personas call tools mid-conversation: calculator, current time, reminders, usage stats and web fetch",
+    },
+    Feature {
+        id: "preflight_checks",
+        name: "Preflight Checks",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Startup checks of the OpenAI key, database writes, disk space and audio tools, stopping with a fix or disabling what depends on a missing tool",
+    },
+    Feature {
+        id: "supervisor",
+        name: "Task Supervisor",
+        version: "1.1.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Restarts the gateway and background tasks independently with exponential backoff, shown in /ops bots and GET /health",
+    },
+    Feature {
+        id: "embedding_api",
+        name: "Embedding API",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "BotBuilder and Bot run/shutdown handle for running the bot inside other Rust programs, with plugins and features disabled per host",
+    },
+    Feature {
+        id: "live_events",
+        name: "Live Event Stream",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Authenticated WebSocket on GET /events streaming commands, costs, conflicts and errors as JSON for real-time ops dashboards",
+    },
+    Feature {
+        id: "dashboard",
+        name: "Web Dashboard",
+        version: "1.2.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Web pages with usage charts, cost by feature, conflict history, settings, feature toggles and error logs for guild admins, behind Discord OAuth2 login with linked accounts and token refresh",
+    },
+    Feature {
+        id: "chat",
+        name: "AI Chat",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: true,
+        description: "Persona replies to mentions, DMs, conversation threads and the chat commands",
+    },
+    Feature {
+        id: "feature_gate",
+        name: "Feature Gate",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Kill switches checked at the top of chat, transcription, imaging, mediation and reminder paths, with /toggle effective within seconds",
+    },
+    Feature {
+        id: "prometheus",
+        name: "Prometheus Metrics",
+        version: "1.1.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "GET /metrics on METRICS_LISTEN_ADDR with gateway latency and reconnects per shard, command counts, OpenAI tokens and cost, reminder queue depth and DB query latency",
+    },
+    Feature {
+        id: "telemetry",
+        name: "Structured Tracing",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Per-interaction spans carrying bot, guild, user and interaction IDs through commands, LLM calls and database checkouts, with optional OTLP export",
+    },
+    Feature {
+        id: "error_explainer",
+        name: "Error Explainer",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "/explain_error explains a pasted or attached stack trace in the teacher or analyst persona, with likely causes and next steps",
+    },
+    Feature {
+        id: "help_digest",
+        name: "Unanswered Question Digest",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Daily staff channel digest of help channel questions with no reply after a few hours, with jump links and optional AI draft answers",
+    },
+    Feature {
+        id: "history_import",
+        name: "History Import",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Admin /import_history backfill of a channel's past messages into conversation history, after a consent confirmation, through a rate-limited background queue",
+    },
+    Feature {
+        id: "hot_reload",
+        name: "Config Hot Reload",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Watches config.yaml and starts, stops or updates bots (model, sensitivity, cooldown, command allowlist) without restarting the process",
+    },
+    Feature {
+        id: "interactions_endpoint",
+        name: "Interactions Endpoint",
+        version: "1.0.0",
+        since: "0.8.0",
+        toggleable: false,
+        description: "Signature-checked POST /interactions endpoint that takes slash commands and button clicks over HTTP instead of the gateway, chosen per bot",
+    },
 ];
 
 /// Get all registered features
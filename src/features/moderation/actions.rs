@@ -0,0 +1,229 @@
+//! # Feature: Moderation Actions
+//!
+//! Helpers behind `/timeout`, `/kick` and `/ban`: reason templates, the DM sent
+//! to the target, mod log entries, and the task that ends expired timeouts.
+//!
+//! Reasons may name a template (`spam`, `harassment`, ...) and may use the
+//! `{user}`, `{server}` and `{duration}` placeholders.
+//!
+//! - **Version**: 1.2.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.2.0: Over-long reasons end with `…` and keep emoji whole
+//! - 1.1.0: Ban and timeout notices point to the appeal button
+//! - 1.0.0: Initial release with reason templates, DM notices and scheduled un-timeouts
+
+use super::mod_log::post_mod_log;
+use crate::database::{Database, ModerationAction};
+use crate::core::discord_limits::{truncate, AUDIT_LOG_REASON};
+use crate::core::Result;
+use log::{info, warn};
+use serenity::http::Http;
+use serenity::model::id::{GuildId, UserId};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Discord's maximum timeout length
+pub const MAX_TIMEOUT_DAYS: i64 = 28;
+
+/// How often expired timeouts are checked
+const EXPIRY_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Named reasons offered by autocomplete: (key, text)
+pub const REASON_TEMPLATES: &[(&str, &str)] = &[
+    ("spam", "Spamming or flooding channels"),
+    ("harassment", "Harassing or targeting other members"),
+    ("nsfw", "Posting NSFW content outside permitted channels"),
+    ("raid", "Taking part in a raid on {server}"),
+    ("hate", "Hate speech or slurs"),
+    ("rules", "Repeatedly breaking the rules of {server}"),
+];
+
+/// A moderation action the bot can take
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModAction {
+    Timeout,
+    Kick,
+    Ban,
+}
+
+impl ModAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModAction::Timeout => "timeout",
+            ModAction::Kick => "kick",
+            ModAction::Ban => "ban",
+        }
+    }
+
+    fn emoji(&self) -> &'static str {
+        match self {
+            ModAction::Timeout => "⏱️",
+            ModAction::Kick => "👢",
+            ModAction::Ban => "🔨",
+        }
+    }
+
+    fn past_tense(&self) -> &'static str {
+        match self {
+            ModAction::Timeout => "timed out",
+            ModAction::Kick => "kicked",
+            ModAction::Ban => "banned",
+        }
+    }
+}
+
+/// Expand a template key and placeholders in a moderator's reason
+pub fn expand_reason(reason: &str, user: &str, server: &str, duration: Option<&str>) -> String {
+    let reason = reason.trim();
+    let text = REASON_TEMPLATES
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(reason))
+        .map(|(_, text)| *text)
+        .unwrap_or(reason);
+    let expanded = text
+        .replace("{user}", user)
+        .replace("{server}", server)
+        .replace("{duration}", duration.unwrap_or("permanently"));
+    truncate(&expanded, AUDIT_LOG_REASON)
+}
+
+/// Opening line of the DM, in the guild's default persona's voice but kept professional
+fn persona_greeting(persona: &str) -> &'static str {
+    match persona {
+        "obi" => "Greetings. I'm afraid I bring news from the moderators.",
+        "muppet" => "Hi there! I have an important update for you from the moderators.",
+        "chef" => "Hello. A quick but important note from the moderators.",
+        "teacher" => "Hello. I need to let you know about a decision from the moderators.",
+        "analyst" => "Hello. Here is a summary of a moderation decision that affects you.",
+        _ => "Hello. This is a message from the moderators.",
+    }
+}
+
+/// DM sent to the member before the action is applied
+pub fn format_dm_notice(
+    action: ModAction,
+    persona: &str,
+    persona_name: &str,
+    server: &str,
+    reason: &str,
+    duration: Option<&str>,
+) -> String {
+    let what = match (action, duration) {
+        (ModAction::Timeout, Some(duration)) => format!("You have been timed out in **{server}** for {duration}."),
+        _ => format!("You have been {} from **{server}**.", action.past_tense()),
+    };
+    let follow_up = match action {
+        ModAction::Timeout => {
+            "You can read messages but not post until the timeout ends. \
+            If you believe this was a mistake, you can appeal with the button below."
+        }
+        ModAction::Kick => "You may rejoin with a new invite if you agree to follow the rules.",
+        ModAction::Ban => "If you believe this was a mistake, you can appeal with the button below.",
+    };
+    format!(
+        "{}\n\n{what}\n**Reason:** {reason}\n\n{follow_up}\n\n— {persona_name}, on behalf of the {server} moderators",
+        persona_greeting(persona)
+    )
+}
+
+/// Mod log line for an action
+pub fn format_mod_log_entry(
+    action: ModAction,
+    target_id: &str,
+    moderator_id: &str,
+    reason: &str,
+    duration: Option<&str>,
+    dm_delivered: bool,
+) -> String {
+    let duration = duration.map(|d| format!(" for {d}")).unwrap_or_default();
+    let dm = if dm_delivered { "" } else { " (DM could not be delivered)" };
+    format!(
+        "{} <@{target_id}> was **{}**{duration} by <@{moderator_id}>{dm}\n**Reason:** {reason}",
+        action.emoji(),
+        action.past_tense()
+    )
+}
+
+async fn end_timeout(http: &Http, db: &Database, action: &ModerationAction) -> Result<()> {
+    let guild = GuildId(action.guild_id.parse::<u64>()?);
+    let user = UserId(action.target_user_id.parse::<u64>()?);
+    // Discord lifts timeouts on its own; clearing explicitly covers clock drift and edits
+    guild.edit_member(http, user, |m| m.enable_communication()).await?;
+    db.end_moderation_action(action.id).await?;
+
+    info!("⏱️ Timeout ended for user {} in guild {}", action.target_user_id, action.guild_id);
+    let text = format!("⏱️ Timeout for <@{}> has ended.", action.target_user_id);
+    if let Err(e) = post_mod_log(http, db, &action.guild_id, &text).await {
+        warn!("Failed to post timeout end to mod log for guild {}: {e}", action.guild_id);
+    }
+    Ok(())
+}
+
+/// Background task that ends timeouts once their scheduled time passes
+pub async fn timeout_expiry_loop(http: Arc<Http>, db: Arc<Database>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(EXPIRY_CHECK_INTERVAL_SECS));
+    info!("Timeout expiry task started (checks every {EXPIRY_CHECK_INTERVAL_SECS}s)");
+
+    loop {
+        interval.tick().await;
+        let due = match db.get_due_timeouts().await {
+            Ok(due) => due,
+            Err(e) => {
+                warn!("Failed to load expired timeouts: {}", e);
+                continue;
+            }
+        };
+
+        for action in due {
+            if let Err(e) = end_timeout(&http, &db, &action).await {
+                warn!("Failed to end timeout {} for user {}: {}", action.id, action.target_user_id, e);
+                // Member left or permissions changed - don't retry forever
+                if let Err(e) = db.end_moderation_action(action.id).await {
+                    warn!("Failed to mark timeout {} as ended: {}", action.id, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_reason() {
+        assert_eq!(expand_reason("spam", "Bob", "Cafe", None), "Spamming or flooding channels");
+        assert_eq!(expand_reason("RAID", "Bob", "Cafe", None), "Taking part in a raid on Cafe");
+        assert_eq!(
+            expand_reason("{user} ignored warnings, muted {duration}", "Bob", "Cafe", Some("1 hour")),
+            "Bob ignored warnings, muted 1 hour"
+        );
+        let long = expand_reason(&"x".repeat(600), "Bob", "Cafe", None);
+        assert_eq!(long.chars().count(), AUDIT_LOG_REASON);
+        assert!(long.ends_with('…'));
+    }
+
+    #[test]
+    fn test_format_dm_notice() {
+        let notice = format_dm_notice(ModAction::Timeout, "obi", "Obi-Wan", "Cafe", "Spam", Some("1 hour"));
+        assert!(notice.starts_with("Greetings."));
+        assert!(notice.contains("timed out in **Cafe** for 1 hour"));
+        assert!(notice.contains("**Reason:** Spam"));
+        assert!(notice.ends_with("— Obi-Wan, on behalf of the Cafe moderators"));
+
+        let ban = format_dm_notice(ModAction::Ban, "unknown", "Bot", "Cafe", "Raid", None);
+        assert!(ban.contains("You have been banned from **Cafe**."));
+        assert!(ban.contains("appeal with the button below"));
+    }
+
+    #[test]
+    fn test_format_mod_log_entry() {
+        let entry = format_mod_log_entry(ModAction::Kick, "1", "2", "Spam", None, false);
+        assert!(entry.starts_with("👢 <@1> was **kicked** by <@2> (DM could not be delivered)"));
+        let entry = format_mod_log_entry(ModAction::Timeout, "1", "2", "Spam", Some("10 minutes"), true);
+        assert!(entry.contains("**timed out** for 10 minutes by <@2>\n"));
+    }
+}
@@ -0,0 +1,176 @@
+//! # Feature: Moderation Appeals
+//!
+//! Banned or timed-out members appeal by DM: the moderation notice carries an
+//! Appeal button (or they DM `appeal`), a modal collects their statement, and
+//! the appeal is posted to the review channel with Approve/Deny buttons.
+//! Approving lifts the ban or timeout; either way the member is told the outcome.
+//!
+//! Appeals go to the `appeal_review_channel` guild setting, falling back to
+//! `mod_log_channel`. Each action can be appealed once.
+//!
+//! - **Version**: 1.0.1
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.1: Appeal choice labels use the shared grapheme-safe truncation
+//! - 1.0.0: Initial release with DM appeal modal, review buttons and outcome DMs
+
+use crate::database::{Database, ModerationAction};
+use crate::core::discord_limits::{truncate, BUTTON_LABEL};
+use crate::core::Result;
+use serenity::model::id::ChannelId;
+
+/// Button on the moderation DM / `appeal` reply: `appeal_open:<guild_id>`. Keyed by guild
+/// because ban notices are sent before the action is recorded.
+pub const APPEAL_OPEN_PREFIX: &str = "appeal_open:";
+/// Statement modal: `appeal_modal:<action_id>`
+pub const APPEAL_MODAL_PREFIX: &str = "appeal_modal:";
+/// Review buttons: `appeal_approve:<appeal_id>` / `appeal_deny:<appeal_id>`
+pub const APPEAL_APPROVE_PREFIX: &str = "appeal_approve:";
+pub const APPEAL_DENY_PREFIX: &str = "appeal_deny:";
+
+/// Statement length bounds enforced by the modal
+pub const MIN_STATEMENT_CHARS: u64 = 20;
+pub const MAX_STATEMENT_CHARS: u64 = 1000;
+
+/// Moderator decision on an appeal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppealDecision {
+    Approved,
+    Denied,
+}
+
+impl AppealDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AppealDecision::Approved => "approved",
+            AppealDecision::Denied => "denied",
+        }
+    }
+}
+
+/// Only actions that are still in effect can be appealed
+pub fn is_appealable(action: &str) -> bool {
+    matches!(action, "ban" | "timeout")
+}
+
+/// Parse the numeric ID after a custom ID prefix
+pub fn parse_appeal_id(custom_id: &str, prefix: &str) -> Option<i64> {
+    custom_id.strip_prefix(prefix)?.parse().ok()
+}
+
+/// Channel appeals are posted to: `appeal_review_channel`, else `mod_log_channel`
+pub async fn review_channel(db: &Database, guild_id: &str) -> Result<Option<ChannelId>> {
+    for key in ["appeal_review_channel", "mod_log_channel"] {
+        if let Some(value) = db.get_guild_setting(guild_id, key).await? {
+            if let Ok(id) = value.parse::<u64>() {
+                return Ok(Some(ChannelId(id)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Label for an action in the member's appeal choices
+pub fn format_appeal_choice(action: &ModerationAction, server: &str) -> String {
+    truncate(&format!("Appeal {} in {server}", action.action), BUTTON_LABEL)
+}
+
+/// Message posted to the review channel
+pub fn format_appeal_review(appeal_id: i64, action: &ModerationAction, statement: &str) -> String {
+    let duration = action
+        .duration_minutes
+        .map(|m| format!(" ({m} min)"))
+        .unwrap_or_default();
+    format!(
+        "📨 **Appeal #{appeal_id}** from <@{}>\n\
+        **Action:** {}{duration} by <@{}> on {}\n\
+        **Original reason:** {}\n\n\
+        **Statement:**\n>>> {statement}",
+        action.target_user_id, action.action, action.moderator_id, action.created_at, action.reason
+    )
+}
+
+/// Line appended to the review message once decided
+pub fn format_review_decision(decision: AppealDecision, reviewer_id: &str) -> String {
+    match decision {
+        AppealDecision::Approved => format!("✅ **Approved** by <@{reviewer_id}>"),
+        AppealDecision::Denied => format!("❌ **Denied** by <@{reviewer_id}>"),
+    }
+}
+
+/// DM telling the member how their appeal went
+pub fn format_appeal_outcome(decision: AppealDecision, action: &str, server: &str) -> String {
+    match (decision, action) {
+        (AppealDecision::Approved, "ban") => {
+            format!("✅ Your appeal in **{server}** was approved and your ban has been lifted. You may rejoin with a new invite.")
+        }
+        (AppealDecision::Approved, _) => {
+            format!("✅ Your appeal in **{server}** was approved and your timeout has been removed.")
+        }
+        (AppealDecision::Denied, _) => {
+            format!("❌ Your appeal in **{server}** was reviewed and denied. The {action} remains in place.")
+        }
+    }
+}
+
+/// Mod log line for a decided appeal
+pub fn format_appeal_mod_log(appeal_id: i64, decision: AppealDecision, action: &str, user_id: &str, reviewer_id: &str) -> String {
+    format!(
+        "📨 Appeal #{appeal_id} by <@{user_id}> against their {action} was **{}** by <@{reviewer_id}>",
+        decision.as_str()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(kind: &str, duration_minutes: Option<i64>) -> ModerationAction {
+        ModerationAction {
+            id: 7,
+            guild_id: "1".to_string(),
+            target_user_id: "2".to_string(),
+            moderator_id: "3".to_string(),
+            action: kind.to_string(),
+            reason: "Spam".to_string(),
+            duration_minutes,
+            created_at: "2026-10-01 12:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_appealable_and_ids() {
+        assert!(is_appealable("ban"));
+        assert!(is_appealable("timeout"));
+        assert!(!is_appealable("kick"));
+        assert_eq!(parse_appeal_id("appeal_approve:42", APPEAL_APPROVE_PREFIX), Some(42));
+        assert_eq!(parse_appeal_id("appeal_deny:x", APPEAL_DENY_PREFIX), None);
+        assert_eq!(parse_appeal_id("appeal_deny:1", APPEAL_APPROVE_PREFIX), None);
+    }
+
+    #[test]
+    fn test_format_appeal_review() {
+        let review = format_appeal_review(5, &action("timeout", Some(60)), "I was sharing a link a friend asked for.");
+        assert!(review.starts_with("📨 **Appeal #5** from <@2>"));
+        assert!(review.contains("**Action:** timeout (60 min) by <@3>"));
+        assert!(review.contains("**Original reason:** Spam"));
+        assert!(review.ends_with(">>> I was sharing a link a friend asked for."));
+    }
+
+    #[test]
+    fn test_format_appeal_outcome() {
+        assert!(format_appeal_outcome(AppealDecision::Approved, "ban", "Cafe").contains("ban has been lifted"));
+        assert!(format_appeal_outcome(AppealDecision::Approved, "timeout", "Cafe").contains("timeout has been removed"));
+        assert!(format_appeal_outcome(AppealDecision::Denied, "ban", "Cafe").contains("The ban remains in place"));
+        assert_eq!(format_review_decision(AppealDecision::Denied, "9"), "❌ **Denied** by <@9>");
+    }
+
+    #[test]
+    fn test_format_appeal_choice_truncates() {
+        let label = format_appeal_choice(&action("ban", None), &"x".repeat(100));
+        assert_eq!(label.chars().count(), 80);
+        assert!(label.starts_with("Appeal ban in ") && label.ends_with('…'));
+    }
+}
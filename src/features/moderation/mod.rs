@@ -0,0 +1,24 @@
+//! # Moderation Feature
+//!
+//! Shared mod log plus `/timeout`, `/kick` and `/ban` wrappers that require a
+//! reason, notify the target by DM, and record every action in
+//! `moderation_actions`. Banned or timed-out members can appeal by DM.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod actions;
+pub mod appeals;
+pub mod mod_log;
+
+pub use actions::{
+    expand_reason, format_dm_notice, format_mod_log_entry, timeout_expiry_loop, ModAction, MAX_TIMEOUT_DAYS,
+    REASON_TEMPLATES,
+};
+pub use appeals::{
+    format_appeal_choice, format_appeal_mod_log, format_appeal_outcome, format_appeal_review, format_review_decision,
+    is_appealable, parse_appeal_id, review_channel, AppealDecision, APPEAL_APPROVE_PREFIX, APPEAL_DENY_PREFIX,
+    APPEAL_MODAL_PREFIX, APPEAL_OPEN_PREFIX, MAX_STATEMENT_CHARS, MIN_STATEMENT_CHARS,
+};
+pub use mod_log::{mod_log_channel, post_mod_log};
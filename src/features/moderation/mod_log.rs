@@ -0,0 +1,33 @@
+//! # Feature: Mod Log
+//!
+//! Posts moderation events (auto slowmode, quarantined attachments, manual
+//! actions) to the channel configured in the `mod_log_channel` guild setting.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: `mod_log_channel` lookup shared with posts that carry buttons
+//! - 1.0.0: Moved out of auto slowmode so all moderation features share it
+
+use crate::database::Database;
+use crate::core::Result;
+use serenity::http::Http;
+use serenity::model::id::ChannelId;
+
+/// The guild's `mod_log_channel`, if one is configured
+pub async fn mod_log_channel(db: &Database, guild_id: &str) -> Result<Option<ChannelId>> {
+    Ok(match db.get_guild_setting(guild_id, "mod_log_channel").await? {
+        Some(value) if value != "disabled" => value.parse::<u64>().ok().map(ChannelId),
+        _ => None,
+    })
+}
+
+/// Post a message to the guild's `mod_log_channel`, if one is configured
+pub async fn post_mod_log(http: &Http, db: &Database, guild_id: &str, text: &str) -> Result<()> {
+    if let Some(channel) = mod_log_channel(db, guild_id).await? {
+        channel.say(http, text).await?;
+    }
+    Ok(())
+}
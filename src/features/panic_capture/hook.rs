@@ -0,0 +1,105 @@
+//! # Feature: Panic Hook
+//!
+//! A process-wide panic hook that keeps the location and backtrace of the most
+//! recent panic, so code that catches the panic later (from a `JoinError`) can
+//! report where it happened rather than just the message. The previous hook
+//! still runs, so panics keep appearing on stderr.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::sync::{Mutex, Once};
+
+static INSTALL: Once = Once::new();
+
+/// The last panic seen by the hook, waiting to be claimed by whoever caught it
+static LAST_PANIC: Mutex<Option<PanicReport>> = Mutex::new(None);
+
+/// What is known about a caught panic
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanicReport {
+    pub message: String,
+    /// `file:line:column`, when the hook saw the panic
+    pub location: Option<String>,
+    pub backtrace: Option<String>,
+}
+
+impl PanicReport {
+    /// Report for a caught payload, with location and backtrace when the hook recorded this panic
+    pub fn from_payload(payload: &(dyn Any + Send)) -> Self {
+        let message = panic_message(payload);
+        let recorded = LAST_PANIC.lock().ok().and_then(|mut last| {
+            // A concurrent panic may have replaced it; only claim our own
+            if last.as_ref().is_some_and(|r| r.message == message) {
+                last.take()
+            } else {
+                None
+            }
+        });
+        recorded.unwrap_or(PanicReport { message, location: None, backtrace: None })
+    }
+}
+
+/// The message a panic was raised with
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panic with a non-string payload".to_string()
+    }
+}
+
+/// Install the hook once; later calls do nothing
+pub fn install_panic_hook() {
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let report = PanicReport {
+                message: panic_message(info.payload()),
+                location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+                backtrace: Some(Backtrace::force_capture().to_string()),
+            };
+            if let Ok(mut last) = LAST_PANIC.lock() {
+                *last = Some(report);
+            }
+            previous(info);
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_message() {
+        let from_str: Box<dyn Any + Send> = Box::new("boom");
+        assert_eq!(panic_message(from_str.as_ref()), "boom");
+        let from_string: Box<dyn Any + Send> = Box::new(format!("index {} out of range", 3));
+        assert_eq!(panic_message(from_string.as_ref()), "index 3 out of range");
+        let other: Box<dyn Any + Send> = Box::new(42);
+        assert_eq!(panic_message(other.as_ref()), "panic with a non-string payload");
+    }
+
+    #[test]
+    fn test_hook_records_location() {
+        install_panic_hook();
+        let payload = std::panic::catch_unwind(|| panic!("captured by the hook test")).unwrap_err();
+        let report = PanicReport::from_payload(payload.as_ref());
+        assert_eq!(report.message, "captured by the hook test");
+        assert!(report.location.unwrap().contains("hook.rs"));
+        assert!(report.backtrace.is_some());
+
+        // Already claimed
+        let again = PanicReport::from_payload(payload.as_ref());
+        assert_eq!(again.location, None);
+    }
+}
@@ -0,0 +1,16 @@
+//! # Panic Capture Feature
+//!
+//! Last-chance error handling: a panic hook records where panics happen, and
+//! interaction handlers run in their own task so a panic in one command is
+//! logged to `error_logs`, reported to the owner and answered with an error
+//! embed instead of leaving the interaction hanging.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod hook;
+pub mod report;
+
+pub use hook::{install_panic_hook, panic_message, PanicReport};
+pub use report::{record_panic, user_error_embed, PanicSource, PANIC_ERROR_TYPE};
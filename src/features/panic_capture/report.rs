@@ -0,0 +1,183 @@
+//! # Feature: Panic Reports
+//!
+//! What happens after an interaction handler panics: the panic is written to
+//! `error_logs` with its location and backtrace, the bot owner gets a DM (at
+//! most once per location every ten minutes), and the user gets an error embed
+//! with a reference code that matches the log entry.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use super::hook::PanicReport;
+use crate::core::discord_limits::fit_embed;
+use crate::database::Database;
+use log::{error, warn};
+use serenity::builder::CreateEmbed;
+use serenity::http::Http;
+use serenity::model::id::UserId;
+use serenity::utils::Color;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// `error_logs.error_type` for caught panics
+pub const PANIC_ERROR_TYPE: &str = "panic";
+
+/// Owner DMs for panics at the same location are sent at most this often
+pub const OWNER_ALERT_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+/// Last owner alert per panic location
+static LAST_ALERTS: Mutex<Vec<(String, Instant)>> = Mutex::new(Vec::new());
+
+/// Where a panic happened, for the log and the owner alert
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanicSource {
+    /// `command`, `component` or `modal`
+    pub kind: &'static str,
+    /// Command name or component custom ID
+    pub name: String,
+    pub user_id: String,
+    pub channel_id: String,
+    pub guild_id: Option<String>,
+}
+
+/// Short code shown to the user and stored with the log entry
+pub fn reference_code() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..8].to_string()
+}
+
+/// Whether an alert for `key` is due, recording it if so
+fn alert_due(alerts: &mut Vec<(String, Instant)>, key: &str, now: Instant) -> bool {
+    alerts.retain(|(_, at)| now.duration_since(*at) < OWNER_ALERT_COOLDOWN);
+    if alerts.iter().any(|(k, _)| k == key) {
+        return false;
+    }
+    alerts.push((key.to_string(), now));
+    true
+}
+
+/// The embed shown to the user instead of a hanging interaction
+pub fn user_error_embed(reference: &str) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed
+        .title("💥 Something went wrong")
+        .description(
+            "I hit an unexpected error while handling that, sorry! The bot owner has been told. \
+             Please try again in a moment.",
+        )
+        .color(Color::RED)
+        .footer(|f| f.text(format!("Reference: {reference}")));
+    embed
+}
+
+/// The owner's DM describing the panic
+pub fn owner_alert_embed(report: &PanicReport, source: &PanicSource, reference: &str) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed
+        .title("🚨 Panic in an interaction handler")
+        .description(format!("```\n{}\n```", report.message))
+        .color(Color::RED)
+        .field("Where", format!("{} `{}`", source.kind, source.name), true)
+        .field("User", format!("<@{}>", source.user_id), true)
+        .field(
+            "Channel",
+            match &source.guild_id {
+                Some(guild_id) => format!("<#{}> (guild {guild_id})", source.channel_id),
+                None => "DM".to_string(),
+            },
+            true,
+        )
+        .field("Location", report.location.as_deref().unwrap_or("unknown"), false)
+        .footer(|f| f.text(format!("Reference: {reference} · full backtrace in error_logs")));
+    fit_embed(&mut embed);
+    embed
+}
+
+/// Log a caught panic, alert the owner, and return the reference code for the user
+pub async fn record_panic(http: &Http, database: &Database, report: &PanicReport, source: &PanicSource) -> String {
+    let reference = reference_code();
+    error!(
+        "💥 Panic in {} '{}' [{}] at {}: {}",
+        source.kind,
+        source.name,
+        reference,
+        report.location.as_deref().unwrap_or("unknown location"),
+        report.message
+    );
+
+    let metadata = serde_json::json!({
+        "reference": reference,
+        "kind": source.kind,
+        "guild_id": source.guild_id,
+        "location": report.location,
+    })
+    .to_string();
+    if let Err(e) = database
+        .log_error(
+            PANIC_ERROR_TYPE,
+            &report.message,
+            report.backtrace.as_deref(),
+            Some(&source.user_id),
+            Some(&source.channel_id),
+            source.guild_id.as_deref(),
+            Some(&source.name),
+            Some(&metadata),
+        )
+        .await
+    {
+        warn!("Failed to log panic {reference}: {e}");
+    }
+
+    let key = report.location.clone().unwrap_or_else(|| report.message.clone());
+    let due = LAST_ALERTS
+        .lock()
+        .map(|mut alerts| alert_due(&mut alerts, &key, Instant::now()))
+        .unwrap_or(true);
+    if due {
+        if let Err(e) = notify_owner(http, database, owner_alert_embed(report, source, &reference)).await {
+            warn!("Failed to alert the owner about panic {reference}: {e}");
+        }
+    }
+    reference
+}
+
+/// DM the owner set in `startup_notify_owner_id`, or the application owner
+async fn notify_owner(http: &Http, database: &Database, embed: CreateEmbed) -> crate::core::Result<()> {
+    let configured = database
+        .get_bot_setting("startup_notify_owner_id")
+        .await?
+        .and_then(|id| id.parse::<u64>().ok())
+        .map(UserId);
+    let owner = match configured {
+        Some(owner) => owner,
+        None => http.get_current_application_info().await?.owner.id,
+    };
+    let dm = owner.create_dm_channel(http).await?;
+    dm.send_message(http, |m| m.set_embed(embed)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alert_due_respects_cooldown() {
+        let mut alerts = Vec::new();
+        let start = Instant::now();
+        assert!(alert_due(&mut alerts, "src/a.rs:1:1", start));
+        assert!(!alert_due(&mut alerts, "src/a.rs:1:1", start + Duration::from_secs(60)));
+        assert!(alert_due(&mut alerts, "src/b.rs:2:2", start + Duration::from_secs(60)));
+        assert!(alert_due(&mut alerts, "src/a.rs:1:1", start + OWNER_ALERT_COOLDOWN));
+    }
+
+    #[test]
+    fn test_reference_code() {
+        let code = reference_code();
+        assert_eq!(code.len(), 8);
+        assert!(code.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+}
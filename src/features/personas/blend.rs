@@ -0,0 +1,68 @@
+//! # Feature: Persona Blending
+//!
+//! One-off answers from a persona other than the user's default (`/as`), and
+//! the `/persona blend` experiment, which answers in a single voice combining
+//! two personas' system prompts. Neither changes the user's default persona or
+//! their conversation history.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use super::manager::Persona;
+
+/// System prompt combining two personas into one voice
+pub fn blend_prompt(first: &Persona, second: &Persona) -> String {
+    format!(
+        "You are a blend of two personas, answering as a single character who combines both. \
+Mix their personalities, knowledge and speaking styles evenly; don't alternate between them or answer twice.\n\n\
+## First persona: {}\n{}\n\n## Second persona: {}\n{}",
+        first.name,
+        first.system_prompt.trim(),
+        second.name,
+        second.system_prompt.trim(),
+    )
+}
+
+/// Label shown above a one-off answer, e.g. "👨‍🍳 **Chef**" or "👨‍🍳 **Chef** × ⚔️ **Obi-Wan**"
+pub fn persona_label(first: &Persona, second: Option<&Persona>) -> String {
+    let label = |p: &Persona| match &p.emoji {
+        Some(emoji) => format!("{emoji} **{}**", p.name),
+        None => format!("**{}**", p.name),
+    };
+    match second {
+        Some(second) => format!("{} × {}", label(first), label(second)),
+        None => label(first),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::personas::PersonaManager;
+
+    #[test]
+    fn test_blend_prompt_contains_both() {
+        let manager = PersonaManager::new();
+        let chef = manager.get_persona("chef").unwrap();
+        let obi = manager.get_persona("obi").unwrap();
+        let prompt = blend_prompt(&chef, &obi);
+        assert!(prompt.contains("## First persona: Chef"));
+        assert!(prompt.contains("## Second persona: Obi-Wan"));
+        assert!(prompt.contains(chef.system_prompt.trim()));
+        assert!(prompt.contains(obi.system_prompt.trim()));
+    }
+
+    #[test]
+    fn test_persona_label() {
+        let manager = PersonaManager::new();
+        let chef = manager.get_persona("chef").unwrap();
+        let mut plain = manager.get_persona("obi").unwrap();
+        plain.emoji = None;
+        assert_eq!(persona_label(&chef, None), "👨‍🍳 **Chef**");
+        assert_eq!(persona_label(&chef, Some(&plain)), "👨‍🍳 **Chef** × **Obi-Wan**");
+    }
+}
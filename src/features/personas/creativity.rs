@@ -0,0 +1,89 @@
+//! # Feature: Response Creativity
+//!
+//! Maps user-facing creativity levels onto OpenAI sampling temperature, so
+//! channels like #support can stay factual while #creative-writing runs hot.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with low/normal/high levels and per-channel defaults
+
+/// Creativity level for AI responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Creativity {
+    Low,
+    Normal,
+    High,
+}
+
+impl Creativity {
+    /// All accepted level names, in display order
+    pub const LEVELS: [&'static str; 3] = ["low", "normal", "high"];
+
+    /// Parse a level name (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "low" => Some(Creativity::Low),
+            "normal" => Some(Creativity::Normal),
+            "high" => Some(Creativity::High),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Creativity::Low => "low",
+            Creativity::Normal => "normal",
+            Creativity::High => "high",
+        }
+    }
+
+    /// Sampling temperature for this level; `None` leaves the model default in place
+    pub fn temperature(&self) -> Option<f32> {
+        match self {
+            Creativity::Low => Some(0.2),
+            Creativity::Normal => None,
+            Creativity::High => Some(1.2),
+        }
+    }
+
+    /// Resolve the effective level: explicit command option first, then the channel default
+    pub fn resolve(option: Option<&str>, channel_default: Option<&str>) -> Self {
+        option
+            .and_then(Self::parse)
+            .or_else(|| channel_default.and_then(Self::parse))
+            .unwrap_or(Creativity::Normal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_levels() {
+        assert_eq!(Creativity::parse("LOW"), Some(Creativity::Low));
+        assert_eq!(Creativity::parse(" high "), Some(Creativity::High));
+        assert_eq!(Creativity::parse("spicy"), None);
+        for level in Creativity::LEVELS {
+            assert_eq!(Creativity::parse(level).unwrap().as_str(), level);
+        }
+    }
+
+    #[test]
+    fn test_temperature_mapping() {
+        assert_eq!(Creativity::Low.temperature(), Some(0.2));
+        assert_eq!(Creativity::Normal.temperature(), None);
+        assert_eq!(Creativity::High.temperature(), Some(1.2));
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        assert_eq!(Creativity::resolve(Some("high"), Some("low")), Creativity::High);
+        assert_eq!(Creativity::resolve(None, Some("low")), Creativity::Low);
+        assert_eq!(Creativity::resolve(Some("bogus"), Some("low")), Creativity::Low);
+        assert_eq!(Creativity::resolve(None, None), Creativity::Normal);
+    }
+}
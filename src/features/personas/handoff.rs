@@ -0,0 +1,103 @@
+//! # Feature: Persona Handoff
+//!
+//! Mid-conversation persona switching. The conversation so far is summarized and
+//! the summary is recorded in conversation history so the new persona picks up
+//! where the old one left off.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with transcript summarization and handoff notes
+
+/// Marker that prefixes handoff notes stored in conversation history
+pub const HANDOFF_MARKER: &str = "🔀 Persona handoff";
+
+/// Maximum transcript length (in characters) sent for summarization
+pub const MAX_TRANSCRIPT_CHARS: usize = 12_000;
+
+/// System prompt used to summarize a conversation before handing it off
+pub const HANDOFF_SUMMARY_PROMPT: &str = "You summarize conversations so another assistant can continue them. \
+Write a concise summary (at most 8 bullet points) covering what the user wants, what has been answered so far, \
+and any open questions. Write in neutral third person and do not add new advice.";
+
+/// Render conversation history as a plain transcript for summarization.
+///
+/// Keeps the most recent messages when the transcript exceeds `max_chars`.
+pub fn format_transcript(history: &[(String, String)], max_chars: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut total = 0;
+
+    for (role, content) in history.iter().rev() {
+        let speaker = match role.as_str() {
+            "user" => "User",
+            "assistant" => "Assistant",
+            _ => continue,
+        };
+        let line = format!("{speaker}: {}", content.trim());
+        let len = line.chars().count() + 1;
+        if total + len > max_chars && !lines.is_empty() {
+            break;
+        }
+        total += len;
+        lines.push(line);
+    }
+
+    lines.reverse();
+    lines.join("\n")
+}
+
+/// Build the note recorded in conversation history for a handoff
+pub fn handoff_note(from: &str, to: &str, summary: &str) -> String {
+    format!(
+        "{HANDOFF_MARKER} from {from} to {to}. Summary of the conversation so far:\n{}",
+        summary.trim()
+    )
+}
+
+/// Instruction appended to the new persona's system prompt for its first reply
+pub fn handoff_instruction(from: &str) -> String {
+    format!(
+        "\n\nYou are taking over this conversation from the {from} persona. \
+Briefly introduce yourself, then give your own perspective on the conversation so far and suggest a next step."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(role: &str, content: &str) -> (String, String) {
+        (role.to_string(), content.to_string())
+    }
+
+    #[test]
+    fn test_format_transcript_labels_roles() {
+        let history = vec![entry("user", "How do I bake bread?"), entry("assistant", " Knead it. ")];
+        assert_eq!(
+            format_transcript(&history, MAX_TRANSCRIPT_CHARS),
+            "User: How do I bake bread?\nAssistant: Knead it."
+        );
+    }
+
+    #[test]
+    fn test_format_transcript_keeps_most_recent() {
+        let history = vec![
+            entry("user", &"a".repeat(50)),
+            entry("assistant", "recent answer"),
+            entry("system", "ignored"),
+        ];
+        let transcript = format_transcript(&history, 30);
+        assert_eq!(transcript, "Assistant: recent answer");
+    }
+
+    #[test]
+    fn test_handoff_note_and_instruction() {
+        let note = handoff_note("chef", "analyst", "  - wants bread  ");
+        assert!(note.starts_with(HANDOFF_MARKER));
+        assert!(note.contains("from chef to analyst"));
+        assert!(note.ends_with("- wants bread"));
+        assert!(handoff_instruction("chef").contains("chef persona"));
+    }
+}
@@ -2,27 +2,96 @@
 //!
 //! Multi-personality AI responses with 5 distinct personas (obi, muppet, chef, teacher, analyst).
 //! Each persona has a unique system prompt loaded from prompt/*.md files at compile time.
+//! Guild admins can add their own with `/persona create`; those are kept alongside the
+//! built-ins and shared by every clone of the manager. A single invocation can
+//! override the user's default persona, or blend two, with a [`PersonaOverride`].
 //!
-//! - **Version**: 1.0.0
+//! - **Version**: 1.4.0
 //! - **Since**: 0.1.0
 //! - **Toggleable**: false
 //!
 //! ## Changelog
+//! - 1.4.0: Per-persona typing delay for response pacing
+//! - 1.3.0: Per-invocation persona overrides and two-persona blends
+//! - 1.2.0: Custom personas from the database, with their own temperature and emoji
+//! - 1.1.0: Optional per-persona avatar URLs for webhook replies
 //! - 1.0.0: Initial release with 5 personas and verbosity modifiers
 
+use super::blend::blend_prompt;
+use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Persona {
     pub name: String,
     pub system_prompt: String,
     pub description: String,
+    /// Avatar shown on webhook replies, set from `PERSONA_AVATARS`
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+    #[serde(default)]
+    pub emoji: Option<String>,
+    /// Sampling temperature used unless the channel or command sets a creativity level
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// Guild that defined a custom persona; `None` for built-ins
+    #[serde(default)]
+    pub guild_id: Option<String>,
+    /// Whether a custom persona can be picked in other guilds
+    #[serde(default)]
+    pub shared: bool,
+    /// Pause before replying for flavor, capped by the guild's `max_response_delay_ms`
+    #[serde(default)]
+    pub typing_delay_ms: Option<u64>,
+}
+
+impl Persona {
+    /// A built-in persona
+    fn builtin(name: &str, system_prompt: &str, description: &str, emoji: &str) -> Self {
+        Persona {
+            name: name.to_string(),
+            system_prompt: system_prompt.to_string(),
+            description: description.to_string(),
+            avatar_url: None,
+            emoji: Some(emoji.to_string()),
+            temperature: None,
+            guild_id: None,
+            shared: false,
+            typing_delay_ms: None,
+        }
+    }
+
+    /// Give a built-in persona a flavor pause before replying
+    fn with_typing_delay(mut self, ms: u64) -> Self {
+        self.typing_delay_ms = Some(ms);
+        self
+    }
+
+    /// Whether members of `guild_id` may pick this persona
+    pub fn available_in(&self, guild_id: Option<&str>) -> bool {
+        match &self.guild_id {
+            None => true,
+            Some(owner) => self.shared || guild_id == Some(owner.as_str()),
+        }
+    }
+}
+
+/// A persona for one invocation in place of the user's default, as used by `/as` and `/persona blend`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PersonaOverride {
+    /// Answer as another persona
+    Single(String),
+    /// Answer in a voice combining two personas
+    Blend(String, String),
 }
 
 #[derive(Debug, Clone)]
 pub struct PersonaManager {
     personas: HashMap<String, Persona>,
+    /// Custom personas by key, shared by every clone so edits apply everywhere at once
+    custom: Arc<DashMap<String, Persona>>,
 }
 
 impl Default for PersonaManager {
@@ -36,45 +105,109 @@ impl PersonaManager {
         let mut personas = HashMap::new();
 
         // Load all personas with prompts embedded at compile time
-        personas.insert("obi".to_string(), Persona {
-            name: "Obi-Wan".to_string(),
-            system_prompt: include_str!("../../../prompt/obi.md").to_string(),
-            description: "A wise Jedi Master who speaks with patience, diplomacy, and philosophical insight".to_string(),
-        });
+        personas.insert("obi".to_string(), Persona::builtin(
+            "Obi-Wan",
+            include_str!("../../../prompt/obi.md"),
+            "A wise Jedi Master who speaks with patience, diplomacy, and philosophical insight",
+            "⚔️",
+        ).with_typing_delay(1500));
 
-        personas.insert("muppet".to_string(), Persona {
-            name: "Muppet Friend".to_string(),
-            system_prompt: include_str!("../../../prompt/muppet.md").to_string(),
-            description: "A warm, enthusiastic friend who brings Muppet-style joy, humor, and heart to every conversation!".to_string(),
-        });
+        personas.insert("muppet".to_string(), Persona::builtin(
+            "Muppet Friend",
+            include_str!("../../../prompt/muppet.md"),
+            "A warm, enthusiastic friend who brings Muppet-style joy, humor, and heart to every conversation!",
+            "🐸",
+        ).with_typing_delay(800));
 
-        personas.insert("chef".to_string(), Persona {
-            name: "Chef".to_string(),
-            system_prompt: include_str!("../../../prompt/chef.md").to_string(),
-            description: "A passionate chef who shares recipes and cooking wisdom".to_string(),
-        });
+        personas.insert("chef".to_string(), Persona::builtin(
+            "Chef",
+            include_str!("../../../prompt/chef.md"),
+            "A passionate chef who shares recipes and cooking wisdom",
+            "👨‍🍳",
+        ));
 
-        personas.insert("teacher".to_string(), Persona {
-            name: "Teacher".to_string(),
-            system_prompt: include_str!("../../../prompt/teacher.md").to_string(),
-            description: "A patient teacher who explains things clearly".to_string(),
-        });
+        personas.insert("teacher".to_string(), Persona::builtin(
+            "Teacher",
+            include_str!("../../../prompt/teacher.md"),
+            "A patient teacher who explains things clearly",
+            "📚",
+        ));
 
-        personas.insert("analyst".to_string(), Persona {
-            name: "Step-by-Step Analyst".to_string(),
-            system_prompt: include_str!("../../../prompt/analyst.md").to_string(),
-            description: "An analyst who breaks things down into clear steps".to_string(),
-        });
+        personas.insert("analyst".to_string(), Persona::builtin(
+            "Step-by-Step Analyst",
+            include_str!("../../../prompt/analyst.md"),
+            "An analyst who breaks things down into clear steps",
+            "📊",
+        ));
 
-        PersonaManager { personas }
+        PersonaManager { personas, custom: Arc::new(DashMap::new()) }
     }
 
-    pub fn get_persona(&self, name: &str) -> Option<&Persona> {
-        self.personas.get(name)
+    /// Set avatar URLs by persona key, ignoring unknown keys
+    pub fn with_avatars(mut self, avatars: &HashMap<String, String>) -> Self {
+        for (key, url) in avatars {
+            if let Some(persona) = self.personas.get_mut(key) {
+                persona.avatar_url = Some(url.clone());
+            }
+        }
+        self
     }
 
-    pub fn list_personas(&self) -> Vec<(&String, &Persona)> {
-        self.personas.iter().collect()
+    pub fn get_persona(&self, name: &str) -> Option<Persona> {
+        self.personas.get(name).cloned().or_else(|| self.custom.get(name).map(|p| p.clone()))
+    }
+
+    /// Built-in personas, then custom ones
+    pub fn list_personas(&self) -> Vec<(String, Persona)> {
+        let mut builtins: Vec<(String, Persona)> = self.personas.iter().map(|(k, p)| (k.clone(), p.clone())).collect();
+        builtins.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut custom: Vec<(String, Persona)> = self.custom.iter().map(|e| (e.key().clone(), e.value().clone())).collect();
+        custom.sort_by(|a, b| a.0.cmp(&b.0));
+        builtins.extend(custom);
+        builtins
+    }
+
+    /// Personas members of `guild_id` may pick: built-ins, the guild's own and shared ones
+    pub fn personas_for_guild(&self, guild_id: Option<&str>) -> Vec<(String, Persona)> {
+        self.list_personas().into_iter().filter(|(_, p)| p.available_in(guild_id)).collect()
+    }
+
+    pub fn is_builtin(&self, key: &str) -> bool {
+        self.personas.contains_key(key)
+    }
+
+    /// Add or replace a custom persona
+    pub fn set_custom(&self, key: &str, persona: Persona) {
+        self.custom.insert(key.to_string(), persona);
+    }
+
+    pub fn remove_custom(&self, key: &str) {
+        self.custom.remove(key);
+    }
+
+    /// A persona's own sampling temperature, if it has one
+    pub fn temperature(&self, key: &str) -> Option<f32> {
+        self.custom.get(key).and_then(|p| p.temperature)
+    }
+
+    /// A persona's flavor pause before replying, if it has one
+    pub fn typing_delay_ms(&self, key: &str) -> Option<u64> {
+        match self.personas.get(key) {
+            Some(persona) => persona.typing_delay_ms,
+            None => self.custom.get(key).and_then(|p| p.typing_delay_ms),
+        }
+    }
+
+    /// Temperature for one invocation; a blend uses the mean of its personas' temperatures
+    pub fn resolve_temperature(&self, default_persona: &str, persona_override: Option<&PersonaOverride>) -> Option<f32> {
+        match persona_override {
+            None => self.temperature(default_persona),
+            Some(PersonaOverride::Single(key)) => self.temperature(key),
+            Some(PersonaOverride::Blend(first, second)) => match (self.temperature(first), self.temperature(second)) {
+                (Some(a), Some(b)) => Some((a + b) / 2.0),
+                (a, b) => a.or(b),
+            },
+        }
     }
 
     pub fn get_system_prompt(&self, persona_name: &str, modifier: Option<&str>) -> String {
@@ -83,10 +216,27 @@ impl PersonaManager {
 
     /// Get system prompt with verbosity level applied
     pub fn get_system_prompt_with_verbosity(&self, persona_name: &str, modifier: Option<&str>, verbosity: &str) -> String {
-        let base_prompt = self.personas
-            .get(persona_name)
-            .map(|p| p.system_prompt.clone())
-            .unwrap_or_else(|| "You are a helpful assistant.".to_string());
+        self.resolve_system_prompt(persona_name, None, modifier, verbosity)
+    }
+
+    /// System prompt for one invocation: the override's persona (or blend) when given, otherwise the user's default
+    pub fn resolve_system_prompt(
+        &self,
+        default_persona: &str,
+        persona_override: Option<&PersonaOverride>,
+        modifier: Option<&str>,
+        verbosity: &str,
+    ) -> String {
+        let prompt_of = |key: &str| self.get_persona(key).map(|p| p.system_prompt);
+        let base_prompt = match persona_override {
+            None => prompt_of(default_persona),
+            Some(PersonaOverride::Single(key)) => prompt_of(key),
+            Some(PersonaOverride::Blend(first, second)) => match (self.get_persona(first), self.get_persona(second)) {
+                (Some(first), Some(second)) => Some(blend_prompt(&first, &second)),
+                _ => None,
+            },
+        }
+        .unwrap_or_else(|| "You are a helpful assistant.".to_string());
 
         // Apply modifier first
         let with_modifier = match modifier {
@@ -132,6 +282,18 @@ mod tests {
         assert!(manager.get_persona("nonexistent").is_none());
     }
 
+    #[test]
+    fn test_with_avatars() {
+        let avatars = HashMap::from([
+            ("chef".to_string(), "https://example.org/chef.png".to_string()),
+            ("pirate".to_string(), "https://example.org/pirate.png".to_string()),
+        ]);
+        let manager = PersonaManager::new().with_avatars(&avatars);
+        assert_eq!(manager.get_persona("chef").unwrap().avatar_url.as_deref(), Some("https://example.org/chef.png"));
+        assert!(manager.get_persona("obi").unwrap().avatar_url.is_none());
+        assert!(manager.get_persona("pirate").is_none());
+    }
+
     #[test]
     fn test_get_system_prompt_with_modifiers() {
         let manager = PersonaManager::new();
@@ -213,4 +375,67 @@ mod tests {
         assert!(prompt.contains("clear explanations"));
         assert!(prompt.contains("brief and to the point"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_custom_personas_scoped_to_guild_unless_shared() {
+        let manager = PersonaManager::new();
+        let mut pirate = manager.get_persona("muppet").unwrap();
+        pirate.guild_id = Some("1".to_string());
+        pirate.temperature = Some(1.1);
+        manager.clone().set_custom("pirate", pirate.clone());
+
+        assert!(manager.get_persona("pirate").unwrap().available_in(Some("1")));
+        assert!(!manager.get_persona("pirate").unwrap().available_in(Some("2")));
+        assert!(!manager.get_persona("pirate").unwrap().available_in(None));
+        assert!(manager.personas_for_guild(Some("1")).iter().any(|(key, _)| key == "pirate"));
+        assert!(!manager.personas_for_guild(Some("2")).iter().any(|(key, _)| key == "pirate"));
+        assert_eq!(manager.temperature("pirate"), Some(1.1));
+        assert!(!manager.is_builtin("pirate"));
+
+        pirate.shared = true;
+        manager.set_custom("pirate", pirate);
+        assert!(manager.personas_for_guild(Some("2")).iter().any(|(key, _)| key == "pirate"));
+
+        manager.remove_custom("pirate");
+        assert!(manager.get_persona("pirate").is_none());
+    }
+
+    #[test]
+    fn test_resolve_system_prompt_with_override() {
+        let manager = PersonaManager::new();
+        let chef = manager.get_persona("chef").unwrap().system_prompt;
+        let muppet = manager.get_persona("muppet").unwrap().system_prompt;
+
+        assert_eq!(manager.resolve_system_prompt("muppet", None, None, "normal"), muppet);
+        let single = PersonaOverride::Single("chef".to_string());
+        assert_eq!(manager.resolve_system_prompt("muppet", Some(&single), None, "normal"), chef);
+
+        let blend = PersonaOverride::Blend("chef".to_string(), "obi".to_string());
+        let prompt = manager.resolve_system_prompt("muppet", Some(&blend), Some("steps"), "concise");
+        assert!(prompt.contains(&chef));
+        assert!(prompt.contains("Obi-Wan"));
+        assert!(!prompt.contains(&muppet));
+        assert!(prompt.contains("actionable steps"));
+        assert!(prompt.contains("brief and to the point"));
+
+        let unknown = PersonaOverride::Blend("chef".to_string(), "nobody".to_string());
+        assert_eq!(manager.resolve_system_prompt("muppet", Some(&unknown), None, "normal"), "You are a helpful assistant.");
+    }
+
+    #[test]
+    fn test_resolve_temperature() {
+        let manager = PersonaManager::new();
+        let mut hot = manager.get_persona("chef").unwrap();
+        hot.temperature = Some(1.2);
+        manager.set_custom("hot", hot.clone());
+        hot.temperature = Some(0.4);
+        manager.set_custom("cool", hot);
+
+        assert_eq!(manager.resolve_temperature("hot", None), Some(1.2));
+        assert_eq!(manager.resolve_temperature("hot", Some(&PersonaOverride::Single("chef".to_string()))), None);
+        let both = PersonaOverride::Blend("hot".to_string(), "cool".to_string());
+        assert!((manager.resolve_temperature("chef", Some(&both)).unwrap() - 0.8).abs() < 1e-6);
+        let one = PersonaOverride::Blend("chef".to_string(), "cool".to_string());
+        assert_eq!(manager.resolve_temperature("chef", Some(&one)), Some(0.4));
+    }
+}
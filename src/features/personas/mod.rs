@@ -1,11 +1,25 @@
 //! # Personas Feature
 //!
-//! Multi-personality AI response system with 5 distinct personas.
+//! Multi-personality AI response system with 5 built-in personas, plus custom
+//! personas defined by guild admins. A question can be asked as another persona,
+//! or a blend of two, without changing the user's default.
 //!
-//! - **Version**: 1.0.0
+//! - **Version**: 1.7.0
 //! - **Since**: 0.1.0
 //! - **Toggleable**: false
 
+pub mod blend;
+pub mod creativity;
+pub mod custom;
+pub mod handoff;
 pub mod manager;
+pub mod pacing;
+pub mod regression;
+pub mod webhooks;
 
-pub use manager::{PersonaManager, Persona};
+pub use blend::{blend_prompt, persona_label};
+pub use creativity::Creativity;
+pub use custom::{custom_persona, PersonaForm, CUSTOM_PERSONA_MODAL_PREFIX, MAX_CUSTOM_PERSONAS};
+pub use manager::{PersonaManager, Persona, PersonaOverride};
+pub use pacing::{stop_typing, ResponsePacing, MAX_RESPONSE_DELAY_MS};
+pub use webhooks::PersonaWebhooks;
@@ -0,0 +1,122 @@
+//! # Feature: Response Pacing
+//!
+//! How a reply is paced: whether the typing indicator shows while the answer is
+//! generated, and how long a persona may hold the reply back for flavor. A
+//! persona's pause is capped by the guild's `max_response_delay_ms`, which is 0
+//! unless an admin raises it, so support servers get answers as fast as the
+//! model can give them. Time spent generating counts towards the pause.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use crate::core::Result;
+use serenity::http::{Http, Typing};
+use serenity::model::id::ChannelId;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Longest artificial delay a guild may allow, in milliseconds
+pub const MAX_RESPONSE_DELAY_MS: u64 = 10_000;
+
+/// Parse a `max_response_delay_ms` value, rejecting anything above [`MAX_RESPONSE_DELAY_MS`]
+pub fn parse_max_delay(value: &str) -> Option<u64> {
+    value.trim().parse::<u64>().ok().filter(|ms| *ms <= MAX_RESPONSE_DELAY_MS)
+}
+
+/// Typing indicator and artificial delay for one reply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponsePacing {
+    pub typing_indicator: bool,
+    /// Shortest time from receiving the message to answering it
+    pub delay: Duration,
+}
+
+impl Default for ResponsePacing {
+    fn default() -> Self {
+        ResponsePacing { typing_indicator: true, delay: Duration::ZERO }
+    }
+}
+
+impl ResponsePacing {
+    /// Pacing from the persona's pause and the guild's `typing_indicator` and
+    /// `max_response_delay_ms` settings; unset or invalid settings use the defaults
+    pub fn resolve(persona_delay_ms: Option<u64>, typing_indicator: Option<&str>, max_delay_ms: Option<&str>) -> Self {
+        let cap = max_delay_ms.and_then(parse_max_delay).unwrap_or(0);
+        ResponsePacing {
+            typing_indicator: typing_indicator != Some("disabled"),
+            delay: Duration::from_millis(persona_delay_ms.unwrap_or(0).min(cap)),
+        }
+    }
+
+    /// Show the typing indicator in `channel` if enabled; end it with [`stop_typing`]
+    pub fn start_typing(&self, http: &Arc<Http>, channel: ChannelId) -> Result<Option<Typing>> {
+        if self.typing_indicator {
+            Ok(Some(channel.start_typing(http)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Delay still owed after `elapsed` has already passed
+    pub fn remaining_delay(&self, elapsed: Duration) -> Duration {
+        self.delay.saturating_sub(elapsed)
+    }
+
+    /// Sleep off whatever is left of the delay
+    pub async fn wait(&self, elapsed: Duration) {
+        let remaining = self.remaining_delay(elapsed);
+        if !remaining.is_zero() {
+            tokio::time::sleep(remaining).await;
+        }
+    }
+}
+
+/// Stop a typing indicator from [`ResponsePacing::start_typing`], if one was started
+pub fn stop_typing(typing: Option<Typing>) {
+    if let Some(typing) = typing {
+        let _ = typing.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_are_fast() {
+        let pacing = ResponsePacing::resolve(Some(1500), None, None);
+        assert_eq!(pacing, ResponsePacing::default());
+        assert!(pacing.typing_indicator);
+        assert_eq!(pacing.delay, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_guild_caps_persona_delay() {
+        assert_eq!(ResponsePacing::resolve(Some(1500), None, Some("1000")).delay, Duration::from_millis(1000));
+        assert_eq!(ResponsePacing::resolve(Some(500), None, Some("1000")).delay, Duration::from_millis(500));
+        assert_eq!(ResponsePacing::resolve(None, None, Some("1000")).delay, Duration::ZERO);
+        assert_eq!(ResponsePacing::resolve(Some(1500), None, Some("99999")).delay, Duration::ZERO);
+        assert!(!ResponsePacing::resolve(None, Some("disabled"), None).typing_indicator);
+        assert!(ResponsePacing::resolve(None, Some("enabled"), None).typing_indicator);
+    }
+
+    #[test]
+    fn test_remaining_delay_counts_generation_time() {
+        let pacing = ResponsePacing::resolve(Some(2000), None, Some("2000"));
+        assert_eq!(pacing.remaining_delay(Duration::from_millis(500)), Duration::from_millis(1500));
+        assert_eq!(pacing.remaining_delay(Duration::from_secs(3)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_parse_max_delay() {
+        assert_eq!(parse_max_delay(" 250 "), Some(250));
+        assert_eq!(parse_max_delay("10000"), Some(MAX_RESPONSE_DELAY_MS));
+        assert_eq!(parse_max_delay("10001"), None);
+        assert_eq!(parse_max_delay("-1"), None);
+        assert_eq!(parse_max_delay("fast"), None);
+    }
+}
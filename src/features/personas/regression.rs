@@ -0,0 +1,301 @@
+//! # Feature: Persona Regression Tests
+//!
+//! Runs a YAML-defined suite of prompts against each persona and checks the
+//! replies against simple expectations, so prompt edits don't silently break a
+//! persona. Invoked from the command line with `bot persona test`.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Replies come from the configured LLM provider instead of OpenAI only
+//! - 1.0.0: Initial release with contains/not_contains, max_chars and tone checks
+
+use super::PersonaManager;
+use crate::features::llm::{ChatMessage, ChatRequest, LlmProvider};
+use crate::core::{BotError, Result};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Default location of the regression suite
+pub const DEFAULT_SUITE_PATH: &str = "prompt/regression.yaml";
+
+/// Tone labels the classifier may return
+pub const TONES: &[&str] = &[
+    "friendly",
+    "enthusiastic",
+    "playful",
+    "calm",
+    "wise",
+    "formal",
+    "analytical",
+    "neutral",
+];
+
+/// A suite of regression cases
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestSuite {
+    pub cases: Vec<TestCase>,
+}
+
+/// A single prompt and what every targeted persona's reply must satisfy
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestCase {
+    pub name: String,
+    pub prompt: String,
+    /// Personas to run against; all personas when omitted
+    #[serde(default)]
+    pub personas: Option<Vec<String>>,
+    /// Prompt modifier (explain, simple, steps, recipe)
+    #[serde(default)]
+    pub modifier: Option<String>,
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+/// Checks applied to a reply
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Expectations {
+    /// Substrings that must appear (case-insensitive)
+    #[serde(default)]
+    pub contains: Vec<String>,
+    /// Substrings that must not appear (case-insensitive)
+    #[serde(default)]
+    pub not_contains: Vec<String>,
+    #[serde(default)]
+    pub max_chars: Option<usize>,
+    /// Acceptable tones as labelled by the tone classifier
+    #[serde(default)]
+    pub tone: Vec<String>,
+}
+
+/// Where replies come from
+pub enum Backend {
+    /// Deterministic canned replies, for checking the suite itself without API calls
+    Mock,
+    /// A real (ideally cheap) chat model
+    Llm { provider: Arc<dyn LlmProvider>, model: String },
+}
+
+impl Backend {
+    async fn complete(&self, system_prompt: &str, user_message: &str) -> Result<String> {
+        match self {
+            Backend::Mock => Ok(mock_reply(system_prompt, user_message)),
+            Backend::Llm { provider, model } => {
+                let request = ChatRequest::new(model, vec![ChatMessage::system(system_prompt), ChatMessage::user(user_message)]);
+                let completion = provider
+                    .chat(&request)
+                    .await
+                    .map_err(|e| BotError::openai(format!("{} API error: {}", provider.name(), e)))?;
+                completion
+                    .content
+                    .map(|content| content.trim().to_string())
+                    .ok_or_else(|| BotError::openai(format!("No response from {}", provider.name())))
+            }
+        }
+    }
+
+    /// Label the tone of a reply; `None` for the mock backend
+    async fn classify_tone(&self, reply: &str) -> Result<Option<String>> {
+        if let Backend::Mock = self {
+            return Ok(None);
+        }
+        let system_prompt = format!(
+            "Classify the overall tone of the user's text. Answer with exactly one word from this list: {}.",
+            TONES.join(", ")
+        );
+        let label = self.complete(&system_prompt, reply).await?;
+        Ok(parse_tone(&label))
+    }
+}
+
+/// Outcome of one case against one persona
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    pub case: String,
+    pub persona: String,
+    pub failures: Vec<String>,
+}
+
+impl CaseResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Parse a suite from YAML
+pub fn load_suite(yaml: &str) -> Result<TestSuite> {
+    let suite: TestSuite = serde_yaml::from_str(yaml).map_err(|e| BotError::validation(format!("Invalid persona regression suite: {e}")))?;
+    if suite.cases.is_empty() {
+        return Err(BotError::validation("Persona regression suite has no cases"));
+    }
+    Ok(suite)
+}
+
+/// Canned reply used by the mock backend
+pub fn mock_reply(system_prompt: &str, user_message: &str) -> String {
+    let persona_line = system_prompt.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    format!("[mock] {} | {}", persona_line.trim(), user_message.trim())
+}
+
+/// Extract a known tone label from a classifier answer
+pub fn parse_tone(label: &str) -> Option<String> {
+    let label = label.trim().trim_matches(|c: char| !c.is_alphabetic()).to_lowercase();
+    TONES.iter().find(|t| **t == label).map(|t| t.to_string())
+}
+
+/// Check a reply against expectations; `tone` is `None` when it wasn't classified
+pub fn check_expectations(expect: &Expectations, reply: &str, tone: Option<&str>) -> Vec<String> {
+    let lower = reply.to_lowercase();
+    let mut failures = Vec::new();
+
+    for needle in &expect.contains {
+        if !lower.contains(&needle.to_lowercase()) {
+            failures.push(format!("missing \"{needle}\""));
+        }
+    }
+    for needle in &expect.not_contains {
+        if lower.contains(&needle.to_lowercase()) {
+            failures.push(format!("unexpected \"{needle}\""));
+        }
+    }
+    if let Some(max) = expect.max_chars {
+        let len = reply.chars().count();
+        if len > max {
+            failures.push(format!("{len} chars exceeds max of {max}"));
+        }
+    }
+    if let (false, Some(tone)) = (expect.tone.is_empty(), tone) {
+        if !expect.tone.iter().any(|t| t.eq_ignore_ascii_case(tone)) {
+            failures.push(format!("tone \"{tone}\" not in {:?}", expect.tone));
+        }
+    }
+
+    failures
+}
+
+/// Run every case against its target personas
+pub async fn run_suite(
+    suite: &TestSuite,
+    manager: &PersonaManager,
+    backend: &Backend,
+    only_persona: Option<&str>,
+) -> Result<Vec<CaseResult>> {
+    let mut all_personas: Vec<String> = manager.list_personas().into_iter().map(|(name, _)| name.clone()).collect();
+    all_personas.sort();
+
+    let mut results = Vec::new();
+    for case in &suite.cases {
+        let targets = case.personas.clone().unwrap_or_else(|| all_personas.clone());
+        for persona in targets {
+            if only_persona.is_some_and(|only| only != persona) {
+                continue;
+            }
+            if manager.get_persona(&persona).is_none() {
+                results.push(CaseResult {
+                    case: case.name.clone(),
+                    persona,
+                    failures: vec!["unknown persona".to_string()],
+                });
+                continue;
+            }
+
+            let system_prompt = manager.get_system_prompt(&persona, case.modifier.as_deref());
+            let failures = match backend.complete(&system_prompt, &case.prompt).await {
+                Ok(reply) => {
+                    let tone = if case.expect.tone.is_empty() {
+                        None
+                    } else {
+                        backend.classify_tone(&reply).await?
+                    };
+                    check_expectations(&case.expect, &reply, tone.as_deref())
+                }
+                Err(e) => vec![format!("request failed: {e}")],
+            };
+            results.push(CaseResult {
+                case: case.name.clone(),
+                persona,
+                failures,
+            });
+        }
+    }
+    Ok(results)
+}
+
+/// Human-readable report of suite results
+pub fn format_report(results: &[CaseResult]) -> String {
+    let mut report = String::new();
+    for result in results {
+        if result.passed() {
+            report.push_str(&format!("✅ {} [{}]\n", result.case, result.persona));
+        } else {
+            report.push_str(&format!(
+                "❌ {} [{}]: {}\n",
+                result.case,
+                result.persona,
+                result.failures.join("; ")
+            ));
+        }
+    }
+    let passed = results.iter().filter(|r| r.passed()).count();
+    report.push_str(&format!("\n{passed}/{} passed\n", results.len()));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUITE: &str = r#"
+cases:
+  - name: greeting
+    prompt: "Say hello"
+    personas: [chef]
+    expect:
+      contains: ["hello"]
+      not_contains: ["as an ai"]
+      max_chars: 200
+      tone: [friendly, playful]
+  - name: bare
+    prompt: "Anything"
+"#;
+
+    #[test]
+    fn test_load_suite() {
+        let suite = load_suite(SUITE).unwrap();
+        assert_eq!(suite.cases.len(), 2);
+        assert_eq!(suite.cases[0].personas.as_deref(), Some(&["chef".to_string()][..]));
+        assert_eq!(suite.cases[0].expect.max_chars, Some(200));
+        assert!(suite.cases[1].expect.contains.is_empty());
+        assert!(load_suite("cases: []").is_err());
+    }
+
+    #[test]
+    fn test_check_expectations() {
+        let suite = load_suite(SUITE).unwrap();
+        let expect = &suite.cases[0].expect;
+        assert!(check_expectations(expect, "Hello there!", Some("friendly")).is_empty());
+        assert!(check_expectations(expect, "Hello there!", None).is_empty());
+
+        let failures = check_expectations(expect, "As an AI I cannot", Some("formal"));
+        assert_eq!(failures.len(), 3);
+        assert_eq!(check_expectations(expect, &"hello ".repeat(50), None).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_tone() {
+        assert_eq!(parse_tone(" Friendly. "), Some("friendly".to_string()));
+        assert_eq!(parse_tone("grumpy"), None);
+    }
+
+    #[tokio::test]
+    async fn test_run_suite_with_mock() {
+        let suite = load_suite(SUITE).unwrap();
+        let manager = PersonaManager::new();
+        let results = run_suite(&suite, &manager, &Backend::Mock, Some("chef")).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.persona == "chef"));
+        assert!(format_report(&results).contains("/2 passed"));
+    }
+}
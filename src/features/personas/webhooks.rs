@@ -0,0 +1,187 @@
+//! # Feature: Persona Webhooks
+//!
+//! Posts persona replies through a channel webhook under the persona's name and
+//! avatar, so different personas are visually distinct in a channel. One
+//! webhook per channel is found or created on first use and cached. Channels
+//! where the bot lacks Manage Webhooks (and threads, which can't own
+//! webhooks) get `None` so the caller falls back to a normal reply.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Over-long persona names end with `…` and keep emoji whole
+//! - 1.0.0: Initial release with per-channel webhook caching and permission fallback
+
+use super::manager::Persona;
+use crate::core::discord_limits::{truncate, WEBHOOK_USERNAME};
+use dashmap::DashMap;
+use log::{debug, warn};
+use serenity::builder::CreateComponents;
+use serenity::http::Http;
+use serenity::model::channel::Message;
+use serenity::model::id::ChannelId;
+use serenity::model::webhook::Webhook;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Name of the webhook the bot creates and reuses in each channel
+pub const WEBHOOK_NAME: &str = "Persona Replies";
+
+/// How long a channel without webhook access is skipped before retrying
+const UNAVAILABLE_RETRY: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone)]
+enum CachedWebhook {
+    Ready(Box<Webhook>),
+    Unavailable(Instant),
+}
+
+/// Per-channel webhook cache shared by all handlers
+#[derive(Clone, Default)]
+pub struct PersonaWebhooks {
+    channels: Arc<DashMap<u64, CachedWebhook>>,
+}
+
+/// Webhook username for a persona. Discord forbids "discord" in webhook names
+/// and limits them to 80 characters.
+pub fn webhook_username(persona: &Persona) -> String {
+    let name = persona.name.trim();
+    let name = if name.is_empty() || name.to_lowercase().contains("discord") {
+        "Persona"
+    } else {
+        name
+    };
+    truncate(name, WEBHOOK_USERNAME)
+}
+
+/// Whether a webhook is one this bot created and can post through
+fn is_own_webhook(webhook: &Webhook, bot_id: u64) -> bool {
+    webhook.token.is_some()
+        && webhook.name.as_deref() == Some(WEBHOOK_NAME)
+        && webhook.user.as_ref().is_some_and(|u| u.id.0 == bot_id)
+}
+
+impl PersonaWebhooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The channel's webhook, creating it if needed. None when the bot can't manage webhooks there.
+    pub async fn webhook_for(&self, http: &Http, channel_id: ChannelId) -> Option<Webhook> {
+        match self.channels.get(&channel_id.0).map(|entry| entry.clone()) {
+            Some(CachedWebhook::Ready(webhook)) => return Some(*webhook),
+            Some(CachedWebhook::Unavailable(since)) if since.elapsed() < UNAVAILABLE_RETRY => return None,
+            _ => {}
+        }
+
+        let bot_id = match http.get_current_user().await {
+            Ok(user) => user.id.0,
+            Err(e) => {
+                warn!("Failed to look up the bot user for webhooks: {e}");
+                return None;
+            }
+        };
+        let existing = match channel_id.webhooks(http).await {
+            Ok(webhooks) => webhooks.into_iter().find(|w| is_own_webhook(w, bot_id)),
+            Err(e) => {
+                debug!("No webhook access in channel {channel_id}, using normal replies: {e}");
+                self.channels.insert(channel_id.0, CachedWebhook::Unavailable(Instant::now()));
+                return None;
+            }
+        };
+        let webhook = match existing {
+            Some(webhook) => webhook,
+            None => match channel_id.create_webhook(http, WEBHOOK_NAME).await {
+                Ok(webhook) => webhook,
+                Err(e) => {
+                    warn!("Failed to create persona webhook in channel {channel_id}: {e}");
+                    self.channels.insert(channel_id.0, CachedWebhook::Unavailable(Instant::now()));
+                    return None;
+                }
+            },
+        };
+        self.channels.insert(channel_id.0, CachedWebhook::Ready(Box::new(webhook.clone())));
+        Some(webhook)
+    }
+
+    /// Post `chunks` as the persona, with `components` on the last one. Returns the
+    /// first message, or None when nothing was sent and the caller should reply normally.
+    pub async fn send(
+        &self,
+        http: &Http,
+        channel_id: ChannelId,
+        persona: &Persona,
+        chunks: &[String],
+        components: Option<CreateComponents>,
+    ) -> Option<Message> {
+        let webhook = self.webhook_for(http, channel_id).await?;
+        let username = webhook_username(persona);
+        let mut first = None;
+        for (i, chunk) in chunks.iter().enumerate() {
+            let last = i + 1 == chunks.len();
+            let result = webhook
+                .execute(http, true, |w| {
+                    w.content(chunk).username(&username);
+                    if let Some(avatar) = &persona.avatar_url {
+                        w.avatar_url(avatar);
+                    }
+                    if let (true, Some(components)) = (last, &components) {
+                        w.set_components(components.clone());
+                    }
+                    w
+                })
+                .await;
+            match result {
+                Ok(message) => {
+                    if first.is_none() {
+                        first = message;
+                    }
+                }
+                Err(e) => {
+                    // The webhook was probably deleted; recreate it next time
+                    warn!("Persona webhook post failed in channel {channel_id}: {e}");
+                    self.channels.remove(&channel_id.0);
+                    first.as_ref()?;
+                    // Finish the reply as the bot rather than leave it cut off
+                    for chunk in &chunks[i..] {
+                        if let Err(e) = channel_id.say(http, chunk).await {
+                            warn!("Failed to finish persona reply in channel {channel_id}: {e}");
+                            break;
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+        first
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn persona(name: &str) -> Persona {
+        Persona {
+            name: name.to_string(),
+            system_prompt: String::new(),
+            description: String::new(),
+            avatar_url: None,
+            emoji: None,
+            temperature: None,
+            guild_id: None,
+            shared: false,
+            typing_delay_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_webhook_username() {
+        assert_eq!(webhook_username(&persona("Obi-Wan")), "Obi-Wan");
+        assert_eq!(webhook_username(&persona("  ")), "Persona");
+        assert_eq!(webhook_username(&persona("Discord Helper")), "Persona");
+        assert_eq!(webhook_username(&persona(&"x".repeat(100))).chars().count(), WEBHOOK_USERNAME);
+    }
+}
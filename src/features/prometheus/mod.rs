@@ -0,0 +1,17 @@
+//! # Prometheus Feature
+//!
+//! Operational metrics for Grafana and other Prometheus-compatible scrapers:
+//! gateway latency overall and per shard, shard reconnects, slash commands by
+//! name, OpenAI tokens and cost, reminder queue depth and a database query
+//! latency histogram, served as `GET /metrics` on `METRICS_LISTEN_ADDR`. Gauges are refreshed by the
+//! system metrics collection loop.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod registry;
+pub mod server;
+
+pub use registry::{escape, Metrics};
+pub use server::{metrics_router, serve_metrics, METRICS_CONTENT_TYPE};
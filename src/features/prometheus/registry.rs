@@ -0,0 +1,217 @@
+//! # Feature: Metrics Registry
+//!
+//! In-process counters and gauges rendered in the Prometheus text exposition
+//! format. Counters are bumped where the work happens (commands by the event
+//! handler, OpenAI usage by the usage tracker's background logger); gauges are
+//! set by the system metrics collection loop on each tick. Database query
+//! latency is read from the connection pool's histogram at scrape time.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Heartbeat latency and lost connections per gateway shard
+//! - 1.0.0: Initial release with command, OpenAI, gateway, reminder and query latency metrics
+
+use crate::database::QueryLatencySnapshot;
+use dashmap::DashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Values set by the collection loop, absent until its first tick
+#[derive(Debug, Clone, Copy, Default)]
+struct Gauges {
+    gateway_latency_seconds: Option<f64>,
+    reminder_queue_depth: Option<i64>,
+}
+
+/// Handle for recording metrics; clones share the same registry
+#[derive(Clone, Default)]
+pub struct Metrics {
+    /// (command, ok) -> invocations
+    commands: Arc<DashMap<(String, bool), u64>>,
+    /// (model, input or output) -> tokens
+    openai_tokens: Arc<DashMap<(String, &'static str), u64>>,
+    /// service -> USD
+    openai_cost: Arc<DashMap<String, f64>>,
+    /// shard ID -> times its gateway connection was lost
+    shard_reconnects: Arc<DashMap<u64, u64>>,
+    gauges: Arc<Mutex<Gauges>>,
+    /// (shard ID, heartbeat latency in seconds), set with the gauges
+    shard_latencies: Arc<Mutex<Vec<(u64, f64)>>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a slash command that finished, successfully or not
+    pub fn record_command(&self, command: &str, ok: bool) {
+        *self.commands.entry((command.to_string(), ok)).or_insert(0) += 1;
+    }
+
+    /// Count the tokens of a chat completion
+    pub fn record_openai_tokens(&self, model: &str, input_tokens: u32, output_tokens: u32) {
+        *self.openai_tokens.entry((model.to_string(), "input")).or_insert(0) += input_tokens as u64;
+        *self.openai_tokens.entry((model.to_string(), "output")).or_insert(0) += output_tokens as u64;
+    }
+
+    /// Add the cost of an OpenAI call (`chat`, `whisper` or `dalle`)
+    pub fn record_openai_cost(&self, service: &str, cost_usd: f64) {
+        *self.openai_cost.entry(service.to_string()).or_insert(0.0) += cost_usd;
+    }
+
+    /// Average heartbeat round trip over the shards, or None before the first heartbeat
+    pub fn set_gateway_latency(&self, latency: Option<Duration>) {
+        if let Ok(mut gauges) = self.gauges.lock() {
+            gauges.gateway_latency_seconds = latency.map(|l| l.as_secs_f64());
+        }
+    }
+
+    /// Heartbeat round trip of each shard; shards without an acknowledged heartbeat have no sample
+    pub fn set_shard_latencies(&self, latencies: &[(u64, Option<Duration>)]) {
+        if let Ok(mut shards) = self.shard_latencies.lock() {
+            *shards = latencies.iter().filter_map(|&(id, latency)| Some((id, latency?.as_secs_f64()))).collect();
+        }
+    }
+
+    /// Count a shard losing its gateway connection, before it resumes or reconnects
+    pub fn record_shard_reconnect(&self, shard_id: u64) {
+        *self.shard_reconnects.entry(shard_id).or_insert(0) += 1;
+    }
+
+    /// Reminders waiting to be delivered
+    pub fn set_reminder_queue_depth(&self, depth: i64) {
+        if let Ok(mut gauges) = self.gauges.lock() {
+            gauges.reminder_queue_depth = Some(depth);
+        }
+    }
+
+    /// Every metric in the text exposition format (version 0.0.4)
+    pub fn render(&self, query_latency: &QueryLatencySnapshot) -> String {
+        let mut out = String::new();
+        let gauges = self.gauges.lock().map(|g| *g).unwrap_or_default();
+
+        header(&mut out, "persona_gateway_latency_seconds", "gauge", "Average Discord gateway heartbeat latency across shards");
+        if let Some(latency) = gauges.gateway_latency_seconds {
+            let _ = writeln!(out, "persona_gateway_latency_seconds {latency}");
+        }
+
+        header(&mut out, "persona_shard_latency_seconds", "gauge", "Discord gateway heartbeat latency, by shard");
+        let shards = self.shard_latencies.lock().map(|s| s.clone()).unwrap_or_default();
+        for (shard, latency) in shards {
+            let _ = writeln!(out, "persona_shard_latency_seconds{{shard=\"{shard}\"}} {latency}");
+        }
+
+        header(&mut out, "persona_shard_reconnects_total", "counter", "Gateway connections lost, by shard");
+        let mut reconnects: Vec<_> = self.shard_reconnects.iter().map(|e| (*e.key(), *e.value())).collect();
+        reconnects.sort();
+        for (shard, count) in reconnects {
+            let _ = writeln!(out, "persona_shard_reconnects_total{{shard=\"{shard}\"}} {count}");
+        }
+
+        header(&mut out, "persona_commands_total", "counter", "Slash commands handled, by command name and outcome");
+        let mut commands: Vec<_> = self.commands.iter().map(|e| (e.key().clone(), *e.value())).collect();
+        commands.sort();
+        for ((command, ok), count) in commands {
+            let status = if ok { "ok" } else { "error" };
+            let _ = writeln!(out, "persona_commands_total{{command=\"{}\",status=\"{status}\"}} {count}", escape(&command));
+        }
+
+        header(&mut out, "persona_openai_tokens_total", "counter", "OpenAI chat completion tokens, by model and direction");
+        let mut tokens: Vec<_> = self.openai_tokens.iter().map(|e| (e.key().clone(), *e.value())).collect();
+        tokens.sort();
+        for ((model, kind), count) in tokens {
+            let _ = writeln!(out, "persona_openai_tokens_total{{model=\"{}\",kind=\"{kind}\"}} {count}", escape(&model));
+        }
+
+        header(&mut out, "persona_openai_cost_usd_total", "counter", "Estimated OpenAI spend in USD, by service");
+        let mut costs: Vec<_> = self.openai_cost.iter().map(|e| (e.key().clone(), *e.value())).collect();
+        costs.sort_by(|a, b| a.0.cmp(&b.0));
+        for (service, cost) in costs {
+            let _ = writeln!(out, "persona_openai_cost_usd_total{{service=\"{}\"}} {cost}", escape(&service));
+        }
+
+        header(&mut out, "persona_reminder_queue_depth", "gauge", "Reminders not yet delivered");
+        if let Some(depth) = gauges.reminder_queue_depth {
+            let _ = writeln!(out, "persona_reminder_queue_depth {depth}");
+        }
+
+        header(&mut out, "persona_db_query_duration_seconds", "histogram", "Time database methods held a pooled connection");
+        for (bound, count) in &query_latency.buckets {
+            let _ = writeln!(out, "persona_db_query_duration_seconds_bucket{{le=\"{bound}\"}} {count}");
+        }
+        let _ = writeln!(out, "persona_db_query_duration_seconds_bucket{{le=\"+Inf\"}} {}", query_latency.count);
+        let _ = writeln!(out, "persona_db_query_duration_seconds_sum {}", query_latency.sum_seconds);
+        let _ = writeln!(out, "persona_db_query_duration_seconds_count {}", query_latency.count);
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Escape a label value: backslash, double quote and newline
+pub fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::QueryLatency;
+
+    #[test]
+    fn test_render_counters_and_gauges() {
+        let metrics = Metrics::new();
+        metrics.record_command("chat", true);
+        metrics.record_command("chat", true);
+        metrics.record_command("imagine", false);
+        metrics.record_openai_tokens("gpt-4o", 120, 30);
+        metrics.record_openai_cost("chat", 0.25);
+        metrics.record_openai_cost("chat", 0.5);
+        metrics.set_gateway_latency(Some(Duration::from_millis(42)));
+        metrics.set_reminder_queue_depth(7);
+        metrics.set_shard_latencies(&[(0, Some(Duration::from_millis(40))), (1, None)]);
+        metrics.record_shard_reconnect(1);
+
+        let text = metrics.render(&QueryLatency::default().snapshot());
+        assert!(text.contains("# TYPE persona_commands_total counter\n"));
+        assert!(text.contains("persona_commands_total{command=\"chat\",status=\"ok\"} 2\n"));
+        assert!(text.contains("persona_commands_total{command=\"imagine\",status=\"error\"} 1\n"));
+        assert!(text.contains("persona_openai_tokens_total{model=\"gpt-4o\",kind=\"input\"} 120\n"));
+        assert!(text.contains("persona_openai_tokens_total{model=\"gpt-4o\",kind=\"output\"} 30\n"));
+        assert!(text.contains("persona_openai_cost_usd_total{service=\"chat\"} 0.75\n"));
+        assert!(text.contains("persona_gateway_latency_seconds 0.042\n"));
+        assert!(text.contains("persona_reminder_queue_depth 7\n"));
+        assert!(text.contains("persona_shard_latency_seconds{shard=\"0\"} 0.04\n"));
+        assert!(!text.contains("persona_shard_latency_seconds{shard=\"1\"}"));
+        assert!(text.contains("persona_shard_reconnects_total{shard=\"1\"} 1\n"));
+    }
+
+    #[test]
+    fn test_render_histogram_and_unset_gauges() {
+        let latency = QueryLatency::default();
+        latency.observe(Duration::from_millis(3));
+        let text = Metrics::new().render(&latency.snapshot());
+
+        assert!(text.contains("# TYPE persona_db_query_duration_seconds histogram\n"));
+        assert!(text.contains("persona_db_query_duration_seconds_bucket{le=\"0.001\"} 0\n"));
+        assert!(text.contains("persona_db_query_duration_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("persona_db_query_duration_seconds_bucket{le=\"+Inf\"} 1\n"));
+        assert!(text.contains("persona_db_query_duration_seconds_count 1\n"));
+        // Gauges have no sample until the collection loop sets them
+        assert!(!text.contains("\npersona_gateway_latency_seconds "));
+        assert!(!text.contains("\npersona_reminder_queue_depth "));
+    }
+
+    #[test]
+    fn test_escape_label_values() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
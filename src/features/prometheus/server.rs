@@ -0,0 +1,49 @@
+//! # Feature: Metrics Endpoint
+//!
+//! `GET /metrics` on its own listener (`METRICS_LISTEN_ADDR`), so it can be
+//! exposed to a Prometheus scraper without also exposing the health endpoint,
+//! live event stream or dashboard.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use super::registry::Metrics;
+use crate::core::Result;
+use crate::database::Database;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use log::info;
+
+/// Content type of the text exposition format
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[derive(Clone)]
+struct MetricsState {
+    metrics: Metrics,
+    database: Database,
+}
+
+async fn metrics(State(state): State<MetricsState>) -> impl IntoResponse {
+    let body = state.metrics.render(&state.database.query_latency());
+    ([(header::CONTENT_TYPE, METRICS_CONTENT_TYPE)], body)
+}
+
+/// Router serving `GET /metrics`
+pub fn metrics_router(metrics: Metrics, database: Database) -> Router {
+    Router::new().route("/metrics", get(self::metrics)).with_state(MetricsState { metrics, database })
+}
+
+/// Serve `GET /metrics` on `addr` (e.g. `0.0.0.0:9464`) until the process exits
+pub async fn serve_metrics(addr: &str, metrics: Metrics, database: Database) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Metrics endpoint listening on {}", addr);
+    axum::serve(listener, metrics_router(metrics, database)).await?;
+    Ok(())
+}
@@ -0,0 +1,324 @@
+//! # Feature: Reminder Changes in Chat
+//!
+//! Chat tools for managing reminders conversationally ("cancel my 3pm
+//! reminder", "move my dentist reminder to Friday"): `list_reminders` lets the
+//! model find the reminder meant, and `cancel_reminder` / `move_reminder`
+//! propose a change instead of making it. Proposals are only accepted while a
+//! DM or mention reply is being written; once it's sent, each one is posted
+//! under it with Confirm / Keep buttons, and only the confirm button changes
+//! the reminder. The buttons carry the whole change, so they keep working
+//! after a restart.
+//!
+//! - **Version**: 1.0.1
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.1: Time zones come from the shared `user_timezone` lookup
+//! - 1.0.0: Initial release with `list_reminders`, `cancel_reminder` and `move_reminder`
+
+use super::timezone::{parse_stored_time, user_timezone};
+use super::{parse_reminder_time, UserTimezone};
+use crate::database::Database;
+use crate::features::llm::FunctionDefinition;
+use crate::features::tools::{Tool, ToolContext};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use log::{info, warn};
+use serde_json::{json, Value};
+use serenity::async_trait;
+use std::sync::Arc;
+
+const LIST_REMINDERS: &str = "list_reminders";
+const CANCEL_REMINDER: &str = "cancel_reminder";
+const MOVE_REMINDER: &str = "move_reminder";
+
+/// Custom ID prefix of the confirmation buttons: `remindchg:<action>:<user>:<reminder>[:<unix time>]`
+pub const REMINDER_CHANGE_PREFIX: &str = "remindchg:";
+
+/// Most changes proposed in one reply
+pub const MAX_PROPOSED_CHANGES: usize = 3;
+
+/// A change to one of the user's reminders, made once they confirm
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReminderChange {
+    Cancel { reminder_id: i64 },
+    Move { reminder_id: i64, remind_at: DateTime<Utc> },
+}
+
+impl ReminderChange {
+    pub fn reminder_id(&self) -> i64 {
+        match self {
+            ReminderChange::Cancel { reminder_id } | ReminderChange::Move { reminder_id, .. } => *reminder_id,
+        }
+    }
+}
+
+/// A pressed confirmation button
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReminderChangeButton {
+    Confirm(ReminderChange),
+    /// Leave reminder `.0` as it is
+    Keep(i64),
+}
+
+/// A change proposed by the model, with the line asking the user to confirm it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposedChange {
+    pub change: ReminderChange,
+    pub question: String,
+}
+
+pub fn change_confirm_id(user_id: &str, change: &ReminderChange) -> String {
+    match change {
+        ReminderChange::Cancel { reminder_id } => format!("{REMINDER_CHANGE_PREFIX}cancel:{user_id}:{reminder_id}"),
+        ReminderChange::Move { reminder_id, remind_at } => {
+            format!("{REMINDER_CHANGE_PREFIX}move:{user_id}:{reminder_id}:{}", remind_at.timestamp())
+        }
+    }
+}
+
+pub fn change_keep_id(user_id: &str, reminder_id: i64) -> String {
+    format!("{REMINDER_CHANGE_PREFIX}keep:{user_id}:{reminder_id}")
+}
+
+/// `(user_id, button)` from a confirmation button ID
+pub fn parse_change_custom_id(custom_id: &str) -> Option<(String, ReminderChangeButton)> {
+    let mut parts = custom_id.strip_prefix(REMINDER_CHANGE_PREFIX)?.split(':');
+    let action = parts.next()?;
+    let user_id = parts.next().filter(|id| id.parse::<u64>().is_ok())?.to_string();
+    let reminder_id = parts.next()?.parse::<i64>().ok()?;
+    let button = match (action, parts.next()) {
+        ("cancel", None) => ReminderChangeButton::Confirm(ReminderChange::Cancel { reminder_id }),
+        ("keep", None) => ReminderChangeButton::Keep(reminder_id),
+        ("move", Some(at)) => {
+            let remind_at = DateTime::from_timestamp(at.parse().ok()?, 0)?;
+            ReminderChangeButton::Confirm(ReminderChange::Move { reminder_id, remind_at })
+        }
+        _ => return None,
+    };
+    parts.next().is_none().then_some((user_id, button))
+}
+
+/// Changes proposed while replies are being written, by request ID, until each reply is sent
+#[derive(Clone, Default)]
+pub struct ProposedReminderChanges {
+    open: Arc<DashMap<String, Vec<ProposedChange>>>,
+}
+
+impl ProposedReminderChanges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept proposals for a DM or mention reply about to be written
+    pub fn open(&self, request_id: &str) {
+        self.open.insert(request_id.to_string(), Vec::new());
+    }
+
+    /// Add a proposal to an open reply; false when the reply isn't open or already has [`MAX_PROPOSED_CHANGES`]
+    pub fn propose(&self, request_id: &str, proposed: ProposedChange) -> bool {
+        match self.open.get_mut(request_id) {
+            Some(mut changes) if changes.len() < MAX_PROPOSED_CHANGES => {
+                changes.retain(|c| c.change.reminder_id() != proposed.change.reminder_id());
+                changes.push(proposed);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Close a reply and return what was proposed during it
+    pub fn take(&self, request_id: &str) -> Vec<ProposedChange> {
+        self.open.remove(request_id).map(|(_, changes)| changes).unwrap_or_default()
+    }
+}
+
+/// Tools listing the user's reminders and proposing changes to them
+#[derive(Clone)]
+pub struct ReminderTools {
+    database: Database,
+    proposed: ProposedReminderChanges,
+}
+
+impl ReminderTools {
+    pub fn new(database: Database, proposed: ProposedReminderChanges) -> Self {
+        ReminderTools { database, proposed }
+    }
+
+    async fn list(&self, user_id: &str) -> String {
+        let reminders = match self.database.get_user_reminders(user_id).await {
+            Ok(reminders) => reminders,
+            Err(e) => {
+                warn!("Chat tool failed to list reminders for {user_id}: {e}");
+                return "Error: reminders are unavailable right now".to_string();
+            }
+        };
+        let timezone = user_timezone(&self.database, user_id).await;
+        format_reminders(&reminders, &timezone)
+    }
+
+    async fn propose(&self, name: &str, args: &Value, context: &ToolContext) -> String {
+        let (Some(user_id), Some(request_id)) = (&context.user_id, &context.request_id) else {
+            return "Error: reminders can only be changed in a conversation with a user".to_string();
+        };
+        let Some(reminder_id) = args.get("reminder_id").and_then(Value::as_i64) else {
+            return "Error: `reminder_id` is required; call list_reminders to find it".to_string();
+        };
+        let reminders = match self.database.get_user_reminders(user_id).await {
+            Ok(reminders) => reminders,
+            Err(e) => {
+                warn!("Chat tool failed to load reminders for {user_id}: {e}");
+                return "Error: reminders are unavailable right now".to_string();
+            }
+        };
+        let Some((_, _, text, remind_at)) = reminders.iter().find(|(id, ..)| *id == reminder_id) else {
+            return format!("Error: the user has no pending reminder #{reminder_id}; call list_reminders to see them");
+        };
+
+        let timezone = user_timezone(&self.database, user_id).await;
+        let current = timezone.format_stored(remind_at);
+        let (change, question) = if name == CANCEL_REMINDER {
+            (
+                ReminderChange::Cancel { reminder_id },
+                format!("Cancel reminder #{reminder_id} for {current}: {text}?"),
+            )
+        } else {
+            let Some(time) = args.get("time").and_then(Value::as_str) else {
+                return "Error: `time` is required".to_string();
+            };
+            let Some(new_time) = parse_reminder_time(time, Utc::now(), &timezone) else {
+                return "Error: invalid time. Use a duration like `30m` or `2h`, or a time like `9am` or `friday 14:30`".to_string();
+            };
+            (
+                ReminderChange::Move { reminder_id, remind_at: new_time },
+                format!("Move reminder #{reminder_id} ({text}) from {current} to {}?", timezone.format(new_time)),
+            )
+        };
+
+        if !self.proposed.propose(request_id, ProposedChange { change, question: question.clone() }) {
+            return format!(
+                "Error: reminders can only be cancelled or moved when the user messages the bot directly or mentions it, \
+                 at most {MAX_PROPOSED_CHANGES} per reply; they can also use /reminders"
+            );
+        }
+        info!("Chat tool proposed a change to reminder {reminder_id} for user {user_id}");
+        format!(
+            "Not changed yet. Buttons asking \"{question}\" will appear under your reply; \
+             the change is only made if the user confirms there. Tell them so briefly."
+        )
+    }
+}
+
+/// One line per pending `(id, channel_id, text, remind_at)` reminder, in the user's zone
+fn format_reminders(reminders: &[(i64, String, String, String)], timezone: &UserTimezone) -> String {
+    if reminders.is_empty() {
+        return "The user has no pending reminders".to_string();
+    }
+    let mut lines = vec![format!("Pending reminders (times in {}):", timezone.label())];
+    for (id, _, text, remind_at) in reminders {
+        let when = match parse_stored_time(remind_at) {
+            Some(at) => timezone.format(at),
+            None => remind_at.clone(),
+        };
+        lines.push(format!("#{id} at {when}: {text}"));
+    }
+    lines.join("\n")
+}
+
+#[async_trait]
+impl Tool for ReminderTools {
+    fn definitions(&self) -> Vec<FunctionDefinition> {
+        vec![
+            FunctionDefinition {
+                name: LIST_REMINDERS.to_string(),
+                description: "List the user's pending reminders with their IDs, times and texts. Use it to find the reminder the user means before cancelling or moving it."
+                    .to_string(),
+                parameters: json!({ "type": "object", "properties": {} }),
+            },
+            FunctionDefinition {
+                name: CANCEL_REMINDER.to_string(),
+                description: "Ask the user to confirm cancelling one of their reminders. Only use this when the user asks to cancel or delete a reminder."
+                    .to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "reminder_id": { "type": "integer", "description": "ID from list_reminders" }
+                    },
+                    "required": ["reminder_id"]
+                }),
+            },
+            FunctionDefinition {
+                name: MOVE_REMINDER.to_string(),
+                description: "Ask the user to confirm moving one of their reminders to a new time. Only use this when the user asks to move, postpone or reschedule a reminder."
+                    .to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "reminder_id": { "type": "integer", "description": "ID from list_reminders" },
+                        "time": { "type": "string", "description": "The new time: a duration from now like 2h, or a time in the user's zone like 9am, tomorrow 14:30 or friday 5pm" }
+                    },
+                    "required": ["reminder_id", "time"]
+                }),
+            },
+        ]
+    }
+
+    async fn run(&self, name: &str, arguments: &str, context: &ToolContext) -> String {
+        let args: Value = serde_json::from_str(arguments).unwrap_or(Value::Null);
+        match name {
+            LIST_REMINDERS => match &context.user_id {
+                Some(user_id) => self.list(user_id).await,
+                None => "Error: reminders need a user".to_string(),
+            },
+            CANCEL_REMINDER | MOVE_REMINDER => self.propose(name, &args, context).await,
+            other => format!("Error: unknown tool `{other}`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_custom_id_round_trip() {
+        let at = Utc.with_ymd_and_hms(2026, 10, 16, 15, 0, 0).unwrap();
+        for change in [ReminderChange::Cancel { reminder_id: 12 }, ReminderChange::Move { reminder_id: 12, remind_at: at }] {
+            let id = change_confirm_id("42", &change);
+            assert!(id.len() <= 100);
+            assert_eq!(parse_change_custom_id(&id), Some(("42".to_string(), ReminderChangeButton::Confirm(change))));
+        }
+        assert_eq!(parse_change_custom_id(&change_keep_id("42", 12)), Some(("42".to_string(), ReminderChangeButton::Keep(12))));
+        assert_eq!(parse_change_custom_id("remindchg:cancel:42:12:99"), None);
+        assert_eq!(parse_change_custom_id("remindchg:move:42:12"), None);
+        assert_eq!(parse_change_custom_id("remindchg:cancel:bob:12"), None);
+    }
+
+    #[test]
+    fn test_proposals_need_an_open_reply() {
+        let proposed = ProposedReminderChanges::new();
+        let change = |reminder_id| ProposedChange { change: ReminderChange::Cancel { reminder_id }, question: String::new() };
+        assert!(!proposed.propose("r1", change(1)));
+
+        proposed.open("r1");
+        assert!(proposed.propose("r1", change(1)));
+        assert!(proposed.propose("r1", change(1)));
+        assert!(proposed.propose("r1", change(2)));
+        assert!(proposed.propose("r1", change(3)));
+        assert!(!proposed.propose("r1", change(4)));
+        assert_eq!(proposed.take("r1").len(), MAX_PROPOSED_CHANGES);
+        assert!(proposed.take("r1").is_empty());
+        assert!(!proposed.propose("r1", change(1)));
+    }
+
+    #[test]
+    fn test_format_reminders() {
+        let utc = UserTimezone::default();
+        assert_eq!(format_reminders(&[], &utc), "The user has no pending reminders");
+        let reminders = vec![(7, "1".to_string(), "dentist".to_string(), "2026-10-16 15:00:00".to_string())];
+        let text = format_reminders(&reminders, &utc);
+        assert!(text.contains("#7 at Fri 2026-10-16 15:00 (UTC): dentist"));
+    }
+}
@@ -0,0 +1,112 @@
+//! # Feature: Reminder Message Context
+//!
+//! Helpers for attaching the originating Discord message to a reminder, so that
+//! "remind me about this" can show what "this" was at delivery time.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Snippets are cut on a grapheme boundary
+//! - 1.0.0: Initial release with message link parsing and snippet building
+
+use crate::core::discord_limits::truncate;
+
+/// Maximum number of characters kept from the originating message
+pub const SNIPPET_MAX_CHARS: usize = 300;
+
+/// Parse a Discord message link into `(guild_id, channel_id, message_id)`.
+///
+/// Accepts `https://discord.com/channels/<guild|@me>/<channel>/<message>` as well as
+/// the `discordapp.com`, `ptb.` and `canary.` variants. `guild_id` is `None` for DMs.
+pub fn parse_message_link(link: &str) -> Option<(Option<u64>, u64, u64)> {
+    let link = link.trim().trim_start_matches('<').trim_end_matches('>');
+    let path = link
+        .split_once("/channels/")
+        .filter(|(host, _)| host.contains("discord.com") || host.contains("discordapp.com"))?
+        .1;
+
+    let parts: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+
+    let guild_id = match parts[0] {
+        "@me" => None,
+        id => Some(id.parse::<u64>().ok()?),
+    };
+    let channel_id = parts[1].parse::<u64>().ok()?;
+    let message_id = parts[2].parse::<u64>().ok()?;
+
+    Some((guild_id, channel_id, message_id))
+}
+
+/// Build a jump link for a message
+pub fn build_message_link(guild_id: Option<u64>, channel_id: u64, message_id: u64) -> String {
+    let guild = guild_id
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| "@me".to_string());
+    format!("https://discord.com/channels/{guild}/{channel_id}/{message_id}")
+}
+
+/// Build a single-line snippet of message content, truncated on a grapheme boundary
+pub fn build_snippet(author: &str, content: &str) -> String {
+    let flattened = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    let body = if flattened.is_empty() {
+        "*(no text content)*".to_string()
+    } else {
+        truncate(&flattened, SNIPPET_MAX_CHARS)
+    };
+    format!("**{author}:** {body}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_guild_message_link() {
+        let parsed = parse_message_link("https://discord.com/channels/1/22/333");
+        assert_eq!(parsed, Some((Some(1), 22, 333)));
+    }
+
+    #[test]
+    fn test_parse_dm_and_variant_links() {
+        assert_eq!(
+            parse_message_link("https://discord.com/channels/@me/22/333"),
+            Some((None, 22, 333))
+        );
+        assert_eq!(
+            parse_message_link("<https://canary.discordapp.com/channels/1/22/333>"),
+            Some((Some(1), 22, 333))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_links() {
+        assert_eq!(parse_message_link("https://example.com/channels/1/2/3"), None);
+        assert_eq!(parse_message_link("https://discord.com/channels/1/2"), None);
+        assert_eq!(parse_message_link("not a link"), None);
+    }
+
+    #[test]
+    fn test_build_message_link_roundtrip() {
+        let link = build_message_link(Some(1), 22, 333);
+        assert_eq!(parse_message_link(&link), Some((Some(1), 22, 333)));
+        assert!(build_message_link(None, 22, 333).contains("/@me/"));
+    }
+
+    #[test]
+    fn test_build_snippet_truncates() {
+        let long = "ä".repeat(SNIPPET_MAX_CHARS + 50);
+        let snippet = build_snippet("alice", &long);
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.chars().count() <= SNIPPET_MAX_CHARS + "**alice:** ".len());
+        assert_eq!(build_snippet("bob", "  hi\nthere "), "**bob:** hi there");
+
+        // Flags are two chars each and never split
+        let flags = build_snippet("carol", &"🇫🇷".repeat(SNIPPET_MAX_CHARS));
+        assert!(flags.trim_end_matches('…').ends_with("🇫🇷"));
+    }
+}
@@ -1,11 +1,23 @@
 //! # Reminders Feature
 //!
-//! Scheduled reminder system with persona-aware delivery.
+//! Scheduled reminder system with persona-aware delivery. The scheduler also
+//! drives calendar event announcements.
 //!
-//! - **Version**: 1.0.0
+//! - **Version**: 1.7.0
 //! - **Since**: 0.1.0
 //! - **Toggleable**: true
 
+pub mod chat_changes;
+pub mod context;
+pub mod quiet_hours;
 pub mod scheduler;
+pub mod timezone;
 
-pub use scheduler::ReminderScheduler;
+pub use chat_changes::{
+    parse_change_custom_id, ProposedChange, ProposedReminderChanges, ReminderChange, ReminderChangeButton, ReminderTools,
+    REMINDER_CHANGE_PREFIX,
+};
+pub use context::{build_message_link, build_snippet, parse_message_link};
+pub use quiet_hours::QuietHours;
+pub use scheduler::{parse_duration, ReminderScheduler};
+pub use timezone::{parse_reminder_time, user_timezone, UserTimezone};
@@ -0,0 +1,188 @@
+//! # Feature: Quiet Hours
+//!
+//! Per-user do-not-disturb windows. Deliveries that fall inside a user's quiet
+//! hours are held until the window ends, unless they are marked urgent.
+//!
+//! - **Version**: 1.2.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.2.0: Offsets saved with 1.0.0 windows become the user's `/timezone` when they have none
+//! - 1.1.0: Windows are read in the user's `/timezone`, so they follow daylight saving
+//! - 1.0.0: Initial release with HH:MM windows, UTC offsets and overnight windows
+
+use super::timezone::UserTimezone;
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+
+/// A user's quiet hours window, as wall-clock times in their `/timezone`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Build quiet hours from `HH:MM` strings
+    pub fn from_strings(start: &str, end: &str) -> Option<Self> {
+        let start = parse_hhmm(start)?;
+        let end = parse_hhmm(end)?;
+        if start == end {
+            return None;
+        }
+        Some(Self { start, end })
+    }
+
+    /// If `now` falls inside the quiet window in `timezone`, return the UTC instant the window ends
+    pub fn window_end_after(&self, now: DateTime<Utc>, timezone: &UserTimezone) -> Option<DateTime<Utc>> {
+        let local = timezone.to_local(now);
+        let time = local.time();
+
+        let in_window = if self.start < self.end {
+            time >= self.start && time < self.end
+        } else {
+            // Overnight window, e.g. 22:00-08:00
+            time >= self.start || time < self.end
+        };
+        if !in_window {
+            return None;
+        }
+
+        let mut end_date = local.date();
+        if self.start > self.end && time >= self.start {
+            end_date += Duration::days(1);
+        }
+        timezone.from_local(end_date.and_time(self.end))
+    }
+
+    /// Parse the stored preference value (`HH:MM-HH:MM`). Values saved before windows
+    /// followed `/timezone` carried a `|offset_minutes` suffix; migration 16 moves it
+    /// into the `timezone` preference, and any left over is ignored.
+    pub fn from_storage(value: &str) -> Option<Self> {
+        let window = value.split('|').next()?;
+        let (start, end) = window.split_once('-')?;
+        Self::from_strings(start, end)
+    }
+
+    /// Serialize for storage as a user preference value
+    pub fn to_storage(&self) -> String {
+        format!("{}-{}", self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+
+    /// Human-readable description, e.g. `22:00–08:00`
+    pub fn describe(&self) -> String {
+        format!("{}–{}", self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+}
+
+/// Parse a `HH:MM` (24h) time string
+pub fn parse_hhmm(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// Parse a UTC offset like `+2`, `-5`, `+05:30` or `0` into minutes
+pub fn parse_utc_offset(value: &str) -> Option<i32> {
+    let value = value.trim().trim_start_matches("UTC").trim_start_matches("utc");
+    if value.is_empty() || value == "0" {
+        return Some(0);
+    }
+
+    let (sign, rest) = match value.chars().next()? {
+        '+' => (1, &value[1..]),
+        '-' => (-1, &value[1..]),
+        _ => (1, value),
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((h, m)) => (h.parse::<i32>().ok()?, m.parse::<i32>().ok()?),
+        None => (rest.parse::<i32>().ok()?, 0),
+    };
+    if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+        return None;
+    }
+    Some(sign * (hours * 60 + minutes))
+}
+
+/// Format an offset in minutes as `UTC+HH:MM`
+pub fn format_utc_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let abs = minutes.abs();
+    format!("UTC{}{:02}:{:02}", sign, abs / 60, abs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_daytime_window() {
+        let quiet = QuietHours::from_strings("12:00", "13:00").unwrap();
+        let tz = UserTimezone::default();
+        assert_eq!(quiet.window_end_after(utc(12, 30), &tz), Some(utc(13, 0)));
+        assert_eq!(quiet.window_end_after(utc(13, 0), &tz), None);
+        assert_eq!(quiet.window_end_after(utc(11, 59), &tz), None);
+    }
+
+    #[test]
+    fn test_overnight_window() {
+        let quiet = QuietHours::from_strings("22:00", "08:00").unwrap();
+        let tz = UserTimezone::default();
+        let end = quiet.window_end_after(utc(23, 0), &tz).unwrap();
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 6, 2, 8, 0, 0).unwrap());
+        assert_eq!(quiet.window_end_after(utc(3, 0), &tz), Some(utc(8, 0)));
+        assert_eq!(quiet.window_end_after(utc(12, 0), &tz), None);
+    }
+
+    #[test]
+    fn test_window_with_offset() {
+        // 22:00-08:00 at UTC+2 is 20:00-06:00 UTC
+        let quiet = QuietHours::from_strings("22:00", "08:00").unwrap();
+        let tz = UserTimezone::Fixed(120);
+        assert_eq!(quiet.window_end_after(utc(21, 0), &tz).unwrap(), Utc.with_ymd_and_hms(2025, 6, 2, 6, 0, 0).unwrap());
+        assert_eq!(quiet.window_end_after(utc(19, 0), &tz), None);
+    }
+
+    #[test]
+    fn test_named_zone_follows_daylight_saving() {
+        // 22:00-08:00 in Berlin ends at 06:00 UTC in summer and 07:00 UTC in winter
+        let quiet = QuietHours::from_strings("22:00", "08:00").unwrap();
+        let tz = UserTimezone::Named(chrono_tz::Europe::Berlin);
+        let summer = Utc.with_ymd_and_hms(2026, 7, 1, 3, 0, 0).unwrap();
+        let winter = Utc.with_ymd_and_hms(2026, 12, 1, 3, 0, 0).unwrap();
+        assert_eq!(quiet.window_end_after(summer, &tz), Some(Utc.with_ymd_and_hms(2026, 7, 1, 6, 0, 0).unwrap()));
+        assert_eq!(quiet.window_end_after(winter, &tz), Some(Utc.with_ymd_and_hms(2026, 12, 1, 7, 0, 0).unwrap()));
+        // 20:30 UTC is 22:30 in summer (quiet) but 21:30 in winter (not yet)
+        assert!(quiet.window_end_after(Utc.with_ymd_and_hms(2026, 7, 1, 20, 30, 0).unwrap(), &tz).is_some());
+        assert!(quiet.window_end_after(Utc.with_ymd_and_hms(2026, 12, 1, 20, 30, 0).unwrap(), &tz).is_none());
+    }
+
+    #[test]
+    fn test_invalid_windows() {
+        assert!(QuietHours::from_strings("25:00", "08:00").is_none());
+        assert!(QuietHours::from_strings("08:00", "08:00").is_none());
+    }
+
+    #[test]
+    fn test_storage_roundtrip() {
+        let quiet = QuietHours::from_strings("22:00", "07:30").unwrap();
+        assert_eq!(quiet.to_storage(), "22:00-07:30");
+        assert_eq!(QuietHours::from_storage(&quiet.to_storage()), Some(quiet));
+        assert_eq!(QuietHours::from_storage("22:00-07:30|-300"), Some(quiet));
+        assert_eq!(QuietHours::from_storage("garbage"), None);
+    }
+
+    #[test]
+    fn test_parse_utc_offset() {
+        assert_eq!(parse_utc_offset("0"), Some(0));
+        assert_eq!(parse_utc_offset("+2"), Some(120));
+        assert_eq!(parse_utc_offset("-05:30"), Some(-330));
+        assert_eq!(parse_utc_offset("UTC+1"), Some(60));
+        assert_eq!(parse_utc_offset("+15"), None);
+        assert_eq!(parse_utc_offset("abc"), None);
+        assert_eq!(format_utc_offset(-330), "UTC-05:30");
+    }
+}
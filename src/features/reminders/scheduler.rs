@@ -4,43 +4,103 @@
 //! for due reminders every 60 seconds and delivers them in the user's preferred
 //! persona style.
 //!
-//! - **Version**: 1.1.0
+//! - **Version**: 1.8.0
 //! - **Since**: 0.1.0
 //! - **Toggleable**: true
 //!
 //! ## Changelog
+//! - 1.8.0: Fall back to the origin channel, then a DM, when the inbox can't be posted to
+//! - 1.7.0: Quiet hours are checked against an injectable clock
+//! - 1.6.0: Reminder times can be clock times in the user's `/timezone`
+//! - 1.5.0: Also drive calendar event announcements on each tick
+//! - 1.4.0: Hold non-urgent reminders during the user's quiet hours
+//! - 1.3.0: Route guild reminders to the `reminders_channel` inbox when configured
+//! - 1.2.0: Deliver the originating message link and snippet as an embed
 //! - 1.1.0: Added OpenAI usage tracking for reminder message generation
 //! - 1.0.0: Initial release with time parsing (30m, 2h, 1d, 1h30m) and persona delivery
 
-use crate::database::Database;
+use crate::core::discord_limits::{fit_embed, truncate, MESSAGE_CONTENT};
+use crate::database::{Database, PendingReminder};
+use crate::features::calendar::{process_calendars, CalendarCache};
 use crate::features::personas::PersonaManager;
+use crate::features::reminders::quiet_hours::QuietHours;
+use crate::features::reminders::timezone::user_timezone;
 use crate::features::analytics::UsageTracker;
-use anyhow::Result;
+use crate::features::analytics::cost_report::cost_feature;
+use crate::features::llm::{ChatMessage, ChatRequest, LlmProvider};
+use crate::core::clock::system_clock;
+use crate::core::{Clock, Result};
 use log::{debug, error, info, warn};
-use openai::chat::{ChatCompletion, ChatCompletionMessage, ChatCompletionMessageRole};
 use serenity::http::Http;
 use serenity::model::id::{ChannelId, UserId};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::interval;
 
+/// Parse a time duration string like "30m", "2h", "1d", "1h30m" into seconds
+pub fn parse_duration(time_str: &str) -> Option<i64> {
+    let time_str = time_str.trim().to_lowercase();
+    let mut total_seconds: i64 = 0;
+    let mut current_number = String::new();
+
+    for c in time_str.chars() {
+        if c.is_ascii_digit() {
+            current_number.push(c);
+        } else if !current_number.is_empty() {
+            let value: i64 = current_number.parse().ok()?;
+            current_number.clear();
+
+            let seconds = match c {
+                's' => value,
+                'm' => value * 60,
+                'h' => value * 60 * 60,
+                'd' => value * 60 * 60 * 24,
+                'w' => value * 60 * 60 * 24 * 7,
+                _ => return None,
+            };
+            total_seconds += seconds;
+        }
+    }
+
+    if total_seconds > 0 {
+        Some(total_seconds)
+    } else {
+        None
+    }
+}
+
 pub struct ReminderScheduler {
     database: Database,
     persona_manager: PersonaManager,
     openai_model: String,
+    llm: Arc<dyn LlmProvider>,
     usage_tracker: UsageTracker,
+    http_client: reqwest::Client,
+    calendar_cache: CalendarCache,
+    clock: Arc<dyn Clock>,
 }
 
 impl ReminderScheduler {
-    pub fn new(database: Database, openai_model: String, usage_tracker: UsageTracker) -> Self {
+    pub fn new(database: Database, openai_model: String, llm: Arc<dyn LlmProvider>, usage_tracker: UsageTracker) -> Self {
         Self {
             database,
             persona_manager: PersonaManager::new(),
             openai_model,
+            llm,
             usage_tracker,
+            http_client: reqwest::Client::new(),
+            calendar_cache: CalendarCache::new(),
+            clock: system_clock(),
         }
     }
 
+    /// Use `clock` instead of the system clock, e.g. a fake one in tests. Which
+    /// reminders are due is decided by the database's own clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Start the reminder scheduler loop
     /// This should be spawned as a tokio task
     pub async fn run(&self, http: Arc<Http>) {
@@ -54,6 +114,10 @@ impl ReminderScheduler {
             if let Err(e) = self.process_due_reminders(&http).await {
                 error!("❌ Error processing reminders: {e}");
             }
+
+            if let Err(e) = process_calendars(&http, &self.database, &self.http_client, &self.calendar_cache).await {
+                error!("❌ Error processing calendars: {e}");
+            }
         }
     }
 
@@ -67,8 +131,23 @@ impl ReminderScheduler {
 
         info!("⏰ Processing {} due reminder(s)", reminders.len());
 
-        for (id, user_id, channel_id, reminder_text) in reminders {
-            match self.deliver_reminder(http, id, &user_id, &channel_id, &reminder_text).await {
+        for reminder in reminders {
+            let id = reminder.id;
+            let user_id = reminder.user_id.clone();
+
+            // Hold non-urgent reminders until the user's quiet hours end
+            if !reminder.urgent {
+                if let Some(window_end) = self.quiet_hours_end(&user_id).await {
+                    let remind_at = window_end.format("%Y-%m-%d %H:%M:%S").to_string();
+                    info!("🌙 Holding reminder #{id} for user {user_id} until {remind_at} (quiet hours)");
+                    if let Err(e) = self.database.reschedule_reminder(id, &remind_at).await {
+                        error!("❌ Failed to reschedule reminder {id}: {e}");
+                    }
+                    continue;
+                }
+            }
+
+            match self.deliver_reminder(http, &reminder).await {
                 Ok(_) => {
                     info!("✅ Delivered reminder #{id} to user {user_id}");
                 }
@@ -85,32 +164,45 @@ impl ReminderScheduler {
         Ok(())
     }
 
-    async fn deliver_reminder(
-        &self,
-        http: &Arc<Http>,
-        reminder_id: i64,
-        user_id: &str,
-        channel_id: &str,
-        reminder_text: &str,
-    ) -> Result<()> {
+    async fn deliver_reminder(&self, http: &Arc<Http>, reminder: &PendingReminder) -> Result<()> {
+        let reminder_id = reminder.id;
+        let user_id = reminder.user_id.as_str();
+        let channel_id = reminder.channel_id.as_str();
+        let reminder_text = reminder.reminder_text.as_str();
+
         // Get user's preferred persona
         let persona_name = self.database.get_user_persona(user_id).await.unwrap_or_else(|_| "obi".to_string());
 
         // Get the persona's system prompt
         let persona = self.persona_manager.get_persona(&persona_name);
-        let system_prompt = persona.map(|p| p.system_prompt.as_str()).unwrap_or("");
+        let system_prompt = persona.as_ref().map(|p| p.system_prompt.as_str()).unwrap_or("");
 
         // Generate a persona-flavored reminder message
         let reminder_message = self.generate_reminder_message(&persona_name, system_prompt, reminder_text, user_id, channel_id).await?;
 
-        // Parse channel ID
-        let channel = ChannelId(channel_id.parse::<u64>()?);
+        // Parse channel ID, routing to the guild's reminders inbox if one is configured
+        let origin = ChannelId(channel_id.parse::<u64>()?);
+        let inbox = self.get_inbox_channel(reminder.guild_id.as_deref()).await;
         let user = UserId(user_id.parse::<u64>()?);
 
-        // Send the reminder with a user mention
-        let message = format!("<@{user}>\n\n{reminder_message}");
-
-        channel.say(http, &message).await?;
+        // Send the reminder with a user mention, falling back from the inbox to the
+        // origin channel and then to a DM when the bot can't post there
+        let mut delivered = false;
+        if let Some(inbox) = inbox.filter(|inbox| *inbox != origin) {
+            let message = format!("<@{user}> *(set in <#{origin}>)*\n\n{reminder_message}");
+            match send_reminder(http, inbox, &message, reminder).await {
+                Ok(()) => delivered = true,
+                Err(e) => warn!("⚠️ Couldn't post reminder #{reminder_id} to inbox {inbox}, falling back to <#{origin}>: {e}"),
+            }
+        }
+        if !delivered {
+            let message = format!("<@{user}>\n\n{reminder_message}");
+            if let Err(e) = send_reminder(http, origin, &message, reminder).await {
+                warn!("⚠️ Couldn't post reminder #{reminder_id} to {origin}, falling back to a DM: {e}");
+                let dm = user.create_dm_channel(http).await?;
+                send_reminder(http, dm.id, &reminder_message, reminder).await?;
+            }
+        }
 
         // Mark reminder as complete
         self.database.complete_reminder(reminder_id).await?;
@@ -118,6 +210,26 @@ impl ReminderScheduler {
         Ok(())
     }
 
+    /// If the user is currently in their quiet hours, return when the window ends
+    async fn quiet_hours_end(&self, user_id: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        let stored = self.database.get_user_preference(user_id, "quiet_hours").await.ok().flatten()?;
+        let quiet = QuietHours::from_storage(&stored)?;
+        quiet.window_end_after(self.clock.now(), &user_timezone(&self.database, user_id).await)
+    }
+
+    /// Look up the guild's reminders inbox channel, if configured
+    async fn get_inbox_channel(&self, guild_id: Option<&str>) -> Option<ChannelId> {
+        let guild_id = guild_id?;
+        match self.database.get_guild_setting(guild_id, "reminders_channel").await {
+            Ok(Some(value)) => value.parse::<u64>().ok().map(ChannelId),
+            Ok(None) => None,
+            Err(e) => {
+                warn!("⚠️ Failed to read reminders_channel for guild {guild_id}: {e}");
+                None
+            }
+        }
+    }
+
     async fn generate_reminder_message(
         &self,
         persona_name: &str,
@@ -135,26 +247,11 @@ impl ReminderScheduler {
             The reminder message is: \"{reminder_text}\""
         );
 
-        let chat_completion = ChatCompletion::builder(&self.openai_model, vec![
-            ChatCompletionMessage {
-                role: ChatCompletionMessageRole::System,
-                content: Some(system_prompt),
-                name: None,
-                function_call: None,
-                tool_call_id: None,
-                tool_calls: None,
-            },
-            ChatCompletionMessage {
-                role: ChatCompletionMessageRole::User,
-                content: Some("Please deliver this reminder to me now.".to_string()),
-                name: None,
-                function_call: None,
-                tool_call_id: None,
-                tool_calls: None,
-            },
-        ])
-        .create()
-        .await;
+        let request = ChatRequest::new(&self.openai_model, vec![
+            ChatMessage::system(system_prompt),
+            ChatMessage::user("Please deliver this reminder to me now."),
+        ]);
+        let chat_completion = self.llm.chat(&request).await;
 
         match chat_completion {
             Ok(completion) => {
@@ -169,13 +266,12 @@ impl ReminderScheduler {
                         None, // Reminders don't have guild context stored
                         Some(channel_id),
                         None,
+                        cost_feature::REMINDERS,
                     );
                 }
 
                 let response = completion
-                    .choices
-                    .first()
-                    .and_then(|choice| choice.message.content.clone())
+                    .content
                     .unwrap_or_else(|| self.fallback_reminder(persona_name, reminder_text));
                 Ok(response)
             }
@@ -197,3 +293,47 @@ impl ReminderScheduler {
         }
     }
 }
+
+/// Post a reminder to a channel, attaching the originating message as an embed when known
+async fn send_reminder(http: &Arc<Http>, channel: ChannelId, message: &str, reminder: &PendingReminder) -> Result<()> {
+    let message = truncate(message, MESSAGE_CONTENT);
+    match (&reminder.source_message_link, &reminder.source_snippet) {
+        (None, None) => {
+            channel.say(http, &message).await?;
+        }
+        (link, snippet) => {
+            channel
+                .send_message(http, |m| {
+                    m.content(&message).embed(|e| {
+                        e.title("📎 Original message")
+                            .description(snippet.as_deref().unwrap_or("*(no preview available)*"));
+                        if let Some(link) = link {
+                            e.url(link).field("Jump to message", link, false);
+                        }
+                        fit_embed(e)
+                    })
+                })
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clock::{sql_timestamp, FakeClock};
+    use chrono::{TimeZone, Utc};
+
+    #[tokio::test]
+    async fn test_due_reminders_follow_the_clock() {
+        let clock = Arc::new(FakeClock::new(Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()));
+        let database = Database::new(":memory:", 1).await.unwrap().with_clock(clock.clone());
+        let remind_at = sql_timestamp(clock.now() + chrono::Duration::minutes(10));
+        database.add_reminder("1", "2", None, "stretch", &remind_at, None, None, false).await.unwrap();
+
+        assert!(database.get_pending_reminders().await.unwrap().is_empty());
+        clock.advance(chrono::Duration::minutes(10));
+        assert_eq!(database.get_pending_reminders().await.unwrap().len(), 1);
+    }
+}
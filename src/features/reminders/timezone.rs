@@ -0,0 +1,338 @@
+//! # Feature: User Timezones
+//!
+//! Per-user time zones set with `/timezone`, stored as the `timezone` user
+//! preference. Reminder times like `9am` or `tomorrow 14:30` are read in the
+//! user's zone, and reminder lists and analytics timestamps are shown in it.
+//! Everything is still stored and compared in UTC.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Shared `user_timezone` lookup, used for quiet hours as well
+//! - 1.0.0: Initial release with IANA zone names, fixed UTC offsets and clock-time reminders
+
+use super::quiet_hours::{format_utc_offset, parse_utc_offset};
+use super::scheduler::parse_duration;
+use crate::database::Database;
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc, Weekday};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use log::warn;
+
+/// `extended_user_preferences` key holding the user's zone
+pub const TIMEZONE_PREFERENCE: &str = "timezone";
+
+/// Format for timestamps stored by SQLite's `CURRENT_TIMESTAMP` and for reminder times
+pub const STORED_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A user's time zone: an IANA zone (follows daylight saving) or a fixed offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserTimezone {
+    Named(Tz),
+    /// Minutes east of UTC
+    Fixed(i32),
+}
+
+impl Default for UserTimezone {
+    fn default() -> Self {
+        UserTimezone::Fixed(0)
+    }
+}
+
+impl UserTimezone {
+    /// Parse an IANA name (`Europe/Berlin`, any case) or an offset (`+2`, `UTC-05:30`)
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        if let Some(tz) = TZ_VARIANTS.iter().find(|tz| tz.name().eq_ignore_ascii_case(value)) {
+            return Some(UserTimezone::Named(*tz));
+        }
+        let offset = parse_utc_offset(value)?;
+        Some(UserTimezone::Fixed(offset))
+    }
+
+    /// Value stored in the `timezone` preference
+    pub fn to_storage(&self) -> String {
+        match self {
+            UserTimezone::Named(tz) => tz.name().to_string(),
+            UserTimezone::Fixed(minutes) => format_utc_offset(*minutes),
+        }
+    }
+
+    /// Offset from UTC at `at`, in minutes
+    pub fn offset_minutes_at(&self, at: DateTime<Utc>) -> i32 {
+        match self {
+            UserTimezone::Named(tz) => tz.offset_from_utc_datetime(&at.naive_utc()).fix().local_minus_utc() / 60,
+            UserTimezone::Fixed(minutes) => *minutes,
+        }
+    }
+
+    /// Wall-clock time in this zone
+    pub fn to_local(&self, at: DateTime<Utc>) -> NaiveDateTime {
+        at.naive_utc() + Duration::minutes(self.offset_minutes_at(at) as i64)
+    }
+
+    /// The UTC instant for a wall-clock time. Ambiguous times (clocks going back) take the
+    /// earlier instant; skipped times (clocks going forward) move an hour later.
+    pub fn from_local(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            UserTimezone::Named(tz) => match tz.from_local_datetime(&local) {
+                LocalResult::Single(dt) => Some(dt.with_timezone(&Utc)),
+                LocalResult::Ambiguous(earlier, _) => Some(earlier.with_timezone(&Utc)),
+                LocalResult::None => tz
+                    .from_local_datetime(&(local + Duration::hours(1)))
+                    .earliest()
+                    .map(|dt| dt.with_timezone(&Utc)),
+            },
+            UserTimezone::Fixed(minutes) => Some(Utc.from_utc_datetime(&(local - Duration::minutes(*minutes as i64)))),
+        }
+    }
+
+    /// Zone label, e.g. `Europe/Berlin (UTC+02:00)` or `UTC+05:30`
+    pub fn describe(&self, at: DateTime<Utc>) -> String {
+        match self {
+            UserTimezone::Named(tz) => format!("{} ({})", tz.name(), format_utc_offset(self.offset_minutes_at(at))),
+            UserTimezone::Fixed(_) => self.label(),
+        }
+    }
+
+    /// Short label for timestamps: the zone name, or the offset for fixed zones
+    pub fn label(&self) -> String {
+        match self {
+            UserTimezone::Named(tz) => tz.name().to_string(),
+            UserTimezone::Fixed(0) => "UTC".to_string(),
+            UserTimezone::Fixed(minutes) => format_utc_offset(*minutes),
+        }
+    }
+
+    /// Format a UTC instant in this zone, e.g. `Thu 2026-10-15 09:00 (Europe/Berlin)`
+    pub fn format(&self, at: DateTime<Utc>) -> String {
+        format!("{} ({})", self.to_local(at).format("%a %Y-%m-%d %H:%M"), self.label())
+    }
+
+    /// Format a stored `YYYY-MM-DD HH:MM:SS` UTC timestamp in this zone, or return it unchanged
+    pub fn format_stored(&self, stored: &str) -> String {
+        match parse_stored_time(stored) {
+            Some(at) => self.format(at),
+            None => stored.to_string(),
+        }
+    }
+
+    /// Names of IANA zones containing `typed`, for autocomplete
+    pub fn suggestions(typed: &str, limit: usize) -> Vec<&'static str> {
+        let typed = typed.trim().to_lowercase().replace(' ', "_");
+        TZ_VARIANTS
+            .iter()
+            .map(|tz| tz.name())
+            .filter(|name| name.to_lowercase().contains(&typed))
+            .take(limit)
+            .collect()
+    }
+}
+
+/// The user's `/timezone`, UTC when unset or unreadable
+pub async fn user_timezone(database: &Database, user_id: &str) -> UserTimezone {
+    match database.get_user_preference(user_id, TIMEZONE_PREFERENCE).await {
+        Ok(stored) => stored.as_deref().and_then(UserTimezone::parse).unwrap_or_default(),
+        Err(e) => {
+            warn!("Failed to load time zone for user {user_id}: {e}");
+            UserTimezone::default()
+        }
+    }
+}
+
+/// Parse a stored UTC timestamp (`YYYY-MM-DD HH:MM:SS`, or ISO with a `T`)
+pub fn parse_stored_time(stored: &str) -> Option<DateTime<Utc>> {
+    let stored = stored.trim().replace('T', " ");
+    let stored = stored.trim_end_matches('Z');
+    NaiveDateTime::parse_from_str(stored.split('.').next()?, STORED_TIME_FORMAT)
+        .ok()
+        .map(|dt| Utc.from_utc_datetime(&dt))
+}
+
+/// Parse a reminder time: a duration (`30m`, `1h30m`) or a clock time in the user's zone
+/// (`9am`, `14:30`, `tomorrow 9am`, `friday 17:00`, `2026-12-24 18:00`). Clock times
+/// without a day mean the next time the clock shows that time. Returns a future instant.
+pub fn parse_reminder_time(input: &str, now: DateTime<Utc>, tz: &UserTimezone) -> Option<DateTime<Utc>> {
+    if let Some(seconds) = parse_duration(input) {
+        return Some(now + Duration::seconds(seconds));
+    }
+
+    let input = input.trim().to_lowercase();
+    let mut words: Vec<&str> = input.split_whitespace().filter(|w| *w != "at" && *w != "on").collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let today = tz.to_local(now).date();
+    let mut day: Option<DayPart> = None;
+    if let Some(first) = words.first() {
+        let parsed = match *first {
+            "today" => Some(DayPart::Date(today)),
+            "tomorrow" => Some(DayPart::Date(today + Duration::days(1))),
+            word => parse_weekday(word)
+                .map(DayPart::Weekday)
+                .or_else(|| NaiveDate::parse_from_str(word, "%Y-%m-%d").ok().map(DayPart::Date)),
+        };
+        if parsed.is_some() {
+            day = parsed;
+            words.remove(0);
+        }
+    }
+
+    let time = match words.concat().as_str() {
+        "" if day.is_some() => NaiveTime::from_hms_opt(9, 0, 0)?,
+        text => parse_clock_time(text)?,
+    };
+
+    let local = match day {
+        Some(DayPart::Date(date)) => date.and_time(time),
+        Some(DayPart::Weekday(weekday)) => {
+            let ahead = (weekday.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64).rem_euclid(7);
+            let candidate = (today + Duration::days(ahead)).and_time(time);
+            if tz.from_local(candidate)? > now { candidate } else { candidate + Duration::days(7) }
+        }
+        None => {
+            let candidate = today.and_time(time);
+            if tz.from_local(candidate)? > now { candidate } else { candidate + Duration::days(1) }
+        }
+    };
+
+    tz.from_local(local).filter(|at| *at > now)
+}
+
+enum DayPart {
+    Date(NaiveDate),
+    Weekday(Weekday),
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    const DAYS: [(&str, Weekday); 7] = [
+        ("monday", Weekday::Mon),
+        ("tuesday", Weekday::Tue),
+        ("wednesday", Weekday::Wed),
+        ("thursday", Weekday::Thu),
+        ("friday", Weekday::Fri),
+        ("saturday", Weekday::Sat),
+        ("sunday", Weekday::Sun),
+    ];
+    DAYS.iter()
+        .find(|(name, _)| word.len() >= 3 && name.starts_with(word))
+        .map(|(_, day)| *day)
+}
+
+/// `9am`, `9:30pm`, `12am`, `14:30`, `noon`, `midnight`
+fn parse_clock_time(text: &str) -> Option<NaiveTime> {
+    match text {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+    let (clock, meridiem) = if let Some(clock) = text.strip_suffix("am") {
+        (clock, Some(false))
+    } else if let Some(clock) = text.strip_suffix("pm") {
+        (clock, Some(true))
+    } else {
+        (text, None)
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) if m.len() == 2 => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None if meridiem.is_some() => (clock.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+    let hour = match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(day: u32, h: u32, m: u32) -> DateTime<Utc> {
+        // 2026-10-15 is a Thursday
+        Utc.with_ymd_and_hms(2026, 10, day, h, m, 0).unwrap()
+    }
+
+    fn berlin() -> UserTimezone {
+        UserTimezone::parse("europe/berlin").unwrap()
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(berlin(), UserTimezone::Named(chrono_tz::Europe::Berlin));
+        assert_eq!(UserTimezone::parse("+05:30"), Some(UserTimezone::Fixed(330)));
+        assert_eq!(UserTimezone::parse("UTC-5"), Some(UserTimezone::Fixed(-300)));
+        assert_eq!(UserTimezone::parse("Mars/Olympus"), None);
+        assert_eq!(UserTimezone::parse(""), None);
+        assert_eq!(UserTimezone::parse(&berlin().to_storage()), Some(berlin()));
+        assert_eq!(UserTimezone::parse(&UserTimezone::Fixed(-300).to_storage()), Some(UserTimezone::Fixed(-300)));
+    }
+
+    #[test]
+    fn test_named_zone_follows_daylight_saving() {
+        let tz = berlin();
+        assert_eq!(tz.offset_minutes_at(utc(15, 12, 0)), 120);
+        assert_eq!(tz.offset_minutes_at(Utc.with_ymd_and_hms(2026, 12, 1, 12, 0, 0).unwrap()), 60);
+        assert_eq!(tz.describe(utc(15, 12, 0)), "Europe/Berlin (UTC+02:00)");
+        assert_eq!(tz.format(utc(15, 7, 0)), "Thu 2026-10-15 09:00 (Europe/Berlin)");
+        assert_eq!(tz.format_stored("2026-10-15 07:00:00"), "Thu 2026-10-15 09:00 (Europe/Berlin)");
+        assert_eq!(tz.format_stored("not a time"), "not a time");
+        assert_eq!(UserTimezone::default().format(utc(15, 7, 0)), "Thu 2026-10-15 07:00 (UTC)");
+    }
+
+    #[test]
+    fn test_durations_still_work() {
+        let now = utc(15, 12, 0);
+        assert_eq!(parse_reminder_time("1h30m", now, &berlin()), Some(now + Duration::minutes(90)));
+    }
+
+    #[test]
+    fn test_clock_times_in_user_zone() {
+        // 12:00 UTC is 14:00 in Berlin
+        let now = utc(15, 12, 0);
+        assert_eq!(parse_reminder_time("9am", now, &berlin()), Some(utc(16, 7, 0)));
+        assert_eq!(parse_reminder_time("at 3pm", now, &berlin()), Some(utc(15, 13, 0)));
+        assert_eq!(parse_reminder_time("14:30", now, &berlin()), Some(utc(15, 12, 30)));
+        assert_eq!(parse_reminder_time("tomorrow 9:15am", now, &berlin()), Some(utc(16, 7, 15)));
+        assert_eq!(parse_reminder_time("tomorrow", now, &berlin()), Some(utc(16, 7, 0)));
+        assert_eq!(parse_reminder_time("noon", now, &UserTimezone::Fixed(-300)), Some(utc(15, 17, 0)));
+    }
+
+    #[test]
+    fn test_weekdays_and_dates() {
+        let now = utc(15, 12, 0);
+        assert_eq!(parse_reminder_time("friday 17:00", now, &berlin()), Some(utc(16, 15, 0)));
+        assert_eq!(parse_reminder_time("thu 9am", now, &berlin()), Some(utc(22, 7, 0)));
+        assert_eq!(parse_reminder_time("on 2026-10-20 at 18:00", now, &berlin()), Some(utc(20, 16, 0)));
+        assert_eq!(parse_reminder_time("2026-10-01 18:00", now, &berlin()), None);
+    }
+
+    #[test]
+    fn test_invalid_times() {
+        let now = utc(15, 12, 0);
+        for input in ["", "13pm", "25:00", "9", "someday", "tomorrow maybe", "9:5am"] {
+            assert_eq!(parse_reminder_time(input, now, &berlin()), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_skipped_local_time_moves_later() {
+        // Berlin clocks jump from 02:00 to 03:00 on 2026-03-29
+        let local = NaiveDate::from_ymd_opt(2026, 3, 29).unwrap().and_hms_opt(2, 30, 0).unwrap();
+        assert_eq!(berlin().from_local(local), Some(Utc.with_ymd_and_hms(2026, 3, 29, 1, 30, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        assert!(UserTimezone::suggestions("berl", 25).contains(&"Europe/Berlin"));
+        assert!(UserTimezone::suggestions("new york", 25).contains(&"America/New_York"));
+        assert!(UserTimezone::suggestions("", 25).len() == 25);
+    }
+}
@@ -0,0 +1,293 @@
+//! # Feature: Response Cache
+//!
+//! Answers repeated questions without a round trip to the model. Answers are
+//! keyed by a digest of guild, asking user, persona, model, verbosity and the
+//! normalized question, so "How do I reset my password?" and "how do i reset
+//! my password" share one entry. Answers are built from the user's own history
+//! and summary, so they are never served to another member. The most recently used [`ResponseCache::with_capacity`]
+//! entries stay in memory; every entry is also kept in the `response_cache`
+//! table so hits survive restarts, until it expires after [`CACHE_TTL_HOURS`].
+//! Each lookup is recorded in `performance_metrics` as a `response_cache_hit`
+//! or `response_cache_miss`.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.1.0: Keys include the asking user, so answers built from one member's context aren't served to another
+//! - 1.0.0: Initial release
+
+use crate::core::clock::SQL_TIMESTAMP_FORMAT;
+use crate::database::Database;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use dashmap::DashMap;
+use log::warn;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// How long a cached answer is served
+pub const CACHE_TTL_HOURS: i64 = 24;
+
+/// Shorter questions ("why?", "and then?") depend on the conversation and are never cached
+const MIN_PROMPT_CHARS: usize = 12;
+
+/// Normalize a question for caching: drop Discord mentions, lowercase,
+/// collapse whitespace and trailing punctuation.
+///
+/// Returns `None` when too little text remains to stand on its own.
+pub fn normalize_prompt(text: &str) -> Option<String> {
+    let cleaned = text
+        .split_whitespace()
+        .filter(|word| !(word.starts_with("<@") && word.ends_with('>')))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let cleaned = cleaned.trim_end_matches(['?', '!', '.', ' ']);
+    (cleaned.chars().count() >= MIN_PROMPT_CHARS).then(|| cleaned.to_string())
+}
+
+/// What an answer is cached under
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    pub guild_id: String,
+    /// The asking user; their history and summary shape the answer
+    pub user_id: String,
+    pub persona: String,
+    pub model: String,
+    pub verbosity: String,
+    /// The question after [`normalize_prompt`]
+    pub prompt: String,
+}
+
+impl CacheKey {
+    /// Key for a question, or `None` when it's too short to answer without context
+    pub fn new(guild_id: &str, user_id: &str, persona: &str, model: &str, verbosity: &str, question: &str) -> Option<Self> {
+        Some(CacheKey {
+            guild_id: guild_id.to_string(),
+            user_id: user_id.to_string(),
+            persona: persona.to_string(),
+            model: model.to_string(),
+            verbosity: verbosity.to_string(),
+            prompt: normalize_prompt(question)?,
+        })
+    }
+
+    /// Hex SHA-256 of every part, used as the `cache_key` column
+    pub fn digest(&self) -> String {
+        let parts = [&self.guild_id, &self.user_id, &self.persona, &self.model, &self.verbosity, &self.prompt];
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part.as_bytes());
+            hasher.update([0u8]);
+        }
+        hasher.finalize().iter().map(|b| format!("{b:02x}")).collect()
+    }
+}
+
+/// Where a cache hit was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheLayer {
+    Memory,
+    Database,
+}
+
+impl CacheLayer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheLayer::Memory => "memory",
+            CacheLayer::Database => "database",
+        }
+    }
+}
+
+struct CachedResponse {
+    guild_id: String,
+    response: String,
+    expires_at: DateTime<Utc>,
+    last_used: Instant,
+}
+
+/// Cached answers, shared by every clone
+#[derive(Clone)]
+pub struct ResponseCache {
+    database: Database,
+    /// Digest -> answer, the most recently used ones only
+    entries: Arc<DashMap<String, CachedResponse>>,
+    max_entries: usize,
+}
+
+impl ResponseCache {
+    /// A cache that keeps at most `max_entries` answers in memory, dropping the least recently used first
+    pub fn with_capacity(database: Database, max_entries: usize) -> Self {
+        ResponseCache { database, entries: Arc::new(DashMap::new()), max_entries }
+    }
+
+    /// The cached answer for `key`, from memory or the database, recording the hit or miss
+    pub async fn get(&self, key: &CacheKey) -> Option<String> {
+        let digest = key.digest();
+        let hit = match self.get_from_memory(&digest) {
+            Some(response) => Some((response, CacheLayer::Memory)),
+            None => self.get_from_database(&digest, &key.guild_id).await.map(|response| (response, CacheLayer::Database)),
+        };
+        self.record(key, hit.as_ref().map(|(_, layer)| *layer)).await;
+        hit.map(|(response, _)| response)
+    }
+
+    /// Cache `response` for `key` for [`CACHE_TTL_HOURS`]
+    pub async fn put(&self, key: &CacheKey, response: &str) {
+        let stored = self
+            .database
+            .store_cached_response(&key.digest(), &key.guild_id, &key.persona, &key.model, response, CACHE_TTL_HOURS)
+            .await;
+        let expires_at = match stored {
+            Ok(expires_at) => parse_expiry(&expires_at),
+            Err(e) => {
+                warn!("Failed to store cached response: {e}");
+                None
+            }
+        };
+        let expires_at = expires_at.unwrap_or_else(|| Utc::now() + Duration::hours(CACHE_TTL_HOURS));
+        self.insert(key.digest(), &key.guild_id, response.to_string(), expires_at);
+    }
+
+    /// Forget a guild's cached answers, in memory and in the database
+    pub async fn clear_guild(&self, guild_id: &str) -> crate::core::Result<i64> {
+        self.entries.retain(|_, entry| entry.guild_id != guild_id);
+        self.database.clear_response_cache(guild_id).await
+    }
+
+    fn get_from_memory(&self, digest: &str) -> Option<String> {
+        let mut entry = self.entries.get_mut(digest)?;
+        if entry.expires_at <= Utc::now() {
+            drop(entry);
+            self.entries.remove(digest);
+            return None;
+        }
+        entry.last_used = Instant::now();
+        Some(entry.response.clone())
+    }
+
+    async fn get_from_database(&self, digest: &str, guild_id: &str) -> Option<String> {
+        match self.database.get_cached_response(digest).await {
+            Ok(Some((response, expires_at))) => {
+                let expires_at = parse_expiry(&expires_at)?;
+                self.insert(digest.to_string(), guild_id, response.clone(), expires_at);
+                Some(response)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Failed to read cached response: {e}");
+                None
+            }
+        }
+    }
+
+    /// Add an entry, then drop expired ones and the least recently used while over capacity
+    fn insert(&self, digest: String, guild_id: &str, response: String, expires_at: DateTime<Utc>) {
+        let entry = CachedResponse { guild_id: guild_id.to_string(), response, expires_at, last_used: Instant::now() };
+        self.entries.insert(digest, entry);
+        if self.entries.len() <= self.max_entries {
+            return;
+        }
+        let now = Utc::now();
+        self.entries.retain(|_, entry| entry.expires_at > now);
+        let excess = self.entries.len().saturating_sub(self.max_entries);
+        if excess > 0 {
+            let mut entries: Vec<(String, Instant)> = self.entries.iter().map(|e| (e.key().clone(), e.value().last_used)).collect();
+            entries.sort_by_key(|(_, used)| *used);
+            for (digest, _) in entries.into_iter().take(excess) {
+                self.entries.remove(&digest);
+            }
+        }
+    }
+
+    async fn record(&self, key: &CacheKey, hit: Option<CacheLayer>) {
+        let metric = if hit.is_some() { "response_cache_hit" } else { "response_cache_miss" };
+        let metadata = serde_json::json!({
+            "guild_id": key.guild_id,
+            "persona": key.persona,
+            "model": key.model,
+            "layer": hit.map(|layer| layer.as_str()),
+        })
+        .to_string();
+        if let Err(e) = self.database.add_performance_metric(metric, 1.0, Some("count"), Some(&metadata)).await {
+            warn!("Failed to record {metric}: {e}");
+        }
+    }
+}
+
+fn parse_expiry(timestamp: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(timestamp, SQL_TIMESTAMP_FORMAT).ok().map(|at| at.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_prompt() {
+        assert_eq!(normalize_prompt("<@123> How do I  reset my Password??").as_deref(), Some("how do i reset my password"));
+        assert_eq!(normalize_prompt("how do i reset my password"), normalize_prompt("How do I reset my password?"));
+        assert_eq!(normalize_prompt("<@123> why?"), None);
+        assert_eq!(normalize_prompt("   "), None);
+    }
+
+    #[test]
+    fn test_digest_covers_every_part() {
+        let key = CacheKey::new("1", "10", "obi", "gpt-4o-mini", "concise", "How do I reset my password?").unwrap();
+        assert_eq!(key.digest().len(), 64);
+        assert_eq!(key.digest(), CacheKey::new("1", "10", "obi", "gpt-4o-mini", "concise", "how do i reset my password").unwrap().digest());
+        for other in [
+            CacheKey::new("2", "10", "obi", "gpt-4o-mini", "concise", "How do I reset my password?"),
+            CacheKey::new("1", "11", "obi", "gpt-4o-mini", "concise", "How do I reset my password?"),
+            CacheKey::new("1", "10", "chef", "gpt-4o-mini", "concise", "How do I reset my password?"),
+            CacheKey::new("1", "10", "obi", "gpt-4o", "concise", "How do I reset my password?"),
+            CacheKey::new("1", "10", "obi", "gpt-4o-mini", "detailed", "How do I reset my password?"),
+        ] {
+            assert_ne!(other.unwrap().digest(), key.digest());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_answers_survive_memory_eviction() {
+        let database = Database::new(":memory:", 1).await.unwrap();
+        let cache = ResponseCache::with_capacity(database.clone(), 1);
+        let password = CacheKey::new("1", "10", "obi", "gpt-4o-mini", "concise", "How do I reset my password?").unwrap();
+        let invite = CacheKey::new("1", "10", "obi", "gpt-4o-mini", "concise", "Where is the invite link?").unwrap();
+        assert_eq!(cache.get(&password).await, None);
+
+        cache.put(&password, "Use the reset link.").await;
+        cache.put(&invite, "Pinned in #welcome.").await;
+        assert_eq!(cache.entries.len(), 1);
+        // Evicted from memory, still in the database
+        assert_eq!(cache.get(&password).await.as_deref(), Some("Use the reset link."));
+        assert_eq!(cache.get(&invite).await.as_deref(), Some("Pinned in #welcome."));
+
+        assert_eq!(cache.clear_guild("1").await.unwrap(), 2);
+        assert_eq!(cache.get(&password).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_users_do_not_share_answers() {
+        // The same question from two members, each asked with their own history and summary
+        let database = Database::new(":memory:", 1).await.unwrap();
+        let cache = ResponseCache::with_capacity(database, 10);
+        let alice = CacheKey::new("1", "10", "obi", "gpt-4o-mini", "concise", "What did I say my project was called?").unwrap();
+        let bob = CacheKey::new("1", "11", "obi", "gpt-4o-mini", "concise", "What did I say my project was called?").unwrap();
+
+        cache.put(&alice, "You called it Moonbeam.").await;
+        assert_eq!(cache.get(&bob).await, None);
+        cache.put(&bob, "You haven't told me yet.").await;
+        assert_eq!(cache.get(&alice).await.as_deref(), Some("You called it Moonbeam."));
+        assert_eq!(cache.get(&bob).await.as_deref(), Some("You haven't told me yet."));
+    }
+
+    #[test]
+    fn test_parse_expiry() {
+        let at = parse_expiry("2026-03-01 12:30:00").unwrap();
+        assert_eq!(at.to_rfc3339(), "2026-03-01T12:30:00+00:00");
+        assert_eq!(parse_expiry("soon"), None);
+    }
+}
@@ -0,0 +1,11 @@
+//! # Response Cache Feature
+//!
+//! Answers repeated questions from a cache instead of calling the model again.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod cache;
+
+pub use cache::{normalize_prompt, CacheKey, CacheLayer, ResponseCache, CACHE_TTL_HOURS};
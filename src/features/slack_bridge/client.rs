@@ -0,0 +1,174 @@
+//! # Feature: Slack Web API Client
+//!
+//! Minimal Slack Web API client used by the bridge, authenticated with the Slack
+//! app's bot token (`SLACK_BOT_TOKEN`). Covers posting with a custom name and
+//! avatar, reading channel history, user lookups and private file downloads.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with chat.postMessage, conversations.history/info, users.info and file downloads
+
+use crate::core::{BotError, Result};
+use dashmap::DashMap;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const SLACK_API: &str = "https://slack.com/api";
+
+/// A message from `conversations.history`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SlackMessage {
+    pub ts: String,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub subtype: Option<String>,
+    #[serde(default)]
+    pub bot_id: Option<String>,
+    #[serde(default)]
+    pub files: Vec<SlackFile>,
+}
+
+/// A file shared in a Slack message
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SlackFile {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub url_private: String,
+    #[serde(default)]
+    pub size: u64,
+}
+
+#[derive(Clone)]
+pub struct SlackClient {
+    token: String,
+    client: reqwest::Client,
+    /// Slack user ID -> display name
+    user_names: Arc<DashMap<String, String>>,
+}
+
+/// Turn a Slack API body into an error unless `ok` is true
+fn check_ok(method: &str, body: Value) -> Result<Value> {
+    if body.get("ok").and_then(Value::as_bool) == Some(true) {
+        Ok(body)
+    } else {
+        let error = body.get("error").and_then(Value::as_str).unwrap_or("unknown_error");
+        Err(BotError::internal(format!("Slack {} failed: {}", method, error)))
+    }
+}
+
+impl SlackClient {
+    pub fn new(token: String) -> Self {
+        SlackClient {
+            token,
+            client: reqwest::Client::new(),
+            user_names: Arc::new(DashMap::new()),
+        }
+    }
+
+    async fn get(&self, method: &str, query: &[(&str, &str)]) -> Result<Value> {
+        let body: Value = self
+            .client
+            .get(format!("{SLACK_API}/{method}"))
+            .bearer_auth(&self.token)
+            .query(query)
+            .send()
+            .await?
+            .json()
+            .await?;
+        check_ok(method, body)
+    }
+
+    /// Post a message to a channel under a custom name and avatar (needs `chat:write.customize`)
+    pub async fn post_message(&self, channel: &str, username: &str, icon_url: &str, text: &str) -> Result<()> {
+        let body: Value = self
+            .client
+            .post(format!("{SLACK_API}/chat.postMessage"))
+            .bearer_auth(&self.token)
+            .json(&json!({
+                "channel": channel,
+                "username": username,
+                "icon_url": icon_url,
+                "text": text,
+                "unfurl_links": false,
+            }))
+            .send()
+            .await?
+            .json()
+            .await?;
+        check_ok("chat.postMessage", body).map(|_| ())
+    }
+
+    /// Name of a channel the bot can see, as a check that the ID is usable
+    pub async fn channel_name(&self, channel: &str) -> Result<String> {
+        let body = self.get("conversations.info", &[("channel", channel)]).await?;
+        Ok(body
+            .pointer("/channel/name")
+            .and_then(Value::as_str)
+            .unwrap_or(channel)
+            .to_string())
+    }
+
+    /// Messages posted after `oldest` (exclusive), oldest first
+    pub async fn history(&self, channel: &str, oldest: &str) -> Result<Vec<SlackMessage>> {
+        let body = self
+            .get("conversations.history", &[("channel", channel), ("oldest", oldest), ("limit", "100")])
+            .await?;
+        let mut messages: Vec<SlackMessage> =
+            serde_json::from_value(body.get("messages").cloned().unwrap_or_else(|| json!([])))?;
+        messages.reverse();
+        Ok(messages)
+    }
+
+    /// Display name for a Slack user, cached for the life of the process
+    pub async fn user_name(&self, user_id: &str) -> Result<String> {
+        if let Some(name) = self.user_names.get(user_id) {
+            return Ok(name.clone());
+        }
+        let body = self.get("users.info", &[("user", user_id)]).await?;
+        let name = ["/user/profile/display_name", "/user/real_name", "/user/name"]
+            .iter()
+            .filter_map(|pointer| body.pointer(pointer).and_then(Value::as_str))
+            .find(|name| !name.is_empty())
+            .unwrap_or(user_id)
+            .to_string();
+        self.user_names.insert(user_id.to_string(), name.clone());
+        Ok(name)
+    }
+
+    /// Download a private file (needs `files:read`)
+    pub async fn download_file(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self.client.get(url).bearer_auth(&self.token).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(BotError::internal(format!("Slack file download failed (status {})", status)));
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_ok() {
+        assert!(check_ok("auth.test", json!({"ok": true})).is_ok());
+        let err = check_ok("chat.postMessage", json!({"ok": false, "error": "channel_not_found"})).unwrap_err();
+        assert_eq!(err.to_string(), "Slack chat.postMessage failed: channel_not_found");
+    }
+
+    #[test]
+    fn test_slack_message_defaults() {
+        let message: SlackMessage = serde_json::from_value(json!({"ts": "1.2", "text": "hi"})).unwrap();
+        assert_eq!(message.user, None);
+        assert!(message.files.is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+//! # Slack Bridge Feature
+//!
+//! Two-way relay between Discord channels and Slack channels, linked with
+//! `/bridge link`. Needs a Slack app bot token in `SLACK_BOT_TOKEN`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod client;
+pub mod relay;
+
+pub use client::{SlackClient, SlackFile, SlackMessage};
+pub use relay::{
+    discord_to_slack_text, format_from_slack, parse_slack_channel_id, relay_to_slack, should_relay, slack_bridge_loop,
+    slack_mentions, slack_to_discord_text, slack_ts_now, MAX_RELAY_FILE_BYTES,
+};
@@ -0,0 +1,264 @@
+//! # Feature: Slack Relay
+//!
+//! Relays messages over bridged channels. Discord messages are posted to Slack
+//! under the author's name and avatar with attachment links; Slack messages are
+//! polled from channel history and posted to Discord with the author's name,
+//! re-uploading shared files. Bot messages are never relayed, so the bridge
+//! can't echo its own posts.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with two-way text relay, attribution and file handling
+
+use super::client::{SlackClient, SlackMessage};
+use crate::core::discord_limits::{truncate, MESSAGE_CONTENT};
+use crate::database::{BridgeLink, Database};
+use crate::core::Result;
+use log::{debug, info, warn};
+use regex::Regex;
+use serenity::http::Http;
+use serenity::model::channel::{AttachmentType, Message};
+use serenity::model::id::ChannelId;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often bridged Slack channels are polled
+const POLL_INTERVAL_SECS: u64 = 10;
+
+/// Largest Slack file re-uploaded to Discord
+pub const MAX_RELAY_FILE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Slack message subtypes that carry user content
+const RELAYED_SUBTYPES: &[&str] = &["file_share", "thread_broadcast", "me_message"];
+
+/// Parse a Slack channel ID (`C0123ABCD`) or a channel link ending in one
+pub fn parse_slack_channel_id(input: &str) -> Option<String> {
+    let id = input.trim().trim_end_matches('/').rsplit('/').next()?.to_uppercase();
+    let valid = id.len() >= 9
+        && matches!(id.chars().next(), Some('C') | Some('G'))
+        && id.chars().all(|c| c.is_ascii_alphanumeric());
+    valid.then_some(id)
+}
+
+/// Slack `ts` for the current time, used as the starting point of a new bridge
+pub fn slack_ts_now() -> String {
+    format!("{}.000000", chrono::Utc::now().timestamp())
+}
+
+/// Whether a Slack message should be relayed to Discord
+pub fn should_relay(message: &SlackMessage) -> bool {
+    message.bot_id.is_none()
+        && message.user.is_some()
+        && message.subtype.as_deref().is_none_or(|s| RELAYED_SUBTYPES.contains(&s))
+}
+
+/// Slack user IDs mentioned as `<@U123>`
+pub fn slack_mentions(text: &str) -> Vec<String> {
+    let re = Regex::new(r"<@([A-Z0-9]+)(?:\|[^>]*)?>").unwrap();
+    re.captures_iter(text).map(|c| c[1].to_string()).collect()
+}
+
+/// Convert Slack mrkdwn to Discord markdown. `names` maps Slack user IDs to display names.
+pub fn slack_to_discord_text(text: &str, names: &HashMap<String, String>) -> String {
+    let re = Regex::new(r"<([^<>]+)>").unwrap();
+    let converted = re.replace_all(text, |caps: &regex::Captures| {
+        let inner = &caps[1];
+        let (target, label) = match inner.split_once('|') {
+            Some((target, label)) => (target, Some(label)),
+            None => (inner, None),
+        };
+        if let Some(user) = target.strip_prefix('@') {
+            format!("@{}", names.get(user).map(String::as_str).or(label).unwrap_or(user))
+        } else if let Some(channel) = target.strip_prefix('#') {
+            format!("#{}", label.unwrap_or(channel))
+        } else if let Some(special) = target.strip_prefix('!') {
+            format!("@{}", label.map(|l| l.trim_start_matches('@')).unwrap_or(special))
+        } else {
+            match label {
+                Some(label) => format!("[{label}]({target})"),
+                None => target.to_string(),
+            }
+        }
+    });
+    converted.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+/// Convert a Discord message to Slack text. `mentions` maps Discord user IDs to names;
+/// `attachments` are (filename, url) pairs appended as links.
+pub fn discord_to_slack_text(content: &str, mentions: &[(u64, String)], attachments: &[(String, String)]) -> String {
+    let mut text = content.to_string();
+    for (id, name) in mentions {
+        text = text
+            .replace(&format!("<@{id}>"), &format!("@{name}"))
+            .replace(&format!("<@!{id}>"), &format!("@{name}"));
+    }
+    let mut text = text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    for (filename, url) in attachments {
+        let filename = filename.replace(['|', '<', '>'], "_");
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&format!("📎 <{url}|{filename}>"));
+    }
+    text
+}
+
+/// Discord message for a relayed Slack message, within the 2000 character limit
+pub fn format_from_slack(name: &str, text: &str) -> String {
+    let message = format!("**{name}** · Slack\n{text}");
+    truncate(&message, MESSAGE_CONTENT)
+}
+
+/// Relay a Discord message if its channel is bridged. Returns whether it was relayed.
+pub async fn relay_to_slack(slack: &SlackClient, db: &Database, msg: &Message) -> Result<bool> {
+    let Some(link) = db.get_bridge_link(&msg.channel_id.to_string()).await? else {
+        return Ok(false);
+    };
+    let mentions: Vec<(u64, String)> = msg.mentions.iter().map(|u| (u.id.0, u.name.clone())).collect();
+    let attachments: Vec<(String, String)> =
+        msg.attachments.iter().map(|a| (a.filename.clone(), a.url.clone())).collect();
+    let text = discord_to_slack_text(&msg.content, &mentions, &attachments);
+    if text.is_empty() {
+        return Ok(false);
+    }
+    let name = msg
+        .member
+        .as_ref()
+        .and_then(|m| m.nick.clone())
+        .unwrap_or_else(|| msg.author.name.clone());
+
+    slack
+        .post_message(&link.slack_channel_id, &format!("{name} (Discord)"), &msg.author.face(), &text)
+        .await?;
+    debug!("Relayed message {} to Slack channel {}", msg.id, link.slack_channel_id);
+    Ok(true)
+}
+
+/// Relay new Slack messages on one bridge to Discord
+async fn relay_from_slack(http: &Http, db: &Database, slack: &SlackClient, link: &BridgeLink) -> Result<()> {
+    let channel = ChannelId(link.discord_channel_id.parse::<u64>()?);
+    for message in slack.history(&link.slack_channel_id, &link.last_slack_ts).await? {
+        if should_relay(&message) {
+            let mut names = HashMap::new();
+            for user in slack_mentions(&message.text).into_iter().chain(message.user.clone()) {
+                if let Ok(name) = slack.user_name(&user).await {
+                    names.insert(user, name);
+                }
+            }
+            let author = message.user.as_deref().unwrap_or_default();
+            let name = names.get(author).cloned().unwrap_or_else(|| author.to_string());
+            let mut text = slack_to_discord_text(&message.text, &names);
+
+            let mut files = Vec::new();
+            for file in &message.files {
+                if file.size > MAX_RELAY_FILE_BYTES || file.url_private.is_empty() {
+                    text.push_str(&format!("\n📎 *{} (too large to relay)*", file.name));
+                    continue;
+                }
+                match slack.download_file(&file.url_private).await {
+                    Ok(data) => files.push((file.name.clone(), data)),
+                    Err(e) => {
+                        warn!("Failed to download Slack file {}: {e}", file.name);
+                        text.push_str(&format!("\n📎 *{} (couldn't be relayed)*", file.name));
+                    }
+                }
+            }
+
+            channel
+                .send_message(http, |m| {
+                    m.content(format_from_slack(&name, &text)).allowed_mentions(|a| a.empty_parse());
+                    for (filename, data) in files {
+                        m.add_file(AttachmentType::Bytes { data: Cow::Owned(data), filename });
+                    }
+                    m
+                })
+                .await?;
+        }
+        db.set_bridge_last_ts(link.id, &message.ts).await?;
+    }
+    Ok(())
+}
+
+/// Background task that relays new Slack messages on every bridge
+pub async fn slack_bridge_loop(http: Arc<Http>, db: Arc<Database>, slack: SlackClient) {
+    let mut interval = tokio::time::interval(Duration::from_secs(POLL_INTERVAL_SECS));
+    info!("Slack bridge task started (polls every {POLL_INTERVAL_SECS}s)");
+
+    loop {
+        interval.tick().await;
+        let links = match db.get_bridge_links(None).await {
+            Ok(links) => links,
+            Err(e) => {
+                warn!("Failed to load Slack bridges: {}", e);
+                continue;
+            }
+        };
+
+        for link in links {
+            if let Err(e) = relay_from_slack(&http, &db, &slack, &link).await {
+                warn!(
+                    "Failed to relay Slack channel {} to Discord channel {}: {}",
+                    link.slack_channel_id, link.discord_channel_id, e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_slack_channel_id() {
+        assert_eq!(parse_slack_channel_id("C0123ABCD"), Some("C0123ABCD".to_string()));
+        assert_eq!(
+            parse_slack_channel_id("https://acme.slack.com/archives/C0123ABCD/"),
+            Some("C0123ABCD".to_string())
+        );
+        assert_eq!(parse_slack_channel_id("#general"), None);
+        assert_eq!(parse_slack_channel_id("U0123ABCD"), None);
+    }
+
+    #[test]
+    fn test_should_relay() {
+        let user = SlackMessage { ts: "1.0".into(), user: Some("U1".into()), ..Default::default() };
+        assert!(should_relay(&user));
+        assert!(should_relay(&SlackMessage { subtype: Some("file_share".into()), ..user.clone() }));
+        assert!(!should_relay(&SlackMessage { subtype: Some("channel_join".into()), ..user.clone() }));
+        assert!(!should_relay(&SlackMessage { bot_id: Some("B1".into()), ..user }));
+    }
+
+    #[test]
+    fn test_slack_to_discord_text() {
+        let names = HashMap::from([("U1".to_string(), "ana".to_string())]);
+        let text = "hi <@U1> &amp; <!here>, see <https://x.io|docs> in <#C1|general> &lt;3";
+        assert_eq!(slack_mentions(text), vec!["U1"]);
+        assert_eq!(
+            slack_to_discord_text(text, &names),
+            "hi @ana & @here, see [docs](https://x.io) in #general <3"
+        );
+    }
+
+    #[test]
+    fn test_discord_to_slack_text() {
+        let text = discord_to_slack_text(
+            "hey <@!42> a<b",
+            &[(42, "sam".to_string())],
+            &[("cat|1.png".to_string(), "https://cdn/cat.png".to_string())],
+        );
+        assert_eq!(text, "hey @sam a&lt;b\n📎 <https://cdn/cat.png|cat_1.png>");
+        assert_eq!(discord_to_slack_text("", &[], &[]), "");
+    }
+
+    #[test]
+    fn test_format_from_slack() {
+        assert_eq!(format_from_slack("ana", "hi"), "**ana** · Slack\nhi");
+        assert_eq!(format_from_slack("ana", &"x".repeat(3000)).chars().count(), 2000);
+    }
+}
@@ -0,0 +1,15 @@
+//! # Stale Settings Feature
+//!
+//! Detects guild settings that reference deleted channels or roles and flags
+//! them in `/settings`.
+//!
+//! - **Version**: 1.6.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod validator;
+
+pub use validator::{
+    format_stale_warning, stale_settings_loop, stored_stale_settings, validate_guild, EntityKind,
+    StaleSetting, REFERENCE_SETTINGS,
+};
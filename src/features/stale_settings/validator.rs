@@ -0,0 +1,257 @@
+//! # Feature: Stale Settings Validator
+//!
+//! Guild settings can point at channels and roles that have since been deleted,
+//! which makes the features using them fail silently. This periodically checks
+//! every referenced ID against the guild and records broken settings so
+//! `/settings` can flag them.
+//!
+//! - **Version**: 1.7.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.7.0: Also check help_digest_channel
+//! - 1.6.0: Also check member_role
+//! - 1.5.0: Also check verification_channel and unverified_role
+//! - 1.4.0: Also check lockdown_categories
+//! - 1.3.0: Also check appeal_review_channel
+//! - 1.2.0: Also check attachment_scan_channels
+//! - 1.1.0: Also check mod_log_channel
+//! - 1.0.0: Initial release covering support_channels, reminders_channel and bot_admin_role
+
+use crate::database::Database;
+use crate::core::Result;
+use log::{debug, info, warn};
+use serenity::http::Http;
+use serenity::model::id::GuildId;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often every guild's settings are re-validated
+pub const VALIDATION_INTERVAL_HOURS: u64 = 6;
+
+/// Kind of Discord entity a setting refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    Channel,
+    Role,
+}
+
+impl EntityKind {
+    pub fn noun(&self) -> &'static str {
+        match self {
+            EntityKind::Channel => "channel",
+            EntityKind::Role => "role",
+        }
+    }
+}
+
+/// Guild settings whose values are Discord IDs
+pub const REFERENCE_SETTINGS: &[(&str, EntityKind)] = &[
+    ("support_channels", EntityKind::Channel),
+    ("reminders_channel", EntityKind::Channel),
+    ("help_digest_channel", EntityKind::Channel),
+    ("mod_log_channel", EntityKind::Channel),
+    ("appeal_review_channel", EntityKind::Channel),
+    ("lockdown_categories", EntityKind::Channel),
+    ("verification_channel", EntityKind::Channel),
+    ("attachment_scan_channels", EntityKind::Channel),
+    ("bot_admin_role", EntityKind::Role),
+    ("unverified_role", EntityKind::Role),
+    ("member_role", EntityKind::Role),
+];
+
+/// A setting that references one or more entities that no longer exist
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleSetting {
+    pub setting: String,
+    pub kind: EntityKind,
+    pub missing_ids: Vec<u64>,
+}
+
+/// IDs referenced by a setting value (comma-separated; `disabled` means none)
+pub fn referenced_ids(value: &str) -> Vec<u64> {
+    if value.trim() == "disabled" {
+        return Vec::new();
+    }
+    value
+        .split(',')
+        .filter_map(|id| id.trim().parse::<u64>().ok())
+        .collect()
+}
+
+/// Rebuild a stale setting from its stored (setting, comma-separated IDs) form
+pub fn from_record(setting: &str, missing: &str) -> Option<StaleSetting> {
+    let kind = REFERENCE_SETTINGS.iter().find(|(name, _)| *name == setting)?.1;
+    Some(StaleSetting {
+        setting: setting.to_string(),
+        kind,
+        missing_ids: referenced_ids(missing),
+    })
+}
+
+/// Stored form of a stale setting: (setting, comma-separated missing IDs)
+pub fn to_record(stale: &StaleSetting) -> (String, String) {
+    let ids = stale.missing_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+    (stale.setting.clone(), ids)
+}
+
+/// Previously detected stale settings for a guild
+pub async fn stored_stale_settings(db: &Database, guild_id: &str) -> Result<Vec<StaleSetting>> {
+    Ok(db
+        .get_stale_settings(guild_id)
+        .await?
+        .iter()
+        .filter_map(|(setting, ids)| from_record(setting, ids))
+        .collect())
+}
+
+/// Referenced IDs that are not in `existing`
+pub fn missing_ids(value: &str, existing: &HashSet<u64>) -> Vec<u64> {
+    referenced_ids(value)
+        .into_iter()
+        .filter(|id| !existing.contains(id))
+        .collect()
+}
+
+/// Warning block listing broken settings, for `/settings`
+pub fn format_stale_warning(stale: &[StaleSetting]) -> String {
+    if stale.is_empty() {
+        return String::new();
+    }
+    let mut lines = vec!["\n⚠️ **Broken Settings** (referenced entities were deleted):".to_string()];
+    for entry in stale {
+        let ids = entry
+            .missing_ids
+            .iter()
+            .map(|id| format!("{} `{id}`", entry.kind.noun()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(format!("• `{}`: {ids} no longer exists", entry.setting));
+    }
+    lines.push("Update them with `/set_guild_setting` (or `/admin_role`).".to_string());
+    lines.join("\n")
+}
+
+/// Check a guild's reference settings against its current channels and roles and
+/// store the result. Returns the stale settings found.
+pub async fn validate_guild(http: &Http, db: &Database, guild_id: &str) -> Result<Vec<StaleSetting>> {
+    let guild = GuildId(guild_id.parse::<u64>()?);
+
+    let mut values = Vec::new();
+    for (setting, kind) in REFERENCE_SETTINGS {
+        if let Some(value) = db.get_guild_setting(guild_id, setting).await? {
+            if !referenced_ids(&value).is_empty() {
+                values.push((*setting, *kind, value));
+            }
+        }
+    }
+    if values.is_empty() {
+        db.replace_stale_settings(guild_id, &[]).await?;
+        return Ok(Vec::new());
+    }
+
+    let channels: HashSet<u64> = guild.channels(http).await?.keys().map(|id| id.0).collect();
+    let roles: HashSet<u64> = guild.roles(http).await?.keys().map(|id| id.0).collect();
+
+    let stale: Vec<StaleSetting> = values
+        .into_iter()
+        .filter_map(|(setting, kind, value)| {
+            let existing = match kind {
+                EntityKind::Channel => &channels,
+                EntityKind::Role => &roles,
+            };
+            let missing = missing_ids(&value, existing);
+            (!missing.is_empty()).then(|| StaleSetting {
+                setting: setting.to_string(),
+                kind,
+                missing_ids: missing,
+            })
+        })
+        .collect();
+
+    let records: Vec<(String, String)> = stale.iter().map(to_record).collect();
+    db.replace_stale_settings(guild_id, &records).await?;
+    Ok(stale)
+}
+
+/// Background task that re-validates every configured guild's settings
+pub async fn stale_settings_loop(http: Arc<Http>, db: Arc<Database>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(VALIDATION_INTERVAL_HOURS * 3600));
+    info!("Stale settings validator started (runs every {VALIDATION_INTERVAL_HOURS}h)");
+
+    loop {
+        interval.tick().await;
+        let guilds = match db.get_guilds_with_settings().await {
+            Ok(guilds) => guilds,
+            Err(e) => {
+                warn!("Failed to list guilds for settings validation: {}", e);
+                continue;
+            }
+        };
+
+        for guild_id in guilds {
+            match validate_guild(&http, &db, &guild_id).await {
+                Ok(stale) => {
+                    for entry in &stale {
+                        warn!(
+                            "Guild {} setting '{}' references deleted {}(s): {:?}",
+                            guild_id,
+                            entry.setting,
+                            entry.kind.noun(),
+                            entry.missing_ids
+                        );
+                    }
+                }
+                // Usually a guild the bot has left
+                Err(e) => debug!("Skipping settings validation for guild {}: {}", guild_id, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_referenced_ids() {
+        assert_eq!(referenced_ids("123, 456,abc"), vec![123, 456]);
+        assert_eq!(referenced_ids("789"), vec![789]);
+        assert!(referenced_ids("disabled").is_empty());
+    }
+
+    #[test]
+    fn test_missing_ids() {
+        let existing: HashSet<u64> = [1, 2].into_iter().collect();
+        assert_eq!(missing_ids("1,2,3", &existing), vec![3]);
+        assert!(missing_ids("2", &existing).is_empty());
+        assert!(missing_ids("disabled", &existing).is_empty());
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let stale = StaleSetting {
+            setting: "support_channels".to_string(),
+            kind: EntityKind::Channel,
+            missing_ids: vec![10, 20],
+        };
+        let (setting, ids) = to_record(&stale);
+        assert_eq!(ids, "10,20");
+        assert_eq!(from_record(&setting, &ids), Some(stale));
+        assert_eq!(from_record("default_persona", "1"), None);
+    }
+
+    #[test]
+    fn test_format_stale_warning() {
+        assert!(format_stale_warning(&[]).is_empty());
+        let warning = format_stale_warning(&[StaleSetting {
+            setting: "bot_admin_role".to_string(),
+            kind: EntityKind::Role,
+            missing_ids: vec![42],
+        }]);
+        assert!(warning.contains("Broken Settings"));
+        assert!(warning.contains("`bot_admin_role`: role `42` no longer exists"));
+    }
+}
@@ -1,11 +1,14 @@
-//! # Startup Notification Feature
+//! # Startup Feature
 //!
-//! Rich notifications when bot comes online.
+//! Preflight dependency checks before connecting, and rich notifications when
+//! the bot comes online.
 //!
-//! - **Version**: 1.1.0
+//! - **Version**: 1.2.0
 //! - **Since**: 0.4.0
 //! - **Toggleable**: true
 
 pub mod notification;
+pub mod preflight;
 
 pub use notification::StartupNotifier;
+pub use preflight::{run_preflight, CheckResult, CheckStatus, PreflightReport};
@@ -4,14 +4,16 @@
 //! Supports DM to bot owner and/or specific guild channels.
 //! Configuration is stored in the database and managed via /set_guild_setting.
 //!
-//! - **Version**: 1.1.0
+//! - **Version**: 1.2.0
 //! - **Since**: 0.4.0
 //! - **Toggleable**: true
 //!
 //! ## Changelog
+//! - 1.2.0: Sent once for a sharded bot, naming the shard that came up first and the shard count
 //! - 1.1.0: Moved configuration from env vars to database
 //! - 1.0.0: Initial release with DM and channel support, rich embeds
 
+use crate::core::discord_limits::fit_embed;
 use crate::database::Database;
 use crate::features::{get_bot_version, get_features};
 use log::{info, warn};
@@ -26,10 +28,11 @@ use std::sync::Arc;
 /// Git commits embedded at compile time by build.rs
 const RECENT_COMMITS: &str = env!("GIT_RECENT_COMMITS");
 
-/// Tracks whether this is the first Ready event (vs reconnect)
+/// Tracks whether this is the first Ready event (vs a reconnect or another shard)
 static FIRST_READY: AtomicBool = AtomicBool::new(true);
 
 /// Handles sending startup notifications to configured destinations
+#[derive(Clone)]
 pub struct StartupNotifier {
     database: Arc<Database>,
 }
@@ -42,9 +45,9 @@ impl StartupNotifier {
 
     /// Sends startup notifications if enabled and this is the first Ready event
     pub async fn send_if_enabled(&self, http: &Http, ready: &Ready) {
-        // Only send on first Ready (not reconnects)
+        // Only send on first Ready (not reconnects, nor the other shards coming up)
         if !FIRST_READY.swap(false, Ordering::SeqCst) {
-            info!("Skipping startup notification (reconnect, not initial startup)");
+            info!("Skipping startup notification (reconnect or additional shard, not initial startup)");
             return;
         }
 
@@ -122,11 +125,10 @@ impl StartupNotifier {
 
         // Basic info fields (inline)
         embed.field("Version", format!("`v{}`", version), true);
-        embed.field("Guilds", ready.guilds.len().to_string(), true);
-
-        // Shard info if available
-        if let Some(shard) = ready.shard {
-            embed.field("Shard", format!("{}/{}", shard[0] + 1, shard[1]), true);
+        let (guilds_label, shard_label) = shard_fields(ready.shard);
+        embed.field(guilds_label, ready.guilds.len().to_string(), true);
+        if let Some(shard) = shard_label {
+            embed.field("Shard", shard, true);
         }
 
         // Feature versions (non-inline for more space)
@@ -165,11 +167,13 @@ impl StartupNotifier {
             embed.thumbnail(url);
         }
 
+        // The feature list alone outgrows a field as features are added
+        fit_embed(&mut embed);
         embed
     }
 
     /// Sends the embed to the bot owner via DM
-    async fn send_to_owner(http: &Http, owner_id: u64, embed: CreateEmbed) -> anyhow::Result<()> {
+    async fn send_to_owner(http: &Http, owner_id: u64, embed: CreateEmbed) -> crate::core::Result<()> {
         let user = UserId(owner_id);
         let dm = user.create_dm_channel(http).await?;
         dm.send_message(http, |m| m.set_embed(embed)).await?;
@@ -182,7 +186,7 @@ impl StartupNotifier {
         http: &Http,
         channel_id: u64,
         embed: CreateEmbed,
-    ) -> anyhow::Result<()> {
+    ) -> crate::core::Result<()> {
         let channel = ChannelId(channel_id);
         channel.send_message(http, |m| m.set_embed(embed)).await?;
         info!("Sent startup notification to channel {}", channel_id);
@@ -190,10 +194,27 @@ impl StartupNotifier {
     }
 }
 
+/// Label for the guild count and the shard field's value. Ready only lists the
+/// guilds of its own shard, so the count is marked as such when there are several.
+fn shard_fields(shard: Option<[u64; 2]>) -> (&'static str, Option<String>) {
+    match shard {
+        Some([id, total]) if total > 1 => ("Guilds (this shard)", Some(format!("{} of {total} (first up)", id + 1))),
+        Some([id, total]) => ("Guilds", Some(format!("{}/{total}", id + 1))),
+        None => ("Guilds", None),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_shard_fields() {
+        assert_eq!(shard_fields(None), ("Guilds", None));
+        assert_eq!(shard_fields(Some([0, 1])), ("Guilds", Some("1/1".to_string())));
+        assert_eq!(shard_fields(Some([2, 4])), ("Guilds (this shard)", Some("3 of 4 (first up)".to_string())));
+    }
+
     #[test]
     fn test_recent_commits_parsing() {
         // Test that the compile-time commits are available
@@ -0,0 +1,367 @@
+//! # Feature: Preflight Checks
+//!
+//! Dependency checks run before connecting to the gateway: the OpenAI key (one
+//! cheap models list call), the local Ollama server when it is the chat
+//! backend, database writes, free disk space next to a SQLite database, and the
+//! curl and ffmpeg binaries used by audio transcription. Failures stop startup
+//! with a message saying what to fix; a missing tool or an unreachable API only
+//! disables what depends on it.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Check Ollama for the chat model; a missing OpenAI key only warns on other providers
+//! - 1.0.0: Initial release with OpenAI, database, disk space and audio tool checks
+
+use crate::core::{Config, ProviderKind};
+use crate::database::{Database, DatabaseBackend};
+use crate::features::analytics::format_bytes;
+use crate::features::audio::AudioTranscriber;
+use log::{error, info, warn};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::Disks;
+
+/// Free space below this next to a SQLite database stops startup
+pub const MIN_FREE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Free space below this next to a SQLite database is warned about
+pub const LOW_FREE_BYTES: u64 = 500 * 1024 * 1024;
+
+const OPENAI_MODELS_URL: &str = "https://api.openai.com/v1/models";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Startup continues with something degraded
+    Warn,
+    /// Startup stops
+    Fail,
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        CheckResult { name, status, detail: detail.into() }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PreflightReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl PreflightReport {
+    pub fn failed(&self) -> bool {
+        self.checks.iter().any(|c| c.status == CheckStatus::Fail)
+    }
+
+    /// One line per check, e.g. `✅ Database: writes succeed`
+    pub fn format(&self) -> String {
+        self.checks
+            .iter()
+            .map(|c| {
+                let icon = match c.status {
+                    CheckStatus::Pass => "✅",
+                    CheckStatus::Warn => "⚠️",
+                    CheckStatus::Fail => "❌",
+                };
+                format!("{icon} {}: {}", c.name, c.detail)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn log(&self) {
+        for check in &self.checks {
+            match check.status {
+                CheckStatus::Pass => info!("Preflight {}: {}", check.name, check.detail),
+                CheckStatus::Warn => warn!("Preflight {}: {}", check.name, check.detail),
+                CheckStatus::Fail => error!("Preflight {}: {}", check.name, check.detail),
+            }
+        }
+    }
+}
+
+/// Interpret the models list response for the configured key and model
+pub fn classify_openai_response(status: u16, body: &str, model: &str) -> CheckResult {
+    const NAME: &str = "OpenAI API";
+    match status {
+        200 => {
+            let listed = serde_json::from_str::<Value>(body)
+                .ok()
+                .and_then(|json| json.get("data").and_then(Value::as_array).cloned())
+                .unwrap_or_default();
+            if listed.iter().any(|m| m.get("id").and_then(Value::as_str) == Some(model)) {
+                CheckResult::new(NAME, CheckStatus::Pass, format!("key accepted, {model} available"))
+            } else {
+                CheckResult::new(
+                    NAME,
+                    CheckStatus::Warn,
+                    format!("key accepted, but {model} is not listed for it; check OPENAI_MODEL"),
+                )
+            }
+        }
+        401 => CheckResult::new(
+            NAME,
+            CheckStatus::Fail,
+            "OPENAI_API_KEY was rejected (401). Create a new key at https://platform.openai.com/api-keys",
+        ),
+        403 => CheckResult::new(
+            NAME,
+            CheckStatus::Fail,
+            "OPENAI_API_KEY lacks permission to list models (403). Give the key the Models read permission or use an unrestricted key",
+        ),
+        429 => CheckResult::new(
+            NAME,
+            CheckStatus::Warn,
+            "rate limited or out of quota (429); AI replies may fail until billing is sorted out",
+        ),
+        code => CheckResult::new(NAME, CheckStatus::Warn, format!("models list returned HTTP {code}; continuing")),
+    }
+}
+
+/// List models with the configured key. Network problems only warn, so an outage
+/// at OpenAI doesn't keep the rest of the bot offline.
+pub async fn check_openai(api_key: &str, model: &str) -> CheckResult {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build();
+    let response = match client {
+        Ok(client) => client.get(OPENAI_MODELS_URL).bearer_auth(api_key).send().await,
+        Err(e) => return CheckResult::new("OpenAI API", CheckStatus::Warn, format!("could not build HTTP client: {e}")),
+    };
+    match response {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let body = response.text().await.unwrap_or_default();
+            classify_openai_response(status, &body, model)
+        }
+        Err(e) => CheckResult::new("OpenAI API", CheckStatus::Warn, format!("could not reach api.openai.com: {e}")),
+    }
+}
+
+/// Interpret Ollama's `/api/tags` listing for the configured model. A bare model
+/// name matches its `:latest` tag.
+pub fn classify_ollama_tags(body: &str, model: &str) -> CheckResult {
+    const NAME: &str = "Ollama";
+    let names: Vec<String> = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|json| json.get("models").and_then(Value::as_array).cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|m| m.get("name").and_then(Value::as_str).map(str::to_string))
+        .collect();
+    let latest = format!("{model}:latest");
+    if names.iter().any(|name| name == model || *name == latest) {
+        CheckResult::new(NAME, CheckStatus::Pass, format!("server reachable, {model} pulled"))
+    } else {
+        CheckResult::new(
+            NAME,
+            CheckStatus::Fail,
+            format!("{model} is not pulled on the Ollama server. Run `ollama pull {model}` or set LLM_MODEL"),
+        )
+    }
+}
+
+/// List the models on the Ollama server; unlike a hosted API it must be up to chat at all
+pub async fn check_ollama(base_url: &str, model: &str) -> CheckResult {
+    let url = format!("{}/api/tags", base_url.trim_end_matches('/'));
+    let response = match reqwest::Client::builder().timeout(Duration::from_secs(10)).build() {
+        Ok(client) => client.get(&url).send().await,
+        Err(e) => return CheckResult::new("Ollama", CheckStatus::Warn, format!("could not build HTTP client: {e}")),
+    };
+    match response {
+        Ok(response) => classify_ollama_tags(&response.text().await.unwrap_or_default(), model),
+        Err(e) => CheckResult::new(
+            "Ollama",
+            CheckStatus::Fail,
+            format!("could not reach {base_url} ({e}). Start Ollama or set OLLAMA_URL"),
+        ),
+    }
+}
+
+/// Write and read back a bot setting
+pub async fn check_database(database: &Database, location: &str) -> CheckResult {
+    const NAME: &str = "Database";
+    let stamp = chrono::Utc::now().to_rfc3339();
+    if let Err(e) = database.set_bot_setting("preflight_last_run", &stamp).await {
+        return CheckResult::new(
+            NAME,
+            CheckStatus::Fail,
+            format!("{location} is not writable ({e}). Check file permissions, or that the PostgreSQL user can write"),
+        );
+    }
+    match database.get_bot_setting("preflight_last_run").await {
+        Ok(Some(value)) if value == stamp => CheckResult::new(NAME, CheckStatus::Pass, format!("writes to {location} succeed")),
+        Ok(_) => CheckResult::new(NAME, CheckStatus::Fail, format!("{location} did not return a value just written")),
+        Err(e) => CheckResult::new(NAME, CheckStatus::Fail, format!("{location} is not readable ({e})")),
+    }
+}
+
+/// Available space on the mount holding `path`: the mount point that is its longest prefix
+pub fn available_space_for(path: &Path, mounts: &[(PathBuf, u64)]) -> Option<u64> {
+    mounts
+        .iter()
+        .filter(|(mount, _)| path.starts_with(mount))
+        .max_by_key(|(mount, _)| mount.as_os_str().len())
+        .map(|(_, available)| *available)
+}
+
+/// Classify free space next to the database
+pub fn classify_free_space(available: u64, directory: &Path) -> CheckResult {
+    const NAME: &str = "Disk space";
+    let free = format_bytes(available);
+    let directory = directory.display();
+    if available < MIN_FREE_BYTES {
+        CheckResult::new(
+            NAME,
+            CheckStatus::Fail,
+            format!("only {free} free for the database in {directory}. Free up space or move DATABASE_PATH"),
+        )
+    } else if available < LOW_FREE_BYTES {
+        CheckResult::new(NAME, CheckStatus::Warn, format!("{free} free in {directory}; the database and backups may fill it"))
+    } else {
+        CheckResult::new(NAME, CheckStatus::Pass, format!("{free} free in {directory}"))
+    }
+}
+
+/// Free space on the disk holding a SQLite database (remote and in-memory databases pass)
+pub fn check_disk_space(backend: &DatabaseBackend) -> CheckResult {
+    let path = match backend {
+        DatabaseBackend::Postgres { .. } => {
+            return CheckResult::new("Disk space", CheckStatus::Pass, "database is on a PostgreSQL server");
+        }
+        DatabaseBackend::Sqlite { path } if path == ":memory:" => {
+            return CheckResult::new("Disk space", CheckStatus::Pass, "database is in memory");
+        }
+        DatabaseBackend::Sqlite { path } => PathBuf::from(path),
+    };
+    let directory = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let directory = directory.canonicalize().unwrap_or_else(|_| directory.to_path_buf());
+
+    let disks = Disks::new_with_refreshed_list();
+    let mounts: Vec<(PathBuf, u64)> = disks
+        .iter()
+        .map(|d| (d.mount_point().to_path_buf(), d.available_space()))
+        .collect();
+    match available_space_for(&directory, &mounts) {
+        Some(available) => classify_free_space(available, &directory),
+        None => CheckResult::new("Disk space", CheckStatus::Warn, format!("could not find the disk holding {}", directory.display())),
+    }
+}
+
+/// Look for curl and ffmpeg and tell the transcriber what it can do without them
+pub fn check_audio_tools() -> Vec<CheckResult> {
+    let curl = AudioTranscriber::is_curl_available();
+    let ffmpeg = AudioTranscriber::is_ffmpeg_available();
+    AudioTranscriber::set_tools_available(ffmpeg, curl);
+
+    let curl_check = if curl {
+        CheckResult::new("curl", CheckStatus::Pass, "found")
+    } else {
+        CheckResult::new("curl", CheckStatus::Warn, "not found; audio transcription is disabled. Install with: apt install curl")
+    };
+    let ffmpeg_check = if ffmpeg {
+        CheckResult::new("ffmpeg", CheckStatus::Pass, "found")
+    } else {
+        CheckResult::new(
+            "ffmpeg",
+            CheckStatus::Warn,
+            "not found; only mp3, mp4, m4a, wav and webm audio can be transcribed. Install with: apt install ffmpeg",
+        )
+    };
+    vec![curl_check, ffmpeg_check]
+}
+
+/// Run every check
+pub async fn run_preflight(config: &Config, database: &Database) -> PreflightReport {
+    let backend = DatabaseBackend::parse(&config.database_path);
+    let mut checks = Vec::new();
+    match config.llm.provider {
+        ProviderKind::OpenAi => checks.push(check_openai(&config.openai_api_key, &config.openai_model).await),
+        provider => {
+            if provider == ProviderKind::Ollama {
+                checks.push(check_ollama(&config.llm.ollama_url, &config.openai_model).await);
+            }
+            // Images, Whisper and moderation still go to OpenAI
+            if config.openai_api_key.is_empty() {
+                checks.push(CheckResult::new(
+                    "OpenAI API",
+                    CheckStatus::Warn,
+                    "OPENAI_API_KEY not set; image generation, voice transcription and image moderation are unavailable",
+                ));
+            } else {
+                checks.push(check_openai(&config.openai_api_key, "whisper-1").await);
+            }
+        }
+    }
+    checks.push(check_database(database, &backend.describe()).await);
+    checks.push(check_disk_space(&backend));
+    checks.extend(check_audio_tools());
+    PreflightReport { checks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_openai_response() {
+        let body = r#"{"data": [{"id": "gpt-4o"}, {"id": "gpt-5.1"}]}"#;
+        assert_eq!(classify_openai_response(200, body, "gpt-5.1").status, CheckStatus::Pass);
+        assert_eq!(classify_openai_response(200, body, "gpt-9").status, CheckStatus::Warn);
+        assert_eq!(classify_openai_response(401, "", "gpt-5.1").status, CheckStatus::Fail);
+        assert_eq!(classify_openai_response(429, "", "gpt-5.1").status, CheckStatus::Warn);
+        assert_eq!(classify_openai_response(503, "", "gpt-5.1").status, CheckStatus::Warn);
+    }
+
+    #[test]
+    fn test_classify_ollama_tags() {
+        let body = r#"{"models": [{"name": "llama3.1:latest"}, {"name": "qwen2.5:7b"}]}"#;
+        assert_eq!(classify_ollama_tags(body, "llama3.1").status, CheckStatus::Pass);
+        assert_eq!(classify_ollama_tags(body, "qwen2.5:7b").status, CheckStatus::Pass);
+        assert_eq!(classify_ollama_tags(body, "mistral").status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_available_space_for_longest_mount() {
+        let mounts = vec![(PathBuf::from("/"), 10), (PathBuf::from("/var"), 20), (PathBuf::from("/var/lib/persona"), 30)];
+        assert_eq!(available_space_for(Path::new("/var/lib/persona"), &mounts), Some(30));
+        assert_eq!(available_space_for(Path::new("/var/log"), &mounts), Some(20));
+        assert_eq!(available_space_for(Path::new("/home/bot"), &mounts), Some(10));
+        // Path prefixes match whole components only
+        assert_eq!(available_space_for(Path::new("/variety"), &mounts), Some(10));
+        assert_eq!(available_space_for(Path::new("relative"), &mounts), None);
+    }
+
+    #[test]
+    fn test_classify_free_space() {
+        let dir = Path::new("/var/lib/persona");
+        assert_eq!(classify_free_space(MIN_FREE_BYTES - 1, dir).status, CheckStatus::Fail);
+        assert_eq!(classify_free_space(LOW_FREE_BYTES - 1, dir).status, CheckStatus::Warn);
+        assert_eq!(classify_free_space(LOW_FREE_BYTES, dir).status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_report_failed_and_format() {
+        let report = PreflightReport {
+            checks: vec![
+                CheckResult::new("Database", CheckStatus::Pass, "writes succeed"),
+                CheckResult::new("ffmpeg", CheckStatus::Warn, "not found"),
+            ],
+        };
+        assert!(!report.failed());
+        assert_eq!(report.format(), "✅ Database: writes succeed\n⚠️ ffmpeg: not found");
+    }
+}
@@ -0,0 +1,17 @@
+//! # Story Feature
+//!
+//! `/story start` opens a thread where a persona narrates a collaborative
+//! story on a theme, tracking characters, inventory and chapter summaries in
+//! `story_sessions`. `/story recap` shows the story so far and `/story end`
+//! closes it with a final recap.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod narration;
+
+pub use narration::{
+    apply_state_update, format_story_recap, normalize_theme, opening_request, state_update_prompt, story_instruction,
+    story_thread_name, StoryState, MAX_STORY_THEME_CHARS, STORY_CHAPTER_TURNS,
+};
@@ -0,0 +1,244 @@
+//! # Feature: Story Mode
+//!
+//! A collaborative story told in a thread, with a persona as the narrator.
+//! Every message in the thread is a player's move; the narrator answers with
+//! what happens next. The story's state — characters, the party's inventory
+//! and a summary of each finished chapter — lives in `story_sessions` and is
+//! put in front of the narrator on every turn, so the story stays consistent
+//! long after the early messages have left the context window. A chapter is
+//! closed, and the state refreshed, every [`STORY_CHAPTER_TURNS`] moves.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use crate::core::discord_limits::truncate;
+use crate::database::StorySession;
+use serde_json::Value;
+
+/// Player moves per chapter; the state is updated when a chapter closes
+pub const STORY_CHAPTER_TURNS: i64 = 12;
+
+/// Longest theme accepted by `/story start`
+pub const MAX_STORY_THEME_CHARS: usize = 200;
+
+/// Most characters or inventory items kept in the state
+const MAX_TRACKED_ITEMS: usize = 25;
+
+/// Longest name or item kept in the state
+const MAX_ITEM_CHARS: usize = 80;
+
+/// Chapter summaries shown to the narrator, newest kept
+const NARRATOR_CHAPTERS: usize = 5;
+
+/// Discord's limit on thread names
+const MAX_THREAD_NAME: usize = 100;
+
+/// The tracked state of a story
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoryState {
+    pub characters: Vec<String>,
+    pub inventory: Vec<String>,
+    /// One summary per finished chapter, oldest first
+    pub chapters: Vec<String>,
+}
+
+impl StoryState {
+    /// The state kept in a session's JSON columns; unreadable columns start empty
+    pub fn from_session(session: &StorySession) -> Self {
+        let list = |json: &str| serde_json::from_str::<Vec<String>>(json).unwrap_or_default();
+        StoryState {
+            characters: list(&session.characters),
+            inventory: list(&session.inventory),
+            chapters: list(&session.chapter_summaries),
+        }
+    }
+
+    /// `(characters, inventory, chapter_summaries)` as JSON arrays for `story_sessions`
+    pub fn to_columns(&self) -> (String, String, String) {
+        let json = |items: &Vec<String>| serde_json::to_string(items).unwrap_or_else(|_| "[]".to_string());
+        (json(&self.characters), json(&self.inventory), json(&self.chapters))
+    }
+
+    /// The chapter being played, from 1
+    pub fn current_chapter(&self) -> usize {
+        self.chapters.len() + 1
+    }
+}
+
+/// A theme as given, trimmed; None when it's empty or too long
+pub fn normalize_theme(theme: &str) -> Option<String> {
+    let theme = theme.split_whitespace().collect::<Vec<_>>().join(" ");
+    if theme.is_empty() || theme.chars().count() > MAX_STORY_THEME_CHARS {
+        return None;
+    }
+    Some(theme)
+}
+
+/// Name of the thread a story is told in
+pub fn story_thread_name(theme: &str) -> String {
+    truncate(&format!("📖 {theme}"), MAX_THREAD_NAME)
+}
+
+/// Added to the narrator persona's system prompt on every turn
+pub fn story_instruction(theme: &str, state: &StoryState) -> String {
+    let list = |items: &[String]| if items.is_empty() { "none yet".to_string() } else { items.join("; ") };
+    let mut instruction = format!(
+        "\n\n## Story Mode\nYou are the narrator of a collaborative story in a Discord thread, staying in your own voice. \
+Theme: {theme}\n\nPlayer messages start with the player's name and describe what their character does or says. \
+Narrate what happens next in two or three short paragraphs, keeping to the established characters, items and events. \
+Never decide what a player's character does or feels for them; end by leaving the players something to react to.\n\n\
+Chapter: {}\nCharacters: {}\nInventory: {}",
+        state.current_chapter(),
+        list(&state.characters),
+        list(&state.inventory)
+    );
+    let start = state.chapters.len().saturating_sub(NARRATOR_CHAPTERS);
+    if !state.chapters.is_empty() {
+        instruction.push_str("\n\nStory so far:");
+        for (i, summary) in state.chapters.iter().enumerate().skip(start) {
+            instruction.push_str(&format!("\n- Chapter {}: {summary}", i + 1));
+        }
+    }
+    instruction
+}
+
+/// User turn that asks the narrator to open the story
+pub fn opening_request(theme: &str) -> String {
+    format!(
+        "Open a new story on the theme \"{theme}\". Set the scene, introduce where the players find themselves, and end \
+by inviting them to say who their characters are and what they do first."
+    )
+}
+
+/// Instructions for closing a chapter: the updated state and a summary of what happened
+pub fn state_update_prompt(theme: &str, state: &StoryState, transcript: &str) -> String {
+    let list = |items: &[String]| serde_json::to_string(items).unwrap_or_else(|_| "[]".to_string());
+    format!(
+        "You keep the records of a collaborative story (theme: {theme}). Current records:\ncharacters: {}\ninventory: {}\n\n\
+Latest chapter:\n{transcript}\n\nUpdate the records from the latest chapter. Characters are the named people and \
+creatures that matter, each as \"Name - a few words\". Inventory is what the players' party carries now, without \
+items they lost or used up. Answer with only JSON: {{\"characters\": [...], \"inventory\": [...], \
+\"chapter_summary\": \"two or three sentences on what happened in this chapter\"}}",
+        list(&state.characters),
+        list(&state.inventory)
+    )
+}
+
+/// Apply the model's reply to [`state_update_prompt`]; None when it isn't the JSON asked for
+pub fn apply_state_update(state: &StoryState, reply: &str) -> Option<StoryState> {
+    let reply = reply.trim();
+    let (start, end) = reply.find('{').zip(reply.rfind('}'))?;
+    let value: Value = serde_json::from_str(reply.get(start..=end)?).ok()?;
+    let list = |key: &str| -> Option<Vec<String>> {
+        let mut items: Vec<String> = Vec::new();
+        for item in value.get(key)?.as_array()? {
+            let item = truncate(item.as_str()?.trim(), MAX_ITEM_CHARS);
+            if !item.is_empty() && !items.iter().any(|i| i.eq_ignore_ascii_case(&item)) {
+                items.push(item);
+            }
+        }
+        items.truncate(MAX_TRACKED_ITEMS);
+        Some(items)
+    };
+    let mut updated = StoryState {
+        characters: list("characters")?,
+        inventory: list("inventory")?,
+        chapters: state.chapters.clone(),
+    };
+    if let Some(summary) = value.get("chapter_summary").and_then(Value::as_str).map(str::trim).filter(|s| !s.is_empty()) {
+        updated.chapters.push(summary.to_string());
+    }
+    Some(updated)
+}
+
+/// The `/story recap` reply, also posted when a story ends
+pub fn format_story_recap(theme: &str, state: &StoryState, ended: bool) -> String {
+    let heading = if ended { "🏁 **The End**" } else { "📖 **Story so far**" };
+    let list = |items: &[String]| {
+        if items.is_empty() {
+            " *none yet*".to_string()
+        } else {
+            items.iter().map(|i| format!("\n• {i}")).collect()
+        }
+    };
+    let mut recap = format!("{heading}: *{theme}*\n");
+    if state.chapters.is_empty() {
+        recap.push_str("\nNo chapter has been completed yet.\n");
+    }
+    for (i, summary) in state.chapters.iter().enumerate() {
+        recap.push_str(&format!("\n**Chapter {}**: {summary}\n", i + 1));
+    }
+    recap.push_str(&format!("\n🎭 **Characters**:{}\n🎒 **Inventory**:{}", list(&state.characters), list(&state.inventory)));
+    recap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> StoryState {
+        StoryState {
+            characters: vec!["Mira - a thief".to_string()],
+            inventory: vec!["rope".to_string()],
+            chapters: vec!["They met in a tavern.".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_normalize_theme() {
+        assert_eq!(normalize_theme("  space   pirates \n"), Some("space pirates".to_string()));
+        assert_eq!(normalize_theme(" "), None);
+        assert_eq!(normalize_theme(&"x".repeat(MAX_STORY_THEME_CHARS + 1)), None);
+    }
+
+    #[test]
+    fn test_story_instruction_includes_state() {
+        let instruction = story_instruction("heist", &state());
+        assert!(instruction.contains("Theme: heist"));
+        assert!(instruction.contains("Chapter: 2"));
+        assert!(instruction.contains("Characters: Mira - a thief"));
+        assert!(instruction.contains("- Chapter 1: They met in a tavern."));
+        assert!(story_instruction("heist", &StoryState::default()).contains("Inventory: none yet"));
+    }
+
+    #[test]
+    fn test_apply_state_update() {
+        let reply = "```json\n{\"characters\": [\"Mira - a thief\", \"mira - a thief\", \"Old Tom\"], \"inventory\": [\"map\", \"\"], \
+\"chapter_summary\": \"They stole the map.\"}\n```";
+        let updated = apply_state_update(&state(), reply).unwrap();
+        assert_eq!(updated.characters, vec!["Mira - a thief".to_string(), "Old Tom".to_string()]);
+        assert_eq!(updated.inventory, vec!["map".to_string()]);
+        assert_eq!(updated.chapters.len(), 2);
+        assert_eq!(updated.current_chapter(), 3);
+
+        assert_eq!(apply_state_update(&state(), "The party moves on."), None);
+        assert_eq!(apply_state_update(&state(), r#"{"characters": []}"#), None);
+    }
+
+    #[test]
+    fn test_state_columns_round_trip() {
+        let (characters, inventory, chapter_summaries) = state().to_columns();
+        let session = StorySession {
+            characters,
+            inventory,
+            chapter_summaries,
+            ..Default::default()
+        };
+        assert_eq!(StoryState::from_session(&session), state());
+    }
+
+    #[test]
+    fn test_format_story_recap() {
+        let recap = format_story_recap("heist", &state(), false);
+        assert!(recap.starts_with("📖 **Story so far**: *heist*"));
+        assert!(recap.contains("**Chapter 1**: They met in a tavern."));
+        assert!(recap.contains("🎒 **Inventory**:\n• rope"));
+        let ended = format_story_recap("heist", &StoryState::default(), true);
+        assert!(ended.starts_with("🏁 **The End**"));
+        assert!(ended.contains("No chapter has been completed yet."));
+    }
+}
@@ -0,0 +1,17 @@
+//! # Summaries Feature
+//!
+//! Rolling conversation summaries: once a (user, channel) conversation grows
+//! past the context window, the turns falling out of it are folded into a
+//! stored, persona-aware summary that is prepended to later prompts instead of
+//! being dropped. `/summary` shows the current summary.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod rolling;
+
+pub use rolling::{
+    format_summary_context, format_summary_display, messages_to_fold, summary_request, summary_system_prompt,
+    MAX_SUMMARY_CHARS, SUMMARY_BATCH,
+};
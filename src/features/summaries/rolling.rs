@@ -0,0 +1,108 @@
+//! # Feature: Rolling Summaries
+//!
+//! Decides when older turns should be folded into the stored summary and
+//! builds the summarizer prompt. Folding waits until [`SUMMARY_BATCH`] turns
+//! have fallen out of the context window, so the model is called once per
+//! batch rather than on every message.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with persona-aware summaries
+
+use crate::core::discord_limits::truncate;
+use crate::features::personas::handoff::format_transcript;
+
+/// Turns beyond the context window collected before they're summarized
+pub const SUMMARY_BATCH: usize = 10;
+
+/// Longest stored summary, in characters
+pub const MAX_SUMMARY_CHARS: usize = 2000;
+
+/// Transcript sent per fold at most, in characters
+const MAX_FOLD_CHARS: usize = 12_000;
+
+/// How many of the oldest unsummarized turns to fold, leaving `max_context` in the window
+pub fn messages_to_fold(unsummarized: usize, max_context: usize) -> usize {
+    if unsummarized > max_context + SUMMARY_BATCH {
+        unsummarized - max_context
+    } else {
+        0
+    }
+}
+
+/// System prompt for the summarizer, written for the persona that will read it
+pub fn summary_system_prompt(persona_name: &str) -> String {
+    format!(
+        "You keep a running summary of a conversation between a user and {persona_name}, so {persona_name} can \
+continue it after the older messages are gone. Merge the new messages into the current summary. Keep what \
+{persona_name} needs to stay consistent: the user's goals, preferences and facts they shared, what has been \
+answered or decided, and open questions. Use at most 10 short bullet points in neutral third person, drop \
+small talk, and keep it under {MAX_SUMMARY_CHARS} characters."
+    )
+}
+
+/// User message for the summarizer: the current summary, if any, and the turns to fold in
+pub fn summary_request(previous: Option<&str>, turns: &[(String, String)]) -> String {
+    let transcript = format_transcript(turns, MAX_FOLD_CHARS);
+    match previous.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(summary) => format!("Current summary:\n{summary}\n\nNew messages:\n{transcript}"),
+        None => format!("Current summary: (none yet)\n\nNew messages:\n{transcript}"),
+    }
+}
+
+/// Text appended to the system prompt when a summary exists
+pub fn format_summary_context(summary: &str) -> String {
+    format!(
+        "\n\nSummary of your earlier conversation with this user (those messages are no longer shown):\n{}",
+        truncate(summary.trim(), MAX_SUMMARY_CHARS)
+    )
+}
+
+/// `/summary` reply
+pub fn format_summary_display(summary: Option<(&str, &str)>) -> String {
+    match summary {
+        Some((text, updated_at)) => format!(
+            "📝 **Conversation summary for this channel** (updated {updated_at} UTC)\n{}",
+            truncate(text.trim(), MAX_SUMMARY_CHARS)
+        ),
+        None => "📝 There's no summary for this channel yet. Older messages are summarized once the conversation \
+grows past the context window."
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(role: &str, content: &str) -> (String, String) {
+        (role.to_string(), content.to_string())
+    }
+
+    #[test]
+    fn test_messages_to_fold() {
+        assert_eq!(messages_to_fold(40, 40), 0);
+        assert_eq!(messages_to_fold(40 + SUMMARY_BATCH, 40), 0);
+        assert_eq!(messages_to_fold(41 + SUMMARY_BATCH, 40), 1 + SUMMARY_BATCH);
+    }
+
+    #[test]
+    fn test_summary_request() {
+        let turns = vec![turn("user", "I'm vegetarian"), turn("assistant", "Noted!")];
+        assert_eq!(
+            summary_request(None, &turns),
+            "Current summary: (none yet)\n\nNew messages:\nUser: I'm vegetarian\nAssistant: Noted!"
+        );
+        assert!(summary_request(Some("- Wants pasta recipes"), &turns).starts_with("Current summary:\n- Wants pasta recipes\n\n"));
+    }
+
+    #[test]
+    fn test_summary_prompt_names_persona() {
+        assert!(summary_system_prompt("Chef Gordon").contains("a user and Chef Gordon"));
+        assert_eq!(format_summary_display(None).lines().count(), 1);
+        assert!(format_summary_context("- Likes cats").ends_with("- Likes cats"));
+    }
+}
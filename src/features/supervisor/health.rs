@@ -0,0 +1,123 @@
+//! # Feature: Health Endpoint
+//!
+//! `GET /health` returns the supervised task states as JSON. The status is
+//! `ok` when every task is running, `degraded` while a task is waiting to
+//! restart (still 200, since the supervisor is handling it) and `failing`
+//! with a 503 once a task has been given up on. Other endpoints, such as the
+//! live event stream, can be served on the same listener.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Extra routes merged into the listener
+//! - 1.0.0: Initial release
+
+use super::tasks::{Supervisor, TaskState, TaskStatus};
+use crate::core::Result;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use log::info;
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Ok,
+    Degraded,
+    Failing,
+}
+
+impl HealthStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthStatus::Ok => "ok",
+            HealthStatus::Degraded => "degraded",
+            HealthStatus::Failing => "failing",
+        }
+    }
+}
+
+/// Overall status and JSON body for a set of task states
+pub fn health_report(statuses: &[TaskStatus]) -> (HealthStatus, Value) {
+    let status = if statuses.iter().any(|s| s.state == TaskState::Failed) {
+        HealthStatus::Failing
+    } else if statuses.iter().any(|s| matches!(s.state, TaskState::Backoff { .. })) {
+        HealthStatus::Degraded
+    } else {
+        HealthStatus::Ok
+    };
+
+    let tasks: Vec<Value> = statuses
+        .iter()
+        .map(|s| {
+            let retry_at = match &s.state {
+                TaskState::Backoff { retry_at } => Some(retry_at.to_rfc3339()),
+                _ => None,
+            };
+            json!({
+                "name": s.name,
+                "state": s.state.label(),
+                "since": s.since.to_rfc3339(),
+                "restarts": s.restarts,
+                "consecutive_failures": s.consecutive_failures,
+                "retry_at": retry_at,
+                "last_error": s.last_error,
+            })
+        })
+        .collect();
+    (status, json!({ "status": status.as_str(), "tasks": tasks }))
+}
+
+async fn health(State(supervisor): State<Supervisor>) -> (StatusCode, Json<Value>) {
+    let (status, body) = health_report(&supervisor.statuses());
+    let code = match status {
+        HealthStatus::Failing => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::OK,
+    };
+    (code, Json(body))
+}
+
+/// Serve `GET /health` and any `extra` routes on `addr` (e.g. `0.0.0.0:8788`) until the process exits
+pub async fn serve_health(addr: &str, supervisor: Supervisor, extra: Router) -> Result<()> {
+    let app = Router::new().route("/health", get(health)).with_state(supervisor).merge(extra);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Health endpoint listening on {}", addr);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn status(name: &str, state: TaskState) -> TaskStatus {
+        TaskStatus {
+            name: name.to_string(),
+            state,
+            restarts: 0,
+            consecutive_failures: 0,
+            last_error: None,
+            since: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_health_report() {
+        let running = status("reminders", TaskState::Running);
+        let backoff = status("slack_bridge", TaskState::Backoff { retry_at: Utc::now() });
+        let failed = status("discord_gateway", TaskState::Failed);
+
+        assert_eq!(health_report(std::slice::from_ref(&running)).0, HealthStatus::Ok);
+        assert_eq!(health_report(&[running.clone(), backoff.clone()]).0, HealthStatus::Degraded);
+        let (overall, body) = health_report(&[running, backoff, failed]);
+        assert_eq!(overall, HealthStatus::Failing);
+        assert_eq!(body["status"], "failing");
+        assert_eq!(body["tasks"][1]["state"], "backoff");
+        assert!(body["tasks"][0]["retry_at"].is_null());
+    }
+}
@@ -0,0 +1,19 @@
+//! # Supervisor Feature
+//!
+//! Runs the Discord gateway and each background task under a supervisor, so a
+//! task that panics or fails is restarted on its own with exponential backoff
+//! while the others keep running. Task states are shown by `/ops bots` and
+//! served as JSON on `GET /health` when `HEALTH_LISTEN_ADDR` is set.
+//! Shutting the supervisor down stops every task.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod health;
+pub mod policy;
+pub mod tasks;
+
+pub use health::{health_report, serve_health, HealthStatus};
+pub use policy::RestartPolicy;
+pub use tasks::{format_task_states, Supervisor, TaskState, TaskStatus};
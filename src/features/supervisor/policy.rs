@@ -0,0 +1,94 @@
+//! # Feature: Restart Policy
+//!
+//! Exponential backoff with jitter for restarting crashed tasks. The delay
+//! doubles with each consecutive crash up to a cap; a task that ran long enough
+//! before crashing starts over at the base delay.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with capped doubling, equal jitter and optional restart limit
+
+use std::time::Duration;
+
+/// A task that runs this long before crashing is treated as healthy again
+pub const STABLE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    pub base: Duration,
+    pub max: Duration,
+    /// Consecutive crashes allowed before giving up; None restarts indefinitely
+    pub max_restarts: Option<u32>,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            base: Duration::from_secs(2),
+            max: Duration::from_secs(300),
+            max_restarts: None,
+        }
+    }
+}
+
+impl RestartPolicy {
+    pub fn new(base_secs: u64, max_secs: u64, max_restarts: Option<u32>) -> Self {
+        RestartPolicy {
+            base: Duration::from_secs(base_secs.max(1)),
+            max: Duration::from_secs(max_secs.max(base_secs).max(1)),
+            max_restarts,
+        }
+    }
+
+    /// Backoff before restart number `attempt` (1-based) of a crash streak, without jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        self.base.saturating_mul(factor).min(self.max)
+    }
+
+    /// Backoff with equal jitter: half the delay is fixed and half scaled by `jitter` in `[0, 1)`,
+    /// so tasks that crashed together don't all restart at the same moment
+    pub fn delay(&self, attempt: u32, jitter: f64) -> Duration {
+        let backoff = self.backoff(attempt);
+        backoff / 2 + backoff.div_f64(2.0).mul_f64(jitter.clamp(0.0, 1.0))
+    }
+
+    /// Whether a task that has crashed `consecutive` times in a row should stay down
+    pub fn gives_up(&self, consecutive: u32) -> bool {
+        self.max_restarts.is_some_and(|max| consecutive > max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let policy = RestartPolicy::new(2, 60, None);
+        assert_eq!(policy.backoff(1), Duration::from_secs(2));
+        assert_eq!(policy.backoff(2), Duration::from_secs(4));
+        assert_eq!(policy.backoff(5), Duration::from_secs(32));
+        assert_eq!(policy.backoff(6), Duration::from_secs(60));
+        assert_eq!(policy.backoff(1000), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_delay_jitter_range() {
+        let policy = RestartPolicy::new(10, 300, None);
+        assert_eq!(policy.delay(1, 0.0), Duration::from_secs(5));
+        assert_eq!(policy.delay(1, 1.0), Duration::from_secs(10));
+        assert_eq!(policy.delay(2, 0.5), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn test_gives_up() {
+        assert!(!RestartPolicy::new(1, 10, None).gives_up(u32::MAX));
+        let limited = RestartPolicy::new(1, 10, Some(5));
+        assert!(!limited.gives_up(5));
+        assert!(limited.gives_up(6));
+    }
+}
@@ -0,0 +1,349 @@
+//! # Feature: Task Supervisor
+//!
+//! Spawns named long-running tasks and restarts each one independently when it
+//! panics or returns an error, following a [`RestartPolicy`]. A task that
+//! returns `Ok(())` has shut down on purpose and is left stopped.
+//! [`Supervisor::shutdown`] stops every task and keeps them from restarting.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Shutdown of all tasks
+//! - 1.0.0: Initial release with per-task restart isolation and state tracking
+
+use super::policy::{RestartPolicy, STABLE_AFTER};
+use crate::core::Result;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use log::{error, info, warn};
+use rand::Rng;
+use std::any::Any;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// Longest error message kept per task
+const MAX_ERROR_CHARS: usize = 300;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskState {
+    Running,
+    /// Crashed and waiting to restart
+    Backoff { retry_at: DateTime<Utc> },
+    /// Returned normally and was not restarted
+    Stopped,
+    /// Hit the restart limit and stays down
+    Failed,
+}
+
+impl TaskState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskState::Running => "running",
+            TaskState::Backoff { .. } => "backoff",
+            TaskState::Stopped => "stopped",
+            TaskState::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskStatus {
+    pub name: String,
+    pub state: TaskState,
+    /// Restarts since the process started
+    pub restarts: u32,
+    /// Crashes in the current streak, reset once the task stays up
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// When the task entered its current state
+    pub since: DateTime<Utc>,
+}
+
+/// Shared registry of supervised tasks
+#[derive(Clone, Default)]
+pub struct Supervisor {
+    policy: RestartPolicy,
+    tasks: Arc<DashMap<String, TaskStatus>>,
+    shutdown: Arc<ShutdownSignal>,
+}
+
+#[derive(Default)]
+struct ShutdownSignal {
+    requested: AtomicBool,
+    notify: Notify,
+}
+
+/// Text of a panic payload
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panic with a non-string payload".to_string()
+    }
+}
+
+impl Supervisor {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Supervisor { policy, tasks: Arc::new(DashMap::new()), shutdown: Arc::default() }
+    }
+
+    /// Stop every task, now and as it comes out of backoff; tasks spawned later never start
+    pub fn shutdown(&self) {
+        self.shutdown.requested.store(true, Ordering::SeqCst);
+        self.shutdown.notify.notify_waiters();
+    }
+
+    /// Resolves once [`Supervisor::shutdown`] has been called
+    async fn shutdown_requested(&self) {
+        // Registered before the flag is read, so a shutdown in between still wakes it
+        let notified = self.shutdown.notify.notified();
+        if self.shutdown.requested.load(Ordering::SeqCst) {
+            return;
+        }
+        notified.await;
+    }
+
+    pub fn policy(&self) -> RestartPolicy {
+        self.policy
+    }
+
+    fn set_state(&self, name: &str, state: TaskState) {
+        if let Some(mut status) = self.tasks.get_mut(name) {
+            status.state = state;
+            status.since = Utc::now();
+        }
+    }
+
+    /// Run the task built by `make` until it returns `Ok(())` or the policy gives up,
+    /// building a fresh future for each restart. The handle resolves to the final state.
+    pub fn spawn<F, Fut>(&self, name: &str, make: F) -> JoinHandle<TaskState>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.tasks.insert(
+            name.to_string(),
+            TaskStatus {
+                name: name.to_string(),
+                state: TaskState::Running,
+                restarts: 0,
+                consecutive_failures: 0,
+                last_error: None,
+                since: Utc::now(),
+            },
+        );
+
+        let supervisor = self.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            loop {
+                if supervisor.shutdown.requested.load(Ordering::SeqCst) {
+                    supervisor.set_state(&name, TaskState::Stopped);
+                    return TaskState::Stopped;
+                }
+                supervisor.set_state(&name, TaskState::Running);
+                let started = Instant::now();
+                let mut task = tokio::spawn(make());
+                let outcome = tokio::select! {
+                    outcome = &mut task => outcome,
+                    _ = supervisor.shutdown_requested() => {
+                        task.abort();
+                        info!("Task {name} shut down");
+                        supervisor.set_state(&name, TaskState::Stopped);
+                        return TaskState::Stopped;
+                    }
+                };
+                let error = match outcome {
+                    Ok(Ok(())) => {
+                        info!("Task {name} stopped");
+                        supervisor.set_state(&name, TaskState::Stopped);
+                        return TaskState::Stopped;
+                    }
+                    Ok(Err(e)) => format!("{e:#}"),
+                    Err(e) if e.is_panic() => format!("panicked: {}", panic_message(e.into_panic())),
+                    Err(_) => {
+                        supervisor.set_state(&name, TaskState::Stopped);
+                        return TaskState::Stopped;
+                    }
+                };
+
+                let consecutive = {
+                    let Some(mut status) = supervisor.tasks.get_mut(&name) else {
+                        return TaskState::Stopped;
+                    };
+                    if started.elapsed() >= STABLE_AFTER {
+                        status.consecutive_failures = 0;
+                    }
+                    status.consecutive_failures += 1;
+                    status.last_error = Some(error.chars().take(MAX_ERROR_CHARS).collect());
+                    status.consecutive_failures
+                };
+
+                if supervisor.policy.gives_up(consecutive) {
+                    error!("Task {name} crashed {consecutive} times in a row, giving up: {error}");
+                    supervisor.set_state(&name, TaskState::Failed);
+                    return TaskState::Failed;
+                }
+
+                let delay = supervisor.policy.delay(consecutive, rand::rng().random::<f64>());
+                warn!("Task {name} crashed ({error}), restarting in {}s", delay.as_secs());
+                let retry_at = Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default();
+                supervisor.set_state(&name, TaskState::Backoff { retry_at });
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = supervisor.shutdown_requested() => {
+                        supervisor.set_state(&name, TaskState::Stopped);
+                        return TaskState::Stopped;
+                    }
+                }
+
+                if let Some(mut status) = supervisor.tasks.get_mut(&name) {
+                    status.restarts += 1;
+                }
+            }
+        })
+    }
+
+    /// All supervised tasks, sorted by name
+    pub fn statuses(&self) -> Vec<TaskStatus> {
+        let mut statuses: Vec<TaskStatus> = self.tasks.iter().map(|entry| entry.value().clone()).collect();
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
+    }
+}
+
+/// `/ops bots` listing: one line per task with state, restarts and the last error
+pub fn format_task_states(statuses: &[TaskStatus], policy: &RestartPolicy, now: DateTime<Utc>) -> String {
+    if statuses.is_empty() {
+        return "**Supervised Tasks**\n\nNo tasks are supervised.".to_string();
+    }
+
+    let limit = match policy.max_restarts {
+        Some(max) => format!("gives up after {max} consecutive crashes"),
+        None => "restarts indefinitely".to_string(),
+    };
+    let mut lines = vec![
+        "**Supervised Tasks**".to_string(),
+        format!(
+            "Backoff {}s → {}s with jitter, {limit}\n",
+            policy.base.as_secs(),
+            policy.max.as_secs()
+        ),
+    ];
+
+    for status in statuses {
+        let icon = match status.state {
+            TaskState::Running => "🟢",
+            TaskState::Backoff { .. } => "🟡",
+            TaskState::Stopped => "⚪",
+            TaskState::Failed => "🔴",
+        };
+        let detail = match &status.state {
+            TaskState::Backoff { retry_at } => {
+                format!("retry in {}s", (*retry_at - now).num_seconds().max(0))
+            }
+            state => format!("{} since <t:{}:R>", state.label(), status.since.timestamp()),
+        };
+        let mut line = format!("{icon} **{}** · {detail} · {} restarts", status.name, status.restarts);
+        if let Some(error) = &status.last_error {
+            line.push_str(&format!("\n└ last error: `{error}`"));
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::BotError;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    fn fast_policy(max_restarts: Option<u32>) -> RestartPolicy {
+        RestartPolicy { base: Duration::from_millis(1), max: Duration::from_millis(2), max_restarts }
+    }
+
+    #[tokio::test]
+    async fn test_crashing_task_is_isolated_and_gives_up() {
+        let supervisor = Supervisor::new(fast_policy(Some(3)));
+        let healthy = supervisor.spawn("healthy", || async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        });
+        let crashing = supervisor.spawn("crashing", || async { panic!("boom") });
+
+        assert_eq!(crashing.await.unwrap(), TaskState::Failed);
+        let statuses = supervisor.statuses();
+        assert_eq!(statuses[0].name, "crashing");
+        assert_eq!(statuses[0].restarts, 3);
+        assert_eq!(statuses[0].last_error.as_deref(), Some("panicked: boom"));
+        assert_eq!(statuses[1].state, TaskState::Running);
+        healthy.abort();
+    }
+
+    #[tokio::test]
+    async fn test_task_recovers_after_errors() {
+        let supervisor = Supervisor::new(fast_policy(None));
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let task = supervisor.spawn("flaky", move || {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                    return Err(BotError::internal("connection reset"));
+                }
+                Ok(())
+            }
+        });
+
+        assert_eq!(task.await.unwrap(), TaskState::Stopped);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        let status = &supervisor.statuses()[0];
+        assert_eq!(status.restarts, 2);
+        assert_eq!(status.last_error.as_deref(), Some("connection reset"));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_running_tasks() {
+        let supervisor = Supervisor::new(fast_policy(None));
+        let task = supervisor.spawn("sleeper", || async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        });
+        tokio::task::yield_now().await;
+
+        supervisor.shutdown();
+        let state = tokio::time::timeout(Duration::from_secs(5), task).await.unwrap().unwrap();
+        assert_eq!(state, TaskState::Stopped);
+        assert_eq!(supervisor.statuses()[0].state, TaskState::Stopped);
+
+        let late = supervisor.spawn("late", || async { Err(BotError::internal("never runs")) });
+        assert_eq!(late.await.unwrap(), TaskState::Stopped);
+    }
+
+    #[test]
+    fn test_format_task_states() {
+        let now = Utc::now();
+        let statuses = vec![TaskStatus {
+            name: "discord_gateway".to_string(),
+            state: TaskState::Backoff { retry_at: now + chrono::Duration::seconds(30) },
+            restarts: 2,
+            consecutive_failures: 2,
+            last_error: Some("gateway closed".to_string()),
+            since: now,
+        }];
+        let output = format_task_states(&statuses, &RestartPolicy::default(), now);
+        assert!(output.contains("🟡 **discord_gateway** · retry in 30s · 2 restarts"));
+        assert!(output.contains("last error: `gateway closed`"));
+        assert!(output.contains("restarts indefinitely"));
+    }
+}
@@ -0,0 +1,18 @@
+//! # Telemetry Feature
+//!
+//! Structured tracing: the event handler opens a span per interaction and
+//! message carrying `bot_id`, `guild_id`, `user_id` and `interaction_id`, and
+//! LLM calls and database checkouts open child spans, so log lines inside them
+//! carry that context. With `OTEL_EXPORTER_OTLP_ENDPOINT` set, finished spans
+//! are exported to an OpenTelemetry collector for end-to-end timing of slow
+//! commands.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod otlp;
+pub mod subscriber;
+
+pub use otlp::{export_request, FinishedSpan, OtlpExporter, SpanValue, OTLP_BATCH_SIZE, OTLP_FLUSH_INTERVAL};
+pub use subscriber::{init_telemetry, SpanTracker, CONTEXT_FIELDS};
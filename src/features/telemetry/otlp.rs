@@ -0,0 +1,217 @@
+//! # Feature: OTLP Span Export
+//!
+//! Finished spans are batched and sent to an OpenTelemetry collector using
+//! OTLP over HTTP with the JSON encoding (`POST /v1/traces`), so slow slash
+//! commands can be followed end to end in Jaeger, Tempo or Honeycomb. Export
+//! runs on its own task; a collector that is down costs one failed request
+//! per batch, and the batch is dropped.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with batched OTLP/HTTP JSON export
+
+use crate::core::{BotError, OtlpConfig, Result};
+use log::{debug, warn};
+use serde_json::{json, Value};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// Spans sent in one request at most
+pub const OTLP_BATCH_SIZE: usize = 256;
+
+/// Longest a finished span waits before its batch is sent
+pub const OTLP_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A span field value, kept typed so the collector can filter on numbers
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpanValue {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl fmt::Display for SpanValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpanValue::Str(value) => f.write_str(value),
+            SpanValue::Int(value) => write!(f, "{value}"),
+            SpanValue::Float(value) => write!(f, "{value}"),
+            SpanValue::Bool(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl SpanValue {
+    /// OTLP `AnyValue` (64-bit integers are strings in the JSON encoding)
+    fn to_otlp(&self) -> Value {
+        match self {
+            SpanValue::Str(value) => json!({ "stringValue": value }),
+            SpanValue::Int(value) => json!({ "intValue": value.to_string() }),
+            SpanValue::Float(value) => json!({ "doubleValue": value }),
+            SpanValue::Bool(value) => json!({ "boolValue": value }),
+        }
+    }
+}
+
+/// A closed span, ready to export
+#[derive(Debug, Clone)]
+pub struct FinishedSpan {
+    pub trace_id: u128,
+    pub span_id: u64,
+    pub parent_span_id: Option<u64>,
+    pub name: &'static str,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub attributes: Vec<(&'static str, SpanValue)>,
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default().to_string()
+}
+
+/// `ExportTraceServiceRequest` body for a batch of spans
+pub fn export_request(service_name: &str, spans: &[FinishedSpan]) -> Value {
+    let spans: Vec<Value> = spans
+        .iter()
+        .map(|span| {
+            let attributes: Vec<Value> = span
+                .attributes
+                .iter()
+                .map(|(key, value)| json!({ "key": key, "value": value.to_otlp() }))
+                .collect();
+            json!({
+                "traceId": format!("{:032x}", span.trace_id),
+                "spanId": format!("{:016x}", span.span_id),
+                "parentSpanId": span.parent_span_id.map(|id| format!("{id:016x}")).unwrap_or_default(),
+                "name": span.name,
+                // SPAN_KIND_INTERNAL
+                "kind": 1,
+                "startTimeUnixNano": unix_nanos(span.start),
+                "endTimeUnixNano": unix_nanos(span.end),
+                "attributes": attributes,
+            })
+        })
+        .collect();
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [{ "key": "service.name", "value": { "stringValue": service_name } }]
+            },
+            "scopeSpans": [{
+                "scope": { "name": "persona", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }]
+        }]
+    })
+}
+
+pub struct OtlpExporter {
+    config: OtlpConfig,
+    client: reqwest::Client,
+}
+
+impl OtlpExporter {
+    pub fn new(config: OtlpConfig) -> Self {
+        let client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build().unwrap_or_default();
+        OtlpExporter { config, client }
+    }
+
+    /// Start the export task, returning the channel finished spans are sent to.
+    /// Must be called inside a Tokio runtime.
+    pub fn spawn(self) -> mpsc::UnboundedSender<FinishedSpan> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(self.run(receiver));
+        sender
+    }
+
+    async fn run(self, mut receiver: mpsc::UnboundedReceiver<FinishedSpan>) {
+        let mut batch = Vec::new();
+        let mut interval = tokio::time::interval(OTLP_FLUSH_INTERVAL);
+        loop {
+            tokio::select! {
+                span = receiver.recv() => match span {
+                    Some(span) => {
+                        batch.push(span);
+                        if batch.len() >= OTLP_BATCH_SIZE {
+                            self.flush(&mut batch).await;
+                        }
+                    }
+                    None => {
+                        self.flush(&mut batch).await;
+                        break;
+                    }
+                },
+                _ = interval.tick() => self.flush(&mut batch).await,
+            }
+        }
+    }
+
+    async fn flush(&self, batch: &mut Vec<FinishedSpan>) {
+        if batch.is_empty() {
+            return;
+        }
+        let spans = std::mem::take(batch);
+        match self.send(&spans).await {
+            Ok(()) => debug!("Exported {} spans", spans.len()),
+            Err(e) => warn!("Failed to export {} spans to {}: {e}", spans.len(), self.config.traces_endpoint),
+        }
+    }
+
+    async fn send(&self, spans: &[FinishedSpan]) -> Result<()> {
+        let mut request = self.client.post(&self.config.traces_endpoint);
+        for (key, value) in &self.config.headers {
+            request = request.header(key.as_str(), value.as_str());
+        }
+        let response = request.json(&export_request(&self.config.service_name, spans)).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(BotError::internal(format!("collector returned {status}: {body}")));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_request() {
+        let start = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+        let span = FinishedSpan {
+            trace_id: 0xabc,
+            span_id: 0x12,
+            parent_span_id: None,
+            name: "interaction",
+            start,
+            end: start + Duration::from_millis(250),
+            attributes: vec![
+                ("command", SpanValue::Str("chat".to_string())),
+                ("input_tokens", SpanValue::Int(120)),
+                ("ok", SpanValue::Bool(true)),
+            ],
+        };
+        let child = FinishedSpan { span_id: 0x13, parent_span_id: Some(0x12), name: "db.query", ..span.clone() };
+
+        let body = export_request("persona-test", &[span, child]);
+        let resource = &body["resourceSpans"][0];
+        assert_eq!(resource["resource"]["attributes"][0]["value"]["stringValue"], "persona-test");
+        let spans = &resource["scopeSpans"][0]["spans"];
+        assert_eq!(spans[0]["traceId"], "00000000000000000000000000000abc");
+        assert_eq!(spans[0]["spanId"], "0000000000000012");
+        assert_eq!(spans[0]["parentSpanId"], "");
+        assert_eq!(spans[0]["startTimeUnixNano"], "1700000000000000000");
+        assert_eq!(spans[0]["endTimeUnixNano"], "1700000000250000000");
+        assert_eq!(spans[0]["attributes"][0]["value"]["stringValue"], "chat");
+        assert_eq!(spans[0]["attributes"][1]["value"]["intValue"], "120");
+        assert_eq!(spans[0]["attributes"][2]["value"]["boolValue"], true);
+        assert_eq!(spans[1]["parentSpanId"], "0000000000000012");
+        assert_eq!(spans[1]["traceId"], spans[0]["traceId"]);
+    }
+}
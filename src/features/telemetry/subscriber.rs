@@ -0,0 +1,381 @@
+//! # Feature: Span Tracker
+//!
+//! A `tracing` subscriber that keeps this crate's spans (interactions, LLM
+//! calls, database checkouts) and forwards every event to the `log` logger,
+//! so env_logger output is unchanged except that lines logged inside a span
+//! start with its context: `[bot_id=… guild_id=… user_id=… interaction_id=…]`.
+//! Spans from dependencies aren't tracked, though their events are logged as
+//! before. Each span gets a random 64-bit ID and inherits its parent's 128-bit
+//! trace ID; finished spans go to the OTLP exporter when one is configured.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with context-prefixed log lines and OTLP hand-off
+
+use super::otlp::{FinishedSpan, OtlpExporter, SpanValue};
+use crate::core::{BotError, OtlpConfig, Result};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::SystemTime;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_core::span::Current;
+
+/// Span fields repeated on each log line inside the span, innermost value winning
+pub const CONTEXT_FIELDS: [&str; 4] = ["bot_id", "guild_id", "user_id", "interaction_id"];
+
+/// Crate whose spans are tracked
+const SPAN_TARGET: &str = "persona";
+
+thread_local! {
+    /// Spans entered on this thread, innermost last
+    static CURRENT: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+struct SpanData {
+    metadata: &'static Metadata<'static>,
+    trace_id: u128,
+    parent: Option<u64>,
+    fields: Vec<(&'static str, SpanValue)>,
+    start: SystemTime,
+    /// Handles to the span still alive; it closes when the last one drops
+    refs: usize,
+}
+
+/// Collects span and event fields, taking an event's `message` apart
+struct FieldVisitor<'a> {
+    fields: &'a mut Vec<(&'static str, SpanValue)>,
+    message: Option<&'a mut String>,
+}
+
+impl FieldVisitor<'_> {
+    fn set(&mut self, field: &Field, value: SpanValue) {
+        match self.fields.iter_mut().find(|(name, _)| *name == field.name()) {
+            Some(existing) => existing.1 = value,
+            None => self.fields.push((field.name(), value)),
+        }
+    }
+}
+
+impl Visit for FieldVisitor<'_> {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.set(field, SpanValue::Int(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match i64::try_from(value) {
+            Ok(value) => self.set(field, SpanValue::Int(value)),
+            Err(_) => self.set(field, SpanValue::Str(value.to_string())),
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.set(field, SpanValue::Float(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.set(field, SpanValue::Bool(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.set(field, SpanValue::Str(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match (field.name(), self.message.as_deref_mut()) {
+            ("message", Some(message)) => {
+                let _ = write!(message, "{value:?}");
+            }
+            _ => self.set(field, SpanValue::Str(format!("{value:?}"))),
+        }
+    }
+}
+
+fn log_level(level: Level) -> log::Level {
+    match level {
+        Level::ERROR => log::Level::Error,
+        Level::WARN => log::Level::Warn,
+        Level::INFO => log::Level::Info,
+        Level::DEBUG => log::Level::Debug,
+        Level::TRACE => log::Level::Trace,
+    }
+}
+
+fn current() -> Option<u64> {
+    CURRENT.with(|stack| stack.borrow().last().copied())
+}
+
+fn random_span_id() -> u64 {
+    loop {
+        let id = rand::random::<u64>();
+        if id != 0 {
+            return id;
+        }
+    }
+}
+
+pub struct SpanTracker {
+    spans: Mutex<HashMap<u64, SpanData>>,
+    exporter: Option<UnboundedSender<FinishedSpan>>,
+}
+
+impl SpanTracker {
+    /// Track spans, sending each finished one to `exporter` when given
+    pub fn new(exporter: Option<UnboundedSender<FinishedSpan>>) -> Self {
+        SpanTracker { spans: Mutex::new(HashMap::new()), exporter }
+    }
+
+    fn spans(&self) -> MutexGuard<'_, HashMap<u64, SpanData>> {
+        self.spans.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// `[key=value …] ` for the context fields set on `span` or its ancestors, or empty
+    pub fn context_prefix(&self, span: Option<u64>) -> String {
+        let spans = self.spans();
+        let mut found: [Option<String>; CONTEXT_FIELDS.len()] = Default::default();
+        let mut next = span;
+        while let Some(data) = next.and_then(|id| spans.get(&id)) {
+            for (slot, key) in found.iter_mut().zip(CONTEXT_FIELDS) {
+                if slot.is_none() {
+                    *slot = data.fields.iter().find(|(name, _)| *name == key).map(|(_, value)| value.to_string());
+                }
+            }
+            next = data.parent;
+        }
+        let pairs: Vec<String> = CONTEXT_FIELDS
+            .iter()
+            .zip(found)
+            .filter_map(|(key, value)| value.map(|value| format!("{key}={value}")))
+            .collect();
+        if pairs.is_empty() {
+            String::new()
+        } else {
+            format!("[{}] ", pairs.join(" "))
+        }
+    }
+}
+
+impl Subscriber for SpanTracker {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        if metadata.is_span() {
+            let target = metadata.target();
+            return target == SPAN_TARGET || target.strip_prefix(SPAN_TARGET).is_some_and(|rest| rest.starts_with("::"));
+        }
+        let level = log_level(*metadata.level());
+        level <= log::max_level()
+            && log::logger().enabled(&log::Metadata::builder().level(level).target(metadata.target()).build())
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let parent = match attributes.parent() {
+            Some(parent) => Some(parent.into_u64()),
+            None if attributes.is_contextual() => current(),
+            None => None,
+        };
+        let mut fields = Vec::new();
+        attributes.record(&mut FieldVisitor { fields: &mut fields, message: None });
+
+        let id = random_span_id();
+        let mut spans = self.spans();
+        let trace_id = parent
+            .and_then(|parent| spans.get(&parent))
+            .map(|data| data.trace_id)
+            .unwrap_or_else(|| rand::random::<u128>().max(1));
+        spans.insert(
+            id,
+            SpanData { metadata: attributes.metadata(), trace_id, parent, fields, start: SystemTime::now(), refs: 1 },
+        );
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Some(data) = self.spans().get_mut(&span.into_u64()) {
+            values.record(&mut FieldVisitor { fields: &mut data.fields, message: None });
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let metadata = event.metadata();
+        let mut message = String::new();
+        let mut fields = Vec::new();
+        event.record(&mut FieldVisitor { fields: &mut fields, message: Some(&mut message) });
+
+        let span = match event.parent() {
+            Some(parent) => Some(parent.into_u64()),
+            None if event.is_contextual() => current(),
+            None => None,
+        };
+        let mut line = self.context_prefix(span);
+        line.push_str(&message);
+        for (key, value) in &fields {
+            let _ = write!(line, " {key}={value}");
+        }
+        log::logger().log(
+            &log::Record::builder()
+                .args(format_args!("{line}"))
+                .level(log_level(*metadata.level()))
+                .target(metadata.target())
+                .module_path(metadata.module_path())
+                .file(metadata.file())
+                .line(metadata.line())
+                .build(),
+        );
+    }
+
+    fn enter(&self, span: &Id) {
+        CURRENT.with(|stack| stack.borrow_mut().push(span.into_u64()));
+    }
+
+    fn exit(&self, span: &Id) {
+        CURRENT.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            if let Some(position) = stack.iter().rposition(|id| *id == span.into_u64()) {
+                stack.remove(position);
+            }
+        });
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Some(data) = self.spans().get_mut(&span.into_u64()) {
+            data.refs += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let id = span.into_u64();
+        let finished = {
+            let mut spans = self.spans();
+            let Some(data) = spans.get_mut(&id) else {
+                return false;
+            };
+            data.refs -= 1;
+            if data.refs > 0 {
+                return false;
+            }
+            spans.remove(&id)
+        };
+        if let (Some(data), Some(exporter)) = (finished, &self.exporter) {
+            let _ = exporter.send(FinishedSpan {
+                trace_id: data.trace_id,
+                span_id: id,
+                parent_span_id: data.parent,
+                name: data.metadata.name(),
+                start: data.start,
+                end: SystemTime::now(),
+                attributes: data.fields,
+            });
+        }
+        true
+    }
+
+    fn current_span(&self) -> Current {
+        match current().and_then(|id| self.spans().get(&id).map(|data| (id, data.metadata))) {
+            Some((id, metadata)) => Current::new(Id::from_u64(id), metadata),
+            None => Current::none(),
+        }
+    }
+}
+
+/// Install the span tracker as the global `tracing` subscriber, exporting spans over
+/// OTLP when `otlp` is given. Call after the `log` logger is set up, since events are
+/// forwarded to it, and inside a Tokio runtime when exporting.
+pub fn init_telemetry(otlp: Option<&OtlpConfig>) -> Result<()> {
+    let exporter = otlp.map(|config| OtlpExporter::new(config.clone()).spawn());
+    tracing::subscriber::set_global_default(SpanTracker::new(exporter))
+        .map_err(|e| BotError::internal(format!("A tracing subscriber is already installed: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+    use tracing::{field, info_span};
+
+    fn tracked() -> (SpanTracker, mpsc::UnboundedReceiver<FinishedSpan>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (SpanTracker::new(Some(sender)), receiver)
+    }
+
+    #[test]
+    fn test_children_share_the_trace_and_close_first() {
+        let (tracker, mut finished) = tracked();
+        let dispatch = tracing::Dispatch::new(tracker);
+        tracing::dispatcher::with_default(&dispatch, || {
+            let interaction = info_span!("interaction", interaction_id = 42u64, command = "chat", tokens = field::Empty);
+            interaction.in_scope(|| {
+                let query = info_span!("db.query", line = 120);
+                drop(query);
+            });
+            interaction.record("tokens", 300);
+        });
+
+        let query = finished.try_recv().unwrap();
+        let interaction = finished.try_recv().unwrap();
+        assert_eq!(query.name, "db.query");
+        assert_eq!(query.parent_span_id, Some(interaction.span_id));
+        assert_eq!(query.trace_id, interaction.trace_id);
+        assert_eq!(interaction.parent_span_id, None);
+        assert_eq!(
+            interaction.attributes,
+            vec![
+                ("interaction_id", SpanValue::Int(42)),
+                ("command", SpanValue::Str("chat".to_string())),
+                ("tokens", SpanValue::Int(300)),
+            ]
+        );
+        assert!(finished.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_separate_roots_get_separate_traces() {
+        let (tracker, mut finished) = tracked();
+        tracing::subscriber::with_default(tracker, || {
+            drop(info_span!("interaction"));
+            drop(info_span!("interaction"));
+        });
+        let (a, b) = (finished.try_recv().unwrap(), finished.try_recv().unwrap());
+        assert_ne!(a.trace_id, b.trace_id);
+        assert_ne!(a.span_id, b.span_id);
+    }
+
+    #[test]
+    fn test_context_prefix_prefers_innermost_values() {
+        let tracker = SpanTracker::new(None);
+        let outer = vec![
+            ("bot_id", SpanValue::Str("1".to_string())),
+            ("guild_id", SpanValue::Str("2".to_string())),
+            ("command", SpanValue::Str("chat".to_string())),
+        ];
+        let inner = vec![("user_id", SpanValue::Str("3".to_string())), ("guild_id", SpanValue::Str("9".to_string()))];
+        let metadata = info_span!("interaction").metadata().unwrap();
+        {
+            let mut spans = tracker.spans();
+            let span = |parent, fields| SpanData { metadata, trace_id: 1, parent, fields, start: SystemTime::now(), refs: 1 };
+            spans.insert(10, span(None, outer));
+            spans.insert(11, span(Some(10), inner));
+        }
+
+        assert_eq!(tracker.context_prefix(Some(11)), "[bot_id=1 guild_id=9 user_id=3] ");
+        assert_eq!(tracker.context_prefix(Some(10)), "[bot_id=1 guild_id=2] ");
+        assert_eq!(tracker.context_prefix(None), "");
+    }
+
+    #[test]
+    fn test_only_this_crates_spans_are_tracked() {
+        let tracker = SpanTracker::new(None);
+        tracing::subscriber::with_default(tracker, || {
+            assert!(info_span!(target: "serenity::gateway", "shard").is_disabled());
+            assert!(!info_span!("interaction").is_disabled());
+        });
+    }
+}
@@ -0,0 +1,216 @@
+//! # Feature: Built-in Chat Tools
+//!
+//! Tools backed by the bot's own data: the current time in the user's zone,
+//! creating a reminder, and the user's recent API usage. Arguments come from
+//! the model, so each one is validated and errors are reported as text.
+//!
+//! - **Version**: 1.0.1
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.1: Time zones come from the shared `reminders::user_timezone` lookup
+//! - 1.0.0: Initial release with `current_time`, `create_reminder` and `usage_stats`
+
+use super::registry::{Tool, ToolContext};
+use crate::database::Database;
+use crate::features::llm::FunctionDefinition;
+use crate::features::reminders::timezone::STORED_TIME_FORMAT;
+use crate::features::reminders::{parse_reminder_time, user_timezone, UserTimezone};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde_json::{json, Value};
+use serenity::async_trait;
+
+const CURRENT_TIME: &str = "current_time";
+const CREATE_REMINDER: &str = "create_reminder";
+const USAGE_STATS: &str = "usage_stats";
+
+/// Longest reminder text accepted from the model
+const MAX_REMINDER_CHARS: usize = 500;
+
+/// Tools reading and writing the bot's database
+#[derive(Clone)]
+pub struct BuiltinTools {
+    database: Database,
+}
+
+impl BuiltinTools {
+    pub fn new(database: Database) -> Self {
+        BuiltinTools { database }
+    }
+
+    async fn create_reminder(&self, args: &Value, context: &ToolContext) -> String {
+        let (Some(user_id), Some(channel_id)) = (&context.user_id, &context.channel_id) else {
+            return "Error: reminders can only be created in a conversation with a user".to_string();
+        };
+        let (Some(time), Some(message)) = (args.get("time").and_then(Value::as_str), args.get("message").and_then(Value::as_str)) else {
+            return "Error: `time` and `message` are required".to_string();
+        };
+        let message = message.trim();
+        if message.is_empty() || message.chars().count() > MAX_REMINDER_CHARS {
+            return format!("Error: `message` must be 1-{MAX_REMINDER_CHARS} characters");
+        }
+
+        let timezone = user_timezone(&self.database, user_id).await;
+        let now = Utc::now();
+        let Some(remind_at) = parse_reminder_time(time, now, &timezone) else {
+            return "Error: invalid time. Use a duration like `30m` or `2h`, or a time like `9am` or `tomorrow 14:30`".to_string();
+        };
+        let stored = remind_at.format(STORED_TIME_FORMAT).to_string();
+        let result = self
+            .database
+            .add_reminder(user_id, channel_id, context.guild_id.as_deref(), message, &stored, None, None, false)
+            .await;
+        match result {
+            Ok(id) => {
+                info!("Chat tool created reminder {id} for user {user_id} at {stored}");
+                if let Err(e) = self.database.log_usage(user_id, "remind", None).await {
+                    warn!("Failed to log reminder usage: {e}");
+                }
+                format!("Reminder #{id} set for {} about: {message}", timezone.format(remind_at))
+            }
+            Err(e) => {
+                warn!("Chat tool failed to create reminder: {e}");
+                "Error: the reminder could not be saved".to_string()
+            }
+        }
+    }
+
+    async fn usage_stats(&self, args: &Value, context: &ToolContext) -> String {
+        let Some(user_id) = &context.user_id else {
+            return "Error: usage stats need a user".to_string();
+        };
+        let days = args.get("days").and_then(Value::as_i64).unwrap_or(30).clamp(1, 365);
+        match self.database.get_user_usage_stats(user_id, days).await {
+            Ok(rows) => format_usage(&rows, days),
+            Err(e) => {
+                warn!("Chat tool failed to load usage for {user_id}: {e}");
+                "Error: usage stats are unavailable right now".to_string()
+            }
+        }
+    }
+}
+
+/// Current time for the model, e.g. `Thu 2026-10-15 09:00 (Europe/Berlin), Europe/Berlin (UTC+02:00)`
+fn current_time(now: DateTime<Utc>, timezone: &UserTimezone) -> String {
+    format!("{}, time zone {}", timezone.format(now), timezone.describe(now))
+}
+
+/// Summarize `(service, requests, tokens, audio_seconds, images, cost)` rows
+fn format_usage(rows: &[(String, i64, i64, f64, i64, f64)], days: i64) -> String {
+    if rows.is_empty() {
+        return format!("No usage recorded in the last {days} days");
+    }
+    let mut lines = vec![format!("Usage in the last {days} days:")];
+    for (service, requests, tokens, audio_seconds, images, cost) in rows {
+        let mut detail = format!("{service}: {requests} requests");
+        if *tokens > 0 {
+            detail.push_str(&format!(", {tokens} tokens"));
+        }
+        if *audio_seconds > 0.0 {
+            detail.push_str(&format!(", {:.1} audio minutes", audio_seconds / 60.0));
+        }
+        if *images > 0 {
+            detail.push_str(&format!(", {images} images"));
+        }
+        detail.push_str(&format!(", ${cost:.4}"));
+        lines.push(detail);
+    }
+    let total: f64 = rows.iter().map(|row| row.5).sum();
+    lines.push(format!("Total cost: ${total:.4}"));
+    lines.join("\n")
+}
+
+#[async_trait]
+impl Tool for BuiltinTools {
+    fn definitions(&self) -> Vec<FunctionDefinition> {
+        vec![
+            FunctionDefinition {
+                name: CURRENT_TIME.to_string(),
+                description: "Get the current date and time, in the user's time zone unless another zone is given."
+                    .to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "timezone": { "type": "string", "description": "Optional IANA zone (e.g. Asia/Tokyo) or UTC offset (e.g. +5:30)" }
+                    }
+                }),
+            },
+            FunctionDefinition {
+                name: CREATE_REMINDER.to_string(),
+                description: "Set a reminder for the user in the current channel. Only use this when the user asks to be reminded."
+                    .to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "time": { "type": "string", "description": "A duration like 30m or 1h30m, or a time in the user's zone like 9am, tomorrow 14:30 or friday 5pm" },
+                        "message": { "type": "string", "description": "What to remind the user about" }
+                    },
+                    "required": ["time", "message"]
+                }),
+            },
+            FunctionDefinition {
+                name: USAGE_STATS.to_string(),
+                description: "Look up the user's own AI usage (requests, tokens and cost) over recent days.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "days": { "type": "integer", "description": "How many days back to look (1-365, default 30)" }
+                    }
+                }),
+            },
+        ]
+    }
+
+    async fn run(&self, name: &str, arguments: &str, context: &ToolContext) -> String {
+        let args: Value = serde_json::from_str(arguments).unwrap_or(Value::Null);
+        match name {
+            CURRENT_TIME => {
+                let timezone = match args.get("timezone").and_then(Value::as_str) {
+                    Some(zone) => match UserTimezone::parse(zone) {
+                        Some(timezone) => timezone,
+                        None => return format!("Error: unknown time zone `{zone}`"),
+                    },
+                    None => match &context.user_id {
+                        Some(user_id) => user_timezone(&self.database, user_id).await,
+                        None => UserTimezone::default(),
+                    },
+                };
+                current_time(Utc::now(), &timezone)
+            }
+            CREATE_REMINDER => self.create_reminder(&args, context).await,
+            USAGE_STATS => self.usage_stats(&args, context).await,
+            other => format!("Error: unknown tool `{other}`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_current_time() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 7, 0, 0).unwrap();
+        let berlin = UserTimezone::parse("Europe/Berlin").unwrap();
+        assert_eq!(
+            current_time(now, &berlin),
+            "Thu 2026-10-15 09:00 (Europe/Berlin), time zone Europe/Berlin (UTC+02:00)"
+        );
+    }
+
+    #[test]
+    fn test_format_usage() {
+        assert_eq!(format_usage(&[], 7), "No usage recorded in the last 7 days");
+        let rows = vec![
+            ("chat".to_string(), 12, 3400, 0.0, 0, 0.0123),
+            ("whisper".to_string(), 2, 0, 90.0, 0, 0.009),
+        ];
+        let summary = format_usage(&rows, 30);
+        assert!(summary.contains("chat: 12 requests, 3400 tokens, $0.0123"));
+        assert!(summary.contains("whisper: 2 requests, 1.5 audio minutes, $0.0090"));
+        assert!(summary.ends_with("Total cost: $0.0213"));
+    }
+}
@@ -0,0 +1,18 @@
+//! # Tools Feature
+//!
+//! Function calling in the chat pipeline: a [`ToolRegistry`] of tools the
+//! persona can call mid-conversation (calculator, current time, reminders,
+//! usage stats and web fetch), run over multiple rounds before it answers.
+//! Each call is logged in `usage_stats` as `tool_<name>`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod builtin;
+pub mod registry;
+pub mod web_fetch;
+
+pub use builtin::BuiltinTools;
+pub use registry::{Tool, ToolContext, ToolRegistry, MAX_TOOL_ROUNDS};
+pub use web_fetch::WebFetchTool;
@@ -0,0 +1,111 @@
+//! # Feature: Tool Registry
+//!
+//! Tools the chat model can call during a conversation. Each [`Tool`] offers
+//! one or more functions; the [`ToolRegistry`] collects their definitions for
+//! the chat request and routes the model's calls back to the right tool.
+//! Results are always text for the model to phrase, including errors, so a
+//! failed tool never fails the whole reply.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: `request_id` in [`ToolContext`]
+//! - 1.0.0: Initial release
+
+use crate::features::llm::FunctionDefinition;
+use serenity::async_trait;
+use std::sync::Arc;
+
+/// Most tool round-trips allowed before the model must answer
+pub const MAX_TOOL_ROUNDS: usize = 5;
+
+/// Who and where a tool is being called for
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolContext {
+    pub user_id: Option<String>,
+    pub guild_id: Option<String>,
+    pub channel_id: Option<String>,
+    /// Request the reply is written for, so tools can attach things to it
+    pub request_id: Option<String>,
+}
+
+/// A set of functions the model may call
+#[async_trait]
+pub trait Tool: Send + Sync {
+    /// Definitions of the functions this tool offers
+    fn definitions(&self) -> Vec<FunctionDefinition>;
+
+    /// Run one of this tool's functions with JSON-encoded arguments and return the result
+    async fn run(&self, name: &str, arguments: &str, context: &ToolContext) -> String;
+}
+
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<Arc<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tool; a function name already offered by an earlier tool stays with that tool
+    pub fn with_tool(mut self, tool: Arc<dyn Tool>) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    /// Function definitions to attach to a chat completion request
+    pub fn definitions(&self) -> Vec<FunctionDefinition> {
+        let mut definitions: Vec<FunctionDefinition> = Vec::new();
+        for definition in self.tools.iter().flat_map(|tool| tool.definitions()) {
+            if !definitions.iter().any(|d| d.name == definition.name) {
+                definitions.push(definition);
+            }
+        }
+        definitions
+    }
+
+    /// Run a function call from the model and return the text result
+    pub async fn call(&self, name: &str, arguments: &str, context: &ToolContext) -> String {
+        let tool = self
+            .tools
+            .iter()
+            .find(|tool| tool.definitions().iter().any(|d| d.name == name));
+        match tool {
+            Some(tool) => tool.run(name, arguments, context).await,
+            None => format!("Error: unknown tool `{name}`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Echo(&'static str);
+
+    #[async_trait]
+    impl Tool for Echo {
+        fn definitions(&self) -> Vec<FunctionDefinition> {
+            vec![FunctionDefinition { name: "echo".to_string(), description: self.0.to_string(), parameters: json!({}) }]
+        }
+
+        async fn run(&self, _name: &str, arguments: &str, context: &ToolContext) -> String {
+            format!("{} {} {}", self.0, arguments, context.user_id.as_deref().unwrap_or("-"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_routes_calls() {
+        let registry = ToolRegistry::new().with_tool(Arc::new(Echo("first"))).with_tool(Arc::new(Echo("second")));
+        assert_eq!(registry.definitions().len(), 1);
+
+        let context = ToolContext { user_id: Some("42".to_string()), ..Default::default() };
+        assert_eq!(registry.call("echo", "{}", &context).await, "first {} 42");
+        assert_eq!(registry.call("missing", "{}", &context).await, "Error: unknown tool `missing`");
+    }
+}
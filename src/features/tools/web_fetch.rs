@@ -0,0 +1,247 @@
+//! # Feature: Web Fetch Tool
+//!
+//! Lets the chat model read a public web page. Only http(s) URLs on the default
+//! ports are fetched, every resolved address must be public (no loopback,
+//! private, link-local or metadata ranges), the connection is pinned to the
+//! checked address, and redirects are reported instead of followed. HTML is
+//! reduced to plain text and truncated before it reaches the model.
+//!
+//! - **Version**: 1.0.1
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.1: Page text is cut with the shared `discord_limits::truncate`
+//! - 1.0.0: Initial release
+
+use super::registry::{Tool, ToolContext};
+use crate::core::discord_limits::truncate;
+use crate::features::llm::FunctionDefinition;
+use log::{info, warn};
+use serde_json::{json, Value};
+use serenity::async_trait;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+const WEB_FETCH: &str = "web_fetch";
+
+/// Bytes read from a response at most
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Characters of page text returned to the model
+const MAX_TEXT_CHARS: usize = 4000;
+
+#[derive(Clone, Default)]
+pub struct WebFetchTool;
+
+impl WebFetchTool {
+    pub fn new() -> Self {
+        WebFetchTool
+    }
+
+    async fn fetch(&self, url: &str) -> Result<String, String> {
+        let parsed = reqwest::Url::parse(url).map_err(|_| "invalid URL".to_string())?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err("only http and https URLs can be fetched".to_string());
+        }
+        if parsed.port().is_some() || !parsed.username().is_empty() || parsed.password().is_some() {
+            return Err("URLs with ports or credentials can't be fetched".to_string());
+        }
+        let host = parsed.host_str().ok_or_else(|| "URL has no host".to_string())?.to_string();
+        let port = parsed.port_or_known_default().unwrap_or(443);
+
+        let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+            .await
+            .map_err(|_| format!("could not resolve {host}"))?
+            .collect();
+        let Some(address) = addresses.first().copied() else {
+            return Err(format!("could not resolve {host}"));
+        };
+        if !addresses.iter().all(|a| is_public_ip(a.ip())) {
+            return Err(format!("{host} is not a public address"));
+        }
+
+        // Pin the checked address so a second lookup can't point somewhere else
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve(&host, address)
+            .user_agent(concat!("persona-bot/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| format!("could not build HTTP client: {e}"))?;
+        let mut response = client.get(parsed).send().await.map_err(|e| format!("request failed: {e}"))?;
+
+        let status = response.status();
+        if status.is_redirection() {
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|l| l.to_str().ok())
+                .unwrap_or("an unknown location");
+            return Err(format!("the page redirects to {location}; fetch that URL instead"));
+        }
+        if !status.is_success() {
+            return Err(format!("HTTP {status}"));
+        }
+        let is_html = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|c| c.to_str().ok())
+            .is_some_and(|c| c.contains("html"));
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| format!("read failed: {e}"))? {
+            body.extend_from_slice(&chunk);
+            if body.len() >= MAX_BODY_BYTES {
+                body.truncate(MAX_BODY_BYTES);
+                break;
+            }
+        }
+        let text = String::from_utf8_lossy(&body);
+        let text = if is_html { html_to_text(&text) } else { collapse_whitespace(&text) };
+        Ok(truncate(&text, MAX_TEXT_CHARS))
+    }
+}
+
+/// Whether an address is on the public internet
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || v4.is_multicast()
+                || a == 0
+                // Carrier-grade NAT 100.64.0.0/10
+                || (a == 100 && (64..128).contains(&b))
+                // Benchmarking 198.18.0.0/15
+                || (a == 198 && (18..20).contains(&b))
+                || a >= 240)
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                // Unique local fc00::/7 and link-local fe80::/10
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Reduce HTML to readable text: drop scripts, styles and tags, decode common entities
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len() / 2);
+    let lower = html.to_ascii_lowercase();
+    let mut i = 0;
+    while i < html.len() {
+        if html[i..].starts_with('<') {
+            // Skip the contents of script and style elements entirely
+            let skipped = ["script", "style", "noscript"].iter().find_map(|tag| {
+                lower[i..].starts_with(&format!("<{tag}")).then(|| {
+                    let close = format!("</{tag}");
+                    lower[i..].find(&close).map(|end| i + end + close.len())
+                })?
+            });
+            let resume = skipped.unwrap_or(i);
+            match html[resume..].find('>') {
+                Some(end) => {
+                    i = resume + end + 1;
+                    text.push(' ');
+                }
+                None => break,
+            }
+        } else {
+            let next = html[i..].find('<').map(|n| i + n).unwrap_or(html.len());
+            text.push_str(&html[i..next]);
+            i = next;
+        }
+    }
+    let decoded = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    collapse_whitespace(&decoded)
+}
+
+#[async_trait]
+impl Tool for WebFetchTool {
+    fn definitions(&self) -> Vec<FunctionDefinition> {
+        vec![FunctionDefinition {
+            name: WEB_FETCH.to_string(),
+            description: "Fetch a public web page and return its text. Use it when the user shares a link or asks about a specific page."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "url": { "type": "string", "description": "Full http or https URL" }
+                },
+                "required": ["url"]
+            }),
+        }]
+    }
+
+    async fn run(&self, _name: &str, arguments: &str, _context: &ToolContext) -> String {
+        let args: Value = serde_json::from_str(arguments).unwrap_or(Value::Null);
+        let Some(url) = args.get("url").and_then(Value::as_str) else {
+            return "Error: missing `url` argument".to_string();
+        };
+        match self.fetch(url).await {
+            Ok(text) => {
+                info!("Chat tool fetched {url} ({} chars)", text.chars().count());
+                text
+            }
+            Err(e) => {
+                warn!("Chat tool could not fetch {url}: {e}");
+                format!("Error: {e}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public_ip() {
+        for blocked in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(!is_public_ip(blocked.parse().unwrap()), "{blocked} should be blocked");
+        }
+        for allowed in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(allowed.parse().unwrap()), "{allowed} should be allowed");
+        }
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        let html = "<html><head><style>p { color: red; }</style><script>alert('<b>')</script></head>\
+                    <body><h1>Title</h1><p>Fish &amp; chips&nbsp;&lt;3</p></body></html>";
+        assert_eq!(html_to_text(html), "Title Fish & chips <3");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_private_and_non_http_urls() {
+        let tool = WebFetchTool::new();
+        let context = ToolContext::default();
+        assert!(tool.run(WEB_FETCH, r#"{"url": "http://127.0.0.1/admin"}"#, &context).await.contains("not a public address"));
+        assert!(tool.run(WEB_FETCH, r#"{"url": "file:///etc/passwd"}"#, &context).await.contains("only http"));
+        assert!(tool.run(WEB_FETCH, r#"{"url": "http://example.com:8080/"}"#, &context).await.contains("ports"));
+        assert!(tool.run(WEB_FETCH, "{}", &context).await.contains("missing"));
+    }
+}
@@ -0,0 +1,12 @@
+//! # User Names Feature
+//!
+//! Cached resolution of user IDs to server display names and avatars for
+//! reports, embeds and AI prompts, with fallbacks for members who have left.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod resolver;
+
+pub use resolver::{Membership, NameResolver, ResolvedUser};
@@ -0,0 +1,257 @@
+//! # Feature: Display Name Resolver
+//!
+//! Turns user IDs into readable names for reports and prompts. Prefers the
+//! member's server nickname, falls back to the global user for people who have
+//! left, and to a short placeholder when Discord doesn't know the ID at all.
+//! Results are cached per guild so repeated reports don't refetch, up to a
+//! configurable number of entries.
+//!
+//! - **Version**: 1.1.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.1.0: Cache size cap (`NAME_CACHE_ENTRIES`)
+//! - 1.0.0: Initial release with member/user fallbacks and a shared cache
+
+use dashmap::DashMap;
+use log::debug;
+use serenity::http::Http;
+use serenity::model::id::{GuildId, UserId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+/// How long a resolved name is reused
+const CACHE_TTL: Duration = Duration::from_secs(3600);
+
+/// How long a failed lookup is remembered before retrying
+const MISS_TTL: Duration = Duration::from_secs(600);
+
+/// Cached names kept by [`NameResolver::new`]
+const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// Lookups run concurrently in batches of this size to stay clear of rate limits
+const FETCH_BATCH: usize = 5;
+
+/// Whether the user is still in the guild the name was resolved for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Membership {
+    Member,
+    /// Known Discord user, no longer (or never) in the guild
+    NotMember,
+    /// Discord returned nothing for the ID
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedUser {
+    pub id: u64,
+    /// Server nickname, or username when there is none
+    pub display_name: String,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub membership: Membership,
+}
+
+impl ResolvedUser {
+    fn unknown(id: u64) -> Self {
+        let short = id.to_string();
+        let short = &short[short.len().saturating_sub(4)..];
+        ResolvedUser {
+            id,
+            display_name: format!("Unknown user …{short}"),
+            username: String::new(),
+            avatar_url: None,
+            membership: Membership::Unknown,
+        }
+    }
+
+    /// Name for reports, marking people who have left the server
+    pub fn label(&self) -> String {
+        match self.membership {
+            Membership::NotMember => format!("{} (left)", self.display_name),
+            _ => self.display_name.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NameResolver {
+    /// (guild or 0 for DMs, user) -> when resolved and the result
+    cache: Arc<DashMap<(u64, u64), (Instant, ResolvedUser)>>,
+    max_entries: usize,
+}
+
+impl Default for NameResolver {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_MAX_ENTRIES)
+    }
+}
+
+impl NameResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A resolver that keeps at most `max_entries` names, dropping the oldest first
+    pub fn with_capacity(max_entries: usize) -> Self {
+        NameResolver { cache: Arc::new(DashMap::new()), max_entries }
+    }
+
+    /// Drop expired entries, then the oldest ones while over capacity
+    fn trim(&self) {
+        self.cache.retain(|_, (at, _)| at.elapsed() < CACHE_TTL);
+        let excess = self.cache.len().saturating_sub(self.max_entries);
+        if excess > 0 {
+            let mut entries: Vec<((u64, u64), Instant)> = self.cache.iter().map(|e| (*e.key(), e.value().0)).collect();
+            entries.sort_by_key(|(_, at)| *at);
+            for (key, _) in entries.into_iter().take(excess) {
+                self.cache.remove(&key);
+            }
+        }
+    }
+
+    fn cached(&self, key: (u64, u64)) -> Option<ResolvedUser> {
+        let entry = self.cache.get(&key)?;
+        let (at, user) = entry.value();
+        let ttl = if user.membership == Membership::Unknown { MISS_TTL } else { CACHE_TTL };
+        (at.elapsed() < ttl).then(|| user.clone())
+    }
+
+    /// Resolve one user, in the context of a guild when given
+    pub async fn resolve(&self, http: &Arc<Http>, guild_id: Option<GuildId>, user_id: UserId) -> ResolvedUser {
+        let mut resolved = self.resolve_many(http, guild_id, &[user_id]).await;
+        resolved.remove(&user_id.0).unwrap_or_else(|| ResolvedUser::unknown(user_id.0))
+    }
+
+    /// Resolve several users, fetching uncached ones concurrently. Every requested ID is in the result.
+    pub async fn resolve_many(
+        &self,
+        http: &Arc<Http>,
+        guild_id: Option<GuildId>,
+        user_ids: &[UserId],
+    ) -> HashMap<u64, ResolvedUser> {
+        let guild_key = guild_id.map_or(0, |g| g.0);
+        let mut resolved = HashMap::new();
+        let mut missing = Vec::new();
+        for user_id in user_ids {
+            match self.cached((guild_key, user_id.0)) {
+                Some(user) => {
+                    resolved.insert(user_id.0, user);
+                }
+                None if !missing.contains(user_id) => missing.push(*user_id),
+                None => {}
+            }
+        }
+
+        for batch in missing.chunks(FETCH_BATCH) {
+            let mut lookups = JoinSet::new();
+            for &user_id in batch {
+                let http = http.clone();
+                lookups.spawn(async move { fetch_user(&http, guild_id, user_id).await });
+            }
+            while let Some(result) = lookups.join_next().await {
+                if let Ok(user) = result {
+                    self.cache.insert((guild_key, user.id), (Instant::now(), user.clone()));
+                    resolved.insert(user.id, user);
+                }
+            }
+        }
+
+        self.trim();
+        resolved
+    }
+
+    /// Display labels keyed by the string IDs stored in the database; unparseable IDs map to themselves
+    pub async fn labels(&self, http: &Arc<Http>, guild_id: Option<GuildId>, user_ids: &[String]) -> HashMap<String, String> {
+        let ids: Vec<UserId> = user_ids.iter().filter_map(|id| id.parse().ok()).map(UserId).collect();
+        let resolved = self.resolve_many(http, guild_id, &ids).await;
+        user_ids
+            .iter()
+            .map(|id| {
+                let label = id
+                    .parse::<u64>()
+                    .ok()
+                    .and_then(|n| resolved.get(&n))
+                    .map_or_else(|| id.clone(), ResolvedUser::label);
+                (id.clone(), label)
+            })
+            .collect()
+    }
+}
+
+async fn fetch_user(http: &Http, guild_id: Option<GuildId>, user_id: UserId) -> ResolvedUser {
+    if let Some(guild_id) = guild_id {
+        match guild_id.member(http, user_id).await {
+            Ok(member) => {
+                return ResolvedUser {
+                    id: user_id.0,
+                    display_name: member.display_name().into_owned(),
+                    username: member.user.name.clone(),
+                    avatar_url: member.avatar_url().or_else(|| Some(member.user.face())),
+                    membership: Membership::Member,
+                };
+            }
+            Err(e) => debug!("Member {user_id} not found in guild {guild_id}: {e}"),
+        }
+    }
+    match user_id.to_user(http).await {
+        Ok(user) => ResolvedUser {
+            id: user_id.0,
+            display_name: user.name.clone(),
+            username: user.name.clone(),
+            avatar_url: Some(user.face()),
+            membership: if guild_id.is_some() { Membership::NotMember } else { Membership::Member },
+        },
+        Err(e) => {
+            debug!("User {user_id} not found: {e}");
+            ResolvedUser::unknown(user_id.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels() {
+        let mut user = ResolvedUser {
+            id: 1,
+            display_name: "Ana".to_string(),
+            username: "ana".to_string(),
+            avatar_url: None,
+            membership: Membership::Member,
+        };
+        assert_eq!(user.label(), "Ana");
+        user.membership = Membership::NotMember;
+        assert_eq!(user.label(), "Ana (left)");
+        assert_eq!(ResolvedUser::unknown(123456789).label(), "Unknown user …6789");
+    }
+
+    #[test]
+    fn test_cache_expiry() {
+        let resolver = NameResolver::new();
+        let stale = Instant::now().checked_sub(MISS_TTL + Duration::from_secs(1)).unwrap();
+        resolver.cache.insert((0, 5), (stale, ResolvedUser::unknown(5)));
+        assert!(resolver.cached((0, 5)).is_none());
+
+        let known = ResolvedUser { membership: Membership::Member, ..ResolvedUser::unknown(6) };
+        resolver.cache.insert((0, 6), (stale, known.clone()));
+        assert_eq!(resolver.cached((0, 6)), Some(known));
+    }
+
+    #[test]
+    fn test_cache_capacity() {
+        let resolver = NameResolver::with_capacity(2);
+        let now = Instant::now();
+        for (id, age) in [(1, 30), (2, 10), (3, 20)] {
+            resolver.cache.insert((0, id), (now - Duration::from_secs(age), ResolvedUser::unknown(id)));
+        }
+        resolver.trim();
+        assert_eq!(resolver.cache.len(), 2);
+        assert!(!resolver.cache.contains_key(&(0, 1)));
+    }
+}
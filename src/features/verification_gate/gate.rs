@@ -0,0 +1,248 @@
+//! # Feature: Verification Gate
+//!
+//! Every new member gets a prompt in the `verification_channel` with a Verify
+//! button. In `question` mode they must also answer a simple AI-generated
+//! question (with a built-in fallback list) before `member_role` is granted.
+//! Members who haven't verified within `verification_timeout_minutes` are kicked.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with button and question modes and timeout kicks
+
+use crate::database::{Database, VerificationGate};
+use crate::features::moderation::post_mod_log;
+use crate::core::Result;
+use log::{info, warn};
+use rand::seq::IndexedRandom;
+use serde::Deserialize;
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Verify button on the gate prompt: `gateverify:<gate_id>`
+pub const GATE_VERIFY_PREFIX: &str = "gateverify:";
+/// Answer modal: `gatemodal:<gate_id>`
+pub const GATE_MODAL_PREFIX: &str = "gatemodal:";
+
+/// Minutes before an unverified member is kicked when the setting is unset (0 = never)
+pub const DEFAULT_GATE_TIMEOUT_MINUTES: i64 = 60;
+
+/// Longest accepted generated question (it doubles as the modal placeholder)
+pub const MAX_QUESTION_CHARS: usize = 100;
+
+/// How often expired gates are checked
+const KICK_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Prompt used to generate a gate question
+pub const QUESTION_PROMPT: &str = "You write verification questions for a Discord server. \
+Write ONE very simple question any human can answer in one or two words, such as basic \
+arithmetic or common knowledge about colors and animals. Avoid trick questions and anything \
+cultural or regional. Reply with only JSON: {\"question\": \"...\", \"answers\": [\"...\"]} \
+listing every reasonable accepted answer in lowercase (e.g. both \"4\" and \"four\").";
+
+/// Used when generation fails or returns something unusable
+const FALLBACK_QUESTIONS: &[(&str, &[&str])] = &[
+    ("What color is the sky on a clear day?", &["blue", "light blue"]),
+    ("How many legs does a cat have?", &["4", "four"]),
+    ("What is 3 + 4?", &["7", "seven"]),
+    ("Which animal says \"moo\"?", &["cow", "cows"]),
+    ("Which is colder: ice or fire?", &["ice"]),
+    ("How many days are in a week?", &["7", "seven"]),
+];
+
+/// How new members are gated (`verification_gate` setting)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GateMode {
+    Disabled,
+    /// Press Verify
+    Button,
+    /// Press Verify, then answer a question
+    Question,
+}
+
+impl GateMode {
+    pub fn from_setting(value: Option<&str>) -> Self {
+        match value {
+            Some("button") => GateMode::Button,
+            Some("question") => GateMode::Question,
+            _ => GateMode::Disabled,
+        }
+    }
+}
+
+/// A question and its accepted answers
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GateQuestion {
+    pub question: String,
+    pub answers: Vec<String>,
+}
+
+/// Parse the model's JSON reply, rejecting empty or overlong questions
+pub fn parse_generated_question(response: &str) -> Option<GateQuestion> {
+    let json = response
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    let mut parsed: GateQuestion = serde_json::from_str(json).ok()?;
+    parsed.question = parsed.question.trim().to_string();
+    parsed.answers = parsed
+        .answers
+        .iter()
+        .map(|a| normalize_answer(a))
+        .filter(|a| !a.is_empty())
+        .collect();
+    if parsed.question.is_empty() || parsed.question.chars().count() > MAX_QUESTION_CHARS || parsed.answers.is_empty() {
+        return None;
+    }
+    Some(parsed)
+}
+
+/// A random built-in question
+pub fn fallback_question() -> GateQuestion {
+    let (question, answers) = FALLBACK_QUESTIONS.choose(&mut rand::rng()).unwrap_or(&FALLBACK_QUESTIONS[0]);
+    GateQuestion {
+        question: question.to_string(),
+        answers: answers.iter().map(|a| a.to_string()).collect(),
+    }
+}
+
+/// Lowercase, drop punctuation and a leading article
+fn normalize_answer(answer: &str) -> String {
+    let cleaned: String = answer
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect();
+    let words: Vec<&str> = cleaned.split_whitespace().collect();
+    let words = match words.first() {
+        Some(&"a") | Some(&"an") | Some(&"the") if words.len() > 1 => &words[1..],
+        _ => &words[..],
+    };
+    words.join(" ")
+}
+
+/// Whether a member's answer matches one of the accepted answers
+pub fn check_answer(given: &str, answers: &[String]) -> bool {
+    let given = normalize_answer(given);
+    !given.is_empty() && answers.iter().any(|a| normalize_answer(a) == given)
+}
+
+/// Gate prompt posted for a new member
+pub fn format_gate_prompt(user_id: &str, question: Option<&str>, timeout_minutes: i64) -> String {
+    let step = match question {
+        Some(question) => format!("press **Verify** and answer this question: *{question}*"),
+        None => "press **Verify** below".to_string(),
+    };
+    let deadline = if timeout_minutes > 0 {
+        format!(" within {timeout_minutes} minutes, or you'll be removed from the server")
+    } else {
+        String::new()
+    };
+    format!("👋 Welcome <@{user_id}>! To get access, {step}{deadline}.")
+}
+
+async fn kick_unverified(http: &Http, db: &Database, gate: &VerificationGate) -> Result<()> {
+    let guild = GuildId(gate.guild_id.parse::<u64>()?);
+    let user = UserId(gate.user_id.parse::<u64>()?);
+    // Claim the gate first so a late Verify press can't race the kick
+    if !db.resolve_verification_gate(gate.id, "kicked").await? {
+        return Ok(());
+    }
+    guild.kick_with_reason(http, user, "Did not complete verification in time").await?;
+
+    if let (Ok(channel), Ok(message)) = (gate.channel_id.parse::<u64>(), gate.message_id.parse::<u64>()) {
+        if let Err(e) = ChannelId(channel).delete_message(http, MessageId(message)).await {
+            warn!("Failed to delete verification prompt {message}: {e}");
+        }
+    }
+    info!("⏰ Kicked unverified member {} from guild {}", gate.user_id, gate.guild_id);
+    let text = format!("⏰ <@{}> was kicked for not completing verification in time.", gate.user_id);
+    if let Err(e) = post_mod_log(http, db, &gate.guild_id, &text).await {
+        warn!("Failed to post verification kick to mod log for guild {}: {e}", gate.guild_id);
+    }
+    Ok(())
+}
+
+/// Background task that kicks members whose verification deadline has passed
+pub async fn gate_kick_loop(http: Arc<Http>, db: Arc<Database>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(KICK_CHECK_INTERVAL_SECS));
+    info!("Verification gate task started (checks every {KICK_CHECK_INTERVAL_SECS}s)");
+
+    loop {
+        interval.tick().await;
+        let expired = match db.get_expired_verification_gates().await {
+            Ok(expired) => expired,
+            Err(e) => {
+                warn!("Failed to load expired verification gates: {}", e);
+                continue;
+            }
+        };
+
+        for gate in expired {
+            if let Err(e) = kick_unverified(&http, &db, &gate).await {
+                // Usually the member already left; the gate is closed either way
+                warn!("Failed to kick unverified member {} from guild {}: {}", gate.user_id, gate.guild_id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gate_mode() {
+        assert_eq!(GateMode::from_setting(Some("question")), GateMode::Question);
+        assert_eq!(GateMode::from_setting(Some("button")), GateMode::Button);
+        assert_eq!(GateMode::from_setting(Some("disabled")), GateMode::Disabled);
+    }
+
+    #[test]
+    fn test_parse_generated_question() {
+        let parsed = parse_generated_question(
+            "```json\n{\"question\": \"What is 2 + 2?\", \"answers\": [\"4\", \"Four\", \" \"]}\n```",
+        )
+        .unwrap();
+        assert_eq!(parsed.question, "What is 2 + 2?");
+        assert_eq!(parsed.answers, vec!["4", "four"]);
+
+        assert!(parse_generated_question("not json").is_none());
+        assert!(parse_generated_question("{\"question\": \"Hi?\", \"answers\": []}").is_none());
+        let long = format!("{{\"question\": \"{}\", \"answers\": [\"x\"]}}", "q".repeat(150));
+        assert!(parse_generated_question(&long).is_none());
+    }
+
+    #[test]
+    fn test_check_answer() {
+        let answers = vec!["cow".to_string(), "cows".to_string()];
+        assert!(check_answer("  A Cow! ", &answers));
+        assert!(check_answer("the cows", &answers));
+        assert!(!check_answer("horse", &answers));
+        assert!(!check_answer("", &answers));
+    }
+
+    #[test]
+    fn test_fallback_question_has_answers() {
+        let question = fallback_question();
+        assert!(question.question.ends_with('?'));
+        assert!(!question.answers.is_empty());
+    }
+
+    #[test]
+    fn test_format_gate_prompt() {
+        assert_eq!(
+            format_gate_prompt("1", None, 0),
+            "👋 Welcome <@1>! To get access, press **Verify** below."
+        );
+        let prompt = format_gate_prompt("1", Some("What is 3 + 4?"), 30);
+        assert!(prompt.contains("answer this question: *What is 3 + 4?*"));
+        assert!(prompt.ends_with("within 30 minutes, or you'll be removed from the server."));
+    }
+}
@@ -0,0 +1,17 @@
+//! # Verification Gate Feature
+//!
+//! New members must press a Verify button (and optionally answer a simple
+//! AI-generated question) in the gate channel to receive the member role.
+//! Unverified members are kicked after a per-guild timeout.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod gate;
+
+pub use gate::{
+    check_answer, fallback_question, format_gate_prompt, gate_kick_loop, parse_generated_question, GateMode,
+    GateQuestion, DEFAULT_GATE_TIMEOUT_MINUTES, GATE_MODAL_PREFIX, GATE_VERIFY_PREFIX, MAX_QUESTION_CHARS,
+    QUESTION_PROMPT,
+};
@@ -0,0 +1,92 @@
+//! # Feature: Image Attachments
+//!
+//! Pick the attachments a vision model can read and encode them for a chat
+//! request. Only formats every provider accepts (PNG, JPEG, GIF and WebP) are
+//! sent, with a size cap below Anthropic's 5 MB per-image limit.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release
+
+use crate::features::llm::ChatImage;
+use base64::Engine;
+
+/// Images sent with one message at most
+pub const MAX_IMAGES: usize = 4;
+
+/// Largest attachment sent by default, in bytes (`IMAGE_MAX_BYTES`)
+pub const MAX_IMAGE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Question used when the message is only an image and a mention
+pub const DEFAULT_IMAGE_PROMPT: &str = "What's in this image?";
+
+/// The message text, or [`DEFAULT_IMAGE_PROMPT`] when it holds nothing but mentions
+pub fn image_question(content: &str) -> &str {
+    if content.split_whitespace().all(|word| word.starts_with("<@") && word.ends_with('>')) {
+        DEFAULT_IMAGE_PROMPT
+    } else {
+        content
+    }
+}
+
+/// MIME type of a supported image, from Discord's content type or the file extension
+pub fn image_media_type(filename: &str, content_type: Option<&str>) -> Option<&'static str> {
+    let from_content_type = content_type.and_then(|c| {
+        match c.split(';').next().unwrap_or("").trim().to_ascii_lowercase().as_str() {
+            "image/png" => Some("image/png"),
+            "image/jpeg" | "image/jpg" => Some("image/jpeg"),
+            "image/gif" => Some("image/gif"),
+            "image/webp" => Some("image/webp"),
+            _ => None,
+        }
+    });
+    from_content_type.or_else(|| {
+        let extension = filename.rsplit_once('.')?.1.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some("image/png"),
+            "jpg" | "jpeg" => Some("image/jpeg"),
+            "gif" => Some("image/gif"),
+            "webp" => Some("image/webp"),
+            _ => None,
+        }
+    })
+}
+
+/// Base64-encode downloaded image bytes for a chat request
+pub fn encode_image(bytes: &[u8], media_type: &str) -> ChatImage {
+    ChatImage {
+        media_type: media_type.to_string(),
+        data: base64::engine::general_purpose::STANDARD.encode(bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_media_type() {
+        assert_eq!(image_media_type("photo.JPG", None), Some("image/jpeg"));
+        assert_eq!(image_media_type("blob", Some("image/webp")), Some("image/webp"));
+        assert_eq!(image_media_type("diagram.png", Some("image/png; charset=binary")), Some("image/png"));
+        assert_eq!(image_media_type("scan.tiff", Some("image/tiff")), None);
+        assert_eq!(image_media_type("notes.txt", Some("text/plain")), None);
+    }
+
+    #[test]
+    fn test_image_question() {
+        assert_eq!(image_question("<@123456>"), DEFAULT_IMAGE_PROMPT);
+        assert_eq!(image_question(""), DEFAULT_IMAGE_PROMPT);
+        assert_eq!(image_question("<@123456> what breed is this?"), "<@123456> what breed is this?");
+    }
+
+    #[test]
+    fn test_encode_image() {
+        let image = encode_image(b"GIF89a", "image/gif");
+        assert_eq!(image.media_type, "image/gif");
+        assert_eq!(image.data, "R0lGODlh");
+    }
+}
@@ -0,0 +1,13 @@
+//! # Vision Feature
+//!
+//! Images attached to a message that mentions the bot are downloaded and sent
+//! with the question to a vision-capable model, so the persona can describe
+//! them or answer questions about them.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: true
+
+pub mod images;
+
+pub use images::{encode_image, image_media_type, image_question, DEFAULT_IMAGE_PROMPT, MAX_IMAGES, MAX_IMAGE_BYTES};
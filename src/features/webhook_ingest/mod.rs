@@ -0,0 +1,16 @@
+//! # Webhook Ingest Feature
+//!
+//! Authenticated HTTP endpoint (`POST /hooks/<id>`) where external systems such as
+//! CI or monitoring post JSON that the bot renders as a persona-styled embed in
+//! the source's channel. Sources are managed with `/webhook`, each with its own
+//! token, optional template and rate limit. Listens on `WEBHOOK_LISTEN_ADDR`.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+
+pub mod render;
+pub mod server;
+
+pub use render::{build_embed, render_template, status_color, valid_source_name, IngestEmbed};
+pub use server::{generate_source_token, serve_webhooks, tokens_match, DEFAULT_SOURCE_RATE_LIMIT, MAX_SOURCE_RATE_LIMIT};
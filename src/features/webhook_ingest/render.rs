@@ -0,0 +1,197 @@
+//! # Feature: Webhook Payload Rendering
+//!
+//! Turns an arbitrary JSON payload into embed parts. Well-known keys (`title`,
+//! `message`, `url`, `status`, ...) are picked up automatically; a source template
+//! with `{{field.path}}` placeholders replaces the description, and otherwise the
+//! remaining top-level values become embed fields.
+//!
+//! - **Version**: 1.0.0
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.0: Initial release with well-known keys, templates and status colors
+
+use crate::core::discord_limits::{truncate, EMBED_DESCRIPTION, EMBED_FIELD_NAME, EMBED_FIELD_VALUE, EMBED_TITLE};
+use serde_json::Value;
+
+const TITLE_KEYS: &[&str] = &["title", "summary", "name", "event"];
+const DESCRIPTION_KEYS: &[&str] = &["message", "text", "description", "body"];
+const URL_KEYS: &[&str] = &["url", "link", "html_url"];
+const STATUS_KEYS: &[&str] = &["status", "level", "state", "severity"];
+
+/// Most payload values shown as fields
+const MAX_FIELDS: usize = 10;
+
+/// Embed parts for an ingested payload
+#[derive(Debug, Clone, PartialEq)]
+pub struct IngestEmbed {
+    pub title: String,
+    pub description: String,
+    pub url: Option<String>,
+    pub color: u32,
+    pub fields: Vec<(String, String)>,
+}
+
+/// Source names: 1-32 characters of letters, digits, `-` and `_`
+pub fn valid_source_name(name: &str) -> bool {
+    (1..=32).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// A value as display text: strings without quotes, everything else as JSON
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn lookup<'a>(payload: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(payload, |value, key| match value {
+        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => value.get(key),
+    })
+}
+
+/// The first well-known key present with a scalar value, and that value as text
+fn first_string(payload: &Value, keys: &[&'static str]) -> Option<(&'static str, String)> {
+    keys.iter().find_map(|key| {
+        payload
+            .get(*key)
+            .filter(|v| !v.is_null() && !v.is_object() && !v.is_array())
+            .map(|v| (*key, display_value(v)))
+    })
+}
+
+/// Replace `{{field.path}}` placeholders with values from the payload (missing fields become empty)
+pub fn render_template(template: &str, payload: &Value) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                let path = after[..end].trim();
+                output.push_str(&lookup(payload, path).map(display_value).unwrap_or_default());
+                rest = &after[end + 2..];
+            }
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Embed color for a status word: green for success, red for failures, yellow for warnings
+pub fn status_color(status: Option<&str>) -> u32 {
+    match status.map(|s| s.to_lowercase()).as_deref() {
+        Some("success" | "succeeded" | "passed" | "ok" | "resolved" | "up" | "green") => 0x2ECC71,
+        Some("failure" | "failed" | "error" | "critical" | "down" | "firing" | "red") => 0xE74C3C,
+        Some("warning" | "warn" | "degraded" | "pending" | "yellow") => 0xF1C40F,
+        _ => 0x5865F2,
+    }
+}
+
+/// Build the embed for a payload from a named source
+pub fn build_embed(source_name: &str, template: Option<&str>, payload: &Value) -> IngestEmbed {
+    let title = first_string(payload, TITLE_KEYS);
+    let description = first_string(payload, DESCRIPTION_KEYS);
+    let url = first_string(payload, URL_KEYS).filter(|(_, u)| u.starts_with("http"));
+    let status = first_string(payload, STATUS_KEYS);
+
+    let mut fields = Vec::new();
+    if template.is_none() {
+        if let Value::Object(map) = payload {
+            let used: Vec<&str> = [&title, &description, &url].iter().filter_map(|f| f.as_ref().map(|(k, _)| *k)).collect();
+            for (key, value) in map {
+                if used.contains(&key.as_str()) || value.is_null() || value.is_object() || value.is_array() {
+                    continue;
+                }
+                if fields.len() == MAX_FIELDS {
+                    break;
+                }
+                fields.push((truncate(key, EMBED_FIELD_NAME), truncate(&display_value(value), EMBED_FIELD_VALUE)));
+            }
+        }
+    }
+
+    let description = match template {
+        Some(template) => render_template(template, payload),
+        None => description.map(|(_, d)| d).unwrap_or_default(),
+    };
+
+    IngestEmbed {
+        title: truncate(&title.map(|(_, t)| t).unwrap_or_else(|| source_name.to_string()), EMBED_TITLE),
+        description: truncate(&description, EMBED_DESCRIPTION),
+        url: url.map(|(_, u)| u),
+        color: status_color(status.as_ref().map(|(_, s)| s.as_str())),
+        fields,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_valid_source_name() {
+        assert!(valid_source_name("github-ci"));
+        assert!(valid_source_name("grafana_alerts"));
+        assert!(!valid_source_name(""));
+        assert!(!valid_source_name("has space"));
+        assert!(!valid_source_name(&"a".repeat(33)));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let payload = json!({"repo": "persona", "build": {"number": 42, "steps": ["test"]}});
+        assert_eq!(
+            render_template("**{{repo}}** #{{ build.number }} {{build.steps.0}} {{missing}}", &payload),
+            "**persona** #42 test "
+        );
+        assert_eq!(render_template("unclosed {{repo", &payload), "unclosed {{repo");
+    }
+
+    #[test]
+    fn test_status_color() {
+        assert_eq!(status_color(Some("SUCCESS")), 0x2ECC71);
+        assert_eq!(status_color(Some("firing")), 0xE74C3C);
+        assert_eq!(status_color(Some("degraded")), 0xF1C40F);
+        assert_eq!(status_color(None), 0x5865F2);
+    }
+
+    #[test]
+    fn test_build_embed_well_known_keys() {
+        let payload = json!({
+            "title": "Deploy finished",
+            "message": "v1.2.3 is live",
+            "url": "https://ci.example.org/runs/1",
+            "status": "success",
+            "env": "production"
+        });
+        let embed = build_embed("ci", None, &payload);
+        assert_eq!(embed.title, "Deploy finished");
+        assert_eq!(embed.description, "v1.2.3 is live");
+        assert_eq!(embed.url.as_deref(), Some("https://ci.example.org/runs/1"));
+        assert_eq!(embed.color, 0x2ECC71);
+        assert!(embed.fields.contains(&("status".to_string(), "success".to_string())));
+        assert!(embed.fields.contains(&("env".to_string(), "production".to_string())));
+        assert!(!embed.fields.iter().any(|(k, _)| k == "title" || k == "message"));
+    }
+
+    #[test]
+    fn test_build_embed_with_template() {
+        let payload = json!({"alert": "disk full", "host": "db1", "status": "firing"});
+        let embed = build_embed("grafana", Some("{{alert}} on `{{host}}`"), &payload);
+        assert_eq!(embed.title, "grafana");
+        assert_eq!(embed.description, "disk full on `db1`");
+        assert_eq!(embed.color, 0xE74C3C);
+        assert!(embed.fields.is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+//! # Feature: Webhook Ingest Server
+//!
+//! HTTP listener for `POST /hooks/<id>`. Requests must carry the source's token as
+//! `Authorization: Bearer <token>` and a JSON body; each source is limited to its
+//! configured number of posts per minute. The embed is attributed to the source's
+//! persona and posted without pinging anyone.
+//!
+//! - **Version**: 1.0.1
+//! - **Since**: 0.8.0
+//! - **Toggleable**: false
+//!
+//! ## Changelog
+//! - 1.0.1: Rebuild a source's rate limiter when its configured rate changes
+//! - 1.0.0: Initial release with bearer tokens and per-source rate limits
+
+use super::render::build_embed;
+use crate::core::discord_limits::fit_embed;
+use crate::database::Database;
+use crate::features::personas::PersonaManager;
+use crate::features::rate_limiting::RateLimiter;
+use crate::core::Result;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use dashmap::DashMap;
+use log::{info, warn};
+use serde_json::{json, Value};
+use serenity::http::Http;
+use serenity::model::id::ChannelId;
+use serenity::model::Timestamp;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Posts per minute for a new source unless set with `/webhook create`
+pub const DEFAULT_SOURCE_RATE_LIMIT: i64 = 30;
+
+/// Highest allowed per-source rate limit
+pub const MAX_SOURCE_RATE_LIMIT: i64 = 120;
+
+#[derive(Clone)]
+struct IngestState {
+    db: Arc<Database>,
+    http: Arc<Http>,
+    personas: Arc<PersonaManager>,
+    /// Source ID -> the rate it was built for and its rate limiter
+    limiters: Arc<DashMap<i64, (usize, Arc<RateLimiter>)>>,
+}
+
+/// The limiter for a source, rebuilt when its configured rate has changed since it was cached
+fn source_limiter(limiters: &DashMap<i64, (usize, Arc<RateLimiter>)>, source_id: i64, rate_limit_per_minute: i64) -> Arc<RateLimiter> {
+    let rate = rate_limit_per_minute.clamp(1, MAX_SOURCE_RATE_LIMIT) as usize;
+    let mut entry = limiters
+        .entry(source_id)
+        .or_insert_with(|| (rate, Arc::new(RateLimiter::new(rate, Duration::from_secs(60)))));
+    if entry.0 != rate {
+        *entry = (rate, Arc::new(RateLimiter::new(rate, Duration::from_secs(60))));
+    }
+    entry.1.clone()
+}
+
+/// A new random source token (32 hex characters)
+pub fn generate_source_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Compare tokens without leaking the position of the first difference through timing
+pub fn tokens_match(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected.bytes().zip(provided.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn reply(status: StatusCode, message: &str) -> (StatusCode, Json<Value>) {
+    let ok = status.is_success();
+    (status, Json(json!({ "ok": ok, "message": message })))
+}
+
+async fn ingest(
+    State(state): State<IngestState>,
+    Path(source_id): Path<i64>,
+    headers: HeaderMap,
+    body: String,
+) -> (StatusCode, Json<Value>) {
+    let source = match state.db.get_webhook_source(source_id).await {
+        Ok(Some(source)) => source,
+        Ok(None) => return reply(StatusCode::NOT_FOUND, "unknown webhook source"),
+        Err(e) => {
+            warn!("Failed to load webhook source {}: {}", source_id, e);
+            return reply(StatusCode::INTERNAL_SERVER_ERROR, "internal error");
+        }
+    };
+
+    let provided = headers
+        .get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if !tokens_match(&source.token, provided.trim()) {
+        return reply(StatusCode::UNAUTHORIZED, "invalid token");
+    }
+
+    let limiter = source_limiter(&state.limiters, source.id, source.rate_limit_per_minute);
+    if !limiter.check_rate_limit(&source.id.to_string()).await {
+        return reply(StatusCode::TOO_MANY_REQUESTS, "rate limit exceeded");
+    }
+
+    let payload: Value = match serde_json::from_str(&body) {
+        Ok(payload) => payload,
+        Err(_) => return reply(StatusCode::BAD_REQUEST, "body must be JSON"),
+    };
+    let Ok(channel_id) = source.channel_id.parse::<u64>() else {
+        return reply(StatusCode::INTERNAL_SERVER_ERROR, "source channel is invalid");
+    };
+
+    let embed = build_embed(&source.name, source.template.as_deref(), &payload);
+    let persona_name = state
+        .personas
+        .get_persona(&source.persona)
+        .map(|p| p.name.clone())
+        .unwrap_or_else(|| source.persona.clone());
+
+    let sent = ChannelId(channel_id)
+        .send_message(&state.http, |m| {
+            m.embed(|e| {
+                e.author(|a| a.name(&persona_name))
+                    .title(&embed.title)
+                    .description(&embed.description)
+                    .color(embed.color)
+                    .footer(|f| f.text(format!("via {} webhook", source.name)))
+                    .timestamp(Timestamp::now());
+                if let Some(url) = &embed.url {
+                    e.url(url);
+                }
+                for (name, value) in &embed.fields {
+                    e.field(name, value, true);
+                }
+                fit_embed(e)
+            })
+            .allowed_mentions(|a| a.empty_parse())
+        })
+        .await;
+
+    match sent {
+        Ok(_) => reply(StatusCode::OK, "posted"),
+        Err(e) => {
+            warn!("Failed to post webhook from source {} to channel {}: {}", source.name, channel_id, e);
+            reply(StatusCode::BAD_GATEWAY, "failed to post to Discord")
+        }
+    }
+}
+
+/// Serve the webhook endpoint on `addr` (e.g. `0.0.0.0:8787`) until the process exits
+pub async fn serve_webhooks(addr: &str, db: Arc<Database>, http: Arc<Http>) -> Result<()> {
+    let state = IngestState {
+        db,
+        http,
+        personas: Arc::new(PersonaManager::new()),
+        limiters: Arc::new(DashMap::new()),
+    };
+    let app = Router::new().route("/hooks/:id", post(ingest)).with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Webhook ingest endpoint listening on {}", addr);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_token() {
+        let token = generate_source_token();
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, generate_source_token());
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("abc123", "abc123"));
+        assert!(!tokens_match("abc123", "abc124"));
+        assert!(!tokens_match("abc123", "abc12"));
+        assert!(!tokens_match("abc123", ""));
+    }
+
+    #[tokio::test]
+    async fn test_limiter_rebuilt_when_rate_changes() {
+        let limiters = DashMap::new();
+        let limiter = source_limiter(&limiters, 7, 1);
+        assert!(limiter.check_rate_limit("7").await);
+        assert!(!limiter.check_rate_limit("7").await);
+
+        // Same rate keeps the cached limiter and its count
+        assert!(Arc::ptr_eq(&limiter, &source_limiter(&limiters, 7, 1)));
+
+        let raised = source_limiter(&limiters, 7, 5);
+        assert!(!Arc::ptr_eq(&limiter, &raised));
+        assert!(raised.check_rate_limit("7").await);
+    }
+}
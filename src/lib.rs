@@ -11,12 +11,16 @@ pub mod message_components;
 pub mod database;
 
 // Application layer
+pub mod bot;
 pub mod command_handler;
 pub mod commands;
 
 // Re-export core config for backwards compatibility
 pub use core::Config;
 
+// Embedding API
+pub use bot::{Bot, BotBuilder, Plugin};
+
 // Re-export feature items for backwards compatibility
 pub use features::{
     // Analytics
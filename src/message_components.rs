@@ -1,15 +1,34 @@
-use anyhow::Result;
+use crate::core::clock::{random_ids, system_clock};
+use crate::core::{BotError, Clock, IdGen, Result};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
 use log::{error, info};
-use serenity::builder::CreateComponents;
+use serenity::builder::{CreateComponents, CreateEmbed, CreateInteractionResponse};
 use serenity::model::application::component::{ActionRowComponent, ButtonStyle};
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
 use serenity::model::application::interaction::message_component::MessageComponentInteraction;
 use serenity::model::application::interaction::modal::ModalSubmitInteraction;
 use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::id::UserId;
 use serenity::prelude::Context;
+use std::sync::Arc;
 
 use crate::commands::CommandHandler;
 use crate::database::Database;
-use crate::features::personas::PersonaManager;
+use crate::features::analytics::ops_overview::{page_custom_id, OverviewSort, OPS_PAGE_PREFIX};
+use crate::features::history_import::{confirm_custom_id, IMPORT_CANCEL_ID, IMPORT_HISTORY_PREFIX};
+use crate::features::join_screening::{CAPTCHA_CHOICES, JOIN_VERIFY_PREFIX};
+use crate::features::code_runner::RUN_MODAL_PREFIX;
+use crate::features::conflict::{action_custom_id, suggestion_custom_id, ConflictDecision, CONFLICT_ACTION_PREFIX, CONFLICT_SUGGEST_PREFIX};
+use crate::features::focus::FOCUS_JOIN_PREFIX;
+use crate::features::error_explainer::EXPLAIN_ERROR_MODAL_PREFIX;
+use crate::features::message_move::MOVE_MODAL_PREFIX;
+use crate::features::verification_gate::{GATE_MODAL_PREFIX, GATE_VERIFY_PREFIX};
+use crate::features::moderation::{
+    AppealDecision, APPEAL_APPROVE_PREFIX, APPEAL_DENY_PREFIX, APPEAL_MODAL_PREFIX, APPEAL_OPEN_PREFIX,
+};
+use crate::features::personas::{PersonaManager, CUSTOM_PERSONA_MODAL_PREFIX};
+use crate::features::reminders::chat_changes::{change_confirm_id, change_keep_id, ReminderChange, REMINDER_CHANGE_PREFIX};
 
 /// Handler for all message component interactions
 pub struct MessageComponentHandler {
@@ -41,11 +60,59 @@ impl MessageComponentHandler {
             id if id.starts_with("confirm_") => {
                 self.handle_confirmation(ctx, interaction).await?;
             }
+            id if id.starts_with("followup_") => {
+                self.command_handler.handle_follow_up_button(ctx, interaction).await?;
+            }
+            id if id.starts_with("askanyway_") => {
+                self.command_handler.handle_ask_anyway_button(ctx, interaction).await?;
+            }
+            id if id.starts_with("regen_") => {
+                self.command_handler.handle_regenerate_button(ctx, interaction).await?;
+            }
+            id if id.starts_with("editprompt_") => {
+                self.command_handler.show_edit_prompt_modal(ctx, interaction).await?;
+            }
             id if id.starts_with("cancel_") => {
                 self.handle_cancellation(ctx, interaction).await?;
             }
-            id if id.starts_with("page_") => {
-                self.handle_pagination(ctx, interaction).await?;
+            id if id.starts_with(OPS_PAGE_PREFIX) => {
+                self.command_handler.handle_ops_page_button(ctx, interaction).await?;
+            }
+            id if id.starts_with(APPEAL_OPEN_PREFIX) => {
+                self.command_handler.show_appeal_modal(ctx, interaction).await?;
+            }
+            id if id.starts_with(APPEAL_APPROVE_PREFIX) => {
+                self.command_handler.handle_appeal_review_button(ctx, interaction, AppealDecision::Approved).await?;
+            }
+            id if id.starts_with(APPEAL_DENY_PREFIX) => {
+                self.command_handler.handle_appeal_review_button(ctx, interaction, AppealDecision::Denied).await?;
+            }
+            id if id.starts_with(CONFLICT_SUGGEST_PREFIX) => {
+                self.command_handler.handle_conflict_suggestion_button(ctx, interaction).await?;
+            }
+            id if id.starts_with(CONFLICT_ACTION_PREFIX) => {
+                self.command_handler.handle_conflict_action_button(ctx, interaction).await?;
+            }
+            id if id.starts_with(FOCUS_JOIN_PREFIX) => {
+                self.command_handler.handle_focus_join_button(ctx, interaction).await?;
+            }
+            id if id.starts_with(JOIN_VERIFY_PREFIX) => {
+                self.command_handler.handle_join_verification_button(ctx, interaction).await?;
+            }
+            id if id.starts_with(IMPORT_HISTORY_PREFIX) => {
+                self.command_handler.handle_import_history_button(ctx, interaction).await?;
+            }
+            id if id.starts_with(REMINDER_CHANGE_PREFIX) => {
+                self.command_handler.handle_reminder_change_button(ctx, interaction).await?;
+            }
+            id if id.starts_with(GATE_VERIFY_PREFIX) => {
+                self.command_handler.handle_gate_button(ctx, interaction).await?;
+            }
+            id if id.starts_with(PAGE_PREFIX) => {
+                self.command_handler.paginator().handle_button(ctx, interaction).await?;
+            }
+            id if id.starts_with(PAGE_JUMP_PREFIX) => {
+                self.command_handler.paginator().show_jump_modal(ctx, interaction).await?;
             }
             "show_help_modal" => {
                 self.show_help_modal(ctx, interaction).await?;
@@ -86,6 +153,33 @@ impl MessageComponentHandler {
             "ai_prompt_modal" => {
                 self.handle_ai_prompt_modal(ctx, interaction).await?;
             }
+            id if id.starts_with("remind_message_modal:") => {
+                self.command_handler.handle_remind_message_modal(ctx, interaction).await?;
+            }
+            id if id.starts_with(MOVE_MODAL_PREFIX) => {
+                self.command_handler.handle_move_message_modal(ctx, interaction).await?;
+            }
+            id if id.starts_with("editprompt_modal:") => {
+                self.command_handler.handle_edit_prompt_modal(ctx, interaction).await?;
+            }
+            id if id.starts_with(APPEAL_MODAL_PREFIX) => {
+                self.command_handler.handle_appeal_modal(ctx, interaction).await?;
+            }
+            id if id.starts_with(GATE_MODAL_PREFIX) => {
+                self.command_handler.handle_gate_modal(ctx, interaction).await?;
+            }
+            id if id.starts_with(CUSTOM_PERSONA_MODAL_PREFIX) => {
+                self.command_handler.handle_custom_persona_modal(ctx, interaction).await?;
+            }
+            id if id.starts_with(RUN_MODAL_PREFIX) => {
+                self.command_handler.handle_run_code_modal(ctx, interaction).await?;
+            }
+            id if id.starts_with(EXPLAIN_ERROR_MODAL_PREFIX) => {
+                self.command_handler.handle_explain_error_modal(ctx, interaction).await?;
+            }
+            id if id.starts_with(PAGE_JUMP_PREFIX) => {
+                self.command_handler.paginator().handle_jump_modal(ctx, interaction).await?;
+            }
             _ => {
                 interaction
                     .create_interaction_response(&ctx.http, |response| {
@@ -160,64 +254,246 @@ impl MessageComponentHandler {
             .to_owned()
     }
 
-    /// Create confirmation buttons
-    pub fn create_confirmation_buttons(action_id: &str) -> CreateComponents {
+    /// Create suggested follow-up question buttons (one row, up to 3 buttons)
+    pub fn create_follow_up_buttons(questions: &[String]) -> CreateComponents {
         CreateComponents::default()
             .create_action_row(|row| {
+                for (i, question) in questions.iter().enumerate() {
+                    row.create_button(|button| {
+                        button
+                            .custom_id(format!("followup_{i}"))
+                            .label(question)
+                            .style(ButtonStyle::Secondary)
+                    });
+                }
+                row
+            })
+            .to_owned()
+    }
+
+    /// Create the controls attached to an AI reply: follow-up questions (if any) plus
+    /// regenerate / edit-prompt buttons when the reply was recorded
+    pub fn create_response_controls(response_id: Option<i64>, follow_ups: &[String], can_regenerate: bool) -> CreateComponents {
+        let mut components = if follow_ups.is_empty() {
+            CreateComponents::default()
+        } else {
+            Self::create_follow_up_buttons(follow_ups)
+        };
+
+        if let Some(id) = response_id {
+            components.create_action_row(|row| {
                 row.create_button(|button| {
                     button
-                        .custom_id(format!("confirm_{action_id}"))
-                        .label("✅ Confirm")
-                        .style(ButtonStyle::Success)
+                        .custom_id(format!("regen_{id}"))
+                        .label("🔁 Regenerate")
+                        .style(ButtonStyle::Secondary)
+                        .disabled(!can_regenerate)
                 })
                 .create_button(|button| {
                     button
-                        .custom_id(format!("cancel_{action_id}"))
-                        .label("❌ Cancel")
-                        .style(ButtonStyle::Danger)
+                        .custom_id(format!("editprompt_{id}"))
+                        .label("✏️ Edit prompt")
+                        .style(ButtonStyle::Secondary)
                 })
-            })
-            .to_owned()
+            });
+        }
+
+        components
     }
 
-    /// Create pagination buttons
-    pub fn create_pagination_buttons(current_page: u32, total_pages: u32) -> CreateComponents {
+    /// Create previous/next buttons for the owner's `/ops overview` (zero-based `page`)
+    pub fn create_ops_page_buttons(sort: OverviewSort, page: usize, total_pages: usize) -> CreateComponents {
         CreateComponents::default()
             .create_action_row(|row| {
                 row.create_button(|button| {
                     button
-                        .custom_id("page_first")
-                        .label("⏮️")
+                        .custom_id(page_custom_id(sort, page.saturating_sub(1)))
+                        .label("⬅️")
                         .style(ButtonStyle::Secondary)
-                        .disabled(current_page == 1)
+                        .disabled(page == 0)
                 })
                 .create_button(|button| {
                     button
-                        .custom_id("page_prev")
-                        .label("⬅️")
+                        .custom_id("opsinfo")
+                        .label(format!("{}/{}", page + 1, total_pages))
                         .style(ButtonStyle::Secondary)
-                        .disabled(current_page == 1)
+                        .disabled(true)
                 })
                 .create_button(|button| {
                     button
-                        .custom_id("page_info")
-                        .label(format!("{current_page}/{total_pages}"))
+                        .custom_id(page_custom_id(sort, page + 1))
+                        .label("➡️")
                         .style(ButtonStyle::Secondary)
-                        .disabled(true)
+                        .disabled(page + 1 >= total_pages)
+                })
+            })
+            .to_owned()
+    }
+
+    /// Create one Appeal button per server with an appealable action: (guild_id, label)
+    pub fn create_appeal_buttons(choices: &[(u64, String)]) -> CreateComponents {
+        let mut components = CreateComponents::default();
+        components.create_action_row(|row| {
+            // Discord allows at most 5 buttons per row
+            for (guild_id, label) in choices.iter().take(5) {
+                row.create_button(|button| {
+                    button
+                        .custom_id(format!("{APPEAL_OPEN_PREFIX}{guild_id}"))
+                        .label(label)
+                        .style(ButtonStyle::Primary)
+                });
+            }
+            row
+        });
+        components
+    }
+
+    /// Create Approve / Deny buttons for an appeal in the review channel
+    pub fn create_appeal_review_buttons(appeal_id: i64) -> CreateComponents {
+        CreateComponents::default()
+            .create_action_row(|row| {
+                row.create_button(|button| {
+                    button
+                        .custom_id(format!("{APPEAL_APPROVE_PREFIX}{appeal_id}"))
+                        .label("✅ Approve")
+                        .style(ButtonStyle::Success)
                 })
                 .create_button(|button| {
                     button
-                        .custom_id("page_next")
-                        .label("➡️")
+                        .custom_id(format!("{APPEAL_DENY_PREFIX}{appeal_id}"))
+                        .label("❌ Deny")
+                        .style(ButtonStyle::Danger)
+                })
+            })
+            .to_owned()
+    }
+
+    /// Create one Send button per suggested reply on a conflict alert, and the
+    /// Mediate now / Dismiss / Escalate row when the conflict waits for a moderator
+    pub fn create_conflict_suggestion_buttons(conflict_id: i64, count: usize, with_actions: bool) -> CreateComponents {
+        let mut components = CreateComponents::default();
+        components.create_action_row(|row| {
+            for number in 1..=count.min(5) {
+                row.create_button(|button| {
+                    button
+                        .custom_id(suggestion_custom_id(conflict_id, number))
+                        .label(format!("Send {number}"))
+                        .style(ButtonStyle::Primary)
+                });
+            }
+            row
+        });
+        if with_actions {
+            components.create_action_row(|row| {
+                for decision in ConflictDecision::ACTIONS {
+                    let style = match decision {
+                        ConflictDecision::Escalate => ButtonStyle::Danger,
+                        ConflictDecision::Dismiss => ButtonStyle::Secondary,
+                        _ => ButtonStyle::Success,
+                    };
+                    row.create_button(|button| {
+                        button
+                            .custom_id(action_custom_id(conflict_id, decision))
+                            .label(decision.label())
+                            .style(style)
+                    });
+                }
+                row
+            });
+        }
+        components
+    }
+
+    /// Create the join captcha buttons (`options` index into `CAPTCHA_CHOICES`)
+    pub fn create_join_captcha_buttons(screening_id: i64, options: &[usize]) -> CreateComponents {
+        let mut components = CreateComponents::default();
+        components.create_action_row(|row| {
+            for &choice in options.iter().filter(|c| **c < CAPTCHA_CHOICES.len()).take(5) {
+                row.create_button(|button| {
+                    button
+                        .custom_id(format!("{JOIN_VERIFY_PREFIX}{screening_id}:{choice}"))
+                        .label(CAPTCHA_CHOICES[choice].0)
                         .style(ButtonStyle::Secondary)
-                        .disabled(current_page == total_pages)
+                });
+            }
+            row
+        });
+        components
+    }
+
+    /// Create the Verify button on a verification gate prompt
+    pub fn create_gate_button(gate_id: i64) -> CreateComponents {
+        CreateComponents::default()
+            .create_action_row(|row| {
+                row.create_button(|button| {
+                    button
+                        .custom_id(format!("{GATE_VERIFY_PREFIX}{gate_id}"))
+                        .label("✅ Verify")
+                        .style(ButtonStyle::Success)
+                })
+            })
+            .to_owned()
+    }
+
+    /// Create the Confirm / Keep buttons under a reminder change proposed in chat
+    pub fn create_reminder_change_buttons(user_id: &str, change: &ReminderChange) -> CreateComponents {
+        let (label, style) = match change {
+            ReminderChange::Cancel { .. } => ("🗑️ Cancel reminder", ButtonStyle::Danger),
+            ReminderChange::Move { .. } => ("✅ Move reminder", ButtonStyle::Primary),
+        };
+        CreateComponents::default()
+            .create_action_row(|row| {
+                row.create_button(|button| {
+                    button
+                        .custom_id(change_confirm_id(user_id, change))
+                        .label(label)
+                        .style(style)
                 })
                 .create_button(|button| {
                     button
-                        .custom_id("page_last")
-                        .label("⏭️")
+                        .custom_id(change_keep_id(user_id, change.reminder_id()))
+                        .label("Keep as is")
+                        .style(ButtonStyle::Secondary)
+                })
+            })
+            .to_owned()
+    }
+
+    /// Create the consent Confirm/Cancel buttons of /import_history
+    pub fn create_import_history_buttons(channel_id: u64, days: i64) -> CreateComponents {
+        CreateComponents::default()
+            .create_action_row(|row| {
+                row.create_button(|button| {
+                    button
+                        .custom_id(confirm_custom_id(channel_id, days))
+                        .label("✅ Members consented, import")
+                        .style(ButtonStyle::Success)
+                })
+                .create_button(|button| {
+                    button
+                        .custom_id(IMPORT_CANCEL_ID)
+                        .label("❌ Cancel")
                         .style(ButtonStyle::Secondary)
-                        .disabled(current_page == total_pages)
+                })
+            })
+            .to_owned()
+    }
+
+    /// Create confirmation buttons
+    pub fn create_confirmation_buttons(action_id: &str) -> CreateComponents {
+        CreateComponents::default()
+            .create_action_row(|row| {
+                row.create_button(|button| {
+                    button
+                        .custom_id(format!("confirm_{action_id}"))
+                        .label("✅ Confirm")
+                        .style(ButtonStyle::Success)
+                })
+                .create_button(|button| {
+                    button
+                        .custom_id(format!("cancel_{action_id}"))
+                        .label("❌ Cancel")
+                        .style(ButtonStyle::Danger)
                 })
             })
             .to_owned()
@@ -301,33 +577,6 @@ impl MessageComponentHandler {
         Ok(())
     }
 
-    /// Handle pagination button clicks
-    async fn handle_pagination(&self, ctx: &Context, interaction: &MessageComponentInteraction) -> Result<()> {
-        let action = interaction.data.custom_id.strip_prefix("page_").unwrap_or("");
-        
-        // This is a simple implementation - in a real app you'd track page state
-        let message = match action {
-            "first" => "📄 Showing first page",
-            "prev" => "📄 Showing previous page", 
-            "next" => "📄 Showing next page",
-            "last" => "📄 Showing last page",
-            _ => "📄 Page navigation",
-        };
-        
-        interaction
-            .create_interaction_response(&ctx.http, |response| {
-                response
-                    .kind(InteractionResponseType::UpdateMessage)
-                    .interaction_response_data(|msg| {
-                        msg.content(message)
-                            .set_components(Self::create_pagination_buttons(1, 3))
-                    })
-            })
-            .await?;
-            
-        Ok(())
-    }
-
     /// Show help modal
     async fn show_help_modal(&self, ctx: &Context, interaction: &MessageComponentInteraction) -> Result<()> {
         interaction
@@ -514,9 +763,279 @@ impl MessageComponentHandler {
     }
 }
 
+/// Custom ID prefix for paginator page buttons: `page_{session}_{page}`
+pub const PAGE_PREFIX: &str = "page_";
+
+/// Custom ID prefix for the paginator's jump button and modal: `pagejump_{session}`
+pub const PAGE_JUMP_PREFIX: &str = "pagejump_";
+
+/// How long a paginated message's buttons keep working
+pub const PAGINATOR_TTL_MINUTES: i64 = 10;
+
+/// Custom ID for the button that shows zero-based `page`
+fn page_button_id(session: &str, page: usize) -> String {
+    format!("{PAGE_PREFIX}{session}_{page}")
+}
+
+/// Parse a page button custom ID into (session, zero-based page)
+fn parse_page_custom_id(custom_id: &str) -> Option<(&str, usize)> {
+    let (session, page) = custom_id.strip_prefix(PAGE_PREFIX)?.rsplit_once('_')?;
+    Some((session, page.parse().ok()?))
+}
+
+/// Result of looking up a page for a button press
+enum PageLookup {
+    /// The embed, its zero-based index and the page count
+    Page(CreateEmbed, usize, usize),
+    NotOwner,
+    Expired,
+}
+
+/// A paginated message: its pages and who may turn them
+struct PageSession {
+    owner: UserId,
+    pages: Vec<CreateEmbed>,
+    expires_at: DateTime<Utc>,
+}
+
+/// Multi-page embed output with first/previous/next/last buttons and a
+/// jump-to-page button. Pages are kept in memory for [`PAGINATOR_TTL_MINUTES`];
+/// the buttons carry the session and the page they lead to, so no per-message
+/// position is stored. Only the user the pages were made for can turn them.
+#[derive(Clone)]
+pub struct Paginator {
+    sessions: Arc<DashMap<String, PageSession>>,
+    clock: Arc<dyn Clock>,
+    ids: Arc<dyn IdGen>,
+}
+
+impl Default for Paginator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Paginator {
+    pub fn new() -> Self {
+        Self::with_sources(system_clock(), random_ids())
+    }
+
+    /// Like [`Paginator::new`], taking expiry times and session IDs from `clock` and `ids`
+    pub fn with_sources(clock: Arc<dyn Clock>, ids: Arc<dyn IdGen>) -> Self {
+        Paginator { sessions: Arc::new(DashMap::new()), clock, ids }
+    }
+
+    /// Keep `pages` for `owner` and return the session their buttons refer to
+    pub fn start(&self, owner: UserId, pages: Vec<CreateEmbed>) -> String {
+        let now = self.clock.now();
+        self.sessions.retain(|_, session| session.expires_at >= now);
+
+        let id = self.ids.new_id().simple().to_string();
+        let expires_at = now + chrono::Duration::minutes(PAGINATOR_TTL_MINUTES);
+        self.sessions.insert(id.clone(), PageSession { owner, pages, expires_at });
+        id
+    }
+
+    /// Page `page` of a session for `user`, clamped to the last page
+    fn page(&self, session: &str, user: UserId, page: usize) -> PageLookup {
+        let Some(entry) = self.sessions.get(session).filter(|s| s.expires_at >= self.clock.now()) else {
+            return PageLookup::Expired;
+        };
+        if entry.owner != user {
+            return PageLookup::NotOwner;
+        }
+        let total = entry.pages.len().max(1);
+        let page = page.min(total - 1);
+        PageLookup::Page(entry.pages.get(page).cloned().unwrap_or_default(), page, total)
+    }
+
+    /// Navigation buttons for zero-based `page` of `total_pages`; the middle one opens the jump modal
+    pub fn buttons(session: &str, page: usize, total_pages: usize) -> CreateComponents {
+        let last = total_pages.saturating_sub(1);
+        CreateComponents::default()
+            .create_action_row(|row| {
+                row.create_button(|button| {
+                    button
+                        .custom_id(format!("{}_first", page_button_id(session, 0)))
+                        .label("⏮️")
+                        .style(ButtonStyle::Secondary)
+                        .disabled(page == 0)
+                })
+                .create_button(|button| {
+                    button
+                        .custom_id(page_button_id(session, page.saturating_sub(1)))
+                        .label("⬅️")
+                        .style(ButtonStyle::Secondary)
+                        .disabled(page == 0)
+                })
+                .create_button(|button| {
+                    button
+                        .custom_id(format!("{PAGE_JUMP_PREFIX}{session}"))
+                        .label(format!("{}/{}", page + 1, total_pages))
+                        .style(ButtonStyle::Secondary)
+                        .disabled(total_pages < 3)
+                })
+                .create_button(|button| {
+                    button
+                        .custom_id(page_button_id(session, page + 1))
+                        .label("➡️")
+                        .style(ButtonStyle::Secondary)
+                        .disabled(page >= last)
+                })
+                .create_button(|button| {
+                    button
+                        .custom_id(format!("{}_last", page_button_id(session, last)))
+                        .label("⏭️")
+                        .style(ButtonStyle::Secondary)
+                        .disabled(page >= last)
+                })
+            })
+            .to_owned()
+    }
+
+    /// Reply to a slash command with page `start` of `pages` (zero-based), with buttons
+    /// when there is more than one page
+    pub async fn send(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+        pages: Vec<CreateEmbed>,
+        start: usize,
+        ephemeral: bool,
+    ) -> Result<()> {
+        let total = pages.len().max(1);
+        let start = start.min(total - 1);
+        let first = pages.get(start).cloned().unwrap_or_default();
+        let session = (total > 1).then(|| self.start(command.user.id, pages));
+
+        command
+            .create_interaction_response(&ctx.http, |response| {
+                response
+                    .kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|message| {
+                        message.set_embed(first).ephemeral(ephemeral);
+                        if let Some(session) = &session {
+                            message.set_components(Self::buttons(session, start, total));
+                        }
+                        message
+                    })
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Handle a page button press
+    pub async fn handle_button(&self, ctx: &Context, interaction: &MessageComponentInteraction) -> Result<()> {
+        // First/last buttons carry a suffix so their IDs differ from previous/next
+        let custom_id = interaction.data.custom_id.trim_end_matches("_first").trim_end_matches("_last");
+        let (session, page) = parse_page_custom_id(custom_id)
+            .ok_or_else(|| BotError::internal(format!("Malformed page id: {}", interaction.data.custom_id)))?;
+        let update = self.page(session, interaction.user.id, page);
+        let session = session.to_string();
+
+        interaction
+            .create_interaction_response(&ctx.http, |response| {
+                Self::page_response(response, &session, update)
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Open the "go to page" modal from the jump button
+    pub async fn show_jump_modal(&self, ctx: &Context, interaction: &MessageComponentInteraction) -> Result<()> {
+        let session = interaction.data.custom_id.trim_start_matches(PAGE_JUMP_PREFIX).to_string();
+        let total = match self.page(&session, interaction.user.id, 0) {
+            PageLookup::Page(_, _, total) => total,
+            update => {
+                interaction
+                    .create_interaction_response(&ctx.http, |response| {
+                        Self::page_response(response, &session, update)
+                    })
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        interaction
+            .create_interaction_response(&ctx.http, |response| {
+                response
+                    .kind(InteractionResponseType::Modal)
+                    .interaction_response_data(|modal| {
+                        modal
+                            .custom_id(format!("{PAGE_JUMP_PREFIX}{session}"))
+                            .title("Go to page")
+                            .components(|c| {
+                                c.create_action_row(|row| {
+                                    row.create_input_text(|input| {
+                                        input
+                                            .custom_id("page")
+                                            .label(format!("Page (1-{total})"))
+                                            .style(serenity::model::application::component::InputTextStyle::Short)
+                                            .required(true)
+                                            .max_length(4)
+                                    })
+                                })
+                            })
+                    })
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Handle the jump modal: show the page typed, clamped to the valid range
+    pub async fn handle_jump_modal(&self, ctx: &Context, interaction: &ModalSubmitInteraction) -> Result<()> {
+        let session = interaction.data.custom_id.trim_start_matches(PAGE_JUMP_PREFIX).to_string();
+        let typed = interaction
+            .data
+            .components
+            .iter()
+            .flat_map(|row| row.components.iter())
+            .find_map(|component| match component {
+                ActionRowComponent::InputText(input) if input.custom_id == "page" => Some(input.value.trim().to_string()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let page = typed.parse::<usize>().unwrap_or(1).max(1) - 1;
+        let update = self.page(&session, interaction.user.id, page);
+
+        interaction
+            .create_interaction_response(&ctx.http, |response| {
+                Self::page_response(response, &session, update)
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Show the requested page in place, or say why it can't be shown
+    fn page_response<'a, 'b>(
+        response: &'a mut CreateInteractionResponse<'b>,
+        session: &str,
+        update: PageLookup,
+    ) -> &'a mut CreateInteractionResponse<'b> {
+        match update {
+            PageLookup::Page(embed, page, total) => response
+                .kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|message| message.set_embed(embed).set_components(Self::buttons(session, page, total))),
+            PageLookup::NotOwner => response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    message.content("❌ Only the person who ran the command can turn these pages.").ephemeral(true)
+                }),
+            PageLookup::Expired => response
+                .kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|message| {
+                    message
+                        .content("⌛ These pages have expired. Run the command again to browse them.")
+                        .set_components(CreateComponents::default())
+                }),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::clock::{system_clock, FakeClock, SequentialIds};
 
     #[test]
     fn test_create_persona_select_menu() {
@@ -538,9 +1057,105 @@ mod tests {
         assert!(!components.0.is_empty());
     }
 
+    #[test]
+    fn test_create_follow_up_buttons() {
+        let questions = vec!["Why?".to_string(), "How?".to_string()];
+        let components = MessageComponentHandler::create_follow_up_buttons(&questions);
+        assert!(!components.0.is_empty());
+    }
+
+    #[test]
+    fn test_create_response_controls() {
+        let without_record = MessageComponentHandler::create_response_controls(None, &[], true);
+        assert!(without_record.0.is_empty());
+
+        let questions = vec!["Why?".to_string()];
+        let with_record = MessageComponentHandler::create_response_controls(Some(7), &questions, true);
+        assert_eq!(with_record.0.len(), 2);
+    }
+
     #[test]
     fn test_create_pagination_buttons() {
-        let components = MessageComponentHandler::create_pagination_buttons(2, 5);
+        let components = Paginator::buttons("abc", 1, 5);
         assert!(!components.0.is_empty());
     }
+
+    fn pages(count: usize) -> Vec<CreateEmbed> {
+        (1..=count)
+            .map(|n| {
+                let mut embed = CreateEmbed::default();
+                embed.title(format!("Page {n}"));
+                embed
+            })
+            .collect()
+    }
+
+    fn title(embed: &CreateEmbed) -> &str {
+        embed.0.get("title").and_then(|t| t.as_str()).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_page_custom_id_round_trip() {
+        assert_eq!(parse_page_custom_id(&page_button_id("abc", 4)), Some(("abc", 4)));
+        assert_eq!(parse_page_custom_id("page_abc_x"), None);
+        assert_eq!(parse_page_custom_id("opspage_cost_1"), None);
+    }
+
+    #[test]
+    fn test_paginator_clamps_pages() {
+        let paginator = Paginator::with_sources(system_clock(), Arc::new(SequentialIds::default()));
+        let session = paginator.start(UserId(1), pages(3));
+        assert_eq!(session, uuid::Uuid::from_u128(1).simple().to_string());
+
+        let PageLookup::Page(embed, page, total) = paginator.page(&session, UserId(1), 9) else { panic!("no page") };
+        assert_eq!((title(&embed), page, total), ("Page 3", 2, 3));
+        assert!(matches!(paginator.page("missing", UserId(1), 0), PageLookup::Expired));
+    }
+
+    #[test]
+    fn test_paginator_only_turns_for_owner() {
+        let paginator = Paginator::new();
+        let session = paginator.start(UserId(1), pages(2));
+        assert!(matches!(paginator.page(&session, UserId(2), 1), PageLookup::NotOwner));
+    }
+
+    #[test]
+    fn test_paginator_sessions_expire() {
+        let clock = Arc::new(FakeClock::new(chrono::Utc::now()));
+        let paginator = Paginator::with_sources(clock.clone(), Arc::new(SequentialIds::default()));
+        let session = paginator.start(UserId(1), pages(2));
+
+        clock.advance(chrono::Duration::minutes(PAGINATOR_TTL_MINUTES));
+        assert!(matches!(paginator.page(&session, UserId(1), 1), PageLookup::Page(..)));
+        clock.advance(chrono::Duration::seconds(1));
+        assert!(matches!(paginator.page(&session, UserId(1), 1), PageLookup::Expired));
+
+        // Starting another session drops the expired one
+        paginator.start(UserId(1), pages(1));
+        assert_eq!(paginator.sessions.len(), 1);
+    }
+
+    #[test]
+    fn test_create_appeal_buttons() {
+        let choices = vec![(1, "Appeal ban in Cafe".to_string()), (2, "Appeal timeout in Lab".to_string())];
+        assert_eq!(MessageComponentHandler::create_appeal_buttons(&choices).0.len(), 1);
+        assert_eq!(MessageComponentHandler::create_appeal_review_buttons(5).0.len(), 1);
+    }
+
+    #[test]
+    fn test_create_join_captcha_buttons() {
+        let components = MessageComponentHandler::create_join_captcha_buttons(3, &[0, 4, 2, 7]);
+        assert_eq!(components.0.len(), 1);
+    }
+
+    #[test]
+    fn test_create_gate_button() {
+        assert_eq!(MessageComponentHandler::create_gate_button(4).0.len(), 1);
+    }
+
+    #[test]
+    fn test_create_ops_page_buttons() {
+        let components = MessageComponentHandler::create_ops_page_buttons(OverviewSort::Cost, 0, 3);
+        assert_eq!(components.0.len(), 1);
+    }
 }
\ No newline at end of file